@@ -1,5 +1,19 @@
+#[cfg(feature = "bundled-tools")]
+use std::fmt::Write as _;
+#[cfg(feature = "bundled-tools")]
+use std::path::Path;
+use std::path::PathBuf;
+
+#[cfg(feature = "bundled-tools")]
+use sha2::{Digest, Sha256};
+
+/// Tools that may be embedded when the `bundled-tools` feature is enabled.
+#[cfg(feature = "bundled-tools")]
+const BUNDLED_TOOLS: &[&str] = &["rg", "fd"];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("cargo:rerun-if-changed=proto/swegrep.proto");
+    println!("cargo:rerun-if-changed=proto/swegrep_v2.proto");
     println!("cargo:rerun-if-changed=proto");
 
     let protoc = protoc_bin_vendored::protoc_bin_path()?;
@@ -9,10 +23,75 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::env::set_var("PROTOC", &protoc);
     }
 
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
     tonic_build::configure()
         .build_server(true)
         .build_client(true)
-        .compile(&["proto/swegrep.proto"], &["proto"])?;
+        .file_descriptor_set_path(out_dir.join("swegrep_descriptor.bin"))
+        .compile(
+            &["proto/swegrep.proto", "proto/swegrep_v2.proto"],
+            &["proto"],
+        )?;
+
+    #[cfg(feature = "bundled-tools")]
+    generate_bundled_tools()?;
 
     Ok(())
 }
+
+/// Emit `bundled_tools.rs` embedding the binaries found in `SWE_GREP_BUNDLE_DIR` together with
+/// their SHA-256 digests, so the runtime can verify the copies it extracts.
+#[cfg(feature = "bundled-tools")]
+fn generate_bundled_tools() -> Result<(), Box<dyn std::error::Error>> {
+    println!("cargo:rerun-if-env-changed=SWE_GREP_BUNDLE_DIR");
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR")?);
+    let exe_suffix = if std::env::var("CARGO_CFG_WINDOWS").is_ok() {
+        ".exe"
+    } else {
+        ""
+    };
+
+    let mut source = String::from("pub(crate) const BUNDLED: &[BundledTool] = &[\n");
+    match std::env::var_os("SWE_GREP_BUNDLE_DIR") {
+        Some(dir) => {
+            let dir = PathBuf::from(dir);
+            for tool in BUNDLED_TOOLS {
+                let binary = dir.join(format!("{tool}{exe_suffix}"));
+                if !binary.is_file() {
+                    println!(
+                        "cargo:warning=bundled-tools: {} not found; `{tool}` will not be embedded",
+                        binary.display()
+                    );
+                    continue;
+                }
+                println!("cargo:rerun-if-changed={}", binary.display());
+                let digest = sha256_hex(&binary)?;
+                writeln!(
+                    source,
+                    "    BundledTool {{ name: {tool:?}, file_name: \"{tool}{exe_suffix}\", sha256: {digest:?}, bytes: include_bytes!({:?}) }},",
+                    binary.display().to_string()
+                )?;
+            }
+        }
+        None => {
+            println!(
+                "cargo:warning=bundled-tools enabled but SWE_GREP_BUNDLE_DIR is unset; no tools embedded"
+            );
+        }
+    }
+    source.push_str("];\n");
+
+    std::fs::write(out_dir.join("bundled_tools.rs"), source)?;
+    Ok(())
+}
+
+#[cfg(feature = "bundled-tools")]
+fn sha256_hex(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let bytes = std::fs::read(path)?;
+    let digest = Sha256::digest(&bytes);
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        write!(hex, "{byte:02x}")?;
+    }
+    Ok(hex)
+}
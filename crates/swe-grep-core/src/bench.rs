@@ -1,14 +1,16 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
 
 use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::json;
 use tokio::fs;
 use tokio::time::Instant;
 
-use crate::cli::{BenchArgs, SearchArgs};
-use crate::search;
+use crate::cli::{BenchArgs, BenchFormat, BenchReplayArgs, SearchArgs, ToolArgs};
+use crate::search::{self, SearchSummary};
 
 pub async fn run(args: BenchArgs) -> Result<()> {
     let cwd = std::env::current_dir().context("failed to determine current directory")?;
@@ -22,88 +24,86 @@ pub async fn run(args: BenchArgs) -> Result<()> {
         cwd.join(scenario_path)
     };
 
-    let raw = fs::read_to_string(&scenario_path).await.with_context(|| {
-        format!(
-            "failed to read benchmark scenarios from {}",
-            scenario_path.display()
-        )
-    })?;
-    let scenarios: Vec<Scenario> = serde_json::from_str(&raw).with_context(|| {
-        format!(
-            "failed to parse benchmark scenarios in {}",
-            scenario_path.display()
-        )
-    })?;
+    let scenarios = load_scenarios(&scenario_path).await?;
 
     let iterations = usize::max(1, args.iterations);
-    let mut reports = Vec::new();
-    let mut total_elapsed = Duration::ZERO;
-    let mut total_iterations = 0usize;
-    let mut total_hits = 0usize;
+    let parallel = usize::max(1, args.parallel);
+    let executor = Executor::new(args.target.as_deref())?;
 
+    let mut runs = Vec::new();
     for scenario in scenarios {
         let repo_root = resolve_path(&cwd, &scenario.path).await?;
-        let mut latencies = Vec::<f64>::new();
-        let mut hits = 0usize;
-        let mut latest_top_hits = Vec::new();
+        runs.push(ScenarioRun::new(scenario, repo_root));
+    }
 
+    let mut jobs: Vec<(usize, SearchArgs)> = Vec::with_capacity(runs.len() * iterations);
+    for (idx, run) in runs.iter().enumerate() {
         for _ in 0..iterations {
-            let search_args = build_search_args(&repo_root, &scenario, &args);
-            let start = Instant::now();
-            let summary = search::execute(search_args).await?;
-            let elapsed = start.elapsed();
-
-            latencies.push(elapsed.as_secs_f64() * 1000.0);
-            total_elapsed += elapsed;
-            total_iterations += 1;
-
-            let matched = matches_expectation(&summary, &scenario);
-            if matched {
-                hits += 1;
-                total_hits += 1;
-            }
-
-            latest_top_hits = summary.top_hits.clone();
+            jobs.push((idx, build_search_args(&run.repo_root, &run.scenario, &args)));
         }
+    }
 
-        let mean_latency_ms = if latencies.is_empty() {
-            0.0
-        } else {
-            latencies.iter().copied().sum::<f64>() / latencies.len() as f64
-        };
-        let success_rate = if latencies.is_empty() {
-            0.0
-        } else {
-            hits as f64 / latencies.len() as f64
+    let wall_start = Instant::now();
+    let mut completions = stream::iter(jobs)
+        .map(|(idx, search_args)| {
+            let executor = &executor;
+            async move {
+                let start = Instant::now();
+                let result = executor.search(search_args).await;
+                (idx, start.elapsed(), result)
+            }
+        })
+        .buffer_unordered(parallel);
+
+    while let Some((idx, elapsed, result)) = completions.next().await {
+        let run = &mut runs[idx];
+        let summary = match result {
+            Ok(summary) => summary,
+            Err(err) if executor.is_remote() => {
+                tracing::warn!(scenario = %run.scenario.name, error = %err, "bench request failed");
+                run.errors += 1;
+                continue;
+            }
+            Err(err) => return Err(err),
         };
-        let throughput_qps = if mean_latency_ms > 0.0 {
-            1000.0 / mean_latency_ms
-        } else {
-            0.0
+
+        run.latencies.push(elapsed.as_secs_f64() * 1000.0);
+        let score = run
+            .scenario
+            .expected
+            .as_ref()
+            .map(|expected| score_expectation(&summary, expected));
+        let matched = match &score {
+            Some(score) => score.meets(run.scenario.expected.as_ref()),
+            None => !summary.top_hits.is_empty(),
         };
+        run.scores.extend(score);
+        if matched {
+            run.hits += 1;
+        }
+        run.latest_top_hits = summary.top_hits;
+    }
+    drop(completions);
+    let wall_elapsed = wall_start.elapsed();
 
-        reports.push(ScenarioReport {
-            name: scenario.name.clone(),
-            symbol: scenario.symbol.clone(),
-            iterations: latencies.len(),
-            mean_latency_ms,
-            throughput_qps,
-            success_rate,
-            hits,
-            expected: scenario.expected.clone(),
-            latest_top_hits,
-        });
+    let mut all_latencies = Vec::new();
+    let mut total_iterations = 0usize;
+    let mut total_hits = 0usize;
+    let mut total_errors = 0usize;
+    let mut reports = Vec::new();
+    for run in runs {
+        all_latencies.extend_from_slice(&run.latencies);
+        total_iterations += run.latencies.len() + run.errors;
+        total_hits += run.hits;
+        total_errors += run.errors;
+        reports.push(run.into_report());
     }
 
-    let overall_mean_latency_ms = if total_iterations == 0 {
-        0.0
-    } else {
-        (total_elapsed.as_secs_f64() * 1000.0) / total_iterations as f64
-    };
-    let overall_qps = if total_elapsed.is_zero() {
+    let overall_mean_latency_ms = mean(&all_latencies);
+    let overall_qps = if wall_elapsed.is_zero() {
         0.0
     } else {
-        total_iterations as f64 / total_elapsed.as_secs_f64()
+        all_latencies.len() as f64 / wall_elapsed.as_secs_f64()
     };
     let overall_success_rate = if total_iterations == 0 {
         0.0
@@ -111,7 +111,12 @@ pub async fn run(args: BenchArgs) -> Result<()> {
         total_hits as f64 / total_iterations as f64
     };
 
+    let repo_roots: Vec<PathBuf> = reports
+        .iter()
+        .map(|report| report.repo_root.clone())
+        .collect();
     let summary = BenchmarkSummary {
+        metadata: collect_metadata(&repo_roots).await,
         scenarios: reports,
         totals: Totals {
             total_iterations,
@@ -119,11 +124,16 @@ pub async fn run(args: BenchArgs) -> Result<()> {
             mean_latency_ms: overall_mean_latency_ms,
             throughput_qps: overall_qps,
             success_rate: overall_success_rate,
+            parallel,
+            target: args.target.clone(),
+            wall_time_ms: wall_elapsed.as_secs_f64() * 1000.0,
+            errors: total_errors,
+            latency: LatencyDistribution::from_samples(&all_latencies),
         },
     };
 
-    let rendered = serde_json::to_string_pretty(&summary)?;
-    println!("{}", rendered);
+    let format = args.bench_format;
+    print!("{}", render(&summary, format, true)?);
 
     if let Some(output_path) = args.output {
         let mut path = if output_path.is_absolute() {
@@ -132,7 +142,7 @@ pub async fn run(args: BenchArgs) -> Result<()> {
             cwd.join(output_path)
         };
         if path.is_dir() {
-            path = path.join("benchmark-summary.jsonl");
+            path = path.join(format!("benchmark-summary.{}", format.extension()));
         }
         fs::create_dir_all(path.parent().unwrap_or_else(|| Path::new(".")))
             .await
@@ -142,21 +152,409 @@ pub async fn run(args: BenchArgs) -> Result<()> {
                     path.display()
                 )
             })?;
+        // CSV files only get the metadata header and column row when they are first created.
+        let fresh = fs::metadata(&path)
+            .await
+            .map(|meta| meta.len() == 0)
+            .unwrap_or(true);
         let mut file = fs::OpenOptions::new()
             .create(true)
             .append(true)
             .open(&path)
             .await
             .with_context(|| format!("failed to open benchmark output file {}", path.display()))?;
-        let mut line = serde_json::to_vec(&summary)?;
-        line.push(b'\n');
+        let appended = match format {
+            BenchFormat::Json => {
+                let mut line = serde_json::to_string(&summary)?;
+                line.push('\n');
+                line
+            }
+            _ => render(&summary, format, fresh)?,
+        };
         use tokio::io::AsyncWriteExt;
-        file.write_all(&line).await?;
+        file.write_all(appended.as_bytes()).await?;
     }
 
     Ok(())
 }
 
+/// Re-run the last `--last` searches of a search log one at a time and compare each with its
+/// logged run: cycle latency, whether the top hit is unchanged, and how much the hit lists
+/// overlap.
+pub async fn replay(args: BenchReplayArgs) -> Result<ReplayReport> {
+    let cwd = std::env::current_dir().context("failed to determine current directory")?;
+    let log = if args.log.is_absolute() {
+        args.log.clone()
+    } else {
+        cwd.join(&args.log)
+    };
+    let raw = fs::read_to_string(&log)
+        .await
+        .with_context(|| format!("failed to read search log {}", log.display()))?;
+    let mut logged = Vec::new();
+    let mut skipped = 0usize;
+    for line in raw.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str::<LoggedSearch>(line) {
+            Ok(entry) if entry.status == "ok" => logged.push(entry),
+            _ => skipped += 1,
+        }
+    }
+    let keep = logged.len().saturating_sub(args.last);
+    let logged = logged.split_off(keep);
+
+    let executor = Executor::new(args.target.as_deref())?;
+    let mut searches = Vec::with_capacity(logged.len());
+    for entry in logged {
+        let search_args = replay_search_args(&entry, args.cache_dir.clone(), &args.tools);
+        let logged_hits = entry.summary.hit_keys();
+        let logged_top_hit = logged_hits
+            .first()
+            .map(|(path, line)| format!("{path}:{line}"));
+        let mut replayed = ReplayedSearch {
+            symbol: entry.symbol,
+            root: entry.root,
+            logged_at: entry.timestamp,
+            logged_latency_ms: entry.latency_ms,
+            replayed_latency_ms: None,
+            latency_delta_ms: None,
+            logged_top_hit,
+            replayed_top_hit: None,
+            top_hit_stable: false,
+            overlap: 0.0,
+            error: None,
+        };
+        match executor.search(search_args).await {
+            Ok(summary) => {
+                let latency = summary.stage_stats.cycle_latency_ms as f64;
+                let replayed_hits: Vec<(String, usize)> = summary
+                    .top_hits
+                    .iter()
+                    .map(|hit| (hit.path.clone(), hit.line))
+                    .collect();
+                replayed.replayed_latency_ms = Some(latency);
+                replayed.latency_delta_ms = Some(latency - replayed.logged_latency_ms);
+                replayed.replayed_top_hit = replayed_hits
+                    .first()
+                    .map(|(path, line)| format!("{path}:{line}"));
+                replayed.top_hit_stable = replayed.replayed_top_hit == replayed.logged_top_hit;
+                replayed.overlap = overlap(&logged_hits, &replayed_hits);
+            }
+            Err(err) => replayed.error = Some(format!("{err:#}")),
+        }
+        searches.push(replayed);
+    }
+
+    let completed: Vec<&ReplayedSearch> = searches
+        .iter()
+        .filter(|search| search.error.is_none())
+        .collect();
+    let logged_ms: Vec<f64> = completed
+        .iter()
+        .map(|search| search.logged_latency_ms)
+        .collect();
+    let replayed_ms: Vec<f64> = completed
+        .iter()
+        .filter_map(|search| search.replayed_latency_ms)
+        .collect();
+    let share = |count: usize| {
+        if completed.is_empty() {
+            0.0
+        } else {
+            count as f64 / completed.len() as f64
+        }
+    };
+    let totals = ReplayTotals {
+        replayed: completed.len(),
+        errors: searches.len() - completed.len(),
+        mean_logged_latency_ms: mean(&logged_ms),
+        mean_replayed_latency_ms: mean(&replayed_ms),
+        logged_latency: LatencyDistribution::from_samples(&logged_ms),
+        replayed_latency: LatencyDistribution::from_samples(&replayed_ms),
+        top_hit_stability: share(
+            completed
+                .iter()
+                .filter(|search| search.top_hit_stable)
+                .count(),
+        ),
+        mean_overlap: if completed.is_empty() {
+            0.0
+        } else {
+            completed.iter().map(|search| search.overlap).sum::<f64>() / completed.len() as f64
+        },
+    };
+
+    Ok(ReplayReport {
+        log,
+        skipped,
+        totals,
+        searches,
+    })
+}
+
+/// A search as the search log records it. Only the parts of the summary a replay compares
+/// against are read, so logs written by older versions still load.
+#[derive(Deserialize)]
+struct LoggedSearch {
+    timestamp: f64,
+    root: PathBuf,
+    symbol: String,
+    #[serde(default)]
+    use_index: bool,
+    #[serde(default)]
+    use_rga: bool,
+    #[serde(default = "default_true")]
+    use_fd: bool,
+    #[serde(default = "default_true")]
+    use_ast_grep: bool,
+    status: String,
+    #[serde(default)]
+    latency_ms: f64,
+    summary: LoggedSummary,
+}
+
+#[derive(Deserialize)]
+struct LoggedSummary {
+    #[serde(default)]
+    top_hits: Vec<LoggedHit>,
+}
+
+impl LoggedSummary {
+    fn hit_keys(&self) -> Vec<(String, usize)> {
+        self.top_hits
+            .iter()
+            .map(|hit| (hit.path.clone(), hit.line))
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct LoggedHit {
+    path: String,
+    line: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Search arguments reproducing a logged search. Tools come from the replay's own flags, options
+/// the log does not record keep their defaults, and nothing is logged, so the replay leaves the log it reads untouched.
+fn replay_search_args(
+    entry: &LoggedSearch,
+    cache_dir: Option<PathBuf>,
+    tools: &ToolArgs,
+) -> SearchArgs {
+    SearchArgs {
+        symbol: entry.symbol.clone(),
+        path: vec![entry.root.clone()],
+        language: None,
+        rg_types: Vec::new(),
+        rg_type_adds: Vec::new(),
+        config: false,
+        rev: None,
+        files_from: None,
+        include_submodules: false,
+        owner: None,
+        case: None,
+        word: None,
+        profile: None,
+        trust_repo_hooks: false,
+        trust_repo_state: false,
+        timeout_secs: None,
+        max_matches: None,
+        max_scope_files: None,
+        latency_target_ms: None,
+        concurrency: 8,
+        context_before: 0,
+        context_after: 0,
+        body: false,
+        redact_secrets: false,
+        enable_index: entry.use_index,
+        index_dir: None,
+        enable_rga: entry.use_rga,
+        cache_dir,
+        state_max_symbols: None,
+        log_dir: None,
+        use_fd: entry.use_fd,
+        use_ast_grep: entry.use_ast_grep,
+        tools: tools.clone(),
+        fields: Vec::new(),
+        explain: false,
+        highlight: false,
+        link_format: None,
+        permalinks: false,
+        sort: None,
+        deterministic: false,
+        format: None,
+        exec: None,
+    }
+}
+
+/// Jaccard similarity of two hit lists by `(path, line)`; two empty lists are identical.
+fn overlap(logged: &[(String, usize)], replayed: &[(String, usize)]) -> f64 {
+    let logged: HashSet<&(String, usize)> = logged.iter().collect();
+    let replayed: HashSet<&(String, usize)> = replayed.iter().collect();
+    let union = logged.union(&replayed).count();
+    if union == 0 {
+        return 1.0;
+    }
+    logged.intersection(&replayed).count() as f64 / union as f64
+}
+
+/// Read a JSON array of scenarios.
+pub(crate) async fn load_scenarios(path: &Path) -> Result<Vec<Scenario>> {
+    let raw = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("failed to read benchmark scenarios from {}", path.display()))?;
+    serde_json::from_str(&raw)
+        .with_context(|| format!("failed to parse benchmark scenarios in {}", path.display()))
+}
+
+/// Where bench searches run: the in-process engine or a running `serve` instance.
+enum Executor {
+    InProcess,
+    Remote {
+        client: reqwest::Client,
+        endpoint: String,
+    },
+}
+
+impl Executor {
+    fn new(target: Option<&str>) -> Result<Self> {
+        let Some(target) = target else {
+            return Ok(Self::InProcess);
+        };
+        let base = target.trim_end_matches('/');
+        if !base.starts_with("http://") && !base.starts_with("https://") {
+            anyhow::bail!("bench target must be an http(s) URL, got `{target}`");
+        }
+        Ok(Self::Remote {
+            client: reqwest::Client::new(),
+            endpoint: format!("{base}/search"),
+        })
+    }
+
+    fn is_remote(&self) -> bool {
+        matches!(self, Self::Remote { .. })
+    }
+
+    async fn search(&self, args: SearchArgs) -> Result<SearchSummary> {
+        match self {
+            Self::InProcess => search::execute(args).await,
+            Self::Remote { client, endpoint } => {
+                let body = json!({
+                    "symbol": args.symbol,
+                    "language": args.language,
+                    "root": args.path.first(),
+                    "timeout_secs": args.timeout_secs,
+                    "max_matches": args.max_matches,
+                    "concurrency": args.concurrency,
+                    "enable_index": args.enable_index,
+                    "enable_rga": args.enable_rga,
+                    "index_dir": args.index_dir,
+                    "cache_dir": args.cache_dir,
+                    "log_dir": args.log_dir,
+                });
+                let response = client
+                    .post(endpoint.as_str())
+                    .json(&body)
+                    .send()
+                    .await
+                    .with_context(|| format!("failed to reach {endpoint}"))?;
+                let status = response.status();
+                if !status.is_success() {
+                    let message = response.text().await.unwrap_or_default();
+                    anyhow::bail!("{endpoint} returned {status}: {}", message.trim());
+                }
+                let payload: RemoteResponse = response
+                    .json()
+                    .await
+                    .with_context(|| format!("failed to decode search response from {endpoint}"))?;
+                Ok(payload.summary)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RemoteResponse {
+    summary: SearchSummary,
+}
+
+/// Per-scenario accumulator filled as searches complete (possibly out of order).
+struct ScenarioRun {
+    scenario: Scenario,
+    repo_root: PathBuf,
+    latencies: Vec<f64>,
+    hits: usize,
+    errors: usize,
+    scores: Vec<RetrievalScore>,
+    latest_top_hits: Vec<search::TopHit>,
+}
+
+impl ScenarioRun {
+    fn new(scenario: Scenario, repo_root: PathBuf) -> Self {
+        Self {
+            scenario,
+            repo_root,
+            latencies: Vec::new(),
+            hits: 0,
+            errors: 0,
+            scores: Vec::new(),
+            latest_top_hits: Vec::new(),
+        }
+    }
+
+    fn into_report(self) -> ScenarioReport {
+        let attempts = self.latencies.len() + self.errors;
+        let mean_latency_ms = mean(&self.latencies);
+        let success_rate = if attempts == 0 {
+            0.0
+        } else {
+            self.hits as f64 / attempts as f64
+        };
+        let throughput_qps = if mean_latency_ms > 0.0 {
+            1000.0 / mean_latency_ms
+        } else {
+            0.0
+        };
+        let mean_score = |field: fn(&RetrievalScore) -> f64| {
+            if self.scores.is_empty() {
+                None
+            } else {
+                Some(self.scores.iter().map(field).sum::<f64>() / self.scores.len() as f64)
+            }
+        };
+
+        ScenarioReport {
+            name: self.scenario.name.clone(),
+            symbol: self.scenario.symbol.clone(),
+            iterations: attempts,
+            mean_latency_ms,
+            throughput_qps,
+            success_rate,
+            hits: self.hits,
+            precision: mean_score(|score| score.precision),
+            recall: mean_score(|score| score.recall),
+            f1: mean_score(|score| score.f1),
+            errors: self.errors,
+            latency: LatencyDistribution::from_samples(&self.latencies),
+            expected: self.scenario.expected.clone(),
+            latest_top_hits: self.latest_top_hits,
+            repo_root: self.repo_root,
+            samples_ms: self.latencies,
+        }
+    }
+}
+
+fn mean(samples: &[f64]) -> f64 {
+    if samples.is_empty() {
+        0.0
+    } else {
+        samples.iter().sum::<f64>() / samples.len() as f64
+    }
+}
+
 fn build_search_args(repo_root: &Path, scenario: &Scenario, bench: &BenchArgs) -> SearchArgs {
     let enable_index = scenario.enable_index.unwrap_or(bench.enable_index);
     let enable_rga = scenario.enable_rga.unwrap_or(bench.enable_rga);
@@ -175,25 +573,51 @@ fn build_search_args(repo_root: &Path, scenario: &Scenario, bench: &BenchArgs) -
 
     SearchArgs {
         symbol: scenario.symbol.clone(),
-        path: Some(repo_root.to_path_buf()),
+        path: vec![repo_root.to_path_buf()],
         language: scenario.language.clone(),
-        timeout_secs: scenario.timeout_secs.unwrap_or(3),
-        max_matches: scenario.max_matches.unwrap_or(20),
+        rg_types: Vec::new(),
+        rg_type_adds: Vec::new(),
+        config: false,
+        rev: None,
+        files_from: None,
+        include_submodules: false,
+        owner: None,
+        case: None,
+        word: None,
+        profile: None,
+        trust_repo_hooks: false,
+        trust_repo_state: false,
+        timeout_secs: Some(scenario.timeout_secs.unwrap_or(3)),
+        max_matches: Some(scenario.max_matches.unwrap_or(20)),
+        max_scope_files: None,
+        latency_target_ms: None,
         concurrency: scenario.concurrency.unwrap_or(8),
         context_before: 0,
         context_after: 0,
         body: false,
+        redact_secrets: false,
         enable_index,
         index_dir: Some(index_dir),
         enable_rga,
         cache_dir,
+        state_max_symbols: None,
         log_dir,
         use_fd: true,
         use_ast_grep: true,
+        tools: ToolArgs::default(),
+        fields: Vec::new(),
+        explain: false,
+        highlight: false,
+        link_format: None,
+        permalinks: false,
+        sort: None,
+        deterministic: false,
+        format: None,
+        exec: None,
     }
 }
 
-async fn resolve_path(base: &Path, path: &Path) -> Result<PathBuf> {
+pub(crate) async fn resolve_path(base: &Path, path: &Path) -> Result<PathBuf> {
     let joined = if path.is_absolute() {
         path.to_path_buf()
     } else {
@@ -205,18 +629,78 @@ async fn resolve_path(base: &Path, path: &Path) -> Result<PathBuf> {
     Ok(canonical)
 }
 
-fn matches_expectation(summary: &search::SearchSummary, scenario: &Scenario) -> bool {
-    if let Some(expected) = &scenario.expected {
-        let top_n = expected.top_n.unwrap_or(1);
-        summary.top_hits.iter().take(top_n).any(|hit| {
-            path_matches(&hit.path, &expected.path)
-                && expected.line.map_or(true, |line| line == hit.line)
+/// Precision/recall of the top-N hits against every expected target of a scenario.
+pub(crate) struct RetrievalScore {
+    pub(crate) precision: f64,
+    pub(crate) recall: f64,
+    pub(crate) f1: f64,
+}
+
+impl RetrievalScore {
+    fn meets(&self, expected: Option<&Expectation>) -> bool {
+        let required = expected.and_then(|e| e.min_recall).unwrap_or(1.0);
+        self.recall > 0.0 && self.recall + f64::EPSILON >= required
+    }
+}
+
+fn score_expectation(summary: &search::SearchSummary, expected: &Expectation) -> RetrievalScore {
+    score_ranked(
+        summary
+            .top_hits
+            .iter()
+            .map(|hit| (hit.path.as_str(), hit.line)),
+        expected,
+    )
+}
+
+/// Score ranked `(path, line)` hits against an expectation.
+pub(crate) fn score_ranked<'a>(
+    ranked: impl IntoIterator<Item = (&'a str, usize)>,
+    expected: &Expectation,
+) -> RetrievalScore {
+    let targets = expected.targets();
+    let top_n = expected.top_n.unwrap_or(targets.len().max(1));
+    let retrieved: Vec<_> = ranked.into_iter().take(top_n).collect();
+
+    let relevant = retrieved
+        .iter()
+        .filter(|hit| {
+            targets
+                .iter()
+                .any(|(path, line)| hit_matches(**hit, path, *line))
         })
+        .count();
+    let found = targets
+        .iter()
+        .filter(|(path, line)| retrieved.iter().any(|hit| hit_matches(*hit, path, *line)))
+        .count();
+
+    let precision = if retrieved.is_empty() {
+        0.0
     } else {
-        !summary.top_hits.is_empty()
+        relevant as f64 / retrieved.len() as f64
+    };
+    let recall = if targets.is_empty() {
+        0.0
+    } else {
+        found as f64 / targets.len() as f64
+    };
+    let f1 = if precision + recall > 0.0 {
+        2.0 * precision * recall / (precision + recall)
+    } else {
+        0.0
+    };
+    RetrievalScore {
+        precision,
+        recall,
+        f1,
     }
 }
 
+fn hit_matches(hit: (&str, usize), path: &str, line: Option<usize>) -> bool {
+    path_matches(hit.0, path) && line.is_none_or(|line| line == hit.1)
+}
+
 fn path_matches(hit_path: &str, expected: &str) -> bool {
     let hit = Path::new(hit_path);
     let expected_path = Path::new(expected);
@@ -224,43 +708,82 @@ fn path_matches(hit_path: &str, expected: &str) -> bool {
 }
 
 #[derive(Clone, Deserialize, Serialize)]
-struct Scenario {
-    name: String,
-    path: PathBuf,
-    symbol: String,
+pub(crate) struct Scenario {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+    pub(crate) symbol: String,
     #[serde(default)]
-    language: Option<String>,
+    pub(crate) language: Option<String>,
     #[serde(default)]
-    expected: Option<Expectation>,
+    pub(crate) expected: Option<Expectation>,
     #[serde(default)]
-    enable_index: Option<bool>,
+    pub(crate) enable_index: Option<bool>,
     #[serde(default)]
-    enable_rga: Option<bool>,
+    pub(crate) enable_rga: Option<bool>,
     #[serde(default)]
-    cache_dir: Option<PathBuf>,
+    pub(crate) cache_dir: Option<PathBuf>,
     #[serde(default)]
-    log_dir: Option<PathBuf>,
+    pub(crate) log_dir: Option<PathBuf>,
     #[serde(default)]
-    index_dir: Option<PathBuf>,
+    pub(crate) index_dir: Option<PathBuf>,
     #[serde(default)]
-    concurrency: Option<usize>,
+    pub(crate) concurrency: Option<usize>,
     #[serde(default)]
-    timeout_secs: Option<u64>,
+    pub(crate) timeout_secs: Option<u64>,
     #[serde(default)]
-    max_matches: Option<usize>,
+    pub(crate) max_matches: Option<usize>,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
-struct Expectation {
-    path: String,
+pub(crate) struct Expectation {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) path: Option<String>,
     #[serde(default)]
-    line: Option<usize>,
+    pub(crate) line: Option<usize>,
     #[serde(default)]
-    top_n: Option<usize>,
+    pub(crate) top_n: Option<usize>,
+    /// Additional targets that should all surface within `top_n`, which defaults to the target count.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) paths: Vec<ExpectedTarget>,
+    /// Fraction of targets that must be found for an iteration to count as a hit (default 1.0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) min_recall: Option<f64>,
+}
+
+impl Expectation {
+    pub(crate) fn targets(&self) -> Vec<(&str, Option<usize>)> {
+        let single = self.path.as_deref().map(|path| (path, self.line));
+        single
+            .into_iter()
+            .chain(self.paths.iter().map(ExpectedTarget::as_target))
+            .collect()
+    }
+}
+
+/// A bare path or a `{ "path", "line" }` object inside `expected.paths`.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub(crate) enum ExpectedTarget {
+    Path(String),
+    Located {
+        path: String,
+        #[serde(default)]
+        line: Option<usize>,
+    },
+}
+
+impl ExpectedTarget {
+    fn as_target(&self) -> (&str, Option<usize>) {
+        match self {
+            Self::Path(path) => (path, None),
+            Self::Located { path, line } => (path, *line),
+        }
+    }
 }
 
 #[derive(Serialize)]
 struct BenchmarkSummary {
+    metadata: MachineMetadata,
     scenarios: Vec<ScenarioReport>,
     totals: Totals,
 }
@@ -275,8 +798,21 @@ struct ScenarioReport {
     success_rate: f64,
     hits: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
+    precision: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    recall: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    f1: Option<f64>,
+    #[serde(skip_serializing_if = "is_zero")]
+    errors: usize,
+    latency: LatencyDistribution,
+    #[serde(skip_serializing_if = "Option::is_none")]
     expected: Option<Expectation>,
     latest_top_hits: Vec<crate::search::TopHit>,
+    #[serde(skip)]
+    repo_root: PathBuf,
+    #[serde(skip)]
+    samples_ms: Vec<f64>,
 }
 
 #[derive(Serialize)]
@@ -286,4 +822,317 @@ struct Totals {
     mean_latency_ms: f64,
     throughput_qps: f64,
     success_rate: f64,
+    parallel: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<String>,
+    wall_time_ms: f64,
+    #[serde(skip_serializing_if = "is_zero")]
+    errors: usize,
+    latency: LatencyDistribution,
+}
+
+/// What `bench replay` prints: every replayed search next to its logged run, with totals.
+#[derive(Serialize)]
+pub struct ReplayReport {
+    log: PathBuf,
+    /// Log lines that were not successful searches or could not be parsed.
+    #[serde(skip_serializing_if = "is_zero")]
+    skipped: usize,
+    totals: ReplayTotals,
+    searches: Vec<ReplayedSearch>,
+}
+
+#[derive(Serialize)]
+struct ReplayTotals {
+    replayed: usize,
+    #[serde(skip_serializing_if = "is_zero")]
+    errors: usize,
+    mean_logged_latency_ms: f64,
+    mean_replayed_latency_ms: f64,
+    logged_latency: LatencyDistribution,
+    replayed_latency: LatencyDistribution,
+    /// Fraction of replayed searches whose first hit matches the logged one.
+    top_hit_stability: f64,
+    /// Mean `overlap` of the replayed searches.
+    mean_overlap: f64,
+}
+
+#[derive(Serialize)]
+struct ReplayedSearch {
+    symbol: String,
+    root: PathBuf,
+    /// Unix time, in seconds, of the logged search.
+    logged_at: f64,
+    logged_latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replayed_latency_ms: Option<f64>,
+    /// Replayed minus logged cycle latency; negative when the search got faster.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_delta_ms: Option<f64>,
+    /// First hit as `path:line`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    logged_top_hit: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replayed_top_hit: Option<String>,
+    top_hit_stable: bool,
+    /// Jaccard similarity of the logged and replayed hits by path and line.
+    overlap: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Nearest-rank percentiles of per-request latency.
+#[derive(Serialize)]
+struct LatencyDistribution {
+    p50_ms: f64,
+    p90_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+}
+
+impl LatencyDistribution {
+    fn from_samples(samples: &[f64]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let percentile = |pct: f64| {
+            if sorted.is_empty() {
+                return 0.0;
+            }
+            let rank = ((pct / 100.0) * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+        Self {
+            p50_ms: percentile(50.0),
+            p90_ms: percentile(90.0),
+            p99_ms: percentile(99.0),
+            max_ms: sorted.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+/// Environment recorded with every bench run so results stay comparable across machines.
+#[derive(Serialize)]
+struct MachineMetadata {
+    swe_grep_version: &'static str,
+    os: &'static str,
+    arch: &'static str,
+    cpu_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpu_model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rg_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fd_version: Option<String>,
+    repos: Vec<RepoSize>,
+}
+
+#[derive(Serialize)]
+struct RepoSize {
+    path: String,
+    files: usize,
+    bytes: u64,
+}
+
+async fn collect_metadata(roots: &[PathBuf]) -> MachineMetadata {
+    let mut unique: Vec<PathBuf> = roots.to_vec();
+    unique.sort();
+    unique.dedup();
+    let repos = tokio::task::spawn_blocking(move || {
+        unique
+            .into_iter()
+            .map(|root| {
+                let (files, bytes) = directory_size(&root);
+                RepoSize {
+                    path: root.display().to_string(),
+                    files,
+                    bytes,
+                }
+            })
+            .collect()
+    })
+    .await
+    .unwrap_or_default();
+
+    MachineMetadata {
+        swe_grep_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        cpu_count: std::thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1),
+        cpu_model: cpu_model(),
+        rg_version: tool_version("rg").await,
+        fd_version: tool_version("fd").await,
+        repos,
+    }
+}
+
+/// Count files and bytes below `root`, skipping hidden entries such as `.git` and caches.
+fn directory_size(root: &Path) -> (usize, u64) {
+    let mut files = 0usize;
+    let mut bytes = 0u64;
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                files += 1;
+                bytes += entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+            }
+        }
+    }
+    (files, bytes)
+}
+
+fn cpu_model() -> Option<String> {
+    let cpuinfo = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo
+        .lines()
+        .find(|line| line.starts_with("model name"))
+        .and_then(|line| line.split_once(':'))
+        .map(|(_, model)| model.trim().to_string())
+}
+
+async fn tool_version(tool: &'static str) -> Option<String> {
+    let mut cmd = crate::tools::binary::command(tool);
+    cmd.arg("--version").kill_on_drop(true);
+    let output = tokio::time::timeout(std::time::Duration::from_secs(2), cmd.output())
+        .await
+        .ok()?
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+}
+
+fn render(summary: &BenchmarkSummary, format: BenchFormat, header: bool) -> Result<String> {
+    match format {
+        BenchFormat::Json => {
+            let mut rendered = serde_json::to_string_pretty(summary)?;
+            rendered.push('\n');
+            Ok(rendered)
+        }
+        BenchFormat::Csv => render_csv(summary, header),
+        BenchFormat::Criterion => render_criterion(summary),
+    }
+}
+
+const CSV_COLUMNS: &str = "scenario,symbol,iterations,hits,errors,success_rate,mean_latency_ms,p50_ms,p90_ms,p99_ms,max_ms,throughput_qps,precision,recall,f1";
+
+fn render_csv(summary: &BenchmarkSummary, header: bool) -> Result<String> {
+    use std::fmt::Write as _;
+
+    let mut out = String::new();
+    if header {
+        let metadata = serde_json::to_value(&summary.metadata)?;
+        if let Some(fields) = metadata.as_object() {
+            for (key, value) in fields {
+                let value = match value {
+                    serde_json::Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                writeln!(out, "# {key}: {value}")?;
+            }
+        }
+        writeln!(out, "{CSV_COLUMNS}")?;
+    }
+    let optional = |value: Option<f64>| value.map(|v| format!("{v:.4}")).unwrap_or_default();
+    for report in &summary.scenarios {
+        writeln!(
+            out,
+            "{},{},{},{},{},{:.4},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{},{},{}",
+            csv_field(&report.name),
+            csv_field(&report.symbol),
+            report.iterations,
+            report.hits,
+            report.errors,
+            report.success_rate,
+            report.mean_latency_ms,
+            report.latency.p50_ms,
+            report.latency.p90_ms,
+            report.latency.p99_ms,
+            report.latency.max_ms,
+            report.throughput_qps,
+            optional(report.precision),
+            optional(report.recall),
+            optional(report.f1),
+        )?;
+    }
+    Ok(out)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Emit cargo-criterion style `--message-format=json` lines (one per scenario, in nanoseconds),
+/// preceded by a metadata line and followed by a `group-complete` message.
+fn render_criterion(summary: &BenchmarkSummary) -> Result<String> {
+    let mut lines = vec![serde_json::to_string(&json!({
+        "reason": "swe-grep-metadata",
+        "metadata": summary.metadata,
+    }))?];
+    let mut ids = Vec::new();
+    for report in &summary.scenarios {
+        let id = format!("swe-grep/{}", report.name);
+        let measured: Vec<f64> = report.samples_ms.iter().map(|ms| ms * 1e6).collect();
+        let mut sorted = measured.clone();
+        sorted.sort_by(f64::total_cmp);
+        let lower = sorted.first().copied().unwrap_or(0.0);
+        let upper = sorted.last().copied().unwrap_or(0.0);
+        let mean_ns = mean(&measured);
+        let median_ns = report.latency.p50_ms * 1e6;
+        let mut deviations: Vec<f64> = measured.iter().map(|v| (v - median_ns).abs()).collect();
+        deviations.sort_by(f64::total_cmp);
+        let mad = deviations.get(deviations.len() / 2).copied().unwrap_or(0.0);
+        let estimate = |value: f64| json!({"estimate": value, "lower_bound": lower, "upper_bound": upper, "unit": "ns"});
+        lines.push(serde_json::to_string(&json!({
+            "reason": "benchmark-complete",
+            "id": id,
+            "report_directory": "",
+            "iteration_count": vec![1u64; measured.len()],
+            "measured_values": measured,
+            "unit": "ns",
+            "throughput": [],
+            "typical": estimate(mean_ns),
+            "mean": estimate(mean_ns),
+            "median": estimate(median_ns),
+            "median_abs_dev": json!({"estimate": mad, "lower_bound": 0.0, "upper_bound": mad, "unit": "ns"}),
+            "slope": null,
+            "change": null,
+        }))?);
+        ids.push(id);
+    }
+    lines.push(serde_json::to_string(&json!({
+        "reason": "group-complete",
+        "group_name": "swe-grep",
+        "benchmarks": ids,
+        "report_directory": "",
+    }))?);
+
+    let mut out = lines.join("\n");
+    out.push('\n');
+    Ok(out)
 }
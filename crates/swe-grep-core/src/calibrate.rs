@@ -0,0 +1,266 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::bench::{self, Expectation, Scenario};
+use crate::cli::{CalibrateArgs, SearchArgs, ToolArgs};
+use crate::config::{self, RepoConfig, RewardWeights, ScoringWeights};
+use crate::search::{self, CalibrationSample};
+
+/// Candidate values tried for every weight: 0.00 to 1.00 in steps of 0.05.
+const GRID_STEPS: u32 = 20;
+
+/// Tune the verify-stage score weights and the reward coefficients against labeled scenarios.
+///
+/// Every scenario is searched once; the recorded candidates are then re-ranked offline for each
+/// weight set. Score weights maximise mean F1 of the surfaced hits (coordinate descent), and
+/// reward coefficients minimise the squared error between the reward and that F1 (grid search
+/// over the simplex), so the reward tracks real retrieval quality.
+pub async fn run(args: CalibrateArgs) -> Result<CalibrationReport> {
+    let cwd = std::env::current_dir().context("failed to determine current directory")?;
+    let mut samples = Vec::new();
+    let mut skipped = 0usize;
+
+    for file in &args.scenarios {
+        let path = if file.is_absolute() {
+            file.clone()
+        } else {
+            cwd.join(file)
+        };
+        for scenario in bench::load_scenarios(&path).await? {
+            let Some(expected) = scenario.expected.clone() else {
+                skipped += 1;
+                continue;
+            };
+            let repo_root = bench::resolve_path(&cwd, &scenario.path).await?;
+            let search_args = build_search_args(&repo_root, &scenario, &args);
+            let (_, sample) = search::execute_for_calibration(search_args)
+                .await
+                .with_context(|| format!("scenario `{}` failed", scenario.name))?;
+            samples.push(LabeledSample { expected, sample });
+        }
+    }
+
+    if samples.is_empty() {
+        anyhow::bail!("no labeled scenarios found; add an `expected` block to calibrate against");
+    }
+
+    let current = RepoConfig::load(&cwd)?;
+    let baseline = evaluate(&samples, current.scoring, current.reward);
+
+    let scoring = tune_scoring(&samples, current.scoring, usize::max(1, args.rounds));
+    let reward = tune_reward(&samples, &scoring, current.reward);
+    let best = evaluate(&samples, scoring, reward);
+
+    let written = match &args.write {
+        Some(target) => {
+            let target = if target.is_absolute() {
+                target.clone()
+            } else {
+                cwd.join(target)
+            };
+            config::write_weights(&target, &best.scoring, &best.reward)?;
+            Some(target.display().to_string())
+        }
+        None => None,
+    };
+
+    Ok(CalibrationReport {
+        scenarios: samples.len(),
+        skipped,
+        baseline,
+        best,
+        written,
+    })
+}
+
+struct LabeledSample {
+    expected: Expectation,
+    sample: CalibrationSample,
+}
+
+fn build_search_args(repo_root: &Path, scenario: &Scenario, args: &CalibrateArgs) -> SearchArgs {
+    SearchArgs {
+        symbol: scenario.symbol.clone(),
+        path: vec![repo_root.to_path_buf()],
+        language: scenario.language.clone(),
+        rg_types: Vec::new(),
+        rg_type_adds: Vec::new(),
+        config: false,
+        rev: None,
+        files_from: None,
+        include_submodules: false,
+        owner: None,
+        case: None,
+        word: None,
+        profile: None,
+        trust_repo_hooks: false,
+        trust_repo_state: false,
+        timeout_secs: Some(scenario.timeout_secs.unwrap_or(args.timeout_secs)),
+        max_matches: Some(scenario.max_matches.unwrap_or(20)),
+        max_scope_files: None,
+        latency_target_ms: None,
+        concurrency: scenario.concurrency.unwrap_or(8),
+        context_before: 0,
+        context_after: 0,
+        body: false,
+        redact_secrets: false,
+        enable_index: scenario.enable_index.unwrap_or(false),
+        index_dir: scenario.index_dir.clone(),
+        enable_rga: scenario.enable_rga.unwrap_or(false),
+        cache_dir: scenario
+            .cache_dir
+            .clone()
+            .or_else(|| args.cache_dir.clone()),
+        state_max_symbols: None,
+        log_dir: None,
+        use_fd: true,
+        use_ast_grep: true,
+        tools: ToolArgs::default(),
+        fields: Vec::new(),
+        explain: false,
+        highlight: false,
+        link_format: None,
+        permalinks: false,
+        sort: None,
+        deterministic: false,
+        format: None,
+        exec: None,
+    }
+}
+
+fn f1_scores(samples: &[LabeledSample], scoring: &ScoringWeights) -> Vec<f64> {
+    samples
+        .iter()
+        .map(|labeled| {
+            let ranked = labeled.sample.rank(scoring);
+            bench::score_ranked(
+                ranked.iter().map(|(path, line)| (path.as_str(), *line)),
+                &labeled.expected,
+            )
+            .f1
+        })
+        .collect()
+}
+
+fn reward_mse(samples: &[LabeledSample], f1: &[f64], reward: &RewardWeights) -> f64 {
+    let total: f64 = samples
+        .iter()
+        .zip(f1)
+        .map(|(labeled, target)| (labeled.sample.reward(reward) - target).powi(2))
+        .sum();
+    total / samples.len() as f64
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len().max(1) as f64
+}
+
+fn evaluate(
+    samples: &[LabeledSample],
+    scoring: ScoringWeights,
+    reward: RewardWeights,
+) -> WeightSet {
+    let f1 = f1_scores(samples, &scoring);
+    WeightSet {
+        mean_f1: round4(mean(&f1)),
+        reward_mse: round4(reward_mse(samples, &f1, &reward)),
+        scoring,
+        reward,
+    }
+}
+
+fn grid_value(step: u32) -> f64 {
+    f64::from(step) / f64::from(GRID_STEPS)
+}
+
+/// Coordinate descent over the score weights; ties keep the current value.
+fn tune_scoring(samples: &[LabeledSample], start: ScoringWeights, rounds: usize) -> ScoringWeights {
+    let mut best = start;
+    let mut best_f1 = mean(&f1_scores(samples, &best));
+
+    for _ in 0..rounds {
+        let mut improved = false;
+        for field in 0..5 {
+            for step in 0..=GRID_STEPS {
+                let mut candidate = best;
+                *scoring_field(&mut candidate, field) = grid_value(step);
+                let f1 = mean(&f1_scores(samples, &candidate));
+                if f1 > best_f1 + f64::EPSILON {
+                    best = candidate;
+                    best_f1 = f1;
+                    improved = true;
+                }
+            }
+        }
+        if !improved {
+            break;
+        }
+    }
+    best
+}
+
+fn scoring_field(weights: &mut ScoringWeights, index: usize) -> &mut f64 {
+    match index {
+        0 => &mut weights.fd_candidate,
+        1 => &mut weights.ast_match,
+        2 => &mut weights.indexed,
+        3 => &mut weights.global_penalty,
+        _ => &mut weights.rga_penalty,
+    }
+}
+
+/// Exhaustive search over reward coefficients that sum to 1; ties keep the current set.
+fn tune_reward(
+    samples: &[LabeledSample],
+    scoring: &ScoringWeights,
+    start: RewardWeights,
+) -> RewardWeights {
+    let f1 = f1_scores(samples, scoring);
+    let mut best = start;
+    let mut best_mse = reward_mse(samples, &f1, &best);
+
+    for precision in 0..=GRID_STEPS {
+        for density in 0..=GRID_STEPS - precision {
+            for clustering in 0..=GRID_STEPS - precision - density {
+                let candidate = RewardWeights {
+                    precision: grid_value(precision),
+                    density: grid_value(density),
+                    clustering: grid_value(clustering),
+                    fd_bonus: grid_value(GRID_STEPS - precision - density - clustering),
+                };
+                let mse = reward_mse(samples, &f1, &candidate);
+                if mse + f64::EPSILON < best_mse {
+                    best = candidate;
+                    best_mse = mse;
+                }
+            }
+        }
+    }
+    best
+}
+
+fn round4(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalibrationReport {
+    /// Labeled scenarios used for tuning.
+    pub scenarios: usize,
+    /// Scenarios without an `expected` block.
+    pub skipped: usize,
+    pub baseline: WeightSet,
+    pub best: WeightSet,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub written: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WeightSet {
+    pub scoring: ScoringWeights,
+    pub reward: RewardWeights,
+    pub mean_f1: f64,
+    pub reward_mse: f64,
+}
@@ -12,91 +12,493 @@ pub struct Cli {
     #[arg(long, global = true, action = ArgAction::SetTrue)]
     pub disable_telemetry: bool,
 
+    /// Only log errors to stderr; non-fatal warnings still appear in the summary.
+    #[arg(long, short, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Log more to stderr: `-v` for progress, `-vv` for debug detail, `-vvv` for tracing.
+    #[arg(long, short, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
     #[command(subcommand)]
     pub command: Commands,
 }
 
+const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+impl Cli {
+    /// Log filter requested with `--quiet` or `-v`; these take precedence over `RUST_LOG`.
+    pub fn log_filter(&self) -> Option<&'static str> {
+        if self.quiet {
+            return Some(LOG_LEVELS[0]);
+        }
+        if self.verbose == 0 {
+            return None;
+        }
+        let level = self.default_level() + usize::from(self.verbose);
+        Some(LOG_LEVELS[level.min(LOG_LEVELS.len() - 1)])
+    }
+
+    /// Log filter when neither the flags nor `RUST_LOG` choose one: warnings only, except for
+    /// `serve`, which also reports lifecycle events.
+    pub fn default_log_filter(&self) -> &'static str {
+        LOG_LEVELS[self.default_level()]
+    }
+
+    fn default_level(&self) -> usize {
+        match self.command {
+            Commands::Serve(_) => 2,
+            _ => 1,
+        }
+    }
+}
+
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Search the repository for occurrences of a symbol.
-    Search(SearchArgs),
+    Search(Box<SearchArgs>),
     /// Run benchmark scenarios and collect performance metrics.
     Bench(BenchArgs),
     /// Serve the SWE-Grep API over HTTP and gRPC.
     Serve(ServeArgs),
+    /// Compare the hits of two searches (git revisions or saved summaries).
+    Diff(DiffArgs),
+    /// Tune scoring weights and reward coefficients against labeled benchmark scenarios.
+    Calibrate(CalibrateArgs),
+    /// List indexed identifiers that start with a prefix (requires the `indexing` feature).
+    Symbols(SymbolsArgs),
+    /// Manage the Tantivy index (requires the `indexing` feature).
+    Index(IndexArgs),
+    /// Report file, line, and language counts for the repository.
+    Stats(StatsArgs),
+    /// Print the stored summary of an earlier search by its `search_id`.
+    Show(ShowArgs),
+    /// Print a file's declarations as a nested outline with line ranges.
+    Outline(OutlineArgs),
+    /// Trace an environment variable or feature flag: where it is defined, written, read, and
+    /// documented.
+    Flag(FlagArgs),
+    /// Count a symbol's occurrences per directory and file, as a JSON tree or an SVG treemap,
+    /// to gauge the impact of a refactoring.
+    Heatmap(HeatmapArgs),
+    /// Send a search to a running `swe-grep serve` instance and print its summary like
+    /// `search` does.
+    Query(Box<QueryArgs>),
+    /// Generate synthetic fixture repositories for benches and integration tests.
+    Fixtures(FixturesArgs),
 }
 
 /// Arguments for the `search` subcommand.
 #[derive(clap::Args, Debug)]
 pub struct SearchArgs {
     /// Symbol or identifier to search for.
-    #[arg(long)]
+    #[arg(long, env = "SWE_GREP_SYMBOL")]
     pub symbol: String,
 
-    /// Root directory of the repository; defaults to the current working directory.
-    #[arg(long)]
-    pub path: Option<PathBuf>,
+    /// Root directory of the repository; repeat to search several roots in one call.
+    /// Defaults to the current working directory.
+    #[arg(long, env = "SWE_GREP_PATH", value_name = "DIR")]
+    pub path: Vec<PathBuf>,
 
     /// Optional explicit language hint for AST-Grep (e.g. rust, tsx, swift, auto-swift-ts).
-    #[arg(long, value_name = "LANGUAGE")]
+    #[arg(long, env = "SWE_GREP_LANGUAGE", value_name = "LANGUAGE")]
     pub language: Option<String>,
 
-    /// Timeout applied per tool invocation (seconds).
-    #[arg(long, default_value_t = 3)]
-    pub timeout_secs: u64,
+    /// ripgrep file type to search (repeatable; see `rg --type-list`), e.g. `rust`. Replaces
+    /// the types `--language` selects.
+    #[arg(long = "rg-type", env = "SWE_GREP_RG_TYPE", value_name = "TYPE")]
+    pub rg_types: Vec<String>,
 
-    /// Maximum number of ripgrep matches to collect per query rewrite.
-    #[arg(long, default_value_t = 20)]
-    pub max_matches: usize,
+    /// Define or extend a ripgrep file type for `--rg-type` (repeatable), e.g. `proto:*.proto`.
+    #[arg(
+        long = "rg-type-add",
+        env = "SWE_GREP_RG_TYPE_ADD",
+        value_name = "SPEC"
+    )]
+    pub rg_type_adds: Vec<String>,
+
+    /// Infrastructure-config mode: add Terraform/HCL and YAML rewrites to the search and probe
+    /// `.tf`, `.tfvars`, `.hcl`, `.yaml`, and `.yml` files first, ranking their hits above
+    /// the rest.
+    #[arg(long, env = "SWE_GREP_CONFIG")]
+    pub config: bool,
+
+    /// Search a git revision (commit, tag, or branch) instead of the working tree.
+    #[arg(long, env = "SWE_GREP_REV", value_name = "COMMITISH")]
+    pub rev: Option<String>,
+
+    /// Newline-delimited list of files to probe (`-` reads stdin); replaces discovery.
+    #[arg(long, env = "SWE_GREP_FILES_FROM", value_name = "FILE")]
+    pub files_from: Option<PathBuf>,
+
+    /// Also search the git submodules listed in each root's `.gitmodules` (recursively), each
+    /// with its own ignore rules and config; their hits are reported under the submodule path.
+    #[arg(long, env = "SWE_GREP_INCLUDE_SUBMODULES")]
+    pub include_submodules: bool,
+
+    /// Keep only hits owned by this `CODEOWNERS` owner (e.g. `@platform-team`).
+    #[arg(long, env = "SWE_GREP_OWNER", value_name = "OWNER")]
+    pub owner: Option<String>,
+
+    /// Case handling for text matches; `smart` ignores case only for all-lowercase symbols
+    /// [default: smart].
+    #[arg(long, env = "SWE_GREP_CASE", value_enum, value_name = "MODE")]
+    pub case: Option<CaseMode>,
+
+    /// Match the symbol only as a whole word; on by default for literal identifiers, and
+    /// `--word=false` allows partial matches.
+    #[arg(
+        long,
+        env = "SWE_GREP_WORD",
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub word: Option<bool>,
+
+    /// Option bundle (fast, thorough, docs, ci, or a `[profiles.<name>]` table in
+    /// `.swe-grep.toml`); explicit flags still take precedence.
+    #[arg(long, env = "SWE_GREP_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Run the `[[hooks]]` of the repository's `.swe-grep.toml`; without this they are skipped,
+    /// so a cloned repository cannot run commands through swe-grep.
+    #[arg(long, env = "SWE_GREP_TRUST_REPO_HOOKS")]
+    pub trust_repo_hooks: bool,
+
+    /// Use the `[state] store` and `endpoint` of the repository's `.swe-grep.toml`; without
+    /// this hint state stays in the cache directory unless `SWE_GREP_STATE_STORE` names a store.
+    #[arg(long, env = "SWE_GREP_TRUST_REPO_STATE")]
+    pub trust_repo_state: bool,
+
+    /// Timeout applied per tool invocation in seconds [default: 3].
+    #[arg(long, env = "SWE_GREP_TIMEOUT_SECS", value_name = "SECS")]
+    pub timeout_secs: Option<u64>,
+
+    /// Maximum number of ripgrep matches to collect per query rewrite [default: 20, scaled to
+    /// the repository size; see `swe-grep stats`].
+    #[arg(long, env = "SWE_GREP_MAX_MATCHES", value_name = "N")]
+    pub max_matches: Option<usize>,
+
+    /// Maximum number of candidate files handed to the scoped ripgrep probe; cache hints and
+    /// file-name matches are kept first [default: no limit].
+    #[arg(long, env = "SWE_GREP_MAX_SCOPE_FILES", value_name = "N")]
+    pub max_scope_files: Option<usize>,
+
+    /// Total latency target in milliseconds, split into per-stage budgets; optional stages are
+    /// skipped once it is spent.
+    #[arg(
+        long,
+        env = "SWE_GREP_LATENCY_TARGET_MS",
+        value_name = "MS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub latency_target_ms: Option<u64>,
 
     /// Maximum number of concurrent tool invocations (defaults to 8 workers).
-    #[arg(long, default_value_t = 8)]
+    #[arg(long, env = "SWE_GREP_CONCURRENCY", default_value_t = 8)]
     pub concurrency: usize,
 
     /// Number of neighbouring lines to include before each match when expanding snippets.
-    #[arg(long = "context-before", default_value_t = 0)]
+    #[arg(
+        long = "context-before",
+        env = "SWE_GREP_CONTEXT_BEFORE",
+        default_value_t = 0
+    )]
     pub context_before: usize,
 
     /// Number of neighbouring lines to include after each match when expanding snippets.
-    #[arg(long = "context-after", default_value_t = 0)]
+    #[arg(
+        long = "context-after",
+        env = "SWE_GREP_CONTEXT_AFTER",
+        default_value_t = 0
+    )]
     pub context_after: usize,
 
     /// Retrieve full file bodies for each surfaced hit.
-    #[arg(long = "body", action = ArgAction::SetTrue, default_value_t = false)]
+    #[arg(
+        long = "body",
+        env = "SWE_GREP_BODY",
+        action = ArgAction::SetTrue,
+        default_value_t = false
+    )]
     pub body: bool,
 
+    /// Replace secrets (API keys, tokens, private keys, quoted passwords, high-entropy strings)
+    /// in snippets, expanded context, bodies, and changed lines with `[REDACTED]`.
+    #[arg(long, env = "SWE_GREP_REDACT_SECRETS", default_value_t = false)]
+    pub redact_secrets: bool,
+
     /// Enable Tantivy-backed micro-indexing for the current repository.
-    #[arg(long, default_value_t = false)]
+    #[arg(long, env = "SWE_GREP_ENABLE_INDEX", default_value_t = false)]
     pub enable_index: bool,
 
     /// Override the default path for the Tantivy index directory.
-    #[arg(long)]
+    #[arg(long, env = "SWE_GREP_INDEX_DIR")]
     pub index_dir: Option<PathBuf>,
 
     /// Enable the ripgrep-all fallback for documentation and config files.
-    #[arg(long, default_value_t = false)]
+    #[arg(long, env = "SWE_GREP_ENABLE_RGA", default_value_t = false)]
     pub enable_rga: bool,
 
     /// Directory used to persist symbol hints and directory cache data.
-    #[arg(long)]
+    #[arg(long, env = "SWE_GREP_CACHE_DIR")]
     pub cache_dir: Option<PathBuf>,
 
+    /// Symbols kept in the hint state; the least recently used are evicted first [default: 500].
+    #[arg(long, env = "SWE_GREP_STATE_MAX_SYMBOLS", value_name = "N")]
+    pub state_max_symbols: Option<usize>,
+
     /// Directory to append structured search logs (JSON Lines).
-    #[arg(long)]
+    #[arg(long, env = "SWE_GREP_LOG_DIR")]
     pub log_dir: Option<PathBuf>,
 
     /// Disable fd-based discovery for this search.
-    #[arg(long = "disable-fd", action = ArgAction::SetFalse, default_value_t = true)]
+    #[arg(
+        long = "disable-fd",
+        env = "SWE_GREP_DISABLE_FD",
+        action = ArgAction::SetFalse,
+        default_value_t = true
+    )]
     pub use_fd: bool,
 
     /// Disable AST-Grep disambiguation for this search.
-    #[arg(long = "disable-ast-grep", action = ArgAction::SetFalse, default_value_t = true)]
+    #[arg(
+        long = "disable-ast-grep",
+        env = "SWE_GREP_DISABLE_AST_GREP",
+        action = ArgAction::SetFalse,
+        default_value_t = true
+    )]
     pub use_ast_grep: bool,
+
+    #[command(flatten)]
+    pub tools: ToolArgs,
+
+    /// Only emit these summary or hit fields (e.g. `path,line,snippet`); output-only.
+    #[arg(
+        long,
+        env = "SWE_GREP_FIELDS",
+        value_name = "FIELDS",
+        value_delimiter = ','
+    )]
+    pub fields: Vec<String>,
+
+    /// Add the repository's composition (`repo_stats`) to the summary, walking the repository
+    /// when the copy in the state file is stale.
+    #[arg(long, env = "SWE_GREP_EXPLAIN")]
+    pub explain: bool,
+
+    /// Attach token spans (keyword, ident, string, comment, number) to each hit's snippet and
+    /// expanded snippet for rich rendering.
+    #[arg(long, env = "SWE_GREP_HIGHLIGHT")]
+    pub highlight: bool,
+
+    /// Attach a clickable `link` to each hit: an editor deep link (`vscode`, `jetbrains`) or a
+    /// GitHub permalink at HEAD built from the `origin` remote (`github`).
+    #[arg(long, env = "SWE_GREP_LINK_FORMAT", value_enum, value_name = "FORMAT")]
+    pub link_format: Option<LinkFormat>,
+
+    /// Attach a `permalink` to each hit: a GitHub or GitLab URL from the `origin` remote,
+    /// pinned to the searched commit (HEAD, or the `--rev` commit).
+    #[arg(long, env = "SWE_GREP_PERMALINKS")]
+    pub permalinks: bool,
+
+    /// Order of the reported hits, which are still the best-scoring ones [default: score].
+    #[arg(long, env = "SWE_GREP_SORT", value_enum, value_name = "ORDER")]
+    pub sort: Option<SortOrder>,
+
+    /// Byte-identical output across runs: timings zeroed, no `search_id`, and candidate lists
+    /// sorted. The summary is not stored for `swe-grep show`.
+    #[arg(long, env = "SWE_GREP_DETERMINISTIC")]
+    pub deterministic: bool,
+
+    /// Output format [default: json]; `snapshot` implies `--deterministic`. Output-only.
+    #[arg(long, env = "SWE_GREP_FORMAT", value_enum, value_name = "FORMAT")]
+    pub format: Option<OutputFormat>,
+
+    /// Run a command with a hit instead of printing the summary, e.g. `code -g {path}:{line}`:
+    /// the hit picked in `$SWE_GREP_PICKER` (default `fzf`) with `--format picker`, otherwise
+    /// the top hit. Output-only.
+    #[arg(long, value_name = "COMMAND")]
+    pub exec: Option<String>,
+}
+
+/// The values a bare `swe-grep search` parses to, for searches built in code.
+impl Default for SearchArgs {
+    fn default() -> Self {
+        Self {
+            symbol: String::new(),
+            path: Vec::new(),
+            language: None,
+            rg_types: Vec::new(),
+            rg_type_adds: Vec::new(),
+            config: false,
+            rev: None,
+            files_from: None,
+            include_submodules: false,
+            owner: None,
+            case: None,
+            word: None,
+            profile: None,
+            trust_repo_hooks: false,
+            trust_repo_state: false,
+            timeout_secs: None,
+            max_matches: None,
+            max_scope_files: None,
+            latency_target_ms: None,
+            concurrency: 8,
+            context_before: 0,
+            context_after: 0,
+            body: false,
+            redact_secrets: false,
+            enable_index: false,
+            index_dir: None,
+            enable_rga: false,
+            cache_dir: None,
+            state_max_symbols: None,
+            log_dir: None,
+            use_fd: true,
+            use_ast_grep: true,
+            tools: ToolArgs::default(),
+            fields: Vec::new(),
+            explain: false,
+            highlight: false,
+            link_format: None,
+            permalinks: false,
+            sort: None,
+            deterministic: false,
+            format: None,
+            exec: None,
+        }
+    }
+}
+
+/// Explicit tool binaries and passthrough arguments; these override `[tools.*]` in
+/// `.swe-grep.toml`.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct ToolArgs {
+    /// ripgrep executable to use instead of searching `PATH`.
+    #[arg(long, env = "SWE_GREP_RG_PATH", value_name = "FILE")]
+    pub rg_path: Option<PathBuf>,
+
+    /// fd executable to use instead of searching `PATH`.
+    #[arg(long, env = "SWE_GREP_FD_PATH", value_name = "FILE")]
+    pub fd_path: Option<PathBuf>,
+
+    /// AST-Grep executable to use instead of searching `PATH`.
+    #[arg(long, env = "SWE_GREP_AST_GREP_PATH", value_name = "FILE")]
+    pub ast_grep_path: Option<PathBuf>,
+
+    /// ripgrep-all executable to use instead of searching `PATH`.
+    #[arg(long, env = "SWE_GREP_RGA_PATH", value_name = "FILE")]
+    pub rga_path: Option<PathBuf>,
+
+    /// Extra argument passed to every ripgrep invocation (repeatable).
+    #[arg(
+        long = "rg-arg",
+        env = "SWE_GREP_RG_ARG",
+        value_name = "ARG",
+        allow_hyphen_values = true
+    )]
+    pub rg_args: Vec<String>,
+
+    /// Extra argument passed to every fd invocation (repeatable).
+    #[arg(
+        long = "fd-arg",
+        env = "SWE_GREP_FD_ARG",
+        value_name = "ARG",
+        allow_hyphen_values = true
+    )]
+    pub fd_args: Vec<String>,
+
+    /// Extra argument passed to every AST-Grep invocation (repeatable).
+    #[arg(
+        long = "ast-grep-arg",
+        env = "SWE_GREP_AST_GREP_ARG",
+        value_name = "ARG",
+        allow_hyphen_values = true
+    )]
+    pub ast_grep_args: Vec<String>,
+
+    /// Extra argument passed to every ripgrep-all invocation (repeatable).
+    #[arg(
+        long = "rga-arg",
+        env = "SWE_GREP_RGA_ARG",
+        value_name = "ARG",
+        allow_hyphen_values = true
+    )]
+    pub rga_args: Vec<String>,
+
+    /// ripgrep-all adapters to enable, comma separated (e.g. `zip,pdfpages`).
+    #[arg(
+        long,
+        env = "SWE_GREP_RGA_ADAPTERS",
+        value_name = "LIST",
+        value_delimiter = ','
+    )]
+    pub rga_adapters: Vec<String>,
+
+    /// Use the tool paths and `extra_args` of the repository's `.swe-grep.toml`; without this
+    /// they are ignored, so a cloned repository cannot choose what swe-grep runs.
+    #[arg(long, env = "SWE_GREP_TRUST_REPO_TOOLS")]
+    pub trust_repo_tools: bool,
+
+    /// Times a tool is rerun after a spawn failure or timeout; 0 disables retries [default: 1].
+    #[arg(long, env = "SWE_GREP_TOOL_RETRIES", value_name = "N")]
+    pub tool_retries: Option<u32>,
+
+    /// Delay before the first tool retry, doubled for each further one [default: 100].
+    #[arg(long, env = "SWE_GREP_TOOL_RETRY_BACKOFF_MS", value_name = "MS")]
+    pub tool_retry_backoff_ms: Option<u64>,
+
+    /// Consecutive failures or timeouts after which fd, ast-grep, or rga is skipped for the
+    /// cooldown; 0 never skips [default: 5].
+    #[arg(long, env = "SWE_GREP_TOOL_CIRCUIT_FAILURES", value_name = "N")]
+    pub tool_circuit_failures: Option<u32>,
+
+    /// Seconds a tool skipped after repeated failures waits before it is tried again
+    /// [default: 30].
+    #[arg(long, env = "SWE_GREP_TOOL_CIRCUIT_COOLDOWN_SECS", value_name = "SECS")]
+    pub tool_circuit_cooldown_secs: Option<u64>,
+
+    /// Niceness (0-19) tools run at on Linux [default: 10 inside a cgroup with a CPU quota].
+    #[arg(
+        long,
+        env = "SWE_GREP_TOOL_NICE",
+        value_name = "N",
+        value_parser = clap::value_parser!(i32).range(0..=19)
+    )]
+    pub tool_nice: Option<i32>,
+
+    /// Address-space limit per tool process on Linux, e.g. `512M` or `2G`; tools run uncapped
+    /// unless this or `[tools] mem_limit` is set.
+    #[arg(
+        long,
+        env = "SWE_GREP_TOOL_MEM_LIMIT",
+        value_name = "SIZE",
+        value_parser = crate::tools::limits::parse_size
+    )]
+    pub tool_mem_limit: Option<u64>,
+
+    /// Inject latency and failures into tool runs, e.g. `rg:delay=500ms,ast-grep:fail=0.2`.
+    #[cfg(feature = "chaos")]
+    #[arg(
+        long,
+        hide = true,
+        value_name = "SPEC",
+        value_parser = crate::tools::chaos::ChaosSpec::parse
+    )]
+    pub chaos: Option<crate::tools::chaos::ChaosSpec>,
 }
 
 /// Arguments for the `bench` subcommand.
 #[derive(clap::Args, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 pub struct BenchArgs {
+    #[command(subcommand)]
+    pub command: Option<BenchCommand>,
+
     /// Path to a benchmark scenario file (JSON). Defaults to benchmarks/default.json.
     #[arg(long)]
     pub scenario: Option<PathBuf>,
@@ -124,23 +526,104 @@ pub struct BenchArgs {
     /// Directory to write per-run cycle logs during benchmarks.
     #[arg(long)]
     pub log_dir: Option<PathBuf>,
+
+    /// Number of searches kept in flight at once (1 runs scenarios serially).
+    #[arg(long, default_value_t = 1)]
+    pub parallel: usize,
+
+    /// Benchmark a running server (e.g. http://127.0.0.1:8080) instead of the in-process engine.
+    #[arg(long, value_name = "URL")]
+    pub target: Option<String>,
+
+    /// Output format for the printed report and `--output` file.
+    #[arg(long = "bench-format", value_enum, default_value_t = BenchFormat::Json)]
+    pub bench_format: BenchFormat,
 }
 
-/// Arguments for the `serve` subcommand.
+#[derive(Subcommand, Debug)]
+pub enum BenchCommand {
+    /// Re-run the last searches recorded in a `search.log.jsonl` and compare their latency
+    /// and top hits with what was logged.
+    Replay(BenchReplayArgs),
+}
+
+/// Arguments for the `bench replay` subcommand.
 #[derive(clap::Args, Debug)]
-pub struct ServeArgs {
-    /// Address to bind the HTTP API server.
-    #[arg(long, default_value = "127.0.0.1:8080")]
-    pub http_addr: SocketAddr,
+pub struct BenchReplayArgs {
+    /// Search log written by `--log-dir`.
+    #[arg(long, default_value = "logs/search.log.jsonl")]
+    pub log: PathBuf,
 
-    /// Address to bind the gRPC server.
-    #[arg(long, default_value = "127.0.0.1:50051")]
-    pub grpc_addr: SocketAddr,
+    /// Number of most recent logged searches to replay.
+    #[arg(long, default_value_t = 100)]
+    pub last: usize,
 
-    /// Root directory of the repository to index; defaults to the current working directory.
+    /// Replay against a running server (e.g. http://127.0.0.1:8080) instead of the in-process
+    /// engine.
+    #[arg(long, value_name = "URL")]
+    pub target: Option<String>,
+
+    /// Directory used for caching during the replay.
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub tools: ToolArgs,
+}
+
+/// Report formats supported by `bench --bench-format`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BenchFormat {
+    /// Pretty JSON on stdout; one JSON line appended per run to `--output`.
+    Json,
+    /// One row per scenario, preceded by `#`-prefixed machine metadata.
+    Csv,
+    /// cargo-criterion `--message-format=json` messages with per-sample timings.
+    Criterion,
+}
+
+impl BenchFormat {
+    /// File extension used when `--output` points at a directory.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "jsonl",
+            Self::Csv => "csv",
+            Self::Criterion => "criterion.jsonl",
+        }
+    }
+}
+
+/// Arguments for the `diff` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+    /// Symbol to search for on both sides; required unless both summaries are supplied.
+    #[arg(long)]
+    pub symbol: Option<String>,
+
+    /// Root directory of the repository; defaults to the current working directory.
     #[arg(long)]
     pub path: Option<PathBuf>,
 
+    /// Optional explicit language hint applied to both searches.
+    #[arg(long, value_name = "LANGUAGE")]
+    pub language: Option<String>,
+
+    /// Git revision searched for the "before" side.
+    #[arg(long, value_name = "REV", conflicts_with = "before_summary")]
+    pub before: Option<String>,
+
+    /// Git revision searched for the "after" side; defaults to the working tree.
+    #[arg(long, value_name = "REV", conflicts_with = "after_summary")]
+    pub after: Option<String>,
+
+    /// Saved summary JSON used as the "before" side instead of running a search.
+    #[arg(long, value_name = "FILE")]
+    pub before_summary: Option<PathBuf>,
+
+    /// Saved summary JSON used as the "after" side instead of running a search.
+    #[arg(long, value_name = "FILE")]
+    pub after_summary: Option<PathBuf>,
+
     /// Timeout applied per tool invocation (seconds).
     #[arg(long, default_value_t = 3)]
     pub timeout_secs: u64,
@@ -149,35 +632,748 @@ pub struct ServeArgs {
     #[arg(long, default_value_t = 20)]
     pub max_matches: usize,
 
+    /// Directory used to persist symbol hints and exported revisions.
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+/// Arguments for the `calibrate` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct CalibrateArgs {
+    /// Benchmark scenario files (JSON); only scenarios with an `expected` block are used.
+    #[arg(long, value_name = "FILE", num_args = 1.., required = true)]
+    pub scenarios: Vec<PathBuf>,
+
+    /// Maximum coordinate-descent passes over the score weights.
+    #[arg(long, default_value_t = 3)]
+    pub rounds: usize,
+
+    /// Write the best weights into a config file (defaults to `.swe-grep.toml`).
+    #[arg(long, value_name = "FILE", num_args = 0..=1, default_missing_value = ".swe-grep.toml")]
+    pub write: Option<PathBuf>,
+
+    /// Timeout applied per tool invocation (seconds) unless a scenario overrides it.
+    #[arg(long, default_value_t = 3)]
+    pub timeout_secs: u64,
+
+    /// Directory used to persist symbol hints during calibration runs.
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+}
+
+/// Arguments for the `symbols` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct SymbolsArgs {
+    /// Identifier prefix to complete.
+    #[arg(long)]
+    pub prefix: String,
+
+    /// Maximum number of identifiers to return.
+    #[arg(long, default_value_t = 20)]
+    pub limit: usize,
+
+    /// Root directory of the repository; defaults to the current working directory.
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Override the default path for the Tantivy index directory.
+    #[arg(long)]
+    pub index_dir: Option<PathBuf>,
+}
+
+/// Arguments for the `index` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct IndexArgs {
+    #[command(subcommand)]
+    pub command: IndexCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum IndexCommand {
+    /// Build the index ahead of the first `--enable-index` search, reporting progress
+    /// (files/sec and ETA) on stderr.
+    Build(IndexBuildArgs),
+}
+
+/// Arguments for the `index build` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct IndexBuildArgs {
+    /// Root directory of the repository; defaults to the current working directory.
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Override the default path for the Tantivy index directory.
+    #[arg(long)]
+    pub index_dir: Option<PathBuf>,
+
+    /// Delete the existing index first, so every file is indexed again.
+    #[arg(long)]
+    pub rebuild: bool,
+}
+
+/// Arguments for the `stats` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct StatsArgs {
+    /// Root directory of the repository; defaults to the current working directory.
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Directory holding the state file the stats are cached in; defaults to
+    /// `<path>/.swe-grep-cache`.
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Walk the repository even when the cached stats are still fresh.
+    #[arg(long)]
+    pub refresh: bool,
+
+    /// Use the `[state] store` and `endpoint` of the repository's `.swe-grep.toml`; see
+    /// `search --trust-repo-state`.
+    #[arg(long, env = "SWE_GREP_TRUST_REPO_STATE")]
+    pub trust_repo_state: bool,
+}
+
+/// Arguments for the `show` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct ShowArgs {
+    /// `search_id` reported by the search.
+    pub id: String,
+
+    /// Root directory of the repository the search ran in; defaults to the current working
+    /// directory.
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Cache directory the search used; defaults to `<path>/.swe-grep-cache`.
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Only emit these summary or hit fields (e.g. `path,line,snippet`).
+    #[arg(long, value_name = "FIELDS", value_delimiter = ',')]
+    pub fields: Vec<String>,
+}
+
+/// Arguments for the `outline` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct OutlineArgs {
+    /// File to outline, relative to `--path` or absolute.
+    #[arg(long)]
+    pub file: PathBuf,
+
+    /// Root directory of the repository; defaults to the current working directory.
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Grammar to parse the file with; inferred from its extension by default.
+    #[arg(long, value_name = "LANGUAGE")]
+    pub language: Option<String>,
+
+    /// Timeout for the AST-Grep run, in seconds.
+    #[arg(long, default_value_t = 3)]
+    pub timeout_secs: u64,
+
+    #[command(flatten)]
+    pub tools: ToolArgs,
+}
+
+/// Arguments for the `flag` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct FlagArgs {
+    /// Flag or environment variable to trace, matched case-sensitively as a whole word
+    /// (e.g. `ENABLE_FOO`).
+    #[arg(long)]
+    pub name: String,
+
+    /// Root directory of the repository; defaults to the current working directory.
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Maximum number of occurrences to report.
+    #[arg(long, default_value_t = 200)]
+    pub max_matches: usize,
+
+    /// Timeout for the ripgrep run, in seconds.
+    #[arg(long, default_value_t = 3)]
+    pub timeout_secs: u64,
+
+    #[command(flatten)]
+    pub tools: ToolArgs,
+}
+
+/// Arguments for the `heatmap` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct HeatmapArgs {
+    /// Symbol to count, matched as a whole word.
+    #[arg(long)]
+    pub symbol: String,
+
+    /// Root directory of the repository; defaults to the current working directory.
+    #[arg(long)]
+    pub path: Option<PathBuf>,
+
+    /// Case handling; `smart` ignores case only for all-lowercase symbols.
+    #[arg(long, value_enum, default_value_t = CaseMode::Smart)]
+    pub case: CaseMode,
+
+    /// Maximum number of matching lines to count.
+    #[arg(long, default_value_t = 10_000)]
+    pub max_matches: usize,
+
+    /// Timeout for the ripgrep run, in seconds.
+    #[arg(long, default_value_t = 10)]
+    pub timeout_secs: u64,
+
+    /// Print the tree as JSON or draw it as an SVG treemap.
+    #[arg(long, value_enum, default_value_t = HeatmapFormat::Json)]
+    pub format: HeatmapFormat,
+
+    #[command(flatten)]
+    pub tools: ToolArgs,
+}
+
+/// Outputs selected by `heatmap --format`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeatmapFormat {
+    /// The per-directory and per-file counts as pretty JSON.
+    #[default]
+    Json,
+    /// A treemap with one rectangle per file, sized by its count and coloured by heat.
+    Svg,
+}
+
+/// Arguments for the `query` subcommand. Engine options such as tool paths, cache and index
+/// directories, and which stages run are the server's.
+#[derive(clap::Args, Debug)]
+pub struct QueryArgs {
+    /// Base URL of the server's HTTP API (e.g. `https://search.internal:8080`); gRPC is not
+    /// supported.
+    #[arg(long, env = "SWE_GREP_SERVER", value_name = "URL")]
+    pub server: String,
+
+    /// Bearer token sent in the `Authorization` header, for servers running with `--tenants`;
+    /// only over `https://` unless the server is on this machine.
+    #[arg(
+        long,
+        env = "SWE_GREP_TOKEN",
+        value_name = "TOKEN",
+        hide_env_values = true
+    )]
+    pub token: Option<String>,
+
+    /// Tenant to search as when the server runs with `--tenants`.
+    #[arg(long, env = "SWE_GREP_TENANT", value_name = "ID")]
+    pub tenant: Option<String>,
+
+    /// Symbol or identifier to search for.
+    #[arg(long, env = "SWE_GREP_SYMBOL")]
+    pub symbol: String,
+
+    /// Repository root on the server, which must be its `--path` or an `--allowed-root`;
+    /// defaults to the server's `--path`.
+    #[arg(long, value_name = "DIR")]
+    pub root: Option<PathBuf>,
+
+    /// Optional explicit language hint for AST-Grep (e.g. rust, tsx, swift, auto-swift-ts).
+    #[arg(long, env = "SWE_GREP_LANGUAGE", value_name = "LANGUAGE")]
+    pub language: Option<String>,
+
+    /// ripgrep file type to search (repeatable; see `rg --type-list`).
+    #[arg(long = "rg-type", env = "SWE_GREP_RG_TYPE", value_name = "TYPE")]
+    pub rg_types: Vec<String>,
+
+    /// Define or extend a ripgrep file type for `--rg-type` (repeatable), e.g. `proto:*.proto`.
+    #[arg(
+        long = "rg-type-add",
+        env = "SWE_GREP_RG_TYPE_ADD",
+        value_name = "SPEC"
+    )]
+    pub rg_type_adds: Vec<String>,
+
+    /// Search Terraform/HCL and YAML files first; see `search --config`.
+    #[arg(long, env = "SWE_GREP_CONFIG")]
+    pub config: bool,
+
+    /// Keep only hits owned by this `CODEOWNERS` owner (e.g. `@platform-team`).
+    #[arg(long, env = "SWE_GREP_OWNER", value_name = "OWNER")]
+    pub owner: Option<String>,
+
+    /// Case handling for text matches [default: smart].
+    #[arg(long, env = "SWE_GREP_CASE", value_enum, value_name = "MODE")]
+    pub case: Option<CaseMode>,
+
+    /// Match the symbol only as a whole word; `--word=false` allows partial matches.
+    #[arg(
+        long,
+        env = "SWE_GREP_WORD",
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub word: Option<bool>,
+
+    /// Option bundle (fast, thorough, docs, ci, or one from the server's `.swe-grep.toml`);
+    /// defaults to the server's `--profile`.
+    #[arg(long, env = "SWE_GREP_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Timeout applied per tool invocation in seconds; defaults to the server's.
+    #[arg(long, env = "SWE_GREP_TIMEOUT_SECS", value_name = "SECS")]
+    pub timeout_secs: Option<u64>,
+
+    /// Maximum number of ripgrep matches to collect per query rewrite; defaults to the
+    /// server's.
+    #[arg(long, env = "SWE_GREP_MAX_MATCHES", value_name = "N")]
+    pub max_matches: Option<usize>,
+
+    /// Maximum number of candidate files handed to the scoped ripgrep probe.
+    #[arg(long, env = "SWE_GREP_MAX_SCOPE_FILES", value_name = "N")]
+    pub max_scope_files: Option<usize>,
+
+    /// Total latency target in milliseconds, split into per-stage budgets.
+    #[arg(
+        long,
+        env = "SWE_GREP_LATENCY_TARGET_MS",
+        value_name = "MS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub latency_target_ms: Option<u64>,
+
+    /// Number of neighbouring lines to include before each match when expanding snippets.
+    #[arg(long, env = "SWE_GREP_CONTEXT_BEFORE", value_name = "N")]
+    pub context_before: Option<usize>,
+
+    /// Number of neighbouring lines to include after each match when expanding snippets.
+    #[arg(long, env = "SWE_GREP_CONTEXT_AFTER", value_name = "N")]
+    pub context_after: Option<usize>,
+
+    /// Retrieve full file bodies for each surfaced hit.
+    #[arg(long, env = "SWE_GREP_BODY")]
+    pub body: bool,
+
+    /// Only emit these summary or hit fields (e.g. `path,line,snippet`); output-only.
+    #[arg(
+        long,
+        env = "SWE_GREP_FIELDS",
+        value_name = "FIELDS",
+        value_delimiter = ','
+    )]
+    pub fields: Vec<String>,
+
+    /// Add the repository's composition (`repo_stats`) to the summary.
+    #[arg(long, env = "SWE_GREP_EXPLAIN")]
+    pub explain: bool,
+
+    /// Attach token spans to each hit's snippet and expanded snippet.
+    #[arg(long, env = "SWE_GREP_HIGHLIGHT")]
+    pub highlight: bool,
+
+    /// Attach a clickable `link` to each hit (`vscode`, `jetbrains`, or `github`); editor
+    /// links point at the server's checkout.
+    #[arg(long, env = "SWE_GREP_LINK_FORMAT", value_enum, value_name = "FORMAT")]
+    pub link_format: Option<LinkFormat>,
+
+    /// Attach a GitHub or GitLab `permalink`, pinned to the searched commit, to each hit.
+    #[arg(long, env = "SWE_GREP_PERMALINKS")]
+    pub permalinks: bool,
+
+    /// Order of the reported hits [default: score].
+    #[arg(long, env = "SWE_GREP_SORT", value_enum, value_name = "ORDER")]
+    pub sort: Option<SortOrder>,
+
+    /// Byte-identical output across runs: timings zeroed, no `search_id`, and candidate lists
+    /// sorted.
+    #[arg(long, env = "SWE_GREP_DETERMINISTIC")]
+    pub deterministic: bool,
+
+    /// Queue class on a busy server [default: interactive].
+    #[arg(long, value_enum, value_name = "CLASS")]
+    pub priority: Option<Priority>,
+
+    /// Output format [default: json]; `snapshot` implies `--deterministic`. Output-only.
+    #[arg(long, env = "SWE_GREP_FORMAT", value_enum, value_name = "FORMAT")]
+    pub format: Option<OutputFormat>,
+
+    /// Run a command with a hit instead of printing the summary, e.g. `code -g {path}:{line}`:
+    /// the hit picked in `$SWE_GREP_PICKER` (default `fzf`) with `--format picker`, otherwise
+    /// the top hit. Output-only.
+    #[arg(long, value_name = "COMMAND")]
+    pub exec: Option<String>,
+}
+
+/// Arguments for the `fixtures` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct FixturesArgs {
+    #[command(subcommand)]
+    pub command: FixturesCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FixturesCommand {
+    /// Write a multi-language fixture repository with colliding and widely referenced
+    /// symbols, and print where each known symbol is defined.
+    Generate(FixturesGenerateArgs),
+}
+
+/// Arguments for the `fixtures generate` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct FixturesGenerateArgs {
+    /// Languages to generate sources for, comma separated.
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "rust,ts,swift"
+    )]
+    pub languages: Vec<FixtureLanguage>,
+
+    /// Preset for the number of modules, files per module, and functions per file.
+    #[arg(long, value_enum, default_value_t = FixtureSize::Small)]
+    pub size: FixtureSize,
+
+    /// Override the preset's number of modules per language; every module defines its own
+    /// `login_user`, so this is also the number of colliding definitions.
+    #[arg(long, value_name = "N")]
+    pub modules: Option<usize>,
+
+    /// Override the preset's number of source files per module.
+    #[arg(long, value_name = "N")]
+    pub files_per_module: Option<usize>,
+
+    /// Directory to write the repository to; must be missing or empty. Defaults to a new
+    /// directory under the system temp dir.
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Source languages `fixtures generate` can write.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FixtureLanguage {
+    Rust,
+    #[value(alias = "typescript")]
+    Ts,
+    Swift,
+}
+
+/// Fixture size presets for `fixtures generate --size`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FixtureSize {
+    /// 2 modules of 3 files with 4 functions each, per language.
+    Small,
+    /// 8 modules of 8 files with 8 functions each, per language.
+    Medium,
+    /// 32 modules of 16 files with 12 functions each, per language.
+    Large,
+}
+
+/// Arguments for the `serve` subcommand.
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// Address to bind the HTTP API server.
+    #[arg(long, env = "SWE_GREP_HTTP_ADDR", default_value = "127.0.0.1:8080")]
+    pub http_addr: SocketAddr,
+
+    /// Address to bind the gRPC server.
+    #[arg(long, env = "SWE_GREP_GRPC_ADDR", default_value = "127.0.0.1:50051")]
+    pub grpc_addr: SocketAddr,
+
+    /// Root directory of the repository to index; defaults to the current working directory.
+    #[arg(long, env = "SWE_GREP_PATH")]
+    pub path: Option<PathBuf>,
+
+    /// Further directory a request's `root` (or cache, index, or log directory) may point into;
+    /// repeatable. Requests outside `--path` and every allowed root are rejected.
+    #[arg(
+        long = "allowed-root",
+        env = "SWE_GREP_ALLOWED_ROOT",
+        value_name = "DIR"
+    )]
+    pub allowed_roots: Vec<PathBuf>,
+
+    /// Profile applied to requests that do not name one.
+    #[arg(long, env = "SWE_GREP_PROFILE", value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Timeout applied per tool invocation in seconds [default: 3].
+    #[arg(long, env = "SWE_GREP_TIMEOUT_SECS", value_name = "SECS")]
+    pub timeout_secs: Option<u64>,
+
+    /// Maximum number of ripgrep matches to collect per query rewrite [default: 20, scaled to
+    /// the repository size; see `swe-grep stats`].
+    #[arg(long, env = "SWE_GREP_MAX_MATCHES", value_name = "N")]
+    pub max_matches: Option<usize>,
+
+    /// Total latency target in milliseconds, split into per-stage budgets; optional stages are
+    /// skipped once it is spent.
+    #[arg(
+        long,
+        env = "SWE_GREP_LATENCY_TARGET_MS",
+        value_name = "MS",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub latency_target_ms: Option<u64>,
+
     /// Maximum number of concurrent tool invocations (defaults to 8 workers).
-    #[arg(long, default_value_t = 8)]
+    #[arg(long, env = "SWE_GREP_CONCURRENCY", default_value_t = 8)]
     pub concurrency: usize,
 
     /// Enable Tantivy-backed micro-indexing by default.
-    #[arg(long, default_value_t = false)]
+    #[arg(long, env = "SWE_GREP_ENABLE_INDEX", default_value_t = false)]
     pub enable_index: bool,
 
     /// Enable the ripgrep-all fallback by default.
-    #[arg(long, default_value_t = false)]
+    #[arg(long, env = "SWE_GREP_ENABLE_RGA", default_value_t = false)]
     pub enable_rga: bool,
 
     /// Override the default path for the Tantivy index directory.
-    #[arg(long)]
+    #[arg(long, env = "SWE_GREP_INDEX_DIR")]
     pub index_dir: Option<PathBuf>,
 
     /// Directory used to persist symbol hints and directory cache data.
-    #[arg(long)]
+    #[arg(long, env = "SWE_GREP_CACHE_DIR")]
     pub cache_dir: Option<PathBuf>,
 
+    /// Symbols kept in the hint state; the least recently used are evicted first [default: 500].
+    #[arg(long, env = "SWE_GREP_STATE_MAX_SYMBOLS", value_name = "N")]
+    pub state_max_symbols: Option<usize>,
+
     /// Directory to append structured search logs (JSON Lines).
-    #[arg(long)]
+    #[arg(long, env = "SWE_GREP_LOG_DIR")]
     pub log_dir: Option<PathBuf>,
 
     /// Disable fd-based discovery by default.
-    #[arg(long = "disable-fd", action = ArgAction::SetFalse, default_value_t = true)]
+    #[arg(
+        long = "disable-fd",
+        env = "SWE_GREP_DISABLE_FD",
+        action = ArgAction::SetFalse,
+        default_value_t = true
+    )]
     pub use_fd: bool,
 
     /// Disable AST-Grep disambiguation by default.
-    #[arg(long = "disable-ast-grep", action = ArgAction::SetFalse, default_value_t = true)]
+    #[arg(
+        long = "disable-ast-grep",
+        env = "SWE_GREP_DISABLE_AST_GREP",
+        action = ArgAction::SetFalse,
+        default_value_t = true
+    )]
     pub use_ast_grep: bool,
+
+    /// Redact secrets in every response's snippets, context, bodies, changed lines, and file
+    /// ranges; on unless `--redact-secrets=false`.
+    #[arg(
+        long,
+        env = "SWE_GREP_REDACT_SECRETS",
+        value_name = "BOOL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_value_t = true,
+        default_missing_value = "true",
+        action = ArgAction::Set
+    )]
+    pub redact_secrets: bool,
+
+    /// Use the tool paths and `extra_args` of each served repository's `.swe-grep.toml`; see
+    /// `search --trust-repo-tools`.
+    #[arg(long, env = "SWE_GREP_TRUST_REPO_TOOLS")]
+    pub trust_repo_tools: bool,
+
+    /// Response compression for the HTTP API; compressed request bodies are accepted unless `off`.
+    #[arg(
+        long = "http-compression",
+        env = "SWE_GREP_HTTP_COMPRESSION",
+        value_enum,
+        default_value_t = HttpCompression::Auto
+    )]
+    pub http_compression: HttpCompression,
+
+    /// Serve HTTP on this Unix domain socket and gRPC on `<path>.grpc` instead of TCP.
+    #[arg(
+        long,
+        env = "SWE_GREP_UDS",
+        value_name = "PATH",
+        conflicts_with = "stdio"
+    )]
+    pub uds: Option<PathBuf>,
+
+    /// Answer newline-delimited JSON-RPC 2.0 on stdin/stdout instead of opening any socket.
+    #[arg(long, env = "SWE_GREP_STDIO", default_value_t = false)]
+    pub stdio: bool,
+
+    /// Searches run at once; further requests queue, interactive ones ahead of batch ones
+    /// [default: available CPUs].
+    #[arg(
+        long,
+        env = "SWE_GREP_MAX_CONCURRENT_SEARCHES",
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub max_concurrent_searches: Option<u64>,
+
+    /// Count searches slower than this many times the startup self-benchmark as degraded; 0
+    /// skips the self-benchmark.
+    #[arg(
+        long,
+        env = "SWE_GREP_LATENCY_DEGRADATION_FACTOR",
+        value_name = "FACTOR",
+        default_value_t = crate::service::baseline::DEFAULT_DEGRADATION_FACTOR
+    )]
+    pub latency_degradation_factor: f64,
+
+    /// TOML file of `[tenants.<id>]` tables; every request must then name a tenant and present
+    /// its token, and searches only that tenant's root.
+    #[arg(long, env = "SWE_GREP_TENANTS", value_name = "FILE")]
+    pub tenants: Option<PathBuf>,
+}
+
+/// Case handling selected by `search --case`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum CaseMode {
+    /// Match the symbol's case exactly.
+    Sensitive,
+    /// Ignore case everywhere.
+    Insensitive,
+    /// Ignore case unless the symbol contains an uppercase letter.
+    #[default]
+    Smart,
+}
+
+impl CaseMode {
+    /// Parse a mode name from a request field, ignoring ASCII case.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        <Self as clap::ValueEnum>::from_str(value.trim(), true).map_err(|_| {
+            format!("unknown case mode `{value}` (expected sensitive, insensitive, or smart)")
+        })
+    }
+
+    /// ripgrep flag selecting this mode.
+    pub fn rg_flag(self) -> &'static str {
+        match self {
+            Self::Sensitive => "--case-sensitive",
+            Self::Insensitive => "--ignore-case",
+            Self::Smart => "--smart-case",
+        }
+    }
+
+    /// Whether matching `pattern` under this mode ignores case.
+    pub fn ignores_case(self, pattern: &str) -> bool {
+        match self {
+            Self::Sensitive => false,
+            Self::Insensitive => true,
+            Self::Smart => !pattern.chars().any(char::is_uppercase),
+        }
+    }
+}
+
+/// URL scheme selected by `search --link-format`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum LinkFormat {
+    /// `vscode://file/<absolute path>:<line>`.
+    Vscode,
+    /// `idea://open?file=<absolute path>&line=<line>`.
+    Jetbrains,
+    /// `https://<host>/<owner>/<repo>/blob/<HEAD>/<path>#L<line>`.
+    Github,
+}
+
+impl LinkFormat {
+    /// Parse a format name from a request field, ignoring ASCII case.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        <Self as clap::ValueEnum>::from_str(value.trim(), true).map_err(|_| {
+            format!("unknown link format `{value}` (expected vscode, jetbrains, or github)")
+        })
+    }
+}
+
+/// Summary formats printed by `search --format`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The full summary as pretty JSON.
+    #[default]
+    Json,
+    /// JSON for golden files: no latencies, startup stats, reward, or search id, and search roots
+    /// replaced by `[root]` placeholders.
+    Snapshot,
+    /// One `path<TAB>line<TAB>snippet` line per hit, for piping into fzf.
+    Picker,
+}
+
+/// Orders selected by `search --sort`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum SortOrder {
+    /// Highest score first, ties broken by path and line.
+    #[default]
+    Score,
+    /// By path, then line.
+    Path,
+    /// By line number, then path.
+    Line,
+    /// Most recently modified file first, then by path and line.
+    Recency,
+}
+
+impl SortOrder {
+    /// Parse an order name from a request field, ignoring ASCII case.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        <Self as clap::ValueEnum>::from_str(value.trim(), true).map_err(|_| {
+            format!("unknown sort order `{value}` (expected score, path, line, or recency)")
+        })
+    }
+}
+
+/// Queue classes for server requests; interactive searches take free slots before batch ones.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub enum Priority {
+    /// A caller waiting on the answer, such as an agent's query.
+    #[default]
+    Interactive,
+    /// Background work, such as searches re-run after a re-index.
+    Batch,
+}
+
+impl Priority {
+    /// Parse a priority name from a request field, ignoring ASCII case.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        <Self as clap::ValueEnum>::from_str(value.trim(), true)
+            .map_err(|_| format!("unknown priority `{value}` (expected interactive or batch)"))
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Interactive => "interactive",
+            Self::Batch => "batch",
+        }
+    }
+}
+
+/// Encodings offered by `serve --http-compression`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HttpCompression {
+    /// Never compress responses and reject compressed request bodies.
+    Off,
+    /// gzip only.
+    Gzip,
+    /// zstd only.
+    Zstd,
+    /// Negotiate gzip or zstd from the client's `Accept-Encoding`.
+    #[default]
+    Auto,
+}
+
+impl HttpCompression {
+    pub fn gzip(self) -> bool {
+        matches!(self, Self::Gzip | Self::Auto)
+    }
+
+    pub fn zstd(self) -> bool {
+        matches!(self, Self::Zstd | Self::Auto)
+    }
 }
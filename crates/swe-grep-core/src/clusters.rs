@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Files that make their directory a package of its own.
+const MANIFESTS: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "go.mod",
+    "Package.swift",
+    "pyproject.toml",
+    "setup.py",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+];
+/// Clusters listed in a summary; smaller ones are left out.
+pub const MAX_CLUSTERS: usize = 8;
+
+/// Deduplicated hits that share a package or top-level directory.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HitCluster {
+    /// The nearest directory below the root holding a package manifest (`Cargo.toml`,
+    /// `package.json`, `go.mod`, …), else the top-level directory; `.` for files at the root.
+    pub path: String,
+    pub hits: usize,
+    /// Fraction of all deduplicated hits in the cluster, rounded to two places.
+    pub share: f32,
+    /// The best-scoring hit in the cluster.
+    pub best_path: String,
+    pub best_line: usize,
+}
+
+/// Group `hits` (relative paths and lines, best first) by package or top-level directory,
+/// largest cluster first. Empty when there are fewer than two hits, which need no summary.
+pub fn cluster<'a, I>(root: &Path, hits: I) -> Vec<HitCluster>
+where
+    I: IntoIterator<Item = (&'a Path, usize)>,
+{
+    let mut packages = PackageDirs::new(root);
+    let mut clusters: Vec<HitCluster> = Vec::new();
+    let mut total = 0;
+    for (path, line) in hits {
+        total += 1;
+        let key = packages.cluster_of(path);
+        match clusters.iter_mut().find(|cluster| cluster.path == key) {
+            Some(cluster) => cluster.hits += 1,
+            None => clusters.push(HitCluster {
+                path: key,
+                hits: 1,
+                share: 0.0,
+                best_path: path.display().to_string(),
+                best_line: line,
+            }),
+        }
+    }
+    if total < 2 {
+        return Vec::new();
+    }
+    for cluster in &mut clusters {
+        cluster.share = crate::search::round_two(cluster.hits as f32 / total as f32);
+    }
+    // Stable, so equally large clusters keep the order of their best hits.
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.hits));
+    clusters.truncate(MAX_CLUSTERS);
+    clusters
+}
+
+/// Recompute `share` after clusters from several roots were combined.
+pub fn reshare(clusters: &mut Vec<HitCluster>) {
+    let total: usize = clusters.iter().map(|cluster| cluster.hits).sum();
+    if total == 0 {
+        return;
+    }
+    for cluster in clusters.iter_mut() {
+        cluster.share = crate::search::round_two(cluster.hits as f32 / total as f32);
+    }
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.hits));
+    clusters.truncate(MAX_CLUSTERS);
+}
+
+/// Which directories under a root hold a manifest, looked up once each.
+struct PackageDirs<'a> {
+    root: &'a Path,
+    known: HashMap<PathBuf, bool>,
+}
+
+impl<'a> PackageDirs<'a> {
+    fn new(root: &'a Path) -> Self {
+        Self {
+            root,
+            known: HashMap::new(),
+        }
+    }
+
+    fn cluster_of(&mut self, path: &Path) -> String {
+        let mut dir = path.parent();
+        while let Some(current) = dir.filter(|dir| !dir.as_os_str().is_empty()) {
+            if self.is_package(current) {
+                return slash_path(current);
+            }
+            dir = current.parent();
+        }
+        match path.components().next() {
+            Some(Component::Normal(first)) if path.components().count() > 1 => {
+                first.to_string_lossy().into_owned()
+            }
+            _ => ".".to_string(),
+        }
+    }
+
+    fn is_package(&mut self, dir: &Path) -> bool {
+        if let Some(known) = self.known.get(dir) {
+            return *known;
+        }
+        let absolute = self.root.join(dir);
+        let package = MANIFESTS
+            .iter()
+            .any(|manifest| absolute.join(manifest).is_file());
+        self.known.insert(dir.to_path_buf(), package);
+        package
+    }
+}
+
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::ToolArgs;
+use crate::profile::SearchProfile;
+use crate::tools::backend::ToolBackend;
+#[cfg(feature = "chaos")]
+use crate::tools::chaos::{ChaosBackend, ChaosSpec};
+use crate::tools::circuit::{self, CircuitPolicy};
+use crate::tools::limits::{self, ResourceLimits};
+use crate::tools::retry::{self, RetryPolicy};
+
+/// Per-repository configuration file, looked up at the search root.
+pub const CONFIG_FILE: &str = ".swe-grep.toml";
+
+/// Settings read from `.swe-grep.toml`; every section is optional.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RepoConfig {
+    pub scoring: ScoringWeights,
+    pub reward: RewardWeights,
+    pub tools: ToolsConfig,
+    /// `[profiles.<name>]` tables: adjust a built-in profile or define a new one.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, SearchProfile>,
+    /// `[[hooks]]` entries post-processing every summary, in file order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookConfig>,
+    /// `[state]`: where learned symbol hints are kept.
+    pub state: StateConfig,
+}
+
+impl RepoConfig {
+    /// Location of the config file for a repository root.
+    pub fn path(root: &Path) -> PathBuf {
+        root.join(CONFIG_FILE)
+    }
+
+    /// Load the config for `root`, falling back to defaults when the file is absent.
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = Self::path(root);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let config: Self =
+            toml::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))?;
+        if config.hooks.iter().any(|hook| hook.command.is_empty()) {
+            anyhow::bail!("{}: every [[hooks]] entry needs a command", path.display());
+        }
+        Ok(config)
+    }
+}
+
+/// Score adjustments applied to each hit during the verify stage.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringWeights {
+    /// Bonus for hits in files surfaced by discovery.
+    pub fd_candidate: f64,
+    /// Bonus for hits confirmed by an AST-Grep match.
+    pub ast_match: f64,
+    /// Bonus for hits found through the Tantivy index.
+    pub indexed: f64,
+    /// Penalty for hits that only turned up in the global escalation probe.
+    pub global_penalty: f64,
+    /// Penalty for ripgrep-all fallback hits.
+    pub rga_penalty: f64,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            fd_candidate: 0.2,
+            ast_match: 0.5,
+            indexed: 0.1,
+            global_penalty: 0.05,
+            rga_penalty: 0.1,
+        }
+    }
+}
+
+/// Coefficients combining the cycle metrics into the reported reward.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RewardWeights {
+    pub precision: f64,
+    pub density: f64,
+    pub clustering: f64,
+    pub fd_bonus: f64,
+}
+
+impl Default for RewardWeights {
+    fn default() -> Self {
+        Self {
+            precision: 0.5,
+            density: 0.3,
+            clustering: 0.15,
+            fd_bonus: 0.05,
+        }
+    }
+}
+
+/// External tool overrides from the `[tools.rg]`, `[tools.fd]`, `[tools.ast_grep]`, and
+/// `[tools.rga]` tables, plus the retry policy, circuit breaker, and resource limits shared by
+/// all of them.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolsConfig {
+    /// Retries after a spawn failure or timeout (default 1; 0 disables retrying).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Delay before the first retry in milliseconds, doubled for each further one (default 100).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_backoff_ms: Option<u64>,
+    /// Consecutive failures or timeouts after which fd, ast-grep, or rga is skipped for the
+    /// cooldown (default 5; 0 never skips).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_failures: Option<u32>,
+    /// Seconds a failing tool is skipped before it is tried again (default 30).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit_cooldown_secs: Option<u64>,
+    /// Niceness (0-19) tools run at on Linux (default 10 inside a cgroup with a CPU quota).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+    /// Address-space limit per tool process on Linux, e.g. `"512M"` (default half the cgroup
+    /// memory limit, if any).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mem_limit: Option<String>,
+    pub rg: ToolSettings,
+    pub fd: ToolSettings,
+    pub ast_grep: ToolSettings,
+    pub rga: ToolSettings,
+    /// ripgrep-all adapters to enable, e.g. `["zip", "pdfpages"]`; empty keeps rga's defaults.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rga_adapters: Vec<String>,
+    /// Faults injected into every tool run, from `--chaos`.
+    #[cfg(feature = "chaos")]
+    #[serde(skip)]
+    pub chaos: Option<ChaosSpec>,
+}
+
+/// Where persistent hint state lives: the cache directory unless `store` names a store shared
+/// by several machines.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StateConfig {
+    /// Shared store: `redis[s]://[[user]:password@]host[:port][/db]` (with the `redis-state`
+    /// feature), or `s3://bucket[/prefix]` or `gs://bucket[/prefix]` (with `s3-state`). The
+    /// `SWE_GREP_STATE_STORE` environment variable takes precedence.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub store: Option<String>,
+    /// `https://host[:port]` of an S3-compatible server such as MinIO, addressed path-style;
+    /// `http://` only for loopback hosts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// Region S3 requests are signed for; defaults to `AWS_REGION`, then `us-east-1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Seconds Redis keeps a commit's state after its last save; kept forever when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
+}
+
+/// Where to find one tool and what to pass it on top of swe-grep's own arguments.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolSettings {
+    /// Executable to run; relative paths are resolved against the repository root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Arguments inserted before swe-grep's own flags on every invocation.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_args: Vec<String>,
+    /// Caps from `[tools] nice` and `mem_limit`, filled in by `ToolsConfig::resolve`.
+    #[serde(skip)]
+    pub limits: ResourceLimits,
+}
+
+impl StateConfig {
+    /// Drop the file's `store` and `endpoint` unless `trust_repo_state` is set: they decide
+    /// where hint state and the signed store credentials are sent, so a cloned repository must
+    /// not pick them. `SWE_GREP_STATE_STORE` still selects a shared store either way.
+    pub fn resolve(mut self, trust_repo_state: bool) -> Self {
+        if !trust_repo_state && (self.store.is_some() || self.endpoint.is_some()) {
+            tracing::warn!(
+                "ignoring the [state] store and endpoint in {CONFIG_FILE}; pass --trust-repo-state to use them"
+            );
+            self.store = None;
+            self.endpoint = None;
+        }
+        self
+    }
+}
+
+impl ToolsConfig {
+    /// Layer command-line overrides on top of the config file and check every configured path.
+    ///
+    /// CLI values replace the file's per field: a `--rg-path` keeps `[tools.rg] extra_args`, and
+    /// any `--rg-arg` replaces the file's list rather than extending it. The file's paths and
+    /// extra arguments name what gets executed, so they are dropped unless
+    /// `--trust-repo-tools` is set.
+    pub fn resolve(mut self, root: &Path, args: &ToolArgs) -> Result<Self> {
+        self.retries = args.tool_retries.or(self.retries);
+        self.retry_backoff_ms = args.tool_retry_backoff_ms.or(self.retry_backoff_ms);
+        self.circuit_failures = args.tool_circuit_failures.or(self.circuit_failures);
+        self.circuit_cooldown_secs = args
+            .tool_circuit_cooldown_secs
+            .or(self.circuit_cooldown_secs);
+        self.nice = args.tool_nice.or(self.nice);
+        if let Some(nice) = self.nice
+            && !(0..=19).contains(&nice)
+        {
+            anyhow::bail!("tool niceness {nice} is outside 0-19");
+        }
+        let mem_limit_bytes = match args.tool_mem_limit {
+            Some(bytes) => Some(bytes),
+            None => self
+                .mem_limit
+                .as_deref()
+                .map(limits::parse_size)
+                .transpose()
+                .map_err(|err| anyhow::anyhow!("invalid [tools] mem_limit: {err}"))?,
+        };
+        let limits = ResourceLimits::resolve(self.nice, mem_limit_bytes);
+        if !args.rga_adapters.is_empty() {
+            self.rga_adapters = args.rga_adapters.clone();
+        }
+        #[cfg(feature = "chaos")]
+        {
+            self.chaos = args.chaos.clone();
+        }
+        if let Some(adapter) = self.rga_adapters.iter().find(|adapter| {
+            adapter.is_empty()
+                || !adapter
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '+'))
+        }) {
+            anyhow::bail!("invalid rga adapter `{adapter}`");
+        }
+        for (name, settings, path, extra_args) in [
+            ("rg", &mut self.rg, &args.rg_path, &args.rg_args),
+            ("fd", &mut self.fd, &args.fd_path, &args.fd_args),
+            (
+                "ast-grep",
+                &mut self.ast_grep,
+                &args.ast_grep_path,
+                &args.ast_grep_args,
+            ),
+            ("rga", &mut self.rga, &args.rga_path, &args.rga_args),
+        ] {
+            if !args.trust_repo_tools
+                && (settings.path.is_some() || !settings.extra_args.is_empty())
+            {
+                tracing::warn!(
+                    tool = name,
+                    "ignoring the tool path and extra_args in {CONFIG_FILE}; pass --trust-repo-tools to use them"
+                );
+                settings.path = None;
+                settings.extra_args.clear();
+            }
+            if let Some(path) = path {
+                settings.path = Some(path.clone());
+            } else if let Some(path) = settings.path.take() {
+                settings.path = Some(if path.is_absolute() {
+                    path
+                } else {
+                    root.join(path)
+                });
+            }
+            if !extra_args.is_empty() {
+                settings.extra_args = extra_args.clone();
+            }
+            settings.limits = limits;
+            settings
+                .validate()
+                .with_context(|| format!("invalid {name} tool configuration"))?;
+        }
+        Ok(self)
+    }
+
+    /// Retry policy handed to every tool.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            retries: self.retries.unwrap_or(retry::DEFAULT_RETRIES),
+            backoff: Duration::from_millis(
+                self.retry_backoff_ms.unwrap_or(retry::DEFAULT_BACKOFF_MS),
+            ),
+        }
+    }
+
+    /// `inner`, with the `--chaos` faults injected when there are any.
+    pub fn backend(&self, inner: Arc<dyn ToolBackend>) -> Arc<dyn ToolBackend> {
+        #[cfg(feature = "chaos")]
+        if let Some(spec) = self.chaos.clone() {
+            return Arc::new(ChaosBackend::new(inner, spec));
+        }
+        inner
+    }
+
+    /// Circuit breaker shared by the optional tools.
+    pub fn circuit_policy(&self) -> CircuitPolicy {
+        CircuitPolicy {
+            failures: self.circuit_failures.unwrap_or(circuit::DEFAULT_FAILURES),
+            cooldown: Duration::from_secs(
+                self.circuit_cooldown_secs
+                    .unwrap_or(circuit::DEFAULT_COOLDOWN_SECS),
+            ),
+        }
+    }
+}
+
+impl ToolSettings {
+    /// Check the extra arguments and make the configured path absolute, since tools are spawned
+    /// from the repository root rather than the caller's working directory.
+    fn validate(&mut self) -> Result<()> {
+        if self.extra_args.iter().any(|arg| arg.trim().is_empty()) {
+            anyhow::bail!("extra arguments must not be empty");
+        }
+        let Some(path) = self.path.clone() else {
+            return Ok(());
+        };
+        let metadata = fs::metadata(&path)
+            .with_context(|| format!("tool binary {} does not exist", path.display()))?;
+        if !metadata.is_file() {
+            anyhow::bail!("tool binary {} is not a file", path.display());
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if metadata.permissions().mode() & 0o111 == 0 {
+                anyhow::bail!("tool binary {} is not executable", path.display());
+            }
+        }
+        self.path = Some(
+            fs::canonicalize(&path)
+                .with_context(|| format!("failed to canonicalize {}", path.display()))?,
+        );
+        Ok(())
+    }
+}
+
+/// An external command that rewrites search summaries: it reads the summary JSON on stdin and
+/// prints the (possibly modified) summary JSON on stdout.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookConfig {
+    /// Program and arguments; a relative program containing a path separator is resolved against
+    /// the repository root, anything else is looked up on `PATH`.
+    pub command: Vec<String>,
+    /// Seconds the hook may run before it is killed (default 5).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Fail the search when the hook fails, instead of keeping the unmodified summary and
+    /// reporting a `hook_failed` warning.
+    pub required: bool,
+}
+
+/// Replace the `[scoring]` and `[reward]` tables in the config at `path`, keeping every other
+/// setting. Comments in an existing file are not preserved.
+pub fn write_weights(path: &Path, scoring: &ScoringWeights, reward: &RewardWeights) -> Result<()> {
+    let mut table = if path.is_file() {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        raw.parse::<toml::Table>()
+            .with_context(|| format!("failed to parse {}", path.display()))?
+    } else {
+        toml::Table::new()
+    };
+    table.insert("scoring".to_string(), toml::Value::try_from(scoring)?);
+    table.insert("reward".to_string(), toml::Value::try_from(reward)?);
+
+    let rendered = toml::to_string_pretty(&table)?;
+    fs::write(path, rendered).with_context(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,280 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::identifier;
+use crate::outline;
+use crate::search::{self, TopHit};
+use crate::source;
+use crate::tools::ast_grep::{AstGrepNode, AstGrepTool};
+
+/// Words that introduce a declaration of the name after them, for files ast-grep cannot parse.
+const DECLARATION_KEYWORDS: &[&str] = &[
+    "actor",
+    "class",
+    "const",
+    "def",
+    "enum",
+    "fn",
+    "func",
+    "function",
+    "interface",
+    "macro_rules!",
+    "mod",
+    "protocol",
+    "static",
+    "struct",
+    "trait",
+    "type",
+    "typealias",
+    "union",
+];
+
+/// Where the identifier under an editor cursor is defined.
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DefinitionResponse {
+    /// The identifier at the requested position.
+    pub symbol: String,
+    /// Hits that declare `symbol` (functions, types, modules, constants, …), best first.
+    pub definitions: Vec<TopHit>,
+    /// The remaining hits, which use or mention it.
+    pub references: Vec<TopHit>,
+}
+
+/// The requested position is not on an identifier.
+#[derive(Debug)]
+pub struct NoIdentifier {
+    pub path: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for NoIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "no identifier at {}:{}:{}",
+            self.path, self.line, self.column
+        )
+    }
+}
+
+impl std::error::Error for NoIdentifier {}
+
+/// Identifier node kinds per grammar: the leaves a cursor can rest on.
+fn identifier_kinds(language: &str) -> Option<&'static [&'static str]> {
+    let kinds: &'static [&'static str] = match language {
+        "rust" => &[
+            "identifier",
+            "type_identifier",
+            "field_identifier",
+            "shorthand_field_identifier",
+        ],
+        "swift" => &["simple_identifier", "type_identifier"],
+        "typescript" | "tsx" => &[
+            "identifier",
+            "type_identifier",
+            "property_identifier",
+            "shorthand_property_identifier",
+        ],
+        "javascript" => &[
+            "identifier",
+            "property_identifier",
+            "shorthand_property_identifier",
+        ],
+        "python" => &["identifier"],
+        _ => return None,
+    };
+    Some(kinds)
+}
+
+/// ast-grep language of `path`; notebooks are JSON on disk, so they have none.
+fn ast_language(path: &Path) -> Option<&'static str> {
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+    {
+        return None;
+    }
+    match search::detect_language_from_path(path)? {
+        "jsx" => Some("javascript"),
+        language => Some(language),
+    }
+}
+
+/// The identifier at 1-based `line` and character `column` of `path` (relative to `root`),
+/// whose text on that line is `line_text`.
+///
+/// With ast-grep the syntax tree decides, so keywords, comments, and strings hold none; without
+/// it, or for languages it is not set up for, the word around the column is taken. A column
+/// just past the last character still selects the identifier, as editors put the cursor there.
+pub async fn identifier_at(
+    root: &Path,
+    path: &Path,
+    line_text: &str,
+    line: usize,
+    column: usize,
+    tool: Option<&AstGrepTool>,
+) -> anyhow::Result<String> {
+    let missing = || NoIdentifier {
+        path: path.to_string_lossy().replace('\\', "/"),
+        line,
+        column,
+    };
+    if column == 0 {
+        return Err(missing().into());
+    }
+    if let Some(tool) = tool
+        && let Some(language) = ast_language(path)
+        && let Some(kinds) = identifier_kinds(language)
+    {
+        match tool.nodes(root, path, language, kinds).await {
+            Ok(nodes) => {
+                return node_at(&nodes, line, column)
+                    .map(|node| node.text.clone())
+                    .ok_or_else(|| missing().into());
+            }
+            Err(err) => tracing::debug!(
+                error = %err,
+                "ast-grep could not parse the file; taking the word at the cursor"
+            ),
+        }
+    }
+    word_at(line_text, column).ok_or_else(|| missing().into())
+}
+
+/// The node covering `column` on `line`, else one ending right before it.
+fn node_at(nodes: &[AstGrepNode], line: usize, column: usize) -> Option<&AstGrepNode> {
+    let on_line = || {
+        nodes
+            .iter()
+            .filter(move |node| node.start_line == line && node.end_line == line)
+    };
+    on_line()
+        .find(|node| node.start_column <= column && column < node.end_column)
+        .or_else(|| on_line().find(|node| node.end_column == column))
+}
+
+/// The identifier around the 1-based character `column` of `text`.
+fn word_at(text: &str, column: usize) -> Option<String> {
+    let chars: Vec<char> = text.trim_end_matches('\n').chars().collect();
+    let is_word = |index: usize| {
+        chars
+            .get(index)
+            .is_some_and(|c| identifier::is_continue(*c))
+    };
+    let index = column - 1;
+    let index = if is_word(index) {
+        index
+    } else if index > 0 && is_word(index - 1) {
+        index - 1
+    } else {
+        return None;
+    };
+    let start = (0..index)
+        .rev()
+        .take_while(|index| is_word(*index))
+        .last()
+        .unwrap_or(index);
+    let end = (index..chars.len())
+        .take_while(|index| is_word(*index))
+        .last()
+        .map_or(index, |end| end + 1);
+    let word: String = chars[start..end].iter().collect();
+    identifier::is_identifier(&word).then_some(word)
+}
+
+/// Split `hits` (relative to `root`) into those that declare `symbol` and the rest, keeping
+/// their order.
+///
+/// ast-grep finds declarations through the node kinds `outline` uses; files it cannot parse fall
+/// back to a declaration keyword right before the name, such as `fn` or `class`.
+pub async fn partition(
+    root: &Path,
+    symbol: &str,
+    hits: Vec<TopHit>,
+    tool: Option<&AstGrepTool>,
+) -> (Vec<TopHit>, Vec<TopHit>) {
+    let mut declared: HashMap<String, Option<HashSet<usize>>> = HashMap::new();
+    let mut definitions = Vec::new();
+    let mut references = Vec::new();
+    for hit in hits {
+        if hit.archive_path.is_some() || hit.cell.is_some() {
+            references.push(hit);
+            continue;
+        }
+        if !declared.contains_key(&hit.path) {
+            let lines = declared_lines(root, Path::new(&hit.path), symbol, tool).await;
+            declared.insert(hit.path.clone(), lines);
+        }
+        let defines = match &declared[&hit.path] {
+            Some(lines) => lines.contains(&hit.line),
+            None => declares_on_line(&root.join(&hit.path), hit.line, symbol),
+        };
+        if defines {
+            definitions.push(hit);
+        } else {
+            references.push(hit);
+        }
+    }
+    (definitions, references)
+}
+
+/// Lines of `path` where ast-grep finds a declaration named `symbol`; `None` when it cannot
+/// tell. `impl` blocks are left out: they extend a type rather than define it.
+async fn declared_lines(
+    root: &Path,
+    path: &Path,
+    symbol: &str,
+    tool: Option<&AstGrepTool>,
+) -> Option<HashSet<usize>> {
+    let tool = tool?;
+    let language = ast_language(path)?;
+    let rules: Vec<(&str, &str)> = outline::declaration_kinds(language)?
+        .iter()
+        .filter(|(_, _, kind)| *kind != "impl")
+        .map(|(node, field, _)| (*node, *field))
+        .collect();
+    match tool.declarations(root, path, language, &rules).await {
+        Ok(declarations) => Some(
+            declarations
+                .into_iter()
+                .filter(|declaration| declaration.name == symbol)
+                .map(|declaration| declaration.name_line)
+                .collect(),
+        ),
+        Err(err) => {
+            tracing::debug!(
+                error = %err,
+                path = %path.display(),
+                "ast-grep could not list declarations; checking for declaration keywords"
+            );
+            None
+        }
+    }
+}
+
+/// Whether 1-based `line` of `path` declares `symbol` after one of [`DECLARATION_KEYWORDS`].
+fn declares_on_line(path: &Path, line: usize, symbol: &str) -> bool {
+    let Ok(contents) = source::read(path) else {
+        return false;
+    };
+    let Some(text) = contents.lines().nth(line.saturating_sub(1)) else {
+        return false;
+    };
+    text.match_indices(symbol).any(|(start, _)| {
+        let after = text[start + symbol.len()..].chars().next();
+        let before = &text[..start];
+        if after.is_some_and(identifier::is_continue) || !before.ends_with(char::is_whitespace) {
+            return false;
+        }
+        before
+            .trim_end()
+            .rsplit(|c: char| c.is_whitespace() || c == '(')
+            .next()
+            .is_some_and(|keyword| DECLARATION_KEYWORDS.contains(&keyword))
+    })
+}
@@ -0,0 +1,247 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{DiffArgs, SearchArgs, ToolArgs};
+use crate::search::{self, SearchSummary};
+
+/// Compare two searches and report which hits were added, removed, or moved.
+pub async fn run(args: DiffArgs) -> Result<DiffReport> {
+    let root = args
+        .path
+        .clone()
+        .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
+
+    let before = match (&args.before_summary, &args.before) {
+        (Some(file), _) => Side::from_file(file)?,
+        (None, Some(rev)) => Side::from_revision(&args, &root, rev).await?,
+        (None, None) => anyhow::bail!("diff requires --before <rev> or --before-summary <file>"),
+    };
+    let after = match (&args.after_summary, &args.after) {
+        (Some(file), _) => Side::from_file(file)?,
+        (None, Some(rev)) => Side::from_revision(&args, &root, rev).await?,
+        (None, None) => Side::from_worktree(&args, &root).await?,
+    };
+
+    let symbol = args
+        .symbol
+        .clone()
+        .or_else(|| before.symbol.clone())
+        .or_else(|| after.symbol.clone())
+        .unwrap_or_default();
+
+    Ok(compare(symbol, before, after))
+}
+
+/// Hits present on each side of a diff, labelled with their origin.
+struct Side {
+    label: String,
+    symbol: Option<String>,
+    hits: Vec<DiffHit>,
+}
+
+impl Side {
+    fn from_summary(label: String, summary: &SearchSummary) -> Self {
+        Self {
+            label,
+            symbol: Some(summary.symbol.clone()),
+            hits: summary
+                .top_hits
+                .iter()
+                .map(|hit| DiffHit {
+                    path: hit.path.clone(),
+                    line: hit.line,
+                    snippet: hit.snippet.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Load a summary saved from the CLI, an HTTP response, or a search log entry.
+    fn from_file(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read summary {}", path.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&raw)
+            .with_context(|| format!("failed to parse summary {}", path.display()))?;
+        let summary_value = value.get("summary").cloned().unwrap_or(value);
+        let saved: SavedSummary = serde_json::from_value(summary_value)
+            .with_context(|| format!("{} does not contain a search summary", path.display()))?;
+        Ok(Self {
+            label: path.display().to_string(),
+            symbol: Some(saved.symbol),
+            hits: saved.top_hits,
+        })
+    }
+
+    async fn from_revision(args: &DiffArgs, root: &Path, rev: &str) -> Result<Self> {
+        let symbol = required_symbol(args)?;
+        let summary = search::execute(search_args(args, symbol, root, Some(rev))).await?;
+        let commit = summary.revision.clone().unwrap_or_default();
+        Ok(Self::from_summary(format!("{rev} ({commit})"), &summary))
+    }
+
+    async fn from_worktree(args: &DiffArgs, root: &Path) -> Result<Self> {
+        let symbol = required_symbol(args)?;
+        let summary = search::execute(search_args(args, symbol, root, None)).await?;
+        Ok(Self::from_summary("working tree".to_string(), &summary))
+    }
+}
+
+fn required_symbol(args: &DiffArgs) -> Result<String> {
+    args.symbol
+        .clone()
+        .filter(|symbol| !symbol.trim().is_empty())
+        .context("--symbol is required unless both sides are saved summaries")
+}
+
+fn search_args(args: &DiffArgs, symbol: String, root: &Path, rev: Option<&str>) -> SearchArgs {
+    SearchArgs {
+        symbol,
+        path: vec![root.to_path_buf()],
+        language: args.language.clone(),
+        rg_types: Vec::new(),
+        rg_type_adds: Vec::new(),
+        config: false,
+        rev: rev.map(str::to_string),
+        files_from: None,
+        include_submodules: false,
+        owner: None,
+        case: None,
+        word: None,
+        profile: None,
+        trust_repo_hooks: false,
+        trust_repo_state: false,
+        timeout_secs: Some(args.timeout_secs),
+        max_matches: Some(args.max_matches),
+        max_scope_files: None,
+        latency_target_ms: None,
+        concurrency: 8,
+        context_before: 0,
+        context_after: 0,
+        body: false,
+        redact_secrets: false,
+        enable_index: false,
+        index_dir: None,
+        enable_rga: false,
+        cache_dir: args.cache_dir.clone(),
+        state_max_symbols: None,
+        log_dir: None,
+        use_fd: true,
+        use_ast_grep: true,
+        tools: ToolArgs::default(),
+        fields: Vec::new(),
+        explain: false,
+        highlight: false,
+        link_format: None,
+        permalinks: false,
+        sort: None,
+        deterministic: false,
+        format: None,
+        exec: None,
+    }
+}
+
+/// Pair up hits between the two sides.
+///
+/// Identical path/line/snippet triples are unchanged; hits whose snippet text survives at a
+/// different location are reported as moved (same-file moves are preferred); the rest are
+/// added or removed.
+fn compare(symbol: String, before: Side, after: Side) -> DiffReport {
+    let mut remaining_after: Vec<Option<DiffHit>> = after.hits.into_iter().map(Some).collect();
+    let mut leftover_before: Vec<DiffHit> = Vec::new();
+    let mut unchanged = 0usize;
+
+    for hit in before.hits {
+        let exact = remaining_after
+            .iter()
+            .position(|candidate| candidate.as_ref() == Some(&hit));
+        match exact {
+            Some(idx) => {
+                remaining_after[idx] = None;
+                unchanged += 1;
+            }
+            None => leftover_before.push(hit),
+        }
+    }
+
+    let mut moved = Vec::new();
+    let mut removed = Vec::new();
+    for hit in leftover_before {
+        let same_text = |candidate: &Option<DiffHit>| {
+            candidate
+                .as_ref()
+                .is_some_and(|c| hit.snippet.is_some() && c.snippet == hit.snippet)
+        };
+        let target = remaining_after
+            .iter()
+            .position(|candidate| {
+                same_text(candidate) && candidate.as_ref().is_some_and(|c| c.path == hit.path)
+            })
+            .or_else(|| remaining_after.iter().position(same_text));
+        match target.and_then(|idx| remaining_after[idx].take()) {
+            Some(to) => moved.push(MovedHit {
+                snippet: hit.snippet.clone(),
+                from: DiffLocation {
+                    path: hit.path,
+                    line: hit.line,
+                },
+                to: DiffLocation {
+                    path: to.path,
+                    line: to.line,
+                },
+            }),
+            None => removed.push(hit),
+        }
+    }
+
+    DiffReport {
+        symbol,
+        before: before.label,
+        after: after.label,
+        added: remaining_after.into_iter().flatten().collect(),
+        removed,
+        moved,
+        unchanged,
+    }
+}
+
+#[derive(Deserialize)]
+struct SavedSummary {
+    symbol: String,
+    #[serde(default)]
+    top_hits: Vec<DiffHit>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffHit {
+    pub path: String,
+    pub line: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffLocation {
+    pub path: String,
+    pub line: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MovedHit {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+    pub from: DiffLocation,
+    pub to: DiffLocation,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffReport {
+    pub symbol: String,
+    pub before: String,
+    pub after: String,
+    pub added: Vec<DiffHit>,
+    pub removed: Vec<DiffHit>,
+    pub moved: Vec<MovedHit>,
+    pub unchanged: usize,
+}
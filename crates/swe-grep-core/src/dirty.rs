@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::tools::git::GitTool;
+
+/// Short enough that a slow or wedged git never holds up a search noticeably.
+const GIT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Uncommitted changes to the files holding a search's hits.
+#[derive(Debug, Default)]
+pub struct WorktreeChanges {
+    files: HashMap<PathBuf, FileChange>,
+}
+
+#[derive(Debug)]
+enum FileChange {
+    /// Not tracked at all, so every line is new.
+    Untracked,
+    Modified(Vec<Hunk>),
+}
+
+/// One `-U0` hunk: the HEAD lines it replaces and where its worktree lines start.
+#[derive(Debug, Default)]
+struct Hunk {
+    worktree_start: usize,
+    head: Vec<String>,
+    worktree: Vec<String>,
+}
+
+/// How a matched line compares with HEAD.
+#[derive(Debug, Default, PartialEq)]
+pub struct LineChange {
+    /// The file has uncommitted changes.
+    pub dirty: bool,
+    /// The line as committed, when it was edited rather than added.
+    pub head_line: Option<String>,
+    /// The line in the worktree, when it differs from HEAD.
+    pub worktree_line: Option<String>,
+}
+
+impl WorktreeChanges {
+    /// Look up which of `paths` (relative to `root`) have uncommitted changes, diffing the
+    /// modified ones against HEAD. Anything outside a git work tree, or a failing git, yields no
+    /// changes; the annotation is best effort.
+    pub async fn detect(root: &Path, paths: &[PathBuf]) -> Self {
+        let mut changes = Self::default();
+        if paths.is_empty() {
+            return changes;
+        }
+        let git = GitTool::new(GIT_TIMEOUT);
+        let (modified, untracked) = match git.uncommitted(root, paths).await {
+            Ok(found) => found,
+            Err(err) => {
+                tracing::debug!(error = %err, "skipping worktree change detection");
+                return changes;
+            }
+        };
+        for path in untracked {
+            changes.files.insert(path, FileChange::Untracked);
+        }
+        for path in modified {
+            let hunks = match git.diff_head(root, &path).await {
+                Ok(diff) => parse_hunks(&diff),
+                Err(err) => {
+                    tracing::debug!(
+                        path = %path.display(),
+                        error = %err,
+                        "failed to diff against HEAD"
+                    );
+                    Vec::new()
+                }
+            };
+            changes.files.insert(path, FileChange::Modified(hunks));
+        }
+        changes
+    }
+
+    /// How `line` (1-based) of `path` compares with HEAD.
+    pub fn line(&self, path: &Path, line: usize) -> LineChange {
+        match self.files.get(path) {
+            None => LineChange::default(),
+            Some(FileChange::Untracked) => LineChange {
+                dirty: true,
+                ..LineChange::default()
+            },
+            Some(FileChange::Modified(hunks)) => {
+                let mut change = LineChange {
+                    dirty: true,
+                    ..LineChange::default()
+                };
+                if let Some(hunk) = hunks.iter().find(|hunk| {
+                    line >= hunk.worktree_start && line < hunk.worktree_start + hunk.worktree.len()
+                }) {
+                    let offset = line - hunk.worktree_start;
+                    change.head_line = hunk.head.get(offset).cloned();
+                    change.worktree_line = hunk.worktree.get(offset).cloned();
+                }
+                change
+            }
+        }
+    }
+}
+
+/// Hunks of a single-file `git diff -U0`, pairing removed and added lines by position.
+fn parse_hunks(diff: &str) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+    let mut current: Option<Hunk> = None;
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("@@ ") {
+            hunks.extend(current.take());
+            current = parse_header(header).map(|worktree_start| Hunk {
+                worktree_start,
+                ..Hunk::default()
+            });
+        } else if let Some(hunk) = current.as_mut() {
+            if let Some(removed) = line.strip_prefix('-') {
+                hunk.head.push(removed.to_string());
+            } else if let Some(added) = line.strip_prefix('+') {
+                hunk.worktree.push(added.to_string());
+            }
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
+
+/// Worktree start line from the rest of a `@@ -a,b +c,d @@` header.
+fn parse_header(header: &str) -> Option<usize> {
+    let added = header
+        .split_whitespace()
+        .find(|part| part.starts_with('+'))?;
+    let start = added[1..].split(',').next()?;
+    start.parse().ok()
+}
@@ -0,0 +1,105 @@
+//! Doc comments of definition hits: the comment block right above the matched line, or the
+//! Python docstring right below it.
+
+/// Most lines kept from one comment; longer blocks, such as license headers, keep the lines
+/// nearest the definition.
+const MAX_DOC_COMMENT_LINES: usize = 40;
+/// Lines scanned past a Python `def` or `class` for the `:` ending its signature.
+const MAX_SIGNATURE_LINES: usize = 20;
+const DOCSTRING_QUOTES: &[&str] = &["\"\"\"", "'''"];
+
+/// The comment documenting the code on 1-based `line`, with its markers kept and common
+/// indentation removed: a run of `///`, `//`, or `#` line comments or a `/* */` block ending
+/// just above the line (attributes and decorators in between are skipped), or the docstring
+/// opening the body of a Python `def` or `class`.
+pub fn doc_comment<S: AsRef<str>>(lines: &[S], line: usize) -> Option<String> {
+    let index = line.checked_sub(1)?;
+    let target = lines.get(index)?.as_ref().trim();
+    if target.is_empty() || is_line_comment(target) || is_block_comment_line(target) {
+        return None;
+    }
+    let text = |at: usize| lines[at].as_ref();
+    if let Some(range) = docstring(lines, index) {
+        return Some(dedent(range.map(text)));
+    }
+
+    let mut end = index;
+    while end > 0 && is_attribute(text(end - 1).trim()) {
+        end -= 1;
+    }
+    let last = text(end.checked_sub(1)?).trim();
+    let mut start = end - 1;
+    if last.ends_with("*/") {
+        while !text(start).contains("/*") {
+            start = start.checked_sub(1)?;
+        }
+    } else if is_line_comment(last) {
+        while start > 0 && is_line_comment(text(start - 1).trim()) {
+            start -= 1;
+        }
+    } else {
+        return None;
+    }
+    let start = start.max(end.saturating_sub(MAX_DOC_COMMENT_LINES));
+    Some(dedent((start..end).map(text)))
+}
+
+/// `//`, `///`, and `#` comments; `#` needs a space or another `#` after it, so preprocessor
+/// directives, shebangs, and Rust attributes are not comments.
+fn is_line_comment(line: &str) -> bool {
+    line.starts_with("//")
+        || line == "#"
+        || line.starts_with("# ")
+        || (line.starts_with("##") && !line.starts_with("##["))
+}
+
+fn is_block_comment_line(line: &str) -> bool {
+    line.starts_with("/*") || line.starts_with("* ") || line == "*" || line.starts_with("*/")
+}
+
+/// Rust `#[...]` attributes, `@decorators` and Java/Swift annotations, and C# `[Attributes]`.
+fn is_attribute(line: &str) -> bool {
+    line.starts_with("#[")
+        || (line.starts_with('@') && line.len() > 1)
+        || (line.starts_with('[') && line.ends_with(']'))
+}
+
+/// Lines of the docstring opening the body of the Python `def` or `class` on line `index`.
+fn docstring<S: AsRef<str>>(lines: &[S], index: usize) -> Option<std::ops::Range<usize>> {
+    let target = lines[index].as_ref().trim();
+    let signature = target.strip_prefix("async ").unwrap_or(target);
+    if !(signature.starts_with("def ") || signature.starts_with("class ")) {
+        return None;
+    }
+    let colon = (index..lines.len().min(index + MAX_SIGNATURE_LINES))
+        .find(|&at| lines[at].as_ref().trim_end().ends_with(':'))?;
+    let start = (colon + 1..lines.len()).find(|&at| !lines[at].as_ref().trim().is_empty())?;
+    let opening = lines[start].as_ref().trim();
+    let opening = opening
+        .strip_prefix(['r', 'R', 'u', 'U'])
+        .unwrap_or(opening);
+    let quote = DOCSTRING_QUOTES
+        .iter()
+        .find(|quote| opening.starts_with(**quote))?;
+    if opening[quote.len()..].contains(quote) {
+        return Some(start..start + 1);
+    }
+    let end = (start + 1..lines.len()).find(|&at| lines[at].as_ref().contains(quote))?;
+    Some(start..(end + 1).min(start + MAX_DOC_COMMENT_LINES))
+}
+
+/// `lines` joined with the indentation they share removed.
+fn dedent<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let lines: Vec<&str> = lines.map(str::trim_end).collect();
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,316 @@
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::plan::Strategy;
+use crate::search::{SearchSummary, StageStats, TopHit};
+
+/// Top-level `SearchSummary` keys accepted by `--fields`.
+pub const SUMMARY_FIELDS: &[&str] = &[
+    "cycle",
+    "symbol",
+    "strategy",
+    "revision",
+    "search_id",
+    "queries",
+    "matched_identifiers",
+    "aliases",
+    "top_hits",
+    "deduped",
+    "clusters",
+    "next_actions",
+    "suggestions",
+    "fd_candidates",
+    "ast_hits",
+    "startup_stats",
+    "stage_stats",
+    "reward",
+    "warnings",
+    "budget_exceeded_stages",
+    "skipped",
+    "repo_stats",
+];
+
+/// Per-hit keys accepted by `--fields`; naming any of them keeps `top_hits`.
+pub const HIT_FIELDS: &[&str] = &[
+    "path",
+    "line",
+    "root",
+    "archive_path",
+    "inner_path",
+    "cell",
+    "owners",
+    "score",
+    "origin",
+    "origin_label",
+    "snippet",
+    "raw_snippet",
+    "snippet_length",
+    "raw_snippet_truncated",
+    "line_window",
+    "expanded_snippet",
+    "context_start",
+    "context_end",
+    "auto_expanded_context",
+    "body",
+    "body_retrieved",
+    "doc_comment",
+    "hints",
+    "snippet_highlights",
+    "expanded_snippet_highlights",
+    "dirty",
+    "head_line",
+    "worktree_line",
+    "link",
+    "permalink",
+    "barrel",
+    "targets",
+];
+
+/// Sparse fieldset requested by a caller; empty keeps the full summary.
+///
+/// Names may be summary keys (`stage_stats`) or hit keys (`path`). Hit keys trim every entry of
+/// `top_hits` to just those keys; naming `top_hits` itself keeps whole hits.
+#[derive(Clone, Debug, Default)]
+pub struct FieldSelection {
+    summary: Vec<&'static str>,
+    hit: Vec<&'static str>,
+}
+
+impl FieldSelection {
+    /// Parse field names; each entry may itself be a comma-separated list.
+    pub fn parse<S: AsRef<str>>(entries: &[S]) -> Result<Self> {
+        let mut selection = Self::default();
+        for name in entries
+            .iter()
+            .flat_map(|entry| entry.as_ref().split(','))
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            if let Some(field) = SUMMARY_FIELDS.iter().find(|field| **field == name) {
+                push_unique(&mut selection.summary, field);
+            } else if let Some(field) = HIT_FIELDS.iter().find(|field| **field == name) {
+                push_unique(&mut selection.hit, field);
+            } else {
+                anyhow::bail!(
+                    "unknown field `{name}`; expected one of {} (summary) or {} (hits)",
+                    SUMMARY_FIELDS.join(", "),
+                    HIT_FIELDS.join(", ")
+                );
+            }
+        }
+        if !selection.hit.is_empty() {
+            push_unique(&mut selection.summary, &"top_hits");
+        }
+        Ok(selection)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.summary.is_empty()
+    }
+
+    /// Serialize `summary` keeping only the selected keys.
+    pub fn to_value(&self, summary: &SearchSummary) -> Result<Value> {
+        let mut value = serde_json::to_value(summary)?;
+        if self.is_empty() {
+            return Ok(value);
+        }
+        if let Value::Object(map) = &mut value {
+            map.retain(|key, _| self.summary.contains(&key.as_str()));
+            if !self.hit.is_empty()
+                && let Some(Value::Array(hits)) = map.get_mut("top_hits")
+            {
+                for hit in hits {
+                    if let Value::Object(hit) = hit {
+                        hit.retain(|key, _| self.hit.contains(&key.as_str()));
+                    }
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    /// Reset unselected fields to their defaults, for transports (protobuf) that cannot omit keys.
+    pub fn prune(&self, summary: &mut SearchSummary) {
+        if self.is_empty() {
+            return;
+        }
+        let keep = |field: &str| self.summary.contains(&field);
+        if !keep("cycle") {
+            summary.cycle = 0;
+        }
+        if !keep("symbol") {
+            summary.symbol.clear();
+        }
+        if !keep("strategy") {
+            summary.strategy = Strategy::default();
+        }
+        if !keep("revision") {
+            summary.revision = None;
+        }
+        if !keep("search_id") {
+            summary.search_id = None;
+        }
+        if !keep("queries") {
+            summary.queries.clear();
+        }
+        if !keep("matched_identifiers") {
+            summary.matched_identifiers.clear();
+        }
+        if !keep("aliases") {
+            summary.aliases.clear();
+        }
+        if !keep("top_hits") {
+            summary.top_hits.clear();
+        }
+        if !keep("deduped") {
+            summary.deduped = 0;
+        }
+        if !keep("clusters") {
+            summary.clusters.clear();
+        }
+        if !keep("next_actions") {
+            summary.next_actions.clear();
+        }
+        if !keep("suggestions") {
+            summary.suggestions = None;
+        }
+        if !keep("fd_candidates") {
+            summary.fd_candidates.clear();
+        }
+        if !keep("ast_hits") {
+            summary.ast_hits.clear();
+        }
+        if !keep("startup_stats") {
+            summary.startup_stats = None;
+        }
+        if !keep("stage_stats") {
+            summary.stage_stats = StageStats::default();
+        }
+        if !keep("reward") {
+            summary.reward = 0.0;
+        }
+        if !keep("warnings") {
+            summary.warnings.clear();
+        }
+        if !keep("budget_exceeded_stages") {
+            summary.budget_exceeded_stages.clear();
+        }
+        if !keep("skipped") {
+            summary.skipped.clear();
+        }
+        if !keep("repo_stats") {
+            summary.repo_stats = None;
+        }
+        if !self.hit.is_empty() {
+            for hit in &mut summary.top_hits {
+                self.prune_hit(hit);
+            }
+        }
+    }
+
+    fn prune_hit(&self, hit: &mut TopHit) {
+        let keep = |field: &str| self.hit.contains(&field);
+        if !keep("path") {
+            hit.path.clear();
+        }
+        if !keep("line") {
+            hit.line = 0;
+        }
+        if !keep("root") {
+            hit.root = None;
+        }
+        if !keep("archive_path") {
+            hit.archive_path = None;
+        }
+        if !keep("inner_path") {
+            hit.inner_path = None;
+        }
+        if !keep("cell") {
+            hit.cell = None;
+        }
+        if !keep("owners") {
+            hit.owners.clear();
+        }
+        if !keep("score") {
+            hit.score = 0.0;
+        }
+        if !keep("origin") {
+            hit.origin.clear();
+        }
+        if !keep("origin_label") {
+            hit.origin_label.clear();
+        }
+        if !keep("snippet") {
+            hit.snippet = None;
+        }
+        if !keep("raw_snippet") {
+            hit.raw_snippet = None;
+        }
+        if !keep("snippet_length") {
+            hit.snippet_length = None;
+        }
+        if !keep("raw_snippet_truncated") {
+            hit.raw_snippet_truncated = false;
+        }
+        if !keep("line_window") {
+            hit.line_window = None;
+        }
+        if !keep("expanded_snippet") {
+            hit.expanded_snippet = None;
+        }
+        if !keep("context_start") {
+            hit.context_start = None;
+        }
+        if !keep("context_end") {
+            hit.context_end = None;
+        }
+        if !keep("auto_expanded_context") {
+            hit.auto_expanded_context = false;
+        }
+        if !keep("body") {
+            hit.body = None;
+        }
+        if !keep("body_retrieved") {
+            hit.body_retrieved = false;
+        }
+        if !keep("doc_comment") {
+            hit.doc_comment = None;
+        }
+        if !keep("hints") {
+            hit.hints.clear();
+        }
+        if !keep("snippet_highlights") {
+            hit.snippet_highlights.clear();
+        }
+        if !keep("expanded_snippet_highlights") {
+            hit.expanded_snippet_highlights.clear();
+        }
+        if !keep("dirty") {
+            hit.dirty = false;
+        }
+        if !keep("head_line") {
+            hit.head_line = None;
+        }
+        if !keep("worktree_line") {
+            hit.worktree_line = None;
+        }
+        if !keep("link") {
+            hit.link = None;
+        }
+        if !keep("permalink") {
+            hit.permalink = None;
+        }
+        if !keep("barrel") {
+            hit.barrel = false;
+        }
+        if !keep("targets") {
+            hit.targets.clear();
+        }
+    }
+}
+
+fn push_unique(fields: &mut Vec<&'static str>, field: &&'static str) {
+    if !fields.contains(field) {
+        fields.push(field);
+    }
+}
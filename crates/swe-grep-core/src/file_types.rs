@@ -0,0 +1,98 @@
+use std::path::Path;
+
+use anyhow::{Result, anyhow};
+use ignore::types::{Types, TypesBuilder};
+
+/// The ripgrep file types a search is limited to, from `--rg-type` or derived from
+/// `--language`. ripgrep applies them while walking directories; discovery candidates, which rg
+/// would search regardless because they are passed explicitly, are checked against the same
+/// definitions here.
+#[derive(Clone, Debug)]
+pub struct FileTypes {
+    selected: Vec<String>,
+    definitions: Vec<String>,
+    matcher: Types,
+}
+
+impl FileTypes {
+    /// Select `selected` on top of ripgrep's built-in types plus `definitions` (`name:glob` or
+    /// `name:include:other,...`, as for `rg --type-add`); `None` when nothing is selected.
+    pub fn new(selected: &[String], definitions: &[String]) -> Result<Option<Self>> {
+        let mut builder = TypesBuilder::new();
+        builder.add_defaults();
+        for definition in definitions {
+            builder
+                .add_def(definition)
+                .map_err(|err| anyhow!("invalid type definition `{definition}`: {err}"))?;
+        }
+        if selected.is_empty() {
+            return Ok(None);
+        }
+        for name in selected {
+            builder.select(name);
+        }
+        let matcher = builder.build().map_err(|err| anyhow!("{err}"))?;
+        Ok(Some(Self {
+            selected: selected.to_vec(),
+            definitions: definitions.to_vec(),
+            matcher,
+        }))
+    }
+
+    /// Types covering every `--language` token, extended where swe-grep's own extension list
+    /// is wider (`*.gql`); `None` when a token has no ripgrep type.
+    pub fn for_languages(tokens: &[String]) -> Option<Self> {
+        let has = |token: &str| tokens.iter().any(|candidate| candidate == token);
+        let mut selected: Vec<String> = Vec::new();
+        let mut definitions: Vec<String> = Vec::new();
+        for token in tokens {
+            let types: &[&str] = match token.as_str() {
+                "rust" => &["rust"],
+                "swift" => &["swift"],
+                "ts" => &["ts"],
+                "js" => &["js"],
+                "kt" => &["kotlin"],
+                "py" => &["py", "jupyter"],
+                "proto" => &["protobuf"],
+                "graphql" => {
+                    definitions.push("graphql:*.gql".to_string());
+                    &["graphql"]
+                }
+                "openapi" => &["yaml", "json"],
+                "objc" => &["objc", "objcpp"],
+                "hcl" => {
+                    for glob in ["*.tf", "*.tfvars", "*.hcl"] {
+                        definitions.push(format!("hcl:{glob}"));
+                    }
+                    &["hcl"]
+                }
+                "yaml" => &["yaml"],
+                // Only covered by a wider type, which must have been asked for as well.
+                "tsx" if has("ts") => &[],
+                "jsx" if has("js") => &[],
+                "kts" if has("kt") => &[],
+                _ => return None,
+            };
+            for name in types {
+                if !selected.iter().any(|existing| existing == name) {
+                    selected.push(name.to_string());
+                }
+            }
+        }
+        Self::new(&selected, &definitions).ok().flatten()
+    }
+
+    /// Arguments limiting a ripgrep run to these types.
+    pub fn rg_args(&self) -> Vec<String> {
+        self.definitions
+            .iter()
+            .map(|definition| format!("--type-add={definition}"))
+            .chain(self.selected.iter().map(|name| format!("--type={name}")))
+            .collect()
+    }
+
+    /// Whether ripgrep would search `path` when walking a directory.
+    pub fn matches(&self, path: &Path) -> bool {
+        !self.matcher.matched(path, false).is_ignore()
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Identity of the repository a search runs in, used to partition shared cache directories.
+///
+/// Read straight from the `.git` directory so no subprocess is spawned at startup. Directories
+/// outside a git checkout are identified by their own path.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoFingerprint {
+    /// Working tree top level (the search root itself outside git).
+    pub toplevel: PathBuf,
+    /// Search root relative to `toplevel`; empty when searching from the top level.
+    #[serde(default, skip_serializing_if = "is_empty_path")]
+    pub prefix: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub origin: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+}
+
+impl RepoFingerprint {
+    /// Fingerprint the repository containing the (canonical) search `root`.
+    pub fn detect(root: &Path) -> Self {
+        let Some((toplevel, git_dir)) = find_git_dir(root) else {
+            return Self {
+                toplevel: root.to_path_buf(),
+                prefix: PathBuf::new(),
+                origin: None,
+                head: None,
+            };
+        };
+        let common_dir = common_dir(&git_dir);
+        Self {
+            prefix: root
+                .strip_prefix(&toplevel)
+                .map(Path::to_path_buf)
+                .unwrap_or_default(),
+            origin: origin_url(&common_dir),
+            head: resolve_head(&git_dir, &common_dir),
+            toplevel,
+        }
+    }
+
+    /// Stable cache partition key: a hash of the top level, search prefix, and origin URL.
+    ///
+    /// HEAD is recorded but deliberately left out so hints survive commits and branch switches;
+    /// clones of the same remote in different directories still get separate partitions.
+    pub fn partition(&self) -> String {
+        let mut hash = Fnv64::default();
+        hash.write(self.toplevel.to_string_lossy().as_bytes());
+        hash.write(&[0]);
+        hash.write(self.prefix.to_string_lossy().as_bytes());
+        hash.write(&[0]);
+        hash.write(self.origin.as_deref().unwrap_or_default().as_bytes());
+        format!("{:016x}", hash.finish())
+    }
+
+    /// Key for state shared between machines: a hash of the origin URL, search prefix, and
+    /// HEAD, so every checkout of the same commit maps to it wherever it lives. `None` without
+    /// an origin or a resolvable HEAD.
+    pub fn shared_key(&self) -> Option<String> {
+        let origin = self.origin.as_deref()?;
+        let head = self.head.as_deref()?;
+        let mut hash = Fnv64::default();
+        hash.write(origin.as_bytes());
+        hash.write(&[0]);
+        hash.write(self.prefix.to_string_lossy().as_bytes());
+        hash.write(&[0]);
+        hash.write(head.as_bytes());
+        Some(format!("{:016x}", hash.finish()))
+    }
+
+    /// Directory under `cache_dir` holding this repository's persistent state.
+    pub fn state_dir(&self, cache_dir: &Path) -> PathBuf {
+        cache_dir.join("repos").join(self.partition())
+    }
+}
+
+fn is_empty_path(path: &Path) -> bool {
+    path.as_os_str().is_empty()
+}
+
+/// Nearest ancestor of `root` with a `.git` entry, following `gitdir:` files for worktrees.
+fn find_git_dir(root: &Path) -> Option<(PathBuf, PathBuf)> {
+    for dir in root.ancestors() {
+        let candidate = dir.join(".git");
+        if candidate.is_dir() {
+            return Some((dir.to_path_buf(), candidate));
+        }
+        if candidate.is_file() {
+            let contents = fs::read_to_string(&candidate).ok()?;
+            let target = contents.trim().strip_prefix("gitdir:")?.trim();
+            return Some((dir.to_path_buf(), dir.join(target)));
+        }
+    }
+    None
+}
+
+/// Shared git directory of a worktree (the git dir itself for ordinary checkouts).
+fn common_dir(git_dir: &Path) -> PathBuf {
+    match fs::read_to_string(git_dir.join("commondir")) {
+        Ok(contents) => git_dir.join(contents.trim()),
+        Err(_) => git_dir.to_path_buf(),
+    }
+}
+
+fn origin_url(common_dir: &Path) -> Option<String> {
+    let config = fs::read_to_string(common_dir.join("config")).ok()?;
+    let mut in_origin = false;
+    for line in config.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_origin = line.replace(' ', "") == "[remote\"origin\"]";
+            continue;
+        }
+        if in_origin
+            && let Some((key, value)) = line.split_once('=')
+            && key.trim() == "url"
+        {
+            return Some(value.trim().to_string());
+        }
+    }
+    None
+}
+
+fn resolve_head(git_dir: &Path, common_dir: &Path) -> Option<String> {
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let Some(reference) = head.strip_prefix("ref:").map(str::trim) else {
+        return Some(head.to_string());
+    };
+    if let Ok(commit) = fs::read_to_string(common_dir.join(reference)) {
+        return Some(commit.trim().to_string());
+    }
+    let packed = fs::read_to_string(common_dir.join("packed-refs")).ok()?;
+    packed.lines().find_map(|line| {
+        let (commit, name) = line.split_once(' ')?;
+        (name == reference).then(|| commit.to_string())
+    })
+}
+
+/// FNV-1a; unlike `DefaultHasher` its output is stable across Rust releases.
+struct Fnv64(u64);
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv64 {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
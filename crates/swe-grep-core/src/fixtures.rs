@@ -0,0 +1,394 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Serialize;
+use tokio::fs;
+use ulid::Ulid;
+
+use crate::cli::{
+    FixtureLanguage, FixtureSize, FixturesArgs, FixturesCommand, FixturesGenerateArgs,
+};
+use crate::search;
+
+/// Outcome of `swe-grep fixtures generate`.
+#[derive(Debug, Serialize)]
+pub struct FixtureReport {
+    pub root: String,
+    pub languages: Vec<String>,
+    pub size: String,
+    /// Modules per language.
+    pub modules: usize,
+    pub files_per_module: usize,
+    pub functions_per_file: usize,
+    /// Files written, including package manifests.
+    pub files: usize,
+    pub lines: usize,
+    /// Symbols with known answers: `resolve_session`, defined once per language and called
+    /// from every file; `login_user`, defined in every module; and the first uniquely named
+    /// handler of each language.
+    pub symbols: Vec<FixtureSymbol>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FixtureSymbol {
+    /// Name to search for; TypeScript and Swift sources spell shared symbols in camelCase.
+    pub symbol: String,
+    pub definitions: Vec<FixtureLocation>,
+    /// Lines other than definitions that mention the symbol.
+    pub references: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FixtureLocation {
+    /// Path relative to the fixture root, with `/` separators.
+    pub path: String,
+    pub line: usize,
+}
+
+/// Entry point for `swe-grep fixtures`.
+pub async fn run(args: FixturesArgs) -> Result<FixtureReport> {
+    match args.command {
+        FixturesCommand::Generate(args) => generate(args).await,
+    }
+}
+
+async fn generate(args: FixturesGenerateArgs) -> Result<FixtureReport> {
+    let (modules, files_per_module, functions_per_file) = match args.size {
+        FixtureSize::Small => (2, 3, 4),
+        FixtureSize::Medium => (8, 8, 8),
+        FixtureSize::Large => (32, 16, 12),
+    };
+    let shape = Shape {
+        modules: args.modules.unwrap_or(modules),
+        files_per_module: args.files_per_module.unwrap_or(files_per_module),
+        functions_per_file,
+    };
+    if shape.modules == 0 || shape.files_per_module == 0 {
+        anyhow::bail!("--modules and --files-per-module must be at least 1");
+    }
+    let mut languages: Vec<FixtureLanguage> = Vec::new();
+    for language in args.languages {
+        if !languages.contains(&language) {
+            languages.push(language);
+        }
+    }
+    if languages.is_empty() {
+        anyhow::bail!("--languages must name at least one language");
+    }
+
+    let root = args.output.unwrap_or_else(|| {
+        std::env::temp_dir().join(format!(
+            "swe-grep-fixture-{}",
+            Ulid::new().to_string().to_lowercase()
+        ))
+    });
+    if let Ok(mut entries) = fs::read_dir(&root).await
+        && entries.next_entry().await?.is_some()
+    {
+        anyhow::bail!("fixture output {} is not empty", root.display());
+    }
+
+    let mut fixture = Fixture::default();
+    for language in &languages {
+        match language {
+            FixtureLanguage::Rust => rust(&mut fixture, &shape),
+            FixtureLanguage::Ts => typescript(&mut fixture, &shape),
+            FixtureLanguage::Swift => swift(&mut fixture, &shape),
+        }
+    }
+
+    let mut lines = 0;
+    for source in &fixture.sources {
+        let path = root.join(&source.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, &source.text)
+            .await
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        lines += source.lines;
+    }
+    let root = search::canonicalize_path(&root)
+        .with_context(|| format!("failed to canonicalize fixture root {}", root.display()))?;
+
+    Ok(FixtureReport {
+        root: root.display().to_string(),
+        languages: languages.into_iter().filter_map(value_name).collect(),
+        size: value_name(args.size).unwrap_or_default(),
+        modules: shape.modules,
+        files_per_module: shape.files_per_module,
+        functions_per_file: shape.functions_per_file,
+        files: fixture.sources.len(),
+        lines,
+        symbols: fixture.symbols,
+    })
+}
+
+fn value_name<T: ValueEnum>(value: T) -> Option<String> {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+}
+
+struct Shape {
+    modules: usize,
+    files_per_module: usize,
+    functions_per_file: usize,
+}
+
+/// Sources to write and the definitions and references recorded while building them.
+#[derive(Default)]
+struct Fixture {
+    sources: Vec<Source>,
+    symbols: Vec<FixtureSymbol>,
+}
+
+impl Fixture {
+    fn symbol(&mut self, name: &str) -> &mut FixtureSymbol {
+        let index = match self.symbols.iter().position(|symbol| symbol.symbol == name) {
+            Some(index) => index,
+            None => {
+                self.symbols.push(FixtureSymbol {
+                    symbol: name.to_string(),
+                    definitions: Vec::new(),
+                    references: 0,
+                });
+                self.symbols.len() - 1
+            }
+        };
+        &mut self.symbols[index]
+    }
+
+    /// Record that the next line written to `source` defines `name`.
+    fn define(&mut self, name: &str, source: &Source) {
+        let location = FixtureLocation {
+            path: source.path.clone(),
+            line: source.lines + 1,
+        };
+        self.symbol(name).definitions.push(location);
+    }
+
+    fn reference(&mut self, name: &str) {
+        self.symbol(name).references += 1;
+    }
+}
+
+struct Source {
+    path: String,
+    text: String,
+    lines: usize,
+}
+
+impl Source {
+    fn new(path: String) -> Self {
+        Self {
+            path,
+            text: String::new(),
+            lines: 0,
+        }
+    }
+
+    fn line(&mut self, line: &str) {
+        self.text.push_str(line);
+        self.text.push('\n');
+        self.lines += 1;
+    }
+}
+
+fn rust(fixture: &mut Fixture, shape: &Shape) {
+    let mut manifest = Source::new("rust/Cargo.toml".to_string());
+    manifest.line("[package]");
+    manifest.line("name = \"fixture-rust\"");
+    manifest.line("version = \"0.1.0\"");
+    manifest.line("edition = \"2021\"");
+    fixture.sources.push(manifest);
+
+    let mut lib = Source::new("rust/src/lib.rs".to_string());
+    lib.line("pub mod core;");
+    for module in 0..shape.modules {
+        lib.line(&format!("pub mod module_{module};"));
+    }
+    fixture.sources.push(lib);
+
+    let mut core = Source::new("rust/src/core.rs".to_string());
+    core.line("/// Look up the session id for a token.");
+    fixture.define("resolve_session", &core);
+    core.line("pub fn resolve_session(token: &str) -> Option<u64> {");
+    core.line("    token.strip_prefix(\"session-\")?.parse().ok()");
+    core.line("}");
+    fixture.sources.push(core);
+
+    for module in 0..shape.modules {
+        let mut module_root = Source::new(format!("rust/src/module_{module}/mod.rs"));
+        for file in 0..shape.files_per_module {
+            module_root.line(&format!("pub mod file_{file};"));
+        }
+        fixture.sources.push(module_root);
+
+        for file in 0..shape.files_per_module {
+            let mut source = Source::new(format!("rust/src/module_{module}/file_{file}.rs"));
+            source.line("use crate::core::resolve_session;");
+            fixture.reference("resolve_session");
+            if file == 0 {
+                source.line("");
+                source.line(&format!("/// Sign in through module {module}."));
+                fixture.define("login_user", &source);
+                source.line("pub fn login_user(name: &str) -> Option<u64> {");
+                source.line("    resolve_session(&format!(\"session-{}\", name.len()))");
+                fixture.reference("resolve_session");
+                source.line("}");
+            }
+            for function in 0..shape.functions_per_file {
+                let name = format!("rs_handler_{module}_{file}_{function}");
+                source.line("");
+                if (module, file, function) == (0, 0, 0) {
+                    fixture.define(&name, &source);
+                }
+                source.line(&format!("pub fn {name}(input: &str) -> usize {{"));
+                source.line("    let session = resolve_session(input).unwrap_or_default();");
+                fixture.reference("resolve_session");
+                if file > 0 && function == 0 {
+                    source.line(
+                        "    let user = super::file_0::login_user(input).unwrap_or_default();",
+                    );
+                    fixture.reference("login_user");
+                    source.line(&format!(
+                        "    input.len() + (session + user) as usize + {function}"
+                    ));
+                } else {
+                    source.line(&format!("    input.len() + session as usize + {function}"));
+                }
+                source.line("}");
+            }
+            fixture.sources.push(source);
+        }
+    }
+}
+
+fn typescript(fixture: &mut Fixture, shape: &Shape) {
+    let mut manifest = Source::new("ts/package.json".to_string());
+    manifest.line("{");
+    manifest.line("  \"name\": \"fixture-ts\",");
+    manifest.line("  \"version\": \"0.1.0\",");
+    manifest.line("  \"private\": true");
+    manifest.line("}");
+    fixture.sources.push(manifest);
+
+    let mut core = Source::new("ts/src/core.ts".to_string());
+    core.line("/** Look up the session id for a token. */");
+    fixture.define("resolve_session", &core);
+    core.line("export function resolveSession(token: string): number | undefined {");
+    core.line("  const id = Number(token.replace(/^session-/, \"\"));");
+    core.line("  return Number.isNaN(id) ? undefined : id;");
+    core.line("}");
+    fixture.sources.push(core);
+
+    for module in 0..shape.modules {
+        for file in 0..shape.files_per_module {
+            let mut source = Source::new(format!("ts/src/module_{module}/file_{file}.ts"));
+            source.line("import { resolveSession } from \"../core\";");
+            fixture.reference("resolve_session");
+            if file == 0 {
+                source.line("");
+                source.line(&format!("/** Sign in through module {module}. */"));
+                fixture.define("login_user", &source);
+                source.line("export function loginUser(name: string): number | undefined {");
+                source.line("  return resolveSession(`session-${name.length}`);");
+                fixture.reference("resolve_session");
+                source.line("}");
+            } else {
+                source.line("import { loginUser } from \"./file_0\";");
+                fixture.reference("login_user");
+            }
+            for function in 0..shape.functions_per_file {
+                let name = format!("ts_handler_{module}_{file}_{function}");
+                source.line("");
+                if (module, file, function) == (0, 0, 0) {
+                    fixture.define(&name, &source);
+                }
+                source.line(&format!("export function {name}(input: string): number {{"));
+                source.line("  const session = resolveSession(input) ?? 0;");
+                fixture.reference("resolve_session");
+                if file > 0 && function == 0 {
+                    source.line("  const user = loginUser(input) ?? 0;");
+                    fixture.reference("login_user");
+                    source.line(&format!(
+                        "  return input.length + session + user + {function};"
+                    ));
+                } else {
+                    source.line(&format!("  return input.length + session + {function};"));
+                }
+                source.line("}");
+            }
+            fixture.sources.push(source);
+        }
+    }
+}
+
+fn swift(fixture: &mut Fixture, shape: &Shape) {
+    let mut manifest = Source::new("swift/Package.swift".to_string());
+    manifest.line("// swift-tools-version:5.9");
+    manifest.line("import PackageDescription");
+    manifest.line("");
+    manifest.line("let package = Package(");
+    manifest.line("    name: \"Fixture\",");
+    manifest.line("    targets: [.target(name: \"Fixture\")]");
+    manifest.line(")");
+    fixture.sources.push(manifest);
+
+    let mut core = Source::new("swift/Sources/Fixture/Core.swift".to_string());
+    core.line("import Foundation");
+    core.line("");
+    core.line("/// Look up the session id for a token.");
+    fixture.define("resolve_session", &core);
+    core.line("func resolveSession(token: String) -> Int? {");
+    core.line("    Int(token.replacingOccurrences(of: \"session-\", with: \"\"))");
+    core.line("}");
+    fixture.sources.push(core);
+
+    for module in 0..shape.modules {
+        for file in 0..shape.files_per_module {
+            let mut source = Source::new(format!(
+                "swift/Sources/Fixture/Module{module}/File{file}.swift"
+            ));
+            source.line(&format!("enum Module{module}File{file} {{"));
+            if file == 0 {
+                source.line(&format!("    /// Sign in through module {module}."));
+                fixture.define("login_user", &source);
+                source.line("    static func loginUser(name: String) -> Int? {");
+                source.line("        resolveSession(token: \"session-\\(name.count)\")");
+                fixture.reference("resolve_session");
+                source.line("    }");
+            }
+            for function in 0..shape.functions_per_file {
+                let name = format!("swift_handler_{module}_{file}_{function}");
+                if file == 0 || function > 0 {
+                    source.line("");
+                }
+                if (module, file, function) == (0, 0, 0) {
+                    fixture.define(&name, &source);
+                }
+                source.line(&format!("    static func {name}(input: String) -> Int {{"));
+                source.line("        let session = resolveSession(token: input) ?? 0");
+                fixture.reference("resolve_session");
+                if file > 0 && function == 0 {
+                    source.line(&format!(
+                        "        let user = Module{module}File0.loginUser(name: input) ?? 0"
+                    ));
+                    fixture.reference("login_user");
+                    source.line(&format!(
+                        "        return input.count + session + user + {function}"
+                    ));
+                } else {
+                    source.line(&format!(
+                        "        return input.count + session + {function}"
+                    ));
+                }
+                source.line("    }");
+            }
+            source.line("}");
+            fixture.sources.push(source);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{CaseMode, FlagArgs};
+use crate::config::RepoConfig;
+use crate::search;
+use crate::tools::backend;
+use crate::tools::rg::{RipgrepMatch, RipgrepTool};
+
+/// Extensions of prose files, whose mentions are documentation.
+const DOC_EXTENSIONS: &[&str] = &["md", "mdx", "markdown", "rst", "txt", "adoc"];
+/// Extensions of configuration files, where a flag is defined as a key.
+const CONFIG_EXTENSIONS: &[&str] = &[
+    "yaml",
+    "yml",
+    "toml",
+    "json",
+    "ini",
+    "cfg",
+    "conf",
+    "properties",
+    "tf",
+    "tfvars",
+    "hcl",
+];
+/// Extensions of shell scripts, where `NAME=value` sets the variable for later commands.
+const SHELL_EXTENSIONS: &[&str] = &["sh", "bash", "zsh", "fish"];
+/// Keywords that declare the name assigned after them.
+const DECLARATION_KEYWORDS: &[&str] = &[
+    "const", "let", "var", "val", "static", "final", "readonly", "export", "#define",
+];
+/// Calls that set an environment variable or flag named by their first argument.
+const SETTERS: &[&str] = &[
+    "set_var(",
+    "setenv(",
+    "Setenv(",
+    "putenv(",
+    "setdefault(",
+    "set(",
+    "override(",
+];
+
+/// Where a flag appears and what each occurrence does with it, definitions first.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FlagReport {
+    pub name: String,
+    pub hits: Vec<FlagHit>,
+    /// Number of hits of each kind.
+    pub counts: BTreeMap<FlagUsage, usize>,
+    /// The probe stopped at `--max-matches`, so some occurrences are missing.
+    pub truncated: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FlagHit {
+    /// The file, relative to the repository root.
+    pub path: String,
+    pub line: usize,
+    pub kind: FlagUsage,
+    pub snippet: String,
+}
+
+/// What an occurrence of a flag does, in report order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagUsage {
+    /// Declares the flag or gives it a default: `.env` entries, config keys, Dockerfile `ENV`,
+    /// constants.
+    Definition,
+    /// Sets it at run time: shell assignments, `os.environ[...] = `, `set_var(...)`.
+    Write,
+    /// Any other use in code or config.
+    Read,
+    /// Comments and prose.
+    Documentation,
+}
+
+/// Probe `--path` for the flag `--name` and classify every occurrence.
+pub async fn run(args: FlagArgs) -> Result<FlagReport> {
+    let name = args.name.trim().to_string();
+    if name.is_empty() {
+        anyhow::bail!("--name must not be empty");
+    }
+    let root = args
+        .path
+        .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
+    let root = search::canonicalize_path(&root).with_context(|| {
+        format!(
+            "failed to canonicalize repository root path: {}",
+            root.display()
+        )
+    })?;
+    let tools = RepoConfig::load(&root)?.tools.resolve(&root, &args.tools)?;
+    // `.env` files are hidden, and flags live in them more often than anywhere else.
+    let mut settings = tools.rg.clone();
+    settings
+        .extra_args
+        .extend(["--hidden", "--glob", "!.git/"].map(str::to_string));
+    let tool = RipgrepTool::new(
+        Duration::from_secs(args.timeout_secs),
+        args.max_matches,
+        0,
+        0,
+        search::DEFAULT_MAX_COLUMNS,
+        8,
+    )
+    .with_max_count(None)
+    .with_case(CaseMode::Sensitive)
+    .with_settings(settings)
+    .with_retry(tools.retry_policy())
+    .with_backend(tools.backend(backend::process()));
+
+    let query = search::whole_word_literal(&name);
+    let matches = tool
+        .search_union(&root, &[query], &[])
+        .await
+        .context("ripgrep failed")?;
+    Ok(report(&root, name, &matches, args.max_matches))
+}
+
+fn report(root: &Path, name: String, matches: &[RipgrepMatch], max_matches: usize) -> FlagReport {
+    let mut hits: Vec<FlagHit> = matches
+        .iter()
+        .map(|found| {
+            let path = found
+                .path
+                .strip_prefix(root)
+                .or_else(|_| found.path.strip_prefix("."))
+                .unwrap_or(&found.path);
+            let snippet = found.lines.trim_end().to_string();
+            FlagHit {
+                path: path.to_string_lossy().replace('\\', "/"),
+                line: found.line_number,
+                kind: classify(path, &snippet, &name),
+                snippet,
+            }
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        a.kind
+            .cmp(&b.kind)
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    let mut counts = BTreeMap::new();
+    for hit in &hits {
+        *counts.entry(hit.kind).or_default() += 1;
+    }
+    FlagReport {
+        name,
+        truncated: matches.len() >= max_matches,
+        hits,
+        counts,
+    }
+}
+
+/// Classify the occurrence of `name` on `line` of `path` by the file it is in and the text
+/// around its first mention.
+fn classify(path: &Path, line: &str, name: &str) -> FlagUsage {
+    let line = line.trim();
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    let in_docs = path
+        .components()
+        .next()
+        .is_some_and(|dir| matches!(dir.as_os_str().to_str(), Some("docs" | "doc")));
+    if DOC_EXTENSIONS.contains(&ext.as_str()) || in_docs || is_comment(line) {
+        return FlagUsage::Documentation;
+    }
+
+    let Some(start) = line.find(name) else {
+        return FlagUsage::Read;
+    };
+    let before = line[..start].trim_end();
+    let after = line[start + name.len()..]
+        .trim_start_matches(['"', '\'', '`', ']', ')'])
+        .trim_start();
+    let assigned = after.starts_with('=') && !after.starts_with("==");
+
+    if file_name == ".env" || file_name.starts_with(".env.") || ext == "env" {
+        return FlagUsage::Definition;
+    }
+    if file_name.starts_with("Dockerfile") || ext == "dockerfile" {
+        return match before {
+            "ENV" | "ARG" => FlagUsage::Definition,
+            _ => FlagUsage::Read,
+        };
+    }
+    if CONFIG_EXTENSIONS.contains(&ext.as_str()) {
+        let keyed = after.starts_with(':') || assigned;
+        let labelled = before.ends_with("name:")
+            || before.ends_with("name =")
+            || before.ends_with("variable \"");
+        return if keyed || labelled {
+            FlagUsage::Definition
+        } else {
+            FlagUsage::Read
+        };
+    }
+    if SHELL_EXTENSIONS.contains(&ext.as_str()) {
+        let assignment = after.starts_with('=') && matches!(before, "" | "export");
+        return if assignment {
+            FlagUsage::Write
+        } else {
+            FlagUsage::Read
+        };
+    }
+
+    let opener = before.trim_end_matches(['"', '\'', '`', '[']).trim_end();
+    if SETTERS.iter().any(|setter| opener.ends_with(setter)) {
+        return FlagUsage::Write;
+    }
+    let declared = before.is_empty()
+        || DECLARATION_KEYWORDS
+            .iter()
+            .any(|keyword| before.split_whitespace().last() == Some(keyword));
+    // `ENABLE_FOO: bool = ...` is typed; `ENABLE_FOO: true` is a key in an object literal.
+    let typed = after.starts_with(':') && !after.starts_with("::");
+    if declared && (assigned || typed || before == "#define") {
+        return FlagUsage::Definition;
+    }
+    if assigned {
+        return FlagUsage::Write;
+    }
+    if typed && before.ends_with([',', '{']) {
+        return FlagUsage::Definition;
+    }
+    FlagUsage::Read
+}
+
+/// Whether `line` (trimmed) is a comment in a common syntax; preprocessor directives and Rust
+/// attributes are not.
+fn is_comment(line: &str) -> bool {
+    if let Some(rest) = line.strip_prefix('#') {
+        return !(rest.starts_with('[')
+            || rest.starts_with('!')
+            || [
+                "define", "if", "ifdef", "ifndef", "elif", "undef", "include", "import",
+            ]
+            .iter()
+            .any(|directive| rest.starts_with(directive)));
+    }
+    ["//", "/*", "* ", "<!--", "-- "]
+        .iter()
+        .any(|marker| line.starts_with(marker))
+        || line == "*"
+}
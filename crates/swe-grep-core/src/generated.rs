@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use crate::identifier;
+
+/// Header lines inspected for generator markers and `source:` comments.
+const HEADER_LINES: usize = 30;
+/// graphql-codegen configuration files, in lookup order.
+const CODEGEN_MANIFESTS: &[&str] = &["codegen.yml", "codegen.yaml"];
+
+/// Maps generated files under one search root back to the schema they were generated from.
+///
+/// Three generators are recognised: protoc plugins, whose header names the `source:` proto;
+/// prost-build and tonic-build, whose `<package>.rs` output is matched to the proto declaring
+/// that package (protos are found in the tree and in string literals of `build.rs` files); and
+/// graphql-codegen, whose `codegen.yml` lists each output file and the schema behind it.
+/// Manifests and protos are only read once a generated file turns up.
+pub struct GeneratedSources {
+    root: PathBuf,
+    resolved: HashMap<PathBuf, Option<PathBuf>>,
+    protos: Option<Vec<ProtoFile>>,
+    codegen: Option<Vec<(PathBuf, PathBuf)>>,
+}
+
+struct ProtoFile {
+    /// Relative to the root when the proto lives inside it.
+    path: PathBuf,
+    package: Option<String>,
+}
+
+impl GeneratedSources {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            resolved: HashMap::new(),
+            protos: None,
+            codegen: None,
+        }
+    }
+
+    /// Schema file that `path` (relative to the root) was generated from, if it is generated.
+    pub fn source_for(&mut self, path: &Path) -> Option<PathBuf> {
+        if let Some(cached) = self.resolved.get(path) {
+            return cached.clone();
+        }
+        let source = self.resolve(path);
+        self.resolved.insert(path.to_path_buf(), source.clone());
+        source
+    }
+
+    fn resolve(&mut self, path: &Path) -> Option<PathBuf> {
+        if let Some((_, schema)) = self.codegen().iter().find(|(output, _)| output == path) {
+            return Some(schema.clone());
+        }
+        let header = read_header(&self.root.join(path))?;
+        if !header.iter().any(|line| is_generated_marker(line)) {
+            return None;
+        }
+        if let Some(source) = header.iter().find_map(|line| protoc_source(line)) {
+            return self.find_proto(Path::new(source));
+        }
+        let package = path.file_stem()?.to_str()?;
+        let is_rust = path.extension().is_some_and(|ext| ext == "rs");
+        if !is_rust {
+            return None;
+        }
+        self.protos()
+            .iter()
+            .find(|proto| proto.package.as_deref() == Some(package))
+            .map(|proto| proto.path.clone())
+    }
+
+    /// The proto a `source:` comment names; such paths are relative to an include directory,
+    /// so any known proto ending with them matches.
+    fn find_proto(&mut self, source: &Path) -> Option<PathBuf> {
+        if self.root.join(source).is_file() {
+            return Some(source.to_path_buf());
+        }
+        self.protos()
+            .iter()
+            .find(|proto| proto.path.ends_with(source))
+            .map(|proto| proto.path.clone())
+    }
+
+    fn protos(&mut self) -> &[ProtoFile] {
+        let root = &self.root;
+        self.protos.get_or_insert_with(|| {
+            let mut paths: Vec<PathBuf> = Vec::new();
+            let entries = WalkBuilder::new(root)
+                .standard_filters(true)
+                .build()
+                .flatten()
+                .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()));
+            for entry in entries {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "proto") {
+                    paths.push(path.to_path_buf());
+                } else if path.file_name().is_some_and(|name| name == "build.rs") {
+                    paths.extend(build_script_protos(path));
+                }
+            }
+            let mut protos: Vec<ProtoFile> = Vec::new();
+            for path in paths {
+                let Ok(canonical) = path.canonicalize() else {
+                    continue;
+                };
+                let relative = canonical
+                    .strip_prefix(root)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(canonical);
+                if protos.iter().any(|proto| proto.path == relative) {
+                    continue;
+                }
+                protos.push(ProtoFile {
+                    package: proto_package(&path),
+                    path: relative,
+                });
+            }
+            protos.sort_by(|a, b| a.path.cmp(&b.path));
+            protos
+        })
+    }
+
+    fn codegen(&mut self) -> &[(PathBuf, PathBuf)] {
+        let root = &self.root;
+        self.codegen.get_or_insert_with(|| {
+            CODEGEN_MANIFESTS
+                .iter()
+                .find_map(|name| fs::read_to_string(root.join(name)).ok())
+                .map(|raw| codegen_outputs(&raw))
+                .unwrap_or_default()
+        })
+    }
+}
+
+fn read_header(path: &Path) -> Option<Vec<String>> {
+    let file = fs::File::open(path).ok()?;
+    Some(
+        BufReader::new(file)
+            .lines()
+            .take(HEADER_LINES)
+            .map_while(Result::ok)
+            .collect(),
+    )
+}
+
+fn is_generated_marker(line: &str) -> bool {
+    line.contains("DO NOT EDIT")
+        || line.contains("@generated")
+        || line.contains("Generated by the protocol buffer compiler")
+}
+
+/// The proto named by a protoc header comment such as `// source: api/user.proto`.
+fn protoc_source(line: &str) -> Option<&str> {
+    let comment = line
+        .trim_start()
+        .trim_start_matches(['/', '#', '*', '-', ' '].as_slice());
+    let source = comment.strip_prefix("source:")?.trim();
+    source.ends_with(".proto").then_some(source)
+}
+
+fn proto_package(path: &Path) -> Option<String> {
+    let raw = fs::read_to_string(path).ok()?;
+    raw.lines().find_map(|line| {
+        let package = line.trim().strip_prefix("package ")?;
+        Some(package.trim_end_matches(';').trim().to_string())
+    })
+}
+
+/// `.proto` string literals in a build script (`compile_protos(&["proto/users.proto"], ..)`),
+/// resolved against the script's directory.
+fn build_script_protos(path: &Path) -> Vec<PathBuf> {
+    let Ok(raw) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    let base = path.parent().unwrap_or(Path::new(""));
+    raw.split('"')
+        .skip(1)
+        .step_by(2)
+        .filter(|literal| literal.ends_with(".proto"))
+        .map(|literal| base.join(literal))
+        .filter(|proto| proto.is_file())
+        .collect()
+}
+
+/// `(output, schema)` pairs from a graphql-codegen config: every key under `generates:` is an
+/// output file, and its own `schema:` (or the top-level one) names the schema.
+fn codegen_outputs(raw: &str) -> Vec<(PathBuf, PathBuf)> {
+    let mut top_schema: Option<String> = None;
+    let mut outputs: Vec<(String, Option<String>)> = Vec::new();
+    let mut in_generates = false;
+    let mut output_indent = None;
+    for line in raw.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        let Some((key, value)) = trimmed.split_once(':') else {
+            continue;
+        };
+        let key = unquote(key);
+        let value = unquote(value);
+        if indent == 0 {
+            in_generates = key == "generates";
+            if key == "schema" && !value.is_empty() {
+                top_schema = Some(value.to_string());
+            }
+            continue;
+        }
+        if !in_generates {
+            continue;
+        }
+        let output_indent = *output_indent.get_or_insert(indent);
+        if indent == output_indent {
+            outputs.push((key.to_string(), None));
+        } else if key == "schema"
+            && !value.is_empty()
+            && let Some((_, schema)) = outputs.last_mut()
+        {
+            *schema = Some(value.to_string());
+        }
+    }
+    outputs
+        .into_iter()
+        .filter_map(|(output, schema)| {
+            let schema = schema.or_else(|| top_schema.clone())?;
+            // Remote schemas (URLs) have no file to point at.
+            (!schema.contains("://")).then(|| (clean(&output), clean(&schema)))
+        })
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    value.trim().trim_matches(['"', '\''].as_slice())
+}
+
+fn clean(path: &str) -> PathBuf {
+    PathBuf::from(path.strip_prefix("./").unwrap_or(path))
+}
+
+/// 1-based line of `source` (relative to `root`) that declares `symbol`, comparing names
+/// without case or underscores since generated code renames (`GetUser` becomes `get_user`).
+pub fn definition_line(root: &Path, source: &Path, symbol: &str) -> usize {
+    let needle = fold(symbol);
+    if needle.is_empty() {
+        return 1;
+    }
+    fs::read_to_string(root.join(source))
+        .ok()
+        .and_then(|raw| {
+            raw.lines().position(|line| {
+                line.split(|c: char| !identifier::is_continue(c))
+                    .any(|word| fold(word) == needle)
+            })
+        })
+        .map_or(1, |index| index + 1)
+}
+
+fn fold(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::HeatmapArgs;
+use crate::config::RepoConfig;
+use crate::search;
+use crate::tools::backend;
+use crate::tools::rg::{RipgrepMatch, RipgrepTool};
+
+/// Size of the SVG treemap, in pixels.
+const SVG_WIDTH: f64 = 1200.0;
+const SVG_HEIGHT: f64 = 800.0;
+/// Height of the label strip above a directory's children.
+const SVG_HEADER: f64 = 16.0;
+/// Gap between a directory's frame and its children.
+const SVG_PADDING: f64 = 2.0;
+
+/// How often a symbol occurs under each directory and in each file of a repository.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HeatmapReport {
+    pub symbol: String,
+    /// Matching lines across the repository.
+    pub total: usize,
+    /// Files with at least one matching line.
+    pub files: usize,
+    /// The probe stopped at `--max-matches`, so counts are low.
+    pub truncated: bool,
+    /// The repository root, `.`, with every directory and file that holds a match.
+    pub tree: HeatmapNode,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HeatmapNode {
+    /// Last path component; `.` for the root.
+    pub name: String,
+    /// Path relative to the repository root, with `/` separators.
+    pub path: String,
+    pub kind: HeatmapNodeKind,
+    /// Matching lines in the file, or in every file below the directory.
+    pub count: usize,
+    /// Entries of a directory, most matches first and then by name.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<HeatmapNode>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HeatmapNodeKind {
+    Directory,
+    File,
+}
+
+/// Probe `--path` for every occurrence of `--symbol` and count them per file and directory.
+pub async fn run(args: HeatmapArgs) -> Result<HeatmapReport> {
+    let symbol = args.symbol.trim().to_string();
+    if symbol.is_empty() {
+        anyhow::bail!("--symbol must not be empty");
+    }
+    let root = args
+        .path
+        .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
+    let root = search::canonicalize_path(&root).with_context(|| {
+        format!(
+            "failed to canonicalize repository root path: {}",
+            root.display()
+        )
+    })?;
+    let tools = RepoConfig::load(&root)?.tools.resolve(&root, &args.tools)?;
+    let tool = RipgrepTool::new(
+        Duration::from_secs(args.timeout_secs),
+        args.max_matches,
+        0,
+        0,
+        search::DEFAULT_MAX_COLUMNS,
+        8,
+    )
+    .with_max_count(None)
+    .with_case(args.case)
+    .with_settings(tools.rg.clone())
+    .with_retry(tools.retry_policy())
+    .with_backend(tools.backend(backend::process()));
+
+    let query = search::whole_word_literal(&symbol);
+    let matches = tool
+        .search_union(&root, &[query], &[])
+        .await
+        .context("ripgrep failed")?;
+    Ok(report(&root, symbol, &matches, args.max_matches))
+}
+
+fn report(
+    root: &Path,
+    symbol: String,
+    matches: &[RipgrepMatch],
+    max_matches: usize,
+) -> HeatmapReport {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for found in matches {
+        let path = found
+            .path
+            .strip_prefix(root)
+            .or_else(|_| found.path.strip_prefix("."))
+            .unwrap_or(&found.path);
+        *counts
+            .entry(path.to_string_lossy().replace('\\', "/"))
+            .or_default() += 1;
+    }
+    let mut tree = Entry::default();
+    for (path, count) in &counts {
+        tree.insert(path.split('/').filter(|part| !part.is_empty()), *count);
+    }
+    HeatmapReport {
+        symbol,
+        total: tree.count,
+        files: counts.len(),
+        truncated: matches.len() >= max_matches,
+        tree: tree.into_node(".".to_string(), String::new()),
+    }
+}
+
+/// A directory or file while the tree is built, keyed by name so entries stay unique.
+#[derive(Default)]
+struct Entry {
+    count: usize,
+    children: BTreeMap<String, Entry>,
+}
+
+impl Entry {
+    fn insert<'a>(&mut self, mut parts: impl Iterator<Item = &'a str>, count: usize) {
+        self.count += count;
+        if let Some(part) = parts.next() {
+            self.children
+                .entry(part.to_string())
+                .or_default()
+                .insert(parts, count);
+        }
+    }
+
+    fn into_node(self, name: String, path: String) -> HeatmapNode {
+        let kind = if self.children.is_empty() && !path.is_empty() {
+            HeatmapNodeKind::File
+        } else {
+            HeatmapNodeKind::Directory
+        };
+        let mut children: Vec<HeatmapNode> = self
+            .children
+            .into_iter()
+            .map(|(child, entry)| {
+                let child_path = if path.is_empty() {
+                    child.clone()
+                } else {
+                    format!("{path}/{child}")
+                };
+                entry.into_node(child, child_path)
+            })
+            .collect();
+        // Stable, so entries with equal counts stay in name order.
+        children.sort_by_key(|child| std::cmp::Reverse(child.count));
+        HeatmapNode {
+            path: if path.is_empty() {
+                ".".to_string()
+            } else {
+                path
+            },
+            name,
+            kind,
+            count: self.count,
+            children,
+        }
+    }
+}
+
+/// Render the report as an SVG treemap: each file is a rectangle sized by its count and
+/// coloured from yellow (few matches) to red (the most), nested in labelled directory frames.
+pub fn svg(report: &HeatmapReport) -> String {
+    let hottest = hottest_file(&report.tree).max(1);
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{SVG_WIDTH}\" height=\"{SVG_HEIGHT}\" \
+         viewBox=\"0 0 {SVG_WIDTH} {SVG_HEIGHT}\" font-family=\"sans-serif\" font-size=\"11\">"
+    );
+    let _ = writeln!(
+        out,
+        "<title>{} occurrences of {} in {} files</title>",
+        report.total,
+        escape_xml(&report.symbol),
+        report.files
+    );
+    if report.total == 0 {
+        let _ = writeln!(
+            out,
+            "<text x=\"8\" y=\"20\">no matches for {}</text>",
+            escape_xml(&report.symbol)
+        );
+    } else {
+        let area = Rect {
+            x: 0.0,
+            y: 0.0,
+            width: SVG_WIDTH,
+            height: SVG_HEIGHT,
+        };
+        draw(&mut out, &report.tree, area, hottest, true);
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+#[derive(Clone, Copy)]
+struct Rect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+fn draw(out: &mut String, node: &HeatmapNode, area: Rect, hottest: usize, root: bool) {
+    if area.width < 1.0 || area.height < 1.0 {
+        return;
+    }
+    let title = format!("{} ({})", escape_xml(&node.path), node.count);
+    if node.kind == HeatmapNodeKind::File {
+        let _ = writeln!(
+            out,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\" \
+             stroke=\"#ffffff\"><title>{title}</title></rect>",
+            area.x,
+            area.y,
+            area.width,
+            area.height,
+            heat_colour(node.count, hottest)
+        );
+        label(out, &node.name, area, 12.0);
+        return;
+    }
+
+    let mut inner = area;
+    if !root {
+        let _ = writeln!(
+            out,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#f4f4f4\" \
+             stroke=\"#888888\"><title>{title}</title></rect>",
+            area.x, area.y, area.width, area.height
+        );
+        let header = if area.height > SVG_HEADER * 2.0 {
+            label(out, &format!("{}/", node.name), area, SVG_HEADER - 4.0);
+            SVG_HEADER
+        } else {
+            0.0
+        };
+        inner = Rect {
+            x: area.x + SVG_PADDING,
+            y: area.y + header + SVG_PADDING,
+            width: area.width - 2.0 * SVG_PADDING,
+            height: area.height - header - 2.0 * SVG_PADDING,
+        };
+    }
+
+    // Slice along the longer side so rectangles stay closer to square.
+    let horizontal = inner.width >= inner.height;
+    let mut offset = 0.0;
+    for child in &node.children {
+        let share = child.count as f64 / node.count.max(1) as f64;
+        let slice = if horizontal {
+            Rect {
+                x: inner.x + offset,
+                width: inner.width * share,
+                ..inner
+            }
+        } else {
+            Rect {
+                y: inner.y + offset,
+                height: inner.height * share,
+                ..inner
+            }
+        };
+        offset += if horizontal {
+            slice.width
+        } else {
+            slice.height
+        };
+        draw(out, child, slice, hottest, false);
+    }
+}
+
+/// Write `text` at the top left of `area` when it has room for it.
+fn label(out: &mut String, text: &str, area: Rect, baseline: f64) {
+    // Roughly the advance of an 11px sans-serif character.
+    let fits = text.chars().count() as f64 * 6.5 + 8.0 <= area.width && area.height >= 14.0;
+    if fits {
+        let _ = writeln!(
+            out,
+            "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+            area.x + 4.0,
+            area.y + baseline,
+            escape_xml(text)
+        );
+    }
+}
+
+fn hottest_file(node: &HeatmapNode) -> usize {
+    match node.kind {
+        HeatmapNodeKind::File => node.count,
+        HeatmapNodeKind::Directory => node.children.iter().map(hottest_file).max().unwrap_or(0),
+    }
+}
+
+/// Yellow for files with one match, through orange, to red for the hottest file.
+fn heat_colour(count: usize, hottest: usize) -> String {
+    let heat = if hottest > 1 {
+        (count.saturating_sub(1)) as f64 / (hottest - 1) as f64
+    } else {
+        1.0
+    };
+    let hue = 55.0 * (1.0 - heat);
+    let lightness = 75.0 - 25.0 * heat;
+    format!("hsl({hue:.0},90%,{lightness:.0}%)")
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
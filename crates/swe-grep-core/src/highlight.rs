@@ -0,0 +1,456 @@
+use serde::{Deserialize, Serialize};
+
+/// Token class of a highlighted span.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    Keyword,
+    Ident,
+    String,
+    Comment,
+    Number,
+}
+
+impl TokenKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Keyword => "keyword",
+            Self::Ident => "ident",
+            Self::String => "string",
+            Self::Comment => "comment",
+            Self::Number => "number",
+        }
+    }
+}
+
+/// A classified token: byte offsets `start..end` into the text it was computed for.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HighlightSpan {
+    pub start: usize,
+    pub end: usize,
+    pub kind: TokenKind,
+}
+
+/// Lexical rules for one language; enough to classify tokens, not to parse.
+struct Syntax {
+    keywords: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Characters that open a single-line string closed by the same character.
+    quotes: &'static [char],
+    /// Delimiters of strings that may span lines (`"""`).
+    multiline_quotes: &'static [&'static str],
+}
+
+const RUST: Syntax = Syntax {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    multiline_quotes: &[],
+};
+
+const SWIFT: Syntax = Syntax {
+    keywords: &[
+        "actor",
+        "as",
+        "associatedtype",
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "continue",
+        "default",
+        "defer",
+        "do",
+        "else",
+        "enum",
+        "extension",
+        "false",
+        "fileprivate",
+        "for",
+        "func",
+        "guard",
+        "if",
+        "import",
+        "in",
+        "init",
+        "internal",
+        "is",
+        "let",
+        "nil",
+        "open",
+        "private",
+        "protocol",
+        "public",
+        "return",
+        "self",
+        "Self",
+        "static",
+        "struct",
+        "super",
+        "switch",
+        "throw",
+        "throws",
+        "true",
+        "try",
+        "typealias",
+        "var",
+        "where",
+        "while",
+    ],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    multiline_quotes: &["\"\"\""],
+};
+
+const TYPESCRIPT: Syntax = Syntax {
+    keywords: &[
+        "abstract",
+        "as",
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "declare",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "private",
+        "protected",
+        "public",
+        "readonly",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "type",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "yield",
+    ],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    multiline_quotes: &["`"],
+};
+
+const PYTHON: Syntax = Syntax {
+    keywords: &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "self", "try", "while", "with", "yield",
+    ],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"', '\''],
+    multiline_quotes: &["\"\"\"", "'''"],
+};
+
+const KOTLIN: Syntax = Syntax {
+    keywords: &[
+        "as",
+        "break",
+        "class",
+        "companion",
+        "continue",
+        "data",
+        "do",
+        "else",
+        "enum",
+        "false",
+        "for",
+        "fun",
+        "if",
+        "import",
+        "in",
+        "interface",
+        "internal",
+        "is",
+        "object",
+        "override",
+        "package",
+        "private",
+        "protected",
+        "public",
+        "return",
+        "sealed",
+        "super",
+        "suspend",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "typealias",
+        "val",
+        "var",
+        "when",
+        "while",
+    ],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    multiline_quotes: &["\"\"\""],
+};
+
+const PROTO: Syntax = Syntax {
+    keywords: &[
+        "enum", "extend", "false", "import", "map", "message", "oneof", "option", "optional",
+        "package", "repeated", "reserved", "returns", "rpc", "service", "stream", "syntax", "true",
+    ],
+    line_comments: &["//"],
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"', '\''],
+    multiline_quotes: &[],
+};
+
+const GRAPHQL: Syntax = Syntax {
+    keywords: &[
+        "enum",
+        "extend",
+        "false",
+        "fragment",
+        "implements",
+        "input",
+        "interface",
+        "mutation",
+        "null",
+        "on",
+        "query",
+        "scalar",
+        "schema",
+        "subscription",
+        "true",
+        "type",
+        "union",
+    ],
+    line_comments: &["#"],
+    block_comment: None,
+    quotes: &['"'],
+    multiline_quotes: &["\"\"\""],
+};
+
+fn syntax_for(language: &str) -> Option<&'static Syntax> {
+    match language {
+        "rust" => Some(&RUST),
+        "swift" => Some(&SWIFT),
+        "typescript" | "tsx" | "javascript" | "jsx" => Some(&TYPESCRIPT),
+        "python" => Some(&PYTHON),
+        "kotlin" => Some(&KOTLIN),
+        "proto" => Some(&PROTO),
+        "graphql" => Some(&GRAPHQL),
+        _ => None,
+    }
+}
+
+/// Spans for `text` in `language` (as named by language detection); empty for languages
+/// without rules.
+pub fn highlight(language: &str, text: &str) -> Vec<HighlightSpan> {
+    let Some(syntax) = syntax_for(language) else {
+        return Vec::new();
+    };
+    let mut lexer = Lexer::new(syntax);
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        lexer.line(line.trim_end_matches(['\n', '\r']), offset);
+        offset += line.len();
+    }
+    lexer.spans
+}
+
+/// Like `highlight`, for windows whose lines start with a line number and a space (an
+/// `expanded_snippet`); the numbers get no span.
+pub fn highlight_numbered(language: &str, text: &str) -> Vec<HighlightSpan> {
+    let Some(syntax) = syntax_for(language) else {
+        return Vec::new();
+    };
+    let mut lexer = Lexer::new(syntax);
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let prefix = content.find(' ').map_or(content.len(), |space| space + 1);
+        lexer.line(&content[prefix..], offset + prefix);
+        offset += line.len();
+    }
+    lexer.spans
+}
+
+/// Line-at-a-time tokenizer carrying block comments and multi-line strings across lines.
+struct Lexer {
+    syntax: &'static Syntax,
+    /// Closing delimiter of the block comment or string left open by the previous line.
+    open: Option<(&'static str, TokenKind)>,
+    spans: Vec<HighlightSpan>,
+}
+
+impl Lexer {
+    fn new(syntax: &'static Syntax) -> Self {
+        Self {
+            syntax,
+            open: None,
+            spans: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, start: usize, end: usize, kind: TokenKind) {
+        if end > start {
+            self.spans.push(HighlightSpan { start, end, kind });
+        }
+    }
+
+    /// Classify `line` (without its newline), which starts `base` bytes into the text.
+    fn line(&mut self, line: &str, base: usize) {
+        let mut i = 0;
+        while i < line.len() {
+            if let Some((close, kind)) = self.open {
+                let end = match line[i..].find(close) {
+                    Some(found) => {
+                        self.open = None;
+                        i + found + close.len()
+                    }
+                    None => line.len(),
+                };
+                self.push(base + i, base + end, kind);
+                i = end;
+                continue;
+            }
+
+            let rest = &line[i..];
+            if self
+                .syntax
+                .line_comments
+                .iter()
+                .any(|marker| rest.starts_with(marker))
+            {
+                self.push(base + i, base + line.len(), TokenKind::Comment);
+                return;
+            }
+            if let Some((open, close)) = self.syntax.block_comment
+                && rest.starts_with(open)
+            {
+                self.open = Some((close, TokenKind::Comment));
+                self.push(base + i, base + i + open.len(), TokenKind::Comment);
+                i += open.len();
+                self.extend_open(line, base, &mut i);
+                continue;
+            }
+            if let Some(quote) = self
+                .syntax
+                .multiline_quotes
+                .iter()
+                .find(|quote| rest.starts_with(**quote))
+            {
+                self.open = Some((quote, TokenKind::String));
+                self.push(base + i, base + i + quote.len(), TokenKind::String);
+                i += quote.len();
+                self.extend_open(line, base, &mut i);
+                continue;
+            }
+
+            let Some(c) = rest.chars().next() else {
+                break;
+            };
+            let end = if self.syntax.quotes.contains(&c) {
+                let end = string_end(line, i, c);
+                self.push(base + i, base + end, TokenKind::String);
+                end
+            } else if c.is_ascii_digit() {
+                let end = i + token_len(rest);
+                self.push(base + i, base + end, TokenKind::Number);
+                end
+            } else if c.is_alphabetic() || c == '_' || c == '$' {
+                let end = i + token_len(rest);
+                let kind = if self.syntax.keywords.contains(&&line[i..end]) {
+                    TokenKind::Keyword
+                } else {
+                    TokenKind::Ident
+                };
+                self.push(base + i, base + end, kind);
+                end
+            } else {
+                i + c.len_utf8()
+            };
+            i = end;
+        }
+    }
+
+    /// Grow the span just pushed for an opening delimiter over the rest of the comment or
+    /// string, up to its close on this line or the end of the line.
+    fn extend_open(&mut self, line: &str, base: usize, i: &mut usize) {
+        let Some((close, _)) = self.open else {
+            return;
+        };
+        let end = match line[*i..].find(close) {
+            Some(found) => {
+                self.open = None;
+                *i + found + close.len()
+            }
+            None => line.len(),
+        };
+        if let Some(span) = self.spans.last_mut() {
+            span.end = base + end;
+        }
+        *i = end;
+    }
+}
+
+/// End of the string opened by `quote` at byte `start`: just past the closing quote, skipping
+/// escaped characters, or the end of the line when it is unterminated.
+fn string_end(line: &str, start: usize, quote: char) -> usize {
+    let mut chars = line[start..].char_indices().skip(1);
+    while let Some((offset, c)) = chars.next() {
+        if c == '\\' {
+            chars.next();
+        } else if c == quote {
+            return start + offset + c.len_utf8();
+        }
+    }
+    line.len()
+}
+
+/// Length of the identifier or number at the start of `text`.
+fn token_len(text: &str) -> usize {
+    text.char_indices()
+        .find(|(_, c)| !(c.is_alphanumeric() || *c == '_' || *c == '$'))
+        .map_or(text.len(), |(offset, _)| offset)
+}
@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use crate::config::HookConfig;
+use crate::search::{SearchSummary, Warning, WarningKind};
+
+/// Seconds a hook may run when its `timeout_secs` is unset.
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 5;
+
+/// The `[[hooks]]` of one repository, run in order on every finished summary.
+#[derive(Clone, Debug, Default)]
+pub struct Hooks {
+    hooks: Vec<HookConfig>,
+}
+
+impl Hooks {
+    /// Resolve relative programs such as `scripts/redact.sh` against `root`.
+    pub fn new(mut hooks: Vec<HookConfig>, root: &Path) -> Self {
+        for hook in &mut hooks {
+            if let Some(program) = hook.command.first_mut() {
+                let path = PathBuf::from(&*program);
+                if path.is_relative() && path.components().count() > 1 {
+                    *program = root.join(path).to_string_lossy().into_owned();
+                }
+            }
+        }
+        Self { hooks }
+    }
+
+    /// Pipe `summary` through every hook in turn, each started in `cwd`. A failing hook leaves
+    /// the summary as it was and adds a `hook_failed` warning, unless the hook is `required`.
+    pub async fn apply(&self, cwd: &Path, mut summary: SearchSummary) -> Result<SearchSummary> {
+        for hook in &self.hooks {
+            let Some(program) = hook.command.first() else {
+                continue;
+            };
+            match run(hook, cwd, &summary).await {
+                Ok(rewritten) => summary = rewritten,
+                Err(err) if hook.required => {
+                    return Err(err.context(format!("required hook `{program}` failed")));
+                }
+                Err(err) => {
+                    tracing::warn!(hook = %program, error = %err, "hook_failed");
+                    summary.warnings.push(
+                        Warning::new(
+                            WarningKind::HookFailed,
+                            format!("hook `{program}` failed, summary left unchanged: {err:#}"),
+                        )
+                        .with_tool("hook"),
+                    );
+                }
+            }
+        }
+        Ok(summary)
+    }
+}
+
+async fn run(hook: &HookConfig, cwd: &Path, summary: &SearchSummary) -> Result<SearchSummary> {
+    let input = serde_json::to_vec(summary).context("failed to serialize summary")?;
+    let limit = Duration::from_secs(hook.timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+
+    let mut cmd = Command::new(&hook.command[0]);
+    cmd.args(&hook.command[1..])
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut child = cmd.spawn().context("failed to spawn")?;
+    let mut stdin = child.stdin.take().context("hook stdin unavailable")?;
+
+    let output = timeout(limit, async {
+        let write = async move {
+            // A hook may exit without reading all of its input; its exit status and output
+            // decide whether it worked, so a broken pipe is not an error here.
+            let _ = stdin.write_all(&input).await;
+        };
+        let (_, output) = tokio::join!(write, child.wait_with_output());
+        output
+    })
+    .await
+    .with_context(|| format!("timed out after {}s", limit.as_secs()))?
+    .context("failed to wait for hook")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let trimmed = stderr.trim();
+        if trimmed.is_empty() {
+            anyhow::bail!("exited with status {}", output.status);
+        }
+        anyhow::bail!("exited with status {}: {}", output.status, trimmed);
+    }
+    serde_json::from_slice(&output.stdout).context("printed an invalid summary")
+}
@@ -0,0 +1,110 @@
+//! Infrastructure-as-code files: Terraform/HCL modules and YAML manifests, which `--config`
+//! searches ahead of everything else.
+
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+/// Extensions of the files `--config` searches first.
+pub const CONFIG_EXTENSIONS: &[&str] = &["tf", "tfvars", "hcl", "yaml", "yml"];
+
+/// Whether `path` is a Terraform/HCL or YAML file.
+pub fn is_config_file(path: &Path) -> bool {
+    has_extension(path, CONFIG_EXTENSIONS)
+}
+
+/// Up to `limit` Terraform/HCL and YAML files that pass `allowed`, relative to `root`, walked
+/// in name order with ignore files honoured.
+pub fn config_files(root: &Path, limit: usize, allowed: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+    WalkBuilder::new(root)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .filter(|path| is_config_file(path) && allowed(path))
+        .take(limit)
+        .collect()
+}
+
+/// Whether `path` is a YAML file.
+pub fn is_yaml_file(path: &Path) -> bool {
+    has_extension(path, &["yaml", "yml"])
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// Keys of a dotted YAML key path such as `spec.template.spec`; `None` for any other symbol.
+pub fn key_path_segments(symbol: &str) -> Option<Vec<&str>> {
+    let segments: Vec<&str> = symbol.split('.').collect();
+    let valid = segments.len() > 1
+        && segments.iter().all(|segment| {
+            !segment.is_empty() && !segment.chars().any(|ch| ch.is_whitespace() || ch == ':')
+        });
+    valid.then_some(segments)
+}
+
+/// Whether the key on 1-based `line` of the YAML `source` is reached by a path ending in
+/// `segments`, list items skipped; `None` when that line holds no key.
+pub fn key_path_ends_with(source: &str, line: usize, segments: &[&str]) -> Option<bool> {
+    let keys = key_path_at(source, line)?;
+    Some(
+        keys.len() >= segments.len()
+            && keys[keys.len() - segments.len()..]
+                .iter()
+                .zip(segments)
+                .all(|(key, segment)| key == segment),
+    )
+}
+
+/// Keys leading to the key on 1-based `line`, outermost first. Each parent is the nearest
+/// key above with less indentation, within the same `---` document.
+fn key_path_at(source: &str, line: usize) -> Option<Vec<String>> {
+    let lines: Vec<&str> = source.lines().collect();
+    let index = line.checked_sub(1)?;
+    let (mut indent, key) = mapping_key(lines.get(index)?)?;
+    let mut keys = vec![key];
+    for text in lines[..index].iter().rev() {
+        if indent == 0 || text.trim_end() == "---" {
+            break;
+        }
+        if let Some((parent_indent, parent)) = mapping_key(text)
+            && parent_indent < indent
+        {
+            keys.push(parent);
+            indent = parent_indent;
+        }
+    }
+    keys.reverse();
+    Some(keys)
+}
+
+/// Indentation and key of a `key: value` line. The key of a list item (`- name: web`) is
+/// indented past its dash, like the item's other keys.
+fn mapping_key(line: &str) -> Option<(usize, String)> {
+    let mut rest = line.trim_start();
+    while let Some(item) = rest.strip_prefix("- ") {
+        rest = item.trim_start();
+    }
+    let indent = line.len() - rest.len();
+    if rest.starts_with('#') {
+        return None;
+    }
+    let (key, value) = rest.split_once(':')?;
+    if !(value.is_empty() || value.starts_with([' ', '\t'])) {
+        return None;
+    }
+    let key = key.trim().trim_matches(['"', '\'']);
+    if key.is_empty() || key.starts_with(['{', '[', '&', '*', '!']) {
+        return None;
+    }
+    Some((indent, key.to_string()))
+}
@@ -0,0 +1,41 @@
+/// Whether `c` may start an identifier.
+///
+/// Identifiers follow Unicode's XID_Start/XID_Continue, the base of the identifier rules in
+/// Rust, Swift, Julia, Python, and JavaScript, plus `_` as a start character.
+pub fn is_start(c: char) -> bool {
+    c == '_' || unicode_ident::is_xid_start(c)
+}
+
+/// Whether `c` may continue an identifier: letters, digits, `_`, and combining marks.
+pub fn is_continue(c: char) -> bool {
+    unicode_ident::is_xid_continue(c)
+}
+
+/// Whether `symbol` is a single identifier, such as `parse_config`, `größe`, or `用户名`.
+pub fn is_identifier(symbol: &str) -> bool {
+    let mut chars = symbol.chars();
+    chars.next().is_some_and(is_start) && chars.all(is_continue)
+}
+
+/// Whether `c` belongs to a script written without spaces between words (Han, kana).
+///
+/// Text in these scripts runs identifiers straight into surrounding prose, so a `\b` next to
+/// such a character would only match at the edge of a whole sentence.
+pub fn is_unsegmented(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30FF}'       // Hiragana, Katakana
+            | '\u{31F0}'..='\u{31FF}' // Katakana phonetic extensions
+            | '\u{3400}'..='\u{4DBF}' // CJK extension A
+            | '\u{4E00}'..='\u{9FFF}' // CJK unified ideographs
+            | '\u{F900}'..='\u{FAFF}' // CJK compatibility ideographs
+            | '\u{FF66}'..='\u{FF9F}' // Halfwidth Katakana
+            | '\u{20000}'..='\u{3134F}' // CJK extensions B through G
+    )
+}
+
+/// Whether a `\b` anchor belongs next to `c`: it is an identifier character from a script that
+/// separates words.
+pub fn takes_word_boundary(c: char) -> bool {
+    is_continue(c) && !is_unsegmented(c)
+}
@@ -0,0 +1,108 @@
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::cli::{IndexArgs, IndexBuildArgs, IndexCommand};
+use crate::search;
+
+/// Outcome of `swe-grep index build`.
+#[derive(Debug, Serialize)]
+pub struct IndexBuildReport {
+    pub root: String,
+    pub index_dir: String,
+    /// The index was (re)built; `false` when an up-to-date index was only opened.
+    pub built: bool,
+    /// Files read into the index, when it was built.
+    pub files: usize,
+    /// Indexed lines.
+    pub documents: u64,
+    /// Why the existing index was quarantined and rebuilt, when it failed its health check.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+/// Entry point for `swe-grep index`.
+pub async fn run(args: IndexArgs) -> Result<IndexBuildReport> {
+    match args.command {
+        IndexCommand::Build(args) => build(args).await,
+    }
+}
+
+async fn build(args: IndexBuildArgs) -> Result<IndexBuildReport> {
+    let root = args
+        .path
+        .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
+    let root = search::canonicalize_path(&root).with_context(|| {
+        format!(
+            "failed to canonicalize repository root path: {}",
+            root.display()
+        )
+    })?;
+    let index_dir = match args.index_dir {
+        Some(dir) if dir.is_absolute() => dir,
+        Some(dir) => root.join(dir),
+        None => root.join(".swe-grep-index"),
+    };
+    build_index(&root, &index_dir, args.rebuild).await
+}
+
+/// Build the index of `root` in `index_dir`, printing progress to stderr.
+#[cfg(feature = "indexing")]
+async fn build_index(root: &Path, index_dir: &Path, rebuild: bool) -> Result<IndexBuildReport> {
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    use swe_grep_indexer::{IndexConfig, IndexProgress, TantivyIndex};
+
+    if rebuild {
+        swe_grep_indexer::remove_index(index_dir)?;
+    }
+    let started = Instant::now();
+    // Only a build reports progress; it always reports its finish last.
+    let finished = Arc::new(Mutex::new(None::<IndexProgress>));
+    let last = finished.clone();
+    let index = TantivyIndex::open_or_build_with_progress(
+        IndexConfig {
+            root: root.to_path_buf(),
+            index_dir: index_dir.to_path_buf(),
+            extensions: None,
+        },
+        Arc::new(move |progress: &IndexProgress| {
+            *last.lock().expect("progress lock poisoned") = Some(*progress);
+            eprintln!("{}", progress_line(progress));
+        }),
+    )
+    .await?;
+    let finished = *finished.lock().expect("progress lock poisoned");
+    Ok(IndexBuildReport {
+        root: root.display().to_string(),
+        index_dir: index_dir.display().to_string(),
+        built: finished.is_some(),
+        files: finished.map_or(0, |progress| progress.files_done),
+        documents: index.num_docs(),
+        recovery: index.recovery().map(str::to_string),
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[cfg(feature = "indexing")]
+fn progress_line(progress: &swe_grep_indexer::IndexProgress) -> String {
+    let eta = match progress.eta() {
+        _ if progress.is_finished() => "done".to_string(),
+        Some(eta) => format!("ETA {}s", eta.as_secs()),
+        None => "ETA unknown".to_string(),
+    };
+    format!(
+        "indexed {}/{} files ({:.0} files/s, {eta})",
+        progress.files_done,
+        progress.files_total,
+        progress.files_per_sec()
+    )
+}
+
+#[cfg(not(feature = "indexing"))]
+async fn build_index(_root: &Path, _index_dir: &Path, _rebuild: bool) -> Result<IndexBuildReport> {
+    anyhow::bail!("index build requires swe-grep built with the `indexing` feature")
+}
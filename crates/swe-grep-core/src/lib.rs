@@ -1,6 +1,50 @@
 pub mod bench;
+pub mod calibrate;
 pub mod cli;
+pub mod clusters;
+pub mod config;
+pub mod definition;
+pub mod diff;
+pub mod dirty;
+pub mod doc_comments;
+pub mod fields;
+pub mod file_types;
+pub mod fingerprint;
+pub mod fixtures;
+pub mod flag;
+pub mod generated;
+pub mod heatmap;
+pub mod highlight;
+pub mod hooks;
+pub mod iac;
+pub mod identifier;
+pub mod index;
+pub mod links;
+pub mod notebook;
+pub mod outline;
+pub mod owners;
+pub mod picker;
+pub mod plan;
+pub mod plugins;
+pub mod profile;
+pub mod query;
+pub mod redact;
+pub mod results;
+pub mod revision;
+pub mod rust_modules;
 pub mod search;
+pub mod skipped;
+pub mod snapshot;
 pub mod service;
+pub mod source;
+pub mod state_store;
+pub mod stats;
+pub mod submodules;
+pub mod suggest;
+pub mod swift_targets;
+pub mod symbols;
 pub mod telemetry;
 pub mod tools;
+pub mod ts_aliases;
+pub mod validate;
+pub mod workspace;
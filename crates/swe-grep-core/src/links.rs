@@ -0,0 +1,188 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::cli::LinkFormat;
+use crate::fingerprint::RepoFingerprint;
+
+/// Builds the per-hit URLs selected by `--link-format` for one search root.
+#[derive(Debug)]
+pub struct Linker {
+    format: LinkFormat,
+    root: PathBuf,
+    /// Needed for GitHub links only.
+    location: Option<RepoLocation>,
+}
+
+impl Linker {
+    pub fn new(format: LinkFormat, root: &Path, location: Option<&RepoLocation>) -> Self {
+        Self {
+            format,
+            root: root.to_path_buf(),
+            location: location.cloned(),
+        }
+    }
+
+    /// URL opening `path` (relative to the root) at `line`, or at the top of the file when `line`
+    /// is `None`. GitHub links are absent without a `RepoLocation`.
+    pub fn link(&self, path: &Path, line: Option<usize>) -> Option<String> {
+        match self.format {
+            LinkFormat::Vscode => {
+                let file = slash_path(&self.root.join(path));
+                let file = if file.starts_with('/') {
+                    file
+                } else {
+                    format!("/{file}")
+                };
+                let mut link = format!("vscode://file{}", encode(&file));
+                if let Some(line) = line {
+                    link.push_str(&format!(":{line}"));
+                }
+                Some(link)
+            }
+            LinkFormat::Jetbrains => {
+                let file = slash_path(&self.root.join(path));
+                let mut link = format!("idea://open?file={}", encode(&file));
+                if let Some(line) = line {
+                    link.push_str(&format!("&line={line}"));
+                }
+                Some(link)
+            }
+            LinkFormat::Github => self
+                .location
+                .as_ref()
+                .map(|location| location.permalink(path, line)),
+        }
+    }
+}
+
+/// Code host serving the `origin` remote, which decides the permalink layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostKind {
+    /// `<repo>/blob/<commit>/<path>#L<line>`; also assumed for hosts that are not GitLab, such as
+    /// GitHub Enterprise servers.
+    Github,
+    /// `<repo>/-/blob/<commit>/<path>#L<line>`.
+    Gitlab,
+}
+
+/// A repository on a code host, parsed from a remote URL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Remote {
+    pub kind: HostKind,
+    /// `https://host/owner/repo`.
+    pub web_url: String,
+}
+
+impl Remote {
+    /// Parse an SSH (`git@host:owner/repo.git`, `ssh://git@host:22/owner/repo`) or HTTPS
+    /// (`https://host/owner/repo.git`) remote URL; credentials and SSH ports are dropped.
+    pub fn parse(url: &str) -> Option<Self> {
+        let url = url.trim();
+        let (host, path) = if let Some((scheme, rest)) = url.split_once("://") {
+            let (authority, path) = rest.split_once('/')?;
+            let host = authority
+                .rsplit_once('@')
+                .map_or(authority, |(_, host)| host);
+            // An SSH port is no use to a browser; an HTTPS one is kept.
+            let host = if scheme.eq_ignore_ascii_case("ssh") || scheme.eq_ignore_ascii_case("git") {
+                host.split_once(':').map_or(host, |(name, _)| name)
+            } else {
+                host
+            };
+            (host, path)
+        } else {
+            let (authority, path) = url.split_once(':')?;
+            let host = authority
+                .rsplit_once('@')
+                .map_or(authority, |(_, host)| host);
+            (host, path)
+        };
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        if host.is_empty() || path.is_empty() {
+            return None;
+        }
+        let kind = if host.to_ascii_lowercase().contains("gitlab") {
+            HostKind::Gitlab
+        } else {
+            HostKind::Github
+        };
+        Some(Self {
+            kind,
+            web_url: format!("https://{host}/{path}"),
+        })
+    }
+}
+
+/// Where a search root's files live on the code host, pinned to one commit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RepoLocation {
+    pub remote: Remote,
+    /// Full commit id links are pinned to.
+    pub commit: String,
+    /// The root's path within the repository.
+    pub prefix: PathBuf,
+}
+
+impl RepoLocation {
+    /// HEAD of the checkout `fingerprint` describes; `None` without a recognizable `origin`
+    /// remote or a resolvable HEAD.
+    pub fn detect(fingerprint: &RepoFingerprint) -> Option<Self> {
+        Some(Self {
+            remote: Remote::parse(fingerprint.origin.as_deref()?)?,
+            commit: fingerprint.head.clone()?,
+            prefix: fingerprint.prefix.clone(),
+        })
+    }
+
+    /// Pin links to `commit` instead.
+    pub fn at_commit(mut self, commit: String) -> Self {
+        self.commit = commit;
+        self
+    }
+
+    /// Permalink to `path` (relative to the root) at `line`, or to the whole file when `line` is
+    /// `None`.
+    pub fn permalink(&self, path: &Path, line: Option<usize>) -> String {
+        let file = encode(&slash_path(&self.prefix.join(path)));
+        let mut link = match self.remote.kind {
+            HostKind::Github => format!("{}/blob/{}/{file}", self.remote.web_url, self.commit),
+            HostKind::Gitlab => format!("{}/-/blob/{}/{file}", self.remote.web_url, self.commit),
+        };
+        if let Some(line) = line {
+            link.push_str(&format!("#L{line}"));
+        }
+        link
+    }
+}
+
+/// `path` with `/` separators, whatever the platform.
+fn slash_path(path: &Path) -> String {
+    let mut text = String::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => text.push_str(&prefix.as_os_str().to_string_lossy()),
+            Component::RootDir => text.push('/'),
+            Component::CurDir => {}
+            other => {
+                if !text.is_empty() && !text.ends_with('/') {
+                    text.push('/');
+                }
+                text.push_str(&other.as_os_str().to_string_lossy());
+            }
+        }
+    }
+    text
+}
+
+/// Percent-encode everything but unreserved characters, `/`, and `:`.
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~' | b'/' | b':') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    encoded
+}
@@ -2,29 +2,181 @@ use anyhow::Result;
 use clap::Parser;
 
 use swe_grep::bench;
-use swe_grep::cli::{Cli, Commands};
-use swe_grep::search;
+use swe_grep::calibrate;
+use swe_grep::cli::{BenchCommand, Cli, Commands, HeatmapFormat, OutputFormat};
+use swe_grep::diff;
+use swe_grep::fields::FieldSelection;
+use swe_grep::fixtures;
+use swe_grep::flag;
+use swe_grep::heatmap;
+use swe_grep::index;
+use swe_grep::outline;
+use swe_grep::picker;
+use swe_grep::query;
+use swe_grep::results;
+use swe_grep::search::{self, SearchSummary};
 use swe_grep::service;
+use swe_grep::snapshot::Snapshot;
+use swe_grep::stats;
+use swe_grep::symbols;
 use swe_grep::telemetry;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    telemetry::init_logging(cli.log_filter(), cli.default_log_filter());
     if !cli.disable_telemetry {
         telemetry::init()?;
     }
     match cli.command {
-        Commands::Search(args) => {
-            let summary = search::execute(args).await?;
-            let json = serde_json::to_string_pretty(&summary)?;
-            println!("{json}");
+        Commands::Search(mut args) => {
+            let fields = FieldSelection::parse(&args.fields)?;
+            let format = args.format.unwrap_or_default();
+            let snapshot = (format == OutputFormat::Snapshot).then(|| {
+                args.deterministic = true;
+                Snapshot::new(&args.path)
+            });
+            let exec = args.exec.take();
+            // Hit paths are relative to the root of a single-root search.
+            let cwd = match args.path.as_slice() {
+                [root] => root.clone(),
+                _ => std::env::current_dir()?,
+            };
+            let summary = search::execute(*args).await?;
+            if let Some(template) = exec {
+                return exec_hit(&summary, &template, format, &cwd).await;
+            }
+            print_summary(&summary, &fields, format, snapshot.as_ref())?;
         }
-        Commands::Bench(args) => {
-            bench::run(args).await?;
+        Commands::Query(mut args) => {
+            let fields = FieldSelection::parse(&args.fields)?;
+            let format = args.format.unwrap_or_default();
+            // Only a `--root` tells the client how the server spells the searched root.
+            let snapshot =
+                (format == OutputFormat::Snapshot).then(|| Snapshot::new(args.root.as_slice()));
+            let exec = args.exec.take();
+            let cwd = match &args.root {
+                Some(root) => root.clone(),
+                None => std::env::current_dir()?,
+            };
+            let summary = query::run(&args).await?;
+            if let Some(template) = exec {
+                return exec_hit(&summary, &template, format, &cwd).await;
+            }
+            print_summary(&summary, &fields, format, snapshot.as_ref())?;
         }
+        Commands::Bench(mut args) => match args.command.take() {
+            Some(BenchCommand::Replay(replay)) => {
+                let report = bench::replay(replay).await?;
+                let json = serde_json::to_string_pretty(&report)?;
+                println!("{json}");
+            }
+            None => bench::run(args).await?,
+        },
         Commands::Serve(args) => {
             service::serve(args).await?;
         }
+        Commands::Diff(args) => {
+            let report = diff::run(args).await?;
+            let json = serde_json::to_string_pretty(&report)?;
+            println!("{json}");
+        }
+        Commands::Calibrate(args) => {
+            let report = calibrate::run(args).await?;
+            let json = serde_json::to_string_pretty(&report)?;
+            println!("{json}");
+        }
+        Commands::Symbols(args) => {
+            let response = symbols::run(args).await?;
+            let json = serde_json::to_string_pretty(&response)?;
+            println!("{json}");
+        }
+        Commands::Index(args) => {
+            let report = index::run(args).await?;
+            let json = serde_json::to_string_pretty(&report)?;
+            println!("{json}");
+        }
+        Commands::Stats(args) => {
+            let stats = stats::run(args).await?;
+            let json = serde_json::to_string_pretty(&stats)?;
+            println!("{json}");
+        }
+        Commands::Show(args) => {
+            let fields = FieldSelection::parse(&args.fields)?;
+            let summary = results::run(args).await?;
+            let json = serde_json::to_string_pretty(&fields.to_value(&summary)?)?;
+            println!("{json}");
+        }
+        Commands::Outline(args) => {
+            let outline = outline::run(args).await?;
+            let json = serde_json::to_string_pretty(&outline)?;
+            println!("{json}");
+        }
+        Commands::Flag(args) => {
+            let report = flag::run(args).await?;
+            let json = serde_json::to_string_pretty(&report)?;
+            println!("{json}");
+        }
+        Commands::Heatmap(args) => {
+            let format = args.format;
+            let report = heatmap::run(args).await?;
+            match format {
+                HeatmapFormat::Json => {
+                    let json = serde_json::to_string_pretty(&report)?;
+                    println!("{json}");
+                }
+                HeatmapFormat::Svg => print!("{}", heatmap::svg(&report)),
+            }
+        }
+        Commands::Fixtures(args) => {
+            let report = fixtures::run(args).await?;
+            let json = serde_json::to_string_pretty(&report)?;
+            println!("{json}");
+        }
+    }
+    Ok(())
+}
+
+/// Print a search summary as `search` and `query` do: picker lines for `--format picker`,
+/// otherwise JSON trimmed to `--fields` and rewritten for `--format snapshot`.
+fn print_summary(
+    summary: &SearchSummary,
+    fields: &FieldSelection,
+    format: OutputFormat,
+    snapshot: Option<&Snapshot>,
+) -> Result<()> {
+    if format == OutputFormat::Picker {
+        print!("{}", picker::render(summary));
+        return Ok(());
     }
+    let mut value = fields.to_value(summary)?;
+    if let Some(snapshot) = snapshot {
+        value = snapshot.apply(value);
+    }
+    let json = serde_json::to_string_pretty(&value)?;
+    println!("{json}");
     Ok(())
 }
+
+/// Run `--exec` with the hit picked interactively under `--format picker`, or the top hit.
+async fn exec_hit(
+    summary: &SearchSummary,
+    template: &str,
+    format: OutputFormat,
+    cwd: &std::path::Path,
+) -> Result<()> {
+    if summary.top_hits.is_empty() {
+        anyhow::bail!("no hits for `{}` to run --exec with", summary.symbol);
+    }
+    let hit = if format == OutputFormat::Picker {
+        let command = std::env::var(picker::PICKER_ENV)
+            .unwrap_or_else(|_| picker::DEFAULT_PICKER.to_string());
+        picker::choose(summary, &command).await?
+    } else {
+        summary.top_hits.first()
+    };
+    match hit {
+        Some(hit) => picker::exec(template, hit, cwd).await,
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde_json::Value;
+
+/// Whether `path` names a Jupyter notebook.
+pub fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+}
+
+/// Code cells of a Jupyter notebook, mapped back to the lines of the JSON file they came from.
+///
+/// Text tools report notebook matches by JSON line; [`Notebook::locate`] turns such a line into
+/// a cell index (0-based, counting every cell) and a line within that cell (1-based). Lines of
+/// markdown cells, outputs, and metadata do not map to anything.
+#[derive(Clone, Debug, Default)]
+pub struct Notebook {
+    cells: HashMap<usize, Vec<String>>,
+    lines: HashMap<usize, CellLine>,
+}
+
+/// One source line of a code cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellLine {
+    pub cell: usize,
+    pub line: usize,
+}
+
+impl Notebook {
+    /// Read and parse the notebook at `path`; `None` when it is unreadable or not nbformat JSON.
+    pub fn load(path: &Path) -> Option<Self> {
+        Self::parse(&fs::read_to_string(path).ok()?)
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        let document: Value = serde_json::from_str(raw).ok()?;
+        let cells = document.get("cells")?.as_array()?;
+        let raw_lines: Vec<&str> = raw.lines().collect();
+        let mut notebook = Self::default();
+        // Cells are written in order and each has one `source` key, so the JSON lines of every
+        // cell's source follow the previous cell's.
+        let mut cursor = 0;
+        for (index, cell) in cells.iter().enumerate() {
+            let source = source_lines(cell.get("source"));
+            let Some(key) = (cursor..raw_lines.len())
+                .find(|&at| raw_lines[at].trim_start().starts_with("\"source\""))
+            else {
+                break;
+            };
+            cursor = key + 1;
+            if cell.get("cell_type").and_then(Value::as_str) != Some("code") {
+                continue;
+            }
+            if cell.get("source").is_some_and(Value::is_array) {
+                for (offset, text) in source.iter().enumerate() {
+                    let Some(at) = (cursor..raw_lines.len())
+                        .find(|&at| decode(raw_lines[at]).as_deref() == Some(text.as_str()))
+                    else {
+                        break;
+                    };
+                    notebook.lines.insert(
+                        at + 1,
+                        CellLine {
+                            cell: index,
+                            line: offset + 1,
+                        },
+                    );
+                    cursor = at + 1;
+                }
+            } else if !source.is_empty() {
+                // Source stored as one string sits on the key's own line.
+                notebook.lines.insert(
+                    key + 1,
+                    CellLine {
+                        cell: index,
+                        line: 1,
+                    },
+                );
+            }
+            notebook.cells.insert(
+                index,
+                source
+                    .iter()
+                    .flat_map(|text| text.lines())
+                    .map(str::to_string)
+                    .collect(),
+            );
+        }
+        Some(notebook)
+    }
+
+    /// Cell and cell line for the 1-based `json_line` of the notebook file.
+    pub fn locate(&self, json_line: usize) -> Option<CellLine> {
+        self.lines.get(&json_line).copied()
+    }
+
+    /// Whether no JSON line could be mapped, e.g. because the file is not pretty-printed.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Source lines of code cell `cell`.
+    pub fn cell_lines(&self, cell: usize) -> Option<&[String]> {
+        self.cells.get(&cell).map(Vec::as_slice)
+    }
+}
+
+/// A cell's `source`, which nbformat allows as one string or a list of lines.
+fn source_lines(source: Option<&Value>) -> Vec<String> {
+    match source {
+        Some(Value::String(text)) => vec![text.clone()],
+        Some(Value::Array(lines)) => lines
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The string literal on one line of pretty-printed notebook JSON.
+fn decode(line: &str) -> Option<String> {
+    let literal = line.trim();
+    let literal = literal.strip_suffix(',').unwrap_or(literal);
+    serde_json::from_str(literal).ok()
+}
@@ -0,0 +1,236 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::OutlineArgs;
+use crate::config::RepoConfig;
+use crate::search;
+use crate::tools::ast_grep::{AstGrepDeclaration, AstGrepTool};
+use crate::tools::backend;
+
+/// Declarations whose nested functions are reported as methods.
+const CONTAINER_KINDS: &[&str] = &[
+    "class",
+    "enum",
+    "extension",
+    "impl",
+    "interface",
+    "protocol",
+    "struct",
+    "trait",
+    "type",
+];
+
+/// Hierarchical symbol outline of one file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Outline {
+    /// The file, relative to the repository root.
+    pub path: String,
+    pub language: String,
+    pub symbols: Vec<OutlineSymbol>,
+}
+
+/// A declaration and the declarations nested inside it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct OutlineSymbol {
+    pub name: String,
+    /// `function`, `method`, `struct`, `class`, `impl`, `trait`, `module`, …
+    pub kind: String,
+    /// 1-based, inclusive line range of the whole declaration.
+    pub start_line: usize,
+    pub end_line: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<OutlineSymbol>,
+}
+
+/// `(tree-sitter node kind, field holding the name, reported kind)`.
+type DeclarationKind = (&'static str, &'static str, &'static str);
+
+/// Node kinds outlined per grammar.
+pub(crate) fn declaration_kinds(language: &str) -> Option<&'static [DeclarationKind]> {
+    let kinds: &'static [DeclarationKind] = match language {
+        "rust" => &[
+            ("function_item", "name", "function"),
+            ("function_signature_item", "name", "function"),
+            ("struct_item", "name", "struct"),
+            ("enum_item", "name", "enum"),
+            ("union_item", "name", "struct"),
+            ("trait_item", "name", "trait"),
+            ("impl_item", "type", "impl"),
+            ("mod_item", "name", "module"),
+            ("type_item", "name", "type"),
+            ("const_item", "name", "constant"),
+            ("static_item", "name", "constant"),
+            ("macro_definition", "name", "macro"),
+        ],
+        "swift" => &[
+            ("class_declaration", "name", "class"),
+            ("protocol_declaration", "name", "protocol"),
+            ("function_declaration", "name", "function"),
+            ("protocol_function_declaration", "name", "function"),
+            ("typealias_declaration", "name", "type"),
+        ],
+        "typescript" | "tsx" => &[
+            ("class_declaration", "name", "class"),
+            ("abstract_class_declaration", "name", "class"),
+            ("interface_declaration", "name", "interface"),
+            ("enum_declaration", "name", "enum"),
+            ("type_alias_declaration", "name", "type"),
+            ("function_declaration", "name", "function"),
+            ("method_definition", "name", "method"),
+            ("method_signature", "name", "method"),
+            ("internal_module", "name", "module"),
+        ],
+        "javascript" => &[
+            ("class_declaration", "name", "class"),
+            ("function_declaration", "name", "function"),
+            ("generator_function_declaration", "name", "function"),
+            ("method_definition", "name", "method"),
+        ],
+        "python" => &[
+            ("class_definition", "name", "class"),
+            ("function_definition", "name", "function"),
+        ],
+        _ => return None,
+    };
+    Some(kinds)
+}
+
+/// Nest `declarations` by line range, reporting functions directly inside a type as methods.
+fn build_tree(
+    declarations: Vec<AstGrepDeclaration>,
+    kinds: &[DeclarationKind],
+) -> Vec<OutlineSymbol> {
+    let mut flat: Vec<OutlineSymbol> = declarations
+        .into_iter()
+        .filter_map(|declaration| {
+            let (_, _, kind) = kinds
+                .iter()
+                .find(|(node, _, _)| *node == declaration.kind)?;
+            Some(OutlineSymbol {
+                name: declaration.name,
+                kind: kind.to_string(),
+                start_line: declaration.start_line,
+                end_line: usize::max(declaration.start_line, declaration.end_line),
+                children: Vec::new(),
+            })
+        })
+        .collect();
+    flat.sort_by(|a, b| {
+        a.start_line
+            .cmp(&b.start_line)
+            .then(b.end_line.cmp(&a.end_line))
+    });
+    flat.dedup();
+
+    let mut roots = Vec::new();
+    let mut open: Vec<OutlineSymbol> = Vec::new();
+    for symbol in flat {
+        while open
+            .last()
+            .is_some_and(|parent| symbol.end_line > parent.end_line)
+        {
+            close(&mut open, &mut roots);
+        }
+        open.push(symbol);
+    }
+    while !open.is_empty() {
+        close(&mut open, &mut roots);
+    }
+    roots
+}
+
+/// Pop the innermost open declaration into its parent, or into `roots` at the top level.
+fn close(open: &mut Vec<OutlineSymbol>, roots: &mut Vec<OutlineSymbol>) {
+    let Some(mut symbol) = open.pop() else {
+        return;
+    };
+    match open.last_mut() {
+        Some(parent) => {
+            if symbol.kind == "function" && CONTAINER_KINDS.contains(&parent.kind.as_str()) {
+                symbol.kind = "method".to_string();
+            }
+            parent.children.push(symbol);
+        }
+        None => roots.push(symbol),
+    }
+}
+
+/// Outline `file` under `root` with AST-Grep, resolving the tool as searches do.
+pub async fn outline(
+    root: &Path,
+    file: &Path,
+    language: Option<&str>,
+    tool: &AstGrepTool,
+) -> Result<Outline> {
+    let absolute = if file.is_absolute() {
+        file.to_path_buf()
+    } else {
+        root.join(file)
+    };
+    let absolute = search::canonicalize_path(&absolute)
+        .with_context(|| format!("failed to resolve {}", file.display()))?;
+    let relative = absolute
+        .strip_prefix(root)
+        .map(Path::to_path_buf)
+        .with_context(|| format!("{} is outside {}", file.display(), root.display()))?;
+
+    let language = match language.map(str::trim).filter(|lang| !lang.is_empty()) {
+        Some(language) => language.to_ascii_lowercase(),
+        None => match search::detect_language_from_path(&relative) {
+            Some("jsx") => "javascript".to_string(),
+            Some(language) => language.to_string(),
+            None => anyhow::bail!(
+                "cannot infer the language of {}; pass --language",
+                relative.display()
+            ),
+        },
+    };
+    if relative
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+    {
+        anyhow::bail!("outline does not support notebooks");
+    }
+    let Some(kinds) = declaration_kinds(&language) else {
+        anyhow::bail!(
+            "outline does not support language `{language}` (supported: rust, swift, typescript, tsx, javascript, python)"
+        );
+    };
+
+    let rules: Vec<(&str, &str)> = kinds
+        .iter()
+        .map(|(node, field, _)| (*node, *field))
+        .collect();
+    let declarations = tool
+        .declarations(root, &relative, &language, &rules)
+        .await
+        .context("ast-grep outline failed")?;
+
+    Ok(Outline {
+        path: relative.to_string_lossy().replace('\\', "/"),
+        language,
+        symbols: build_tree(declarations, kinds),
+    })
+}
+
+/// Entry point for `swe-grep outline`.
+pub async fn run(args: OutlineArgs) -> Result<Outline> {
+    let root = args
+        .path
+        .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
+    let root = search::canonicalize_path(&root).with_context(|| {
+        format!(
+            "failed to canonicalize repository root path: {}",
+            root.display()
+        )
+    })?;
+    let tools = RepoConfig::load(&root)?.tools.resolve(&root, &args.tools)?;
+    let tool = AstGrepTool::new(Duration::from_secs(args.timeout_secs), usize::MAX)
+        .with_settings(tools.ast_grep.clone())
+        .with_retry(tools.retry_policy())
+        .with_backend(tools.backend(backend::process()));
+    outline(&root, &args.file, args.language.as_deref(), &tool).await
+}
@@ -0,0 +1,106 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+
+/// Where GitHub and GitLab look for the ownership file, in lookup order.
+pub(crate) const CODEOWNERS_LOCATIONS: &[&str] = &[
+    "CODEOWNERS",
+    ".github/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+    "docs/CODEOWNERS",
+];
+
+/// Ownership rules from a repository's `CODEOWNERS` file.
+///
+/// Patterns follow gitignore syntax and the last matching line wins, so a later line without
+/// owners leaves its paths unowned. GitLab `[Section]` headers are skipped.
+pub struct CodeOwners {
+    rules: Vec<Rule>,
+    /// Search root relative to the top level; hit paths are matched below it.
+    prefix: PathBuf,
+}
+
+struct Rule {
+    matcher: Gitignore,
+    owners: Vec<String>,
+}
+
+impl CodeOwners {
+    /// Read the first `CODEOWNERS` file found under the repository top level `toplevel`, for
+    /// a search rooted at `prefix` inside it; `None` when there is none.
+    pub fn load(toplevel: &Path, prefix: &Path) -> Result<Option<Self>> {
+        let Some(path) = CODEOWNERS_LOCATIONS
+            .iter()
+            .map(|location| toplevel.join(location))
+            .find(|path| path.is_file())
+        else {
+            return Ok(None);
+        };
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Ok(Some(Self {
+            prefix: prefix.to_path_buf(),
+            ..Self::parse(toplevel, &raw)
+        }))
+    }
+
+    pub fn parse(root: &Path, raw: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in raw.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(['#', '[', '!']) {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let Some(pattern) = fields.next() else {
+                continue;
+            };
+            let owners: Vec<String> = fields
+                .take_while(|field| !field.starts_with('#'))
+                .map(str::to_string)
+                .collect();
+            let mut builder = GitignoreBuilder::new(root);
+            if let Err(err) = builder.add_line(None, pattern) {
+                tracing::warn!(pattern, error = %err, "skipping invalid CODEOWNERS pattern");
+                continue;
+            }
+            match builder.build() {
+                Ok(matcher) => rules.push(Rule { matcher, owners }),
+                Err(err) => {
+                    tracing::warn!(pattern, error = %err, "skipping invalid CODEOWNERS pattern");
+                }
+            }
+        }
+        Self {
+            rules,
+            prefix: PathBuf::new(),
+        }
+    }
+
+    /// Owners of `path` (relative to the search root); empty when no rule assigns any.
+    pub fn owners_for(&self, path: &Path) -> &[String] {
+        let path = self.prefix.join(path);
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                rule.matcher
+                    .matched_path_or_any_parents(&path, false)
+                    .is_ignore()
+            })
+            .map(|rule| rule.owners.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// Whether `owner` is among `owners`, ignoring case and a leading `@`.
+pub fn is_owned_by(owners: &[String], owner: &str) -> bool {
+    let wanted = owner.trim().trim_start_matches('@');
+    owners.iter().any(|candidate| {
+        candidate
+            .trim_start_matches('@')
+            .eq_ignore_ascii_case(wanted)
+    })
+}
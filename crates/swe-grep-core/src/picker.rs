@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::process::Stdio;
+
+use anyhow::{Context, Result};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::search::{SearchSummary, TopHit};
+
+/// Environment variable naming the interactive picker `--exec` feeds `--format picker` lines to.
+pub const PICKER_ENV: &str = "SWE_GREP_PICKER";
+/// Picker used when `SWE_GREP_PICKER` is unset.
+pub const DEFAULT_PICKER: &str = "fzf";
+
+/// The hits as `--format picker` prints them: one `path<TAB>line<TAB>snippet` line each, in
+/// summary order, so `fzf --delimiter '\t'` can preview `{1}` at `{2}`.
+pub fn render(summary: &SearchSummary) -> String {
+    let mut out = String::new();
+    for hit in &summary.top_hits {
+        out.push_str(&line(hit));
+        out.push('\n');
+    }
+    out
+}
+
+/// A hit's picker line. The path is joined with the hit's root in multi-root searches, and
+/// tabs and line breaks in the snippet become spaces so every hit stays one record.
+pub fn line(hit: &TopHit) -> String {
+    let snippet: String = hit
+        .snippet
+        .as_deref()
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .map(|c| {
+            if matches!(c, '\t' | '\n' | '\r') {
+                ' '
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("{}\t{}\t{snippet}", path(hit), hit.line)
+}
+
+fn path(hit: &TopHit) -> String {
+    match &hit.root {
+        Some(root) => Path::new(root).join(&hit.path).display().to_string(),
+        None => hit.path.clone(),
+    }
+}
+
+/// Let the user choose a hit in `picker` (a command line such as `fzf --height 40%`) and return
+/// it; `None` when the picker was dismissed or printed nothing.
+pub async fn choose<'a>(summary: &'a SearchSummary, picker: &str) -> Result<Option<&'a TopHit>> {
+    let argv = split_command(picker)?;
+    let mut child = Command::new(&argv[0])
+        .args(&argv[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to start picker `{}`", argv[0]))?;
+    let mut stdin = child.stdin.take().context("picker stdin unavailable")?;
+    let lines = render(summary);
+    // A picker may exit before reading every line; its selection is all that matters.
+    let _ = stdin.write_all(lines.as_bytes()).await;
+    drop(stdin);
+    let output = child
+        .wait_with_output()
+        .await
+        .context("failed to wait for picker")?;
+    let chosen = String::from_utf8_lossy(&output.stdout);
+    let Some(chosen) = chosen.lines().next().filter(|line| !line.is_empty()) else {
+        return Ok(None);
+    };
+    Ok(summary.top_hits.iter().find(|hit| line(hit) == chosen))
+}
+
+/// Run `template` for `hit` in `cwd`, with `{path}`, `{line}`, `{root}`, and `{snippet}`
+/// replaced in each argument. The template is split into arguments on whitespace, keeping
+/// quoted runs together, and runs without a shell.
+pub async fn exec(template: &str, hit: &TopHit, cwd: &Path) -> Result<()> {
+    let argv: Vec<String> = split_command(template)?
+        .iter()
+        .map(|arg| expand(arg, hit, cwd))
+        .collect();
+    let status = Command::new(&argv[0])
+        .args(&argv[1..])
+        .current_dir(cwd)
+        .status()
+        .await
+        .with_context(|| format!("failed to run `{}`", argv[0]))?;
+    if !status.success() {
+        anyhow::bail!("`{}` exited with {status}", argv[0]);
+    }
+    Ok(())
+}
+
+/// `arg` with the hit's placeholders filled in.
+pub fn expand(arg: &str, hit: &TopHit, cwd: &Path) -> String {
+    let root = hit
+        .root
+        .clone()
+        .unwrap_or_else(|| cwd.display().to_string());
+    arg.replace("{path}", &path(hit))
+        .replace("{line}", &hit.line.to_string())
+        .replace("{root}", &root)
+        .replace(
+            "{snippet}",
+            hit.snippet.as_deref().unwrap_or_default().trim(),
+        )
+}
+
+/// Split a command line into arguments on whitespace; single or double quotes group a run
+/// that contains whitespace and are removed.
+pub fn split_command(command: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        anyhow::bail!("unterminated quote in `{command}`");
+    }
+    if in_arg {
+        args.push(current);
+    }
+    if args.is_empty() {
+        anyhow::bail!("empty command");
+    }
+    Ok(args)
+}
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+
+use crate::identifier;
+use crate::rust_modules::RustPath;
+
+/// Quote characters that turn a symbol into a string literal, e.g. `"failed to open"`.
+const QUOTES: &[char] = &['"', '\'', '`'];
+/// Characters that only appear in code, never in identifiers or prose, e.g. the `(` in
+/// `login_user(` or the `<` in `Vec<String>`.
+const OPERATORS: &[char] = &[
+    '(', ')', '[', ']', '{', '}', '<', '>', '=', '+', '!', '&', '|', ',', ';', '%', '^', '~',
+];
+/// Wildcards of a glob symbol such as `handle_*_event`.
+const GLOB_CHARS: &[char] = &['*', '?'];
+
+/// How a search is carried out, chosen from the shape of the symbol.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Identifier rewrites, the literal fast path, and AST disambiguation.
+    #[default]
+    Identifier,
+    /// A Rust path such as `crate::auth::login_user`: its last segment is searched as an
+    /// identifier, starting in the module the path names.
+    ModulePath,
+    /// Words separated by whitespace, matched as written by ripgrep's `--fixed-strings` and
+    /// ranked by how exactly each line holds them.
+    Phrase,
+    /// A quoted string, matched inside string literals before anywhere else.
+    StringLiteral,
+    /// An identifier with `*` or `?` wildcards, matched against whole identifiers; the
+    /// identifiers found are reported in `matched_identifiers`.
+    Glob,
+    /// Code with operators or `$NAME` metavariables, matched literally by ripgrep and as an
+    /// ast-grep pattern.
+    Structural,
+}
+
+impl Strategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Identifier => "identifier",
+            Self::ModulePath => "module_path",
+            Self::Phrase => "phrase",
+            Self::StringLiteral => "string_literal",
+            Self::Glob => "glob",
+            Self::Structural => "structural",
+        }
+    }
+
+    /// Whether the search runs the identifier-centric pipeline: rewrites per language, the
+    /// symbol store, fd file-name discovery, alias following, and case variants.
+    pub fn is_identifier(self) -> bool {
+        matches!(self, Self::Identifier | Self::ModulePath)
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether rewrites are anchored at word boundaries unless `--word` says otherwise.
+    pub fn bounds_words(self) -> bool {
+        matches!(self, Self::Glob)
+    }
+}
+
+/// The strategy for a symbol and the text it searches for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueryPlan {
+    pub strategy: Strategy,
+    /// The symbol without its quotes for [`Strategy::StringLiteral`]; otherwise trimmed.
+    pub text: String,
+    /// The parsed path for [`Strategy::ModulePath`].
+    pub rust_path: Option<RustPath>,
+}
+
+impl QueryPlan {
+    /// Inspect `symbol` in order: quotes, a Rust path, `$NAME` metavariables, wildcards,
+    /// operators, then whitespace. Anything else is an identifier, as are symbols with `.`,
+    /// `-`, or `/` such as `config.load` or `--max-matches`, which are searched as written.
+    /// `rust_paths` is false when the language rules out Rust.
+    pub fn for_symbol(symbol: &str, rust_paths: bool) -> Self {
+        let trimmed = symbol.trim();
+        let plan = |strategy: Strategy, text: &str| Self {
+            strategy,
+            text: text.to_string(),
+            rust_path: None,
+        };
+        if let Some(inner) = unquote(trimmed) {
+            return plan(Strategy::StringLiteral, inner);
+        }
+        if rust_paths && let Some(path) = RustPath::parse(trimmed) {
+            return Self {
+                rust_path: Some(path),
+                ..plan(Strategy::ModulePath, trimmed)
+            };
+        }
+        if has_metavariable(trimmed) {
+            return plan(Strategy::Structural, trimmed);
+        }
+        if is_glob(trimmed) {
+            return plan(Strategy::Glob, trimmed);
+        }
+        if trimmed.contains(OPERATORS) || trimmed.contains(GLOB_CHARS) {
+            return plan(Strategy::Structural, trimmed);
+        }
+        if trimmed.contains(char::is_whitespace) {
+            return plan(Strategy::Phrase, trimmed);
+        }
+        plan(Strategy::Identifier, trimmed)
+    }
+}
+
+/// The text between matching quotes around the whole symbol, if it is not empty.
+fn unquote(symbol: &str) -> Option<&str> {
+    let quote = symbol.chars().next().filter(|c| QUOTES.contains(c))?;
+    let inner = symbol.strip_prefix(quote)?.strip_suffix(quote)?;
+    (!inner.trim().is_empty()).then_some(inner)
+}
+
+/// An ast-grep metavariable: `$` followed by an uppercase letter, `_`, or another `$`, as in
+/// `$ARGS` or `$$$`.
+fn has_metavariable(symbol: &str) -> bool {
+    symbol.match_indices('$').any(|(index, _)| {
+        symbol[index + 1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_uppercase() || c == '_' || c == '$')
+    })
+}
+
+/// Identifier characters and at least one wildcard, with something besides wildcards.
+fn is_glob(symbol: &str) -> bool {
+    symbol.contains(GLOB_CHARS)
+        && symbol.chars().any(identifier::is_continue)
+        && symbol
+            .chars()
+            .all(|c| identifier::is_continue(c) || GLOB_CHARS.contains(&c))
+}
+
+/// Whether the identifier `name` matches `glob`, where `*` stands for any run of identifier
+/// characters and `?` for exactly one.
+pub fn glob_matches(glob: &str, name: &str, ignore_case: bool) -> bool {
+    let fold = |c: char| {
+        if ignore_case {
+            c.to_lowercase().next().unwrap_or(c)
+        } else {
+            c
+        }
+    };
+    let glob: Vec<char> = glob.chars().map(fold).collect();
+    let name: Vec<char> = name.chars().map(fold).collect();
+    let (mut g, mut n) = (0, 0);
+    // Where the last `*` was seen and how much of `name` it has swallowed so far.
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, n));
+                g += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                g += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_g, star_n)) => {
+                    g = star_g + 1;
+                    n = star_n + 1;
+                    star = Some((star_g, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|&c| c == '*')
+}
+
+/// The identifiers in `text` that match `glob`, in order of appearance.
+pub fn glob_identifiers<'a>(
+    glob: &'a str,
+    text: &'a str,
+    ignore_case: bool,
+) -> impl Iterator<Item = &'a str> + 'a {
+    text.split(|c: char| !identifier::is_continue(c))
+        .filter(move |word| !word.is_empty() && glob_matches(glob, word, ignore_case))
+}
@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::search::Warning;
+
+/// Directory below the repository root holding `.wasm` plugins, run in file name order.
+pub const PLUGIN_DIR: &str = ".swe-grep/plugins";
+
+/// Instructions a plugin may execute per call before it is stopped.
+pub const PLUGIN_FUEL: u64 = 10_000_000;
+
+/// Linear memory a plugin may grow to.
+pub const PLUGIN_MAX_MEMORY_BYTES: usize = 16 << 20;
+
+/// The hit handed to a plugin; mirrors the `hit` record in `wit/plugin.wit`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluginHit {
+    pub path: String,
+    pub line: u32,
+    pub score: f32,
+    pub origin: String,
+    pub snippet: String,
+    pub language: Option<String>,
+}
+
+/// `.wasm` files in the plugin directory of `root`, sorted by name; empty when it is absent.
+pub fn plugin_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let dir = root.join(PLUGIN_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(&dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry
+            .with_context(|| format!("failed to read {}", dir.display()))?
+            .path();
+        if path.is_file() && path.extension().is_some_and(|ext| ext == "wasm") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Compiled plugins of one search, each with its own sandboxed store.
+#[derive(Default)]
+pub struct Plugins {
+    #[cfg(feature = "plugins")]
+    plugins: Vec<runtime::Plugin>,
+    /// `plugin_failed` warnings not yet handed to the search.
+    warnings: Vec<Warning>,
+}
+
+impl Plugins {
+    /// Compile `files`; a module that fails to compile or instantiate fails the search. Without
+    /// the `plugins` feature the files are ignored with a log warning.
+    pub fn load(files: &[PathBuf]) -> Result<Self> {
+        #[cfg(feature = "plugins")]
+        {
+            runtime::load(files).map(|plugins| Self {
+                plugins,
+                warnings: Vec::new(),
+            })
+        }
+        #[cfg(not(feature = "plugins"))]
+        {
+            if !files.is_empty() {
+                tracing::warn!(
+                    plugins = files.len(),
+                    "plugin support not compiled; ignoring {PLUGIN_DIR}"
+                );
+            }
+            Ok(Self::default())
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        #[cfg(feature = "plugins")]
+        {
+            self.plugins.is_empty()
+        }
+        #[cfg(not(feature = "plugins"))]
+        {
+            true
+        }
+    }
+
+    /// Run `hit` through every plugin in order: `filter`, then `score` for hits it keeps.
+    /// Returns `false` once a plugin drops the hit. A plugin that traps, runs out of fuel, or
+    /// exceeds its memory is disabled for the rest of the search with a `plugin_failed` warning,
+    /// leaving the hit as it was given.
+    #[cfg_attr(not(feature = "plugins"), allow(unused_variables))]
+    pub fn apply(&mut self, hit: &mut PluginHit) -> bool {
+        #[cfg(feature = "plugins")]
+        for plugin in &mut self.plugins {
+            match plugin.apply(hit) {
+                Ok(true) => {}
+                Ok(false) => return false,
+                Err(warning) => self.warnings.push(warning),
+            }
+        }
+        true
+    }
+
+    /// Warnings raised since the last call.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+}
+
+#[cfg(feature = "plugins")]
+mod runtime {
+    use std::path::{Path, PathBuf};
+
+    use anyhow::{Context, Result};
+    use wasmtime::component::{Component, Linker};
+    use wasmtime::{Config, Engine, Store, StoreLimits, StoreLimitsBuilder};
+
+    use super::{PLUGIN_FUEL, PLUGIN_MAX_MEMORY_BYTES, PluginHit};
+    use crate::search::{Warning, WarningKind};
+
+    mod bindings {
+        wasmtime::component::bindgen!({
+            path: "wit/plugin.wit",
+            world: "plugin",
+        });
+    }
+
+    pub(super) struct Plugin {
+        name: String,
+        store: Store<StoreLimits>,
+        bindings: bindings::Plugin,
+        /// Set after a failed call; a trapped instance cannot be entered again.
+        failed: bool,
+    }
+
+    pub(super) fn load(files: &[PathBuf]) -> Result<Vec<Plugin>> {
+        if files.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config).context("failed to start the plugin runtime")?;
+        // No imports are linked, so plugins have no access to the host.
+        let linker = Linker::new(&engine);
+        files
+            .iter()
+            .map(|path| {
+                Plugin::new(&engine, &linker, path)
+                    .with_context(|| format!("failed to load plugin {}", path.display()))
+            })
+            .collect()
+    }
+
+    impl Plugin {
+        fn new(engine: &Engine, linker: &Linker<StoreLimits>, path: &Path) -> Result<Self> {
+            let component = Component::from_file(engine, path)?;
+            let limits = StoreLimitsBuilder::new()
+                .memory_size(PLUGIN_MAX_MEMORY_BYTES)
+                .build();
+            let mut store = Store::new(engine, limits);
+            store.limiter(|limits| limits);
+            store.set_fuel(PLUGIN_FUEL)?;
+            let bindings = bindings::Plugin::instantiate(&mut store, &component, linker)?;
+            Ok(Self {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                store,
+                bindings,
+                failed: false,
+            })
+        }
+
+        /// Filter and rescore `hit`; `Ok(false)` drops it.
+        pub(super) fn apply(&mut self, hit: &mut PluginHit) -> Result<bool, Warning> {
+            if self.failed {
+                return Ok(true);
+            }
+            match self.call(hit) {
+                Ok(Some(score)) => {
+                    hit.score = score;
+                    Ok(true)
+                }
+                Ok(None) => Ok(false),
+                Err(err) => {
+                    self.failed = true;
+                    tracing::warn!(plugin = %self.name, error = %err, "plugin_failed");
+                    Err(Warning::new(
+                        WarningKind::PluginFailed,
+                        format!(
+                            "plugin `{}` failed and was skipped for the rest of the search: {err:#}",
+                            self.name
+                        ),
+                    )
+                    .with_tool("plugin"))
+                }
+            }
+        }
+
+        /// The new score, or `None` when the plugin filters the hit out.
+        fn call(&mut self, hit: &PluginHit) -> Result<Option<f32>> {
+            let record = bindings::Hit {
+                path: hit.path.clone(),
+                line: hit.line,
+                score: hit.score,
+                origin: hit.origin.clone(),
+                snippet: hit.snippet.clone(),
+                language: hit.language.clone(),
+            };
+            self.store.set_fuel(PLUGIN_FUEL)?;
+            if !self.bindings.call_filter(&mut self.store, &record)? {
+                return Ok(None);
+            }
+            self.store.set_fuel(PLUGIN_FUEL)?;
+            let score = self.bindings.call_score(&mut self.store, &record)?;
+            if !score.is_finite() {
+                anyhow::bail!("score returned {score}");
+            }
+            Ok(Some(score))
+        }
+    }
+}
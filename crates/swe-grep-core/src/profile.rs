@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config::RepoConfig;
+
+/// Profiles every build knows about; `.swe-grep.toml` may tune these or add its own.
+pub const BUILTIN_PROFILES: &[&str] = &["fast", "thorough", "docs", "ci"];
+
+/// Named bundle of search options selected with `--profile` or a request's `profile` field.
+///
+/// Unset fields keep the regular defaults. Explicit options always beat the profile: numbers
+/// given on the command line or in a request replace the profile's, `--enable-*` flags switch a
+/// stage on, and `--disable-*` flags switch it off.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchProfile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_matches: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_fd: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_ast_grep: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_rga: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enable_index: Option<bool>,
+    /// Retry repository-wide when the scoped probe finds nothing.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub escalate: Option<bool>,
+    /// Number of hits kept in `top_hits`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_hits: Option<usize>,
+}
+
+impl SearchProfile {
+    /// The bundle shipped under `name`, if it is one of [`BUILTIN_PROFILES`].
+    pub fn builtin(name: &str) -> Option<Self> {
+        let profile = match name {
+            // Interactive lookups: scoped probe only, no AST pass, a short answer.
+            "fast" => Self {
+                timeout_secs: Some(1),
+                max_matches: Some(10),
+                use_fd: Some(true),
+                use_ast_grep: Some(false),
+                enable_rga: Some(false),
+                enable_index: Some(false),
+                escalate: Some(false),
+                top_hits: Some(3),
+            },
+            // Every stage and fallback, with room for slow tools on large trees.
+            "thorough" => Self {
+                timeout_secs: Some(10),
+                max_matches: Some(50),
+                use_fd: Some(true),
+                use_ast_grep: Some(true),
+                enable_rga: Some(true),
+                enable_index: Some(true),
+                escalate: Some(true),
+                top_hits: Some(10),
+            },
+            // Prose and config files: ripgrep-all on, AST disambiguation off.
+            "docs" => Self {
+                timeout_secs: Some(5),
+                max_matches: Some(30),
+                use_fd: Some(true),
+                use_ast_grep: Some(false),
+                enable_rga: Some(true),
+                enable_index: Some(false),
+                escalate: Some(true),
+                top_hits: Some(10),
+            },
+            // Loaded CI runners: generous timeouts so results do not depend on machine load.
+            "ci" => Self {
+                timeout_secs: Some(30),
+                max_matches: Some(20),
+                use_fd: Some(true),
+                use_ast_grep: Some(true),
+                enable_rga: Some(false),
+                enable_index: Some(false),
+                escalate: Some(true),
+                top_hits: Some(5),
+            },
+            _ => return None,
+        };
+        Some(profile)
+    }
+
+    /// Resolve `name` to its built-in bundle with any `[profiles.<name>]` fields laid on top.
+    pub fn resolve(name: &str, configured: &BTreeMap<String, SearchProfile>) -> Result<Self> {
+        let builtin = Self::builtin(name);
+        let overrides = configured.get(name);
+        if builtin.is_none() && overrides.is_none() {
+            let mut known: Vec<&str> = BUILTIN_PROFILES.to_vec();
+            known.extend(
+                configured
+                    .keys()
+                    .map(String::as_str)
+                    .filter(|name| !BUILTIN_PROFILES.contains(name)),
+            );
+            anyhow::bail!(
+                "unknown profile `{name}`; expected one of {}",
+                known.join(", ")
+            );
+        }
+        Ok(builtin.unwrap_or_default().overlay(overrides))
+    }
+
+    /// Resolve `name` (when given) against the config of the repository at `root`.
+    pub fn load(root: &Path, name: Option<&str>) -> Result<Self> {
+        match name {
+            Some(name) => Self::resolve(name, &RepoConfig::load(root)?.profiles),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn overlay(self, other: Option<&Self>) -> Self {
+        let Some(other) = other else {
+            return self;
+        };
+        Self {
+            timeout_secs: other.timeout_secs.or(self.timeout_secs),
+            max_matches: other.max_matches.or(self.max_matches),
+            use_fd: other.use_fd.or(self.use_fd),
+            use_ast_grep: other.use_ast_grep.or(self.use_ast_grep),
+            enable_rga: other.enable_rga.or(self.enable_rga),
+            enable_index: other.enable_index.or(self.enable_index),
+            escalate: other.escalate.or(self.escalate),
+            top_hits: other.top_hits.or(self.top_hits),
+        }
+    }
+}
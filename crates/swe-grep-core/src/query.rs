@@ -0,0 +1,122 @@
+use std::net::IpAddr;
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use crate::cli::{OutputFormat, QueryArgs};
+use crate::search::SearchSummary;
+
+#[derive(Deserialize)]
+struct QueryResponse {
+    summary: SearchSummary,
+}
+
+/// The `message` of a failed request, as every HTTP endpoint reports it.
+#[derive(Deserialize)]
+struct QueryError {
+    message: String,
+}
+
+/// Send the search to `--server`'s `POST /search` and return the summary it answers with.
+///
+/// Only the HTTP API is spoken, over TLS for `https://` servers; a `--token` is never sent over
+/// plain `http://` to another machine.
+pub async fn run(args: &QueryArgs) -> Result<SearchSummary> {
+    let base = args.server.trim_end_matches('/');
+    let server = Url::parse(base)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some());
+    let Some(server) = server else {
+        anyhow::bail!(
+            "--server must be an http(s) URL of the server's HTTP API, got `{}`; query does not \
+             speak gRPC",
+            args.server
+        );
+    };
+    if args.token.is_some() && server.scheme() == "http" && !is_loopback(&server) {
+        anyhow::bail!(
+            "refusing to send --token in cleartext to `{}`; use an https:// URL",
+            args.server
+        );
+    }
+    let endpoint = format!("{base}/search");
+    let mut request = reqwest::Client::new()
+        .post(endpoint.as_str())
+        .json(&request_body(args));
+    if let Some(token) = &args.token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("failed to reach {endpoint}"))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<QueryError>(&body)
+            .map(|error| error.message)
+            .unwrap_or(body);
+        anyhow::bail!("{endpoint} returned {status}: {}", message.trim());
+    }
+    let payload: QueryResponse = response
+        .json()
+        .await
+        .with_context(|| format!("failed to decode search response from {endpoint}"))?;
+    Ok(payload.summary)
+}
+
+/// Whether `url` points at this machine: `localhost` or a loopback address.
+fn is_loopback(url: &Url) -> bool {
+    url.host_str().is_some_and(|host| {
+        host.eq_ignore_ascii_case("localhost")
+            || host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback())
+    })
+}
+
+/// The `POST /search` body for `args`. Options left unset are sent as `null`, so the server's
+/// defaults and `--profile` apply as they would to any other client. `--fields` is applied to
+/// the full summary locally, like the other output options.
+fn request_body(args: &QueryArgs) -> Value {
+    let flag = |enabled: bool| enabled.then_some(true);
+    json!({
+        "symbol": args.symbol,
+        "root": args.root,
+        "language": args.language,
+        "rg_types": args.rg_types,
+        "rg_type_adds": args.rg_type_adds,
+        "config": flag(args.config),
+        "profile": args.profile,
+        "owner": args.owner,
+        "case": args.case.and_then(value_name),
+        "word": args.word,
+        "timeout_secs": args.timeout_secs,
+        "max_matches": args.max_matches,
+        "max_scope_files": args.max_scope_files,
+        "latency_target_ms": args.latency_target_ms,
+        "context_before": args.context_before,
+        "context_after": args.context_after,
+        "body": flag(args.body),
+        "explain": flag(args.explain),
+        "highlight": flag(args.highlight),
+        "link_format": args.link_format.and_then(value_name),
+        "permalinks": flag(args.permalinks),
+        "sort": args.sort.and_then(value_name),
+        "deterministic": flag(args.deterministic || args.format == Some(OutputFormat::Snapshot)),
+        "priority": args.priority.and_then(value_name),
+        "tenant": args.tenant,
+    })
+}
+
+/// The name an option value has on the command line, which is also its name over HTTP.
+fn value_name<T: ValueEnum>(value: T) -> Option<String> {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+}
@@ -0,0 +1,215 @@
+//! Secret redaction for text that leaves the engine: snippets, context windows, and bodies.
+
+/// Replacement for every redacted secret.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Token prefixes of well-known credential formats, with the characters that must follow.
+const PREFIXED_TOKENS: &[(&str, usize)] = &[
+    // AWS access key IDs: the prefix and 16 uppercase letters or digits.
+    ("AKIA", 16),
+    ("ASIA", 16),
+    ("ghp_", 30),
+    ("gho_", 30),
+    ("ghu_", 30),
+    ("ghs_", 30),
+    ("ghr_", 30),
+    ("github_pat_", 20),
+    ("glpat-", 20),
+    ("xoxa-", 10),
+    ("xoxb-", 10),
+    ("xoxp-", 10),
+    ("xoxr-", 10),
+    ("xoxs-", 10),
+    ("sk_live_", 16),
+    ("rk_live_", 16),
+    ("sk_test_", 16),
+    ("AIza", 30),
+    ("sk-", 20),
+    ("npm_", 30),
+];
+/// Names whose quoted value is treated as a secret (`password = "..."`).
+const SECRET_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "access_key",
+    "private_key",
+    "client_secret",
+];
+/// Shortest quoted value redacted after a secret key.
+const MIN_KEYED_SECRET: usize = 8;
+/// Shortest unrecognised token the entropy check looks at.
+const MIN_ENTROPY_TOKEN: usize = 32;
+/// Bits per character above which a long mixed-case alphanumeric token counts as random.
+const ENTROPY_THRESHOLD: f64 = 4.3;
+
+/// Replace secrets in `text` with [`REDACTED`], returning how many were replaced: private key
+/// blocks, tokens in well-known formats (AWS, GitHub, GitLab, Slack, Stripe, Google, npm,
+/// `sk-` API keys, JWTs), quoted values of keys such as `password` or `api_key`, and long
+/// random-looking tokens.
+pub fn redact(text: &mut String) -> usize {
+    let mut count = 0;
+    let mut output = String::with_capacity(text.len());
+    let mut in_private_key = false;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let ending = &line[content.len()..];
+        if content.contains("-----BEGIN") && content.contains("PRIVATE KEY-----") {
+            in_private_key = true;
+            count += 1;
+            output.push_str(line);
+            continue;
+        }
+        if in_private_key {
+            if content.contains("-----END") {
+                in_private_key = false;
+                output.push_str(line);
+            } else {
+                // Keep the line number of an expanded snippet (`12 MIIEv...`).
+                let digits = content.len()
+                    - content
+                        .trim_start_matches(|ch: char| ch.is_ascii_digit())
+                        .len();
+                let gutter = if digits > 0 && content[digits..].starts_with(' ') {
+                    digits + 1
+                } else {
+                    0
+                };
+                output.push_str(&content[..gutter]);
+                output.push_str(REDACTED);
+                output.push_str(ending);
+            }
+            continue;
+        }
+        let (redacted, found) = redact_line(content);
+        count += found;
+        output.push_str(&redacted);
+        output.push_str(ending);
+    }
+    if count > 0 {
+        *text = output;
+    }
+    count
+}
+
+/// [`redact`] for an optional text.
+pub fn redact_option(text: &mut Option<String>) -> usize {
+    text.as_mut().map_or(0, redact)
+}
+
+fn redact_line(line: &str) -> (String, usize) {
+    let mut count = 0;
+    let mut output = String::with_capacity(line.len());
+    let bytes = line.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        if !is_token_byte(bytes[index]) {
+            let start = index;
+            while index < bytes.len() && !is_token_byte(bytes[index]) {
+                index += 1;
+            }
+            output.push_str(&line[start..index]);
+            continue;
+        }
+        let start = index;
+        while index < bytes.len() && is_token_byte(bytes[index]) {
+            index += 1;
+        }
+        let token = &line[start..index];
+        if is_secret_token(token) || follows_secret_key(&line[..start], token) {
+            output.push_str(REDACTED);
+            count += 1;
+        } else {
+            output.push_str(token);
+        }
+    }
+    (output, count)
+}
+
+fn is_token_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-' | b'+' | b'/' | b'.')
+}
+
+/// Whether `token` is a credential in a known format or looks random enough to be one.
+fn is_secret_token(token: &str) -> bool {
+    let token = token.trim_end_matches('.');
+    let prefixed = PREFIXED_TOKENS.iter().any(|(prefix, min_len)| {
+        token.strip_prefix(prefix).is_some_and(|rest| {
+            rest.len() >= *min_len
+                && rest
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-'))
+        })
+    });
+    prefixed || is_jwt(token) || is_random(token)
+}
+
+/// `header.payload.signature`, each base64url, with a JSON header (`eyJ`).
+fn is_jwt(token: &str) -> bool {
+    let parts: Vec<&str> = token.split('.').collect();
+    parts.len() == 3
+        && parts[0].starts_with("eyJ")
+        && parts.iter().all(|part| {
+            part.len() >= 8
+                && part
+                    .bytes()
+                    .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-'))
+        })
+}
+
+/// A long token mixing upper- and lowercase letters and digits with high Shannon entropy;
+/// identifiers, paths, hex digests, and UUIDs fall short.
+fn is_random(token: &str) -> bool {
+    if token.len() < MIN_ENTROPY_TOKEN || token.contains('.') {
+        return false;
+    }
+    let bytes = token.as_bytes();
+    let mixed = bytes.iter().any(u8::is_ascii_uppercase)
+        && bytes.iter().any(u8::is_ascii_lowercase)
+        && bytes.iter().any(u8::is_ascii_digit);
+    mixed && entropy(bytes) >= ENTROPY_THRESHOLD
+}
+
+fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &byte in bytes {
+        counts[usize::from(byte)] += 1;
+    }
+    let len = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Whether `token` is the quoted value assigned to a secret key at the end of `prefix`
+/// (`password = "`, `"api_key": "`, `token: '`).
+fn follows_secret_key(prefix: &str, token: &str) -> bool {
+    if token.len() < MIN_KEYED_SECRET {
+        return false;
+    }
+    let Some(before_quote) = prefix.strip_suffix(['"', '\'']) else {
+        return false;
+    };
+    let Some(key) = before_quote
+        .trim_end()
+        .strip_suffix([':', '='])
+        .map(|key| key.trim_end().trim_end_matches(['"', '\'']))
+    else {
+        return false;
+    };
+    let key = key
+        .rsplit(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '-'))
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+        .replace('-', "_");
+    SECRET_KEYS.iter().any(|name| key.ends_with(name))
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use ulid::Ulid;
+
+use crate::cli::ShowArgs;
+use crate::search::{self, SearchSummary};
+
+/// Summaries kept per cache directory; the oldest are removed as new searches are recorded.
+pub const MAX_STORED_RESULTS: usize = 500;
+
+const EXTENSION: &str = ".json.zst";
+/// Layout of stored results; bump it and extend `load` when the envelope or summary changes in
+/// a way older readers would misread.
+pub const RESULT_VERSION: u32 = 1;
+
+/// A stored summary with the layout version it was written with. Results stored before
+/// versioning are bare summaries.
+#[derive(Serialize, Deserialize)]
+struct StoredResult<S> {
+    version: u32,
+    summary: S,
+}
+
+/// A search id that is malformed or has no stored summary.
+#[derive(Debug)]
+pub enum ResultError {
+    /// Not a ULID.
+    InvalidId(String),
+    /// Never recorded in this cache directory, or already pruned.
+    NotFound(String),
+    /// Stored by a newer swe-grep, in a layout this build cannot read.
+    NewerVersion { id: String, version: u32 },
+}
+
+impl fmt::Display for ResultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidId(id) => write!(f, "invalid search id `{id}`: expected a ULID"),
+            Self::NotFound(id) => write!(f, "no stored result for search id {id}"),
+            Self::NewerVersion { id, version } => write!(
+                f,
+                "stored result {id} was written by a newer swe-grep (version {version}, this \
+                 build reads {RESULT_VERSION}); show it with that version"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ResultError {}
+
+/// Directory holding the summaries of searches that use `cache_dir`.
+pub fn results_dir(cache_dir: &Path) -> PathBuf {
+    cache_dir.join("results")
+}
+
+/// Give `summary` a new search id and store it under `cache_dir`, pruning the oldest summaries
+/// beyond `MAX_STORED_RESULTS`. The id is assigned even when storing fails.
+pub fn record(cache_dir: &Path, summary: &mut SearchSummary) -> Result<()> {
+    let id = Ulid::new().to_string();
+    summary.search_id = Some(id.clone());
+
+    let dir = results_dir(cache_dir);
+    fs::create_dir_all(&dir)
+        .with_context(|| format!("failed to create results directory {}", dir.display()))?;
+    let path = dir.join(format!("{id}{EXTENSION}"));
+    let tmp_path = dir.join(format!("{id}{EXTENSION}.tmp"));
+    let file = fs::File::create(&tmp_path)
+        .with_context(|| format!("failed to create {}", tmp_path.display()))?;
+    let mut writer = zstd::Encoder::new(BufWriter::new(file), 0)
+        .context("failed to start compressing search result")?;
+    let stored = StoredResult {
+        version: RESULT_VERSION,
+        summary,
+    };
+    serde_json::to_writer(&mut writer, &stored).context("failed to serialize search result")?;
+    writer
+        .finish()
+        .and_then(|mut inner| inner.flush())
+        .with_context(|| format!("failed to flush {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, &path)
+        .with_context(|| format!("failed to move search result into place {}", path.display()))?;
+
+    prune(&dir);
+    Ok(())
+}
+
+/// The summary stored under `id` in `cache_dir`.
+pub fn load(cache_dir: &Path, id: &str) -> Result<SearchSummary> {
+    let id = id.trim();
+    let ulid = Ulid::from_string(id).map_err(|_| ResultError::InvalidId(id.to_string()))?;
+    let path = results_dir(cache_dir).join(format!("{ulid}{EXTENSION}"));
+    let contents = match fs::read(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ResultError::NotFound(ulid.to_string()).into());
+        }
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+    let json = zstd::decode_all(contents.as_slice())
+        .with_context(|| format!("failed to decompress {}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_slice(&json)
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    let version = value.get("version").and_then(serde_json::Value::as_u64);
+    let summary = match version {
+        Some(version) if version > u64::from(RESULT_VERSION) => {
+            return Err(ResultError::NewerVersion {
+                id: ulid.to_string(),
+                version: u32::try_from(version).unwrap_or(u32::MAX),
+            }
+            .into());
+        }
+        Some(_) => serde_json::from_value::<StoredResult<SearchSummary>>(value)
+            .map(|stored| stored.summary),
+        // Stored before versioning: the bare summary.
+        None => serde_json::from_value(value),
+    };
+    summary.with_context(|| format!("failed to parse {}", path.display()))
+}
+
+/// Remove the oldest summaries beyond `MAX_STORED_RESULTS`; ULIDs sort by creation time.
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.ends_with(EXTENSION))
+        .collect();
+    if names.len() <= MAX_STORED_RESULTS {
+        return;
+    }
+    names.sort();
+    let excess = names.len() - MAX_STORED_RESULTS;
+    for name in names.into_iter().take(excess) {
+        if let Err(err) = fs::remove_file(dir.join(&name)) {
+            tracing::warn!(file = %name, error = %err, "failed to prune stored search result");
+        }
+    }
+}
+
+/// Entry point for `swe-grep show`.
+pub async fn run(args: ShowArgs) -> Result<SearchSummary> {
+    let root = args
+        .path
+        .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
+    let root = search::canonicalize_path(&root).with_context(|| {
+        format!(
+            "failed to canonicalize repository root path: {}",
+            root.display()
+        )
+    })?;
+    let cache_dir = args
+        .cache_dir
+        .unwrap_or_else(|| root.join(".swe-grep-cache"));
+    load(&cache_dir, &args.id)
+}
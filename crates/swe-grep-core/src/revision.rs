@@ -0,0 +1,395 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use ignore::Match;
+use ignore::gitignore::Gitignore;
+
+use crate::cli::SearchArgs;
+use crate::config::CONFIG_FILE;
+use crate::owners::CODEOWNERS_LOCATIONS;
+use crate::plugins::PLUGIN_DIR;
+use crate::rust_modules::RustPath;
+use crate::search;
+use crate::tools::git::GitTool;
+
+/// Grepping a large revision and reading its blobs can take a while.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(120);
+const COMPLETE_MARKER: &str = ".complete";
+/// Folder of a commit's snapshots; renamed whenever exports gain files, so snapshots missing
+/// them are never reused.
+const SNAPSHOTS_DIR: &str = "sparse-v2";
+/// Ignore files a walk of the checkout would apply.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", ".rgignore", ".fdignore"];
+/// Manifests that scope Rust path and Swift target searches.
+const MANIFESTS: &[&str] = &["Cargo.toml", "Package.swift"];
+/// Keeps fd/rg walks of the working tree out of exported snapshots stored in the cache dir.
+const IGNORE_RULES: &str = "/revisions/\n";
+/// Snapshots kept per cache directory; the least recently used beyond this are removed.
+const MAX_SNAPSHOTS: usize = 16;
+/// Longest snapshot directory name derived from the search terms.
+const MAX_KEY_LEN: usize = 80;
+
+/// A read-only copy of the files a search can match in a specific commit, read from the
+/// object database.
+#[derive(Clone, Debug)]
+pub struct RevisionSnapshot {
+    /// Full commit id the snapshot was exported from.
+    pub commit: String,
+    /// Directory to search; mirrors the requested root inside the exported tree.
+    pub root: PathBuf,
+    /// The exported tree, mirroring the repository's top level.
+    pub toplevel: PathBuf,
+    /// `root` relative to `toplevel`.
+    pub prefix: PathBuf,
+    /// The requested root in the checkout the snapshot was exported from.
+    pub source_root: PathBuf,
+    /// Cache directory keyed by the commit so hints never leak between revisions.
+    pub cache_dir: PathBuf,
+    /// Default Tantivy index location for the snapshot.
+    pub index_dir: PathBuf,
+}
+
+/// Point `args` at an exported snapshot of `rev`, keeping hint state and the index per commit.
+///
+/// Relative log directories still resolve against the original repository root.
+pub async fn retarget(mut args: SearchArgs, rev: &str) -> Result<(SearchArgs, RevisionSnapshot)> {
+    let root = args
+        .path
+        .first()
+        .cloned()
+        .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
+    let root = search::canonicalize_path(&root).with_context(|| {
+        format!(
+            "failed to canonicalize repository root path: {}",
+            root.display()
+        )
+    })?;
+    let cache_root = args
+        .cache_dir
+        .clone()
+        .unwrap_or_else(|| root.join(".swe-grep-cache"));
+    let snapshot = materialize(&root, rev, &cache_root, &args.symbol).await?;
+
+    args.path = vec![snapshot.root.clone()];
+    args.cache_dir = Some(snapshot.cache_dir.clone());
+    args.index_dir = Some(
+        args.index_dir
+            .take()
+            .unwrap_or_else(|| snapshot.index_dir.clone()),
+    );
+    args.log_dir = args.log_dir.take().map(|dir| {
+        if dir.is_absolute() {
+            dir
+        } else {
+            root.join(dir)
+        }
+    });
+    args.rev = None;
+    Ok((args, snapshot))
+}
+
+/// Export the files of `rev` that can match `symbol` under
+/// `cache_dir/revisions/<commit>/sparse-v2/<terms>`.
+///
+/// Candidates are the files under `repo_root` containing every word of the symbol (see
+/// [`search_terms`]), found with `git grep` against the commit and written with
+/// `git cat-file --batch`, so the working tree and index are never touched and nothing else
+/// of the revision is checked out. The files a search reads besides its candidates come from
+/// the same commit: `.swe-grep.toml` and plugins, ignore files, `CODEOWNERS`, and the
+/// `Cargo.toml` and `Package.swift` manifests, plus every Rust source when the symbol is a Rust path so its
+/// modules resolve. Candidates the commit's `.gitignore` rules exclude are left out, as a walk
+/// of a checkout would skip them. Snapshots are immutable, so an existing complete export is
+/// reused as-is.
+pub async fn materialize(
+    repo_root: &Path,
+    rev: &str,
+    cache_dir: &Path,
+    symbol: &str,
+) -> Result<RevisionSnapshot> {
+    let git = GitTool::new(EXPORT_TIMEOUT);
+    let commit = git.rev_parse(repo_root, rev).await?;
+    let prefix = git.show_prefix(repo_root).await?;
+    let terms = search_terms(symbol);
+    let rust_sources = RustPath::parse(symbol).is_some();
+
+    let commit_dir = cache_dir.join("revisions").join(&commit);
+    let base = commit_dir
+        .join(SNAPSHOTS_DIR)
+        .join(snapshot_key(&terms, rust_sources));
+    let tree = base.join("tree");
+    let snapshot = RevisionSnapshot {
+        root: tree.join(&prefix),
+        toplevel: tree.clone(),
+        prefix: prefix.clone(),
+        source_root: repo_root.to_path_buf(),
+        cache_dir: commit_dir.join("state"),
+        index_dir: base.join("index"),
+        commit: commit.clone(),
+    };
+
+    let marker = base.join(COMPLETE_MARKER);
+    if marker.is_file() {
+        // Mark the snapshot as recently used so eviction keeps it.
+        if let Err(err) = fs::File::options()
+            .write(true)
+            .open(&marker)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+        {
+            tracing::debug!(path = %marker.display(), error = %err, "failed to touch snapshot");
+        }
+        return Ok(snapshot);
+    }
+
+    let ignore_file = cache_dir.join(".ignore");
+    if !ignore_file.exists() {
+        fs::create_dir_all(cache_dir)
+            .with_context(|| format!("failed to create cache directory {}", cache_dir.display()))?;
+        fs::write(&ignore_file, IGNORE_RULES)
+            .with_context(|| format!("failed to write {}", ignore_file.display()))?;
+    }
+
+    let candidates = git.grep_files(repo_root, &commit, &terms).await?;
+    let support: Vec<PathBuf> = git
+        .list_files(repo_root, &commit)
+        .await?
+        .into_iter()
+        .filter(|path| is_support_file(path, &prefix, rust_sources))
+        .collect();
+
+    let staging = base.join(format!("tree.tmp-{}", std::process::id()));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .with_context(|| format!("failed to clear {}", staging.display()))?;
+    }
+    // The root must exist even when nothing matched, so the search reports no hits.
+    fs::create_dir_all(staging.join(&prefix))
+        .with_context(|| format!("failed to create {}", staging.display()))?;
+
+    // Support files go first: the ignore rules among them decide which candidates to export.
+    let exported = async {
+        git.write_blobs(repo_root, &commit, &support, &staging)
+            .await?;
+        let exported: HashSet<&PathBuf> = support.iter().collect();
+        let candidates: Vec<PathBuf> = without_gitignored(&staging, candidates)
+            .into_iter()
+            .filter(|path| !exported.contains(path))
+            .collect();
+        git.write_blobs(repo_root, &commit, &candidates, &staging)
+            .await?;
+        Ok::<_, anyhow::Error>(candidates.len())
+    }
+    .await
+    .with_context(|| format!("failed to export files of {commit}"));
+    let candidates = match exported {
+        Ok(candidates) => candidates,
+        Err(err) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(err);
+        }
+    };
+
+    if tree.exists() {
+        // Another process finished the same export first; keep theirs.
+        let _ = fs::remove_dir_all(&staging);
+    } else {
+        fs::rename(&staging, &tree)
+            .with_context(|| format!("failed to move snapshot into place {}", tree.display()))?;
+    }
+    fs::write(&marker, &commit)
+        .with_context(|| format!("failed to mark snapshot {commit} complete"))?;
+    evict(&cache_dir.join("revisions"), &base);
+
+    tracing::info!(
+        commit = %commit,
+        files = candidates,
+        path = %tree.display(),
+        "materialized git revision"
+    );
+    Ok(snapshot)
+}
+
+/// Whether the search reads `path` (relative to the top level) even when it cannot match:
+/// config, plugins, and manifests under the searched `prefix`, Rust sources there when
+/// `rust_sources` is set, ignore files under the prefix or in a directory above it, and the
+/// repository's `CODEOWNERS`.
+fn is_support_file(path: &Path, prefix: &Path, rust_sources: bool) -> bool {
+    if CODEOWNERS_LOCATIONS
+        .iter()
+        .any(|location| path == Path::new(location))
+    {
+        return true;
+    }
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    if IGNORE_FILES.contains(&name) {
+        return path.parent().is_some_and(|dir| prefix.starts_with(dir))
+            || path.starts_with(prefix);
+    }
+    let Ok(relative) = path.strip_prefix(prefix) else {
+        return false;
+    };
+    relative == Path::new(CONFIG_FILE)
+        || (relative.parent() == Some(Path::new(PLUGIN_DIR))
+            && relative.extension().is_some_and(|ext| ext == "wasm"))
+        || MANIFESTS.contains(&name)
+        || (name == "project.pbxproj"
+            && relative
+                .parent()
+                .and_then(Path::extension)
+                .is_some_and(|ext| ext == "xcodeproj"))
+        || (rust_sources && relative.extension().is_some_and(|ext| ext == "rs"))
+}
+
+/// `paths` (relative to the top level) minus the ones the `.gitignore` files exported under
+/// `tree` exclude. The snapshot is not a git checkout, so walks of it would not apply them.
+fn without_gitignored(tree: &Path, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut matchers: HashMap<PathBuf, Option<Gitignore>> = HashMap::new();
+    paths
+        .into_iter()
+        .filter(|path| {
+            // The deepest `.gitignore` with an opinion wins, as in git.
+            for dir in path.ancestors().skip(1) {
+                let matcher = matchers.entry(dir.to_path_buf()).or_insert_with(|| {
+                    let file = tree.join(dir).join(".gitignore");
+                    file.is_file().then(|| Gitignore::new(&file).0)
+                });
+                let Some(matcher) = matcher else {
+                    continue;
+                };
+                match matcher.matched_path_or_any_parents(tree.join(path), false) {
+                    Match::Ignore(_) => return false,
+                    Match::Whitelist(_) => return true,
+                    Match::None => {}
+                }
+            }
+            true
+        })
+        .collect()
+}
+
+/// Remove the least recently used snapshots under `revisions` beyond [`MAX_SNAPSHOTS`], never
+/// `keep`, along with the hint state and directory of every commit left without a snapshot.
+///
+/// Snapshots still being exported have no marker yet and are left alone.
+fn evict(revisions: &Path, keep: &Path) {
+    let mut snapshots: Vec<(SystemTime, PathBuf)> = Vec::new();
+    let commits: Vec<PathBuf> = fs::read_dir(revisions)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    for commit_dir in &commits {
+        for entry in fs::read_dir(commit_dir.join(SNAPSHOTS_DIR))
+            .into_iter()
+            .flatten()
+            .flatten()
+        {
+            let used = entry
+                .path()
+                .join(COMPLETE_MARKER)
+                .metadata()
+                .and_then(|metadata| metadata.modified());
+            if let Ok(used) = used {
+                snapshots.push((used, entry.path()));
+            }
+        }
+    }
+    if snapshots.len() <= MAX_SNAPSHOTS {
+        return;
+    }
+
+    snapshots.sort_by_key(|(used, _)| std::cmp::Reverse(*used));
+    for (_, path) in snapshots.into_iter().skip(MAX_SNAPSHOTS) {
+        if path == keep {
+            continue;
+        }
+        match fs::remove_dir_all(&path) {
+            Ok(()) => tracing::debug!(path = %path.display(), "evicted revision snapshot"),
+            Err(err) => {
+                tracing::debug!(path = %path.display(), error = %err, "failed to evict snapshot")
+            }
+        }
+    }
+    for commit_dir in commits {
+        // Commits exported in an older layout have no snapshots folder at all.
+        let empty = match fs::read_dir(commit_dir.join(SNAPSHOTS_DIR)) {
+            Ok(mut entries) => entries.next().is_none(),
+            Err(err) => err.kind() == std::io::ErrorKind::NotFound,
+        };
+        if empty {
+            let _ = fs::remove_dir_all(&commit_dir);
+        }
+    }
+}
+
+/// Lowercased words a file must contain to match `symbol`: the alphanumeric runs of its last
+/// `::` segment, split at camelCase humps. Single characters narrow nothing and are dropped.
+///
+/// Every rewrite of an identifier (`fooBar`, `foo_bar`, `FOO_BAR`, ...) contains the same
+/// words, so a case-insensitive match on all of them never drops a file the search could hit.
+pub fn search_terms(symbol: &str) -> Vec<String> {
+    let segment = symbol.rsplit("::").next().unwrap_or(symbol);
+    let mut terms: Vec<String> = Vec::new();
+    for run in segment.split(|ch: char| !ch.is_alphanumeric()) {
+        let chars: Vec<char> = run.chars().collect();
+        let mut start = 0;
+        for idx in 1..chars.len() {
+            let hump = chars[idx].is_uppercase()
+                && (chars[idx - 1].is_lowercase()
+                    || chars[idx - 1].is_numeric()
+                    || chars.get(idx + 1).is_some_and(|next| next.is_lowercase()));
+            if hump {
+                terms.push(chars[start..idx].iter().collect());
+                start = idx;
+            }
+        }
+        if start < chars.len() {
+            terms.push(chars[start..].iter().collect());
+        }
+    }
+    let mut unique: Vec<String> = Vec::new();
+    for term in terms {
+        let term = term.to_lowercase();
+        if term.chars().count() > 1 && !unique.contains(&term) {
+            unique.push(term);
+        }
+    }
+    unique
+}
+
+/// Directory name of the snapshot for `terms`; `_all` when every file is a candidate, and
+/// `.rs` appended when it holds every Rust source.
+///
+/// Terms are alphanumeric, so neither `_all` nor a hashed, truncated key can collide with the
+/// key of another set of terms.
+fn snapshot_key(terms: &[String], rust_sources: bool) -> String {
+    let key = terms_key(terms);
+    if rust_sources {
+        format!("{key}.rs")
+    } else {
+        key
+    }
+}
+
+fn terms_key(terms: &[String]) -> String {
+    if terms.is_empty() {
+        return "_all".to_string();
+    }
+    let key = terms.join("-");
+    if key.len() <= MAX_KEY_LEN {
+        return key;
+    }
+    let mut end = MAX_KEY_LEN;
+    while !key.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    format!("{}_{:016x}", &key[..end], hasher.finish())
+}
@@ -0,0 +1,291 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+use crate::identifier;
+
+/// Directory levels searched below the root for crate manifests.
+const MANIFEST_DEPTH: usize = 4;
+/// Path prefixes that name no module of their own.
+const RELATIVE_PREFIXES: &[&str] = &["crate", "self", "super"];
+
+/// A Rust path such as `crate::auth::login_user`, split into its segments.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RustPath {
+    /// Crate and module segments, without a leading `crate`, `self`, or `super`.
+    pub modules: Vec<String>,
+    /// The last segment: the item being searched for.
+    pub item: String,
+}
+
+impl RustPath {
+    /// `None` unless `symbol` is at least two `::`-separated identifiers.
+    pub fn parse(symbol: &str) -> Option<Self> {
+        let symbol = symbol.trim();
+        let symbol = symbol.strip_prefix("::").unwrap_or(symbol);
+        let mut segments: Vec<&str> = symbol.split("::").map(str::trim).collect();
+        if segments.len() < 2 || !segments.iter().all(|segment| is_identifier(segment)) {
+            return None;
+        }
+        let item = segments.pop()?.to_string();
+        let modules = segments
+            .into_iter()
+            .skip_while(|segment| RELATIVE_PREFIXES.contains(segment))
+            .map(str::to_string)
+            .collect();
+        Some(Self { modules, item })
+    }
+
+    /// Files (relative to `root`) that can define the item: the deepest module the path
+    /// resolves to in any crate under `root`, plus its submodules. A leading segment naming a
+    /// crate limits the search to that crate; segments past the last resolvable module (such
+    /// as a type in `Session::new`) are ignored. Empty when no segment resolves.
+    pub fn resolve(&self, root: &Path) -> Vec<PathBuf> {
+        let crates = crate_sources(root);
+        let (crates, modules) = match self.modules.split_first() {
+            Some((first, rest)) if crates.iter().any(|krate| krate.name == *first) => (
+                crates
+                    .into_iter()
+                    .filter(|krate| krate.name == *first)
+                    .collect(),
+                rest,
+            ),
+            _ => (crates, self.modules.as_slice()),
+        };
+        let whole_crate = modules.len() < self.modules.len();
+
+        let mut files = Vec::new();
+        for krate in &crates {
+            let module = krate.resolve(modules);
+            if module.depth == 0 && !whole_crate {
+                continue;
+            }
+            files.extend(module.files(root));
+        }
+        files.sort();
+        files.dedup();
+        files
+    }
+}
+
+fn is_identifier(segment: &str) -> bool {
+    let segment = segment.strip_prefix("r#").unwrap_or(segment);
+    identifier::is_identifier(segment)
+}
+
+/// The `src` directory of one crate.
+struct CrateSource {
+    /// Package name with `-` replaced by `_`, as paths spell it.
+    name: String,
+    /// Crate root files (`lib.rs`, `main.rs`) that exist.
+    entries: Vec<PathBuf>,
+    dir: PathBuf,
+}
+
+/// A module found by following path segments from a crate root.
+struct Module {
+    /// Files holding the module's items: the crate roots at the top, and the parent's file for
+    /// an inline module.
+    sources: Vec<PathBuf>,
+    /// Where its submodule files live.
+    dir: PathBuf,
+    /// Segments resolved to reach it.
+    depth: usize,
+}
+
+impl Module {
+    /// The module's file and every `.rs` file under its submodule directory, relative to
+    /// `root`.
+    fn files(&self, root: &Path) -> Vec<PathBuf> {
+        let mut files = self.sources.clone();
+        if self.dir.is_dir() {
+            let walker = WalkBuilder::new(&self.dir).build();
+            files.extend(
+                walker
+                    .flatten()
+                    .map(|entry| entry.into_path())
+                    .filter(|path| {
+                        path.is_file() && path.extension().is_some_and(|ext| ext == "rs")
+                    }),
+            );
+        }
+        files
+            .into_iter()
+            .filter_map(|path| path.strip_prefix(root).ok().map(Path::to_path_buf))
+            .collect()
+    }
+}
+
+impl CrateSource {
+    fn new(name: String, dir: PathBuf) -> Self {
+        let entries = ["lib.rs", "main.rs"]
+            .into_iter()
+            .map(|file| dir.join(file))
+            .filter(|file| file.is_file())
+            .collect();
+        Self { name, entries, dir }
+    }
+
+    /// Follow `segments` as far as they name modules.
+    fn resolve(&self, segments: &[String]) -> Module {
+        let mut module = Module {
+            sources: self.entries.clone(),
+            dir: self.dir.clone(),
+            depth: 0,
+        };
+        for segment in segments {
+            match child_module(&module, segment) {
+                Some(child) => module = child,
+                None => break,
+            }
+        }
+        module
+    }
+}
+
+/// `name` declared in `parent` through `#[path]`, a file, or an inline block, in that order.
+fn child_module(parent: &Module, name: &str) -> Option<Module> {
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    let depth = parent.depth + 1;
+    let mut inline = None;
+    for file in &parent.sources {
+        let Ok(source) = fs::read_to_string(file) else {
+            continue;
+        };
+        match declaration(&source, name) {
+            Some(Declaration::Path(path)) => {
+                let file = file.parent().unwrap_or(&parent.dir).join(path);
+                if file.is_file() {
+                    let dir = file.with_extension("");
+                    return Some(Module {
+                        sources: vec![file],
+                        dir,
+                        depth,
+                    });
+                }
+            }
+            Some(Declaration::Inline) => inline = Some(file.clone()),
+            Some(Declaration::External) | None => {}
+        }
+    }
+    let dir = parent.dir.join(name);
+    for file in [parent.dir.join(format!("{name}.rs")), dir.join("mod.rs")] {
+        if file.is_file() {
+            return Some(Module {
+                sources: vec![file],
+                dir,
+                depth,
+            });
+        }
+    }
+    inline.map(|file| Module {
+        sources: vec![file],
+        dir,
+        depth,
+    })
+}
+
+enum Declaration {
+    /// `#[path = "..."] mod name;`
+    Path(String),
+    /// `mod name;`
+    External,
+    /// `mod name { ... }`
+    Inline,
+}
+
+/// How `source` declares the module `name`, if it does; only declarations on their own lines
+/// are recognised.
+fn declaration(source: &str, name: &str) -> Option<Declaration> {
+    let mut path_attr: Option<String> = None;
+    for line in source.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("#[path") {
+            path_attr = rest
+                .split('"')
+                .nth(1)
+                .map(str::to_string)
+                .filter(|_| rest.trim_start().starts_with('='));
+            continue;
+        }
+        if line.starts_with("#[") || line.starts_with("//") || line.is_empty() {
+            continue;
+        }
+        let declared = strip_visibility(line)
+            .strip_prefix("mod ")
+            .map(str::trim_start)
+            .and_then(|rest| {
+                rest.strip_prefix(name)
+                    .or_else(|| rest.strip_prefix(&format!("r#{name}")))
+            })
+            .map(str::trim_start);
+        match declared {
+            Some(rest) if rest.starts_with(';') => {
+                return Some(match path_attr.take() {
+                    Some(path) => Declaration::Path(path),
+                    None => Declaration::External,
+                });
+            }
+            Some(rest) if rest.starts_with('{') => return Some(Declaration::Inline),
+            _ => path_attr = None,
+        }
+    }
+    None
+}
+
+/// `line` without a leading `pub`, `pub(crate)`, `pub(super)`, or `pub(in ...)`.
+fn strip_visibility(line: &str) -> &str {
+    let Some(rest) = line.strip_prefix("pub") else {
+        return line;
+    };
+    if let Some(scoped) = rest.strip_prefix('(') {
+        return scoped
+            .find(')')
+            .map_or(line, |end| scoped[end + 1..].trim_start());
+    }
+    if rest.starts_with(char::is_whitespace) {
+        rest.trim_start()
+    } else {
+        line
+    }
+}
+
+/// Crates with a `Cargo.toml` within `MANIFEST_DEPTH` levels of `root`; a root without
+/// manifests is treated as one crate when it has `src/lib.rs` or `src/main.rs`.
+fn crate_sources(root: &Path) -> Vec<CrateSource> {
+    let mut crates = Vec::new();
+    let walker = WalkBuilder::new(root)
+        .max_depth(Some(MANIFEST_DEPTH))
+        .build();
+    for entry in walker.flatten() {
+        if entry.file_name() != "Cargo.toml" {
+            continue;
+        }
+        let manifest = entry.path();
+        let Some(dir) = manifest.parent() else {
+            continue;
+        };
+        let Some(name) = package_name(manifest) else {
+            continue;
+        };
+        crates.push(CrateSource::new(name, dir.join("src")));
+    }
+    if crates.is_empty() {
+        let name = root
+            .file_name()
+            .map(|name| name.to_string_lossy().replace('-', "_"))
+            .unwrap_or_default();
+        crates.push(CrateSource::new(name, root.join("src")));
+    }
+    crates.retain(|krate| !krate.entries.is_empty());
+    crates
+}
+
+/// `[package] name` of a manifest, spelled as Rust paths spell it.
+fn package_name(manifest: &Path) -> Option<String> {
+    let contents = fs::read_to_string(manifest).ok()?;
+    let value: toml::Value = toml::from_str(&contents).ok()?;
+    let name = value.get("package")?.get("name")?.as_str()?;
+    Some(name.replace('-', "_"))
+}
@@ -1,8 +1,9 @@
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
 use std::fmt::Write as _;
 use std::fs;
-use std::io::{BufWriter, Write};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant as StdInstant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
@@ -12,34 +13,250 @@ use tokio::fs::OpenOptions;
 use tokio::io::AsyncWriteExt;
 use tokio::time::Instant;
 
-use crate::cli::SearchArgs;
+use crate::cli::{CaseMode, LinkFormat, SearchArgs, SortOrder};
+use crate::clusters::{self, HitCluster};
+use crate::config::{
+    CONFIG_FILE, RepoConfig, RewardWeights, ScoringWeights, StateConfig, ToolsConfig,
+};
+use crate::dirty::WorktreeChanges;
+use crate::doc_comments;
+use crate::file_types::FileTypes;
+use crate::fingerprint::RepoFingerprint;
+use crate::generated::{self, GeneratedSources};
+use crate::highlight::{self, HighlightSpan};
+use crate::hooks::Hooks;
+use crate::iac;
+use crate::identifier;
+use crate::links::{Linker, RepoLocation};
+use crate::notebook::{self, Notebook};
+use crate::owners::{self, CodeOwners};
+use crate::plan::{self, QueryPlan, Strategy};
+use crate::plugins::{self, PluginHit, Plugins};
+use crate::profile::SearchProfile;
+use crate::redact;
+use crate::results;
+use crate::skipped;
+use crate::source;
+use crate::state_store::{self, StateStore};
+use crate::stats::{RepoScale, RepoStats};
+use crate::suggest::Suggestions;
+use crate::swift_targets::SwiftTargets;
 use crate::tools::ast_grep::{AstGrepMatch, AstGrepTool, AstPatternError};
-use crate::tools::fd::FdTool;
+use crate::tools::backend::{self, ToolBackend};
+use crate::tools::fd::{self, FdTool};
 use crate::tools::rg::{RipgrepMatch, RipgrepTool};
 use crate::tools::rga::{RgaMatch, RgaTool};
+use crate::ts_aliases::{self, MAX_ALIAS_DEPTH, SymbolAlias};
+use crate::validate;
 #[cfg(feature = "indexing")]
-use swe_grep_indexer::{IndexConfig, TantivyIndex};
+use swe_grep_indexer::{IndexConfig, IndexMatch, SymbolStore, TantivyIndex};
 
-const DEFAULT_MAX_COLUMNS: usize = 200;
-const DEFAULT_MAX_BODY_BYTES: usize = 512 * 1024;
+mod pipeline;
+
+use pipeline::{CycleState, Pipeline};
+
+pub(crate) const DEFAULT_MAX_COLUMNS: usize = 200;
+pub(crate) const DEFAULT_MAX_BODY_BYTES: usize = 512 * 1024;
 const DEFAULT_INLINE_CONTEXT: usize = 2;
 const TRUNCATED_INLINE_CONTEXT: usize = 4;
-
-/// Execute a single SWE-grep cycle using the phase-3 workflow.
+pub(crate) const MAX_TOP_HITS: usize = 5;
+pub(crate) const DEFAULT_TIMEOUT_SECS: u64 = 3;
+const DEFAULT_MAX_MATCHES: usize = 20;
+/// Default `max_matches` for repositories of `stats::HUGE_MIN_FILES` or more.
+const HUGE_REPO_MAX_MATCHES: usize = 100;
+/// Default per-file ripgrep cap for huge repositories, so one noisy file cannot fill the probe.
+const HUGE_REPO_MAX_COUNT: usize = 5;
+const DEFAULT_STATE_MAX_SYMBOLS: usize = 500;
+/// Other spellings of a case-folded symbol that AST-Grep also looks for.
+const MAX_CASE_VARIANTS: usize = 4;
+/// Files of each Swift package or Xcode target that discovery lists.
+const SWIFT_TARGET_FILES: usize = 32;
+/// Terraform/HCL and YAML files that discovery lists in `--config` mode.
+const CONFIG_FILES: usize = 64;
+/// Languages `--config` adds to the search.
+const CONFIG_LANGUAGES: &[&str] = &["hcl", "yaml"];
+/// Identifiers listed in a glob search's `matched_identifiers`.
+const MAX_MATCHED_IDENTIFIERS: usize = 20;
+/// Base scores of phrase hits by how exactly the line holds the phrase; see
+/// [`phrase_exactness`].
+const PHRASE_EXACT_SCORE: f32 = 1.0;
+const PHRASE_PARTIAL_WORD_SCORE: f32 = 0.9;
+const PHRASE_FOLDED_SCORE: f32 = 0.8;
+
+/// Execute a single SWE-grep cycle using the phase-3 workflow. The summary gets a `search_id`
+/// and is stored so `swe-grep show` can return it later.
 pub async fn execute(args: SearchArgs) -> Result<SearchSummary> {
-    let config = SearchConfig::try_from_args(args)?;
-    let mut engine = SearchEngine::new(config)?;
+    execute_with_backend(args, backend::process()).await
+}
+
+/// [`execute`] with every tool run started through `backend`, so tests can answer them from a
+/// [`MockBackend`](crate::tools::backend::MockBackend) instead of installed binaries.
+pub async fn execute_with_backend(
+    args: SearchArgs,
+    backend: Arc<dyn ToolBackend>,
+) -> Result<SearchSummary> {
+    validate::search_args(&args)?;
+    let cache_dir = results_cache_dir(&args)?;
+    execute_and_record(args, backend, &cache_dir).await
+}
+
+/// [`execute`], storing the summary under `cache_dir` whatever cache directory the search
+/// itself uses, so a server can keep the results of every root it searches in one place.
+pub async fn execute_recorded_in(args: SearchArgs, cache_dir: &Path) -> Result<SearchSummary> {
+    validate::search_args(&args)?;
+    execute_and_record(args, backend::process(), cache_dir).await
+}
+
+async fn execute_and_record(
+    args: SearchArgs,
+    backend: Arc<dyn ToolBackend>,
+    cache_dir: &Path,
+) -> Result<SearchSummary> {
+    let deterministic = args.deterministic;
+    let mut summary = if args.path.len() > 1 || args.include_submodules {
+        crate::workspace::execute(args, backend).await?
+    } else {
+        execute_root(args, backend).await?
+    };
+    if deterministic {
+        // A search id is unique by design, so deterministic summaries go without one.
+        summary.make_deterministic();
+    } else if let Err(err) = results::record(cache_dir, &mut summary) {
+        tracing::warn!(error = %err, "failed to store search result");
+    }
+    Ok(summary)
+}
+
+/// Cache directory whose `results/` folder keeps this search: `--cache-dir`, or the default one
+/// of the first root. Chosen before `--rev` moves the hint state into a revision folder.
+fn results_cache_dir(args: &SearchArgs) -> Result<PathBuf> {
+    if let Some(cache_dir) = &args.cache_dir {
+        return Ok(cache_dir.clone());
+    }
+    let root = match args.path.first() {
+        Some(root) => root.clone(),
+        None => std::env::current_dir().context("failed to resolve current directory")?,
+    };
+    let root = canonicalize_path(&root).with_context(|| {
+        format!(
+            "failed to canonicalize repository root path: {}",
+            root.display()
+        )
+    })?;
+    Ok(root.join(".swe-grep-cache"))
+}
+
+/// Search one root without recording the result; `workspace` merges several of these.
+pub(crate) async fn execute_root(
+    args: SearchArgs,
+    backend: Arc<dyn ToolBackend>,
+) -> Result<SearchSummary> {
+    let (args, snapshot) = match args.rev.clone() {
+        Some(rev) => {
+            let (args, snapshot) = crate::revision::retarget(args, &rev).await?;
+            (args, Some(snapshot))
+        }
+        None => (args, None),
+    };
+    let mut config = SearchConfig::try_from_args(args)?;
+    if let Some(snapshot) = snapshot {
+        // Links point into the checkout the snapshot came from, pinned to the searched commit.
+        if config.permalinks || config.link_format == Some(LinkFormat::Github) {
+            config.location = RepoLocation::detect(&RepoFingerprint::detect(&snapshot.source_root))
+                .map(|location| location.at_commit(snapshot.commit.clone()));
+        }
+        config.toplevel = snapshot.toplevel;
+        config.prefix = snapshot.prefix;
+        config.revision = Some(snapshot.commit);
+    }
+    let mut engine = SearchEngine::new(config, backend).await?;
     engine.run_cycle().await
 }
 
+/// Run a search while recording the raw verify-stage inputs so `calibrate` can re-score them
+/// under different weights without invoking the tools again.
+pub(crate) async fn execute_for_calibration(
+    args: SearchArgs,
+) -> Result<(SearchSummary, CalibrationSample)> {
+    validate::search_args(&args)?;
+    let config = SearchConfig::try_from_args(args)?;
+    let mut engine = SearchEngine::new(config, backend::process()).await?;
+    engine.calibration = Some(CalibrationSample::default());
+    let summary = engine.run_cycle().await?;
+    Ok((summary, engine.calibration.take().unwrap_or_default()))
+}
+
+/// Composition of `root` from its state file in `cache_dir`, walking the tree when the stored
+/// copy is stale or `refresh` is set, and saving the result for later searches. The repository
+/// config's `[state]` store is only used with `trust_repo_state`.
+pub(crate) fn repo_stats(
+    root: &Path,
+    cache_dir: &Path,
+    refresh: bool,
+    trust_repo_state: bool,
+) -> Result<RepoStats> {
+    let fingerprint = RepoFingerprint::detect(root);
+    let state_config = RepoConfig::load(root)?.state.resolve(trust_repo_state);
+    let mut state = PersistentState::load(
+        root,
+        &fingerprint,
+        cache_dir,
+        &state_config,
+        DEFAULT_STATE_MAX_SYMBOLS,
+    )?;
+    let stats = state.repo_stats(refresh);
+    if let Err(err) = state.save() {
+        tracing::warn!(error = %err, "failed to persist cache state");
+    }
+    Ok(stats)
+}
+
 struct SearchConfig {
     root: PathBuf,
+    /// Commit being searched when `--rev` points the root at an exported snapshot.
+    revision: Option<String>,
     symbol: String,
+    /// How the symbol is searched, from its shape; see [`QueryPlan::for_symbol`].
+    strategy: Strategy,
+    /// Explicit probe scope from `--files-from`; bypasses discovery and global fallbacks.
+    scope: Option<FileScope>,
+    /// Files of the module a Rust path symbol (`crate::auth::login_user`) names; they replace
+    /// discovery, while escalation still covers the rest of the repository.
+    module_scope: Vec<PathBuf>,
+    /// `CODEOWNERS` owner that every reported hit must belong to.
+    owner: Option<String>,
+    /// Top level of the checkout holding `root`, or of the exported tree under `--rev`; its
+    /// `CODEOWNERS` applies.
+    toplevel: PathBuf,
+    /// `root` relative to `toplevel`, which `CODEOWNERS` patterns are anchored at.
+    prefix: PathBuf,
+    /// Case handling for rg probes, symbol-store lookups, and AST patterns.
+    case: CaseMode,
+    /// Bound rewrites with `\b` so the symbol only matches as a whole word.
+    whole_word: bool,
     #[allow(dead_code)]
     language: Option<String>,
     language_tokens: Vec<String>,
+    /// File types from `--rg-type`, or else those matching `--language`; without them the
+    /// language's extension list filters discovery instead.
+    file_types: Option<FileTypes>,
+    /// Swift package and Xcode targets and bridging headers, read on first use.
+    swift_targets: SwiftTargets,
+    /// `--config`: Terraform/HCL and YAML files are discovered first and their hits outrank
+    /// the rest.
+    config_mode: bool,
     timeout: Duration,
     max_matches: usize,
+    /// Candidate files handed to the scoped probe; `None` probes them all.
+    max_scope_files: Option<usize>,
+    /// Per-file cap passed to ripgrep as `--max-count`; `None` reads every match.
+    max_count: Option<usize>,
+    /// `max_matches` came from the built-in default and is scaled to the repository's size.
+    adaptive_limits: bool,
+    /// Never probe the whole repository, even through the fast path; set for huge repositories.
+    require_scope: bool,
+    /// Total cycle latency split into per-stage budgets by `--latency-target-ms`.
+    latency_target: Option<Duration>,
     #[allow(dead_code)]
     concurrency: usize,
     use_index: bool,
@@ -47,41 +264,145 @@ struct SearchConfig {
     use_rga: bool,
     use_fd: bool,
     use_ast: bool,
+    /// Probe the whole repository when the scoped probe finds nothing.
+    escalate: bool,
+    /// Hits kept in `top_hits`; `MAX_TOP_HITS` unless the profile says otherwise.
+    top_hits: usize,
+    /// Report `repo_stats` in the summary.
+    explain: bool,
+    /// Attach token spans to hit snippets.
+    highlight: bool,
+    /// Attach a `link` of this format to each hit.
+    link_format: Option<LinkFormat>,
+    /// Attach a commit-pinned `permalink` to each hit.
+    permalinks: bool,
+    /// Order of `top_hits`, applied after the best-scoring hits are picked.
+    sort: SortOrder,
+    /// The root on the code host; detected only when links or permalinks need it.
+    location: Option<RepoLocation>,
     cache_dir: PathBuf,
+    /// Symbols kept in the hint state before the least recently used are evicted.
+    state_max_symbols: usize,
+    /// Partitions hint state so a shared `--cache-dir` never mixes repositories.
+    fingerprint: RepoFingerprint,
+    /// `[state]`: a shared store to keep hint state in instead of `cache_dir`.
+    state: StateConfig,
     log_dir: Option<PathBuf>,
     context_before: usize,
     context_after: usize,
     max_columns: usize,
     body: bool,
     max_body_bytes: usize,
+    /// Redact secrets in snippets, context, and bodies; see `--redact-secrets`.
+    redact_secrets: bool,
+    scoring: ScoringWeights,
+    reward_weights: RewardWeights,
+    /// Validated tool binaries and extra arguments (CLI flags over `[tools]`).
+    tools: ToolsConfig,
+    /// `[[hooks]]` rewriting the summary before it is logged and returned.
+    hooks: Hooks,
+    /// `.wasm` modules from the repository's plugin directory, in run order.
+    plugins: Vec<PathBuf>,
+}
+
+/// Stages with a slice of `--latency-target-ms`, in cycle order.
+#[derive(Clone, Copy, Debug)]
+enum BudgetStage {
+    Discover,
+    /// The scoped probe and its fallbacks (escalation, index, ripgrep-all).
+    Probe,
+    Disambiguate,
 }
 
-/// Maximum allowed symbol length to prevent regex bombs and performance issues.
-const MAX_SYMBOL_LENGTH: usize = 256;
+impl BudgetStage {
+    /// Share of the target that may have elapsed by the time this stage ends. The remaining 15%
+    /// is left for verification, which always runs.
+    fn deadline_share(self) -> f64 {
+        match self {
+            Self::Discover => 0.15,
+            Self::Probe => 0.55,
+            Self::Disambiguate => 0.85,
+        }
+    }
+}
 
-impl SearchConfig {
-    fn try_from_args(args: SearchArgs) -> Result<Self> {
-        // Validate symbol length to prevent regex bombs and performance issues
-        if args.symbol.len() > MAX_SYMBOL_LENGTH {
-            anyhow::bail!(
-                "symbol exceeds maximum length of {} characters (got {})",
-                MAX_SYMBOL_LENGTH,
-                args.symbol.len()
-            );
+/// Per-stage time budgets carved out of a total latency target. Each stage may run until its
+/// cumulative deadline, so time an early stage leaves unused rolls over to the later ones.
+#[derive(Clone, Copy, Debug)]
+struct LatencyBudget {
+    start: Instant,
+    target: Duration,
+}
+
+impl LatencyBudget {
+    fn new(target: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            target,
         }
+    }
+
+    /// Time left for `stage`; zero once its deadline has passed.
+    fn remaining(&self, stage: BudgetStage) -> Duration {
+        self.target
+            .mul_f64(stage.deadline_share())
+            .saturating_sub(self.start.elapsed())
+    }
+}
 
+/// Await `future` for at most `limit`; `None` when the budget ran out first. A zero limit skips
+/// the future entirely, and dropping a timed-out future kills any tool it spawned.
+async fn within_budget<F: std::future::Future>(
+    limit: Option<Duration>,
+    future: F,
+) -> Option<F::Output> {
+    match limit {
+        None => Some(future.await),
+        Some(limit) if limit.is_zero() => None,
+        Some(limit) => tokio::time::timeout(limit, future).await.ok(),
+    }
+}
+
+impl SearchConfig {
+    fn try_from_args(args: SearchArgs) -> Result<Self> {
         let root = args
             .path
+            .into_iter()
+            .next()
             .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
-        let root = root.canonicalize().with_context(|| {
+        let root = canonicalize_path(&root).with_context(|| {
             format!(
                 "failed to canonicalize repository root path: {}",
                 root.display()
             )
         })?;
 
+        let repo_config = RepoConfig::load(&root)?;
+        let tools = repo_config.tools.resolve(&root, &args.tools)?;
+        let hooks = if args.trust_repo_hooks {
+            Hooks::new(repo_config.hooks, &root)
+        } else {
+            if !repo_config.hooks.is_empty() {
+                tracing::warn!(
+                    "skipping the [[hooks]] in {CONFIG_FILE}; pass --trust-repo-hooks to run them"
+                );
+            }
+            Hooks::default()
+        };
+        let plugins = plugins::plugin_files(&root)?;
+        let profile = match args.profile.as_deref() {
+            Some(name) => SearchProfile::resolve(name, &repo_config.profiles)?,
+            None => SearchProfile::default(),
+        };
+
         let concurrency = usize::max(1, args.concurrency);
-        let timeout = Duration::from_secs(args.timeout_secs);
+        let timeout = Duration::from_secs(
+            args.timeout_secs
+                .or(profile.timeout_secs)
+                .unwrap_or(DEFAULT_TIMEOUT_SECS),
+        );
+        let explicit_max_matches = args.max_matches.or(profile.max_matches);
+        let max_matches = usize::max(1, explicit_max_matches.unwrap_or(DEFAULT_MAX_MATCHES));
         let index_dir = args
             .index_dir
             .clone()
@@ -90,6 +411,10 @@ impl SearchConfig {
             .cache_dir
             .clone()
             .unwrap_or_else(|| root.join(".swe-grep-cache"));
+        let fingerprint = RepoFingerprint::detect(&root);
+        let location = (args.permalinks || args.link_format == Some(LinkFormat::Github))
+            .then(|| RepoLocation::detect(&fingerprint))
+            .flatten();
         let log_dir = args.log_dir.map(|dir| {
             if dir.is_absolute() {
                 dir
@@ -98,12 +423,26 @@ impl SearchConfig {
             }
         });
 
-        let use_fd = args.use_fd;
-        let use_ast = args.use_ast_grep;
+        let scope = args
+            .files_from
+            .as_deref()
+            .map(|source| FileScope::load(&root, source))
+            .transpose()?;
+
+        let owner = args
+            .owner
+            .map(|owner| owner.trim().to_string())
+            .filter(|owner| !owner.is_empty());
+
+        let use_fd = args.use_fd && profile.use_fd.unwrap_or(true);
+        let use_ast = args.use_ast_grep && profile.use_ast_grep.unwrap_or(true);
 
-        let mut use_index = args.enable_index;
+        let mut use_index = args.enable_index || profile.enable_index.unwrap_or(false);
         if use_index && !cfg!(feature = "indexing") {
-            tracing::warn!("indexing support not compiled; ignoring --enable-index");
+            // Profiles ask for the index opportunistically; only an explicit flag is worth a warning.
+            if args.enable_index {
+                tracing::warn!("indexing support not compiled; ignoring --enable-index");
+            }
             use_index = false;
         }
 
@@ -111,30 +450,142 @@ impl SearchConfig {
             .language
             .map(|lang| lang.trim().to_string())
             .filter(|s| !s.is_empty());
-        let language_tokens = expand_language_hint(language.as_deref());
+        let mut language_tokens = expand_language_hint(language.as_deref());
+        // Swift in a mixed target calls Objective-C through its bridging header, so the
+        // Objective-C side is searched as well.
+        let mut swift_targets = SwiftTargets::new(&root);
+        if languages_include(&language_tokens, "swift")
+            && !languages_include(&language_tokens, "objc")
+            && !swift_targets.bridging_headers().is_empty()
+        {
+            language_tokens.push("objc".to_string());
+        }
+        // On its own, `--config` ranks config files first without hiding the rest of the
+        // repository from ripgrep; with `--language`, it widens the selected types.
+        let explicit_language = !language_tokens.is_empty();
+        if args.config {
+            for language in CONFIG_LANGUAGES {
+                if !languages_include(&language_tokens, language) {
+                    language_tokens.push(language.to_string());
+                }
+            }
+        }
+        let file_types = match FileTypes::new(&args.rg_types, &args.rg_type_adds)? {
+            Some(types) => Some(types),
+            None if args.config && !explicit_language => None,
+            None => FileTypes::for_languages(&language_tokens),
+        };
+
+        let rust_paths = scope.is_none()
+            && (language_tokens.is_empty() || language_tokens.iter().any(|token| token == "rust"));
+        let plan = QueryPlan::for_symbol(&args.symbol, rust_paths);
+        // A Rust path naming a module is searched as its last segment, within that module;
+        // anything else, such as `Config::load` outside a crate, is searched as written.
+        let (strategy, symbol, module_scope) = match plan.rust_path {
+            Some(path) => {
+                let module_scope = path.resolve(&root);
+                if module_scope.is_empty() {
+                    (Strategy::Identifier, args.symbol, module_scope)
+                } else {
+                    tracing::debug!(
+                        item = %path.item,
+                        files = module_scope.len(),
+                        "resolved rust module path"
+                    );
+                    (plan.strategy, path.item, module_scope)
+                }
+            }
+            None if plan.strategy == Strategy::Identifier => {
+                (plan.strategy, args.symbol, Vec::new())
+            }
+            None => (plan.strategy, plan.text, Vec::new()),
+        };
+        tracing::debug!(strategy = strategy.as_str(), "planned search");
+        let whole_word = args
+            .word
+            .unwrap_or_else(|| bounds_words_by_default(strategy, &symbol));
 
         Ok(Self {
             root,
-            symbol: args.symbol,
+            revision: None,
+            symbol,
+            strategy,
+            scope,
+            module_scope,
+            owner,
+            toplevel: fingerprint.toplevel.clone(),
+            prefix: fingerprint.prefix.clone(),
+            case: args.case.unwrap_or_default(),
+            whole_word,
             language,
             language_tokens,
+            file_types,
+            swift_targets,
+            config_mode: args.config,
             timeout,
-            max_matches: usize::max(1, args.max_matches),
+            max_matches,
+            max_scope_files: args.max_scope_files,
+            max_count: Some(max_matches),
+            adaptive_limits: explicit_max_matches.is_none(),
+            require_scope: false,
+            latency_target: args.latency_target_ms.map(Duration::from_millis),
             concurrency,
             use_index,
             index_dir,
-            use_rga: args.enable_rga,
+            use_rga: args.enable_rga || profile.enable_rga.unwrap_or(false),
             use_fd,
             use_ast,
+            escalate: profile.escalate.unwrap_or(true),
+            top_hits: usize::max(1, profile.top_hits.unwrap_or(MAX_TOP_HITS)),
+            explain: args.explain,
+            highlight: args.highlight,
+            link_format: args.link_format,
+            permalinks: args.permalinks,
+            sort: args.sort.unwrap_or_default(),
+            location,
             cache_dir,
+            state_max_symbols: usize::max(
+                1,
+                args.state_max_symbols.unwrap_or(DEFAULT_STATE_MAX_SYMBOLS),
+            ),
+            fingerprint,
+            state: repo_config.state.resolve(args.trust_repo_state),
             log_dir,
             context_before: args.context_before,
             context_after: args.context_after,
             max_columns: DEFAULT_MAX_COLUMNS,
             body: args.body,
             max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            redact_secrets: args.redact_secrets,
+            scoring: repo_config.scoring,
+            reward_weights: repo_config.reward,
+            tools,
+            hooks,
+            plugins,
         })
     }
+
+    /// Replace the default match limits with ones sized for the repository: tiny repositories
+    /// are probed exhaustively, huge ones get a wider total, a tighter per-file cap, and scoped
+    /// probes only. Limits set by a flag or profile are left alone.
+    fn scale_limits(&mut self, scale: RepoScale) {
+        if !self.adaptive_limits {
+            return;
+        }
+        match scale {
+            RepoScale::Tiny => {
+                self.max_matches = validate::MAX_MATCHES;
+                self.max_count = None;
+            }
+            RepoScale::Typical => {}
+            RepoScale::Huge => {
+                self.max_matches = HUGE_REPO_MAX_MATCHES;
+                self.max_count = Some(HUGE_REPO_MAX_COUNT);
+                self.escalate = false;
+                self.require_scope = true;
+            }
+        }
+    }
 }
 
 struct SearchEngine {
@@ -143,34 +594,43 @@ struct SearchEngine {
     rg_tool: RipgrepTool,
     rga_tool: Option<RgaTool>,
     ast_tool: Option<AstGrepTool>,
+    /// Starts every tool run; the process backend outside hermetic tests.
+    backend: Arc<dyn ToolBackend>,
     #[cfg(feature = "indexing")]
     index: Option<TantivyIndex>,
+    #[cfg(feature = "indexing")]
+    symbol_store: Option<SymbolStore>,
     dedup_cache: SearchCache,
-    state: PersistentState,
+    /// Shared with the blocking pool, where it is loaded and saved.
+    state: Arc<Mutex<PersistentState>>,
+    generated: GeneratedSources,
+    code_owners: Option<CodeOwners>,
     reward_total: f32,
     startup_stats: StartupStats,
     language_cache: HashMap<PathBuf, &'static str>,
     body_cache: HashMap<PathBuf, BodyPayload>,
-    warnings: Vec<String>,
+    warnings: Vec<Warning>,
+    budget: Option<LatencyBudget>,
+    /// Stages cut short or skipped this cycle because their budget ran out.
+    budget_exceeded: Vec<String>,
+    /// Extensions of discovered files the language filter dropped this cycle.
+    filtered_extensions: BTreeSet<String>,
+    calibration: Option<CalibrationSample>,
+    plugins: Plugins,
+    /// Stages of the full cycle; the literal fast path bypasses them.
+    pipeline: Arc<Pipeline>,
 }
 
 impl SearchEngine {
-    fn new(config: SearchConfig) -> Result<Self> {
+    async fn new(mut config: SearchConfig, backend: Arc<dyn ToolBackend>) -> Result<Self> {
         let init_start = StdInstant::now();
         let mut startup_stats = StartupStats::default();
+        let backend = config.tools.backend(backend);
 
-        let fd_tool = None;
+        #[cfg(feature = "bundled-tools")]
+        crate::tools::bundled::set_install_dir(config.cache_dir.join("tools"));
 
-        let rg_start = StdInstant::now();
-        let rg_tool = RipgrepTool::new(
-            config.timeout,
-            config.max_matches,
-            config.context_before,
-            config.context_after,
-            config.max_columns,
-            config.concurrency,
-        );
-        startup_stats.rg_ms = elapsed_std_ms(rg_start);
+        let fd_tool = None;
 
         let ast_tool = None;
 
@@ -186,13 +646,57 @@ impl SearchEngine {
         }
 
         let state_start = StdInstant::now();
-        let state = PersistentState::load(&config.root, &config.cache_dir)?;
+        let mut state = {
+            let root = config.root.clone();
+            let fingerprint = config.fingerprint.clone();
+            let cache_dir = config.cache_dir.clone();
+            let state_config = config.state.clone();
+            let max_symbols = config.state_max_symbols;
+            // Stores wait on file locks and, when shared, on the network.
+            tokio::task::spawn_blocking(move || {
+                PersistentState::load(&root, &fingerprint, &cache_dir, &state_config, max_symbols)
+            })
+            .await
+            .context("persistent state task failed")??
+        };
         let state_elapsed = elapsed_std_ms(state_start);
         startup_stats.state_ms = state_elapsed;
         startup_stats.cache_ms = state_elapsed;
 
+        if config.adaptive_limits {
+            config.scale_limits(state.repo_scale());
+        }
+
+        let rg_start = StdInstant::now();
+        let rg_tool = RipgrepTool::new(
+            config.timeout,
+            config.max_matches,
+            config.context_before,
+            config.context_after,
+            config.max_columns,
+            config.concurrency,
+        )
+        .with_max_count(config.max_count)
+        .with_case(config.case)
+        .with_fixed_strings(config.strategy == Strategy::Phrase, config.whole_word)
+        .with_types(config.file_types.as_ref())
+        .with_settings(config.tools.rg.clone())
+        .with_retry(config.tools.retry_policy())
+        .with_backend(backend.clone());
+        startup_stats.rg_ms = elapsed_std_ms(rg_start);
+
         let rga_tool = None;
 
+        let plugins = Plugins::load(&config.plugins)?;
+
+        let code_owners = CodeOwners::load(&config.toplevel, &config.prefix)?;
+        if code_owners.is_none() && config.owner.is_some() {
+            anyhow::bail!(
+                "--owner needs a CODEOWNERS file in {}",
+                config.toplevel.display()
+            );
+        }
+
         startup_stats.init_ms = elapsed_std_ms(init_start);
         crate::telemetry::record_stage_latency("init", startup_stats.init_ms);
         crate::telemetry::record_stage_latency("init_rg", startup_stats.rg_ms);
@@ -201,20 +705,31 @@ impl SearchEngine {
         crate::telemetry::record_stage_latency("init_index", startup_stats.index_ms);
 
         Ok(Self {
+            generated: GeneratedSources::new(&config.root),
+            code_owners,
             config,
             fd_tool,
             rg_tool,
             rga_tool,
             ast_tool,
+            backend,
             #[cfg(feature = "indexing")]
             index: None,
+            #[cfg(feature = "indexing")]
+            symbol_store: None,
             dedup_cache: SearchCache::default(),
-            state,
+            state: Arc::new(Mutex::new(state)),
             reward_total: 0.0,
             startup_stats,
             language_cache: HashMap::new(),
             body_cache: HashMap::new(),
             warnings: Vec::new(),
+            budget: None,
+            budget_exceeded: Vec::new(),
+            filtered_extensions: BTreeSet::new(),
+            calibration: None,
+            plugins,
+            pipeline: Arc::new(Pipeline::standard()),
         })
     }
 
@@ -237,7 +752,7 @@ impl SearchEngine {
                         retrieved: false,
                     }
                 } else {
-                    match fs::read_to_string(&absolute) {
+                    match source::read(&absolute) {
                         Ok(contents) => BodyPayload {
                             body: Some(contents),
                             retrieved: true,
@@ -284,7 +799,7 @@ impl SearchEngine {
             self.config.root.join(path)
         };
 
-        let Ok(contents) = fs::read_to_string(&absolute) else {
+        let Ok(contents) = source::read(&absolute) else {
             return Vec::new();
         };
 
@@ -301,12 +816,15 @@ impl SearchEngine {
     }
 
     fn ensure_fd_tool(&mut self) -> Option<&FdTool> {
-        if !self.config.use_fd {
+        if !self.config.use_fd || self.circuit_open("fd") {
             return None;
         }
         if self.fd_tool.is_none() {
             let start = StdInstant::now();
-            let tool = FdTool::new(self.config.timeout, 200);
+            let tool = FdTool::new(self.config.timeout, fd::DEFAULT_MAX_RESULTS)
+                .with_settings(self.config.tools.fd.clone())
+                .with_retry(self.config.tools.retry_policy())
+                .with_backend(self.backend.clone());
             let elapsed = elapsed_std_ms(start);
             if self.startup_stats.fd_ms == 0 {
                 self.startup_stats.fd_ms = elapsed;
@@ -318,12 +836,15 @@ impl SearchEngine {
     }
 
     fn ensure_ast_tool(&mut self) -> Option<&AstGrepTool> {
-        if !self.config.use_ast {
+        if !self.config.use_ast || self.circuit_open("ast-grep") {
             return None;
         }
         if self.ast_tool.is_none() {
             let start = StdInstant::now();
-            let tool = AstGrepTool::new(self.config.timeout, self.config.max_matches);
+            let tool = AstGrepTool::new(self.config.timeout, self.config.max_matches)
+                .with_settings(self.config.tools.ast_grep.clone())
+                .with_retry(self.config.tools.retry_policy())
+                .with_backend(self.backend.clone());
             let elapsed = elapsed_std_ms(start);
             if self.startup_stats.ast_ms == 0 {
                 self.startup_stats.ast_ms = elapsed;
@@ -335,12 +856,17 @@ impl SearchEngine {
     }
 
     fn ensure_rga_tool(&mut self) -> Option<&RgaTool> {
-        if !self.config.use_rga {
+        if !self.config.use_rga || self.circuit_open("rga") {
             return None;
         }
         if self.rga_tool.is_none() {
             let start = StdInstant::now();
-            let tool = RgaTool::new(self.config.timeout, self.config.max_matches);
+            let tool = RgaTool::new(self.config.timeout, self.config.max_matches)
+                .with_settings(self.config.tools.rga.clone())
+                .with_adapters(self.config.tools.rga_adapters.clone())
+                .with_word_regexp(self.config.whole_word)
+                .with_retry(self.config.tools.retry_policy())
+                .with_backend(self.backend.clone());
             let elapsed = elapsed_std_ms(start);
             if self.startup_stats.rga_ms == 0 {
                 self.startup_stats.rga_ms = elapsed;
@@ -351,8 +877,88 @@ impl SearchEngine {
         self.rga_tool.as_ref()
     }
 
-    fn push_warning(&mut self, message: impl Into<String>) {
-        self.warnings.push(message.into());
+    /// Filtered directories and extensions for `SearchSummary::skipped`. An explicit
+    /// `--files-from` scope names its files directly, so ignore rules never applied to it.
+    fn skipped_areas(&self) -> Vec<String> {
+        let mut skipped = if self.config.scope.is_some() {
+            Vec::new()
+        } else {
+            skipped::filtered_directories(&self.config.root)
+        };
+        skipped.extend(
+            self.filtered_extensions
+                .iter()
+                .map(|ext| skipped::extension_filtered(ext)),
+        );
+        skipped
+    }
+
+    fn push_warning(&mut self, warning: Warning) {
+        self.warnings.push(warning);
+    }
+
+    /// Whether `tool` is being skipped after repeated failures, noted once per search.
+    fn circuit_open(&mut self, tool: &'static str) -> bool {
+        let binary = self.tool_binary(tool);
+        let Some(remaining) = self.config.tools.circuit_policy().open_for(tool, binary) else {
+            return false;
+        };
+        let noted = self.warnings.iter().any(|warning| {
+            warning.kind == WarningKind::ToolCircuitOpen && warning.tool.as_deref() == Some(tool)
+        });
+        if !noted {
+            self.push_warning(
+                Warning::new(
+                    WarningKind::ToolCircuitOpen,
+                    format!(
+                        "{tool} skipped after repeated failures; retried in {}s",
+                        remaining.as_secs().max(1)
+                    ),
+                )
+                .with_tool(tool),
+            );
+        }
+        true
+    }
+
+    /// Count a run of an optional tool toward its circuit breaker. Only failures and timeouts
+    /// count: a missing binary costs nothing to skip, and a bad pattern is the query's fault.
+    fn record_tool_outcome(&self, tool: &'static str, err: Option<&anyhow::Error>) {
+        let failed = err.is_some_and(|err| {
+            matches!(
+                tool_error_kind(err),
+                WarningKind::ToolTimedOut | WarningKind::ToolFailed
+            )
+        });
+        if err.is_none() || failed {
+            let binary = self.tool_binary(tool);
+            self.config
+                .tools
+                .circuit_policy()
+                .record(tool, binary, failed);
+        }
+    }
+
+    /// Configured binary of an optional tool; `None` when it runs from `PATH`.
+    fn tool_binary(&self, tool: &str) -> Option<&Path> {
+        let settings = match tool {
+            "fd" => &self.config.tools.fd,
+            "ast-grep" => &self.config.tools.ast_grep,
+            _ => &self.config.tools.rga,
+        };
+        settings.path.as_deref()
+    }
+
+    /// Time `stage` may still take under `--latency-target-ms`; `None` without a target.
+    fn stage_limit(&self, stage: BudgetStage) -> Option<Duration> {
+        self.budget.map(|budget| budget.remaining(stage))
+    }
+
+    fn exceed_budget(&mut self, stage: &str) {
+        tracing::debug!(stage, "stage budget exhausted");
+        if !self.budget_exceeded.iter().any(|name| name == stage) {
+            self.budget_exceeded.push(stage.to_string());
+        }
     }
 
     fn format_origin_label(&mut self, origin: &HitOrigin, path: &Path) -> String {
@@ -368,127 +974,82 @@ impl SearchEngine {
         }
     }
 
+    /// Hand each hit to the repository's WASM plugins, dropping the ones they filter out and
+    /// taking their scores for the rest.
+    fn apply_plugins(&mut self, hits: Vec<SearchHit>) -> Vec<SearchHit> {
+        let hits = hits
+            .into_iter()
+            .filter_map(|mut hit| {
+                let mut plugin_hit = PluginHit {
+                    path: hit.location().to_string_lossy().into_owned(),
+                    line: u32::try_from(hit.line).unwrap_or(u32::MAX),
+                    score: hit.score,
+                    origin: hit.origin.as_str().to_string(),
+                    snippet: hit.snippet.clone(),
+                    language: detect_language_from_path(&hit.path).map(str::to_string),
+                };
+                if !self.plugins.apply(&mut plugin_hit) {
+                    return None;
+                }
+                hit.score = plugin_hit.score;
+                Some(hit)
+            })
+            .collect();
+        for warning in self.plugins.take_warnings() {
+            self.push_warning(warning);
+        }
+        hits
+    }
+
     async fn run_cycle(&mut self) -> Result<SearchSummary> {
-        let mut stage_stats = StageStats::default();
         self.warnings.clear();
+        self.budget_exceeded.clear();
+        self.filtered_extensions.clear();
+        self.budget = self.config.latency_target.map(LatencyBudget::new);
 
         tracing::info!(symbol = %self.config.symbol, "search_cycle_start");
 
-        let rewrites =
-            QueryRewriter::for_symbol(&self.config.symbol, &self.config.language_tokens).build();
-        if let Some(summary) = self.try_fast_path(&rewrites).await? {
+        let rewrites = QueryRewriter::for_symbol(&self.config.symbol, &self.config.language_tokens)
+            .strategy(self.config.strategy)
+            .whole_word(self.config.whole_word)
+            .build();
+        let scoped = self.config.scope.is_some();
+        // Exact identifiers are answered from the symbol store before any tool is spawned.
+        let stored = if scoped {
+            Vec::new()
+        } else {
+            self.stored_candidates().await
+        };
+        if !scoped
+            && !self.config.require_scope
+            && stored.is_empty()
+            && self.config.module_scope.is_empty()
+            && let Some(summary) = self.try_fast_path(rewrites.clone()).await?
+        {
             return Ok(summary);
         }
 
-        // --- Discover ---
-        let discover_start = Instant::now();
-        let discover_candidates = self.discover().await;
-        stage_stats.discover_ms = elapsed_ms(discover_start);
-        stage_stats.discover_candidates = discover_candidates.len();
-        stage_stats.record_discover_languages(&discover_candidates, stage_stats.discover_ms);
-        let discover_set: HashSet<PathBuf> = discover_candidates.iter().cloned().collect();
-
-        // --- Probe (Scoped) ---
-        let probe_start = Instant::now();
-        let (mut hits, scoped_hits_count) = self
-            .probe(&rewrites, &discover_candidates, ProbeKind::Scoped)
-            .await;
-        stage_stats.probe_ms = elapsed_ms(probe_start);
-        stage_stats.probe_hits = scoped_hits_count;
-        stage_stats.record_probe_languages(&hits, stage_stats.probe_ms);
-
-        // --- Escalate to global if needed ---
-        if hits.is_empty() {
-            let escalate_start = Instant::now();
-            let (global_hits, global_hits_count) =
-                self.probe(&rewrites, &[], ProbeKind::Global).await;
-            stage_stats.escalate_ms = elapsed_ms(escalate_start);
-            stage_stats.escalate_hits = global_hits_count;
-            stage_stats.record_escalate_languages(&global_hits, stage_stats.escalate_ms);
-            hits = global_hits;
-        }
-
-        #[cfg(feature = "indexing")]
-        if hits.is_empty() && self.config.use_index {
-            let index_stage_start = Instant::now();
-            let symbol = self.config.symbol.clone();
-            let max_matches = self.config.max_matches;
-            match self.ensure_index().await {
-                Ok(index) => {
-                    crate::telemetry::record_tool_invocation("index");
-                    match index.search(&symbol, max_matches).await {
-                        Ok(candidates) => {
-                            stage_stats.index_candidates = candidates.len();
-                            crate::telemetry::record_tool_results("index", candidates.len());
-                            if !candidates.is_empty() {
-                                let (indexed_hits, indexed_count) =
-                                    self.probe(&rewrites, &candidates, ProbeKind::Indexed).await;
-                                stage_stats.index_probe_hits = indexed_count;
-                                hits.extend(indexed_hits);
-                            }
-                        }
-                        Err(err) => {
-                            tracing::warn!(error = %err, "tantivy search failed");
-                            self.push_warning(format!("index search failed: {err}"));
-                        }
-                    }
-                }
-                Err(err) => {
-                    tracing::warn!(error = %err, "failed to initialize index");
-                    self.push_warning(format!("index initialization failed: {err}"));
-                }
-            }
-            stage_stats.index_ms = elapsed_ms(index_stage_start);
-        }
-
-        if hits.is_empty() {
-            let root_clone = self.config.root.clone();
-            let symbol_clone = self.config.symbol.clone();
-            if let Some(rga_tool) = self.ensure_rga_tool() {
-                let rga_start = Instant::now();
-                crate::telemetry::record_tool_invocation("rga");
-                match rga_tool.search(&root_clone, symbol_clone.as_str()).await {
-                    Ok(matches) => {
-                        stage_stats.rga_hits = matches.len();
-                        crate::telemetry::record_tool_results("rga", matches.len());
-                        for m in matches {
-                            hits.push(SearchHit::from_rga(&self.config.root, m));
-                        }
-                    }
-                    Err(err) => {
-                        tracing::warn!(error = %err, "rga search failed");
-                        self.push_warning(format!("rga search failed: {err}"));
-                    }
-                }
-                stage_stats.rga_ms = elapsed_ms(rga_start);
-            }
-        }
-
-        // --- Disambiguate ---
-        let disambiguate_start = Instant::now();
-        let ast_scope: Vec<PathBuf> = hits
-            .iter()
-            .map(|hit| hit.path.clone())
-            .collect::<HashSet<_>>()
-            .into_iter()
-            .collect();
-        let ast_matches = self.disambiguate(&ast_scope).await;
-        stage_stats.disambiguate_ms = elapsed_ms(disambiguate_start);
-        stage_stats.ast_matches = ast_matches.len();
-        stage_stats.record_disambiguate_languages(&ast_matches, stage_stats.disambiguate_ms);
-
-        // --- Verify & Summarize ---
-        let verify_start = Instant::now();
-        let verification = self
-            .verify(hits, ast_matches, discover_set, discover_candidates.clone())
-            .await?;
-        stage_stats.verify_ms = elapsed_ms(verify_start);
-        stage_stats.record_verify_languages(&verification.language_counts, stage_stats.verify_ms);
-
-        stage_stats.precision = round_two(verification.metrics.precision);
-        stage_stats.density = round_two(verification.metrics.density);
-        stage_stats.clustering = round_two(verification.metrics.cluster_score);
-        stage_stats.reward = round_two(verification.metrics.reward);
+        let mut cycle = CycleState {
+            rewrites,
+            scoped,
+            scoped_only: scoped || self.config.require_scope,
+            stored,
+            ..CycleState::default()
+        };
+        cycle.stats.symbol_store_candidates = cycle.stored.len();
+        let pipeline = self.pipeline.clone();
+        pipeline.run(self, &mut cycle).await?;
+
+        let CycleState {
+            rewrites,
+            aliases,
+            verification,
+            suggestions,
+            stats: mut stage_stats,
+            ..
+        } = cycle;
+        let verification = verification.context("search pipeline has no verify stage")?;
+        stage_stats.record_quality(&verification.metrics, &self.config.reward_weights);
         stage_stats.cycle_latency_ms = stage_stats.discover_ms
             + stage_stats.probe_ms
             + stage_stats.escalate_ms
@@ -499,9 +1060,11 @@ impl SearchEngine {
 
         self.reward_total += verification.metrics.reward;
 
-        if let Err(err) = self.state.save() {
-            tracing::warn!(error = %err, "failed to persist cache state");
-        }
+        let repo_stats = self
+            .config
+            .explain
+            .then(|| self.state.lock().unwrap().repo_stats(false));
+        self.save_state().await;
 
         crate::telemetry::record_stage_latency("discover", stage_stats.discover_ms);
         crate::telemetry::record_stage_latency("probe", stage_stats.probe_ms);
@@ -510,23 +1073,36 @@ impl SearchEngine {
         crate::telemetry::record_stage_latency("rga", stage_stats.rga_ms);
         crate::telemetry::record_stage_latency("ast", stage_stats.disambiguate_ms);
         crate::telemetry::record_stage_latency("verify", stage_stats.verify_ms);
+        stage_stats.record_language_telemetry();
 
         let summary = SearchSummary {
             cycle: 1,
             symbol: self.config.symbol.clone(),
+            strategy: self.config.strategy,
+            revision: self.config.revision.clone(),
+            search_id: None,
             queries: rewrites,
+            matched_identifiers: verification.matched_identifiers,
+            aliases,
             top_hits: verification.top_hits,
             deduped: verification.dedup_count,
+            clusters: verification.clusters,
             next_actions: verification.next_actions,
+            suggestions,
             fd_candidates: verification.fd_candidates,
             ast_hits: verification.ast_hits,
             startup_stats: Some(self.startup_stats.clone()),
             stage_stats,
             reward: round_two(self.reward_total),
             warnings: self.warnings.clone(),
+            budget_exceeded_stages: self.budget_exceeded.clone(),
+            skipped: self.skipped_areas(),
+            repo_stats,
         };
 
-        crate::telemetry::record_reward(verification.metrics.reward);
+        verification
+            .metrics
+            .record_telemetry(&self.config.reward_weights);
         crate::telemetry::record_cycle_latency(summary.stage_stats.cycle_latency_ms);
         crate::telemetry::record_stage_latency("cycle", summary.stage_stats.cycle_latency_ms);
 
@@ -540,26 +1116,29 @@ impl SearchEngine {
             "search_cycle_complete"
         );
 
+        let summary = self.config.hooks.apply(&self.config.root, summary).await?;
         self.log_summary(&summary).await?;
 
         Ok(summary)
     }
 
-    async fn try_fast_path(&mut self, rewrites: &[String]) -> Result<Option<SearchSummary>> {
+    async fn try_fast_path(&mut self, mut rewrites: Vec<String>) -> Result<Option<SearchSummary>> {
         if !self.is_literal_symbol() {
             return Ok(None);
         }
 
         crate::telemetry::record_tool_invocation("rg");
         let probe_start = Instant::now();
-        let matches = match self
-            .rg_tool
-            .search_union(&self.config.root, rewrites, &[])
-            .await
-        {
-            Ok(matches) => matches,
-            Err(err) => {
-                self.push_warning(format!("fast-path ripgrep failed: {err}"));
+        let limit = self.stage_limit(BudgetStage::Probe);
+        let search = self.rg_tool.search_union(&self.config.root, &rewrites, &[]);
+        let matches = match within_budget(limit, search).await {
+            None => {
+                self.exceed_budget("probe");
+                return Ok(None);
+            }
+            Some(Ok(matches)) => matches,
+            Some(Err(err)) => {
+                self.push_warning(Warning::tool_error("rg", "fast-path ripgrep failed", &err));
                 tracing::warn!(error = %err, "fast-path ripgrep failed");
                 return Ok(None);
             }
@@ -570,7 +1149,6 @@ impl SearchEngine {
             return Ok(None);
         }
 
-        let probe_ms = elapsed_ms(probe_start);
         let mut hits: Vec<SearchHit> = matches
             .into_iter()
             .map(|m| {
@@ -582,6 +1160,10 @@ impl SearchEngine {
                 )
             })
             .collect();
+        let aliases = self
+            .follow_aliases(&mut hits, &mut rewrites, false, &[])
+            .await;
+        let probe_ms = elapsed_ms(probe_start);
         let total_hits = hits.len();
         let probe_hits_snapshot = hits.clone();
 
@@ -602,37 +1184,49 @@ impl SearchEngine {
         stage_stats.record_probe_languages(&probe_hits_snapshot, stage_stats.probe_ms);
         stage_stats.verify_ms = verify_ms;
         stage_stats.cycle_latency_ms = probe_ms + verify_ms;
-        stage_stats.precision = round_two(verification.metrics.precision);
-        stage_stats.density = round_two(verification.metrics.density);
-        stage_stats.clustering = round_two(verification.metrics.cluster_score);
-        stage_stats.reward = round_two(verification.metrics.reward);
+        stage_stats.record_quality(&verification.metrics, &self.config.reward_weights);
         stage_stats.record_verify_languages(&verification.language_counts, stage_stats.verify_ms);
 
         self.reward_total += verification.metrics.reward;
 
-        if let Err(err) = self.state.save() {
-            tracing::warn!(error = %err, "failed to persist cache state");
-        }
+        let repo_stats = self
+            .config
+            .explain
+            .then(|| self.state.lock().unwrap().repo_stats(false));
+        self.save_state().await;
 
         crate::telemetry::record_stage_latency("probe", stage_stats.probe_ms);
         crate::telemetry::record_stage_latency("verify", stage_stats.verify_ms);
+        stage_stats.record_language_telemetry();
 
         let summary = SearchSummary {
             cycle: 1,
             symbol: self.config.symbol.clone(),
-            queries: rewrites.to_vec(),
+            strategy: self.config.strategy,
+            revision: self.config.revision.clone(),
+            search_id: None,
+            queries: rewrites,
+            matched_identifiers: verification.matched_identifiers,
+            aliases,
             top_hits: verification.top_hits,
             deduped: verification.dedup_count,
+            clusters: verification.clusters,
             next_actions: verification.next_actions,
+            suggestions: None,
             fd_candidates: Vec::new(),
             ast_hits: Vec::new(),
             startup_stats: Some(self.startup_stats.clone()),
             stage_stats,
             reward: round_two(self.reward_total),
             warnings: self.warnings.clone(),
+            budget_exceeded_stages: self.budget_exceeded.clone(),
+            skipped: self.skipped_areas(),
+            repo_stats,
         };
 
-        crate::telemetry::record_reward(verification.metrics.reward);
+        verification
+            .metrics
+            .record_telemetry(&self.config.reward_weights);
         crate::telemetry::record_cycle_latency(summary.stage_stats.cycle_latency_ms);
         crate::telemetry::record_stage_latency("cycle", summary.stage_stats.cycle_latency_ms);
 
@@ -646,14 +1240,42 @@ impl SearchEngine {
             "search_cycle_complete"
         );
 
+        let summary = self.config.hooks.apply(&self.config.root, summary).await?;
         self.log_summary(&summary).await?;
 
         Ok(Some(summary))
     }
 
+    /// Save the hint state on the blocking pool, where waiting on a file lock or a shared store
+    /// cannot stall other searches.
+    async fn save_state(&self) {
+        let state = Arc::clone(&self.state);
+        match tokio::task::spawn_blocking(move || state.lock().unwrap().save()).await {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => tracing::warn!(error = %err, "failed to persist cache state"),
+            Err(err) => tracing::warn!(error = %err, "persistent state task failed"),
+        }
+    }
+
+    /// Suggestions for a zero-hit cycle; scoped searches only draw on their own files.
+    async fn suggest(&self, scope_files: &[PathBuf]) -> Suggestions {
+        let root = self.config.root.clone();
+        let index_dir = self.config.index_dir.clone();
+        let symbol = self.config.symbol.clone();
+        let languages = self.config.language_tokens.clone();
+        let scope = self.config.scope.is_some().then(|| scope_files.to_vec());
+        tokio::task::spawn_blocking(move || {
+            Suggestions::collect(&root, &index_dir, &symbol, &languages, scope.as_deref())
+        })
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!(error = %err, "suggestion task failed");
+            Suggestions::default()
+        })
+    }
+
     fn is_literal_symbol(&self) -> bool {
-        let s = self.config.symbol.trim();
-        !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        self.config.strategy.is_identifier() && is_literal_identifier(&self.config.symbol)
     }
 
     fn should_run_ast(&self) -> bool {
@@ -664,8 +1286,19 @@ impl SearchEngine {
         if s.is_empty() {
             return false;
         }
-        s.chars()
-            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '$'))
+        // ast-grep has no grammar for schema, Objective-C, or config files, so such a search has
+        // nothing to parse.
+        let tokens = &self.config.language_tokens;
+        if !tokens.is_empty() && !tokens.iter().any(|token| has_ast_grammar(token)) {
+            return false;
+        }
+        match self.config.strategy {
+            Strategy::Identifier | Strategy::ModulePath => {
+                s.chars().all(|c| identifier::is_continue(c) || c == '$')
+            }
+            Strategy::Structural | Strategy::Glob => true,
+            Strategy::Phrase | Strategy::StringLiteral => false,
+        }
     }
 
     fn context_padding(&self, hit: &SearchHit) -> (usize, usize, bool) {
@@ -730,24 +1363,54 @@ impl SearchEngine {
         let mut line = serde_json::to_vec(&entry)?;
         line.push(b'\n');
         file.write_all(&line).await?;
+        // Tokio files finish writes in the background; flush so the line is on disk on return.
+        file.flush().await?;
 
         Ok(())
     }
 
+    /// Candidate files in priority order: remembered hits for the symbol, fd file-name
+    /// matches, config files in `--config` mode, then files from frequently hit directories
+    /// and Swift package hints.
     async fn discover(&mut self) -> Vec<PathBuf> {
         let root = self.config.root.clone();
         let symbol = self.config.symbol.clone();
+        // Checked here because rg searches the paths it is handed whatever their type.
+        let file_types = self.config.file_types.clone();
         let extension_filters = extensions_for_languages(&self.config.language_tokens);
-        let extensions = extension_filters.as_deref();
+        let allowed = |path: &Path| match &file_types {
+            Some(types) => types.matches(path),
+            None => passes_extension_filter(path, extension_filters.as_deref()),
+        };
         let mut candidates: Vec<PathBuf> = Vec::new();
         let mut seen: HashSet<PathBuf> = HashSet::new();
 
-        let fd_results = if let Some(fd_tool) = self.ensure_fd_tool() {
+        let symbol_hints = self
+            .state
+            .lock()
+            .unwrap()
+            .hints_for_symbol(&self.config.symbol);
+        crate::telemetry::record_cache_hits("symbol_hints", symbol_hints.len());
+        for hint in symbol_hints {
+            if allowed(&hint) && seen.insert(hint.clone()) {
+                candidates.push(hint);
+            }
+        }
+
+        // fd matches file names, which only an identifier can be part of.
+        let fd_tool = if self.config.strategy.is_identifier() {
+            self.ensure_fd_tool()
+        } else {
+            None
+        };
+        let fd_results = if let Some(fd_tool) = fd_tool {
             crate::telemetry::record_tool_invocation("fd");
-            match fd_tool.run(&root, symbol.as_str()).await {
+            let result = fd_tool.run(&root, symbol.as_str()).await;
+            self.record_tool_outcome("fd", result.as_ref().err());
+            match result {
                 Ok(results) => results,
                 Err(err) => {
-                    self.push_warning(format!("fd invocation failed: {err}"));
+                    self.push_warning(Warning::tool_error("fd", "fd invocation failed", &err));
                     tracing::warn!(error = %err, "fd invocation failed");
                     Vec::new()
                 }
@@ -759,23 +1422,27 @@ impl SearchEngine {
 
         for path in fd_results {
             if let Ok(normalized) = normalize_path(&root, &path) {
-                if passes_extension_filter(&normalized, extensions)
-                    && seen.insert(normalized.clone())
-                {
+                if !allowed(&normalized) {
+                    if let Some(ext) = normalized.extension().and_then(|ext| ext.to_str()) {
+                        self.filtered_extensions.insert(ext.to_ascii_lowercase());
+                    }
+                    continue;
+                }
+                if seen.insert(normalized.clone()) {
                     candidates.push(normalized);
                 }
             }
         }
 
-        let symbol_hints = self.state.hints_for_symbol(&self.config.symbol);
-        crate::telemetry::record_cache_hits("symbol_hints", symbol_hints.len());
-        for hint in symbol_hints {
-            if passes_extension_filter(&hint, extensions) && seen.insert(hint.clone()) {
-                candidates.push(hint);
+        if self.config.config_mode {
+            for path in iac::config_files(&root, CONFIG_FILES, allowed) {
+                if seen.insert(path.clone()) {
+                    candidates.push(path);
+                }
             }
         }
 
-        let directory_hints = self.state.top_directories(3);
+        let directory_hints = self.state.lock().unwrap().top_directories(3);
         crate::telemetry::record_cache_hits("directory_hints", directory_hints.len());
         for dir in directory_hints {
             let dir_path = root.join(&dir);
@@ -793,9 +1460,7 @@ impl SearchEngine {
                                 }
                             }
                             if let Ok(normalized) = normalize_path(&root, &path) {
-                                if passes_extension_filter(&normalized, extensions)
-                                    && seen.insert(normalized.clone())
-                                {
+                                if allowed(&normalized) && seen.insert(normalized.clone()) {
                                     candidates.push(normalized);
                                 }
                             }
@@ -813,44 +1478,24 @@ impl SearchEngine {
         }
 
         if languages_include(&self.config.language_tokens, "swift") {
-            let mut swift_hints: Vec<PathBuf> = Vec::new();
-            let package_manifest = root.join("Package.swift");
-            if package_manifest.is_file() {
-                swift_hints.push(package_manifest);
-            }
-            if let Ok(entries) = fs::read_dir(&root) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                        if name.eq_ignore_ascii_case("sources") && path.is_dir() {
-                            if let Ok(children) = fs::read_dir(&path) {
-                                for child in children.flatten().take(20) {
-                                    let child_path = child.path();
-                                    if child_path.is_file() {
-                                        swift_hints.push(child_path);
-                                    } else if child_path.is_dir() {
-                                        if let Ok(grandchildren) = fs::read_dir(&child_path) {
-                                            for file in grandchildren.flatten().take(10) {
-                                                let file_path = file.path();
-                                                if file_path.is_file() {
-                                                    swift_hints.push(file_path);
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+            for hint in self
+                .config
+                .swift_targets
+                .discover(SWIFT_TARGET_FILES, allowed)
+            {
+                if seen.insert(hint.clone()) {
+                    candidates.push(hint);
                 }
             }
-            for hint in swift_hints {
-                if let Ok(normalized) = normalize_path(&root, &hint) {
-                    if passes_extension_filter(&normalized, extensions)
-                        && seen.insert(normalized.clone())
-                    {
-                        candidates.push(normalized);
-                    }
+        }
+        if languages_include(&self.config.language_tokens, "objc") {
+            for hint in self
+                .config
+                .swift_targets
+                .bridged_sources(SWIFT_TARGET_FILES, allowed)
+            {
+                if seen.insert(hint.clone()) {
+                    candidates.push(hint);
                 }
             }
         }
@@ -858,6 +1503,29 @@ impl SearchEngine {
         candidates
     }
 
+    /// The leading `--max-scope-files` candidates, which `discover` lists by priority; dropping
+    /// any is reported as a warning rather than silently narrowing the search.
+    fn sample_scope<'a>(&mut self, candidates: &'a [PathBuf]) -> &'a [PathBuf] {
+        let Some(cap) = self.config.max_scope_files else {
+            return candidates;
+        };
+        if candidates.len() <= cap {
+            return candidates;
+        }
+        tracing::warn!(candidates = candidates.len(), cap, "probe scope truncated");
+        self.push_warning(
+            Warning::new(
+                WarningKind::ScopeTruncated,
+                format!(
+                    "probed {cap} of {} candidate files; raise --max-scope-files to search the rest",
+                    candidates.len()
+                ),
+            )
+            .with_tool("rg"),
+        );
+        &candidates[..cap]
+    }
+
     async fn probe(
         &mut self,
         rewrites: &[String],
@@ -891,32 +1559,269 @@ impl SearchEngine {
                 (hits, hit_count)
             }
             Err(err) => {
-                self.push_warning(format!("ripgrep invocation failed: {err}"));
+                self.push_warning(Warning::tool_error("rg", "ripgrep invocation failed", &err));
                 tracing::warn!(error = %err, "ripgrep invocation failed");
                 (Vec::new(), 0)
             }
         }
     }
 
-    async fn disambiguate(&mut self, scope: &[PathBuf]) -> Vec<AstGrepMatch> {
+    /// Hits for the indexed lines in `matches` that contain the symbol under the search's case
+    /// and whole-word rules, read back from disk, plus the notebooks among the files, whose
+    /// lines must be probed with ripgrep instead.
+    #[cfg(feature = "indexing")]
+    fn indexed_line_hits(&self, matches: &[IndexMatch]) -> (Vec<SearchHit>, Vec<PathBuf>) {
+        let symbol = self.config.symbol.trim();
+        let ignore_case = self.config.case.ignores_case(symbol);
+        let mut lines_by_file: BTreeMap<&Path, Vec<usize>> = BTreeMap::new();
+        for found in matches {
+            lines_by_file
+                .entry(found.path.as_path())
+                .or_default()
+                .push(found.line);
+        }
+
+        let mut hits = Vec::new();
+        let mut notebooks = Vec::new();
+        for (path, lines) in lines_by_file {
+            if notebook::is_notebook(path) {
+                notebooks.push(path.to_path_buf());
+                continue;
+            }
+            let Ok(contents) = source::read(&self.config.root.join(path)) else {
+                continue;
+            };
+            let file_lines: Vec<&str> = contents.lines().collect();
+            for line in lines {
+                let Some(text) = line.checked_sub(1).and_then(|index| file_lines.get(index)) else {
+                    continue;
+                };
+                let Some(submatch) =
+                    find_literal(text, symbol, ignore_case, self.config.whole_word)
+                else {
+                    continue;
+                };
+                let snippet: String = text.chars().take(self.config.max_columns).collect();
+                let snippet_length = text.chars().count();
+                hits.push(SearchHit {
+                    path: path.to_path_buf(),
+                    inner_path: None,
+                    cell: None,
+                    line,
+                    snippet,
+                    score: 1.0,
+                    origin: HitOrigin::Index,
+                    raw_snippet: None,
+                    snippet_length,
+                    raw_snippet_truncated: snippet_length >= self.config.max_columns,
+                    submatch: Some(submatch),
+                });
+            }
+        }
+        (hits, notebooks)
+    }
+
+    /// Swift package and Xcode targets compiling the file of `hit`.
+    fn hit_targets(&mut self, hit: &SearchHit) -> Vec<String> {
+        if hit.inner_path.is_some() || !SwiftTargets::is_target_source(&hit.path) {
+            return Vec::new();
+        }
+        self.config.swift_targets.targets_for(&hit.path)
+    }
+
+    /// Drop YAML hits on a key whose path does not end in the dotted key path `segments`, so
+    /// `spec.replicas` skips `replicas:` under `status`. Hits on other lines are kept.
+    fn retain_key_path_hits(&self, hits: &mut Vec<SearchHit>, segments: &[&str]) {
+        let mut sources: HashMap<PathBuf, Option<String>> = HashMap::new();
+        hits.retain(|hit| {
+            if hit.inner_path.is_some() || hit.cell.is_some() || !iac::is_yaml_file(&hit.path) {
+                return true;
+            }
+            let source = sources
+                .entry(hit.path.clone())
+                .or_insert_with(|| fs::read_to_string(self.config.root.join(&hit.path)).ok());
+            source
+                .as_deref()
+                .and_then(|source| iac::key_path_ends_with(source, hit.line, segments))
+                .unwrap_or(true)
+        });
+    }
+
+    /// Files among `hits` that are TypeScript/JavaScript barrels.
+    fn barrel_files(&self, hits: &[SearchHit]) -> HashSet<PathBuf> {
+        let paths: HashSet<&PathBuf> = hits
+            .iter()
+            .filter(|hit| hit.inner_path.is_none() && hit.cell.is_none())
+            .map(|hit| &hit.path)
+            .collect();
+        paths
+            .into_iter()
+            .filter(|path| ts_aliases::is_barrel_file(&self.config.root.join(path)))
+            .cloned()
+            .collect()
+    }
+
+    /// Follow TypeScript/JavaScript renames of the symbol found in `hits` (`export { target as
+    /// symbol }`, `import symbol from` a module exporting `target` as default), up to
+    /// `MAX_ALIAS_DEPTH` hops. Each target's rewrites are probed and added to `rewrites`, and its
+    /// hits to `hits`; the hops taken are returned for the summary. `scoped` searches probe
+    /// only `scope`.
+    async fn follow_aliases(
+        &mut self,
+        hits: &mut Vec<SearchHit>,
+        rewrites: &mut Vec<String>,
+        scoped: bool,
+        scope: &[PathBuf],
+    ) -> Vec<SymbolAlias> {
+        let tokens = &self.config.language_tokens;
+        let scripts = tokens.is_empty()
+            || ["ts", "tsx", "js", "jsx"]
+                .iter()
+                .any(|token| languages_include(tokens, token));
+        if !scripts || !self.is_literal_symbol() {
+            return Vec::new();
+        }
+        let kind = if scoped {
+            if scope.is_empty() {
+                return Vec::new();
+            }
+            ProbeKind::Scoped
+        } else {
+            ProbeKind::Global
+        };
+
+        let mut aliases: Vec<SymbolAlias> = Vec::new();
+        let mut names = vec![self.config.symbol.trim().to_string()];
+        let mut searched: HashSet<String> = names.iter().cloned().collect();
+        let mut scanned = 0;
+        for _ in 0..MAX_ALIAS_DEPTH {
+            let mut targets: Vec<String> = Vec::new();
+            for hit in &hits[scanned..] {
+                if hit.inner_path.is_some() || !ts_aliases::is_module_path(&hit.path) {
+                    continue;
+                }
+                for name in &names {
+                    let found =
+                        ts_aliases::targets(&self.config.root, &hit.path, &hit.snippet, name);
+                    for (target, kind) in found {
+                        let known = aliases
+                            .iter()
+                            .any(|alias| alias.alias == *name && alias.target == target);
+                        if !known {
+                            aliases.push(SymbolAlias {
+                                alias: name.clone(),
+                                target: target.clone(),
+                                kind,
+                                path: hit.path.to_string_lossy().into_owned(),
+                                line: hit.line,
+                            });
+                        }
+                        if searched.insert(target.clone()) {
+                            targets.push(target);
+                        }
+                    }
+                }
+            }
+            scanned = hits.len();
+            if targets.is_empty() {
+                break;
+            }
+            tracing::debug!(?targets, "following symbol aliases");
+            let target_rewrites: Vec<String> = targets
+                .iter()
+                .flat_map(|target| {
+                    QueryRewriter::for_symbol(target, &self.config.language_tokens)
+                        .whole_word(self.config.whole_word)
+                        .build()
+                })
+                .collect();
+            let limit = self.stage_limit(BudgetStage::Probe);
+            let probe = self.probe(&target_rewrites, scope, kind.clone());
+            match within_budget(limit, probe).await {
+                Some((found, _)) => hits.extend(found),
+                None => {
+                    self.exceed_budget("aliases");
+                    break;
+                }
+            }
+            *rewrites = dedup_queries(rewrites.drain(..).chain(target_rewrites));
+            names = targets;
+        }
+        aliases
+    }
+
+    /// Spellings of the symbol that differ only in case and turned up in `hits`. AST patterns
+    /// match identifiers exactly, so a case-folded search names each spelling explicitly.
+    fn case_variants(&self, hits: &[SearchHit]) -> Vec<String> {
+        let symbol = self.config.symbol.trim();
+        if !self.is_literal_symbol() || !self.config.case.ignores_case(symbol) {
+            return Vec::new();
+        }
+        let variants: BTreeSet<&str> = hits
+            .iter()
+            .flat_map(|hit| hit.snippet.split(|c: char| !identifier::is_continue(c)))
+            .filter(|word| *word != symbol && word.eq_ignore_ascii_case(symbol))
+            .collect();
+        variants
+            .into_iter()
+            .take(MAX_CASE_VARIANTS)
+            .map(str::to_string)
+            .collect()
+    }
+
+    async fn disambiguate(&mut self, scope: &[PathBuf], spellings: &[String]) -> Vec<AstGrepMatch> {
         if !self.should_run_ast() {
             return Vec::new();
         }
 
         let root = self.config.root.clone();
         let symbol = self.config.symbol.clone();
-        let language_tokens = self.config.language_tokens.clone();
+        let symbols: Vec<String> = std::iter::once(symbol.clone())
+            .chain(spellings.iter().cloned())
+            .collect();
+        let language_tokens: Vec<String> = self
+            .config
+            .language_tokens
+            .iter()
+            .filter(|token| has_ast_grammar(token))
+            .cloned()
+            .collect();
+        let strategy = self.config.strategy;
+        let glob_regex = (strategy == Strategy::Glob).then(|| {
+            let regex = QueryRewriter::glob_regex(symbol.trim());
+            if self.config.case.ignores_case(&symbol) {
+                format!("(?i){regex}")
+            } else {
+                regex
+            }
+        });
         let Some(ast_tool) = self.ensure_ast_tool() else {
             return Vec::new();
         };
 
         crate::telemetry::record_tool_invocation("ast-grep");
 
-        ast_tool
-            .search_identifier(&root, symbol.as_str(), &language_tokens, scope)
-            .await
+        let search = match (strategy, glob_regex) {
+            (Strategy::Structural, _) => {
+                ast_tool
+                    .search_pattern(&root, &symbol, &language_tokens, scope)
+                    .await
+            }
+            (_, Some(regex)) => {
+                ast_tool
+                    .search_identifier_regex(&root, &regex, &language_tokens, scope)
+                    .await
+            }
+            _ => {
+                ast_tool
+                    .search_identifiers(&root, &symbols, &language_tokens, scope)
+                    .await
+            }
+        };
+        search
             .map(|matches| {
                 crate::telemetry::record_tool_results("ast-grep", matches.len());
+                self.record_tool_outcome("ast-grep", None);
                 matches
             })
             .unwrap_or_else(|err| {
@@ -926,13 +1831,24 @@ impl SearchEngine {
                         pattern = %pattern_err.pattern(),
                         "ast-grep pattern invalid; falling back to literal search"
                     );
-                    self.push_warning(format!(
-                        "ast-grep pattern invalid for `{symbol}`: {}",
-                        pattern_err.message()
-                    ));
+                    self.push_warning(
+                        Warning::new(
+                            WarningKind::PatternError,
+                            format!(
+                                "ast-grep pattern invalid for `{symbol}`: {}",
+                                pattern_err.message()
+                            ),
+                        )
+                        .with_tool("ast-grep"),
+                    );
                 } else {
                     tracing::warn!(error = %err, "ast-grep invocation failed");
-                    self.push_warning(format!("ast-grep invocation failed: {err}"));
+                    self.record_tool_outcome("ast-grep", Some(&err));
+                    self.push_warning(Warning::tool_error(
+                        "ast-grep",
+                        "ast-grep invocation failed",
+                        &err,
+                    ));
                 }
                 Vec::new()
             })
@@ -949,11 +1865,81 @@ impl SearchEngine {
                 extensions,
             };
             let built = TantivyIndex::open_or_build(index_config).await?;
+            if let Some(reason) = built.recovery() {
+                self.push_warning(
+                    Warning::new(
+                        WarningKind::IndexRebuilt,
+                        format!(
+                            "{reason}; rebuilt the index and moved the old files to {}",
+                            self.config
+                                .index_dir
+                                .join(swe_grep_indexer::QUARANTINE_DIRNAME)
+                                .display()
+                        ),
+                    )
+                    .with_tool("index"),
+                );
+            }
             self.index = Some(built);
         }
         Ok(self.index.as_ref().expect("index initialized"))
     }
 
+    #[cfg(feature = "indexing")]
+    async fn ensure_symbol_store(&mut self) -> Result<&SymbolStore> {
+        if self.symbol_store.is_none() {
+            let extensions = extensions_for_languages(&self.config.language_tokens)
+                .map(|exts| exts.into_iter().map(|s| s.to_string()).collect::<Vec<_>>());
+            let store_config = IndexConfig {
+                root: self.config.root.clone(),
+                index_dir: self.config.index_dir.clone(),
+                extensions,
+            };
+            let store = SymbolStore::open_or_refresh(store_config).await?;
+            self.symbol_store = Some(store);
+        }
+        Ok(self
+            .symbol_store
+            .as_ref()
+            .expect("symbol store initialized"))
+    }
+
+    /// Files the symbol store lists for an exact identifier query.
+    #[cfg(feature = "indexing")]
+    async fn stored_candidates(&mut self) -> Vec<PathBuf> {
+        if !self.config.use_index || !self.is_literal_symbol() {
+            return Vec::new();
+        }
+        let symbol = self.config.symbol.trim().to_string();
+        let ignore_case = self.config.case.ignores_case(&symbol);
+        crate::telemetry::record_tool_invocation("symbol_store");
+        match self.ensure_symbol_store().await {
+            Ok(store) => {
+                let files = if ignore_case {
+                    store.lookup_ignore_case(&symbol)
+                } else {
+                    store.lookup(&symbol)
+                };
+                crate::telemetry::record_tool_results("symbol_store", files.len());
+                files
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to open symbol store");
+                self.push_warning(Warning::tool_error(
+                    "index",
+                    "symbol store unavailable",
+                    &err,
+                ));
+                Vec::new()
+            }
+        }
+    }
+
+    #[cfg(not(feature = "indexing"))]
+    async fn stored_candidates(&mut self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
     async fn verify(
         &mut self,
         hits: Vec<SearchHit>,
@@ -970,23 +1956,42 @@ impl SearchEngine {
             })
             .collect();
 
+        let mut notebooks: HashMap<PathBuf, Option<Notebook>> = HashMap::new();
+        let hits: Vec<SearchHit> = hits
+            .into_iter()
+            .filter_map(|hit| hit.into_notebook_cell(&self.config.root, &mut notebooks))
+            .collect();
+
+        let aliases = file_aliases(&self.config.root, hits.iter().map(|hit| &hit.path));
         let mut dedup: HashMap<(PathBuf, usize), SearchHit> = HashMap::new();
         for mut hit in hits {
-            let key = (hit.path.clone(), hit.line);
-            if fd_set.contains(&hit.path) {
-                hit.score += 0.2;
-            }
-            match &hit.origin {
-                HitOrigin::Ripgrep(ProbeKind::Global) => hit.score -= 0.05,
-                #[cfg(feature = "indexing")]
-                HitOrigin::Ripgrep(ProbeKind::Indexed) => hit.score += 0.1,
-                HitOrigin::Rga => hit.score -= 0.1,
-                _ => {}
+            if self.config.strategy == Strategy::Phrase {
+                hit.score = phrase_exactness(&hit.snippet, self.config.symbol.trim());
             }
-            if ast_set.contains(&key) {
-                hit.score += 0.5;
+            let key = (hit.location(), hit.line);
+            let in_fd = fd_set.contains(&hit.path);
+            let in_ast = hit.inner_path.is_none() && ast_set.contains(&key);
+            if let Some(sample) = self.calibration.as_mut() {
+                sample.candidates.push(CalibrationCandidate {
+                    path: hit.path.clone(),
+                    line: hit.line,
+                    base_score: hit.score,
+                    origin: hit.origin.clone(),
+                    in_fd,
+                    in_ast,
+                });
+            }
+            hit.score = weighted_score(hit.score, &hit.origin, in_fd, in_ast, &self.config.scoring);
+            if in_ast {
                 hit.origin = HitOrigin::AstGrep;
             }
+            let key = match aliases.get(&hit.path) {
+                Some(alias) => {
+                    hit.path = alias.clone();
+                    (hit.location(), hit.line)
+                }
+                None => key,
+            };
 
             dedup
                 .entry(key)
@@ -999,32 +2004,116 @@ impl SearchEngine {
         }
 
         let mut dedup_hits: Vec<SearchHit> = dedup.into_values().collect();
+        if !self.plugins.is_empty() {
+            dedup_hits = self.apply_plugins(dedup_hits);
+        }
+        if languages_include(&self.config.language_tokens, "yaml")
+            && let Some(segments) = iac::key_path_segments(&self.config.symbol)
+        {
+            self.retain_key_path_hits(&mut dedup_hits, &segments);
+        }
+        let barrels = self.barrel_files(&dedup_hits);
+        let in_barrel = |hit: &SearchHit| hit.inner_path.is_none() && barrels.contains(&hit.path);
+        let config_mode = self.config.config_mode;
+        let outside_config = |hit: &SearchHit| {
+            config_mode && (hit.inner_path.is_some() || !iac::is_config_file(&hit.path))
+        };
+        // In `--config` mode config files rank first; barrel re-exports rank below every other
+        // hit. Break score ties by location so repeated searches (e.g. paged requests) rank
+        // alike.
         dedup_hits.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
+            outside_config(a)
+                .cmp(&outside_config(b))
+                .then_with(|| in_barrel(a).cmp(&in_barrel(b)))
+                .then_with(|| {
+                    b.score
+                        .partial_cmp(&a.score)
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .then_with(|| a.path.cmp(&b.path))
+                .then_with(|| a.inner_path.cmp(&b.inner_path))
+                .then_with(|| a.cell.cmp(&b.cell))
+                .then_with(|| a.line.cmp(&b.line))
         });
 
+        if let (Some(owner), Some(code_owners)) = (&self.config.owner, &self.code_owners) {
+            dedup_hits.retain(|hit| owners::is_owned_by(code_owners.owners_for(&hit.path), owner));
+        }
+
         self.dedup_cache.retain_new(&mut dedup_hits);
+        let clusters = clusters::cluster(
+            &self.config.root,
+            dedup_hits.iter().map(|hit| (hit.path.as_path(), hit.line)),
+        );
+        let matched_identifiers = if self.config.strategy == Strategy::Glob {
+            self.matched_identifiers(&dedup_hits)
+        } else {
+            Vec::new()
+        };
+
+        self.state
+            .lock()
+            .unwrap()
+            .observe(&self.config.symbol, &dedup_hits);
 
-        self.state.observe(&self.config.symbol, &dedup_hits);
+        // Snapshots of `--rev` are exported trees, so only the live worktree can be dirty.
+        let worktree_changes = if self.config.revision.is_some() {
+            WorktreeChanges::default()
+        } else {
+            let mut paths: Vec<PathBuf> = dedup_hits
+                .iter()
+                .take(self.config.top_hits)
+                .filter(|hit| hit.path.is_relative())
+                .map(|hit| hit.path.clone())
+                .collect();
+            paths.sort();
+            paths.dedup();
+            WorktreeChanges::detect(&self.config.root, &paths).await
+        };
+        let linker = self
+            .config
+            .link_format
+            .map(|format| Linker::new(format, &self.config.root, self.config.location.as_ref()));
 
-        let top_hits: Vec<TopHit> = dedup_hits
+        let mut redactions = 0;
+        let mut top_hits: Vec<TopHit> = dedup_hits
             .iter()
-            .take(5)
+            .take(self.config.top_hits)
             .map(|hit| {
-                let formatted_snippet =
+                // Archive members cannot be read from disk, so they get no context, body, or hints;
+                // notebook cells take their context from the cell rather than the JSON file.
+                let in_archive = hit.inner_path.is_some();
+                let mut formatted_snippet =
                     format_snippet(&self.config.root, &hit.path, hit.line, &hit.snippet);
                 let (context_before, context_after, auto_expanded_context) =
                     self.context_padding(hit);
-                let context_window = gather_expanded_snippet(
-                    &self.config.root,
-                    &hit.path,
-                    hit.line,
-                    context_before,
-                    context_after,
-                );
-                let (expanded_snippet, context_start, context_end, auto_context_flag) =
+                let cell_lines = hit.cell.and_then(|cell| {
+                    notebooks
+                        .get(&hit.path)
+                        .and_then(Option::as_ref)
+                        .and_then(|notebook| notebook.cell_lines(cell))
+                });
+                let context_window = if let Some(lines) = cell_lines {
+                    numbered_window(lines, hit.line, context_before, context_after)
+                } else if in_archive || hit.cell.is_some() {
+                    None
+                } else {
+                    gather_expanded_snippet(
+                        &self.config.root,
+                        &hit.path,
+                        hit.line,
+                        context_before,
+                        context_after,
+                    )
+                };
+                let mut doc_comment = if let Some(lines) = cell_lines {
+                    doc_comments::doc_comment(lines, hit.line)
+                } else if in_archive || hit.cell.is_some() {
+                    None
+                } else {
+                    gather_doc_comment(&self.config.root, &hit.path, hit.line)
+                };
+                let (mut expanded_snippet, context_start, context_end, auto_context_flag) =
                     match context_window {
                         Some((snippet, start, end)) => {
                             (Some(snippet), Some(start), Some(end), auto_expanded_context)
@@ -1032,43 +2121,169 @@ impl SearchEngine {
                         None => (None, None, None, false),
                     };
 
-                let include_body = self.should_attach_body(&hit.path);
-                let (body, body_retrieved) = if include_body {
+                let mut line_window =
+                    if hit.raw_snippet_truncated && !in_archive && hit.cell.is_none() {
+                        gather_line_window(
+                            &self.config.root,
+                            &hit.path,
+                            hit.line,
+                            hit.submatch,
+                            &self.config.symbol,
+                        )
+                    } else {
+                        None
+                    };
+
+                let include_body =
+                    !in_archive && hit.cell.is_none() && self.should_attach_body(&hit.path);
+                let (mut body, body_retrieved) = if include_body {
                     let payload = self.fetch_body(&hit.path);
                     (payload.body, payload.retrieved)
                 } else {
                     (None, false)
                 };
 
-                let hints = self.compute_context_hints(&hit.path, hit.line);
+                let hints = if in_archive || hit.cell.is_some() {
+                    Vec::new()
+                } else {
+                    let mut hints = self.compute_context_hints(&hit.path, hit.line);
+                    if let Some(source) = self.generated.source_for(&hit.path) {
+                        let line = generated::definition_line(
+                            &self.config.root,
+                            &source,
+                            &self.config.symbol,
+                        );
+                        push_hint(&mut hints, "generated_from", display_path(&source), line);
+                    }
+                    hints
+                };
+
+                // Archive members and notebook cells number lines apart from the file on disk,
+                // so they are only flagged as dirty, and their links open the file itself.
+                let mut change = worktree_changes.line(&hit.path, hit.line);
+                if in_archive || hit.cell.is_some() {
+                    change.head_line = None;
+                    change.worktree_line = None;
+                }
+
+                let mut raw_snippet = hit.raw_snippet.clone();
+                if self.config.redact_secrets {
+                    redactions += [
+                        &mut formatted_snippet,
+                        &mut raw_snippet,
+                        &mut line_window,
+                        &mut expanded_snippet,
+                        &mut doc_comment,
+                        &mut body,
+                        &mut change.head_line,
+                        &mut change.worktree_line,
+                    ]
+                    .into_iter()
+                    .map(redact::redact_option)
+                    .sum::<usize>();
+                }
+
+                let (snippet_highlights, expanded_snippet_highlights) = match self
+                    .config
+                    .highlight
+                    .then(|| {
+                        detect_language_from_path(hit.inner_path.as_deref().unwrap_or(&hit.path))
+                    })
+                    .flatten()
+                {
+                    Some(language) => (
+                        formatted_snippet
+                            .as_deref()
+                            .map(|snippet| highlight::highlight(language, snippet))
+                            .unwrap_or_default(),
+                        expanded_snippet
+                            .as_deref()
+                            .map(|window| highlight::highlight_numbered(language, window))
+                            .unwrap_or_default(),
+                    ),
+                    None => (Vec::new(), Vec::new()),
+                };
+
+                let line = (!in_archive && hit.cell.is_none()).then_some(hit.line);
+                let link = linker
+                    .as_ref()
+                    .and_then(|linker| linker.link(&hit.path, line));
+                let targets = if self.config.language_tokens.is_empty()
+                    || languages_include(&self.config.language_tokens, "swift")
+                    || languages_include(&self.config.language_tokens, "objc")
+                {
+                    self.hit_targets(hit)
+                } else {
+                    Vec::new()
+                };
+                let permalink = self
+                    .config
+                    .location
+                    .as_ref()
+                    .filter(|_| self.config.permalinks)
+                    .map(|location| location.permalink(&hit.path, line));
 
                 TopHit {
-                    path: hit.path.to_string_lossy().to_string(),
+                    path: display_path(&hit.location()),
                     line: hit.line,
+                    root: None,
+                    archive_path: in_archive.then(|| display_path(&hit.path)),
+                    inner_path: hit.inner_path.as_deref().map(display_path),
+                    cell: hit.cell,
+                    owners: self
+                        .code_owners
+                        .as_ref()
+                        .map(|code_owners| code_owners.owners_for(&hit.path).to_vec())
+                        .unwrap_or_default(),
                     score: round_two(hit.score),
                     origin: hit.origin.as_str().to_string(),
                     origin_label: self.format_origin_label(&hit.origin, &hit.path),
                     snippet: formatted_snippet,
-                    raw_snippet: hit.raw_snippet.clone(),
+                    raw_snippet,
                     snippet_length: Some(hit.snippet_length),
                     raw_snippet_truncated: hit.raw_snippet_truncated,
+                    line_window,
                     expanded_snippet,
                     context_start,
                     context_end,
                     auto_expanded_context: auto_context_flag,
                     body,
                     body_retrieved,
+                    doc_comment,
                     hints,
+                    snippet_highlights,
+                    expanded_snippet_highlights,
+                    dirty: change.dirty,
+                    head_line: change.head_line,
+                    worktree_line: change.worktree_line,
+                    link,
+                    permalink,
+                    barrel: in_barrel(hit),
+                    targets,
                 }
             })
             .collect();
+        sort_top_hits(&mut top_hits, self.config.sort, &self.config.root);
+        if redactions > 0 {
+            self.push_warning(Warning::new(
+                WarningKind::SecretsRedacted,
+                format!(
+                    "redacted {redactions} secrets from snippets, context, bodies, and changed lines"
+                ),
+            ));
+        }
 
-        let next_actions: Vec<String> = top_hits
-            .iter()
-            .map(|hit| format!("inspect {}:{}", hit.path, hit.line))
-            .collect();
+        let next_actions = hit_actions(&top_hits);
 
-        let metrics = compute_metrics(&dedup_hits, &ast_set, fd_set.len());
+        let metrics = compute_metrics(
+            &dedup_hits,
+            &ast_set,
+            fd_set.len(),
+            &self.config.reward_weights,
+        );
+        if let Some(sample) = self.calibration.as_mut() {
+            sample.metrics = metrics.clone();
+        }
 
         let language_counts =
             aggregate_language_counts(dedup_hits.iter().map(|hit| hit.path.as_path()));
@@ -1088,13 +2303,48 @@ impl SearchEngine {
                 .collect(),
             metrics,
             language_counts,
+            matched_identifiers,
+            clusters,
         })
     }
+
+    /// The identifiers a glob symbol matched in `hits`, most common first, each counted once
+    /// per hit.
+    fn matched_identifiers(&self, hits: &[SearchHit]) -> Vec<MatchedIdentifier> {
+        let glob = self.config.symbol.trim();
+        let ignore_case = self.config.case.ignores_case(glob);
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for hit in hits {
+            let found: BTreeSet<&str> =
+                plan::glob_identifiers(glob, &hit.snippet, ignore_case).collect();
+            for identifier in found {
+                *counts.entry(identifier).or_default() += 1;
+            }
+        }
+        let mut matched: Vec<MatchedIdentifier> = counts
+            .into_iter()
+            .map(|(identifier, hits)| MatchedIdentifier {
+                identifier: identifier.to_string(),
+                hits,
+            })
+            .collect();
+        matched.sort_by(|a, b| {
+            b.hits
+                .cmp(&a.hits)
+                .then_with(|| a.identifier.cmp(&b.identifier))
+        });
+        matched.truncate(MAX_MATCHED_IDENTIFIERS);
+        matched
+    }
 }
 
 #[derive(Clone, Debug)]
 struct SearchHit {
     path: PathBuf,
+    /// Archive member the match came from; `path` is then the archive itself.
+    inner_path: Option<PathBuf>,
+    /// Notebook code cell the match came from; `line` then counts within the cell.
+    cell: Option<usize>,
     line: usize,
     snippet: String,
     score: f32,
@@ -1102,6 +2352,8 @@ struct SearchHit {
     raw_snippet: Option<String>,
     snippet_length: usize,
     raw_snippet_truncated: bool,
+    /// Byte range of the match within the line, when ripgrep reported one.
+    submatch: Option<(usize, usize)>,
 }
 
 #[derive(Clone)]
@@ -1111,6 +2363,51 @@ struct BodyPayload {
 }
 
 impl SearchHit {
+    /// Path that identifies the match: the file, `archive/member` for archive hits, or
+    /// `notebook.ipynb#cell=N` for notebook hits.
+    fn location(&self) -> PathBuf {
+        match (&self.inner_path, self.cell) {
+            (Some(inner), _) => self.path.join(inner),
+            (None, Some(cell)) => {
+                let mut location = self.path.clone().into_os_string();
+                location.push(format!("#cell={cell}"));
+                PathBuf::from(location)
+            }
+            (None, None) => self.path.clone(),
+        }
+    }
+
+    /// Point a match in a notebook's JSON at its code cell.
+    ///
+    /// Matches on notebook lines outside code cells (markdown, outputs, metadata) are dropped.
+    /// Notebooks that cannot be parsed or mapped keep their raw JSON hits.
+    fn into_notebook_cell(
+        mut self,
+        root: &Path,
+        notebooks: &mut HashMap<PathBuf, Option<Notebook>>,
+    ) -> Option<Self> {
+        if self.inner_path.is_some() || !notebook::is_notebook(&self.path) {
+            return Some(self);
+        }
+        let notebook = notebooks
+            .entry(self.path.clone())
+            .or_insert_with(|| Notebook::load(&root.join(&self.path)));
+        let Some(notebook) = notebook.as_ref().filter(|notebook| !notebook.is_empty()) else {
+            return Some(self);
+        };
+        let located = notebook.locate(self.line)?;
+        self.cell = Some(located.cell);
+        self.line = located.line;
+        if let Some(text) = notebook
+            .cell_lines(located.cell)
+            .and_then(|lines| lines.get(located.line - 1))
+        {
+            self.snippet = text.clone();
+            self.snippet_length = text.chars().count();
+        }
+        Some(self)
+    }
+
     fn from_ripgrep(
         root: &Path,
         rg_match: RipgrepMatch,
@@ -1131,6 +2428,8 @@ impl SearchHit {
         let raw_snippet_truncated = snippet_length >= max_columns;
         Self {
             path: normalized,
+            inner_path: None,
+            cell: None,
             line,
             snippet: rg_match.lines,
             score: 1.0,
@@ -1138,6 +2437,7 @@ impl SearchHit {
             raw_snippet: Some(rg_match.raw_json),
             snippet_length,
             raw_snippet_truncated,
+            submatch: rg_match.submatch,
         }
     }
 
@@ -1152,6 +2452,8 @@ impl SearchHit {
         let snippet_length = rga_match.lines.chars().count();
         Self {
             path: normalized,
+            inner_path: rga_match.inner_path,
+            cell: None,
             line,
             snippet: rga_match.lines,
             score: 0.9,
@@ -1159,6 +2461,7 @@ impl SearchHit {
             raw_snippet: None,
             snippet_length,
             raw_snippet_truncated: false,
+            submatch: None,
         }
     }
 }
@@ -1168,6 +2471,9 @@ enum HitOrigin {
     Ripgrep(ProbeKind),
     AstGrep,
     Rga,
+    /// A line the index returned, checked on disk without ripgrep.
+    #[cfg(feature = "indexing")]
+    Index,
 }
 
 impl HitOrigin {
@@ -1179,6 +2485,8 @@ impl HitOrigin {
             HitOrigin::Ripgrep(ProbeKind::Indexed) => "rg-indexed",
             HitOrigin::AstGrep => "ast-grep",
             HitOrigin::Rga => "rga",
+            #[cfg(feature = "indexing")]
+            HitOrigin::Index => "index",
         }
     }
 }
@@ -1191,63 +2499,205 @@ enum ProbeKind {
     Indexed,
 }
 
+/// Uncompressed state written by earlier versions; migrated on first save.
+const LEGACY_STATE_FILE: &str = "state.json";
+/// Layout of the state file; bump it and extend `PersistentStateData::migrate` when it changes.
+const STATE_VERSION: u32 = 1;
+
 #[derive(Serialize, Deserialize, Default)]
 struct PersistentStateData {
-    symbol_hits: HashMap<String, Vec<String>>,
+    /// Layout the file was written with; 0 for files that predate versioning.
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    symbols: HashMap<String, SymbolHints>,
     directory_scores: HashMap<String, u32>,
+    /// Repository the hints were recorded for; refreshed (including HEAD) on every load.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repo: Option<RepoFingerprint>,
+    /// Pre-LRU layout (`symbol -> paths`); folded into `symbols` on load and never written.
+    #[serde(default, skip_serializing)]
+    symbol_hits: HashMap<String, Vec<String>>,
+    /// Last repository walk, shared by `swe-grep stats` and `--explain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repo_stats: Option<RepoStats>,
+}
+
+impl PersistentStateData {
+    /// Bring data read from an older layout up to `STATE_VERSION`, returning whether it
+    /// changed.
+    fn migrate(&mut self) -> bool {
+        if self.version >= STATE_VERSION {
+            return false;
+        }
+        // Version 0: hints may still use the pre-LRU `symbol -> paths` map.
+        for (symbol, paths) in std::mem::take(&mut self.symbol_hits) {
+            self.symbols.entry(symbol).or_insert(SymbolHints {
+                paths,
+                last_used_ms: 0,
+            });
+        }
+        self.version = STATE_VERSION;
+        true
+    }
+
+    /// Written by a newer swe-grep, whose layout this build cannot interpret.
+    fn is_newer(&self) -> bool {
+        self.version > STATE_VERSION
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Default)]
+struct SymbolHints {
+    paths: Vec<String>,
+    /// Milliseconds since the Unix epoch when the symbol was last searched for; drives eviction.
+    #[serde(default)]
+    last_used_ms: u64,
 }
 
 struct PersistentState {
     root: PathBuf,
-    file_path: PathBuf,
+    store: Arc<dyn StateStore>,
+    /// Uncompressed file to delete once the compressed state has been written.
+    legacy_path: Option<PathBuf>,
+    max_symbols: usize,
     data: PersistentStateData,
+    /// Symbols looked up or observed since the last save; only these overwrite the stored copy.
+    touched: HashSet<String>,
+    /// Directory score increments since the last save, added to whatever the store holds.
+    directory_deltas: HashMap<String, u32>,
     dirty: bool,
+    /// The state was written by a newer swe-grep; hints start empty and the stored state is
+    /// never overwritten with this build's layout.
+    read_only: bool,
 }
 
 impl PersistentState {
-    fn load(root: &Path, cache_dir: &Path) -> Result<Self> {
-        let file_path = cache_dir.join("state.json");
-        let data = if file_path.exists() {
-            match fs::read_to_string(&file_path) {
-                Ok(contents) => {
-                    match serde_json::from_str(&contents) {
-                        Ok(parsed) => parsed,
-                        Err(err) => {
-                            tracing::warn!(
-                                path = %file_path.display(),
-                                error = %err,
-                                "failed to parse persistent state JSON; resetting to default"
-                            );
-                            PersistentStateData::default()
-                        }
-                    }
-                }
-                Err(err) => {
-                    tracing::warn!(
-                        path = %file_path.display(),
-                        error = %err,
-                        "failed to read persistent state file"
-                    );
-                    PersistentStateData::default()
-                }
+    /// Load the hint state for `fingerprint` from the store `config` selects: its partition of
+    /// `cache_dir` by default, or a shared store (see [`state_store::open`]). A shared store that
+    /// cannot be reached is logged and the search starts without hints.
+    ///
+    /// When the store holds nothing yet, an uncompressed `state.json` in the partition is
+    /// migrated on the next save. A pre-partitioning `state.json` at the top of the cache
+    /// directory is adopted once, but only when the cache lives inside the root and therefore
+    /// cannot belong to another repo. State in an older layout is migrated to `STATE_VERSION`;
+    /// state from a newer swe-grep is ignored and left untouched.
+    fn load(
+        root: &Path,
+        fingerprint: &RepoFingerprint,
+        cache_dir: &Path,
+        config: &StateConfig,
+        max_symbols: usize,
+    ) -> Result<Self> {
+        let store = state_store::open(config, fingerprint, cache_dir)?;
+        let state_dir = fingerprint.state_dir(cache_dir);
+        let uncompressed_path = state_dir.join(LEGACY_STATE_FILE);
+        let top_level_path = cache_dir.join(LEGACY_STATE_FILE);
+        let mut legacy_path = None;
+        let stored = match store.load() {
+            Ok(Some(contents)) => Some(Self::decode(&contents, &store.location())),
+            Ok(None) => None,
+            Err(err) => {
+                tracing::warn!(
+                    store = %store.location(),
+                    error = %err,
+                    "failed to load persistent state"
+                );
+                None
             }
+        };
+        let stored = if stored.is_some() {
+            stored
+        } else if uncompressed_path.exists() {
+            legacy_path = Some(uncompressed_path.clone());
+            Some(Self::read_legacy(&uncompressed_path))
+        } else if cache_dir.starts_with(root) && top_level_path.exists() {
+            Some(Self::read_legacy(&top_level_path))
         } else {
-            PersistentStateData::default()
+            None
         };
-        Ok(Self {
+        let read_only = stored.as_ref().is_some_and(PersistentStateData::is_newer);
+        let (mut data, migrated) = match stored {
+            Some(data) if data.is_newer() => {
+                tracing::warn!(
+                    store = %store.location(),
+                    version = data.version,
+                    supported = STATE_VERSION,
+                    "persistent state was written by a newer swe-grep; ignoring it"
+                );
+                (PersistentStateData::default(), false)
+            }
+            Some(mut data) => {
+                let migrated = data.migrate();
+                (data, migrated)
+            }
+            None => (PersistentStateData::default(), false),
+        };
+        data.repo = Some(fingerprint.clone());
+        let mut state = Self {
             root: root.to_path_buf(),
-            file_path,
+            store,
+            dirty: migrated || legacy_path.is_some(),
+            legacy_path,
+            max_symbols,
             data,
-            dirty: false,
+            touched: HashSet::new(),
+            directory_deltas: HashMap::new(),
+            read_only,
+        };
+        state.evict_if_needed();
+        Ok(state)
+    }
+
+    /// Parse zstd-compressed state read from `location`, resetting to default when corrupt.
+    fn decode(contents: &[u8], location: &str) -> PersistentStateData {
+        zstd::decode_all(contents)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(serde_json::from_slice(&json)?))
+            .unwrap_or_else(|err| {
+                tracing::warn!(
+                    store = %location,
+                    error = %err,
+                    "failed to parse persistent state; resetting to default"
+                );
+                PersistentStateData::default()
+            })
+    }
+
+    /// Read an uncompressed `state.json` written by earlier versions.
+    fn read_legacy(file_path: &Path) -> PersistentStateData {
+        let contents = match fs::read(file_path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                tracing::warn!(
+                    path = %file_path.display(),
+                    error = %err,
+                    "failed to read persistent state file"
+                );
+                return PersistentStateData::default();
+            }
+        };
+        serde_json::from_slice(&contents).unwrap_or_else(|err| {
+            tracing::warn!(
+                path = %file_path.display(),
+                error = %err,
+                "failed to parse persistent state; resetting to default"
+            );
+            PersistentStateData::default()
         })
     }
 
-    fn hints_for_symbol(&self, symbol: &str) -> Vec<PathBuf> {
-        self.data
-            .symbol_hits
-            .get(symbol)
-            .into_iter()
-            .flat_map(|paths| paths.iter())
+    /// Remembered paths for `symbol` that still exist; a lookup counts as a use for eviction.
+    fn hints_for_symbol(&mut self, symbol: &str) -> Vec<PathBuf> {
+        let Some(hints) = self.data.symbols.get_mut(symbol) else {
+            return Vec::new();
+        };
+        hints.last_used_ms = now_ms();
+        self.touched.insert(symbol.to_string());
+        self.dirty = true;
+        hints
+            .paths
+            .iter()
             .filter_map(|text| {
                 let relative = PathBuf::from(text);
                 let absolute = self.root.join(&relative);
@@ -1279,8 +2729,32 @@ impl PersistentState {
             .collect()
     }
 
-    /// Maximum number of distinct symbols to cache
-    const MAX_SYMBOLS: usize = 500;
+    /// Repository composition, walking the tree again only when `refresh` is set or the stored
+    /// copy predates the current HEAD or `stats::MAX_AGE_MS`.
+    fn repo_stats(&mut self, refresh: bool) -> RepoStats {
+        let head = self.data.repo.as_ref().and_then(|repo| repo.head.clone());
+        let now = now_ms();
+        if !refresh
+            && let Some(stats) = &self.data.repo_stats
+            && stats.is_fresh(head.as_deref(), now)
+        {
+            return stats.clone();
+        }
+        let stats = RepoStats::collect(&self.root, head, now);
+        self.data.repo_stats = Some(stats.clone());
+        self.dirty = true;
+        stats
+    }
+
+    /// Size class from the stored composition whatever its age; size classes move too slowly
+    /// to justify a walk per HEAD, so the tree is only walked when nothing is stored yet.
+    fn repo_scale(&mut self) -> RepoScale {
+        match &self.data.repo_stats {
+            Some(stats) => stats.scale(),
+            None => self.repo_stats(false).scale(),
+        }
+    }
+
     /// Maximum number of directories to track
     const MAX_DIRECTORIES: usize = 200;
 
@@ -1288,16 +2762,18 @@ impl PersistentState {
         if hits.is_empty() {
             return;
         }
-        let entry = self.data.symbol_hits.entry(symbol.to_string()).or_default();
+        self.touched.insert(symbol.to_string());
+        let entry = self.data.symbols.entry(symbol.to_string()).or_default();
+        entry.last_used_ms = now_ms();
 
         for hit in hits.iter().take(10) {
-            let text = hit.path.to_string_lossy().to_string();
-            if !entry.contains(&text) {
-                entry.push(text);
+            let text = display_path(&hit.path);
+            if !entry.paths.contains(&text) {
+                entry.paths.push(text);
             }
         }
-        if entry.len() > 10 {
-            entry.drain(10..);
+        if entry.paths.len() > 10 {
+            entry.paths.drain(10..);
         }
 
         for hit in hits.iter().take(20) {
@@ -1312,6 +2788,8 @@ impl PersistentState {
                         .entry(dir.to_string())
                         .or_insert(0);
                     *counter = counter.saturating_add(1);
+                    let delta = self.directory_deltas.entry(dir.to_string()).or_insert(0);
+                    *delta = delta.saturating_add(1);
                 }
             }
         }
@@ -1322,25 +2800,25 @@ impl PersistentState {
         self.dirty = true;
     }
 
-    /// Evict oldest/least-used entries when cache exceeds size limits.
+    /// Evict least recently used symbols and lowest-scoring directories beyond the caps.
     fn evict_if_needed(&mut self) {
-        // Evict symbols if over limit (keep most recently added)
-        if self.data.symbol_hits.len() > Self::MAX_SYMBOLS {
-            let excess = self.data.symbol_hits.len() - Self::MAX_SYMBOLS;
-            let keys_to_remove: Vec<String> = self
+        if self.data.symbols.len() > self.max_symbols {
+            let excess = self.data.symbols.len() - self.max_symbols;
+            let mut by_age: Vec<(u64, String)> = self
                 .data
-                .symbol_hits
-                .keys()
-                .take(excess)
-                .cloned()
+                .symbols
+                .iter()
+                .map(|(symbol, hints)| (hints.last_used_ms, symbol.clone()))
                 .collect();
-            for key in keys_to_remove {
-                self.data.symbol_hits.remove(&key);
+            by_age.sort();
+            for (_, symbol) in by_age.into_iter().take(excess) {
+                self.data.symbols.remove(&symbol);
             }
+            self.dirty = true;
             tracing::debug!(
                 evicted = excess,
-                remaining = self.data.symbol_hits.len(),
-                "evicted symbol cache entries"
+                remaining = self.data.symbols.len(),
+                "evicted least recently used symbol hints"
             );
         }
 
@@ -1360,6 +2838,7 @@ impl PersistentState {
                 .collect();
             let before = self.data.directory_scores.len();
             self.data.directory_scores.retain(|k, _| to_keep.contains(k));
+            self.dirty = true;
             tracing::debug!(
                 evicted = before - self.data.directory_scores.len(),
                 remaining = self.data.directory_scores.len(),
@@ -1368,33 +2847,104 @@ impl PersistentState {
         }
     }
 
+    /// Merge this process's changes into the stored state and write it back.
+    ///
+    /// Other processes sharing the store may have saved since this one loaded, so the store
+    /// re-reads its copy under a lock or a conditional write: symbols touched here replace the
+    /// stored entries, directory increments are added to its scores, and everything else is
+    /// kept.
     fn save(&mut self) -> Result<()> {
-        if !self.dirty {
+        if !self.dirty || self.read_only {
             return Ok(());
         }
-        if let Some(parent) = self.file_path.parent() {
-            fs::create_dir_all(parent).with_context(|| {
-                format!("failed to create cache directory {}", parent.display())
-            })?;
-        }
-        let tmp_path = self.file_path.with_extension("json.tmp");
-        let file = fs::File::create(&tmp_path)
-            .with_context(|| format!("failed to create {}", tmp_path.display()))?;
-        let mut writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(&mut writer, &self.data)
-            .context("failed to serialize persistent state")?;
-        writer
-            .flush()
-            .with_context(|| format!("failed to flush {}", tmp_path.display()))?;
-        fs::rename(&tmp_path, &self.file_path).with_context(|| {
-            format!(
-                "failed to move persistent state into place {}",
-                self.file_path.display()
-            )
+        let store = Arc::clone(&self.store);
+        store.update(&mut |current| {
+            let stored = current.map(|contents| Self::decode(contents, &store.location()));
+            if stored.as_ref().is_some_and(PersistentStateData::is_newer) {
+                // A newer swe-grep saved since this one loaded; leave its state alone.
+                self.read_only = true;
+                return Ok(None);
+            }
+            if let Some(mut stored) = stored {
+                stored.migrate();
+                self.merge_from(stored);
+            }
+            self.data.version = STATE_VERSION;
+            self.encode().map(Some)
         })?;
+        if self.read_only {
+            self.dirty = false;
+            return Ok(());
+        }
+
+        if let Some(legacy_path) = self.legacy_path.take()
+            && let Err(err) = fs::remove_file(&legacy_path)
+        {
+            tracing::warn!(
+                path = %legacy_path.display(),
+                error = %err,
+                "failed to remove migrated persistent state file"
+            );
+        }
+        self.touched.clear();
+        self.directory_deltas.clear();
         self.dirty = false;
         Ok(())
     }
+
+    /// Rebase this process's touched symbols and directory increments onto `on_disk`. May run
+    /// more than once per save when a shared store retries, so nothing of ours is moved out.
+    fn merge_from(&mut self, mut on_disk: PersistentStateData) {
+        for symbol in &self.touched {
+            let Some(ours) = self.data.symbols.get(symbol) else {
+                continue;
+            };
+            let merged = match on_disk.symbols.remove(symbol) {
+                Some(theirs) => {
+                    let mut paths = ours.paths.clone();
+                    for path in theirs.paths {
+                        if paths.len() >= 10 {
+                            break;
+                        }
+                        if !paths.contains(&path) {
+                            paths.push(path);
+                        }
+                    }
+                    SymbolHints {
+                        paths,
+                        last_used_ms: ours.last_used_ms.max(theirs.last_used_ms),
+                    }
+                }
+                None => ours.clone(),
+            };
+            on_disk.symbols.insert(symbol.clone(), merged);
+        }
+        for (dir, delta) in &self.directory_deltas {
+            let score = on_disk.directory_scores.entry(dir.clone()).or_insert(0);
+            *score = score.saturating_add(*delta);
+        }
+        on_disk.repo = self.data.repo.clone();
+        if let Some(ours) = self.data.repo_stats.clone()
+            && on_disk
+                .repo_stats
+                .as_ref()
+                .is_none_or(|theirs| theirs.collected_at_ms < ours.collected_at_ms)
+        {
+            on_disk.repo_stats = Some(ours);
+        }
+        self.data = on_disk;
+        self.evict_if_needed();
+    }
+
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut writer = zstd::Encoder::new(Vec::new(), 0)
+            .context("failed to start compressing persistent state")?;
+        serde_json::to_writer(&mut writer, &self.data)
+            .context("failed to serialize persistent state")?;
+        writer
+            .finish()
+            .context("failed to compress persistent state")
+    }
 }
 
 #[derive(Default)]
@@ -1405,7 +2955,7 @@ struct SearchCache {
 impl SearchCache {
     fn retain_new(&mut self, hits: &mut Vec<SearchHit>) {
         hits.retain(|hit| {
-            let key = (hit.path.to_string_lossy().to_string(), hit.line);
+            let key = (hit.location().to_string_lossy().to_string(), hit.line);
             if self.seen.contains(&key) {
                 false
             } else {
@@ -1420,6 +2970,8 @@ impl SearchCache {
 struct QueryRewriter {
     symbol: String,
     languages: Vec<String>,
+    strategy: Strategy,
+    whole_word: bool,
 }
 
 impl QueryRewriter {
@@ -1427,14 +2979,47 @@ impl QueryRewriter {
         Self {
             symbol: symbol.to_string(),
             languages: languages.iter().cloned().collect(),
+            strategy: Strategy::Identifier,
+            whole_word: false,
         }
     }
 
+    /// Rewrite for `strategy`; only identifiers get per-language variants.
+    fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Bound every rewrite at word boundaries (see [`bound_words`]).
+    fn whole_word(mut self, enabled: bool) -> Self {
+        self.whole_word = enabled;
+        self
+    }
+
     fn build(&self) -> Vec<String> {
         let s = self.symbol.trim();
         if s.is_empty() {
             return Vec::new();
         }
+        let mut queries = match self.strategy {
+            Strategy::Identifier | Strategy::ModulePath => return self.build_identifier(s),
+            // Probed with `--fixed-strings`, which `--word-regexp` bounds instead of `\b`.
+            Strategy::Phrase => return vec![s.to_string()],
+            Strategy::Structural => vec![Self::loose_whitespace(s)],
+            Strategy::StringLiteral => {
+                // The string as written, between quotes first, then anywhere, e.g. in a comment.
+                let text = Self::loose_whitespace(s);
+                vec![format!("[\"'`]{text}[\"'`]"), text]
+            }
+            Strategy::Glob => vec![Self::glob_regex(s)],
+        };
+        if self.whole_word {
+            queries = queries.iter().map(|query| bound_words(query)).collect();
+        }
+        dedup_queries(queries)
+    }
+
+    fn build_identifier(&self, s: &str) -> Vec<String> {
         let type_hint = self.derive_type_hint();
 
         let mut queries = vec![
@@ -1455,10 +3040,31 @@ impl QueryRewriter {
                 "rust" => {
                     queries.extend(self.build_rust_variants(s));
                 }
+                "proto" => {
+                    queries.extend(self.build_proto_variants(s));
+                }
+                "graphql" => {
+                    queries.extend(self.build_graphql_variants(s));
+                }
+                "openapi" => {
+                    queries.extend(self.build_openapi_variants(s));
+                }
+                "objc" => {
+                    queries.extend(self.build_objc_variants(s));
+                }
+                "hcl" => {
+                    queries.extend(self.build_hcl_variants(s));
+                }
+                "yaml" => {
+                    queries.extend(self.build_yaml_variants(s));
+                }
                 _ => {}
             }
         }
 
+        if self.whole_word {
+            queries = queries.iter().map(|query| bound_words(query)).collect();
+        }
         dedup_queries(queries)
     }
 
@@ -1556,6 +3162,57 @@ impl QueryRewriter {
         ]
     }
 
+    fn build_proto_variants(&self, symbol: &str) -> Vec<String> {
+        if symbol.is_empty() {
+            return Vec::new();
+        }
+
+        vec![
+            Self::escape_literal(&format!("message {symbol}")),
+            Self::escape_literal(&format!("service {symbol}")),
+            Self::escape_literal(&format!("rpc {symbol}")),
+            Self::escape_literal(&format!("rpc {symbol}(")),
+            Self::escape_literal(&format!("enum {symbol}")),
+        ]
+    }
+
+    fn build_graphql_variants(&self, symbol: &str) -> Vec<String> {
+        if symbol.is_empty() {
+            return Vec::new();
+        }
+
+        vec![
+            Self::escape_literal(&format!("type {symbol}")),
+            Self::escape_literal(&format!("input {symbol}")),
+            Self::escape_literal(&format!("interface {symbol}")),
+            Self::escape_literal(&format!("enum {symbol}")),
+            Self::escape_literal(&format!("query {symbol}")),
+            Self::escape_literal(&format!("mutation {symbol}")),
+            Self::escape_literal(&format!("subscription {symbol}")),
+            Self::escape_literal(&format!("fragment {symbol}")),
+        ]
+    }
+
+    /// OpenAPI documents name operations by `operationId` and endpoints by path keys
+    /// (`/users/{id}:`), in YAML or JSON.
+    fn build_openapi_variants(&self, symbol: &str) -> Vec<String> {
+        if symbol.is_empty() {
+            return Vec::new();
+        }
+
+        let mut variants = vec![
+            Self::escape_literal(&format!("operationId: {symbol}")),
+            Self::escape_literal(&format!("operationId: \"{symbol}\"")),
+            Self::escape_literal(&format!("operationId: '{symbol}'")),
+            Self::escape_literal(&format!("\"operationId\": \"{symbol}\"")),
+        ];
+        if symbol.starts_with('/') {
+            variants.push(Self::escape_literal(&format!("{symbol}:")));
+            variants.push(Self::escape_literal(&format!("\"{symbol}\":")));
+        }
+        variants
+    }
+
     fn build_swift_variants(&self, symbol: &str) -> Vec<String> {
         if symbol.is_empty() {
             return Vec::new();
@@ -1589,6 +3246,129 @@ impl QueryRewriter {
         variants
     }
 
+    /// Objective-C declarations, message sends, and the annotations that rename a symbol
+    /// across the Swift bridge (`NS_SWIFT_NAME`, `@objc(...)`).
+    fn build_objc_variants(&self, symbol: &str) -> Vec<String> {
+        if symbol.is_empty() {
+            return Vec::new();
+        }
+
+        let is_type_like = symbol
+            .chars()
+            .next()
+            .map(|ch| ch.is_uppercase())
+            .unwrap_or(false);
+
+        let mut variants = vec![
+            Self::escape_literal(&format!("- (void){symbol}")),
+            Self::escape_literal(&format!("+ (void){symbol}")),
+            Self::escape_literal(&format!("){symbol}")),
+            Self::escape_literal(&format!(" {symbol}]")),
+            Self::escape_literal(&format!(" {symbol}:")),
+            Self::escape_literal(&format!("NS_SWIFT_NAME({symbol}")),
+            Self::escape_literal(&format!("@objc({symbol}")),
+        ];
+        if is_type_like {
+            variants.push(Self::escape_literal(&format!("@interface {symbol}")));
+            variants.push(Self::escape_literal(&format!("@implementation {symbol}")));
+            variants.push(Self::escape_literal(&format!("@protocol {symbol}")));
+            variants.push(Self::escape_literal(&format!("@class {symbol}")));
+            variants.push(Self::escape_literal(&format!("[{symbol} ")));
+            variants.push(Self::escape_literal(&format!("{symbol} *")));
+            variants.push(Self::escape_literal(&format!("#import \"{symbol}.h\"")));
+            variants.push(Self::escape_literal(&format!("/{symbol}.h>")));
+        }
+
+        variants
+    }
+
+    /// Terraform blocks are labelled by quoted strings (`resource "aws_s3_bucket" "logs"`) and
+    /// referenced by dotted addresses (`var.region`, `module.vpc`, `aws_s3_bucket.logs`), which
+    /// also map back to their blocks.
+    fn build_hcl_variants(&self, symbol: &str) -> Vec<String> {
+        if symbol.is_empty() {
+            return Vec::new();
+        }
+
+        let mut variants = vec![
+            Self::escape_literal(&format!("resource \"{symbol}\"")),
+            Self::escape_literal(&format!("data \"{symbol}\"")),
+            Self::escape_literal(&format!("variable \"{symbol}\"")),
+            Self::escape_literal(&format!("module \"{symbol}\"")),
+            Self::escape_literal(&format!("output \"{symbol}\"")),
+            Self::escape_literal(&format!("provider \"{symbol}\"")),
+            Self::escape_literal(&format!("\"{symbol}\" {{")),
+            Self::escape_literal(&format!("var.{symbol}")),
+            Self::escape_literal(&format!("local.{symbol}")),
+            Self::escape_literal(&format!("module.{symbol}")),
+            Self::escape_literal(&format!("{symbol} =")),
+        ];
+        match symbol.split('.').collect::<Vec<_>>().as_slice() {
+            ["var", name] => {
+                variants.push(Self::escape_literal(&format!("variable \"{name}\"")));
+            }
+            ["local", name] => {
+                variants.push(Self::escape_literal(&format!("{name} =")));
+            }
+            ["module", name] => {
+                variants.push(Self::escape_literal(&format!("module \"{name}\"")));
+            }
+            ["data", kind, name] => {
+                variants.push(Self::escape_literal(&format!("data \"{kind}\" \"{name}\"")));
+            }
+            [kind, name] => {
+                variants.push(Self::escape_literal(&format!(
+                    "resource \"{kind}\" \"{name}\""
+                )));
+            }
+            _ => {}
+        }
+        variants
+    }
+
+    /// YAML manifests name things by value (`kind: Deployment`, `name: web`); a dotted key path
+    /// (`spec.replicas`) is searched by its last key and checked against the rest in verify.
+    fn build_yaml_variants(&self, symbol: &str) -> Vec<String> {
+        if symbol.is_empty() {
+            return Vec::new();
+        }
+
+        if let Some(segments) = iac::key_path_segments(symbol) {
+            let key = segments[segments.len() - 1];
+            return vec![Self::escape_literal(&format!("{key}:"))];
+        }
+        vec![
+            Self::escape_literal(&format!("kind: {symbol}")),
+            Self::escape_literal(&format!("name: {symbol}")),
+            Self::escape_literal(&format!("name: \"{symbol}\"")),
+            Self::escape_literal(&format!("{symbol}:")),
+            Self::escape_literal(&format!(": {symbol}")),
+        ]
+    }
+
+    /// `value` escaped, with each run of whitespace matching any run of whitespace, so a
+    /// phrase still matches where it was aligned or indented differently.
+    fn loose_whitespace(value: &str) -> String {
+        value
+            .split_whitespace()
+            .map(Self::escape_literal)
+            .collect::<Vec<_>>()
+            .join("\\s+")
+    }
+
+    /// A glob as a regex over identifier characters: `*` is any run of them and `?` exactly one.
+    fn glob_regex(glob: &str) -> String {
+        let mut regex = String::with_capacity(glob.len() + 8);
+        for ch in glob.chars() {
+            match ch {
+                '*' => regex.push_str("\\w*"),
+                '?' => regex.push_str("\\w"),
+                _ => regex.push_str(&Self::escape_literal(ch.encode_utf8(&mut [0; 4]))),
+            }
+        }
+        regex
+    }
+
     fn escape_literal(value: &str) -> String {
         let mut escaped = String::with_capacity(value.len());
         for ch in value.chars() {
@@ -1605,10 +3385,51 @@ impl QueryRewriter {
     }
 }
 
+/// Verify-stage score of a hit given its origin and the signals that confirmed it.
+fn weighted_score(
+    base: f32,
+    origin: &HitOrigin,
+    in_fd: bool,
+    in_ast: bool,
+    weights: &ScoringWeights,
+) -> f32 {
+    let mut score = base;
+    if in_fd {
+        score += weights.fd_candidate as f32;
+    }
+    match origin {
+        HitOrigin::Ripgrep(ProbeKind::Global) => score -= weights.global_penalty as f32,
+        #[cfg(feature = "indexing")]
+        HitOrigin::Ripgrep(ProbeKind::Indexed) | HitOrigin::Index => {
+            score += weights.indexed as f32
+        }
+        HitOrigin::Rga => score -= weights.rga_penalty as f32,
+        _ => {}
+    }
+    if in_ast {
+        score += weights.ast_match as f32;
+    }
+    score
+}
+
+fn weighted_reward(metrics: &SearchMetrics, weights: &RewardWeights) -> f32 {
+    reward_components(metrics, weights).total()
+}
+
+fn reward_components(metrics: &SearchMetrics, weights: &RewardWeights) -> RewardComponents {
+    RewardComponents {
+        precision: weights.precision as f32 * metrics.precision,
+        density: weights.density as f32 * metrics.density,
+        clustering: weights.clustering as f32 * metrics.cluster_score,
+        fd_bonus: weights.fd_bonus as f32 * metrics.fd_bonus,
+    }
+}
+
 fn compute_metrics(
     hits: &[SearchHit],
     ast_set: &HashSet<(PathBuf, usize)>,
     fd_candidates: usize,
+    weights: &RewardWeights,
 ) -> SearchMetrics {
     if hits.is_empty() {
         return SearchMetrics::default();
@@ -1637,14 +3458,15 @@ fn compute_metrics(
         0.0
     };
 
-    let reward = 0.5 * precision + 0.3 * density + 0.15 * cluster_score + 0.05 * fd_bonus;
-
-    SearchMetrics {
+    let mut metrics = SearchMetrics {
         precision,
         density,
         cluster_score,
-        reward,
-    }
+        fd_bonus,
+        reward: 0.0,
+    };
+    metrics.reward = weighted_reward(&metrics, weights);
+    metrics
 }
 
 fn capitalize(segment: &str) -> String {
@@ -1655,7 +3477,100 @@ fn capitalize(segment: &str) -> String {
         result.extend(chars);
         return result;
     }
-    segment.to_string()
+    segment.to_string()
+}
+
+/// A run of identifier characters in any script; a leading digit is allowed so `404` still
+/// takes the literal fast path.
+fn is_literal_identifier(symbol: &str) -> bool {
+    let s = symbol.trim();
+    !s.is_empty() && s.chars().all(identifier::is_continue)
+}
+
+/// Base score of a phrase hit: full when `line` holds the phrase as written and as whole
+/// words, less when it runs into a longer word, and least when only the case-folded phrase
+/// matches.
+fn phrase_exactness(line: &str, phrase: &str) -> f32 {
+    if find_literal(line, phrase, false, true).is_some() {
+        PHRASE_EXACT_SCORE
+    } else if find_literal(line, phrase, false, false).is_some() {
+        PHRASE_PARTIAL_WORD_SCORE
+    } else {
+        PHRASE_FOLDED_SCORE
+    }
+}
+
+/// Byte range of the first occurrence of `symbol` in `line`, ignoring case when asked and, for
+/// `whole_word`, only where no identifier character touches either end.
+fn find_literal(
+    line: &str,
+    symbol: &str,
+    ignore_case: bool,
+    whole_word: bool,
+) -> Option<(usize, usize)> {
+    let folded = symbol.to_lowercase();
+    line.char_indices().find_map(|(start, _)| {
+        let end = start + symbol.len();
+        let candidate = line.get(start..end)?;
+        let equal = if ignore_case {
+            candidate.to_lowercase() == folded
+        } else {
+            candidate == symbol
+        };
+        let before = line[..start].chars().next_back();
+        let after = line[end..].chars().next();
+        let bounded = !whole_word
+            || !(before.is_some_and(identifier::is_continue)
+                || after.is_some_and(identifier::is_continue));
+        (equal && bounded).then_some((start, end))
+    })
+}
+
+/// The ripgrep patterns a search for `symbol` probes with first: the symbol planned as
+/// [`QueryPlan::for_symbol`] plans it and rewritten for `languages`, bounded at word boundaries
+/// unless `word` says otherwise. Rust paths are kept as written, as they are outside a crate.
+/// [`Strategy::Phrase`] patterns are fixed strings rather than regexes.
+pub fn rewrite_queries(
+    symbol: &str,
+    languages: &[String],
+    word: Option<bool>,
+) -> (Strategy, Vec<String>) {
+    let plan = QueryPlan::for_symbol(symbol, true);
+    let (strategy, text) = match plan.strategy {
+        Strategy::Identifier | Strategy::ModulePath => (Strategy::Identifier, symbol.to_string()),
+        strategy => (strategy, plan.text),
+    };
+    let whole_word = word.unwrap_or_else(|| bounds_words_by_default(strategy, &text));
+    let queries = QueryRewriter::for_symbol(&text, languages)
+        .strategy(strategy)
+        .whole_word(whole_word)
+        .build();
+    (strategy, queries)
+}
+
+/// Whether rewrites of `symbol` are bounded at word boundaries when `--word` is not given.
+fn bounds_words_by_default(strategy: Strategy, symbol: &str) -> bool {
+    (strategy.is_identifier() && is_literal_identifier(symbol)) || strategy.bounds_words()
+}
+
+/// `text` as a ripgrep pattern matching it literally and only as a whole word.
+pub(crate) fn whole_word_literal(text: &str) -> String {
+    bound_words(&QueryRewriter::escape_literal(text))
+}
+
+/// Anchor an escaped rewrite at word boundaries on each end that starts or ends with a word
+/// character, so `run` stops matching inside `runtime` while `run(` keeps matching `run(x)`.
+/// Han and kana ends stay unanchored, since those scripts do not separate words.
+fn bound_words(query: &str) -> String {
+    let mut bounded = String::with_capacity(query.len() + 4);
+    if query.starts_with(identifier::takes_word_boundary) {
+        bounded.push_str("\\b");
+    }
+    bounded.push_str(query);
+    if query.ends_with(identifier::takes_word_boundary) {
+        bounded.push_str("\\b");
+    }
+    bounded
 }
 
 fn dedup_queries<I>(queries: I) -> Vec<String>
@@ -1726,11 +3641,27 @@ fn expand_language_token(token: &str) -> Vec<String> {
         "kotlin" | "kt" => vec!["kt".to_string(), "kts".to_string()],
         "kts" => vec!["kts".to_string()],
         "python" | "py" => vec!["py".to_string()],
+        "proto" | "protobuf" => vec!["proto".to_string()],
+        "graphql" | "gql" => vec!["graphql".to_string()],
+        "openapi" | "swagger" => vec!["openapi".to_string()],
         "swiftui" => vec!["swift".to_string()],
+        "objc" | "objective-c" | "objectivec" | "m" | "mm" | "h" => vec!["objc".to_string()],
+        "hcl" | "terraform" | "tf" | "tfvars" => vec!["hcl".to_string()],
+        "yaml" | "yml" | "k8s" | "kubernetes" => vec!["yaml".to_string()],
         other => vec![other.to_string()],
     }
 }
 
+/// Interface-definition languages: matched by rewrites only, never handed to ast-grep.
+fn is_schema_language(token: &str) -> bool {
+    matches!(token, "proto" | "graphql" | "openapi")
+}
+
+/// Languages ast-grep can parse; the rest are matched by rewrites only.
+fn has_ast_grammar(token: &str) -> bool {
+    !is_schema_language(token) && !matches!(token, "objc" | "hcl" | "yaml")
+}
+
 fn languages_include(tokens: &[String], needle: &str) -> bool {
     tokens.iter().any(|token| token == needle)
 }
@@ -1787,6 +3718,46 @@ fn extensions_for_languages(languages: &[String]) -> Option<Vec<&'static str>> {
                 if !results.contains(&"py") {
                     results.push("py");
                 }
+                if !results.contains(&"ipynb") {
+                    results.push("ipynb");
+                }
+            }
+            "proto" if !results.contains(&"proto") => results.push("proto"),
+            "graphql" => {
+                if !results.contains(&"graphql") {
+                    results.push("graphql");
+                }
+                if !results.contains(&"gql") {
+                    results.push("gql");
+                }
+            }
+            "hcl" => {
+                for ext in ["tf", "tfvars", "hcl"] {
+                    if !results.contains(&ext) {
+                        results.push(ext);
+                    }
+                }
+            }
+            "yaml" => {
+                for ext in ["yaml", "yml"] {
+                    if !results.contains(&ext) {
+                        results.push(ext);
+                    }
+                }
+            }
+            "objc" => {
+                for ext in ["m", "mm", "h"] {
+                    if !results.contains(&ext) {
+                        results.push(ext);
+                    }
+                }
+            }
+            "openapi" => {
+                for ext in ["yaml", "yml", "json"] {
+                    if !results.contains(&ext) {
+                        results.push(ext);
+                    }
+                }
             }
             _ => {}
         }
@@ -1798,7 +3769,7 @@ fn extensions_for_languages(languages: &[String]) -> Option<Vec<&'static str>> {
     }
 }
 
-fn detect_language_from_path(path: &Path) -> Option<&'static str> {
+pub(crate) fn detect_language_from_path(path: &Path) -> Option<&'static str> {
     let ext = path.extension()?.to_str()?;
     match ext.to_ascii_lowercase().as_str() {
         "rs" => Some("rust"),
@@ -1807,9 +3778,13 @@ fn detect_language_from_path(path: &Path) -> Option<&'static str> {
         "tsx" => Some("tsx"),
         "js" => Some("javascript"),
         "jsx" => Some("jsx"),
-        "py" => Some("python"),
+        "py" | "ipynb" => Some("python"),
+        "proto" => Some("proto"),
+        "graphql" | "gql" => Some("graphql"),
         "kt" => Some("kotlin"),
         "kts" => Some("kotlin"),
+        "m" | "mm" => Some("objc"),
+        "tf" | "tfvars" | "hcl" => Some("hcl"),
         _ => None,
     }
 }
@@ -1844,10 +3819,31 @@ fn gather_expanded_snippet(
     } else {
         root.join(path)
     };
-    let contents = fs::read_to_string(&absolute).ok()?;
+    let contents = source::read(&absolute).ok()?;
+    let lines: Vec<&str> = contents.lines().collect();
+    numbered_window(&lines, line, before, after)
+}
+
+/// Doc comment of the definition on the 1-based `line`; see [`doc_comments::doc_comment`].
+fn gather_doc_comment(root: &Path, path: &Path, line: usize) -> Option<String> {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    let contents = source::read(&absolute).ok()?;
     let lines: Vec<&str> = contents.lines().collect();
+    doc_comments::doc_comment(&lines, line)
+}
 
-    if lines.is_empty() || line > lines.len() {
+/// `lines` around the 1-based `line`, each prefixed with its zero-padded line number.
+fn numbered_window<S: AsRef<str>>(
+    lines: &[S],
+    line: usize,
+    before: usize,
+    after: usize,
+) -> Option<(String, usize, usize)> {
+    if line == 0 || lines.is_empty() || line > lines.len() {
         return None;
     }
 
@@ -1860,7 +3856,7 @@ fn gather_expanded_snippet(
 
     let mut buffer = String::new();
     for idx in start..=end {
-        let text = lines.get(idx - 1).copied().unwrap_or_default();
+        let text = lines.get(idx - 1).map(AsRef::as_ref).unwrap_or_default();
         if writeln!(&mut buffer, "{:0width$} {}", idx, text, width = width).is_err() {
             return None;
         }
@@ -1869,6 +3865,70 @@ fn gather_expanded_snippet(
     Some((buffer, start, end))
 }
 
+/// Characters kept on each side of the match in a hit's `line_window`.
+const LINE_WINDOW_RADIUS: usize = 80;
+
+/// Window around the match on the 1-based `line`, read back from disk since ripgrep's copy of a
+/// long line may be cut at `--max-columns`.
+///
+/// The match is ripgrep's byte range when it still fits the line, otherwise the first
+/// occurrence of `symbol` ignoring ASCII case.
+fn gather_line_window(
+    root: &Path,
+    path: &Path,
+    line: usize,
+    submatch: Option<(usize, usize)>,
+    symbol: &str,
+) -> Option<String> {
+    if line == 0 {
+        return None;
+    }
+
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    let contents = source::read(&absolute).ok()?;
+    let text = contents.lines().nth(line - 1)?;
+    let (start, end) = submatch
+        .filter(|&(start, end)| {
+            start <= end && text.is_char_boundary(start) && text.is_char_boundary(end)
+        })
+        .or_else(|| {
+            // ASCII lowercasing keeps byte offsets, so they index `text` directly.
+            let start = text
+                .to_ascii_lowercase()
+                .find(&symbol.trim().to_ascii_lowercase())?;
+            Some((start, start + symbol.trim().len()))
+        })?;
+    Some(line_window(text, start, end, LINE_WINDOW_RADIUS))
+}
+
+/// `text[start..end]` with up to `radius` characters on either side.
+fn line_window(text: &str, start: usize, end: usize, radius: usize) -> String {
+    let from = text[..start]
+        .char_indices()
+        .rev()
+        .take(radius)
+        .last()
+        .map_or(start, |(idx, _)| idx);
+    let to = text[end..]
+        .char_indices()
+        .nth(radius)
+        .map_or(text.len(), |(idx, _)| end + idx);
+
+    let mut window = String::with_capacity(to - from + 2 * '…'.len_utf8());
+    if from > 0 {
+        window.push('…');
+    }
+    window.push_str(&text[from..to]);
+    if to < text.len() {
+        window.push('…');
+    }
+    window
+}
+
 const HINT_KEYWORD_LIMIT: usize = 32;
 
 fn rust_context_hints(contents: &str, line: usize) -> Vec<ContextHint> {
@@ -2059,7 +4119,7 @@ fn format_swift_snippet(root: &Path, path: &Path, line: usize, raw: &str) -> Opt
         } else {
             root.join(path)
         };
-        if let Ok(contents) = fs::read_to_string(&full_path) {
+        if let Ok(contents) = source::read(&full_path) {
             let lines: Vec<&str> = contents.lines().collect();
             if !lines.is_empty() {
                 let mut idx = line.saturating_sub(1);
@@ -2211,45 +4271,21 @@ fn keyword_near_start(text: &str, keyword: &str) -> bool {
         return false;
     }
 
-    let bytes = text.as_bytes();
-    let needle = keyword.as_bytes();
-    let first_requires_boundary = !keyword
-        .chars()
-        .next()
-        .map(|ch| ch.is_whitespace())
-        .unwrap_or(false);
-    let last_requires_boundary = !keyword
-        .chars()
-        .last()
-        .map(|ch| ch.is_whitespace())
-        .unwrap_or(false);
-
-    let limit = bytes.len().min(HINT_KEYWORD_LIMIT + needle.len());
-    let mut idx = 0;
-    while idx + needle.len() <= limit {
-        if &bytes[idx..idx + needle.len()] == needle {
-            let before_ok = if first_requires_boundary {
-                idx == 0 || !bytes[idx - 1].is_ascii_alphanumeric() && bytes[idx - 1] != b'_'
-            } else {
-                true
-            };
-            let after_pos = idx + needle.len();
-            let after_ok = if last_requires_boundary {
-                after_pos >= bytes.len()
-                    || !bytes
-                        .get(after_pos)
-                        .map(|ch| ch.is_ascii_alphanumeric() || *ch == b'_')
-                        .unwrap_or(false)
-            } else {
-                true
-            };
-            if before_ok && after_ok {
-                return true;
-            }
-        }
-        idx += 1;
-    }
-    false
+    let first_requires_boundary = !keyword.starts_with(char::is_whitespace);
+    let last_requires_boundary = !keyword.ends_with(char::is_whitespace);
+    let limit = text.len().min(HINT_KEYWORD_LIMIT + keyword.len());
+    text.match_indices(keyword)
+        .take_while(|(idx, _)| idx + keyword.len() <= limit)
+        .any(|(idx, _)| {
+            // Compare whole characters so a multi-byte letter next to the keyword still counts
+            // as part of the same word.
+            let before = text[..idx].chars().next_back();
+            let after = text[idx + keyword.len()..].chars().next();
+            let before_ok =
+                !first_requires_boundary || !before.is_some_and(identifier::is_continue);
+            let after_ok = !last_requires_boundary || !after.is_some_and(identifier::is_continue);
+            before_ok && after_ok
+        })
 }
 
 fn collect_rust_attributes(lines: &[&str], anchor: usize, hints: &mut Vec<ContextHint>) {
@@ -2568,19 +4604,190 @@ fn passes_extension_filter(path: &Path, extensions: Option<&[&str]>) -> bool {
     }
 }
 
-fn normalize_path(root: &Path, path: &Path) -> Result<PathBuf> {
+/// Files supplied through `--files-from`, relative to the search root.
+#[derive(Clone)]
+struct FileScope {
+    files: Vec<PathBuf>,
+    skipped: usize,
+}
+
+impl FileScope {
+    /// Read a newline-delimited file list from `source` (`-` for stdin). Relative entries
+    /// resolve against `root`; entries that are missing or escape the root are skipped.
+    fn load(root: &Path, source: &Path) -> Result<Self> {
+        let raw = if source == Path::new("-") {
+            std::io::read_to_string(std::io::stdin())
+                .context("failed to read file list from stdin")?
+        } else {
+            fs::read_to_string(source)
+                .with_context(|| format!("failed to read file list {}", source.display()))?
+        };
+
+        let mut files = Vec::new();
+        let mut seen = HashSet::new();
+        let mut skipped = 0;
+        for line in raw.lines() {
+            let entry = line.trim();
+            if entry.is_empty() {
+                continue;
+            }
+            let absolute = root.join(entry);
+            if !absolute.is_file() {
+                skipped += 1;
+                continue;
+            }
+            match normalize_path(root, &absolute) {
+                Ok(relative) if relative.is_relative() => {
+                    if seen.insert(relative.clone()) {
+                        files.push(relative);
+                    }
+                }
+                _ => skipped += 1,
+            }
+        }
+        Ok(Self { files, skipped })
+    }
+}
+
+/// `path` relative to `root` when it resolves inside it, following symlinks; otherwise its
+/// canonical absolute form. Paths that do not exist are only joined onto `root`.
+pub fn normalize_path(root: &Path, path: &Path) -> Result<PathBuf> {
     let absolute = if path.is_absolute() {
         path.to_path_buf()
     } else {
         root.join(path)
     };
-    let canonical = absolute.canonicalize().unwrap_or_else(|_| absolute.clone());
+    let canonical = canonicalize_path(&absolute).unwrap_or_else(|_| absolute.clone());
     Ok(canonical
         .strip_prefix(root)
         .map(|p| p.to_path_buf())
         .unwrap_or(canonical))
 }
 
+/// `relative` with each component spelled the way its directory lists it, so `src/Foo.rs` from
+/// one tool and `src/foo.rs` from another name the same hit.
+fn listed_case(root: &Path, relative: &Path) -> PathBuf {
+    let mut listed = PathBuf::new();
+    for component in relative.components() {
+        let name = component.as_os_str();
+        let spelled = match component {
+            Component::Normal(_) => fs::read_dir(root.join(&listed))
+                .ok()
+                .and_then(|entries| listed_name(entries, name)),
+            _ => None,
+        };
+        listed.push(spelled.as_deref().unwrap_or(name));
+    }
+    listed
+}
+
+/// The entry named exactly `name`, else the first one equal to it ignoring case.
+fn listed_name(entries: fs::ReadDir, name: &OsStr) -> Option<OsString> {
+    let wanted = name.to_str()?.to_lowercase();
+    let mut folded = None;
+    for entry in entries.flatten() {
+        let entry = entry.file_name();
+        if entry == name {
+            return Some(entry);
+        }
+        if folded.is_none() && entry.to_str().is_some_and(|e| e.to_lowercase() == wanted) {
+            folded = Some(entry);
+        }
+    }
+    folded
+}
+
+/// Hit paths that name the same file as another hit, mapped to the path kept for it: the
+/// lexically smallest of hard links, or the spelling the directories list when the paths differ
+/// only in case, as on APFS, where `canonicalize` keeps the caller's casing. Directories are
+/// only listed for such collisions, never per hit.
+fn file_aliases<'a>(
+    root: &Path,
+    paths: impl IntoIterator<Item = &'a PathBuf>,
+) -> HashMap<PathBuf, PathBuf> {
+    let mut by_file: HashMap<(u64, u64), BTreeSet<&PathBuf>> = HashMap::new();
+    for path in paths {
+        if let Some(id) = file_id(&root.join(path)) {
+            by_file.entry(id).or_default().insert(path);
+        }
+    }
+    let mut aliases = HashMap::new();
+    for paths in by_file.into_values() {
+        let Some(first) = paths.first() else {
+            continue;
+        };
+        if paths.len() == 1 {
+            continue;
+        }
+        let folded = first.to_string_lossy().to_lowercase();
+        let kept = if paths
+            .iter()
+            .all(|path| path.to_string_lossy().to_lowercase() == folded)
+        {
+            listed_case(root, first)
+        } else {
+            (*first).clone()
+        };
+        for path in paths {
+            if *path != kept {
+                aliases.insert(path.clone(), kept.clone());
+            }
+        }
+    }
+    aliases
+}
+
+/// Device and inode of `path`, which every link and spelling of one file share.
+#[cfg(unix)]
+fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    fs::metadata(path)
+        .ok()
+        .map(|metadata| (metadata.dev(), metadata.ino()))
+}
+
+/// Windows exposes file ids only on nightly; `canonicalize` already restores the on-disk casing
+/// there, which covers the common case.
+#[cfg(not(unix))]
+fn file_id(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
+
+/// Canonicalize a path, dropping the `\\?\` verbatim prefix Windows adds so `strip_prefix`
+/// keeps working against tool output that reports plain drive-letter paths.
+pub fn canonicalize_path(path: &Path) -> std::io::Result<PathBuf> {
+    path.canonicalize().map(simplify_verbatim)
+}
+
+#[cfg(windows)]
+fn simplify_verbatim(path: PathBuf) -> PathBuf {
+    let simplified = {
+        let text = path.to_string_lossy();
+        if let Some(rest) = text.strip_prefix(r"\\?\UNC\") {
+            Some(PathBuf::from(format!(r"\\{rest}")))
+        } else {
+            text.strip_prefix(r"\\?\").map(PathBuf::from)
+        }
+    };
+    simplified.unwrap_or(path)
+}
+
+#[cfg(not(windows))]
+fn simplify_verbatim(path: PathBuf) -> PathBuf {
+    path
+}
+
+/// Render a repository-relative path with forward slashes regardless of platform.
+fn display_path(path: &Path) -> String {
+    let text = path.to_string_lossy();
+    if std::path::MAIN_SEPARATOR == '\\' {
+        text.replace('\\', "/")
+    } else {
+        text.into_owned()
+    }
+}
+
 fn elapsed_std_ms(start: StdInstant) -> u64 {
     let nanos = start.elapsed().as_nanos();
     ((nanos + 999_999) / 1_000_000) as u64
@@ -2590,29 +4797,102 @@ fn elapsed_ms(start: Instant) -> u64 {
     start.elapsed().as_millis() as u64
 }
 
-fn round_two(value: f32) -> f32 {
+/// Wall-clock milliseconds since the Unix epoch; zero if the clock is before it.
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+pub(crate) fn round_two(value: f32) -> f32 {
     (value * 100.0).round() / 100.0
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 struct SearchMetrics {
     precision: f32,
     density: f32,
     cluster_score: f32,
+    fd_bonus: f32,
     reward: f32,
 }
 
+impl SearchMetrics {
+    /// Export the reward and its weighted components.
+    fn record_telemetry(&self, weights: &RewardWeights) {
+        crate::telemetry::record_reward(self.reward);
+        for (component, value) in reward_components(self, weights).entries() {
+            crate::telemetry::record_reward_component(component, value);
+        }
+    }
+}
+
+/// Verify-stage inputs of one search, recorded for offline weight calibration.
+#[derive(Default)]
+pub(crate) struct CalibrationSample {
+    candidates: Vec<CalibrationCandidate>,
+    metrics: SearchMetrics,
+}
+
+struct CalibrationCandidate {
+    path: PathBuf,
+    line: usize,
+    base_score: f32,
+    origin: HitOrigin,
+    in_fd: bool,
+    in_ast: bool,
+}
+
+impl CalibrationSample {
+    /// Top hits (path, line) the verify stage would surface under `weights`.
+    pub(crate) fn rank(&self, weights: &ScoringWeights) -> Vec<(String, usize)> {
+        let mut best: HashMap<(&Path, usize), f32> = HashMap::new();
+        for candidate in &self.candidates {
+            let score = weighted_score(
+                candidate.base_score,
+                &candidate.origin,
+                candidate.in_fd,
+                candidate.in_ast,
+                weights,
+            );
+            best.entry((candidate.path.as_path(), candidate.line))
+                .and_modify(|existing| *existing = existing.max(score))
+                .or_insert(score);
+        }
+        let mut ranked: Vec<_> = best.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked
+            .into_iter()
+            .take(MAX_TOP_HITS)
+            .map(|((path, line), _)| (display_path(path), line))
+            .collect()
+    }
+
+    /// Cycle reward under `weights`, from the metrics recorded for this search.
+    pub(crate) fn reward(&self, weights: &RewardWeights) -> f64 {
+        if self.candidates.is_empty() {
+            return 0.0;
+        }
+        f64::from(weighted_reward(&self.metrics, weights))
+    }
+}
+
 struct VerificationOutcome {
     top_hits: Vec<TopHit>,
-    next_actions: Vec<String>,
+    next_actions: Vec<NextAction>,
     dedup_count: usize,
     fd_candidates: Vec<PathBuf>,
     ast_hits: Vec<(PathBuf, usize)>,
     metrics: SearchMetrics,
     language_counts: BTreeMap<String, usize>,
+    matched_identifiers: Vec<MatchedIdentifier>,
+    clusters: Vec<HitCluster>,
 }
 
-#[derive(Default, Clone, Serialize)]
+#[derive(Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
 pub struct StartupStats {
     pub init_ms: u64,
     #[serde(skip_serializing_if = "is_zero")]
@@ -2631,7 +4911,9 @@ pub struct StartupStats {
     pub index_ms: u64,
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
 pub struct StageStats {
     pub discover_candidates: usize,
     pub discover_ms: u64,
@@ -2644,6 +4926,9 @@ pub struct StageStats {
     pub index_probe_hits: usize,
     #[serde(skip_serializing_if = "is_zero")]
     pub index_ms: u64,
+    /// Files the FST symbol store returned for an exact identifier (indexing builds only).
+    #[serde(skip_serializing_if = "is_usize_zero")]
+    pub symbol_store_candidates: usize,
     pub rga_hits: usize,
     #[serde(skip_serializing_if = "is_zero")]
     pub rga_ms: u64,
@@ -2654,12 +4939,54 @@ pub struct StageStats {
     pub precision: f32,
     pub density: f32,
     pub clustering: f32,
+    /// Share of the verified hits found among the fd candidates.
+    pub fd_bonus: f32,
     pub reward: f32,
+    /// Each metric's weighted contribution to `reward`, which is their sum.
+    pub reward_components: RewardComponents,
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub language_metrics: BTreeMap<String, LanguageMetrics>,
 }
 
-#[derive(Default, Serialize)]
+/// Reward contributions: each quality metric times its `[reward]` coefficient.
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct RewardComponents {
+    pub precision: f32,
+    pub density: f32,
+    pub clustering: f32,
+    pub fd_bonus: f32,
+}
+
+impl RewardComponents {
+    pub fn total(&self) -> f32 {
+        self.precision + self.density + self.clustering + self.fd_bonus
+    }
+
+    /// Component names as exported on `swegrep_reward_component`, with their values.
+    pub fn entries(&self) -> [(&'static str, f32); 4] {
+        [
+            ("precision", self.precision),
+            ("density", self.density),
+            ("clustering", self.clustering),
+            ("fd_bonus", self.fd_bonus),
+        ]
+    }
+
+    fn rounded(self) -> Self {
+        Self {
+            precision: round_two(self.precision),
+            density: round_two(self.density),
+            clustering: round_two(self.clustering),
+            fd_bonus: round_two(self.fd_bonus),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
 pub struct LanguageMetrics {
     #[serde(skip_serializing_if = "is_usize_zero")]
     pub discover_candidates: usize,
@@ -2675,7 +5002,9 @@ pub struct LanguageMetrics {
     pub latency: LanguageLatencyStats,
 }
 
-#[derive(Default, Serialize)]
+#[derive(Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
 pub struct LanguageLatencyStats {
     #[serde(skip_serializing_if = "is_zero")]
     pub discover_ms: u64,
@@ -2690,6 +5019,83 @@ pub struct LanguageLatencyStats {
 }
 
 impl StageStats {
+    /// Fold in the stats of a search that ran concurrently with this one: counts add up,
+    /// latencies keep the slowest root, and per-language metrics are summed.
+    /// Quality metrics (precision, density, clustering, fd bonus, reward) are left to the caller.
+    pub(crate) fn absorb(&mut self, other: StageStats) {
+        self.discover_candidates += other.discover_candidates;
+        self.discover_ms = self.discover_ms.max(other.discover_ms);
+        self.probe_hits += other.probe_hits;
+        self.probe_ms = self.probe_ms.max(other.probe_ms);
+        self.escalate_hits += other.escalate_hits;
+        self.escalate_ms = self.escalate_ms.max(other.escalate_ms);
+        self.index_candidates += other.index_candidates;
+        self.index_probe_hits += other.index_probe_hits;
+        self.index_ms = self.index_ms.max(other.index_ms);
+        self.symbol_store_candidates += other.symbol_store_candidates;
+        self.rga_hits += other.rga_hits;
+        self.rga_ms = self.rga_ms.max(other.rga_ms);
+        self.ast_matches += other.ast_matches;
+        self.disambiguate_ms = self.disambiguate_ms.max(other.disambiguate_ms);
+        self.verify_ms = self.verify_ms.max(other.verify_ms);
+        self.cycle_latency_ms = self.cycle_latency_ms.max(other.cycle_latency_ms);
+        for (lang, theirs) in other.language_metrics {
+            let ours = self.language_metrics.entry(lang).or_default();
+            ours.discover_candidates += theirs.discover_candidates;
+            ours.probe_hits += theirs.probe_hits;
+            ours.escalate_hits += theirs.escalate_hits;
+            ours.disambiguate_hits += theirs.disambiguate_hits;
+            ours.verify_hits += theirs.verify_hits;
+            ours.latency.discover_ms += theirs.latency.discover_ms;
+            ours.latency.probe_ms += theirs.latency.probe_ms;
+            ours.latency.escalate_ms += theirs.latency.escalate_ms;
+            ours.latency.disambiguate_ms += theirs.latency.disambiguate_ms;
+            ours.latency.verify_ms += theirs.latency.verify_ms;
+        }
+    }
+
+    /// Zero every latency, including the per-language ones; counts and quality metrics stay.
+    fn clear_timings(&mut self) {
+        self.discover_ms = 0;
+        self.probe_ms = 0;
+        self.escalate_ms = 0;
+        self.index_ms = 0;
+        self.rga_ms = 0;
+        self.disambiguate_ms = 0;
+        self.verify_ms = 0;
+        self.cycle_latency_ms = 0;
+        for metrics in self.language_metrics.values_mut() {
+            metrics.latency = LanguageLatencyStats::default();
+        }
+    }
+
+    /// Fill in the cycle's quality metrics and reward breakdown, rounded for display.
+    fn record_quality(&mut self, metrics: &SearchMetrics, weights: &RewardWeights) {
+        self.precision = round_two(metrics.precision);
+        self.density = round_two(metrics.density);
+        self.clustering = round_two(metrics.cluster_score);
+        self.fd_bonus = round_two(metrics.fd_bonus);
+        self.reward = round_two(metrics.reward);
+        self.reward_components = reward_components(metrics, weights).rounded();
+    }
+
+    /// Export `language_metrics` with a `language` label, under the stage names
+    /// `record_stage_latency` uses.
+    fn record_language_telemetry(&self) {
+        for (lang, metrics) in &self.language_metrics {
+            let latency = &metrics.latency;
+            for (stage, hits, latency_ms) in [
+                ("discover", metrics.discover_candidates, latency.discover_ms),
+                ("probe", metrics.probe_hits, latency.probe_ms),
+                ("escalate", metrics.escalate_hits, latency.escalate_ms),
+                ("ast", metrics.disambiguate_hits, latency.disambiguate_ms),
+                ("verify", metrics.verify_hits, latency.verify_ms),
+            ] {
+                crate::telemetry::record_language_stage(lang, stage, hits, latency_ms);
+            }
+        }
+    }
+
     fn record_discover_languages(&mut self, candidates: &[PathBuf], latency_ms: u64) {
         if candidates.is_empty() {
             return;
@@ -2772,37 +5178,309 @@ impl StageStats {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SearchSummary {
     pub cycle: u32,
     pub symbol: String,
+    /// How the symbol was searched, chosen from its shape; omitted for identifiers.
+    #[serde(default, skip_serializing_if = "Strategy::is_default")]
+    pub strategy: Strategy,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    /// Id under which the summary is stored; fetch it again with `swe-grep show <id>` or
+    /// `GET /search/result/{id}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_id: Option<String>,
     pub queries: Vec<String>,
+    /// For a glob symbol, the concrete identifiers it matched, most common first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_identifiers: Vec<MatchedIdentifier>,
+    /// TypeScript/JavaScript renames followed from the symbol, in the order they were found;
+    /// the targets' rewrites are part of `queries`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<SymbolAlias>,
     pub top_hits: Vec<TopHit>,
     pub deduped: usize,
-    pub next_actions: Vec<String>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    /// Deduplicated hits grouped by package or top-level directory, largest first; omitted for
+    /// fewer than two hits.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clusters: Vec<HitCluster>,
+    pub next_actions: Vec<NextAction>,
+    /// Alternatives offered when the cycle surfaced no hits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestions: Option<Suggestions>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<String>))]
     pub fd_candidates: Vec<PathBuf>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    /// `[path, line]` pairs confirmed by AST-Grep.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Vec<(String, usize)>))]
     pub ast_hits: Vec<(PathBuf, usize)>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub startup_stats: Option<StartupStats>,
     pub stage_stats: StageStats,
     pub reward: f32,
+    /// Problems the cycle recovered from; results may be incomplete when any are present.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+    /// Stages cut short or skipped under `--latency-target-ms` (`discover`, `probe`,
+    /// `escalate`, `index`, `rga`, `aliases`, `ast`).
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub warnings: Vec<String>,
+    pub budget_exceeded_stages: Vec<String>,
+    /// Areas the search never looked at: directories near the root that ignore rules or
+    /// hidden-file filtering exclude (`node_modules (ignored)`, `.github (hidden)`) and
+    /// extensions the language filter dropped (`*.md (extension filter)`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<String>,
+    /// File, line, and language counts for the repository; only with `--explain`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_stats: Option<RepoStats>,
+}
+
+impl SearchSummary {
+    /// Drop everything that varies between identical searches for `--deterministic`: the
+    /// search id and timings are cleared, and candidate lists, which follow tool output
+    /// order, are sorted. Hits are already ordered by score, then path and line.
+    pub fn make_deterministic(&mut self) {
+        self.search_id = None;
+        if let Some(startup_stats) = &mut self.startup_stats {
+            *startup_stats = StartupStats::default();
+        }
+        self.stage_stats.clear_timings();
+        self.fd_candidates.sort();
+        self.ast_hits.sort();
+    }
+}
+
+/// Non-fatal problem met during a cycle, e.g. a tool that failed and was skipped.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Warning {
+    pub kind: WarningKind,
+    /// Tool or stage involved (`rg`, `fd`, `ast-grep`, `rga`, `index`), when there is one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    /// Human-readable detail; not meant to be matched on.
+    pub message: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum WarningKind {
+    /// The tool could not be started; it is not installed or not executable.
+    ToolMissing,
+    /// The tool ran past `--timeout-secs` and was killed.
+    ToolTimedOut,
+    /// The tool ran but failed.
+    ToolFailed,
+    /// The symbol could not be turned into a valid regex or AST pattern.
+    PatternError,
+    /// The index refers to files that changed since it was built; rebuild it.
+    IndexStale,
+    /// Some `--files-from` entries were ignored.
+    ScopeSkipped,
+    /// The scoped probe skipped candidate files past `--max-scope-files`.
+    ScopeTruncated,
+    /// One root of a multi-root search failed; the others still answered.
+    RootFailed,
+    /// A `[[hooks]]` command failed; the summary is the one it was given.
+    HookFailed,
+    /// A WASM plugin failed and was skipped for the rest of the search.
+    PluginFailed,
+    /// Secrets were replaced with `[REDACTED]` in snippets, context, or bodies.
+    SecretsRedacted,
+    /// The index failed its health check when opened and was quarantined and rebuilt.
+    IndexRebuilt,
+    /// The tool failed repeatedly in this process and its stage was skipped until a cooldown
+    /// ends.
+    ToolCircuitOpen,
+}
+
+impl WarningKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ToolMissing => "tool_missing",
+            Self::ToolTimedOut => "tool_timed_out",
+            Self::ToolFailed => "tool_failed",
+            Self::PatternError => "pattern_error",
+            Self::IndexStale => "index_stale",
+            Self::ScopeSkipped => "scope_skipped",
+            Self::ScopeTruncated => "scope_truncated",
+            Self::RootFailed => "root_failed",
+            Self::HookFailed => "hook_failed",
+            Self::PluginFailed => "plugin_failed",
+            Self::SecretsRedacted => "secrets_redacted",
+            Self::IndexRebuilt => "index_rebuilt",
+            Self::ToolCircuitOpen => "tool_circuit_open",
+        }
+    }
+}
+
+impl Warning {
+    pub fn new(kind: WarningKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            tool: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_tool(mut self, tool: &str) -> Self {
+        self.tool = Some(tool.to_string());
+        self
+    }
+
+    /// Warning for a failed tool run, classified from the error's cause chain.
+    pub(crate) fn tool_error(tool: &str, context: &str, err: &anyhow::Error) -> Self {
+        Self::new(tool_error_kind(err), format!("{context}: {err}")).with_tool(tool)
+    }
+}
+
+/// Classify a failed tool run from the error's cause chain.
+fn tool_error_kind(err: &anyhow::Error) -> WarningKind {
+    if err
+        .chain()
+        .any(|cause| cause.is::<tokio::time::error::Elapsed>())
+    {
+        WarningKind::ToolTimedOut
+    } else if err.chain().any(|cause| {
+        cause.downcast_ref::<std::io::Error>().is_some_and(|io| {
+            matches!(
+                io.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::PermissionDenied
+            )
+        })
+    }) {
+        WarningKind::ToolMissing
+    } else if format!("{err:#}").contains("regex parse error") {
+        WarningKind::PatternError
+    } else {
+        WarningKind::ToolFailed
+    }
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Machine-actionable follow-up for the caller, serialized with a `kind` tag.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NextAction {
+    /// Open a surfaced hit.
+    OpenFile { path: String, line: usize },
+    /// Fetch more context around `top_hits[hit]`; its snippet was truncated.
+    ExpandContext { hit: usize },
+    /// Rerun without the explicit scope, or with an explicit `max_matches` in a huge
+    /// repository; the scoped search found nothing.
+    EscalateGlobal,
+    /// Retry with a different language hint.
+    TryLanguage { lang: String },
+    /// Retry with a different symbol spelling.
+    RefineSymbol { suggestion: String },
+}
+
+/// Reorder already ranked `top_hits` for `--sort`; `root` resolves hits without their own
+/// `root` when reading modification times. Hits whose file cannot be read sort last by recency.
+pub(crate) fn sort_top_hits(top_hits: &mut [TopHit], order: SortOrder, root: &Path) {
+    let location = |hit: &TopHit| (hit.root.clone(), hit.path.clone(), hit.line);
+    match order {
+        SortOrder::Score => {}
+        SortOrder::Path => top_hits.sort_by_key(location),
+        SortOrder::Line => top_hits.sort_by_key(|hit| (hit.line, location(hit))),
+        SortOrder::Recency => top_hits.sort_by_cached_key(|hit| {
+            let file = match &hit.archive_path {
+                Some(archive) => archive.as_str(),
+                None => hit.path.split("#cell=").next().unwrap_or(&hit.path),
+            };
+            let base = hit.root.as_deref().map_or(root, Path::new);
+            let modified = fs::metadata(base.join(file))
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            (std::cmp::Reverse(modified), location(hit))
+        }),
+    }
+}
+
+/// Open every hit, then expand the ones whose snippet was truncated.
+pub(crate) fn hit_actions(top_hits: &[TopHit]) -> Vec<NextAction> {
+    let mut actions: Vec<NextAction> = top_hits
+        .iter()
+        .map(|hit| NextAction::OpenFile {
+            path: match &hit.root {
+                Some(root) => display_path(&Path::new(root).join(&hit.path)),
+                None => hit.path.clone(),
+            },
+            line: hit.line,
+        })
+        .collect();
+    actions.extend(
+        top_hits
+            .iter()
+            .enumerate()
+            .filter(|(_, hit)| hit.raw_snippet_truncated)
+            .map(|(hit, _)| NextAction::ExpandContext { hit }),
+    );
+    actions
+}
+
+impl std::fmt::Display for NextAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OpenFile { path, line } => write!(f, "inspect {path}:{line}"),
+            Self::ExpandContext { hit } => write!(f, "expand context for hit {hit}"),
+            Self::EscalateGlobal => f.write_str("search the whole repository"),
+            Self::TryLanguage { lang } => write!(f, "retry with --language {lang}"),
+            Self::RefineSymbol { suggestion } => write!(f, "search for `{suggestion}`"),
+        }
+    }
 }
 
-#[derive(Clone, Serialize)]
+/// An identifier a glob symbol matched and how many deduplicated hits contain it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MatchedIdentifier {
+    pub identifier: String,
+    pub hits: usize,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct ContextHint {
+    /// `declaration`, `type`, or `extension` for the enclosing code; `generated_from` when the
+    /// hit is in a generated file, with `label` naming the schema and `line` the definition in it.
     pub kind: String,
     pub label: String,
     pub line: usize,
 }
 
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct TopHit {
     pub path: String,
     pub line: usize,
+    /// Repository root the hit belongs to; set only for multi-root searches.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// Archive on disk holding the match when ripgrep-all searched inside one; `path` is then
+    /// `archive_path/inner_path`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub archive_path: Option<String>,
+    /// Member of `archive_path` that matched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inner_path: Option<String>,
+    /// Notebook code cell (0-based) holding the match; `path` then ends in `#cell=N` and `line`
+    /// counts within the cell.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cell: Option<usize>,
+    /// `CODEOWNERS` owners of the file holding the match.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub owners: Vec<String>,
     pub score: f32,
     pub origin: String,
     pub origin_label: String,
@@ -2814,6 +5492,10 @@ pub struct TopHit {
     pub snippet_length: Option<usize>,
     #[serde(default, skip_serializing_if = "is_false")]
     pub raw_snippet_truncated: bool,
+    /// For lines too long to return whole, the characters around the match, with `…` marking
+    /// each end that was cut.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line_window: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub expanded_snippet: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -2826,8 +5508,43 @@ pub struct TopHit {
     pub body: Option<String>,
     #[serde(default, skip_serializing_if = "is_false")]
     pub body_retrieved: bool,
+    /// Comment block or docstring documenting the matched definition (`///`, `/** */`, `#`,
+    /// Python `"""`), markers kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc_comment: Option<String>,
     #[serde(default, skip_serializing_if = "hints_is_empty")]
     pub hints: Vec<ContextHint>,
+    /// Token spans over `snippet` (byte offsets); only with `--highlight`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snippet_highlights: Vec<HighlightSpan>,
+    /// Token spans over `expanded_snippet`, whose line numbers get none; only with
+    /// `--highlight`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expanded_snippet_highlights: Vec<HighlightSpan>,
+    /// The file holding the match has uncommitted changes (modified since HEAD, or untracked).
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub dirty: bool,
+    /// The matched line as committed at HEAD; set when the worktree edited that line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head_line: Option<String>,
+    /// The matched line in the worktree; set when a tracked file's line differs from HEAD, with
+    /// `head_line` absent for lines added since.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_line: Option<String>,
+    /// Editor deep link or GitHub permalink to the hit; only with `--link-format`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    /// Code-host URL pinned to the searched commit (HEAD, or `--rev`); only with `--permalinks`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permalink: Option<String>,
+    /// The file is a TypeScript/JavaScript barrel (mostly re-exports, like a package's
+    /// `index.ts`); such hits rank below all others.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub barrel: bool,
+    /// Swift package or Xcode targets that compile the file, from `Package.swift` and
+    /// `.xcodeproj` sources.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
 }
 
 fn is_zero(value: &u64) -> bool {
@@ -0,0 +1,548 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::time::Instant;
+
+use super::{
+    BudgetStage, NextAction, ProbeKind, SearchEngine, SearchHit, StageStats, VerificationOutcome,
+    Warning, WarningKind, elapsed_ms, within_budget,
+};
+use crate::suggest::Suggestions;
+use crate::tools::ast_grep::AstGrepMatch;
+use crate::ts_aliases::SymbolAlias;
+
+/// What the stages of one cycle hand to each other.
+#[derive(Default)]
+pub(super) struct CycleState {
+    pub(super) rewrites: Vec<String>,
+    /// `--files-from` limits the search.
+    pub(super) scoped: bool,
+    /// Nothing may be searched outside the discovered files.
+    pub(super) scoped_only: bool,
+    /// Files the symbol store lists for an exact identifier.
+    pub(super) stored: Vec<PathBuf>,
+    pub(super) discover_candidates: Vec<PathBuf>,
+    pub(super) hits: Vec<SearchHit>,
+    pub(super) aliases: Vec<SymbolAlias>,
+    pub(super) ast_matches: Vec<AstGrepMatch>,
+    /// Set by the verify stage.
+    pub(super) verification: Option<VerificationOutcome>,
+    pub(super) suggestions: Option<Suggestions>,
+    pub(super) stats: StageStats,
+}
+
+/// One step of a search cycle.
+///
+/// The pipeline decides whether the stage runs, bounds it by its slice of
+/// `--latency-target-ms`, times it, and calls the hooks around it, so a stage only does its own
+/// work.
+#[async_trait]
+pub(super) trait Stage: Send + Sync {
+    /// Name used in `budget_exceeded_stages`, hooks, and logs.
+    fn name(&self) -> &'static str;
+
+    /// Whether the stage runs this cycle; it may set up the stage's tool. A stage that does not
+    /// run records no latency.
+    fn applies(&self, _engine: &mut SearchEngine, _cycle: &CycleState) -> bool {
+        true
+    }
+
+    /// Budget the stage runs under, if any. A stage still running when it expires is dropped
+    /// and named in `budget_exceeded_stages`; one whose budget is already spent never starts.
+    fn budget(&self, _engine: &SearchEngine, _cycle: &CycleState) -> Option<BudgetStage> {
+        None
+    }
+
+    async fn run(&self, engine: &mut SearchEngine, cycle: &mut CycleState) -> Result<()>;
+
+    /// Record the stage's latency in `cycle.stats`, whether it finished or ran out of budget.
+    fn record(&self, _cycle: &mut CycleState, _elapsed_ms: u64) {}
+}
+
+/// How a stage ended, as reported to [`StageHook::after`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum StageOutcome {
+    Completed,
+    BudgetExceeded,
+    Failed,
+}
+
+/// Code wrapped around every stage, e.g. tracing. `before` hooks run in the order they were
+/// added and `after` hooks in reverse, so hooks nest like middleware.
+pub(super) trait StageHook: Send + Sync {
+    fn before(&self, _stage: &'static str, _cycle: &CycleState) {}
+
+    fn after(
+        &self,
+        _stage: &'static str,
+        _cycle: &CycleState,
+        _elapsed: Duration,
+        _outcome: StageOutcome,
+    ) {
+    }
+}
+
+/// The stages of a cycle and the hooks around them, run in order.
+pub(super) struct Pipeline {
+    stages: Vec<Box<dyn Stage>>,
+    hooks: Vec<Box<dyn StageHook>>,
+}
+
+impl Pipeline {
+    pub(super) fn new(stages: Vec<Box<dyn Stage>>) -> Self {
+        Self {
+            stages,
+            hooks: Vec::new(),
+        }
+    }
+
+    /// Discover, probe, escalate, index (indexing builds only), rga, aliases, ast, and verify,
+    /// with stage tracing.
+    pub(super) fn standard() -> Self {
+        let mut stages: Vec<Box<dyn Stage>> =
+            vec![Box::new(Discover), Box::new(Probe), Box::new(Escalate)];
+        #[cfg(feature = "indexing")]
+        stages.push(Box::new(Index));
+        stages.push(Box::new(Rga));
+        stages.push(Box::new(Aliases));
+        stages.push(Box::new(Disambiguate));
+        stages.push(Box::new(Verify));
+        Self::new(stages).with_hook(TraceStages)
+    }
+
+    pub(super) fn with_hook(mut self, hook: impl StageHook + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Run every applicable stage; the first stage error ends the cycle.
+    pub(super) async fn run(
+        &self,
+        engine: &mut SearchEngine,
+        cycle: &mut CycleState,
+    ) -> Result<()> {
+        for stage in &self.stages {
+            if !stage.applies(engine, cycle) {
+                continue;
+            }
+            let name = stage.name();
+            for hook in &self.hooks {
+                hook.before(name, cycle);
+            }
+            let start = Instant::now();
+            let limit = stage
+                .budget(engine, cycle)
+                .and_then(|budget| engine.stage_limit(budget));
+            let (outcome, result) = match within_budget(limit, stage.run(engine, cycle)).await {
+                Some(Ok(())) => (StageOutcome::Completed, Ok(())),
+                Some(Err(err)) => (StageOutcome::Failed, Err(err)),
+                None => {
+                    engine.exceed_budget(name);
+                    (StageOutcome::BudgetExceeded, Ok(()))
+                }
+            };
+            stage.record(cycle, elapsed_ms(start));
+            for hook in self.hooks.iter().rev() {
+                hook.after(name, cycle, start.elapsed(), outcome);
+            }
+            result?;
+        }
+        Ok(())
+    }
+}
+
+/// Logs every stage's latency and outcome at debug level.
+struct TraceStages;
+
+impl StageHook for TraceStages {
+    fn after(
+        &self,
+        stage: &'static str,
+        _cycle: &CycleState,
+        elapsed: Duration,
+        outcome: StageOutcome,
+    ) {
+        tracing::debug!(
+            stage,
+            elapsed_ms = elapsed.as_millis() as u64,
+            ?outcome,
+            "search_stage_complete"
+        );
+    }
+}
+
+/// Candidate files: the `--files-from` list, the module a Rust path names, the symbol store's
+/// files, or fd discovery.
+struct Discover;
+
+#[async_trait]
+impl Stage for Discover {
+    fn name(&self) -> &'static str {
+        "discover"
+    }
+
+    fn budget(&self, engine: &SearchEngine, cycle: &CycleState) -> Option<BudgetStage> {
+        // Only running discovery is budgeted; a scope that is already known is used as is.
+        let known = engine.config.scope.is_some()
+            || !engine.config.module_scope.is_empty()
+            || !cycle.stored.is_empty();
+        (!known).then_some(BudgetStage::Discover)
+    }
+
+    async fn run(&self, engine: &mut SearchEngine, cycle: &mut CycleState) -> Result<()> {
+        cycle.discover_candidates = match engine.config.scope.clone() {
+            Some(scope) => {
+                if scope.skipped > 0 {
+                    tracing::warn!(skipped = scope.skipped, "files-from entries skipped");
+                    engine.push_warning(Warning::new(
+                        WarningKind::ScopeSkipped,
+                        format!(
+                            "skipped {} --files-from entries that are missing or outside the root",
+                            scope.skipped
+                        ),
+                    ));
+                }
+                scope.files
+            }
+            None if !engine.config.module_scope.is_empty() => engine.config.module_scope.clone(),
+            None if !cycle.stored.is_empty() => std::mem::take(&mut cycle.stored),
+            None => engine.discover().await,
+        };
+        Ok(())
+    }
+
+    fn record(&self, cycle: &mut CycleState, elapsed_ms: u64) {
+        cycle.stats.discover_ms = elapsed_ms;
+        cycle.stats.discover_candidates = cycle.discover_candidates.len();
+        cycle
+            .stats
+            .record_discover_languages(&cycle.discover_candidates, elapsed_ms);
+    }
+}
+
+/// Ripgrep over the discovered files.
+struct Probe;
+
+impl Probe {
+    /// An empty scope would otherwise turn into a global search.
+    fn skipped(cycle: &CycleState) -> bool {
+        cycle.scoped_only && cycle.discover_candidates.is_empty()
+    }
+}
+
+#[async_trait]
+impl Stage for Probe {
+    fn name(&self) -> &'static str {
+        "probe"
+    }
+
+    fn budget(&self, _engine: &SearchEngine, cycle: &CycleState) -> Option<BudgetStage> {
+        (!Self::skipped(cycle)).then_some(BudgetStage::Probe)
+    }
+
+    async fn run(&self, engine: &mut SearchEngine, cycle: &mut CycleState) -> Result<()> {
+        if Self::skipped(cycle) {
+            return Ok(());
+        }
+        let scope = engine.sample_scope(&cycle.discover_candidates);
+        let (hits, scoped_hits) = engine
+            .probe(&cycle.rewrites, scope, ProbeKind::Scoped)
+            .await;
+        cycle.hits = hits;
+        cycle.stats.probe_hits = scoped_hits;
+        Ok(())
+    }
+
+    fn record(&self, cycle: &mut CycleState, elapsed_ms: u64) {
+        cycle.stats.probe_ms = elapsed_ms;
+        cycle.stats.record_probe_languages(&cycle.hits, elapsed_ms);
+    }
+}
+
+/// Ripgrep over the whole root when the scoped probe found nothing.
+struct Escalate;
+
+#[async_trait]
+impl Stage for Escalate {
+    fn name(&self) -> &'static str {
+        "escalate"
+    }
+
+    fn applies(&self, engine: &mut SearchEngine, cycle: &CycleState) -> bool {
+        cycle.hits.is_empty() && !cycle.scoped && engine.config.escalate
+    }
+
+    fn budget(&self, _engine: &SearchEngine, _cycle: &CycleState) -> Option<BudgetStage> {
+        Some(BudgetStage::Probe)
+    }
+
+    async fn run(&self, engine: &mut SearchEngine, cycle: &mut CycleState) -> Result<()> {
+        let (hits, global_hits) = engine.probe(&cycle.rewrites, &[], ProbeKind::Global).await;
+        cycle.hits = hits;
+        cycle.stats.escalate_hits = global_hits;
+        Ok(())
+    }
+
+    fn record(&self, cycle: &mut CycleState, elapsed_ms: u64) {
+        cycle.stats.escalate_ms = elapsed_ms;
+        cycle
+            .stats
+            .record_escalate_languages(&cycle.hits, elapsed_ms);
+    }
+}
+
+/// Tantivy candidates, checked with ripgrep, when nothing has matched yet.
+#[cfg(feature = "indexing")]
+struct Index;
+
+#[cfg(feature = "indexing")]
+#[async_trait]
+impl Stage for Index {
+    fn name(&self) -> &'static str {
+        "index"
+    }
+
+    fn applies(&self, engine: &mut SearchEngine, cycle: &CycleState) -> bool {
+        cycle.hits.is_empty() && !cycle.scoped && engine.config.use_index
+    }
+
+    fn budget(&self, _engine: &SearchEngine, _cycle: &CycleState) -> Option<BudgetStage> {
+        Some(BudgetStage::Probe)
+    }
+
+    async fn run(&self, engine: &mut SearchEngine, cycle: &mut CycleState) -> Result<()> {
+        let symbol = engine.config.symbol.clone();
+        let max_matches = engine.config.max_matches;
+        let index = match engine.ensure_index().await {
+            Ok(index) => index,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to initialize index");
+                engine.push_warning(Warning::tool_error(
+                    "index",
+                    "index initialization failed",
+                    &err,
+                ));
+                return Ok(());
+            }
+        };
+        crate::telemetry::record_tool_invocation("index");
+        let mut matches = match index.search(&symbol, max_matches).await {
+            Ok(matches) => matches,
+            Err(err) => {
+                tracing::warn!(error = %err, "tantivy search failed");
+                engine.push_warning(Warning::tool_error("index", "index search failed", &err));
+                return Ok(());
+            }
+        };
+        let mut candidates: Vec<PathBuf> = matches.iter().map(|found| found.path.clone()).collect();
+        candidates.sort();
+        candidates.dedup();
+        let indexed = candidates.len();
+        candidates.retain(|path| engine.config.root.join(path).exists());
+        matches.retain(|found| candidates.binary_search(&found.path).is_ok());
+        if candidates.len() < indexed {
+            let missing = indexed - candidates.len();
+            tracing::warn!(missing, "index lists files that no longer exist");
+            engine.push_warning(
+                Warning::new(
+                    WarningKind::IndexStale,
+                    format!(
+                        "index lists {missing} files that no longer exist; \
+                         delete the index directory to rebuild it"
+                    ),
+                )
+                .with_tool("index"),
+            );
+        }
+        cycle.stats.index_candidates = candidates.len();
+        crate::telemetry::record_tool_results("index", candidates.len());
+        // Exact identifiers are checked on the indexed lines themselves; only notebooks, whose
+        // lines count within their cells, still need ripgrep.
+        let mut indexed_hits = Vec::new();
+        if engine.is_literal_symbol() {
+            (indexed_hits, candidates) = engine.indexed_line_hits(&matches);
+        }
+        if !candidates.is_empty() {
+            let (probed, _) = engine
+                .probe(&cycle.rewrites, &candidates, ProbeKind::Indexed)
+                .await;
+            indexed_hits.extend(probed);
+        }
+        cycle.stats.index_probe_hits = indexed_hits.len();
+        cycle.hits.extend(indexed_hits);
+        Ok(())
+    }
+
+    fn record(&self, cycle: &mut CycleState, elapsed_ms: u64) {
+        cycle.stats.index_ms = elapsed_ms;
+    }
+}
+
+/// ripgrep-all over archives and documents when nothing has matched yet.
+struct Rga;
+
+#[async_trait]
+impl Stage for Rga {
+    fn name(&self) -> &'static str {
+        "rga"
+    }
+
+    fn applies(&self, engine: &mut SearchEngine, cycle: &CycleState) -> bool {
+        cycle.hits.is_empty() && !cycle.scoped && engine.ensure_rga_tool().is_some()
+    }
+
+    fn budget(&self, _engine: &SearchEngine, _cycle: &CycleState) -> Option<BudgetStage> {
+        Some(BudgetStage::Probe)
+    }
+
+    async fn run(&self, engine: &mut SearchEngine, cycle: &mut CycleState) -> Result<()> {
+        let root = engine.config.root.clone();
+        let symbol = engine.config.symbol.clone();
+        let Some(rga_tool) = engine.ensure_rga_tool() else {
+            return Ok(());
+        };
+        crate::telemetry::record_tool_invocation("rga");
+        match rga_tool.search(&root, &symbol).await {
+            Ok(matches) => {
+                engine.record_tool_outcome("rga", None);
+                cycle.stats.rga_hits = matches.len();
+                crate::telemetry::record_tool_results("rga", matches.len());
+                for m in matches {
+                    cycle.hits.push(SearchHit::from_rga(&root, m));
+                }
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "rga search failed");
+                engine.record_tool_outcome("rga", Some(&err));
+                engine.push_warning(Warning::tool_error("rga", "rga search failed", &err));
+            }
+        }
+        Ok(())
+    }
+
+    fn record(&self, cycle: &mut CycleState, elapsed_ms: u64) {
+        cycle.stats.rga_ms = elapsed_ms;
+    }
+}
+
+/// TypeScript/JavaScript renames of the symbol, probed like the symbol itself. Counted as
+/// probe time.
+struct Aliases;
+
+#[async_trait]
+impl Stage for Aliases {
+    fn name(&self) -> &'static str {
+        "aliases"
+    }
+
+    async fn run(&self, engine: &mut SearchEngine, cycle: &mut CycleState) -> Result<()> {
+        // Renames are probed in the same files as the symbol; `sample_scope` already warned.
+        let candidates = &cycle.discover_candidates;
+        let alias_scope = match engine.config.max_scope_files {
+            Some(cap) if cycle.scoped_only => &candidates[..cap.min(candidates.len())],
+            _ if cycle.scoped_only => candidates.as_slice(),
+            _ => &[],
+        };
+        cycle.aliases = engine
+            .follow_aliases(
+                &mut cycle.hits,
+                &mut cycle.rewrites,
+                cycle.scoped_only,
+                alias_scope,
+            )
+            .await;
+        Ok(())
+    }
+
+    fn record(&self, cycle: &mut CycleState, elapsed_ms: u64) {
+        cycle.stats.probe_ms += elapsed_ms;
+    }
+}
+
+/// AST-Grep over the files with hits, confirming structural matches.
+struct Disambiguate;
+
+impl Disambiguate {
+    fn skipped(engine: &SearchEngine, cycle: &CycleState) -> bool {
+        (cycle.scoped && cycle.hits.is_empty()) || !engine.should_run_ast()
+    }
+}
+
+#[async_trait]
+impl Stage for Disambiguate {
+    fn name(&self) -> &'static str {
+        "ast"
+    }
+
+    fn budget(&self, engine: &SearchEngine, cycle: &CycleState) -> Option<BudgetStage> {
+        (!Self::skipped(engine, cycle)).then_some(BudgetStage::Disambiguate)
+    }
+
+    async fn run(&self, engine: &mut SearchEngine, cycle: &mut CycleState) -> Result<()> {
+        if Self::skipped(engine, cycle) {
+            return Ok(());
+        }
+        let ast_scope: Vec<PathBuf> = cycle
+            .hits
+            .iter()
+            .map(|hit| hit.path.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        let spellings = engine.case_variants(&cycle.hits);
+        cycle.ast_matches = engine.disambiguate(&ast_scope, &spellings).await;
+        Ok(())
+    }
+
+    fn record(&self, cycle: &mut CycleState, elapsed_ms: u64) {
+        cycle.stats.disambiguate_ms = elapsed_ms;
+        cycle.stats.ast_matches = cycle.ast_matches.len();
+        cycle
+            .stats
+            .record_disambiguate_languages(&cycle.ast_matches, elapsed_ms);
+    }
+}
+
+/// Score, deduplicate, and enrich the hits; suggest alternatives when none survive.
+struct Verify;
+
+#[async_trait]
+impl Stage for Verify {
+    fn name(&self) -> &'static str {
+        "verify"
+    }
+
+    async fn run(&self, engine: &mut SearchEngine, cycle: &mut CycleState) -> Result<()> {
+        let discover_set: HashSet<PathBuf> = cycle.discover_candidates.iter().cloned().collect();
+        let mut verification = engine
+            .verify(
+                std::mem::take(&mut cycle.hits),
+                std::mem::take(&mut cycle.ast_matches),
+                discover_set,
+                cycle.discover_candidates.clone(),
+            )
+            .await?;
+        if cycle.scoped_only && verification.top_hits.is_empty() {
+            verification.next_actions.push(NextAction::EscalateGlobal);
+        }
+        if verification.top_hits.is_empty() {
+            let suggestions = engine.suggest(&cycle.discover_candidates).await;
+            verification.next_actions.extend(suggestions.next_actions());
+            cycle.suggestions = Some(suggestions).filter(|suggestions| !suggestions.is_empty());
+        }
+        cycle.verification = Some(verification);
+        Ok(())
+    }
+
+    fn record(&self, cycle: &mut CycleState, elapsed_ms: u64) {
+        cycle.stats.verify_ms = elapsed_ms;
+        if let Some(verification) = &cycle.verification {
+            cycle
+                .stats
+                .record_verify_languages(&verification.language_counts, elapsed_ms);
+        }
+    }
+}
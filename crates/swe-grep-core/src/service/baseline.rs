@@ -0,0 +1,205 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use anyhow::{Context, Result, bail};
+
+use crate::cli::{SearchArgs, ToolArgs};
+use crate::config::RepoConfig;
+use crate::search;
+use crate::telemetry;
+
+/// Default ratio of a search's latency to the startup baseline past which it counts as degraded.
+pub const DEFAULT_DEGRADATION_FACTOR: f64 = 20.0;
+
+/// Folder under the cache directory the fixture is written to, so the benchmark reads from the
+/// same filesystem as the served repository.
+const FIXTURE_DIR: &str = "self-benchmark";
+const FIXTURE_SYMBOL: &str = "load_settings";
+/// Searches the baseline is the median of.
+const RUNS: usize = 3;
+const FIXTURE: &[(&str, &str)] = &[
+    (
+        "src/lib.rs",
+        "pub mod settings;\n\npub fn load_settings(path: &str) -> settings::Settings {\n    settings::Settings::from_file(path)\n}\n",
+    ),
+    (
+        "src/settings.rs",
+        "pub struct Settings {\n    pub name: String,\n}\n\nimpl Settings {\n    pub fn from_file(path: &str) -> Self {\n        Self { name: path.to_string() }\n    }\n}\n",
+    ),
+    (
+        "app/main.py",
+        "from app.settings import load_settings\n\n\ndef main():\n    return load_settings(\"app.toml\")\n",
+    ),
+    (
+        "web/index.ts",
+        "import { loadSettings } from \"./settings\";\n\nexport const settings = loadSettings();\n",
+    ),
+];
+
+/// Latency of a tiny search measured when the server starts, and the check of later searches
+/// against it.
+///
+/// The fixture is the same few files on every machine, so its latency reflects process spawning
+/// and the filesystem rather than the repository. Searches slower than `factor` times the
+/// baseline are counted in `swegrep_latency_degraded_total`, and the first of a run of them is
+/// logged, giving operators an early signal that the disk has become the bottleneck.
+pub struct LatencyBaseline {
+    factor: f64,
+    baseline_ms: OnceLock<f64>,
+    degraded: AtomicBool,
+}
+
+impl LatencyBaseline {
+    /// `factor == 0` turns the self-benchmark and the check off.
+    pub fn new(factor: f64) -> Self {
+        Self {
+            factor,
+            baseline_ms: OnceLock::new(),
+            degraded: AtomicBool::new(false),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.factor > 0.0
+    }
+
+    /// Median fixture latency in milliseconds, once measured.
+    pub fn baseline_ms(&self) -> Option<f64> {
+        self.baseline_ms.get().copied()
+    }
+
+    /// Whether the last search observed was slower than the baseline allows.
+    pub fn degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// Search the bundled fixture under `cache_dir` with `root`'s ripgrep and keep the median
+    /// latency as the baseline.
+    pub async fn measure(&self, root: &Path, cache_dir: &Path, tools: &ToolArgs) -> Result<f64> {
+        // A cache directory that does not exist yet is left that way.
+        let created = !cache_dir.exists();
+        let fixture = cache_dir.join(FIXTURE_DIR);
+        let result = self.measure_in(root, &fixture, tools).await;
+        let _ = std::fs::remove_dir_all(if created { cache_dir } else { &fixture });
+        result
+    }
+
+    async fn measure_in(&self, root: &Path, fixture: &Path, tools: &ToolArgs) -> Result<f64> {
+        for (path, contents) in FIXTURE {
+            let path = fixture.join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create {}", parent.display()))?;
+            }
+            std::fs::write(&path, contents)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+        }
+        let rg = RepoConfig::load(root)?.tools.resolve(root, tools)?.rg;
+        // The fixture sits in the cache directory, which the repository usually ignores.
+        let mut rg_args = rg.extra_args;
+        rg_args.push("--no-ignore-parent".to_string());
+
+        let mut latencies = Vec::with_capacity(RUNS);
+        for _ in 0..RUNS {
+            let args = fixture_args(fixture, rg.path.clone(), rg_args.clone());
+            let started = Instant::now();
+            let summary = search::execute(args).await?;
+            let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+            if summary.top_hits.is_empty() {
+                let warnings: Vec<String> = summary
+                    .warnings
+                    .iter()
+                    .map(|warning| warning.message.clone())
+                    .collect();
+                bail!("self-benchmark found no hits: {}", warnings.join("; "));
+            }
+            telemetry::record_self_benchmark(latency_ms);
+            latencies.push(latency_ms);
+        }
+        latencies.sort_by(f64::total_cmp);
+        let baseline_ms = latencies[RUNS / 2];
+        let _ = self.baseline_ms.set(baseline_ms);
+        Ok(baseline_ms)
+    }
+
+    /// Check a finished search's latency against the baseline.
+    pub fn observe(&self, latency_ms: u64) {
+        let Some(baseline_ms) = self.baseline_ms().filter(|_| self.enabled()) else {
+            return;
+        };
+        let limit_ms = baseline_ms * self.factor;
+        let degraded = latency_ms as f64 > limit_ms;
+        if degraded {
+            telemetry::record_latency_degraded();
+        }
+        if self.degraded.swap(degraded, Ordering::Relaxed) != degraded {
+            if degraded {
+                tracing::warn!(
+                    latency_ms,
+                    baseline_ms,
+                    factor = self.factor,
+                    "search latency is far above the startup baseline; check disk and filesystem load"
+                );
+            } else {
+                tracing::info!(
+                    latency_ms,
+                    baseline_ms,
+                    "search latency back within baseline"
+                );
+            }
+        }
+    }
+}
+
+fn fixture_args(fixture: &Path, rg_path: Option<PathBuf>, rg_args: Vec<String>) -> SearchArgs {
+    SearchArgs {
+        symbol: FIXTURE_SYMBOL.to_string(),
+        path: vec![fixture.to_path_buf()],
+        language: None,
+        rg_types: Vec::new(),
+        rg_type_adds: Vec::new(),
+        config: false,
+        rev: None,
+        files_from: None,
+        include_submodules: false,
+        owner: None,
+        case: None,
+        word: None,
+        profile: None,
+        trust_repo_hooks: false,
+        trust_repo_state: false,
+        timeout_secs: Some(5),
+        max_matches: Some(20),
+        max_scope_files: None,
+        latency_target_ms: None,
+        concurrency: 1,
+        context_before: 0,
+        context_after: 0,
+        body: false,
+        redact_secrets: false,
+        enable_index: false,
+        index_dir: None,
+        enable_rga: false,
+        cache_dir: Some(fixture.join(".swe-grep-cache")),
+        state_max_symbols: None,
+        log_dir: None,
+        use_fd: false,
+        use_ast_grep: false,
+        tools: ToolArgs {
+            rg_path,
+            rg_args,
+            ..ToolArgs::default()
+        },
+        fields: Vec::new(),
+        explain: false,
+        highlight: false,
+        link_format: None,
+        permalinks: false,
+        sort: None,
+        deterministic: true,
+        format: None,
+        exec: None,
+    }
+}
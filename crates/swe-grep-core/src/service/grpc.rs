@@ -7,27 +7,75 @@ use tonic::async_trait;
 use tonic::transport::Server;
 use tonic::{Request, Response, Status};
 
-use crate::search::{SearchSummary, StageStats, StartupStats};
+use crate::cli::{CaseMode, LinkFormat, Priority, SortOrder};
+use crate::fields::FieldSelection;
+use crate::highlight::HighlightSpan;
+use crate::search::{NextAction, SearchSummary, StageStats, StartupStats};
+use crate::source::RangeError;
+use crate::stats::RepoStats;
+use crate::suggest::Suggestions;
+use crate::validate::{self, ValidationError};
 
+use super::API_VERSIONS;
 use super::proto::{
     self,
     swe_grep_service_server::{SweGrepService, SweGrepServiceServer},
 };
-use super::server::{SearchExecutor, SearchInput};
+use super::server::{RootNotAllowed, SearchExecutor, SearchInput};
+use super::tenant::TenantError;
 
 /// Start the gRPC server and block until shutdown.
 pub async fn serve(addr: SocketAddr, executor: Arc<SearchExecutor>) -> Result<()> {
-    let service = SweGrepGrpc { executor };
-
-    Server::builder()
-        .add_service(SweGrepServiceServer::new(service))
-        .serve_with_shutdown(addr, async {
-            let _ = tokio::signal::ctrl_c().await;
-        })
+    router(executor)
+        .await?
+        .serve_with_shutdown(addr, super::shutdown_signal())
         .await
         .with_context(|| format!("failed to start gRPC server on {addr}"))
 }
 
+/// Serve gRPC on an already-bound Unix domain socket until shutdown.
+#[cfg(unix)]
+pub async fn serve_unix(
+    listener: tokio::net::UnixListener,
+    executor: Arc<SearchExecutor>,
+) -> Result<()> {
+    let incoming = tokio_stream::wrappers::UnixListenerStream::new(listener);
+    router(executor)
+        .await?
+        .serve_with_incoming_shutdown(incoming, super::shutdown_signal())
+        .await
+        .context("failed to run gRPC server on unix socket")
+}
+
+/// Both API versions on one server, sharing `executor`, next to the standard
+/// `grpc.health.v1.Health` service and server reflection so grpcurl and service meshes can
+/// probe and introspect it without knowing the swe-grep protos.
+async fn router(executor: Arc<SearchExecutor>) -> Result<tonic::transport::server::Router> {
+    let service = SweGrepGrpc {
+        executor: executor.clone(),
+    };
+
+    let (mut reporter, health) = tonic_health::server::health_reporter();
+    reporter
+        .set_serving::<SweGrepServiceServer<SweGrepGrpc>>()
+        .await;
+    reporter.set_serving::<super::grpc_v2::Server>().await;
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(super::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        .build()
+        .context("failed to build the gRPC reflection service")?;
+
+    Ok(Server::builder()
+        .add_service(
+            SweGrepServiceServer::new(service)
+                .max_decoding_message_size(validate::MAX_REQUEST_BYTES),
+        )
+        .add_service(super::grpc_v2::service(executor))
+        .add_service(health)
+        .add_service(reflection))
+}
+
 #[derive(Clone)]
 struct SweGrepGrpc {
     executor: Arc<SearchExecutor>,
@@ -39,17 +87,19 @@ impl SweGrepService for SweGrepGrpc {
         &self,
         request: Request<proto::SearchRequest>,
     ) -> Result<Response<proto::SearchResponse>, Status> {
+        let token = bearer_token(&request);
         let inner = request.into_inner();
-        let input = map_request(inner);
+        let fields = FieldSelection::parse(&inner.fields)
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+        let mut input = map_request(inner).map_err(Status::invalid_argument)?;
+        input.token = token;
 
-        let summary = self.executor.execute(input).await.map_err(|err| {
-            let msg = err.to_string();
-            if msg.contains("symbol is required") {
-                Status::invalid_argument(msg)
-            } else {
-                Status::internal(msg)
-            }
-        })?;
+        let mut summary = self
+            .executor
+            .execute(input)
+            .await
+            .map_err(status_from_error)?;
+        fields.prune(&mut summary);
 
         let response = proto::SearchResponse {
             summary: Some(summary.into()),
@@ -64,18 +114,80 @@ impl SweGrepService for SweGrepGrpc {
     ) -> Result<Response<proto::HealthCheckResponse>, Status> {
         let response = proto::HealthCheckResponse {
             status: "ok".to_string(),
+            api_versions: API_VERSIONS.iter().map(ToString::to_string).collect(),
         };
         Ok(Response::new(response))
     }
 }
 
-fn map_request(proto: proto::SearchRequest) -> SearchInput {
-    SearchInput {
+/// Token from `authorization: Bearer <token>` request metadata; shared by every API version.
+pub(super) fn bearer_token<T>(request: &Request<T>) -> Option<String> {
+    let value = request.metadata().get("authorization")?.to_str().ok()?;
+    let token = value.strip_prefix("Bearer ")?.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+/// Map a failed request onto a gRPC status; shared by every API version.
+///
+/// Validation failures also list the rejected fields, comma-separated, in `invalid-fields`
+/// response metadata.
+pub(super) fn status_from_error(err: anyhow::Error) -> Status {
+    let msg = err.to_string();
+    if let Some(invalid) = err.downcast_ref::<ValidationError>() {
+        let mut status = Status::invalid_argument(msg);
+        let fields: Vec<&str> = invalid.fields.iter().map(|error| error.field).collect();
+        if let Ok(value) = fields.join(",").parse() {
+            status.metadata_mut().insert("invalid-fields", value);
+        }
+        return status;
+    }
+    if let Some(err) = err.downcast_ref::<TenantError>() {
+        return match err {
+            TenantError::Required | TenantError::Override { .. } => Status::invalid_argument(msg),
+            TenantError::Unknown(_) => Status::not_found(msg),
+            TenantError::Unauthorized(_) => Status::unauthenticated(msg),
+            TenantError::QuotaExceeded { .. } => Status::resource_exhausted(msg),
+        };
+    }
+    if err.is::<RootNotAllowed>() {
+        return Status::permission_denied(msg);
+    }
+    if let Some(err) = err.downcast_ref::<RangeError>() {
+        return match err {
+            RangeError::InvalidRange { .. } | RangeError::NotAFile(_) => {
+                Status::invalid_argument(msg)
+            }
+            RangeError::NotFound(_) => Status::not_found(msg),
+            RangeError::Binary(_) => Status::failed_precondition(msg),
+            RangeError::PastEnd { .. } => Status::out_of_range(msg),
+        };
+    }
+    if msg.contains("symbol is required") || msg.contains("unknown profile") {
+        Status::invalid_argument(msg)
+    } else {
+        Status::internal(msg)
+    }
+}
+
+fn map_request(proto: proto::SearchRequest) -> Result<SearchInput, String> {
+    Ok(SearchInput {
         symbol: proto.symbol,
         language: option_from_string(proto.language),
+        rg_types: proto.rg_types,
+        rg_type_adds: proto.rg_type_adds,
+        config: Some(proto.config),
         root: path_from_string(proto.root),
+        profile: option_from_string(proto.profile),
+        owner: option_from_string(proto.owner),
+        case: option_from_string(proto.case)
+            .as_deref()
+            .map(CaseMode::parse)
+            .transpose()?,
+        word: proto.partial_words.then_some(false),
         timeout_secs: zeroable(proto.timeout_secs),
         max_matches: zeroable_usize(proto.max_matches),
+        max_scope_files: zeroable_usize(proto.max_scope_files),
+        latency_target_ms: zeroable(proto.latency_target_ms),
         concurrency: zeroable_usize(proto.concurrency),
         enable_index: Some(proto.enable_index),
         enable_rga: Some(proto.enable_rga),
@@ -86,7 +198,25 @@ fn map_request(proto: proto::SearchRequest) -> SearchInput {
         context_after: zeroable_usize(proto.context_after),
         body: Some(proto.body),
         tool_flags: proto.tool_flags,
-    }
+        explain: Some(proto.explain),
+        highlight: Some(proto.highlight),
+        link_format: option_from_string(proto.link_format)
+            .as_deref()
+            .map(LinkFormat::parse)
+            .transpose()?,
+        permalinks: Some(proto.permalinks),
+        sort: option_from_string(proto.sort)
+            .as_deref()
+            .map(SortOrder::parse)
+            .transpose()?,
+        deterministic: Some(proto.deterministic),
+        priority: option_from_string(proto.priority)
+            .as_deref()
+            .map(Priority::parse)
+            .transpose()?,
+        tenant: option_from_string(proto.tenant),
+        token: None,
+    })
 }
 
 fn option_from_string(value: String) -> Option<String> {
@@ -142,6 +272,19 @@ impl From<SearchSummary> for proto::SearchSummary {
                         raw_snippet: hit.raw_snippet.unwrap_or_default(),
                         snippet_length,
                         raw_snippet_truncated: hit.raw_snippet_truncated,
+                        line_window: hit.line_window.unwrap_or_default(),
+                        dirty: hit.dirty,
+                        head_line: hit.head_line.unwrap_or_default(),
+                        worktree_line: hit.worktree_line.unwrap_or_default(),
+                        link: hit.link.unwrap_or_default(),
+                        permalink: hit.permalink.unwrap_or_default(),
+                        barrel: hit.barrel,
+                        targets: hit.targets,
+                        doc_comment: hit.doc_comment.unwrap_or_default(),
+                        snippet_highlights: convert_highlights(hit.snippet_highlights),
+                        expanded_snippet_highlights: convert_highlights(
+                            hit.expanded_snippet_highlights,
+                        ),
                         expanded_snippet: hit.expanded_snippet.unwrap_or_default(),
                         context_start,
                         context_end,
@@ -156,11 +299,25 @@ impl From<SearchSummary> for proto::SearchSummary {
                                 line: hint.line as u32,
                             })
                             .collect(),
+                        root: hit.root.unwrap_or_default(),
+                        archive_path: hit.archive_path.unwrap_or_default(),
+                        inner_path: hit.inner_path.unwrap_or_default(),
+                        cell: hit.cell.map_or(-1, |cell| cell as i64),
+                        owners: hit.owners,
                     }
                 })
                 .collect(),
             deduped: summary.deduped as u32,
-            next_actions: summary.next_actions,
+            next_actions: summary
+                .next_actions
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            next_action_details: summary
+                .next_actions
+                .into_iter()
+                .map(map_next_action)
+                .collect(),
             fd_candidates: summary
                 .fd_candidates
                 .into_iter()
@@ -177,11 +334,88 @@ impl From<SearchSummary> for proto::SearchSummary {
             stage_stats,
             reward: summary.reward,
             startup_stats,
-            warnings: summary.warnings,
+            warnings: summary
+                .warnings
+                .iter()
+                .map(|warning| warning.message.clone())
+                .collect(),
+            warning_details: summary
+                .warnings
+                .into_iter()
+                .map(|warning| proto::Warning {
+                    kind: warning.kind.as_str().to_string(),
+                    tool: warning.tool.unwrap_or_default(),
+                    message: warning.message,
+                })
+                .collect(),
+            suggestions: summary.suggestions.map(convert_suggestions),
+            budget_exceeded_stages: summary.budget_exceeded_stages,
+            skipped: summary.skipped,
+            repo_stats: summary.repo_stats.map(convert_repo_stats),
+            search_id: summary.search_id.unwrap_or_default(),
+            aliases: summary
+                .aliases
+                .into_iter()
+                .map(|alias| proto::SymbolAlias {
+                    alias: alias.alias,
+                    target: alias.target,
+                    kind: alias.kind.as_str().to_string(),
+                    path: alias.path,
+                    line: alias.line as u32,
+                })
+                .collect(),
         }
     }
 }
 
+fn map_next_action(action: NextAction) -> proto::NextAction {
+    let mut message = proto::NextAction::default();
+    match action {
+        NextAction::OpenFile { path, line } => {
+            message.kind = "open_file".to_string();
+            message.path = path;
+            message.line = line as u32;
+        }
+        NextAction::ExpandContext { hit } => {
+            message.kind = "expand_context".to_string();
+            message.hit = hit as u32;
+        }
+        NextAction::EscalateGlobal => message.kind = "escalate_global".to_string(),
+        NextAction::TryLanguage { lang } => {
+            message.kind = "try_language".to_string();
+            message.lang = lang;
+        }
+        NextAction::RefineSymbol { suggestion } => {
+            message.kind = "refine_symbol".to_string();
+            message.suggestion = suggestion;
+        }
+    }
+    message
+}
+
+fn convert_suggestions(suggestions: Suggestions) -> proto::Suggestions {
+    proto::Suggestions {
+        symbols: suggestions
+            .symbols
+            .into_iter()
+            .map(|suggestion| proto::SymbolSuggestion {
+                symbol: suggestion.symbol,
+                distance: suggestion.distance as u32,
+                path: suggestion.path,
+            })
+            .collect(),
+        case_variants: suggestions.case_variants,
+        languages: suggestions
+            .languages
+            .into_iter()
+            .map(|hint| proto::LanguageHint {
+                language: hint.language,
+                files: hint.files as u32,
+            })
+            .collect(),
+    }
+}
+
 fn convert_stage_stats(stats: StageStats) -> proto::StageStats {
     proto::StageStats {
         discover_candidates: stats.discover_candidates as u32,
@@ -203,6 +437,14 @@ fn convert_stage_stats(stats: StageStats) -> proto::StageStats {
         density: stats.density,
         clustering: stats.clustering,
         reward: stats.reward,
+        symbol_store_candidates: stats.symbol_store_candidates as u32,
+        fd_bonus: stats.fd_bonus,
+        reward_components: Some(proto::RewardComponents {
+            precision: stats.reward_components.precision,
+            density: stats.reward_components.density,
+            clustering: stats.reward_components.clustering,
+            fd_bonus: stats.reward_components.fd_bonus,
+        }),
     }
 }
 
@@ -218,3 +460,32 @@ fn convert_startup_stats(stats: StartupStats) -> proto::StartupStats {
         index_ms: stats.index_ms,
     }
 }
+
+fn convert_repo_stats(stats: RepoStats) -> proto::RepoStats {
+    proto::RepoStats {
+        files: stats.files as u64,
+        lines: stats.lines as u64,
+        bytes: stats.bytes,
+        languages: stats
+            .languages
+            .into_iter()
+            .map(|language| proto::LanguageStats {
+                language: language.language,
+                files: language.files as u64,
+                lines: language.lines as u64,
+            })
+            .collect(),
+        collected_at_ms: stats.collected_at_ms,
+    }
+}
+
+fn convert_highlights(spans: Vec<HighlightSpan>) -> Vec<proto::HighlightSpan> {
+    spans
+        .into_iter()
+        .map(|span| proto::HighlightSpan {
+            start: span.start as u32,
+            end: span.end as u32,
+            kind: span.kind.as_str().to_string(),
+        })
+        .collect()
+}
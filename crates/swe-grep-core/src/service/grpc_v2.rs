@@ -0,0 +1,536 @@
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use futures::Stream;
+use tonic::async_trait;
+use tonic::{Request, Response, Status};
+
+use crate::cli::{CaseMode, LinkFormat, Priority, SortOrder};
+use crate::fields::FieldSelection;
+use crate::highlight::HighlightSpan;
+use crate::search::{NextAction, SearchSummary, StageStats, StartupStats, TopHit};
+use crate::stats::RepoStats;
+use crate::suggest::Suggestions;
+
+use super::API_VERSIONS;
+use super::grpc::{bearer_token, status_from_error};
+use super::proto_v2::{
+    self,
+    swe_grep_service_server::{SweGrepService, SweGrepServiceServer},
+};
+use super::server::{BatchResults, SearchExecutor, SearchInput};
+
+type SearchEventStream = Pin<Box<dyn Stream<Item = Result<proto_v2::SearchEvent, Status>> + Send>>;
+
+/// The v2 gRPC service, as named in health statuses.
+pub(super) type Server = SweGrepServiceServer<SweGrepGrpcV2>;
+
+/// Build the v2 service; it shares the executor (and therefore all defaults) with v1.
+pub(super) fn service(executor: Arc<SearchExecutor>) -> Server {
+    SweGrepServiceServer::new(SweGrepGrpcV2 { executor })
+        .max_decoding_message_size(crate::validate::MAX_REQUEST_BYTES)
+}
+
+#[derive(Clone)]
+pub(super) struct SweGrepGrpcV2 {
+    executor: Arc<SearchExecutor>,
+}
+
+impl SweGrepGrpcV2 {
+    /// Run the search and clear every field outside the requested fieldset.
+    async fn run(
+        &self,
+        request: proto_v2::SearchRequest,
+        token: Option<String>,
+    ) -> Result<SearchSummary, Status> {
+        let fields = FieldSelection::parse(&request.fields)
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+        let mut input = map_request(request).map_err(Status::invalid_argument)?;
+        input.token = token;
+        let mut summary = self
+            .executor
+            .execute(input)
+            .await
+            .map_err(status_from_error)?;
+        fields.prune(&mut summary);
+        Ok(summary)
+    }
+}
+
+#[async_trait]
+impl SweGrepService for SweGrepGrpcV2 {
+    async fn search(
+        &self,
+        request: Request<proto_v2::SearchRequest>,
+    ) -> Result<Response<proto_v2::SearchResponse>, Status> {
+        let token = bearer_token(&request);
+        let inner = request.into_inner();
+        let offset = parse_page_token(&inner.page_token).map_err(Status::invalid_argument)?;
+        let page_size = positive("page_size", inner.page_size)
+            .map_err(Status::invalid_argument)?
+            .map(|size| size as usize);
+
+        let mut summary = self.run(inner, token).await?;
+        let total_hits = summary.top_hits.len();
+        let end = page_size
+            .map(|size| offset.saturating_add(size).min(total_hits))
+            .unwrap_or(total_hits);
+        summary.top_hits = if offset < total_hits {
+            summary.top_hits.drain(offset..end).collect()
+        } else {
+            Vec::new()
+        };
+        let next_page_token = if end < total_hits {
+            end.to_string()
+        } else {
+            String::new()
+        };
+
+        Ok(Response::new(proto_v2::SearchResponse {
+            summary: Some(convert_summary(summary)),
+            next_page_token,
+            total_hits: total_hits as u32,
+        }))
+    }
+
+    type SearchStreamStream = SearchEventStream;
+
+    async fn search_stream(
+        &self,
+        request: Request<proto_v2::SearchRequest>,
+    ) -> Result<Response<Self::SearchStreamStream>, Status> {
+        let token = bearer_token(&request);
+        let mut summary = self.run(request.into_inner(), token).await?;
+        let hits = std::mem::take(&mut summary.top_hits);
+
+        let events: Vec<Result<proto_v2::SearchEvent, Status>> = hits
+            .into_iter()
+            .map(|hit| proto_v2::search_event::Event::Hit(convert_hit(hit)))
+            .chain(std::iter::once(proto_v2::search_event::Event::Summary(
+                convert_summary(summary),
+            )))
+            .map(|event| proto_v2::SearchEvent { event: Some(event) })
+            .map(Ok)
+            .collect();
+
+        Ok(Response::new(Box::pin(futures::stream::iter(events))))
+    }
+
+    async fn read_file(
+        &self,
+        request: Request<proto_v2::ReadFileRequest>,
+    ) -> Result<Response<proto_v2::ReadFileResponse>, Status> {
+        let token = bearer_token(&request);
+        let inner = request.into_inner();
+        if inner.path.trim().is_empty() {
+            return Err(Status::invalid_argument("path is required"));
+        }
+        let range = self
+            .executor
+            .read_file(
+                &inner.path,
+                inner.start.map(|start| start as usize),
+                inner.end.map(|end| end as usize),
+                inner.tenant.as_deref(),
+                token.as_deref(),
+            )
+            .await
+            .map_err(status_from_error)?;
+        Ok(Response::new(proto_v2::ReadFileResponse {
+            path: range.path,
+            start: clamp_u32(range.start),
+            end: clamp_u32(range.end),
+            content: range.content,
+            truncated: range.truncated,
+            secrets_redacted: clamp_u32(range.secrets_redacted),
+        }))
+    }
+
+    async fn batch_search(
+        &self,
+        request: Request<proto_v2::BatchSearchRequest>,
+    ) -> Result<Response<proto_v2::BatchSearchResponse>, Status> {
+        let token = bearer_token(&request);
+        let mut results = Vec::new();
+        let mut selections = Vec::new();
+        let mut inputs = Vec::new();
+        for search in request.into_inner().searches {
+            let parsed = FieldSelection::parse(&search.fields)
+                .map_err(|err| err.to_string())
+                .and_then(|fields| Ok((fields, map_request(search)?)));
+            match parsed {
+                Ok((fields, mut input)) => {
+                    input.token = token.clone();
+                    results.push(None);
+                    selections.push(fields);
+                    inputs.push(input);
+                }
+                Err(message) => results.push(Some(batch_error(&Status::invalid_argument(message)))),
+            }
+        }
+        if results.is_empty() {
+            return Err(Status::invalid_argument("searches is required"));
+        }
+
+        let batch = if inputs.is_empty() {
+            BatchResults::default()
+        } else {
+            self.executor
+                .execute_batch(inputs)
+                .await
+                .map_err(status_from_error)?
+        };
+        let mut search_ms = 0;
+        let mut outcomes = batch.results.into_iter().zip(selections);
+        let results = results
+            .into_iter()
+            .map(|result| {
+                let outcome = result.unwrap_or_else(|| {
+                    let (outcome, fields) =
+                        outcomes.next().expect("one outcome per executed search");
+                    match outcome {
+                        Ok(mut summary) => {
+                            search_ms += summary.stage_stats.cycle_latency_ms;
+                            fields.prune(&mut summary);
+                            proto_v2::batch_search_result::Outcome::Summary(convert_summary(
+                                summary,
+                            ))
+                        }
+                        Err(err) => batch_error(&status_from_error(err)),
+                    }
+                });
+                proto_v2::BatchSearchResult {
+                    outcome: Some(outcome),
+                }
+            })
+            .collect();
+        Ok(Response::new(proto_v2::BatchSearchResponse {
+            results,
+            elapsed_ms: batch.elapsed_ms,
+            search_ms,
+            shared_discovery: clamp_u32(batch.shared_discovery),
+        }))
+    }
+
+    async fn health(
+        &self,
+        _request: Request<proto_v2::HealthCheckRequest>,
+    ) -> Result<Response<proto_v2::HealthCheckResponse>, Status> {
+        Ok(Response::new(proto_v2::HealthCheckResponse {
+            status: "ok".to_string(),
+            api_versions: API_VERSIONS.iter().map(ToString::to_string).collect(),
+        }))
+    }
+}
+
+fn batch_error(status: &Status) -> proto_v2::batch_search_result::Outcome {
+    proto_v2::batch_search_result::Outcome::Error(proto_v2::BatchSearchError {
+        code: status.code() as i32,
+        message: status.message().to_string(),
+    })
+}
+
+/// Translate a v2 request; errors name the offending field and map to `INVALID_ARGUMENT`.
+fn map_request(proto: proto_v2::SearchRequest) -> Result<SearchInput, String> {
+    let timeout_secs = positive("timeout_secs", proto.timeout_secs)?;
+    let max_matches = positive("max_matches", proto.max_matches)?;
+    let max_scope_files = positive("max_scope_files", proto.max_scope_files)?;
+    let latency_target_ms = positive("latency_target_ms", proto.latency_target_ms)?;
+    let concurrency = positive("concurrency", proto.concurrency)?;
+
+    let mut tool_flags = std::collections::HashMap::new();
+    if let Some(value) = proto.use_fd {
+        tool_flags.insert("fd".to_string(), value);
+    }
+    if let Some(value) = proto.use_ast_grep {
+        tool_flags.insert("ast-grep".to_string(), value);
+    }
+
+    Ok(SearchInput {
+        symbol: proto.symbol,
+        language: proto.language,
+        rg_types: proto.rg_types,
+        rg_type_adds: proto.rg_type_adds,
+        config: proto.config,
+        root: proto.root.map(PathBuf::from),
+        profile: proto.profile,
+        owner: proto.owner,
+        case: proto.case.as_deref().map(CaseMode::parse).transpose()?,
+        word: proto.word,
+        timeout_secs: timeout_secs.map(u64::from),
+        max_matches: max_matches.map(|value| value as usize),
+        max_scope_files: max_scope_files.map(|value| value as usize),
+        latency_target_ms: latency_target_ms.map(u64::from),
+        concurrency: concurrency.map(|value| value as usize),
+        enable_index: proto.enable_index,
+        enable_rga: proto.enable_rga,
+        index_dir: proto.index_dir.map(PathBuf::from),
+        cache_dir: proto.cache_dir.map(PathBuf::from),
+        log_dir: proto.log_dir.map(PathBuf::from),
+        context_before: proto.context_before.map(|value| value as usize),
+        context_after: proto.context_after.map(|value| value as usize),
+        body: proto.body,
+        tool_flags,
+        explain: proto.explain,
+        highlight: proto.highlight,
+        link_format: proto
+            .link_format
+            .as_deref()
+            .map(LinkFormat::parse)
+            .transpose()?,
+        permalinks: proto.permalinks,
+        sort: proto.sort.as_deref().map(SortOrder::parse).transpose()?,
+        deterministic: proto.deterministic,
+        priority: proto.priority.as_deref().map(Priority::parse).transpose()?,
+        tenant: proto.tenant,
+        token: None,
+    })
+}
+
+/// Reject an explicit zero for options where it can never be meaningful.
+fn positive(field: &str, value: Option<u32>) -> Result<Option<u32>, String> {
+    match value {
+        Some(0) => Err(format!("{field} must be greater than zero when set")),
+        other => Ok(other),
+    }
+}
+
+fn parse_page_token(token: &str) -> Result<usize, String> {
+    if token.is_empty() {
+        return Ok(0);
+    }
+    token
+        .parse()
+        .map_err(|_| format!("invalid page_token `{token}`"))
+}
+
+fn clamp_u32(value: usize) -> u32 {
+    value.min(u32::MAX as usize) as u32
+}
+
+fn convert_summary(summary: SearchSummary) -> proto_v2::SearchSummary {
+    proto_v2::SearchSummary {
+        cycle: summary.cycle,
+        symbol: summary.symbol,
+        revision: summary.revision,
+        search_id: summary.search_id,
+        queries: summary.queries,
+        aliases: summary
+            .aliases
+            .into_iter()
+            .map(|alias| proto_v2::SymbolAlias {
+                alias: alias.alias,
+                target: alias.target,
+                kind: alias.kind.as_str().to_string(),
+                path: alias.path,
+                line: clamp_u32(alias.line),
+            })
+            .collect(),
+        top_hits: summary.top_hits.into_iter().map(convert_hit).collect(),
+        deduped: clamp_u32(summary.deduped),
+        next_actions: summary
+            .next_actions
+            .into_iter()
+            .map(convert_next_action)
+            .collect(),
+        suggestions: summary.suggestions.map(convert_suggestions),
+        fd_candidates: summary
+            .fd_candidates
+            .into_iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect(),
+        ast_hits: summary
+            .ast_hits
+            .into_iter()
+            .map(|(path, line)| proto_v2::AstHit {
+                path: path.to_string_lossy().to_string(),
+                line: clamp_u32(line),
+            })
+            .collect(),
+        stage_stats: Some(convert_stage_stats(summary.stage_stats)),
+        reward: summary.reward,
+        startup_stats: summary.startup_stats.map(convert_startup_stats),
+        warnings: summary
+            .warnings
+            .iter()
+            .map(|warning| warning.message.clone())
+            .collect(),
+        warning_details: summary
+            .warnings
+            .into_iter()
+            .map(|warning| proto_v2::Warning {
+                kind: warning.kind.as_str().to_string(),
+                tool: warning.tool,
+                message: warning.message,
+            })
+            .collect(),
+        budget_exceeded_stages: summary.budget_exceeded_stages,
+        skipped: summary.skipped,
+        repo_stats: summary.repo_stats.map(convert_repo_stats),
+    }
+}
+
+fn convert_hit(hit: TopHit) -> proto_v2::TopHit {
+    proto_v2::TopHit {
+        path: hit.path,
+        line: clamp_u32(hit.line),
+        root: hit.root,
+        archive_path: hit.archive_path,
+        inner_path: hit.inner_path,
+        cell: hit.cell.map(clamp_u32),
+        owners: hit.owners,
+        score: hit.score,
+        origin: hit.origin,
+        origin_label: hit.origin_label,
+        snippet: hit.snippet,
+        raw_snippet: hit.raw_snippet,
+        snippet_length: hit.snippet_length.map(clamp_u32),
+        raw_snippet_truncated: hit.raw_snippet_truncated,
+        line_window: hit.line_window,
+        dirty: hit.dirty,
+        head_line: hit.head_line,
+        worktree_line: hit.worktree_line,
+        link: hit.link,
+        permalink: hit.permalink,
+        barrel: hit.barrel,
+        targets: hit.targets,
+        doc_comment: hit.doc_comment,
+        snippet_highlights: convert_highlights(hit.snippet_highlights),
+        expanded_snippet_highlights: convert_highlights(hit.expanded_snippet_highlights),
+        expanded_snippet: hit.expanded_snippet,
+        context_start: hit.context_start.map(clamp_u32),
+        context_end: hit.context_end.map(clamp_u32),
+        auto_expanded_context: hit.auto_expanded_context,
+        body: hit.body,
+        body_retrieved: hit.body_retrieved,
+        hints: hit
+            .hints
+            .into_iter()
+            .map(|hint| proto_v2::ContextHint {
+                kind: hint.kind,
+                label: hint.label,
+                line: clamp_u32(hint.line),
+            })
+            .collect(),
+    }
+}
+
+fn convert_next_action(action: NextAction) -> proto_v2::NextAction {
+    use proto_v2::next_action::{self, Action};
+
+    let action = match action {
+        NextAction::OpenFile { path, line } => Action::OpenFile(next_action::OpenFile {
+            path,
+            line: clamp_u32(line),
+        }),
+        NextAction::ExpandContext { hit } => Action::ExpandContext(next_action::ExpandContext {
+            hit: clamp_u32(hit),
+        }),
+        NextAction::EscalateGlobal => Action::EscalateGlobal(next_action::EscalateGlobal {}),
+        NextAction::TryLanguage { lang } => Action::TryLanguage(next_action::TryLanguage { lang }),
+        NextAction::RefineSymbol { suggestion } => {
+            Action::RefineSymbol(next_action::RefineSymbol { suggestion })
+        }
+    };
+    proto_v2::NextAction {
+        action: Some(action),
+    }
+}
+
+fn convert_suggestions(suggestions: Suggestions) -> proto_v2::Suggestions {
+    proto_v2::Suggestions {
+        symbols: suggestions
+            .symbols
+            .into_iter()
+            .map(|suggestion| proto_v2::SymbolSuggestion {
+                symbol: suggestion.symbol,
+                distance: clamp_u32(suggestion.distance),
+                path: suggestion.path,
+            })
+            .collect(),
+        case_variants: suggestions.case_variants,
+        languages: suggestions
+            .languages
+            .into_iter()
+            .map(|hint| proto_v2::LanguageHint {
+                language: hint.language,
+                files: clamp_u32(hint.files),
+            })
+            .collect(),
+    }
+}
+
+fn convert_stage_stats(stats: StageStats) -> proto_v2::StageStats {
+    proto_v2::StageStats {
+        discover_candidates: clamp_u32(stats.discover_candidates),
+        discover_ms: stats.discover_ms,
+        probe_hits: clamp_u32(stats.probe_hits),
+        probe_ms: stats.probe_ms,
+        escalate_hits: clamp_u32(stats.escalate_hits),
+        escalate_ms: stats.escalate_ms,
+        index_candidates: clamp_u32(stats.index_candidates),
+        index_probe_hits: clamp_u32(stats.index_probe_hits),
+        index_ms: stats.index_ms,
+        rga_hits: clamp_u32(stats.rga_hits),
+        rga_ms: stats.rga_ms,
+        ast_matches: clamp_u32(stats.ast_matches),
+        disambiguate_ms: stats.disambiguate_ms,
+        verify_ms: stats.verify_ms,
+        cycle_latency_ms: stats.cycle_latency_ms,
+        precision: stats.precision,
+        density: stats.density,
+        clustering: stats.clustering,
+        reward: stats.reward,
+        symbol_store_candidates: clamp_u32(stats.symbol_store_candidates),
+        fd_bonus: stats.fd_bonus,
+        reward_components: Some(proto_v2::RewardComponents {
+            precision: stats.reward_components.precision,
+            density: stats.reward_components.density,
+            clustering: stats.reward_components.clustering,
+            fd_bonus: stats.reward_components.fd_bonus,
+        }),
+    }
+}
+
+fn convert_startup_stats(stats: StartupStats) -> proto_v2::StartupStats {
+    proto_v2::StartupStats {
+        init_ms: stats.init_ms,
+        fd_ms: stats.fd_ms,
+        rg_ms: stats.rg_ms,
+        ast_ms: stats.ast_ms,
+        rga_ms: stats.rga_ms,
+        cache_ms: stats.cache_ms,
+        state_ms: stats.state_ms,
+        index_ms: stats.index_ms,
+    }
+}
+
+fn convert_repo_stats(stats: RepoStats) -> proto_v2::RepoStats {
+    proto_v2::RepoStats {
+        files: stats.files as u64,
+        lines: stats.lines as u64,
+        bytes: stats.bytes,
+        languages: stats
+            .languages
+            .into_iter()
+            .map(|language| proto_v2::LanguageStats {
+                language: language.language,
+                files: language.files as u64,
+                lines: language.lines as u64,
+            })
+            .collect(),
+        collected_at_ms: stats.collected_at_ms,
+    }
+}
+
+fn convert_highlights(spans: Vec<HighlightSpan>) -> Vec<proto_v2::HighlightSpan> {
+    spans
+        .into_iter()
+        .map(|span| proto_v2::HighlightSpan {
+            start: clamp_u32(span.start),
+            end: clamp_u32(span.end),
+            kind: span.kind.as_str().to_string(),
+        })
+        .collect()
+}
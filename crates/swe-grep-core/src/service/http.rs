@@ -5,30 +5,70 @@ use std::sync::Arc;
 
 use anyhow::{Context, Result};
 use axum::body::Body;
-use axum::extract::State;
-use axum::http::header::CONTENT_TYPE;
-use axum::http::{Response, StatusCode};
+use axum::extract::{DefaultBodyLimit, Path, Query, State};
+use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
+use axum::http::{HeaderMap, Response, StatusCode};
+use axum::response::IntoResponse;
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use serde::{Deserialize, Serialize};
+use tower_http::compression::CompressionLayer;
+use tower_http::decompression::RequestDecompressionLayer;
 
+use crate::cli::{CaseMode, HttpCompression, LinkFormat, Priority, SortOrder};
+use crate::definition::{DefinitionResponse, NoIdentifier};
+use crate::fields::FieldSelection;
+use crate::results::ResultError;
 use crate::search::SearchSummary;
+use crate::source::{FileRange, RangeError};
+use crate::symbols::SymbolsResponse;
+use crate::validate::{self, FieldError, ValidationError};
 
-use super::server::{SearchExecutor, SearchInput};
+use super::API_VERSIONS;
+use super::server::{BatchResults, RootNotAllowed, SearchExecutor, SearchInput};
+use super::tenant::TenantError;
 
 type SharedExecutor = Arc<SearchExecutor>;
 
 #[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct HttpSearchRequest {
     pub symbol: String,
     #[serde(default)]
     pub language: Option<String>,
+    /// ripgrep file types to search (`rg --type-list`); see `--rg-type`.
+    #[serde(default)]
+    pub rg_types: Vec<String>,
+    /// ripgrep type definitions such as `proto:*.proto`; see `--rg-type-add`.
+    #[serde(default)]
+    pub rg_type_adds: Vec<String>,
+    /// Search Terraform/HCL and YAML files first; see `--config`.
+    #[serde(default)]
+    pub config: Option<bool>,
     #[serde(default)]
     pub root: Option<String>,
+    /// Option bundle (`fast`, `thorough`, `docs`, `ci`, or one from `.swe-grep.toml`).
+    #[serde(default)]
+    pub profile: Option<String>,
+    /// Keep only hits owned by this `CODEOWNERS` owner (e.g. `@platform-team`).
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// `sensitive`, `insensitive`, or `smart` (the default).
+    #[serde(default)]
+    pub case: Option<CaseMode>,
+    /// Whole-word matching; unset leaves it on for literal identifiers.
+    #[serde(default)]
+    pub word: Option<bool>,
     #[serde(default)]
     pub timeout_secs: Option<u64>,
     #[serde(default)]
     pub max_matches: Option<usize>,
+    /// Cap on the files handed to the scoped probe; see `--max-scope-files`.
+    #[serde(default)]
+    pub max_scope_files: Option<usize>,
+    /// Total latency target split into per-stage budgets; see `--latency-target-ms`.
+    #[serde(default)]
+    pub latency_target_ms: Option<u64>,
     #[serde(default)]
     pub concurrency: Option<usize>,
     #[serde(default)]
@@ -57,6 +97,36 @@ pub struct HttpSearchRequest {
     pub context_after: Option<usize>,
     #[serde(default)]
     pub body: Option<bool>,
+    /// Sparse fieldset (e.g. `["path", "line", "snippet"]`); empty returns the full summary.
+    #[serde(default)]
+    pub fields: Vec<String>,
+    /// Add `repo_stats` (file, line, and language counts) to the summary.
+    #[serde(default)]
+    pub explain: Option<bool>,
+    /// Add `snippet_highlights` and `expanded_snippet_highlights` token spans to each hit.
+    #[serde(default)]
+    pub highlight: Option<bool>,
+    /// Add a `link` to each hit: `vscode`, `jetbrains`, or `github`.
+    #[serde(default)]
+    pub link_format: Option<LinkFormat>,
+    /// Add a GitHub or GitLab `permalink`, pinned to the searched commit, to each hit.
+    #[serde(default)]
+    pub permalinks: Option<bool>,
+    /// Order of `top_hits`: `score` (default), `path`, `line`, or `recency`.
+    #[serde(default)]
+    pub sort: Option<SortOrder>,
+    /// Zero timings, omit `search_id`, and sort keys and candidate lists, so identical
+    /// searches return byte-identical bodies.
+    #[serde(default)]
+    pub deterministic: Option<bool>,
+    /// `interactive` (default) or `batch`; batch searches wait behind interactive ones while
+    /// the server is busy.
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    /// Tenant to search as when the server runs with `--tenants`; its token goes in the
+    /// `Authorization: Bearer` header.
+    #[serde(default)]
+    pub tenant: Option<String>,
 }
 
 impl From<HttpSearchRequest> for SearchInput {
@@ -78,9 +148,18 @@ impl From<HttpSearchRequest> for SearchInput {
         SearchInput {
             symbol: req.symbol,
             language: req.language,
+            rg_types: req.rg_types,
+            rg_type_adds: req.rg_type_adds,
+            config: req.config,
             root: req.root.map(PathBuf::from),
+            profile: req.profile,
+            owner: req.owner.filter(|owner| !owner.trim().is_empty()),
+            case: req.case,
+            word: req.word,
             timeout_secs: req.timeout_secs,
             max_matches: req.max_matches,
+            max_scope_files: req.max_scope_files.filter(|files| *files > 0),
+            latency_target_ms: req.latency_target_ms.filter(|ms| *ms > 0),
             concurrency: req.concurrency,
             enable_index: req.enable_index,
             enable_rga: req.enable_rga,
@@ -91,79 +170,631 @@ impl From<HttpSearchRequest> for SearchInput {
             context_before: req.context_before,
             context_after: req.context_after,
             body: req.body,
+            explain: req.explain,
+            highlight: req.highlight,
+            link_format: req.link_format,
+            permalinks: req.permalinks,
+            sort: req.sort,
+            deterministic: req.deterministic,
+            priority: req.priority,
+            tenant: req.tenant.filter(|tenant| !tenant.trim().is_empty()),
+            token: None,
         }
     }
 }
 
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct HttpSymbolsQuery {
+    pub prefix: String,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Tenant whose symbols to complete when the server runs with `--tenants`.
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct HttpResultQuery {
+    /// Tenant whose search to fetch when the server runs with `--tenants`.
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct HttpFileQuery {
+    /// File to read, relative to the served root or absolute within an allowed root.
+    pub path: String,
+    /// First line to return, 1-based (default 1).
+    #[serde(default)]
+    pub start: Option<usize>,
+    /// Last line to return, inclusive (default: the end of the file).
+    #[serde(default)]
+    pub end: Option<usize>,
+    /// Tenant whose root to read from when the server runs with `--tenants`.
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HttpDefinitionRequest {
+    /// File holding the cursor, relative to the served root or absolute within an allowed root.
+    pub path: String,
+    /// Cursor line, 1-based.
+    pub line: usize,
+    /// Cursor column in characters, 1-based; the position just past an identifier selects it.
+    pub column: usize,
+    /// Tenant whose root to search when the server runs with `--tenants`.
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
 #[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct HttpSearchResponse {
     pub summary: SearchSummary,
 }
 
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct HttpBatchSearchRequest {
+    /// Up to 32 searches, each shaped like a `POST /search` body.
+    pub searches: Vec<HttpSearchRequest>,
+}
+
 #[derive(Serialize)]
-struct ErrorResponse {
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub(super) struct HttpBatchSearchResponse {
+    /// One result per search, in request order.
+    results: Vec<HttpBatchResult>,
+    /// Wall-clock time of the whole batch.
+    elapsed_ms: u64,
+    /// Sum of the searches' `cycle_latency_ms`; above `elapsed_ms` when they overlapped.
+    search_ms: u64,
+    /// Searches whose fd file-name discovery came from one walk shared with the batch.
+    shared_discovery: usize,
+}
+
+/// One search of a batch: the status `POST /search` would have answered, with its summary
+/// (trimmed to `fields`) or its error.
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub(super) struct HttpBatchResult {
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "openapi", schema(value_type = Option<SearchSummary>))]
+    summary: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorResponse>,
+}
+
+impl HttpBatchResult {
+    fn failed(status: StatusCode, error: ErrorResponse) -> Self {
+        Self {
+            status: status.as_u16(),
+            summary: None,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub(super) struct ErrorResponse {
     message: String,
+    /// Every rejected field when the request failed validation.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fields: Vec<FieldError>,
+}
+
+impl ErrorResponse {
+    fn new(message: String) -> Self {
+        Self {
+            message,
+            fields: Vec::new(),
+        }
+    }
 }
 
 #[derive(Serialize)]
-struct HealthResponse {
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub(super) struct HealthResponse {
     status: &'static str,
+    /// gRPC API packages served next to this HTTP endpoint.
+    api_versions: &'static [&'static str],
 }
 
-/// Start the HTTP server and run until shutdown.
-pub async fn serve(addr: SocketAddr, executor: SharedExecutor) -> Result<()> {
+/// Build the HTTP API, compressing responses and decoding request bodies per `compression`.
+pub fn router(executor: SharedExecutor, compression: HttpCompression) -> Router {
     let app = Router::new()
         .route("/healthz", get(health))
         .route("/search", post(search))
-        .route("/metrics", get(metrics))
-        .with_state(executor);
+        .route("/search/batch", post(search_batch))
+        .route("/search/result/:id", get(search_result))
+        .route("/definition", post(definition))
+        .route("/symbols", get(symbols))
+        .route("/file", get(read_file))
+        .route("/metrics", get(metrics));
+    #[cfg(feature = "openapi")]
+    let app = app.merge(super::openapi::routes());
+    let app = app
+        .with_state(executor)
+        .layer(DefaultBodyLimit::max(validate::MAX_REQUEST_BYTES));
+
+    if compression == HttpCompression::Off {
+        return app;
+    }
+    app.layer(
+        CompressionLayer::new()
+            .gzip(compression.gzip())
+            .zstd(compression.zstd())
+            .no_br()
+            .no_deflate(),
+    )
+    .layer(
+        RequestDecompressionLayer::new()
+            .gzip(compression.gzip())
+            .zstd(compression.zstd())
+            .no_br()
+            .no_deflate(),
+    )
+}
+
+/// Start the HTTP server and run until shutdown.
+pub async fn serve(
+    addr: SocketAddr,
+    executor: SharedExecutor,
+    compression: HttpCompression,
+) -> Result<()> {
+    let app = router(executor, compression);
 
     let listener = tokio::net::TcpListener::bind(addr)
         .await
         .with_context(|| format!("failed to bind HTTP address {addr}"))?;
 
     axum::serve(listener, app)
-        .with_graceful_shutdown(async {
-            let _ = tokio::signal::ctrl_c().await;
-        })
+        .with_graceful_shutdown(super::shutdown_signal())
         .await
         .with_context(|| format!("failed to run HTTP server on {addr}"))
 }
 
-async fn health() -> Json<HealthResponse> {
-    Json(HealthResponse { status: "ok" })
+/// Serve the HTTP API on an already-bound Unix domain socket until shutdown.
+///
+/// `axum::serve` only accepts TCP listeners, so connections are driven through hyper directly.
+#[cfg(unix)]
+pub async fn serve_unix(
+    listener: tokio::net::UnixListener,
+    executor: SharedExecutor,
+    compression: HttpCompression,
+) -> Result<()> {
+    use hyper_util::rt::TokioIo;
+    use tower::Service;
+
+    let app = router(executor, compression);
+    let shutdown = super::shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => {
+                accepted.context("failed to accept HTTP connection on unix socket")?.0
+            }
+            _ = &mut shutdown => return Ok(()),
+        };
+        let app = app.clone();
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(move |request| app.clone().call(request));
+            if let Err(err) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!(error = %err, "unix socket HTTP connection closed with error");
+            }
+        });
+    }
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/healthz",
+    responses((status = 200, description = "Service is up", body = HealthResponse))
+))]
+pub(super) async fn health() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok",
+        api_versions: API_VERSIONS,
+    })
 }
 
-async fn search(
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/search",
+    request_body = HttpSearchRequest,
+    responses(
+        (status = 200, description = "Search summary, trimmed to `fields` when given", body = HttpSearchResponse),
+        (status = 400, description = "Missing symbol or out-of-bounds fields, listed in `fields`", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid tenant token", body = ErrorResponse),
+        (status = 403, description = "Root or directory outside the allowed roots", body = ErrorResponse),
+        (status = 404, description = "Unknown tenant", body = ErrorResponse),
+        (status = 413, description = "Request body larger than 64 KiB"),
+        (status = 429, description = "Tenant has no free search slots", body = ErrorResponse),
+        (status = 500, description = "Search failed", body = ErrorResponse),
+    )
+))]
+pub(super) async fn search(
     State(executor): State<SharedExecutor>,
+    headers: HeaderMap,
     Json(request): Json<HttpSearchRequest>,
-) -> Result<Json<HttpSearchResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<axum::response::Response, (StatusCode, Json<ErrorResponse>)> {
+    let bad_request =
+        |message: String| (StatusCode::BAD_REQUEST, Json(ErrorResponse::new(message)));
     if request.symbol.trim().is_empty() {
+        return Err(bad_request("symbol is required".to_string()));
+    }
+    let fields =
+        FieldSelection::parse(&request.fields).map_err(|err| bad_request(err.to_string()))?;
+
+    let mut input: SearchInput = request.into();
+    input.token = bearer_token(&headers);
+    let deterministic = input.deterministic.unwrap_or(false);
+
+    match executor.execute(input).await {
+        // Going through `Value` sorts object keys; struct serialization keeps field order.
+        Ok(summary) if fields.is_empty() && !deterministic => {
+            Ok(Json(HttpSearchResponse { summary }).into_response())
+        }
+        Ok(summary) => match fields.to_value(&summary) {
+            Ok(summary) => Ok(Json(serde_json::json!({ "summary": summary })).into_response()),
+            Err(err) => Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse::new(err.to_string())),
+            )),
+        },
+        Err(err) => {
+            let (status, response) = search_error(&err);
+            Err((status, Json(response)))
+        }
+    }
+}
+
+/// Status and body for a failed search.
+fn search_error(err: &anyhow::Error) -> (StatusCode, ErrorResponse) {
+    let msg = err.to_string();
+    if let Some(invalid) = err.downcast_ref::<ValidationError>() {
+        let response = ErrorResponse {
+            message: msg,
+            fields: invalid.fields.clone(),
+        };
+        return (StatusCode::BAD_REQUEST, response);
+    }
+    let status = if let Some(err) = err.downcast_ref::<TenantError>() {
+        tenant_status(err)
+    } else if err.is::<RootNotAllowed>() {
+        StatusCode::FORBIDDEN
+    } else if msg.contains("symbol is required") || msg.contains("unknown profile") {
+        StatusCode::BAD_REQUEST
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+    (status, ErrorResponse::new(msg))
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/search/batch",
+    request_body = HttpBatchSearchRequest,
+    responses(
+        (status = 200, description = "One result per search, in request order, with the batch's timing", body = HttpBatchSearchResponse),
+        (status = 400, description = "No searches, or more than 32", body = ErrorResponse),
+        (status = 413, description = "Request body larger than 64 KiB"),
+    )
+))]
+pub(super) async fn search_batch(
+    State(executor): State<SharedExecutor>,
+    headers: HeaderMap,
+    Json(request): Json<HttpBatchSearchRequest>,
+) -> Result<Json<HttpBatchSearchResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let token = bearer_token(&headers);
+    let mut results: Vec<Option<HttpBatchResult>> = Vec::new();
+    let mut selections = Vec::new();
+    let mut inputs = Vec::new();
+    for search in request.searches {
+        match FieldSelection::parse(&search.fields) {
+            Ok(fields) => {
+                results.push(None);
+                selections.push(fields);
+                let mut input: SearchInput = search.into();
+                input.token = token.clone();
+                inputs.push(input);
+            }
+            Err(err) => results.push(Some(HttpBatchResult::failed(
+                StatusCode::BAD_REQUEST,
+                ErrorResponse::new(err.to_string()),
+            ))),
+        }
+    }
+    if results.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                message: "symbol is required".to_string(),
-            }),
+            Json(ErrorResponse::new("searches is required".to_string())),
         ));
     }
 
-    let input: SearchInput = request.into();
+    let batch = if inputs.is_empty() {
+        BatchResults::default()
+    } else {
+        executor.execute_batch(inputs).await.map_err(|err| {
+            let (status, response) = search_error(&err);
+            (status, Json(response))
+        })?
+    };
+    let mut search_ms = 0;
+    let mut outcomes = batch.results.into_iter().zip(selections);
+    let results = results
+        .into_iter()
+        .map(|result| {
+            if let Some(result) = result {
+                return result;
+            }
+            let (outcome, fields) = outcomes.next().expect("one outcome per executed search");
+            let outcome = outcome.and_then(|summary| {
+                search_ms += summary.stage_stats.cycle_latency_ms;
+                fields.to_value(&summary)
+            });
+            match outcome {
+                Ok(summary) => HttpBatchResult {
+                    status: StatusCode::OK.as_u16(),
+                    summary: Some(summary),
+                    error: None,
+                },
+                Err(err) => {
+                    let (status, response) = search_error(&err);
+                    HttpBatchResult::failed(status, response)
+                }
+            }
+        })
+        .collect();
+    Ok(Json(HttpBatchSearchResponse {
+        results,
+        elapsed_ms: batch.elapsed_ms,
+        search_ms,
+        shared_discovery: batch.shared_discovery,
+    }))
+}
 
-    match executor.execute(input).await {
-        Ok(summary) => Ok(Json(HttpSearchResponse { summary })),
-        Err(err) => {
-            let msg = err.to_string();
-            let status = if msg.contains("symbol is required") {
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/search/result/{id}",
+    params(("id" = String, Path, description = "`search_id` from an earlier search"), HttpResultQuery),
+    responses(
+        (status = 200, description = "Stored summary of the earlier search", body = HttpSearchResponse),
+        (status = 400, description = "Malformed search id", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid tenant token", body = ErrorResponse),
+        (status = 404, description = "Unknown tenant, or no result stored under the id", body = ErrorResponse),
+    )
+))]
+pub(super) async fn search_result(
+    State(executor): State<SharedExecutor>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query): Query<HttpResultQuery>,
+) -> Result<Json<HttpSearchResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let token = bearer_token(&headers);
+    executor
+        .result(&id, query.tenant.as_deref(), token.as_deref())
+        .await
+        .map(|summary| Json(HttpSearchResponse { summary }))
+        .map_err(|err| {
+            let status = if let Some(err) = err.downcast_ref::<TenantError>() {
+                tenant_status(err)
+            } else {
+                match err.downcast_ref::<ResultError>() {
+                    Some(ResultError::InvalidId(_)) => StatusCode::BAD_REQUEST,
+                    Some(ResultError::NotFound(_)) => StatusCode::NOT_FOUND,
+                    Some(ResultError::NewerVersion { .. }) => StatusCode::CONFLICT,
+                    None => StatusCode::INTERNAL_SERVER_ERROR,
+                }
+            };
+            (status, Json(ErrorResponse::new(err.to_string())))
+        })
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/file",
+    params(HttpFileQuery),
+    responses(
+        (status = 200, description = "The requested lines, cut short at 512 KiB (`truncated`)", body = FileRange),
+        (status = 400, description = "Missing path, invalid line range, or not a regular file", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid tenant token", body = ErrorResponse),
+        (status = 403, description = "Path outside the allowed roots", body = ErrorResponse),
+        (status = 404, description = "Unknown tenant, or no such file", body = ErrorResponse),
+        (status = 415, description = "Binary file", body = ErrorResponse),
+        (status = 416, description = "Range starts past the end of the file", body = ErrorResponse),
+    )
+))]
+pub(super) async fn read_file(
+    State(executor): State<SharedExecutor>,
+    headers: HeaderMap,
+    Query(query): Query<HttpFileQuery>,
+) -> Result<Json<FileRange>, (StatusCode, Json<ErrorResponse>)> {
+    if query.path.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("path is required".to_string())),
+        ));
+    }
+    let token = bearer_token(&headers);
+    executor
+        .read_file(
+            &query.path,
+            query.start,
+            query.end,
+            query.tenant.as_deref(),
+            token.as_deref(),
+        )
+        .await
+        .map(Json)
+        .map_err(|err| {
+            let status = if let Some(err) = err.downcast_ref::<TenantError>() {
+                tenant_status(err)
+            } else if err.is::<RootNotAllowed>() {
+                StatusCode::FORBIDDEN
+            } else {
+                match err.downcast_ref::<RangeError>() {
+                    Some(RangeError::InvalidRange { .. } | RangeError::NotAFile(_)) => {
+                        StatusCode::BAD_REQUEST
+                    }
+                    Some(RangeError::NotFound(_)) => StatusCode::NOT_FOUND,
+                    Some(RangeError::Binary(_)) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    Some(RangeError::PastEnd { .. }) => StatusCode::RANGE_NOT_SATISFIABLE,
+                    None => StatusCode::INTERNAL_SERVER_ERROR,
+                }
+            };
+            (status, Json(ErrorResponse::new(err.to_string())))
+        })
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post,
+    path = "/definition",
+    request_body = HttpDefinitionRequest,
+    responses(
+        (status = 200, description = "The identifier at the position, its definitions, and its other hits", body = DefinitionResponse),
+        (status = 400, description = "Missing path, invalid position, or no identifier there", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid tenant token", body = ErrorResponse),
+        (status = 403, description = "Path outside the allowed roots", body = ErrorResponse),
+        (status = 404, description = "Unknown tenant, or no such file", body = ErrorResponse),
+        (status = 415, description = "Binary file", body = ErrorResponse),
+        (status = 429, description = "Tenant has no free search slots", body = ErrorResponse),
+        (status = 500, description = "Search failed", body = ErrorResponse),
+    )
+))]
+pub(super) async fn definition(
+    State(executor): State<SharedExecutor>,
+    headers: HeaderMap,
+    Json(request): Json<HttpDefinitionRequest>,
+) -> Result<Json<DefinitionResponse>, (StatusCode, Json<ErrorResponse>)> {
+    if request.path.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse::new("path is required".to_string())),
+        ));
+    }
+    let token = bearer_token(&headers);
+    executor
+        .definition(
+            &request.path,
+            request.line,
+            request.column,
+            request.tenant.as_deref(),
+            token.as_deref(),
+        )
+        .await
+        .map(Json)
+        .map_err(|err| {
+            let status = if let Some(err) = err.downcast_ref::<TenantError>() {
+                tenant_status(err)
+            } else if err.is::<RootNotAllowed>() {
+                StatusCode::FORBIDDEN
+            } else if err.is::<NoIdentifier>() {
                 StatusCode::BAD_REQUEST
             } else {
-                StatusCode::INTERNAL_SERVER_ERROR
+                match err.downcast_ref::<RangeError>() {
+                    Some(
+                        RangeError::InvalidRange { .. }
+                        | RangeError::NotAFile(_)
+                        | RangeError::PastEnd { .. },
+                    ) => StatusCode::BAD_REQUEST,
+                    Some(RangeError::NotFound(_)) => StatusCode::NOT_FOUND,
+                    Some(RangeError::Binary(_)) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    None => StatusCode::INTERNAL_SERVER_ERROR,
+                }
             };
-            Err((status, Json(ErrorResponse { message: msg })))
-        }
+            (status, Json(ErrorResponse::new(err.to_string())))
+        })
+}
+
+/// Token from an `Authorization: Bearer <token>` header.
+fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let token = value.strip_prefix("Bearer ")?.trim();
+    (!token.is_empty()).then(|| token.to_string())
+}
+
+fn tenant_status(err: &TenantError) -> StatusCode {
+    match err {
+        TenantError::Required | TenantError::Override { .. } => StatusCode::BAD_REQUEST,
+        TenantError::Unknown(_) => StatusCode::NOT_FOUND,
+        TenantError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+        TenantError::QuotaExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
     }
 }
 
-async fn metrics() -> Result<Response<Body>, StatusCode> {
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/symbols",
+    params(HttpSymbolsQuery),
+    responses(
+        (status = 200, description = "Identifiers starting with the prefix", body = SymbolsResponse),
+        (status = 400, description = "Empty prefix", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid tenant token", body = ErrorResponse),
+        (status = 404, description = "Unknown tenant", body = ErrorResponse),
+        (status = 501, description = "Built without the `indexing` feature", body = ErrorResponse),
+    )
+))]
+pub(super) async fn symbols(
+    State(executor): State<SharedExecutor>,
+    headers: HeaderMap,
+    Query(query): Query<HttpSymbolsQuery>,
+) -> Result<Json<SymbolsResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let status = if query.prefix.trim().is_empty() {
+        StatusCode::BAD_REQUEST
+    } else if !cfg!(feature = "indexing") {
+        StatusCode::NOT_IMPLEMENTED
+    } else {
+        StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    let token = bearer_token(&headers);
+    executor
+        .symbols(
+            &query.prefix,
+            query.limit,
+            query.tenant.as_deref(),
+            token.as_deref(),
+        )
+        .await
+        .map(Json)
+        .map_err(|err| {
+            let status = err
+                .downcast_ref::<TenantError>()
+                .map_or(status, tenant_status);
+            (status, Json(ErrorResponse::new(err.to_string())))
+        })
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get,
+    path = "/metrics",
+    responses((status = 200, description = "Prometheus text exposition", content_type = "text/plain"))
+))]
+pub(super) async fn metrics() -> Result<Response<Body>, StatusCode> {
     match crate::telemetry::export_prometheus() {
         Ok(body) => Response::builder()
             .status(StatusCode::OK)
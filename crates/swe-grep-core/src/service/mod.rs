@@ -3,14 +3,33 @@ use anyhow::Result;
 use crate::cli::ServeArgs;
 use crate::telemetry;
 
+pub mod baseline;
 pub mod grpc;
+mod grpc_v2;
 pub mod http;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod queue;
 pub mod server;
+pub mod stdio;
+pub mod tenant;
 
 pub mod proto {
     tonic::include_proto!("swegrep.v1");
 }
 
+/// Field-presence variant of the API with pagination and streaming; served alongside v1.
+#[allow(clippy::large_enum_variant)]
+pub mod proto_v2 {
+    tonic::include_proto!("swegrep.v2");
+}
+
+/// Encoded descriptors of both API versions, served by gRPC reflection.
+pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("swegrep_descriptor");
+
+/// API packages answered by the gRPC server, reported by every health endpoint.
+pub const API_VERSIONS: &[&str] = &["swegrep.v1", "swegrep.v2"];
+
 /// Launch the combined HTTP and gRPC services using the provided CLI arguments.
 pub async fn serve(args: ServeArgs) -> Result<()> {
     telemetry::init()?;
@@ -18,3 +37,49 @@ pub async fn serve(args: ServeArgs) -> Result<()> {
     let server = server::SweGrepServer::new(config);
     server.run().await
 }
+
+/// Resolve once the process is asked to stop.
+///
+/// Unix listens for Ctrl-C and SIGTERM (the signal service managers send); Windows listens for
+/// Ctrl-C plus the console close/shutdown events delivered to services and closing terminals.
+pub(crate) async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to install SIGTERM handler");
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use tokio::signal::windows::{ctrl_close, ctrl_shutdown};
+        match (ctrl_close(), ctrl_shutdown()) {
+            (Ok(mut close), Ok(mut shutdown)) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = close.recv() => {}
+                    _ = shutdown.recv() => {}
+                }
+            }
+            _ => {
+                tracing::warn!("failed to install console close handlers");
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
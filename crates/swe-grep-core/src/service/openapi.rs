@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use axum::Router;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use super::http;
+use super::server::SearchExecutor;
+
+/// Path of the generated OpenAPI document.
+pub const OPENAPI_PATH: &str = "/openapi.json";
+/// Mount point of the bundled Swagger UI.
+pub const DOCS_PATH: &str = "/docs";
+
+/// OpenAPI description of the HTTP API, generated from the handler and payload types.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "swe-grep HTTP API"),
+    paths(
+        http::health,
+        http::search,
+        http::search_batch,
+        http::search_result,
+        http::definition,
+        http::symbols,
+        http::read_file,
+        http::metrics
+    )
+)]
+pub struct ApiDoc;
+
+/// `/openapi.json` plus the Swagger UI at `/docs`.
+pub(super) fn routes() -> Router<Arc<SearchExecutor>> {
+    SwaggerUi::new(DOCS_PATH)
+        .url(OPENAPI_PATH, ApiDoc::openapi())
+        .into()
+}
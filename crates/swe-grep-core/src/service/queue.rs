@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::oneshot;
+
+use crate::cli::Priority;
+use crate::telemetry;
+
+/// Slots for the searches a server runs at once, handed out interactive-first.
+///
+/// A request takes a free slot immediately only when nobody is queued; otherwise it waits in
+/// its class's queue. A finishing search passes its slot to the oldest interactive waiter, and
+/// to a batch waiter only when no interactive one is left, so agent queries overtake queued
+/// background searches. Searches already running are never interrupted.
+pub struct SearchQueue {
+    slots: usize,
+    state: Mutex<QueueState>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    running: usize,
+    next_id: u64,
+    interactive: VecDeque<Waiter>,
+    batch: VecDeque<Waiter>,
+}
+
+struct Waiter {
+    id: u64,
+    grant: oneshot::Sender<()>,
+}
+
+impl QueueState {
+    fn waiters(&mut self, priority: Priority) -> &mut VecDeque<Waiter> {
+        match priority {
+            Priority::Interactive => &mut self.interactive,
+            Priority::Batch => &mut self.batch,
+        }
+    }
+}
+
+/// A held search slot, passed on to the next waiter when dropped.
+pub struct QueuePermit {
+    queue: Arc<SearchQueue>,
+}
+
+impl Drop for QueuePermit {
+    fn drop(&mut self) {
+        self.queue.release();
+    }
+}
+
+/// Removes a waiter whose request was cancelled, returning a slot it was granted meanwhile.
+struct Pending {
+    queue: Arc<SearchQueue>,
+    priority: Priority,
+    id: u64,
+    granted: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        let Some(mut granted) = self.granted.take() else {
+            return;
+        };
+        let mut state = self.queue.state.lock().unwrap();
+        let waiters = state.waiters(self.priority);
+        if let Some(position) = waiters.iter().position(|waiter| waiter.id == self.id) {
+            waiters.remove(position);
+            telemetry::record_queue_depth(self.priority.as_str(), -1);
+        } else if granted.try_recv().is_ok() {
+            drop(state);
+            self.queue.release();
+        }
+    }
+}
+
+impl SearchQueue {
+    pub fn new(slots: usize) -> Arc<Self> {
+        Arc::new(Self {
+            slots: slots.max(1),
+            state: Mutex::new(QueueState::default()),
+        })
+    }
+
+    /// Wait for a slot, behind every queued request of `priority` and, for batch requests,
+    /// behind every queued interactive one.
+    pub async fn acquire(self: &Arc<Self>, priority: Priority) -> QueuePermit {
+        let mut pending = {
+            let mut state = self.state.lock().unwrap();
+            let idle = state.interactive.is_empty() && state.batch.is_empty();
+            if idle && state.running < self.slots {
+                state.running += 1;
+                return QueuePermit {
+                    queue: self.clone(),
+                };
+            }
+            let (grant, granted) = oneshot::channel();
+            let id = state.next_id;
+            state.next_id += 1;
+            state.waiters(priority).push_back(Waiter { id, grant });
+            telemetry::record_queue_depth(priority.as_str(), 1);
+            Pending {
+                queue: self.clone(),
+                priority,
+                id,
+                granted: Some(granted),
+            }
+        };
+
+        if let Some(granted) = pending.granted.as_mut() {
+            // Waiters leave the queue only by being granted a slot, so this cannot fail.
+            let _ = granted.await;
+        }
+        pending.granted = None;
+        QueuePermit {
+            queue: self.clone(),
+        }
+    }
+
+    /// Hand a finished search's slot to the next waiter, or free it.
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let (waiter, priority) = match state.interactive.pop_front() {
+                Some(waiter) => (waiter, Priority::Interactive),
+                None => match state.batch.pop_front() {
+                    Some(waiter) => (waiter, Priority::Batch),
+                    None => {
+                        state.running -= 1;
+                        return;
+                    }
+                },
+            };
+            telemetry::record_queue_depth(priority.as_str(), -1);
+            if waiter.grant.send(()).is_ok() {
+                return;
+            }
+        }
+    }
+}
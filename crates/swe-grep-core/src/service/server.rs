@@ -1,32 +1,83 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, bail};
+use futures::StreamExt;
 use tokio::try_join;
 
-use crate::cli::{SearchArgs, ServeArgs};
+use crate::cli::{
+    CaseMode, HttpCompression, LinkFormat, Priority, SearchArgs, ServeArgs, SortOrder, ToolArgs,
+};
+use crate::config::RepoConfig;
+use crate::definition::{self, DefinitionResponse};
+use crate::redact;
+use crate::results;
 use crate::search::{self, SearchSummary};
+use crate::source::{self, FileRange};
+use crate::symbols::{self, SymbolsResponse};
+use crate::telemetry;
+use crate::tools::ast_grep::AstGrepTool;
+use crate::tools::fd::{FdTool, SharedWalk};
+use crate::validate::{FieldError, ValidationError};
 
-use super::{grpc, http};
+use super::baseline::LatencyBaseline;
+use super::queue::SearchQueue;
+use super::tenant::{Admission, Tenant, TenantError, Tenants};
+use super::{grpc, http, stdio};
+
+const DEFAULT_SYMBOL_LIMIT: usize = 20;
 
 /// Configuration applied when launching the SWE-Grep services.
 #[derive(Clone)]
 pub struct ServeConfig {
     pub root: PathBuf,
+    /// Directories besides `root` that request paths may point into.
+    pub allowed_roots: Vec<PathBuf>,
     pub http_addr: SocketAddr,
     pub grpc_addr: SocketAddr,
-    pub timeout_secs: u64,
-    pub max_matches: usize,
+    /// Profile for requests that do not name one.
+    pub profile: Option<String>,
+    pub timeout_secs: Option<u64>,
+    pub max_matches: Option<usize>,
+    pub latency_target_ms: Option<u64>,
     pub concurrency: usize,
     pub use_index: bool,
     pub use_rga: bool,
     pub use_fd: bool,
     pub use_ast_grep: bool,
+    /// Redact secrets from every response; see `--redact-secrets`.
+    pub redact_secrets: bool,
+    /// Honour tool paths and extra arguments from repository config; see `--trust-repo-tools`.
+    pub trust_repo_tools: bool,
     pub index_dir: Option<PathBuf>,
     pub cache_dir: Option<PathBuf>,
+    /// Hint-state cap applied to every search; see `--state-max-symbols`.
+    pub state_max_symbols: Option<usize>,
     pub log_dir: Option<PathBuf>,
+    pub http_compression: HttpCompression,
+    pub transport: Transport,
+    /// When set, every request must name one of these tenants and is confined to its root.
+    pub tenants: Option<Tenants>,
+    /// Searches run at once; see `--max-concurrent-searches`.
+    pub max_concurrent_searches: usize,
+    /// Slowdown over the startup self-benchmark counted as degraded; 0 skips the benchmark.
+    pub latency_degradation_factor: f64,
+}
+
+/// Where the services accept requests.
+#[derive(Clone, Debug, Default)]
+pub enum Transport {
+    /// HTTP on `http_addr` and gRPC on `grpc_addr`.
+    #[default]
+    Tcp,
+    /// HTTP on the socket path and gRPC on the same path with a `.grpc` suffix.
+    Uds(PathBuf),
+    /// Newline-delimited JSON-RPC 2.0 over stdin/stdout; no sockets are opened.
+    Stdio,
 }
 
 impl ServeConfig {
@@ -35,13 +86,24 @@ impl ServeConfig {
         let provided_root = args
             .path
             .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
-        let root = provided_root.canonicalize().with_context(|| {
+        let root = search::canonicalize_path(&provided_root).with_context(|| {
             format!(
                 "failed to canonicalize repository root path: {}",
                 provided_root.display()
             )
         })?;
 
+        let allowed_roots = args
+            .allowed_roots
+            .iter()
+            .map(|path| {
+                search::canonicalize_path(path).with_context(|| {
+                    format!("failed to canonicalize allowed root: {}", path.display())
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let tenants = args.tenants.as_deref().map(Tenants::load).transpose()?;
+
         let mut use_index = args.enable_index;
         if use_index && !cfg!(feature = "indexing") {
             tracing::warn!("indexing support not compiled; ignoring --enable-index");
@@ -50,20 +112,79 @@ impl ServeConfig {
 
         Ok(Self {
             root: root.clone(),
+            allowed_roots,
             http_addr: args.http_addr,
             grpc_addr: args.grpc_addr,
+            profile: args.profile,
             timeout_secs: args.timeout_secs,
-            max_matches: usize::max(1, args.max_matches),
+            max_matches: args.max_matches,
+            latency_target_ms: args.latency_target_ms,
             concurrency: usize::max(1, args.concurrency),
             use_index,
             use_rga: args.enable_rga,
             use_fd: args.use_fd,
             use_ast_grep: args.use_ast_grep,
+            redact_secrets: args.redact_secrets,
+            trust_repo_tools: args.trust_repo_tools,
             index_dir: normalize_relative(&root, args.index_dir),
             cache_dir: normalize_relative(&root, args.cache_dir),
+            state_max_symbols: args.state_max_symbols,
             log_dir: normalize_relative(&root, args.log_dir),
+            http_compression: args.http_compression,
+            transport: match (args.uds, args.stdio) {
+                (_, true) => Transport::Stdio,
+                (Some(path), false) => Transport::Uds(path),
+                (None, false) => Transport::Tcp,
+            },
+            tenants,
+            max_concurrent_searches: args.max_concurrent_searches.map_or_else(
+                || std::thread::available_parallelism().map_or(1, usize::from),
+                |searches| searches as usize,
+            ),
+            latency_degradation_factor: args.latency_degradation_factor,
         })
     }
+
+    /// Configuration for searching `root` in-process, without listening anywhere: the CLI's
+    /// search defaults, no tenants, no secret redaction, untrusted repository tool settings, and no
+    /// self-benchmark.
+    pub fn embedded(root: PathBuf) -> Self {
+        Self {
+            root,
+            allowed_roots: Vec::new(),
+            http_addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            grpc_addr: SocketAddr::from(([127, 0, 0, 1], 0)),
+            profile: None,
+            timeout_secs: None,
+            max_matches: None,
+            latency_target_ms: None,
+            concurrency: 8,
+            use_index: false,
+            use_rga: false,
+            use_fd: true,
+            use_ast_grep: true,
+            redact_secrets: false,
+            trust_repo_tools: false,
+            index_dir: None,
+            cache_dir: None,
+            state_max_symbols: None,
+            log_dir: None,
+            http_compression: HttpCompression::Auto,
+            transport: Transport::default(),
+            tenants: None,
+            max_concurrent_searches: std::thread::available_parallelism().map_or(1, usize::from),
+            latency_degradation_factor: 0.0,
+        }
+    }
+
+    /// Tool arguments for every search: served repositories' tool settings apply only when
+    /// trusted, since requests cannot name binaries themselves.
+    fn tool_args(&self) -> ToolArgs {
+        ToolArgs {
+            trust_repo_tools: self.trust_repo_tools,
+            ..ToolArgs::default()
+        }
+    }
 }
 
 /// Top-level service runner that coordinates both HTTP and gRPC servers.
@@ -76,30 +197,99 @@ impl SweGrepServer {
         Self { config }
     }
 
-    /// Run the gRPC and HTTP services until a shutdown signal is received.
+    /// Run the configured transport until a shutdown signal (or, for stdio, end of input).
     pub async fn run(self) -> Result<()> {
         let grpc_addr = self.config.grpc_addr;
         let http_addr = self.config.http_addr;
+        let compression = self.config.http_compression;
+        let transport = self.config.transport.clone();
         let executor = Arc::new(SearchExecutor::new(self.config));
+        executor.self_benchmark().await;
 
-        try_join!(
-            grpc::serve(grpc_addr, executor.clone()),
-            http::serve(http_addr, executor)
-        )?;
+        match transport {
+            Transport::Tcp => {
+                try_join!(
+                    grpc::serve(grpc_addr, executor.clone()),
+                    http::serve(http_addr, executor, compression)
+                )?;
+            }
+            Transport::Uds(path) => serve_uds(&path, executor, compression).await?,
+            Transport::Stdio => stdio::serve(executor).await?,
+        }
 
         Ok(())
     }
 }
 
+/// Socket path used for gRPC when HTTP is bound to `http_path`.
+pub fn grpc_socket_path(http_path: &Path) -> PathBuf {
+    let mut path = http_path.as_os_str().to_owned();
+    path.push(".grpc");
+    PathBuf::from(path)
+}
+
+#[cfg(unix)]
+async fn serve_uds(
+    http_path: &Path,
+    executor: Arc<SearchExecutor>,
+    compression: HttpCompression,
+) -> Result<()> {
+    let grpc_path = grpc_socket_path(http_path);
+    let http_listener = bind_unix(http_path)?;
+    let grpc_listener = bind_unix(&grpc_path)?;
+    tracing::info!(
+        http = %http_path.display(),
+        grpc = %grpc_path.display(),
+        "serving on unix domain sockets"
+    );
+
+    let result = try_join!(
+        grpc::serve_unix(grpc_listener, executor.clone()),
+        http::serve_unix(http_listener, executor, compression)
+    );
+    let _ = std::fs::remove_file(http_path);
+    let _ = std::fs::remove_file(&grpc_path);
+    result.map(|_| ())
+}
+
+#[cfg(not(unix))]
+async fn serve_uds(
+    _http_path: &Path,
+    _executor: Arc<SearchExecutor>,
+    _compression: HttpCompression,
+) -> Result<()> {
+    bail!("--uds requires a Unix platform")
+}
+
+/// Bind a Unix listener, replacing a socket left behind by a previous run.
+#[cfg(unix)]
+fn bind_unix(path: &Path) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            bail!("{} exists and is not a socket", path.display());
+        }
+        std::fs::remove_file(path)
+            .with_context(|| format!("failed to remove stale socket {}", path.display()))?;
+    }
+    tokio::net::UnixListener::bind(path)
+        .with_context(|| format!("failed to bind unix socket {}", path.display()))
+}
+
 /// Internal helper that converts structured requests into CLI-compatible search executions.
 #[derive(Clone)]
 pub struct SearchExecutor {
     config: Arc<ServeConfig>,
+    queue: Arc<SearchQueue>,
+    baseline: Arc<LatencyBaseline>,
 }
 
 impl SearchExecutor {
     pub fn new(config: ServeConfig) -> Self {
         Self {
+            queue: SearchQueue::new(config.max_concurrent_searches),
+            baseline: Arc::new(LatencyBaseline::new(config.latency_degradation_factor)),
             config: Arc::new(config),
         }
     }
@@ -108,6 +298,35 @@ impl SearchExecutor {
         &self.config.root
     }
 
+    /// Measure the latency baseline later searches are checked against; a failed benchmark
+    /// only disables the check.
+    pub async fn self_benchmark(&self) {
+        if !self.baseline.enabled() {
+            return;
+        }
+        let cache_dir = self
+            .config
+            .cache_dir
+            .clone()
+            .unwrap_or_else(|| self.config.root.join(".swe-grep-cache"));
+        match self
+            .baseline
+            .measure(&self.config.root, &cache_dir, &self.config.tool_args())
+            .await
+        {
+            Ok(baseline_ms) => tracing::info!(baseline_ms, "measured self-benchmark baseline"),
+            Err(err) => tracing::warn!(
+                error = %err,
+                "self-benchmark failed; latency degradation is not tracked"
+            ),
+        }
+    }
+
+    /// Startup latency baseline and the degradation check of later searches.
+    pub fn baseline(&self) -> &LatencyBaseline {
+        &self.baseline
+    }
+
     fn normalize_with_root(&self, path: PathBuf) -> PathBuf {
         if path.is_absolute() {
             path
@@ -116,14 +335,177 @@ impl SearchExecutor {
         }
     }
 
+    /// Resolve a request-supplied path, rejecting it unless it stays inside the served root or
+    /// an `--allowed-root` once symlinks and `..` are resolved.
+    fn confine(&self, field: &'static str, path: PathBuf) -> Result<PathBuf> {
+        let path = self.normalize_with_root(path);
+        confine_within(
+            field,
+            path,
+            std::iter::once(&self.config.root).chain(&self.config.allowed_roots),
+        )
+        .map(|(resolved, _)| resolved)
+    }
+
     /// Execute a search using values supplied by the calling protocol layer.
+    ///
+    /// With tenants configured the request must name one and carry its token, and the search
+    /// runs against that tenant's directories within its quotas. Admitted requests then wait for
+    /// one of the server's search slots by priority.
     pub async fn execute(&self, request: SearchInput) -> Result<SearchSummary> {
+        let admission = self
+            .admit(request.tenant.as_deref(), request.token.as_deref())
+            .inspect_err(|err| {
+                if let Some(tenant) = err.tenant() {
+                    telemetry::record_tenant_search(tenant, err.outcome(), None);
+                }
+            })?;
+        let _permit = self
+            .queue
+            .acquire(request.priority.unwrap_or_default())
+            .await;
+        let Some(admission) = admission else {
+            return self.run(request, None).await;
+        };
+
+        let started = Instant::now();
+        let result = self
+            .run(request, Some((admission.id, admission.tenant)))
+            .await;
+        let latency_ms = started.elapsed().as_millis() as u64;
+        let (outcome, latency_ms) = match &result {
+            Ok(_) => ("ok", Some(latency_ms)),
+            Err(err) => match err.downcast_ref::<TenantError>() {
+                Some(err) => (err.outcome(), None),
+                None => ("error", Some(latency_ms)),
+            },
+        };
+        telemetry::record_tenant_search(admission.id, outcome, latency_ms);
+        result
+    }
+
+    /// Execute several searches as one request, returning their results in request order.
+    ///
+    /// Each search is admitted and queued like [`Self::execute`], and at most the server's
+    /// search slots (or the smallest `max_concurrent` of the batch's tenants) run at once.
+    /// Searches of the same root share one fd walk for file-name discovery, run by the first of
+    /// them that gets past the literal fast path.
+    pub async fn execute_batch(&self, requests: Vec<SearchInput>) -> Result<BatchResults> {
+        if requests.is_empty() {
+            bail!("searches is required");
+        }
+        if requests.len() > MAX_BATCH_SEARCHES {
+            return Err(ValidationError {
+                fields: vec![FieldError {
+                    field: "searches",
+                    message: format!(
+                        "must hold at most {MAX_BATCH_SEARCHES} searches, got {}",
+                        requests.len()
+                    ),
+                }],
+            }
+            .into());
+        }
+
+        let started = Instant::now();
+        let (walks, concurrency) = self.share_discovery(&requests);
+        let results =
+            futures::stream::iter(requests.into_iter().map(|request| self.execute(request)))
+                .buffered(concurrency)
+                .collect::<Vec<_>>()
+                .await;
+        let shared_discovery = walks.iter().map(SharedWalk::finish).sum();
+        Ok(BatchResults {
+            results,
+            elapsed_ms: started.elapsed().as_millis() as u64,
+            shared_discovery,
+        })
+    }
+
+    /// Register the batch's searches of each root for one shared fd walk and size the batch's
+    /// concurrency; requests that fail admission are left to fail on their own.
+    fn share_discovery(&self, requests: &[SearchInput]) -> (Vec<Arc<SharedWalk>>, usize) {
+        let mut concurrency = usize::max(1, self.config.max_concurrent_searches);
+        let mut needles: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+        for request in requests {
+            let Ok(admission) = self.admit(request.tenant.as_deref(), request.token.as_deref())
+            else {
+                continue;
+            };
+            let root = match &admission {
+                Some(admission) => {
+                    if let Some(limit) = admission.tenant.max_concurrent() {
+                        concurrency = concurrency.min(limit);
+                    }
+                    admission.tenant.root.clone()
+                }
+                None => match request.root.clone().map(|root| self.confine("root", root)) {
+                    Some(Ok(root)) => root,
+                    Some(Err(_)) => continue,
+                    None => self.config.root.clone(),
+                },
+            };
+            let use_fd = request
+                .tool_flags
+                .iter()
+                .filter(|(key, _)| {
+                    matches!(
+                        key.to_ascii_lowercase().as_str(),
+                        "fd" | "use_fd" | "disable_fd"
+                    )
+                })
+                .fold(self.config.use_fd, |_, (_, value)| *value);
+            if use_fd {
+                // Keyed like the root the search canonicalizes for itself.
+                let root = search::canonicalize_path(&root).unwrap_or(root);
+                needles
+                    .entry(root)
+                    .or_default()
+                    .push(request.symbol.trim().to_string());
+            }
+        }
+        let walks = needles
+            .iter()
+            .filter_map(|(root, needles)| FdTool::share(root, needles))
+            .collect();
+        (walks, concurrency)
+    }
+
+    /// Admit a request under the tenant it names; `None` when the server has no tenants.
+    fn admit(
+        &self,
+        tenant: Option<&str>,
+        token: Option<&str>,
+    ) -> Result<Option<Admission<'_>>, TenantError> {
+        match &self.config.tenants {
+            Some(tenants) => tenants.admit(tenant, token).map(Some),
+            None => match tenant.map(str::trim).filter(|tenant| !tenant.is_empty()) {
+                Some(tenant) => Err(TenantError::Unknown(tenant.to_string())),
+                None => Ok(None),
+            },
+        }
+    }
+
+    async fn run(
+        &self,
+        request: SearchInput,
+        tenant: Option<(&str, &Tenant)>,
+    ) -> Result<SearchSummary> {
         let SearchInput {
             symbol,
             language,
+            rg_types,
+            rg_type_adds,
+            config,
             root,
+            profile,
+            owner,
+            case,
+            word,
             timeout_secs,
             max_matches,
+            max_scope_files,
+            latency_target_ms,
             concurrency,
             enable_index,
             enable_rga,
@@ -134,33 +516,72 @@ impl SearchExecutor {
             context_after,
             body,
             tool_flags,
+            explain,
+            highlight,
+            link_format,
+            permalinks,
+            sort,
+            deterministic,
+            priority: _,
+            tenant: _,
+            token: _,
         } = request;
 
         if symbol.trim().is_empty() {
             bail!("symbol is required");
         }
 
-        let root_path = root
-            .map(|p| self.normalize_with_root(p))
-            .unwrap_or_else(|| self.config.root.clone());
-
-        let timeout_secs = timeout_secs.unwrap_or(self.config.timeout_secs);
-        let max_matches = usize::max(1, max_matches.unwrap_or(self.config.max_matches));
+        // Request values, then server flags; whatever is still unset comes from the profile.
+        let profile = profile.or_else(|| self.config.profile.clone());
+        let mut timeout_secs = timeout_secs.or(self.config.timeout_secs);
+        let mut max_matches = max_matches.or(self.config.max_matches);
+        let latency_target_ms = latency_target_ms.or(self.config.latency_target_ms);
         let concurrency = usize::max(1, concurrency.unwrap_or(self.config.concurrency));
         let enable_index = enable_index.unwrap_or(self.config.use_index);
         let enable_rga = enable_rga.unwrap_or(self.config.use_rga);
 
-        let index_dir = index_dir
-            .map(|p| self.normalize_with_root(p))
-            .or_else(|| self.config.index_dir.clone());
-
-        let cache_dir = cache_dir
-            .map(|p| self.normalize_with_root(p))
-            .or_else(|| self.config.cache_dir.clone());
-
-        let log_dir = log_dir
-            .map(|p| self.normalize_with_root(p))
-            .or_else(|| self.config.log_dir.clone());
+        let (root_path, index_dir, cache_dir, log_dir) = match tenant {
+            Some((id, tenant)) => {
+                let overrides = [
+                    ("root", root.is_some()),
+                    ("index_dir", index_dir.is_some()),
+                    ("cache_dir", cache_dir.is_some()),
+                    ("log_dir", log_dir.is_some()),
+                ];
+                if let Some((field, _)) = overrides.into_iter().find(|(_, set)| *set) {
+                    return Err(TenantError::Override {
+                        tenant: id.to_string(),
+                        field,
+                    }
+                    .into());
+                }
+                timeout_secs = tenant.clamp_timeout_secs(timeout_secs);
+                max_matches = tenant.clamp_max_matches(max_matches);
+                (
+                    tenant.root.clone(),
+                    tenant.index_dir.clone(),
+                    tenant.cache_dir.clone(),
+                    tenant.log_dir.clone(),
+                )
+            }
+            None => (
+                root.map(|p| self.confine("root", p))
+                    .transpose()?
+                    .unwrap_or_else(|| self.config.root.clone()),
+                index_dir
+                    .map(|p| self.confine("index_dir", p))
+                    .transpose()?
+                    .or_else(|| self.config.index_dir.clone()),
+                cache_dir
+                    .map(|p| self.confine("cache_dir", p))
+                    .transpose()?
+                    .or_else(|| self.config.cache_dir.clone()),
+                log_dir
+                    .map(|p| self.confine("log_dir", p))
+                    .transpose()?
+                    .or_else(|| self.config.log_dir.clone()),
+            ),
+        };
 
         let context_before = context_before.unwrap_or(0);
         let context_after = context_after.unwrap_or(0);
@@ -168,39 +589,278 @@ impl SearchExecutor {
 
         let mut args = SearchArgs {
             symbol,
-            path: Some(root_path),
+            path: vec![root_path],
             language,
+            rg_types,
+            rg_type_adds,
+            config: config.unwrap_or(false),
+            rev: None,
+            files_from: None,
+            include_submodules: false,
+            owner,
+            case,
+            word,
+            profile,
+            // A served checkout is whatever sits under the allowed roots, so its hooks never run.
+            trust_repo_hooks: false,
+            trust_repo_state: false,
             timeout_secs,
             max_matches,
+            max_scope_files,
+            latency_target_ms,
             concurrency,
             context_before,
             context_after,
             body,
+            redact_secrets: self.config.redact_secrets,
             enable_index,
             index_dir,
             enable_rga,
             cache_dir,
+            state_max_symbols: self.config.state_max_symbols,
             log_dir,
             use_fd: self.config.use_fd,
             use_ast_grep: self.config.use_ast_grep,
+            tools: self.config.tool_args(),
+            fields: Vec::new(),
+            explain: explain.unwrap_or(false),
+            highlight: highlight.unwrap_or(false),
+            link_format,
+            permalinks: permalinks.unwrap_or(false),
+            sort,
+            deterministic: deterministic.unwrap_or(false),
+            format: None,
+            exec: None,
         };
 
         if !tool_flags.is_empty() {
             args = apply_tool_flags(args, tool_flags);
         }
 
-        search::execute(args).await
+        let started = Instant::now();
+        let results_dir = self.results_cache_dir(tenant.map(|(_, tenant)| tenant));
+        let summary = search::execute_recorded_in(args, &results_dir).await?;
+        self.baseline.observe(started.elapsed().as_millis() as u64);
+        Ok(summary)
+    }
+
+    /// Complete an identifier prefix against the served repository's symbol dictionary, or the
+    /// named tenant's when tenants are configured.
+    pub async fn symbols(
+        &self,
+        prefix: &str,
+        limit: Option<usize>,
+        tenant: Option<&str>,
+        token: Option<&str>,
+    ) -> Result<SymbolsResponse> {
+        let admission = self.admit(tenant, token)?;
+        let (root, index_dir) = match &admission {
+            Some(admission) => (&admission.tenant.root, admission.tenant.index_dir.clone()),
+            None => (&self.config.root, self.config.index_dir.clone()),
+        };
+        symbols::lookup(
+            root,
+            index_dir,
+            prefix,
+            limit.unwrap_or(DEFAULT_SYMBOL_LIMIT),
+        )
+        .await
+    }
+
+    /// Stored summary of an earlier search served by this instance, or by the named tenant
+    /// when tenants are configured.
+    pub async fn result(
+        &self,
+        id: &str,
+        tenant: Option<&str>,
+        token: Option<&str>,
+    ) -> Result<SearchSummary> {
+        let admission = self.admit(tenant, token)?;
+        let cache_dir =
+            self.results_cache_dir(admission.as_ref().map(|admission| admission.tenant));
+        results::load(&cache_dir, id)
+    }
+
+    /// Cache directory whose `results/` folder keeps every search served for `tenant` (or
+    /// without tenants), whatever root or cache directory the request named: the tenant's or
+    /// the server's cache directory, or `.swe-grep-cache` under its root.
+    fn results_cache_dir(&self, tenant: Option<&Tenant>) -> PathBuf {
+        let (root, cache_dir) = match tenant {
+            Some(tenant) => (&tenant.root, &tenant.cache_dir),
+            None => (&self.config.root, &self.config.cache_dir),
+        };
+        cache_dir
+            .clone()
+            .unwrap_or_else(|| root.join(".swe-grep-cache"))
+    }
+
+    /// Lines `start..=end` of a file under the served root or an `--allowed-root` (under the
+    /// tenant's root when tenants are configured), capped at the byte budget of `--body`, with
+    /// secrets redacted under `--redact-secrets`.
+    pub async fn read_file(
+        &self,
+        path: &str,
+        start: Option<usize>,
+        end: Option<usize>,
+        tenant: Option<&str>,
+        token: Option<&str>,
+    ) -> Result<FileRange> {
+        let admission = self.admit(tenant, token)?;
+        let (resolved, root) = self.resolve_file(admission.as_ref(), path)?;
+        let display = resolved
+            .strip_prefix(&root)
+            .unwrap_or(&resolved)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let mut range = source::read_range(
+            &resolved,
+            display,
+            start.unwrap_or(1),
+            end,
+            search::DEFAULT_MAX_BODY_BYTES,
+        )?;
+        if self.config.redact_secrets {
+            range.secrets_redacted = redact::redact(&mut range.content);
+        }
+        Ok(range)
+    }
+
+    /// Definitions of the identifier at 1-based `line` and character `column` of `path`: the
+    /// identifier is searched for like any symbol, and hits declaring it are split from the rest.
+    ///
+    /// The file is checked against the tenant or allowed roots as in [`Self::read_file`], and the
+    /// search runs from the root that contains it.
+    pub async fn definition(
+        &self,
+        path: &str,
+        line: usize,
+        column: usize,
+        tenant: Option<&str>,
+        token: Option<&str>,
+    ) -> Result<DefinitionResponse> {
+        let admission = self.admit(tenant, token)?;
+        let (resolved, root) = self.resolve_file(admission.as_ref(), path)?;
+        let relative = resolved.strip_prefix(&root).unwrap_or(&resolved);
+        let cursor = source::read_range(
+            &resolved,
+            relative.to_string_lossy().replace('\\', "/"),
+            line,
+            Some(line),
+            search::DEFAULT_MAX_BODY_BYTES,
+        )?;
+        let tool = if self.config.use_ast_grep {
+            let tools = RepoConfig::load(&root)?
+                .tools
+                .resolve(&root, &self.config.tool_args())?;
+            let timeout = self
+                .config
+                .timeout_secs
+                .unwrap_or(search::DEFAULT_TIMEOUT_SECS);
+            Some(
+                AstGrepTool::new(Duration::from_secs(timeout), usize::MAX)
+                    .with_settings(tools.ast_grep.clone())
+                    .with_retry(tools.retry_policy()),
+            )
+        } else {
+            None
+        };
+        let symbol = definition::identifier_at(
+            &root,
+            relative,
+            &cursor.content,
+            line,
+            column,
+            tool.as_ref(),
+        )
+        .await?;
+        // The search admits the tenant again, so this admission must not hold a slot meanwhile.
+        let tenant = admission.map(|admission| admission.id.to_string());
+        let summary = self
+            .execute(SearchInput {
+                symbol: symbol.clone(),
+                root: tenant.is_none().then(|| root.clone()),
+                tenant,
+                token: token.map(str::to_string),
+                ..SearchInput::default()
+            })
+            .await?;
+        let (definitions, references) =
+            definition::partition(&root, &symbol, summary.top_hits, tool.as_ref()).await;
+        Ok(DefinitionResponse {
+            symbol,
+            definitions,
+            references,
+        })
+    }
+
+    /// Resolve a request's file `path` within the admitted tenant's root, or else the served and
+    /// allowed roots, returning it with the root that contains it.
+    fn resolve_file(
+        &self,
+        admission: Option<&Admission<'_>>,
+        path: &str,
+    ) -> Result<(PathBuf, PathBuf)> {
+        if path.trim().is_empty() {
+            bail!("path is required");
+        }
+        let path = PathBuf::from(path);
+        match admission {
+            Some(admission) => {
+                let root = &admission.tenant.root;
+                let path = if path.is_absolute() {
+                    path
+                } else {
+                    root.join(path)
+                };
+                confine_within("path", path, std::iter::once(root))
+            }
+            None => confine_within(
+                "path",
+                self.normalize_with_root(path),
+                std::iter::once(&self.config.root).chain(&self.config.allowed_roots),
+            ),
+        }
     }
 }
 
+/// Most searches one batch request may carry.
+pub const MAX_BATCH_SEARCHES: usize = 32;
+
+/// Outcome of [`SearchExecutor::execute_batch`].
+#[derive(Default)]
+pub struct BatchResults {
+    /// One result per search, in request order.
+    pub results: Vec<Result<SearchSummary>>,
+    /// Wall-clock time of the whole batch.
+    pub elapsed_ms: u64,
+    /// Searches whose file-name discovery came from a walk shared with the rest of the batch.
+    pub shared_discovery: usize,
+}
+
 /// Mutable request wrapper shared by the gRPC and HTTP entry points.
 #[derive(Default)]
 pub struct SearchInput {
     pub symbol: String,
     pub language: Option<String>,
+    /// ripgrep file types to search; see `--rg-type`.
+    pub rg_types: Vec<String>,
+    /// ripgrep type definitions; see `--rg-type-add`.
+    pub rg_type_adds: Vec<String>,
+    /// Search Terraform/HCL and YAML files first.
+    pub config: Option<bool>,
     pub root: Option<PathBuf>,
+    /// Named option bundle; falls back to the server's `--profile`.
+    pub profile: Option<String>,
+    /// Keep only hits owned by this `CODEOWNERS` owner.
+    pub owner: Option<String>,
+    pub case: Option<CaseMode>,
+    /// Whole-word matching; unset leaves it on for literal identifiers.
+    pub word: Option<bool>,
     pub timeout_secs: Option<u64>,
     pub max_matches: Option<usize>,
+    /// Cap on the files handed to the scoped probe; see `--max-scope-files`.
+    pub max_scope_files: Option<usize>,
+    pub latency_target_ms: Option<u64>,
     pub concurrency: Option<usize>,
     pub enable_index: Option<bool>,
     pub enable_rga: Option<bool>,
@@ -211,6 +871,77 @@ pub struct SearchInput {
     pub context_after: Option<usize>,
     pub body: Option<bool>,
     pub tool_flags: HashMap<String, bool>,
+    /// Report repository stats in the summary.
+    pub explain: Option<bool>,
+    /// Attach token spans to hit snippets.
+    pub highlight: Option<bool>,
+    /// Attach a `link` of this format to each hit.
+    pub link_format: Option<LinkFormat>,
+    /// Attach a commit-pinned `permalink` to each hit.
+    pub permalinks: Option<bool>,
+    /// Order of `top_hits`; by score when unset.
+    pub sort: Option<SortOrder>,
+    /// Make the summary byte-identical across runs.
+    pub deterministic: Option<bool>,
+    /// Queue class while the server is busy; interactive when unset.
+    pub priority: Option<Priority>,
+    /// Tenant to search as; required when the server runs with `--tenants`.
+    pub tenant: Option<String>,
+    /// Bearer token presented for `tenant`.
+    pub token: Option<String>,
+}
+
+/// A request path outside the served root and every `--allowed-root`.
+#[derive(Debug)]
+pub struct RootNotAllowed {
+    pub field: &'static str,
+    pub path: PathBuf,
+}
+
+impl fmt::Display for RootNotAllowed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} is outside the allowed roots",
+            self.field,
+            self.path.display()
+        )
+    }
+}
+
+impl std::error::Error for RootNotAllowed {}
+
+/// `path` with symlinks and `..` resolved, for paths whose trailing components do not exist
+/// yet (a cache directory about to be created); `None` when `..` follows a missing component.
+fn resolve_existing_prefix(path: &Path) -> Option<PathBuf> {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(mut resolved) = existing.canonicalize() {
+            resolved.extend(missing.iter().rev());
+            return Some(resolved);
+        }
+        missing.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+/// Resolve `path` (absolute) and check it stays inside one of `roots`, returning it with the
+/// resolved root that contains it.
+fn confine_within<'a>(
+    field: &'static str,
+    path: PathBuf,
+    roots: impl IntoIterator<Item = &'a PathBuf>,
+) -> Result<(PathBuf, PathBuf)> {
+    let allowed = resolve_existing_prefix(&path).and_then(|resolved| {
+        roots.into_iter().find_map(|allowed| {
+            let allowed = allowed.canonicalize().unwrap_or_else(|_| allowed.clone());
+            resolved
+                .starts_with(&allowed)
+                .then(|| (resolved.clone(), allowed))
+        })
+    });
+    allowed.ok_or_else(|| RootNotAllowed { field, path }.into())
 }
 
 fn normalize_relative(base: &Path, value: Option<PathBuf>) -> Option<PathBuf> {
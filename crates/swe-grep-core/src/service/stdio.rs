@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::fields::FieldSelection;
+use crate::validate::ValidationError;
+
+use super::API_VERSIONS;
+use super::http::{HttpSearchRequest, HttpSymbolsQuery};
+use super::server::{RootNotAllowed, SearchExecutor, SearchInput};
+use super::tenant::TenantError;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+const UNAUTHORIZED: i64 = -32001;
+const QUOTA_EXCEEDED: i64 = -32002;
+const FORBIDDEN: i64 = -32003;
+
+/// Answer newline-delimited JSON-RPC 2.0 requests on stdin until EOF or shutdown.
+///
+/// Methods mirror the HTTP routes: `search` takes the `/search` body, `symbols` the `/symbols`
+/// query, and `health` no params. Requests are answered in order, one JSON line each;
+/// notifications (requests without an `id`) run but get no reply. With `--tenants`, `search`
+/// and `symbols` also take the tenant's bearer token as a `token` param.
+pub async fn serve(executor: Arc<SearchExecutor>) -> Result<()> {
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    let mut stdout = tokio::io::stdout();
+    let shutdown = super::shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        let line = tokio::select! {
+            line = lines.next_line() => line.context("failed to read JSON-RPC request from stdin")?,
+            _ = &mut shutdown => return Ok(()),
+        };
+        let Some(line) = line else {
+            return Ok(());
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(&executor, &line).await {
+            let mut payload = serde_json::to_vec(&response)?;
+            payload.push(b'\n');
+            stdout
+                .write_all(&payload)
+                .await
+                .context("failed to write JSON-RPC response to stdout")?;
+            stdout.flush().await?;
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+/// Handle one request line; `None` when it was a notification.
+async fn handle_line(executor: &SearchExecutor, line: &str) -> Option<Value> {
+    let value: Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(err) => {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(PARSE_ERROR, err.to_string()),
+            ));
+        }
+    };
+    let id = value.get("id").cloned().unwrap_or(Value::Null);
+    let request: RpcRequest = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(err) => {
+            return Some(error_response(
+                id,
+                RpcError::new(INVALID_REQUEST, err.to_string()),
+            ));
+        }
+    };
+
+    let result = dispatch(executor, &request.method, request.params).await;
+    let id = request.id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(err) => error_response(id, err),
+    })
+}
+
+async fn dispatch(
+    executor: &SearchExecutor,
+    method: &str,
+    params: Option<Value>,
+) -> Result<Value, RpcError> {
+    match method {
+        "health" => Ok(json!({ "status": "ok", "api_versions": API_VERSIONS })),
+        "search" => {
+            let token = token_param(params.as_ref());
+            let request: HttpSearchRequest = params_as(params)?;
+            let fields = FieldSelection::parse(&request.fields)
+                .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))?;
+            let mut input: SearchInput = request.into();
+            input.token = token;
+            let summary = executor.execute(input).await.map_err(|err| {
+                let message = err.to_string();
+                if let Some(invalid) = err.downcast_ref::<ValidationError>() {
+                    RpcError {
+                        data: Some(json!({ "fields": invalid.fields })),
+                        ..RpcError::new(INVALID_PARAMS, message)
+                    }
+                } else if let Some(err) = err.downcast_ref::<TenantError>() {
+                    RpcError::new(tenant_code(err), message)
+                } else if err.is::<RootNotAllowed>() {
+                    RpcError::new(FORBIDDEN, message)
+                } else if message.contains("symbol is required")
+                    || message.contains("unknown profile")
+                {
+                    RpcError::new(INVALID_PARAMS, message)
+                } else {
+                    RpcError::new(SERVER_ERROR, message)
+                }
+            })?;
+            let summary = fields
+                .to_value(&summary)
+                .map_err(|err| RpcError::new(SERVER_ERROR, err.to_string()))?;
+            Ok(json!({ "summary": summary }))
+        }
+        "symbols" => {
+            let token = token_param(params.as_ref());
+            let query: HttpSymbolsQuery = params_as(params)?;
+            if query.prefix.trim().is_empty() {
+                return Err(RpcError::new(INVALID_PARAMS, "prefix is required"));
+            }
+            let response = executor
+                .symbols(
+                    &query.prefix,
+                    query.limit,
+                    query.tenant.as_deref(),
+                    token.as_deref(),
+                )
+                .await
+                .map_err(|err| {
+                    let code = err
+                        .downcast_ref::<TenantError>()
+                        .map_or(SERVER_ERROR, tenant_code);
+                    RpcError::new(code, err.to_string())
+                })?;
+            serde_json::to_value(response)
+                .map_err(|err| RpcError::new(SERVER_ERROR, err.to_string()))
+        }
+        other => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("unknown method `{other}`; expected one of search, symbols, health"),
+        )),
+    }
+}
+
+fn token_param(params: Option<&Value>) -> Option<String> {
+    params?
+        .get("token")?
+        .as_str()
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+}
+
+fn tenant_code(err: &TenantError) -> i64 {
+    match err {
+        TenantError::Unauthorized(_) => UNAUTHORIZED,
+        TenantError::QuotaExceeded { .. } => QUOTA_EXCEEDED,
+        TenantError::Required | TenantError::Unknown(_) | TenantError::Override { .. } => {
+            INVALID_PARAMS
+        }
+    }
+}
+
+fn params_as<T: serde::de::DeserializeOwned>(params: Option<Value>) -> Result<T, RpcError> {
+    serde_json::from_value(params.unwrap_or(Value::Null))
+        .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    let mut body = json!({ "code": error.code, "message": error.message });
+    if let Some(data) = error.data {
+        body["data"] = data;
+    }
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": body,
+    })
+}
@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::search;
+
+/// Repositories served to separate callers from one `serve` process, read from `--tenants`.
+///
+/// Each `[tenants.<id>]` table pins a root plus its own cache, index, and log directories, so
+/// requests naming that tenant can neither see nor write into another tenant's tree. Requests
+/// must present the tenant's token and stay within its quotas.
+#[derive(Clone, Debug)]
+pub struct Tenants {
+    tenants: BTreeMap<String, Tenant>,
+}
+
+/// One tenant's isolated search root and limits.
+#[derive(Clone)]
+pub struct Tenant {
+    pub root: PathBuf,
+    pub cache_dir: Option<PathBuf>,
+    pub index_dir: Option<PathBuf>,
+    pub log_dir: Option<PathBuf>,
+    /// Ceiling (and default) for `max_matches`.
+    pub max_matches: Option<usize>,
+    /// Ceiling (and default) for `timeout_secs`.
+    pub timeout_secs: Option<u64>,
+    token: String,
+    /// Slots for concurrent searches, with the configured `max_concurrent`.
+    searches: Option<(Arc<Semaphore>, usize)>,
+}
+
+impl fmt::Debug for Tenant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The token stays out of logs and panics.
+        f.debug_struct("Tenant")
+            .field("root", &self.root)
+            .field("cache_dir", &self.cache_dir)
+            .field("index_dir", &self.index_dir)
+            .field("log_dir", &self.log_dir)
+            .field("max_matches", &self.max_matches)
+            .field("timeout_secs", &self.timeout_secs)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TenantsFile {
+    #[serde(default)]
+    tenants: BTreeMap<String, TenantEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TenantEntry {
+    root: PathBuf,
+    #[serde(default)]
+    cache_dir: Option<PathBuf>,
+    #[serde(default)]
+    index_dir: Option<PathBuf>,
+    #[serde(default)]
+    log_dir: Option<PathBuf>,
+    #[serde(default)]
+    token: Option<String>,
+    /// Environment variable holding the token, so the file itself can be checked in.
+    #[serde(default)]
+    token_env: Option<String>,
+    /// Searches allowed to run at once; further ones are rejected rather than queued.
+    #[serde(default)]
+    max_concurrent: Option<usize>,
+    #[serde(default)]
+    max_matches: Option<usize>,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+}
+
+impl Tenants {
+    /// Read a tenants file; relative paths are resolved against the file's directory.
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        Self::parse(base, &raw).with_context(|| format!("failed to load {}", path.display()))
+    }
+
+    pub fn parse(base: &Path, raw: &str) -> Result<Self> {
+        let file: TenantsFile = toml::from_str(raw)?;
+        if file.tenants.is_empty() {
+            bail!("no [tenants.<id>] tables defined");
+        }
+        let resolve = |path: PathBuf| base.join(path);
+        let mut tenants = BTreeMap::new();
+        for (id, entry) in file.tenants {
+            let token = match (entry.token, entry.token_env) {
+                (Some(token), None) => token,
+                (None, Some(var)) => std::env::var(&var).with_context(|| {
+                    format!("tenant `{id}`: environment variable {var} is not set")
+                })?,
+                (Some(_), Some(_)) => bail!("tenant `{id}`: set token or token_env, not both"),
+                (None, None) => bail!("tenant `{id}` needs a token or token_env"),
+            };
+            if token.trim().is_empty() {
+                bail!("tenant `{id}` has an empty token");
+            }
+            if entry.max_concurrent == Some(0) {
+                bail!("tenant `{id}`: max_concurrent must be greater than zero");
+            }
+            let root = resolve(entry.root);
+            let root = search::canonicalize_path(&root).with_context(|| {
+                format!("tenant `{id}`: failed to resolve root {}", root.display())
+            })?;
+            tenants.insert(
+                id,
+                Tenant {
+                    root,
+                    cache_dir: entry.cache_dir.map(resolve),
+                    index_dir: entry.index_dir.map(resolve),
+                    log_dir: entry.log_dir.map(resolve),
+                    max_matches: entry.max_matches,
+                    timeout_secs: entry.timeout_secs,
+                    token,
+                    searches: entry
+                        .max_concurrent
+                        .map(|limit| (Arc::new(Semaphore::new(limit)), limit)),
+                },
+            );
+        }
+        Ok(Self { tenants })
+    }
+
+    /// Check a request's tenant and token, then take one of the tenant's search slots.
+    pub fn admit(
+        &self,
+        tenant: Option<&str>,
+        token: Option<&str>,
+    ) -> Result<Admission<'_>, TenantError> {
+        let id = tenant
+            .map(str::trim)
+            .filter(|id| !id.is_empty())
+            .ok_or(TenantError::Required)?;
+        let (id, tenant) = self
+            .tenants
+            .get_key_value(id)
+            .ok_or_else(|| TenantError::Unknown(id.to_string()))?;
+        let authorized =
+            token.is_some_and(|token| constant_time_eq(token.as_bytes(), tenant.token.as_bytes()));
+        if !authorized {
+            return Err(TenantError::Unauthorized(id.clone()));
+        }
+        let permit = match &tenant.searches {
+            Some((searches, limit)) => {
+                Some(searches.clone().try_acquire_owned().map_err(|_| {
+                    TenantError::QuotaExceeded {
+                        tenant: id.clone(),
+                        limit: *limit,
+                    }
+                })?)
+            }
+            None => None,
+        };
+        Ok(Admission {
+            id,
+            tenant,
+            _permit: permit,
+        })
+    }
+}
+
+/// An authorized request; holds the tenant's search slot until dropped.
+pub struct Admission<'a> {
+    pub id: &'a str,
+    pub tenant: &'a Tenant,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Tenant {
+    /// `requested`, or the tenant's ceiling when it is unset or above it.
+    pub fn clamp_max_matches(&self, requested: Option<usize>) -> Option<usize> {
+        clamp(requested, self.max_matches)
+    }
+
+    pub fn clamp_timeout_secs(&self, requested: Option<u64>) -> Option<u64> {
+        clamp(requested, self.timeout_secs)
+    }
+
+    /// Searches the tenant may run at once; `None` when unlimited.
+    pub fn max_concurrent(&self) -> Option<usize> {
+        self.searches.as_ref().map(|(_, limit)| *limit)
+    }
+}
+
+fn clamp<T: Ord + Copy>(requested: Option<T>, ceiling: Option<T>) -> Option<T> {
+    match (requested, ceiling) {
+        (Some(value), Some(ceiling)) => Some(value.min(ceiling)),
+        (None, ceiling) => ceiling,
+        (value, None) => value,
+    }
+}
+
+/// Compare tokens without returning early, so response timing does not leak a matching prefix.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// Why a request was refused before any search ran.
+#[derive(Debug)]
+pub enum TenantError {
+    /// Tenants are configured but the request did not name one.
+    Required,
+    Unknown(String),
+    /// Missing or wrong token.
+    Unauthorized(String),
+    QuotaExceeded {
+        tenant: String,
+        limit: usize,
+    },
+    /// The request tried to point a tenant search at its own directory.
+    Override {
+        tenant: String,
+        field: &'static str,
+    },
+}
+
+impl TenantError {
+    /// Tenant to attribute the rejection to in metrics; `None` for ids nobody configured, so
+    /// callers cannot mint new label values.
+    pub fn tenant(&self) -> Option<&str> {
+        match self {
+            Self::Required | Self::Unknown(_) => None,
+            Self::Unauthorized(tenant)
+            | Self::QuotaExceeded { tenant, .. }
+            | Self::Override { tenant, .. } => Some(tenant),
+        }
+    }
+
+    /// Short outcome label for the per-tenant metrics.
+    pub fn outcome(&self) -> &'static str {
+        match self {
+            Self::Required | Self::Unknown(_) | Self::Override { .. } => "rejected",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::QuotaExceeded { .. } => "quota_exceeded",
+        }
+    }
+}
+
+impl fmt::Display for TenantError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Required => write!(f, "tenant is required"),
+            Self::Unknown(tenant) => write!(f, "unknown tenant `{tenant}`"),
+            Self::Unauthorized(tenant) => {
+                write!(f, "missing or invalid token for tenant `{tenant}`")
+            }
+            Self::QuotaExceeded { tenant, limit } => write!(
+                f,
+                "tenant `{tenant}` already has {limit} searches running; retry later"
+            ),
+            Self::Override { tenant, field } => {
+                write!(f, "{field} cannot be set for tenant `{tenant}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TenantError {}
@@ -0,0 +1,68 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+/// Directory levels below the root inspected for filtered directories.
+const MAX_DEPTH: usize = 2;
+/// Filtered directories reported per search, in path order.
+const MAX_FILTERED_DIRECTORIES: usize = 12;
+
+/// Directories within two levels of `root` that ripgrep's default filters leave out, labelled
+/// `node_modules (ignored)` or `.github (hidden)`.
+///
+/// A directory counts as ignored when `.gitignore`, `.ignore`, `.rgignore`, or git's exclude
+/// files drop it, and as hidden when its name starts with a dot. `.git` and swe-grep's own
+/// cache and index directories are never reported, and nothing below a reported directory is.
+pub fn filtered_directories(root: &Path) -> Vec<String> {
+    let kept: HashSet<PathBuf> = WalkBuilder::new(root)
+        .standard_filters(true)
+        .add_custom_ignore_filename(".rgignore")
+        .max_depth(Some(MAX_DEPTH))
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_dir()))
+        .map(|entry| entry.into_path())
+        .collect();
+
+    let mut filtered = Vec::new();
+    let mut pending = vec![(root.to_path_buf(), 1)];
+    while let Some((dir, depth)) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            if !entry.file_type().is_ok_and(|ft| ft.is_dir()) {
+                continue;
+            }
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if name == ".git" || name.starts_with(".swe-grep") {
+                continue;
+            }
+            let path = entry.path();
+            if kept.contains(&path) {
+                if depth < MAX_DEPTH {
+                    pending.push((path, depth + 1));
+                }
+                continue;
+            }
+            let reason = if name.starts_with('.') {
+                "hidden"
+            } else {
+                "ignored"
+            };
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            filtered.push(format!("{} ({reason})", relative.display()));
+        }
+    }
+    filtered.sort();
+    filtered.truncate(MAX_FILTERED_DIRECTORIES);
+    filtered
+}
+
+/// Label for files with `extension` that the language filter dropped.
+pub fn extension_filtered(extension: &str) -> String {
+    format!("*.{extension} (extension filter)")
+}
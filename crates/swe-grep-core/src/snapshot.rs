@@ -0,0 +1,126 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::search;
+
+/// Summary keys that change between identical searches.
+const VOLATILE_SUMMARY_KEYS: &[&str] = &["search_id", "startup_stats", "reward"];
+
+/// Keys whose string values are file paths, wherever they appear.
+const PATH_KEYS: &[&str] = &["path", "root", "archive_path"];
+
+/// Rewrites a serialized summary for `--format snapshot`: volatile fields are dropped, search
+/// roots become `[root]` (`[root1]`, `[root2]`, … for several), and path separators are `/`.
+#[derive(Debug)]
+pub struct Snapshot {
+    /// Spellings of each root and their placeholder, longest spelling first.
+    roots: Vec<(String, String)>,
+}
+
+impl Snapshot {
+    /// Placeholders for the searched `roots`, the current directory when none were given.
+    pub fn new(roots: &[PathBuf]) -> Self {
+        let roots = if roots.is_empty() {
+            std::env::current_dir().into_iter().collect()
+        } else {
+            roots.to_vec()
+        };
+        // Summaries spell roots canonically; an absolute root as given may differ (symlinks).
+        let mut resolved: Vec<(PathBuf, Vec<&Path>)> = Vec::new();
+        for root in &roots {
+            let canonical = search::canonicalize_path(root).unwrap_or_else(|_| root.clone());
+            let given = Some(root.as_path()).filter(|root| root.is_absolute());
+            match resolved
+                .iter_mut()
+                .find(|(existing, _)| *existing == canonical)
+            {
+                Some((_, spellings)) => spellings.extend(given),
+                None => resolved.push((canonical, given.into_iter().collect())),
+            }
+        }
+        let mut spellings = Vec::new();
+        for (index, (canonical, given)) in resolved.iter().enumerate() {
+            let placeholder = if resolved.len() == 1 {
+                "[root]".to_string()
+            } else {
+                format!("[root{}]", index + 1)
+            };
+            for spelling in given.iter().copied().chain([canonical.as_path()]) {
+                spellings.push((spelling.display().to_string(), placeholder.clone()));
+            }
+        }
+        // Longest first, so a root nested in another is replaced before its parent.
+        spellings.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        spellings.dedup();
+        Self { roots: spellings }
+    }
+
+    /// Apply the snapshot rules to `value`, the output of `FieldSelection::to_value`.
+    pub fn apply(&self, mut value: Value) -> Value {
+        // Roots are replaced first, while they still use the platform's separators.
+        self.normalize(&mut value);
+        if let Value::Object(summary) = &mut value {
+            for key in VOLATILE_SUMMARY_KEYS {
+                summary.remove(*key);
+            }
+            if let Some(Value::Object(stats)) = summary.get_mut("stage_stats") {
+                strip_stage_timings(stats);
+            }
+            for key in ["fd_candidates", "ast_hits"] {
+                if let Some(Value::Array(entries)) = summary.get_mut(key) {
+                    for entry in entries {
+                        match entry {
+                            Value::Array(pair) => pair.iter_mut().take(1).for_each(slash_path),
+                            other => slash_path(other),
+                        }
+                    }
+                }
+            }
+        }
+        value
+    }
+
+    /// Replace root spellings in every string, and separators in path-valued keys.
+    fn normalize(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => {
+                for (spelling, placeholder) in &self.roots {
+                    if text.contains(spelling.as_str()) {
+                        *text = text.replace(spelling.as_str(), placeholder);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.normalize(item)),
+            Value::Object(map) => {
+                for (key, item) in map.iter_mut() {
+                    self.normalize(item);
+                    if PATH_KEYS.contains(&key.as_str()) {
+                        slash_path(item);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Drop latencies and the stage reward, including per-language latencies.
+fn strip_stage_timings(stats: &mut Map<String, Value>) {
+    stats.retain(|key, _| !key.ends_with("_ms") && key != "reward");
+    if let Some(Value::Object(languages)) = stats.get_mut("language_metrics") {
+        for metrics in languages.values_mut() {
+            if let Value::Object(metrics) = metrics {
+                metrics.remove("latency");
+            }
+        }
+    }
+}
+
+fn slash_path(value: &mut Value) {
+    if let Value::String(path) = value
+        && path.contains('\\')
+    {
+        *path = path.replace('\\', "/");
+    }
+}
@@ -0,0 +1,197 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Bytes read at a time from a line before the requested range.
+const SKIP_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Read a source file for snippets, context windows, and bodies, normalized as below.
+pub fn read(path: &Path) -> io::Result<String> {
+    fs::read_to_string(path).map(normalize)
+}
+
+/// Drop a leading UTF-8 byte order mark and turn CRLF line endings into LF.
+///
+/// Lone carriage returns are kept: ripgrep ends lines only at `\n`, and line numbers must stay
+/// in step with its matches.
+pub fn normalize(contents: String) -> String {
+    let contents = match contents.strip_prefix('\u{feff}') {
+        Some(rest) => rest.to_string(),
+        None => contents,
+    };
+    if contents.contains("\r\n") {
+        contents.replace("\r\n", "\n")
+    } else {
+        contents
+    }
+}
+
+/// A line range of a file, normalized like `read`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FileRange {
+    /// The file, relative to the root it was read from.
+    pub path: String,
+    /// First line returned, 1-based.
+    pub start: usize,
+    /// Last line returned, inclusive; below the requested end when the file is shorter or the
+    /// byte budget ran out, and `start - 1` when not even the first line fit.
+    pub end: usize,
+    pub content: String,
+    /// The byte budget cut the range short.
+    #[serde(default)]
+    pub truncated: bool,
+    /// Secrets replaced with `[REDACTED]` in `content`; see `serve --redact-secrets`.
+    #[serde(default)]
+    pub secrets_redacted: usize,
+}
+
+/// Why a line range could not be read.
+#[derive(Debug)]
+pub enum RangeError {
+    /// `start` is 0 or `end` precedes it.
+    InvalidRange {
+        start: usize,
+        end: usize,
+    },
+    NotFound(PathBuf),
+    NotAFile(PathBuf),
+    /// The file holds NUL bytes.
+    Binary(PathBuf),
+    /// The file has fewer than `start` lines.
+    PastEnd {
+        start: usize,
+        lines: usize,
+    },
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRange { start, end } => {
+                write!(f, "invalid line range {start}-{end}: lines start at 1")
+            }
+            Self::NotFound(path) => write!(f, "{} does not exist", path.display()),
+            Self::NotAFile(path) => write!(f, "{} is not a regular file", path.display()),
+            Self::Binary(path) => write!(f, "{} is a binary file", path.display()),
+            Self::PastEnd { start, lines } => {
+                write!(
+                    f,
+                    "line {start} is past the end of the file ({lines} lines)"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+/// Read lines `start..=end` (1-based; `end` defaults to the end of the file) of `path`,
+/// stopping before the content would exceed `max_bytes`. Lines past `end` are never read, so
+/// large files cost only the prefix up to the range, and no more of a line is held in memory
+/// than could still fit.
+pub fn read_range(
+    path: &Path,
+    display: String,
+    start: usize,
+    end: Option<usize>,
+    max_bytes: usize,
+) -> anyhow::Result<FileRange> {
+    if start == 0 || end.is_some_and(|end| end < start) {
+        return Err(RangeError::InvalidRange {
+            start,
+            end: end.unwrap_or(start),
+        }
+        .into());
+    }
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(RangeError::NotFound(PathBuf::from(&display)).into());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    if !file.metadata()?.is_file() {
+        return Err(RangeError::NotAFile(PathBuf::from(&display)).into());
+    }
+
+    let mut reader = BufReader::new(file);
+    let mut range = FileRange {
+        path: display,
+        start,
+        end: start - 1,
+        content: String::new(),
+        truncated: false,
+        secrets_redacted: 0,
+    };
+    let mut buffer = Vec::new();
+    let mut line_number = 0;
+    while end.is_none_or(|end| line_number < end) {
+        buffer.clear();
+        let skipping = line_number + 1 < start;
+        // Lines before the range are scanned a chunk at a time; a line in it is read only until
+        // it cannot fit, even after dropping a BOM and a CR.
+        let limit = if skipping {
+            SKIP_CHUNK_BYTES
+        } else {
+            max_bytes - range.content.len() + "\u{feff}\r".len() + 1
+        };
+        let complete = read_line_capped(&mut reader, &mut buffer, limit)?;
+        if buffer.is_empty() {
+            break;
+        }
+        if buffer.contains(&0) {
+            return Err(RangeError::Binary(PathBuf::from(&range.path)).into());
+        }
+        if skipping {
+            if complete {
+                line_number += 1;
+            }
+            continue;
+        }
+        line_number += 1;
+        if !complete {
+            range.truncated = true;
+            break;
+        }
+        let mut line = String::from_utf8_lossy(&buffer).into_owned();
+        if line_number == 1 && line.starts_with('\u{feff}') {
+            line.remove(0);
+        }
+        if line.ends_with("\r\n") {
+            line.truncate(line.len() - 2);
+            line.push('\n');
+        }
+        if range.content.len() + line.len() > max_bytes {
+            range.truncated = true;
+            break;
+        }
+        range.content.push_str(&line);
+        range.end = line_number;
+    }
+    if line_number < start {
+        return Err(RangeError::PastEnd {
+            start,
+            lines: line_number,
+        }
+        .into());
+    }
+    Ok(range)
+}
+
+/// Append the next line of `reader`, newline included, to `buffer`, reading at most `limit`
+/// bytes; returns whether the line ended, at a newline or the end of the file, within them.
+fn read_line_capped(
+    reader: &mut impl BufRead,
+    buffer: &mut Vec<u8>,
+    limit: usize,
+) -> io::Result<bool> {
+    let read = reader
+        .by_ref()
+        .take(limit as u64)
+        .read_until(b'\n', buffer)?;
+    Ok(read < limit || buffer.ends_with(b"\n"))
+}
@@ -0,0 +1,187 @@
+//! Where the hint state of a repository partition is kept: a file in the cache directory, or a
+//! store several machines share so ephemeral CI agents start from what others learned.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use fs2::FileExt;
+
+use crate::config::StateConfig;
+use crate::fingerprint::RepoFingerprint;
+
+#[cfg(feature = "s3-state")]
+mod object;
+#[cfg(feature = "redis-state")]
+mod redis;
+
+/// Environment variable naming a shared store; overrides `[state] store`.
+pub const STATE_STORE_ENV: &str = "SWE_GREP_STATE_STORE";
+/// Hint state file inside a repository's cache partition: zstd-compressed JSON.
+pub const STATE_FILE: &str = "state.json.zst";
+/// Advisory lock held while a process merges its observations into the state file.
+const STATE_LOCK_FILE: &str = "state.lock";
+/// Attempts a shared store makes to save before giving up on concurrent writers.
+#[cfg(any(feature = "redis-state", feature = "s3-state"))]
+const MAX_UPDATE_ATTEMPTS: usize = 5;
+
+/// Turns the stored state into the state to store; `None` leaves the store unchanged.
+pub type Update<'a> = dyn FnMut(Option<&[u8]>) -> Result<Option<Vec<u8>>> + 'a;
+
+/// Storage for the compressed state of one repository partition.
+pub trait StateStore: Send + Sync {
+    /// Where the state lives, for logs.
+    fn location(&self) -> String;
+
+    /// The stored state, or `None` when nothing was saved yet.
+    fn load(&self) -> Result<Option<Vec<u8>>>;
+
+    /// Replace the stored state with what `update` makes of it, atomically with respect to
+    /// other writers. Shared stores call `update` again with the newer state when another
+    /// writer saved in between.
+    fn update(&self, update: &mut Update<'_>) -> Result<()>;
+}
+
+/// The store holding `fingerprint`'s state: its partition of `cache_dir`, or the shared store
+/// `SWE_GREP_STATE_STORE` or `config.store` names. Shared stores key the state by
+/// [`RepoFingerprint::shared_key`] (origin, prefix, and commit), falling back to the local
+/// partition key outside a git checkout with an origin.
+pub fn open(
+    config: &StateConfig,
+    fingerprint: &RepoFingerprint,
+    cache_dir: &Path,
+) -> Result<Arc<dyn StateStore>> {
+    let url = std::env::var(STATE_STORE_ENV)
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .or_else(|| config.store.clone());
+    let Some(url) = url else {
+        return Ok(Arc::new(FileStateStore::new(
+            &fingerprint.state_dir(cache_dir),
+        )));
+    };
+    let url = url.trim();
+    let key = fingerprint
+        .shared_key()
+        .unwrap_or_else(|| fingerprint.partition());
+    let (scheme, _) = url
+        .split_once("://")
+        .with_context(|| format!("state store `{}` is not a URL", redact_userinfo(url)))?;
+    match scheme {
+        "redis" | "rediss" => open_redis(url, &key, config),
+        "s3" | "gs" => open_object(url, &key, config),
+        _ => anyhow::bail!(
+            "unsupported state store `{}`: expected redis://, s3://, or gs://",
+            redact_userinfo(url)
+        ),
+    }
+}
+
+/// `url` with its `user:password@` part masked, for errors that reach logs and responses.
+pub(crate) fn redact_userinfo(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    let authority_end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    match rest[..authority_end].rfind('@') {
+        Some(at) => format!("{scheme}://***@{}", &rest[at + 1..]),
+        None => url.to_string(),
+    }
+}
+
+#[cfg(feature = "redis-state")]
+fn open_redis(url: &str, key: &str, config: &StateConfig) -> Result<Arc<dyn StateStore>> {
+    Ok(Arc::new(redis::RedisStateStore::from_url(
+        url,
+        key,
+        config.ttl_secs,
+    )?))
+}
+
+#[cfg(not(feature = "redis-state"))]
+fn open_redis(url: &str, _key: &str, _config: &StateConfig) -> Result<Arc<dyn StateStore>> {
+    anyhow::bail!(
+        "state store `{}` requires swe-grep built with the `redis-state` feature",
+        redact_userinfo(url)
+    )
+}
+
+#[cfg(feature = "s3-state")]
+fn open_object(url: &str, key: &str, config: &StateConfig) -> Result<Arc<dyn StateStore>> {
+    Ok(Arc::new(object::ObjectStateStore::from_url(
+        url, key, config,
+    )?))
+}
+
+#[cfg(not(feature = "s3-state"))]
+fn open_object(url: &str, _key: &str, _config: &StateConfig) -> Result<Arc<dyn StateStore>> {
+    anyhow::bail!(
+        "state store `{}` requires swe-grep built with the `s3-state` feature",
+        redact_userinfo(url)
+    )
+}
+
+/// `state.json.zst` in a partition directory, merged under an advisory lock on `state.lock`.
+pub struct FileStateStore {
+    path: PathBuf,
+    lock_path: PathBuf,
+}
+
+impl FileStateStore {
+    pub fn new(state_dir: &Path) -> Self {
+        Self {
+            path: state_dir.join(STATE_FILE),
+            lock_path: state_dir.join(STATE_LOCK_FILE),
+        }
+    }
+}
+
+impl StateStore for FileStateStore {
+    fn location(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>> {
+        match fs::read(&self.path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", self.path.display())),
+        }
+    }
+
+    fn update(&self, update: &mut Update<'_>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create cache directory {}", parent.display())
+            })?;
+        }
+        let lock = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&self.lock_path)
+            .with_context(|| format!("failed to open {}", self.lock_path.display()))?;
+        lock.lock_exclusive()
+            .with_context(|| format!("failed to lock {}", self.lock_path.display()))?;
+
+        let written = self.load().and_then(|current| {
+            let Some(contents) = update(current.as_deref())? else {
+                return Ok(());
+            };
+            let tmp_path = self.path.with_extension("zst.tmp");
+            fs::write(&tmp_path, contents)
+                .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+            fs::rename(&tmp_path, &self.path).with_context(|| {
+                format!(
+                    "failed to move persistent state into place {}",
+                    self.path.display()
+                )
+            })
+        });
+        if let Err(err) = FileExt::unlock(&lock) {
+            tracing::debug!(path = %self.lock_path.display(), error = %err, "failed to release state lock");
+        }
+        written
+    }
+}
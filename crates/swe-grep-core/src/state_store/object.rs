@@ -0,0 +1,381 @@
+//! S3 and Google Cloud Storage backend: the state is one object, written back only while it
+//! still has the ETag (S3) or generation (GCS) it was read with. Requests are signed with AWS
+//! Signature Version 4, which GCS accepts with HMAC keys.
+
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use reqwest::Url;
+use reqwest::blocking::Client;
+use reqwest::header::HeaderMap;
+use sha2::{Digest, Sha256};
+
+use super::{MAX_UPDATE_ATTEMPTS, STATE_FILE, StateStore, Update};
+use crate::config::StateConfig;
+
+const GCS_HOST: &str = "storage.googleapis.com";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest part of an error response quoted in the error.
+const MAX_ERROR_BODY: usize = 300;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Provider {
+    S3,
+    Gcs,
+}
+
+/// Access keys from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, and optionally
+/// `AWS_SESSION_TOKEN`; for GCS, an HMAC key pair in the same variables.
+struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+impl Credentials {
+    fn from_env() -> Result<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")
+                .context("the object state store needs AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")
+                .context("the object state store needs AWS_SECRET_ACCESS_KEY")?,
+            session_token: var("AWS_SESSION_TOKEN"),
+        })
+    }
+}
+
+struct Response {
+    status: u16,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name)?.to_str().ok()
+    }
+}
+
+pub struct ObjectStateStore {
+    provider: Provider,
+    client: Client,
+    /// `scheme://name[:port]` requests go to.
+    origin: String,
+    /// `name[:port]` as sent in the `Host` header.
+    host: String,
+    /// Percent-encoded path of the object.
+    path: String,
+    region: String,
+    credentials: Credentials,
+}
+
+impl ObjectStateStore {
+    /// Store for `key` in the bucket `url` names (`s3://bucket[/prefix]` or
+    /// `gs://bucket[/prefix]`), at `<prefix>/repos/<key>/state.json.zst`.
+    pub fn from_url(url: &str, key: &str, config: &StateConfig) -> Result<Self> {
+        let (provider, rest) = if let Some(rest) = url.strip_prefix("s3://") {
+            (Provider::S3, rest)
+        } else if let Some(rest) = url.strip_prefix("gs://") {
+            (Provider::Gcs, rest)
+        } else {
+            anyhow::bail!("state store `{url}` is not an s3:// or gs:// URL");
+        };
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            anyhow::bail!("state store `{url}` has no bucket");
+        }
+        let object: Vec<&str> = prefix
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .chain(["repos", key, STATE_FILE])
+            .collect();
+        let object = object
+            .iter()
+            .map(|segment| uri_encode(segment))
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let region = config
+            .region
+            .clone()
+            .or_else(|| std::env::var("AWS_REGION").ok())
+            .or_else(|| std::env::var("AWS_DEFAULT_REGION").ok())
+            .filter(|region| !region.is_empty())
+            .unwrap_or_else(|| match provider {
+                Provider::S3 => "us-east-1".to_string(),
+                Provider::Gcs => "auto".to_string(),
+            });
+        let (origin, path) = match (&config.endpoint, provider) {
+            (Some(endpoint), _) => (
+                endpoint_origin(endpoint)?,
+                format!("/{}/{object}", uri_encode(bucket)),
+            ),
+            (None, Provider::S3) => (
+                Url::parse(&format!("https://{bucket}.s3.{region}.amazonaws.com"))
+                    .with_context(|| format!("state store `{url}` has an invalid bucket"))?,
+                format!("/{object}"),
+            ),
+            (None, Provider::Gcs) => (
+                Url::parse(&format!("https://{GCS_HOST}")).expect("GCS origin is a valid URL"),
+                format!("/{}/{object}", uri_encode(bucket)),
+            ),
+        };
+        let host = origin.host_str().context("state store has no host")?;
+        // The signed `Host` must be what reqwest sends, which omits the scheme's default port.
+        let host = match origin.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        };
+        let client = Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .context("failed to set up the object store client")?;
+        Ok(Self {
+            provider,
+            client,
+            origin: format!("{}://{host}", origin.scheme()),
+            host,
+            path,
+            region,
+            credentials: Credentials::from_env()?,
+        })
+    }
+
+    /// Send a signed request for the object.
+    fn request(&self, method: &str, extra: &[(&str, String)], body: &[u8]) -> Result<Response> {
+        let (date, time) = utc_timestamp(SystemTime::now());
+        let amz_date = format!("{date}T{time}Z");
+        let payload_hash = hex(&Sha256::digest(body));
+        let mut signed = vec![
+            ("host", self.host.clone()),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            signed.push(("x-amz-security-token", token.clone()));
+        }
+        let canonical_headers: String = signed
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        let signed_names = signed
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = format!(
+            "{method}\n{}\n\n{canonical_headers}\n{signed_names}\n{payload_hash}",
+            self.path
+        );
+        let scope = format!("{date}/{}/s3/aws4_request", self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let mut signing_key = hmac(
+            format!("AWS4{}", self.credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            signing_key = hmac(&signing_key, part.as_bytes());
+        }
+        let signature = hex(&hmac(&signing_key, string_to_sign.as_bytes()));
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_names}, Signature={signature}",
+            self.credentials.access_key_id
+        );
+
+        let mut headers: Vec<(&str, String)> = signed
+            .into_iter()
+            .filter(|(name, _)| *name != "host")
+            .collect();
+        headers.push(("Authorization", authorization));
+        headers.extend(extra.iter().cloned());
+
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .with_context(|| format!("invalid HTTP method {method}"))?;
+        let mut request = self
+            .client
+            .request(method.clone(), self.location())
+            .body(body.to_vec());
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .with_context(|| format!("failed to send {method} {}", self.location()))?;
+        Ok(Response {
+            status: response.status().as_u16(),
+            headers: response.headers().clone(),
+            body: response
+                .bytes()
+                .with_context(|| format!("failed to read the response from {}", self.origin))?
+                .to_vec(),
+        })
+    }
+
+    /// The object and the precondition that it is still unchanged (its ETag or generation);
+    /// `None` and no precondition when it does not exist yet.
+    fn fetch(&self) -> Result<(Option<Vec<u8>>, Option<String>)> {
+        let response = self.request("GET", &[], &[])?;
+        match response.status {
+            200 => {
+                let version = match self.provider {
+                    Provider::S3 => response.header("etag"),
+                    Provider::Gcs => response.header("x-goog-generation"),
+                }
+                .map(str::to_string);
+                Ok((Some(response.body), version))
+            }
+            404 => Ok((None, None)),
+            _ => Err(self.failure("GET", &response)),
+        }
+    }
+
+    fn failure(&self, method: &str, response: &Response) -> anyhow::Error {
+        let body = String::from_utf8_lossy(&response.body);
+        let excerpt: String = body.chars().take(MAX_ERROR_BODY).collect();
+        anyhow::anyhow!(
+            "{method} {} returned HTTP {}: {}",
+            self.location(),
+            response.status,
+            excerpt.trim()
+        )
+    }
+}
+
+impl StateStore for ObjectStateStore {
+    fn location(&self) -> String {
+        format!("{}{}", self.origin, self.path)
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>> {
+        Ok(self.fetch()?.0)
+    }
+
+    fn update(&self, update: &mut Update<'_>) -> Result<()> {
+        for _ in 0..MAX_UPDATE_ATTEMPTS {
+            let (current, version) = self.fetch()?;
+            let Some(contents) = update(current.as_deref())? else {
+                return Ok(());
+            };
+            let precondition = match (self.provider, version) {
+                (Provider::S3, Some(etag)) => ("If-Match", etag),
+                (Provider::S3, None) => ("If-None-Match", "*".to_string()),
+                (Provider::Gcs, generation) => (
+                    "x-goog-if-generation-match",
+                    generation.unwrap_or_else(|| "0".to_string()),
+                ),
+            };
+            let response = self.request("PUT", &[precondition], &contents)?;
+            match response.status {
+                200 => return Ok(()),
+                // Another writer saved since the read; merge into its state instead.
+                409 | 412 => continue,
+                _ => return Err(self.failure("PUT", &response)),
+            }
+        }
+        anyhow::bail!(
+            "gave up saving state to {} after {MAX_UPDATE_ATTEMPTS} concurrent updates",
+            self.location()
+        )
+    }
+}
+
+/// Parse a custom `endpoint`. Requests carry signed credentials and the state itself, so plain
+/// `http://` is accepted only for servers on this machine, such as a local MinIO.
+fn endpoint_origin(endpoint: &str) -> Result<Url> {
+    let origin = Url::parse(endpoint.trim_end_matches('/'))
+        .with_context(|| format!("state store endpoint `{endpoint}` is not a URL"))?;
+    let host = origin
+        .host_str()
+        .with_context(|| format!("state store endpoint `{endpoint}` has no host"))?;
+    let loopback = host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback());
+    match origin.scheme() {
+        "https" => Ok(origin),
+        "http" if loopback => Ok(origin),
+        "http" => anyhow::bail!(
+            "state store endpoint `{endpoint}` must use https://; plain http:// is only \
+             accepted for loopback hosts"
+        ),
+        _ => anyhow::bail!("state store endpoint `{endpoint}` must be an https:// URL"),
+    }
+}
+
+/// Percent-encode everything but unreserved characters, as SigV4 canonical URIs require.
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+                (byte as char).to_string()
+            } else {
+                format!("%{byte:02X}")
+            }
+        })
+        .collect()
+}
+
+fn hmac(key: &[u8], message: &[u8]) -> [u8; 32] {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner = Sha256::new()
+        .chain_update(block.map(|byte| byte ^ 0x36))
+        .chain_update(message)
+        .finalize();
+    Sha256::new()
+        .chain_update(block.map(|byte| byte ^ 0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// `YYYYMMDD` and `HHMMSS` of `time` in UTC.
+fn utc_timestamp(time: SystemTime) -> (String, String) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (
+        format!("{year:04}{month:02}{day:02}"),
+        format!(
+            "{:02}{:02}{:02}",
+            secs_of_day / 3_600,
+            secs_of_day % 3_600 / 60,
+            secs_of_day % 60
+        ),
+    )
+}
@@ -0,0 +1,89 @@
+//! Redis backend: the state is one string value, saved with `WATCH`/`MULTI`/`EXEC` so
+//! concurrent savers retry against each other's state instead of overwriting it.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+use super::{MAX_UPDATE_ATTEMPTS, StateStore, Update};
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+/// Prefix of every key swe-grep writes.
+const KEY_PREFIX: &str = "swe-grep:state:";
+
+pub struct RedisStateStore {
+    client: redis::Client,
+    /// The server URL with its credentials masked, for logs.
+    url: String,
+    key: String,
+    ttl_secs: Option<u64>,
+}
+
+impl RedisStateStore {
+    /// Store for `key` on the server `url`
+    /// (`redis[s]://[[user]:password@]host[:port][/db]`; `rediss://` connects over TLS).
+    pub fn from_url(url: &str, key: &str, ttl_secs: Option<u64>) -> Result<Self> {
+        let shown = super::redact_userinfo(url);
+        let client = redis::Client::open(url)
+            .with_context(|| format!("state store `{shown}` is not a valid redis URL"))?;
+        Ok(Self {
+            client,
+            url: shown,
+            key: format!("{KEY_PREFIX}{key}"),
+            ttl_secs,
+        })
+    }
+
+    fn connect(&self) -> Result<redis::Connection> {
+        let connection = self
+            .client
+            .get_connection_with_timeout(CONNECT_TIMEOUT)
+            .with_context(|| format!("failed to connect to redis at {}", self.url))?;
+        connection.set_read_timeout(Some(IO_TIMEOUT))?;
+        connection.set_write_timeout(Some(IO_TIMEOUT))?;
+        Ok(connection)
+    }
+}
+
+impl StateStore for RedisStateStore {
+    fn location(&self) -> String {
+        format!("{} {}", self.url, self.key)
+    }
+
+    fn load(&self) -> Result<Option<Vec<u8>>> {
+        let mut connection = self.connect()?;
+        Ok(redis::cmd("GET").arg(&self.key).query(&mut connection)?)
+    }
+
+    fn update(&self, update: &mut Update<'_>) -> Result<()> {
+        let mut connection = self.connect()?;
+        for _ in 0..MAX_UPDATE_ATTEMPTS {
+            redis::cmd("WATCH")
+                .arg(&self.key)
+                .query::<()>(&mut connection)?;
+            let current: Option<Vec<u8>> =
+                redis::cmd("GET").arg(&self.key).query(&mut connection)?;
+            let Some(contents) = update(current.as_deref())? else {
+                redis::cmd("UNWATCH").query::<()>(&mut connection)?;
+                return Ok(());
+            };
+            let mut transaction = redis::pipe();
+            transaction.atomic();
+            match self.ttl_secs {
+                Some(ttl) => transaction.set_ex(&self.key, contents, ttl),
+                None => transaction.set(&self.key, contents),
+            }
+            .ignore();
+            // A nil reply means the key changed after WATCH and the transaction was dropped.
+            let applied: Option<()> = transaction.query(&mut connection)?;
+            if applied.is_some() {
+                return Ok(());
+            }
+        }
+        anyhow::bail!(
+            "gave up saving state to {} after {MAX_UPDATE_ATTEMPTS} concurrent updates",
+            self.location()
+        )
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::cli::StatsArgs;
+use crate::search;
+
+/// How long stored stats are reused while HEAD stays put, so uncommitted edits show up within
+/// the hour.
+pub const MAX_AGE_MS: u64 = 60 * 60 * 1000;
+/// Repositories with at most this many files are probed exhaustively by default.
+pub const TINY_MAX_FILES: usize = 1_000;
+/// Repositories with at least this many files get tighter default caps and scoped probes only.
+pub const HUGE_MIN_FILES: usize = 100_000;
+
+/// Size class that picks the default match limits when `max_matches` is not set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RepoScale {
+    Tiny,
+    Typical,
+    Huge,
+}
+
+/// Size and language makeup of a repository.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RepoStats {
+    pub files: usize,
+    /// Lines across text files; binary files count toward `files` and `bytes` only.
+    pub lines: usize,
+    pub bytes: u64,
+    /// Per-language totals, most files first; `other` collects unrecognised extensions.
+    pub languages: Vec<LanguageStats>,
+    /// HEAD commit the walk saw; stored stats are recollected once it moves.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub head: Option<String>,
+    /// Milliseconds since the Unix epoch when the walk ran.
+    pub collected_at_ms: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+}
+
+impl RepoStats {
+    /// Walk `root` the way the indexer does: ignore files are honoured and symlinks are not
+    /// followed. Hidden entries are skipped too, which keeps `.git` and swe-grep's own cache out.
+    pub fn collect(root: &Path, head: Option<String>, collected_at_ms: u64) -> Self {
+        let mut stats = Self {
+            head,
+            collected_at_ms,
+            ..Self::default()
+        };
+        let mut languages: BTreeMap<&'static str, (usize, usize)> = BTreeMap::new();
+        let walker = WalkBuilder::new(root)
+            .follow_links(false)
+            .standard_filters(true)
+            .build();
+        for entry in walker.flatten() {
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                continue;
+            }
+            let path = entry.path();
+            let lines = count_lines(path).unwrap_or(0);
+            stats.files += 1;
+            stats.lines += lines;
+            stats.bytes += entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+            let language = search::detect_language_from_path(path).unwrap_or("other");
+            let totals = languages.entry(language).or_default();
+            totals.0 += 1;
+            totals.1 += lines;
+        }
+        stats.languages = languages
+            .into_iter()
+            .map(|(language, (files, lines))| LanguageStats {
+                language: language.to_string(),
+                files,
+                lines,
+            })
+            .collect();
+        stats
+            .languages
+            .sort_by(|a, b| b.files.cmp(&a.files).then(a.language.cmp(&b.language)));
+        stats
+    }
+
+    pub fn scale(&self) -> RepoScale {
+        if self.files <= TINY_MAX_FILES {
+            RepoScale::Tiny
+        } else if self.files >= HUGE_MIN_FILES {
+            RepoScale::Huge
+        } else {
+            RepoScale::Typical
+        }
+    }
+
+    /// Whether stored stats still describe the tree at `head` as of `now_ms`.
+    pub fn is_fresh(&self, head: Option<&str>, now_ms: u64) -> bool {
+        self.head.as_deref() == head && now_ms.saturating_sub(self.collected_at_ms) < MAX_AGE_MS
+    }
+
+    /// Add another root's totals, as for a multi-root search.
+    pub fn absorb(&mut self, other: RepoStats) {
+        self.files += other.files;
+        self.lines += other.lines;
+        self.bytes += other.bytes;
+        for theirs in other.languages {
+            match self
+                .languages
+                .iter_mut()
+                .find(|ours| ours.language == theirs.language)
+            {
+                Some(ours) => {
+                    ours.files += theirs.files;
+                    ours.lines += theirs.lines;
+                }
+                None => self.languages.push(theirs),
+            }
+        }
+        self.languages
+            .sort_by(|a, b| b.files.cmp(&a.files).then(a.language.cmp(&b.language)));
+        self.head = None;
+        self.collected_at_ms = self.collected_at_ms.min(other.collected_at_ms);
+    }
+}
+
+/// Lines in `path`, counting a final line without a newline; `None` for unreadable files and
+/// binary ones (a NUL byte in the first block).
+fn count_lines(path: &Path) -> Option<usize> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut lines = 0;
+    let mut first = true;
+    let mut last = b'\n';
+    loop {
+        let read = file.read(&mut buffer).ok()?;
+        if read == 0 {
+            break;
+        }
+        let chunk = &buffer[..read];
+        if first && chunk.contains(&0) {
+            return None;
+        }
+        first = false;
+        lines += chunk.iter().filter(|&&byte| byte == b'\n').count();
+        last = chunk[read - 1];
+    }
+    if last != b'\n' {
+        lines += 1;
+    }
+    Some(lines)
+}
+
+/// Entry point for `swe-grep stats`.
+pub async fn run(args: StatsArgs) -> Result<RepoStats> {
+    let root = args
+        .path
+        .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
+    let root = search::canonicalize_path(&root).with_context(|| {
+        format!(
+            "failed to canonicalize repository root path: {}",
+            root.display()
+        )
+    })?;
+    let cache_dir = args
+        .cache_dir
+        .unwrap_or_else(|| root.join(".swe-grep-cache"));
+    // The state store may wait on a file lock or the network, and a refresh walks the tree.
+    tokio::task::spawn_blocking(move || {
+        search::repo_stats(&root, &cache_dir, args.refresh, args.trust_repo_state)
+    })
+    .await
+    .context("stats task failed")?
+}
@@ -0,0 +1,74 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+/// Checked-out git submodules under `root`, relative to it, from `.gitmodules` and the
+/// `.gitmodules` of every submodule found, parents before their children.
+///
+/// Submodules that are not initialized (no `.git` in their directory) are skipped, as are
+/// paths that are absolute or climb out of their repository.
+pub fn discover(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut found = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(repo) = pending.pop() {
+        let mut nested = Vec::new();
+        for path in declared(&root.join(&repo))? {
+            let relative = repo.join(path);
+            if !root.join(&relative).join(".git").exists() {
+                tracing::debug!(submodule = %relative.display(), "skipping uninitialized submodule");
+                continue;
+            }
+            nested.push(relative);
+        }
+        // Reversed so the stack yields each repository's submodules in file order.
+        pending.extend(nested.iter().rev().cloned());
+        found.extend(nested);
+    }
+    Ok(found)
+}
+
+/// `path` values of the `[submodule "..."]` sections in `repo/.gitmodules`.
+fn declared(repo: &Path) -> Result<Vec<PathBuf>> {
+    let file = repo.join(".gitmodules");
+    let contents = match fs::read_to_string(&file) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("failed to read {}", file.display())),
+    };
+    let mut paths = Vec::new();
+    let mut in_submodule = false;
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+        if let Some(section) = line.strip_prefix('[') {
+            in_submodule = section.trim_start().starts_with("submodule");
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        if !in_submodule || !key.trim().eq_ignore_ascii_case("path") {
+            continue;
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(value);
+        let path = PathBuf::from(value);
+        let contained = path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+        if value.is_empty() || !contained {
+            tracing::warn!(path = value, file = %file.display(), "ignoring submodule path outside the repository");
+            continue;
+        }
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
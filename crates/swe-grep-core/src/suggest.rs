@@ -0,0 +1,328 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::identifier;
+use crate::search::{NextAction, detect_language_from_path};
+
+/// Upper bound on source files tokenized when no ctags file is available.
+const MAX_DICTIONARY_FILES: usize = 4_000;
+/// Files larger than this are skipped while building the dictionary.
+const MAX_DICTIONARY_FILE_BYTES: u64 = 256 * 1024;
+const MIN_IDENTIFIER_LENGTH: usize = 3;
+pub(crate) const MAX_SYMBOL_SUGGESTIONS: usize = 5;
+pub(crate) const MAX_LANGUAGE_HINTS: usize = 3;
+/// ctags files looked up at the repository root, in order.
+const TAG_FILES: &[&str] = &["tags", ".tags", "TAGS"];
+
+/// Alternatives offered when a search surfaces no hits.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(default)]
+pub struct Suggestions {
+    /// Closest identifiers in the repository, nearest first.
+    pub symbols: Vec<SymbolSuggestion>,
+    /// The symbol rewritten in other naming conventions.
+    pub case_variants: Vec<String>,
+    /// Languages present in the repository that the search did not target.
+    pub languages: Vec<LanguageHint>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SymbolSuggestion {
+    pub symbol: String,
+    /// Edit distance after ignoring case and `_`/`-` separators.
+    pub distance: usize,
+    /// First file the identifier was seen in, relative to the root.
+    pub path: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LanguageHint {
+    pub language: String,
+    pub files: usize,
+}
+
+impl Suggestions {
+    /// Build suggestions for `symbol` from the files under `root` (or only `scope`, when given).
+    pub(crate) fn collect(
+        root: &Path,
+        index_dir: &Path,
+        symbol: &str,
+        languages: &[String],
+        scope: Option<&[PathBuf]>,
+    ) -> Self {
+        let symbol = symbol.trim();
+        let case_variants = case_variants(symbol);
+        let dictionary = Dictionary::load(root, index_dir, scope);
+
+        let target = normalize(symbol);
+        let max_distance = (target.chars().count() / 3).clamp(1, 3);
+        let mut symbols: Vec<SymbolSuggestion> = dictionary
+            .identifiers
+            .iter()
+            .filter(|(candidate, _)| candidate.as_str() != symbol)
+            .filter_map(|(candidate, path)| {
+                let normalized = normalize(candidate);
+                if normalized.len().abs_diff(target.len()) > max_distance {
+                    return None;
+                }
+                let distance = edit_distance(&target, &normalized);
+                (distance <= max_distance).then(|| SymbolSuggestion {
+                    symbol: candidate.clone(),
+                    distance,
+                    path: path.clone(),
+                })
+            })
+            .collect();
+        symbols.sort_by(|a, b| a.distance.cmp(&b.distance).then(a.symbol.cmp(&b.symbol)));
+        symbols.truncate(MAX_SYMBOL_SUGGESTIONS);
+
+        let mut languages: Vec<LanguageHint> = dictionary
+            .languages
+            .into_iter()
+            .filter(|(language, _)| !languages.iter().any(|token| token == language))
+            .map(|(language, files)| LanguageHint { language, files })
+            .collect();
+        languages.sort_by(|a, b| b.files.cmp(&a.files).then(a.language.cmp(&b.language)));
+        languages.truncate(MAX_LANGUAGE_HINTS);
+
+        Self {
+            symbols,
+            case_variants,
+            languages,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty() && self.case_variants.is_empty() && self.languages.is_empty()
+    }
+
+    /// Follow-up actions for the closest symbols and the most common untargeted language.
+    pub(crate) fn next_actions(&self) -> Vec<NextAction> {
+        let mut actions: Vec<NextAction> = self
+            .symbols
+            .iter()
+            .map(|suggestion| NextAction::RefineSymbol {
+                suggestion: suggestion.symbol.clone(),
+            })
+            .collect();
+        if let Some(hint) = self.languages.first() {
+            actions.push(NextAction::TryLanguage {
+                lang: hint.language.clone(),
+            });
+        }
+        actions
+    }
+}
+
+#[derive(Default)]
+struct Dictionary {
+    /// Identifier -> first path it was seen in.
+    identifiers: HashMap<String, String>,
+    /// Language -> number of source files.
+    languages: BTreeMap<String, usize>,
+}
+
+impl Dictionary {
+    fn load(root: &Path, index_dir: &Path, scope: Option<&[PathBuf]>) -> Self {
+        let files = match scope {
+            Some(files) => files.iter().map(|path| root.join(path)).collect(),
+            None => source_files(root),
+        };
+
+        let mut dictionary = Self::default();
+        for path in &files {
+            if let Some(language) = detect_language_from_path(path) {
+                *dictionary
+                    .languages
+                    .entry(language.to_string())
+                    .or_default() += 1;
+            }
+        }
+
+        if scope.is_none() && (dictionary.load_index(index_dir) || dictionary.load_tags(root)) {
+            return dictionary;
+        }
+        for path in files.iter().take(MAX_DICTIONARY_FILES) {
+            dictionary.tokenize(root, path);
+        }
+        dictionary
+    }
+
+    /// Read the identifier dictionary persisted next to the Tantivy index, if one was built.
+    #[cfg(feature = "indexing")]
+    fn load_index(&mut self, index_dir: &Path) -> bool {
+        let symbols = match swe_grep_indexer::SymbolDictionary::load(index_dir) {
+            Ok(symbols) => symbols,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to load symbol dictionary");
+                return false;
+            }
+        };
+        for entry in symbols.iter() {
+            self.insert(&entry.symbol, &entry.path.display().to_string());
+        }
+        !symbols.is_empty()
+    }
+
+    #[cfg(not(feature = "indexing"))]
+    fn load_index(&mut self, _index_dir: &Path) -> bool {
+        false
+    }
+
+    /// Read symbol names from a ctags file; returns `false` when none is present.
+    fn load_tags(&mut self, root: &Path) -> bool {
+        let Some(contents) = TAG_FILES
+            .iter()
+            .map(|name| root.join(name))
+            .find(|path| path.is_file())
+            .and_then(|path| fs::read_to_string(path).ok())
+        else {
+            return false;
+        };
+        for line in contents.lines().filter(|line| !line.starts_with("!_")) {
+            let mut fields = line.split('\t');
+            if let (Some(name), Some(file)) = (fields.next(), fields.next()) {
+                self.insert(name, file);
+            }
+        }
+        true
+    }
+
+    fn tokenize(&mut self, root: &Path, path: &Path) {
+        let too_large = fs::metadata(path)
+            .map(|meta| meta.len() > MAX_DICTIONARY_FILE_BYTES)
+            .unwrap_or(true);
+        if too_large {
+            return;
+        }
+        let Ok(contents) = fs::read_to_string(path) else {
+            return;
+        };
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string();
+        for token in contents.split(|c: char| !identifier::is_continue(c)) {
+            if token.starts_with(identifier::is_start) {
+                self.insert(token, &relative);
+            }
+        }
+    }
+
+    fn insert(&mut self, name: &str, path: &str) {
+        if name.len() < MIN_IDENTIFIER_LENGTH || self.identifiers.contains_key(name) {
+            return;
+        }
+        self.identifiers
+            .insert(name.to_string(), path.trim_start_matches("./").to_string());
+    }
+}
+
+/// Source files under `root`, honouring ignore files and skipping hidden entries.
+fn source_files(root: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkBuilder::new(root)
+        .standard_filters(true)
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+        .map(|entry| entry.into_path())
+        .filter(|path| detect_language_from_path(path).is_some())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Split an identifier into lowercase words across snake, kebab, and camel case.
+fn split_words(symbol: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous: Option<char> = None;
+    for ch in symbol.chars() {
+        if !ch.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous = None;
+            continue;
+        }
+        let boundary = ch.is_uppercase()
+            && previous.is_some_and(|prev| prev.is_lowercase() || prev.is_ascii_digit());
+        if boundary && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.extend(ch.to_lowercase());
+        previous = Some(ch);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// The symbol in snake, camel, Pascal, screaming snake, and kebab case, minus the original.
+fn case_variants(symbol: &str) -> Vec<String> {
+    let words = split_words(symbol);
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let snake = words.join("_");
+    let pascal: String = words.iter().map(|word| capitalize(word)).collect();
+    let camel: String = std::iter::once(words[0].clone())
+        .chain(words[1..].iter().map(|word| capitalize(word)))
+        .collect();
+    let candidates = [
+        snake.clone(),
+        camel,
+        pascal,
+        snake.to_uppercase(),
+        words.join("-"),
+    ];
+
+    let mut variants: Vec<String> = Vec::new();
+    for candidate in candidates {
+        if candidate != symbol && !variants.contains(&candidate) {
+            variants.push(candidate);
+        }
+    }
+    variants
+}
+
+fn normalize(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| !matches!(c, '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Levenshtein distance over characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
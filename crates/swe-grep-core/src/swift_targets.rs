@@ -0,0 +1,832 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+/// Directory levels searched below the root for `Package.swift` files and Xcode projects.
+const MANIFEST_DEPTH: usize = 4;
+/// SwiftPM target declarations that compile sources, and the directory each looks in by default.
+const PACKAGE_TARGET_KINDS: &[(&str, &str)] = &[
+    ("target", "Sources"),
+    ("executableTarget", "Sources"),
+    ("testTarget", "Tests"),
+    ("macro", "Sources"),
+    ("plugin", "Plugins"),
+];
+/// Alternatives SwiftPM accepts for `Sources` when a target has no `path:`.
+const PACKAGE_SOURCE_DIRS: &[&str] = &["Sources", "Source", "src", "srcs"];
+/// File extensions that belong to Swift package and Xcode targets.
+const TARGET_SOURCE_EXTENSIONS: &[&str] = &["swift", "m", "mm"];
+/// Suffix Xcode gives the bridging header it generates for a target.
+const BRIDGING_HEADER_SUFFIX: &str = "-Bridging-Header.h";
+/// Build-setting prefixes that stand for the project directory in `SWIFT_OBJC_BRIDGING_HEADER`.
+const PROJECT_DIR_VARIABLES: &[&str] = &[
+    "$(SRCROOT)/",
+    "${SRCROOT}/",
+    "$(PROJECT_DIR)/",
+    "${PROJECT_DIR}/",
+];
+
+/// The Swift package and Xcode targets under one search root, read on first use.
+///
+/// SwiftPM targets come from every `Package.swift` (`.target`, `.executableTarget`,
+/// `.testTarget`, `.macro`, `.plugin` with their `path:`, `sources:`, and `exclude:`); Xcode
+/// targets from the `Sources` build phases and synchronized folders of every `.xcodeproj`,
+/// including projects an `.xcworkspace` refers to outside the search depth. Bridging headers
+/// come from the projects' `SWIFT_OBJC_BRIDGING_HEADER` settings and from files named
+/// `<Target>-Bridging-Header.h`.
+pub struct SwiftTargets {
+    root: PathBuf,
+    loaded: Option<Loaded>,
+}
+
+#[derive(Default)]
+struct Loaded {
+    /// `Package.swift` files, relative to the root.
+    manifests: Vec<PathBuf>,
+    targets: Vec<SwiftTarget>,
+    /// Objective-C headers exposed to Swift, relative to the root.
+    bridging_headers: Vec<PathBuf>,
+}
+
+/// One target and the files it compiles, relative to the root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SwiftTarget {
+    pub name: String,
+    /// Directories whose files all belong to the target.
+    dirs: Vec<PathBuf>,
+    /// Files listed one by one.
+    files: Vec<PathBuf>,
+    /// Paths under `dirs` the target leaves out.
+    exclude: Vec<PathBuf>,
+}
+
+impl SwiftTarget {
+    fn contains(&self, path: &Path) -> bool {
+        self.files.iter().any(|file| file == path)
+            || (self.dirs.iter().any(|dir| path.starts_with(dir))
+                && !self
+                    .exclude
+                    .iter()
+                    .any(|excluded| path.starts_with(excluded)))
+    }
+
+    /// Source files of the target, directories walked in name order.
+    fn source_files(&self, root: &Path) -> Vec<PathBuf> {
+        let mut files = self.files.clone();
+        for dir in &self.dirs {
+            let mut found: Vec<PathBuf> = WalkBuilder::new(root.join(dir))
+                .build()
+                .flatten()
+                .filter(|entry| entry.file_type().is_some_and(|kind| kind.is_file()))
+                .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+                .filter(|path| {
+                    !self
+                        .exclude
+                        .iter()
+                        .any(|excluded| path.starts_with(excluded))
+                })
+                .collect();
+            found.sort();
+            files.extend(found);
+        }
+        files
+    }
+}
+
+impl SwiftTargets {
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            loaded: None,
+        }
+    }
+
+    /// Whether `path` is a source type that Swift package and Xcode targets compile.
+    pub fn is_target_source(path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| TARGET_SOURCE_EXTENSIONS.contains(&ext))
+    }
+
+    /// Names of the targets that compile `path` (relative to the root), in manifest order.
+    pub fn targets_for(&mut self, path: &Path) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for target in &self.load().targets {
+            if target.contains(path) && !names.contains(&target.name) {
+                names.push(target.name.clone());
+            }
+        }
+        names
+    }
+
+    /// Discovery candidates for a Swift search: every `Package.swift`, then up to `per_target`
+    /// files of each target that pass `allowed`, so one large target cannot crowd out the rest.
+    pub fn discover(&mut self, per_target: usize, allowed: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+        let root = self.root.clone();
+        let loaded = self.load();
+        let mut candidates = loaded.manifests.clone();
+        for target in &loaded.targets {
+            candidates.extend(
+                target
+                    .source_files(&root)
+                    .into_iter()
+                    .filter(|path| allowed(path))
+                    .take(per_target),
+            );
+        }
+        candidates
+    }
+
+    /// Objective-C headers that Swift code under the root sees through a bridging header.
+    pub fn bridging_headers(&mut self) -> &[PathBuf] {
+        &self.load().bridging_headers
+    }
+
+    /// Discovery candidates for the Objective-C half of a mixed target: each bridging header,
+    /// then the headers it imports by quoted path and the `.m`/`.mm` files beside them, up to
+    /// `per_header` files per bridging header that pass `allowed`.
+    pub fn bridged_sources(
+        &mut self,
+        per_header: usize,
+        allowed: impl Fn(&Path) -> bool,
+    ) -> Vec<PathBuf> {
+        let root = self.root.clone();
+        let mut candidates = Vec::new();
+        for header in &self.load().bridging_headers {
+            let mut files = vec![header.clone()];
+            for imported in quoted_imports(&root, header) {
+                let siblings = ["m", "mm"].map(|ext| imported.with_extension(ext));
+                files.push(imported);
+                files.extend(
+                    siblings
+                        .into_iter()
+                        .filter(|path| root.join(path).is_file()),
+                );
+            }
+            candidates.extend(
+                files
+                    .into_iter()
+                    .filter(|path| allowed(path))
+                    .take(per_header),
+            );
+        }
+        candidates
+    }
+
+    fn load(&mut self) -> &Loaded {
+        let root = &self.root;
+        self.loaded.get_or_insert_with(|| load(root))
+    }
+}
+
+fn load(root: &Path) -> Loaded {
+    let mut loaded = Loaded::default();
+    let mut projects: Vec<PathBuf> = Vec::new();
+    let walker = WalkBuilder::new(root)
+        .max_depth(Some(MANIFEST_DEPTH + 1))
+        .build();
+    for entry in walker.flatten() {
+        let path = entry.path();
+        let parent_ext = path
+            .parent()
+            .and_then(|parent| parent.extension())
+            .and_then(|ext| ext.to_str());
+        match (entry.file_name().to_str(), parent_ext) {
+            (Some("Package.swift"), _) if entry.depth() <= MANIFEST_DEPTH => {
+                let Ok(relative) = path.strip_prefix(root) else {
+                    continue;
+                };
+                loaded.manifests.push(relative.to_path_buf());
+                loaded.targets.extend(package_targets(root, path));
+            }
+            (Some(name), _) if name.ends_with(BRIDGING_HEADER_SUFFIX) => {
+                loaded.bridging_headers.extend(relative_to(root, path));
+            }
+            (Some("project.pbxproj"), Some("xcodeproj")) => {
+                projects.extend(path.parent().map(Path::to_path_buf));
+            }
+            (Some("contents.xcworkspacedata"), Some("xcworkspace")) => {
+                // Every project carries an internal `project.xcworkspace`; it names only itself.
+                let embedded = path
+                    .parent()
+                    .and_then(Path::parent)
+                    .and_then(|dir| dir.extension())
+                    .is_some_and(|ext| ext == "xcodeproj");
+                if !embedded {
+                    projects.extend(workspace_projects(path));
+                }
+            }
+            _ => {}
+        }
+    }
+    projects.sort();
+    projects.dedup();
+    for project in projects {
+        load_project(root, &project, &mut loaded);
+    }
+    loaded.bridging_headers.sort();
+    loaded.bridging_headers.dedup();
+    loaded
+}
+
+/// Targets declared in the `Package.swift` at `manifest`.
+fn package_targets(root: &Path, manifest: &Path) -> Vec<SwiftTarget> {
+    let Ok(source) = fs::read_to_string(manifest) else {
+        return Vec::new();
+    };
+    let Some(package_dir) = manifest.parent() else {
+        return Vec::new();
+    };
+    let source = strip_comments(&source);
+    let mut targets = Vec::new();
+    let mut rest = source.as_str();
+    while let Some(dot) = rest.find('.') {
+        rest = &rest[dot + 1..];
+        let kind_len = rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let Some(&(_, default_dir)) = PACKAGE_TARGET_KINDS
+            .iter()
+            .find(|(kind, _)| *kind == &rest[..kind_len])
+        else {
+            continue;
+        };
+        let Some(call) = rest[kind_len..].strip_prefix('(') else {
+            continue;
+        };
+        let Some(args) = balanced(call) else {
+            continue;
+        };
+        rest = &call[args.len()..];
+        if let Some(target) = package_target(root, package_dir, default_dir, args) {
+            targets.push(target);
+        }
+    }
+    targets
+}
+
+/// A target from the arguments of its declaration.
+fn package_target(
+    root: &Path,
+    package_dir: &Path,
+    default_dir: &str,
+    args: &str,
+) -> Option<SwiftTarget> {
+    let args = labelled_args(args);
+    // `.plugin(name:targets:)` in `products` declares a product, not the plugin target.
+    if default_dir == "Plugins" && !args.contains_key("capability") {
+        return None;
+    }
+    let name = args.get("name").and_then(|value| string_literal(value))?;
+    let target_dir = match args.get("path").and_then(|value| string_literal(value)) {
+        Some(path) => package_dir.join(path),
+        None => {
+            let dirs: &[&str] = if default_dir == "Sources" {
+                PACKAGE_SOURCE_DIRS
+            } else {
+                &[default_dir]
+            };
+            let existing = dirs
+                .iter()
+                .map(|dir| package_dir.join(dir).join(&name))
+                .find(|dir| dir.is_dir());
+            existing.unwrap_or_else(|| package_dir.join(default_dir).join(&name))
+        }
+    };
+    let relative = |path: &str| relative_to(root, &target_dir.join(path));
+    let exclude = args
+        .get("exclude")
+        .map(|value| string_array(value))
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| relative(path))
+        .collect();
+    let (dirs, files) = match args.get("sources").map(|value| string_array(value)) {
+        Some(sources) => sources
+            .iter()
+            .filter_map(|path| relative(path))
+            .partition(|path| root.join(path).is_dir()),
+        None => (
+            relative_to(root, &target_dir).into_iter().collect(),
+            Vec::new(),
+        ),
+    };
+    Some(SwiftTarget {
+        name,
+        dirs,
+        files,
+        exclude,
+    })
+}
+
+/// `source` with `//` and `/* */` comments removed; string literals are left alone.
+fn strip_comments(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            stripped.push(c);
+            match c {
+                '\\' => stripped.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+                stripped.push(' ');
+            }
+            _ => {
+                in_string = c == '"';
+                stripped.push(c);
+            }
+        }
+    }
+    stripped
+}
+
+/// The text up to the `)` that closes an already opened `(`, skipping nested brackets and
+/// strings; `None` when it is never closed.
+fn balanced(text: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' if depth == 0 => return Some(&text[..index]),
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `label: value` arguments at the top level of a call's argument list.
+fn labelled_args<'a>(args: &'a str) -> HashMap<&'a str, &'a str> {
+    let mut labelled = HashMap::new();
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut push = |arg: &'a str| {
+        if let Some((label, value)) = arg.split_once(':') {
+            let label = label.trim();
+            if label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                labelled.entry(label).or_insert_with(|| value.trim());
+            }
+        }
+    };
+    for (index, c) in args.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                push(&args[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    push(&args[start..]);
+    labelled
+}
+
+/// The contents of a plain `"..."` literal.
+fn string_literal(value: &str) -> Option<String> {
+    let inner = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    (!inner.contains("\\(")).then(|| inner.replace("\\\"", "\""))
+}
+
+/// The string literals of a `[...]` array.
+fn string_array(value: &str) -> Vec<String> {
+    let Some(inner) = value
+        .trim()
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+    else {
+        return Vec::new();
+    };
+    inner.split(',').filter_map(string_literal).collect()
+}
+
+/// `path` relative to `root` after resolving `.` and `..`; `None` outside the root.
+fn relative_to(root: &Path, path: &Path) -> Option<PathBuf> {
+    lexical(path).strip_prefix(root).ok().map(Path::to_path_buf)
+}
+
+/// `path` with `.` and `..` components resolved without touching the file system.
+fn lexical(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+/// `.xcodeproj` bundles listed in an `.xcworkspace`'s `contents.xcworkspacedata`.
+fn workspace_projects(data: &Path) -> Vec<PathBuf> {
+    let (Ok(contents), Some(workspace)) = (fs::read_to_string(data), data.parent()) else {
+        return Vec::new();
+    };
+    let Some(base) = workspace.parent() else {
+        return Vec::new();
+    };
+    let mut groups: Vec<PathBuf> = vec![base.to_path_buf()];
+    let mut projects = Vec::new();
+    let mut rest = contents.as_str();
+    while let Some(open) = rest.find('<') {
+        rest = &rest[open + 1..];
+        let end = rest.find('>').unwrap_or(rest.len());
+        let tag = &rest[..end];
+        if tag.starts_with("/Group") {
+            if groups.len() > 1 {
+                groups.pop();
+            }
+            continue;
+        }
+        let is_group = tag.starts_with("Group");
+        if !is_group && !tag.starts_with("FileRef") {
+            continue;
+        }
+        let current = groups.last().cloned().unwrap_or_default();
+        let location = tag
+            .split_once("location")
+            .and_then(|(_, value)| value.split('"').nth(1))
+            .and_then(|location| location.split_once(':'))
+            .and_then(|(kind, path)| match kind {
+                "group" => Some(current.join(path)),
+                "container" | "self" => Some(base.join(path)),
+                "absolute" => Some(PathBuf::from(path)),
+                _ => None,
+            });
+        if is_group {
+            if !tag.ends_with('/') {
+                groups.push(location.unwrap_or(current));
+            }
+        } else if let Some(location) =
+            location.filter(|path| path.extension().is_some_and(|ext| ext == "xcodeproj"))
+        {
+            projects.push(lexical(&location));
+        }
+    }
+    projects
+}
+
+/// Headers a bridging header imports by quoted path (`#import "Legacy.h"`), resolved against
+/// its directory; framework imports (`<UIKit/UIKit.h>`) and missing files are skipped.
+fn quoted_imports(root: &Path, header: &Path) -> Vec<PathBuf> {
+    let (Ok(source), Some(dir)) = (fs::read_to_string(root.join(header)), header.parent()) else {
+        return Vec::new();
+    };
+    source
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start().strip_prefix('#')?.trim_start();
+            let rest = line
+                .strip_prefix("import")
+                .or_else(|| line.strip_prefix("include"))?;
+            rest.trim_start().strip_prefix('"')?.split('"').next()
+        })
+        .map(|name| lexical(&dir.join(name)))
+        .filter(|path| root.join(path).is_file())
+        .collect()
+}
+
+/// Native targets and bridging headers of the `.xcodeproj` bundle at `project`.
+fn load_project(root: &Path, project: &Path, loaded: &mut Loaded) {
+    let Ok(contents) = fs::read_to_string(project.join("project.pbxproj")) else {
+        return;
+    };
+    let Some(Plist::Dict(document)) = PlistParser::new(&contents).value() else {
+        return;
+    };
+    let Some(Plist::Dict(objects)) = document.get("objects") else {
+        return;
+    };
+    let Some(project_dir) = project.parent() else {
+        return;
+    };
+    loaded
+        .bridging_headers
+        .extend(bridging_header_settings(root, objects, project_dir));
+    let tree = ProjectTree::new(objects, project_dir);
+    let Some(project_object) = document
+        .get("rootObject")
+        .and_then(Plist::as_str)
+        .and_then(|id| tree.object(id))
+    else {
+        return;
+    };
+    for target in project_object.ids("targets") {
+        let Some(target) = tree
+            .object(target)
+            .filter(|target| target.string("isa") == Some("PBXNativeTarget"))
+        else {
+            continue;
+        };
+        let Some(name) = target.string("name") else {
+            continue;
+        };
+        let mut files = Vec::new();
+        for phase in target.ids("buildPhases") {
+            let Some(phase) = tree
+                .object(phase)
+                .filter(|phase| phase.string("isa") == Some("PBXSourcesBuildPhase"))
+            else {
+                continue;
+            };
+            files.extend(
+                phase
+                    .ids("files")
+                    .filter_map(|file| tree.object(file)?.string("fileRef"))
+                    .filter_map(|reference| tree.path(reference))
+                    .filter_map(|path| relative_to(root, &path)),
+            );
+        }
+        let dirs = target
+            .ids("fileSystemSynchronizedGroups")
+            .filter_map(|group| tree.path(group))
+            .filter_map(|path| relative_to(root, &path))
+            .collect();
+        loaded.targets.push(SwiftTarget {
+            name: name.to_string(),
+            dirs,
+            files,
+            exclude: Vec::new(),
+        });
+    }
+}
+
+/// `SWIFT_OBJC_BRIDGING_HEADER` of every build configuration, relative to the root; settings
+/// built from variables other than the project directory are skipped.
+fn bridging_header_settings(
+    root: &Path,
+    objects: &BTreeMap<String, Plist>,
+    project_dir: &Path,
+) -> Vec<PathBuf> {
+    objects
+        .values()
+        .filter_map(|object| match object {
+            Plist::Dict(object) if object.string("isa") == Some("XCBuildConfiguration") => {
+                match object.get("buildSettings")? {
+                    Plist::Dict(settings) => settings.string("SWIFT_OBJC_BRIDGING_HEADER"),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .filter_map(|setting| {
+            let setting = PROJECT_DIR_VARIABLES
+                .iter()
+                .find_map(|variable| setting.strip_prefix(variable))
+                .unwrap_or(setting);
+            (!setting.is_empty() && !setting.contains('$')).then(|| project_dir.join(setting))
+        })
+        .filter_map(|path| relative_to(root, &path))
+        .collect()
+}
+
+/// The objects of a `project.pbxproj` and where each file or group lives on disk.
+struct ProjectTree<'a> {
+    objects: &'a BTreeMap<String, Plist>,
+    /// Group that lists each file or group among its `children`.
+    parents: HashMap<&'a str, &'a str>,
+    project_dir: &'a Path,
+}
+
+impl<'a> ProjectTree<'a> {
+    fn new(objects: &'a BTreeMap<String, Plist>, project_dir: &'a Path) -> Self {
+        let mut parents = HashMap::new();
+        for (id, object) in objects {
+            if let Plist::Dict(object) = object {
+                for child in object.ids("children") {
+                    parents.insert(child, id.as_str());
+                }
+            }
+        }
+        Self {
+            objects,
+            parents,
+            project_dir,
+        }
+    }
+
+    fn object(&self, id: &str) -> Option<&'a BTreeMap<String, Plist>> {
+        match self.objects.get(id)? {
+            Plist::Dict(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Location of the file or group `id`; `None` for products and SDK files.
+    fn path(&self, id: &str) -> Option<PathBuf> {
+        let object = self.object(id)?;
+        let own = object.string("path").map(Path::new);
+        let base = match object.string("sourceTree").unwrap_or("<group>") {
+            "<group>" => match self.parents.get(id) {
+                Some(parent) => self.path(parent)?,
+                None => self.project_dir.to_path_buf(),
+            },
+            "SOURCE_ROOT" => self.project_dir.to_path_buf(),
+            "<absolute>" => PathBuf::new(),
+            _ => return None,
+        };
+        Some(match own {
+            Some(own) => base.join(own),
+            None => base,
+        })
+    }
+}
+
+trait PlistObject {
+    fn string(&self, key: &str) -> Option<&str>;
+    fn ids(&self, key: &str) -> impl Iterator<Item = &str>;
+}
+
+impl PlistObject for BTreeMap<String, Plist> {
+    fn string(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Plist::as_str)
+    }
+
+    fn ids(&self, key: &str) -> impl Iterator<Item = &str> {
+        let items: &[Plist] = match self.get(key) {
+            Some(Plist::Array(items)) => items,
+            _ => &[],
+        };
+        items.iter().filter_map(Plist::as_str)
+    }
+}
+
+/// A value of an old-style (OpenStep) property list, the format of `project.pbxproj`.
+enum Plist {
+    String(String),
+    Array(Vec<Plist>),
+    Dict(BTreeMap<String, Plist>),
+}
+
+impl Plist {
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+struct PlistParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> PlistParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, pos: 0 }
+    }
+
+    fn value(&mut self) -> Option<Plist> {
+        self.skip_trivia();
+        match self.peek()? {
+            '{' => {
+                self.pos += 1;
+                let mut dict = BTreeMap::new();
+                loop {
+                    self.skip_trivia();
+                    if self.eat('}') {
+                        return Some(Plist::Dict(dict));
+                    }
+                    let key = self.string()?;
+                    self.skip_trivia();
+                    if !self.eat('=') {
+                        return None;
+                    }
+                    let value = self.value()?;
+                    self.skip_trivia();
+                    self.eat(';');
+                    dict.insert(key, value);
+                }
+            }
+            '(' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_trivia();
+                    if self.eat(')') {
+                        return Some(Plist::Array(items));
+                    }
+                    items.push(self.value()?);
+                    self.skip_trivia();
+                    self.eat(',');
+                }
+            }
+            _ => self.string().map(Plist::String),
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.skip_trivia();
+        if self.eat('"') {
+            let mut value = String::new();
+            let mut chars = self.text[self.pos..].char_indices();
+            while let Some((offset, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        self.pos += offset + 1;
+                        return Some(value);
+                    }
+                    '\\' => match chars.next() {
+                        Some((_, 'n')) => value.push('\n'),
+                        Some((_, 't')) => value.push('\t'),
+                        Some((_, escaped)) => value.push(escaped),
+                        None => return None,
+                    },
+                    other => value.push(other),
+                }
+            }
+            return None;
+        }
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || "_$+/:.-<>".contains(c)))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return None;
+        }
+        self.pos += len;
+        Some(rest[..len].to_string())
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            let rest = &self.text[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if let Some(comment) = trimmed.strip_prefix("/*") {
+                self.pos += 2 + comment.find("*/").map_or(comment.len(), |end| end + 2);
+            } else if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::cli::SymbolsArgs;
+use crate::search;
+
+/// Prefix matches from the identifier dictionary built alongside the Tantivy index.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SymbolsResponse {
+    pub prefix: String,
+    pub symbols: Vec<SymbolMatch>,
+}
+
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SymbolMatch {
+    pub symbol: String,
+    /// First file the identifier appears in, relative to the repository root.
+    pub path: String,
+}
+
+/// Entry point for `swe-grep symbols`.
+pub async fn run(args: SymbolsArgs) -> Result<SymbolsResponse> {
+    let root = args
+        .path
+        .unwrap_or(std::env::current_dir().context("failed to resolve current directory")?);
+    let root = search::canonicalize_path(&root).with_context(|| {
+        format!(
+            "failed to canonicalize repository root path: {}",
+            root.display()
+        )
+    })?;
+    let index_dir = args.index_dir.map(|dir| {
+        if dir.is_absolute() {
+            dir
+        } else {
+            root.join(dir)
+        }
+    });
+    lookup(&root, index_dir, &args.prefix, args.limit).await
+}
+
+/// Complete `prefix` against the identifiers of `root`, building the index on first use.
+pub async fn lookup(
+    root: &Path,
+    index_dir: Option<PathBuf>,
+    prefix: &str,
+    limit: usize,
+) -> Result<SymbolsResponse> {
+    let prefix = prefix.trim();
+    if prefix.is_empty() {
+        anyhow::bail!("prefix is required");
+    }
+    let index_dir = index_dir.unwrap_or_else(|| root.join(".swe-grep-index"));
+    let symbols = prefix_matches(root, &index_dir, prefix, limit.max(1)).await?;
+    Ok(SymbolsResponse {
+        prefix: prefix.to_string(),
+        symbols,
+    })
+}
+
+#[cfg(feature = "indexing")]
+async fn prefix_matches(
+    root: &Path,
+    index_dir: &Path,
+    prefix: &str,
+    limit: usize,
+) -> Result<Vec<SymbolMatch>> {
+    use swe_grep_indexer::{IndexConfig, TantivyIndex};
+
+    let index = TantivyIndex::open_or_build(IndexConfig {
+        root: root.to_path_buf(),
+        index_dir: index_dir.to_path_buf(),
+        extensions: None,
+    })
+    .await?;
+    Ok(index
+        .symbols()
+        .with_prefix(prefix, limit)
+        .into_iter()
+        .map(|entry| SymbolMatch {
+            symbol: entry.symbol,
+            path: entry.path.display().to_string(),
+        })
+        .collect())
+}
+
+#[cfg(not(feature = "indexing"))]
+async fn prefix_matches(
+    _root: &Path,
+    _index_dir: &Path,
+    _prefix: &str,
+    _limit: usize,
+) -> Result<Vec<SymbolMatch>> {
+    anyhow::bail!("symbol lookup requires swe-grep built with the `indexing` feature")
+}
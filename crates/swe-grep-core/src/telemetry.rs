@@ -3,7 +3,7 @@ use std::sync::OnceLock;
 use anyhow::{Context, Result, anyhow};
 use opentelemetry::KeyValue;
 use opentelemetry::global;
-use opentelemetry::metrics::{Counter, Histogram};
+use opentelemetry::metrics::{Counter, Histogram, UpDownCounter};
 use opentelemetry_prometheus::PrometheusExporter;
 use opentelemetry_sdk::Resource;
 use opentelemetry_sdk::metrics::SdkMeterProvider;
@@ -22,24 +22,41 @@ struct TelemetryState {
 struct MetricsHandles {
     tool_invocations: Counter<u64>,
     tool_results: Counter<u64>,
+    tool_retries: Counter<u64>,
     cache_hits: Counter<u64>,
     reward_histogram: Histogram<f64>,
+    reward_component_histogram: Histogram<f64>,
     cycle_latency_histogram: Histogram<f64>,
     stage_latency_histogram: Histogram<f64>,
+    language_hits: Counter<u64>,
+    language_latency_histogram: Histogram<f64>,
+    tenant_searches: Counter<u64>,
+    tenant_latency_histogram: Histogram<f64>,
+    search_queue_depth: UpDownCounter<i64>,
+    self_benchmark_histogram: Histogram<f64>,
+    latency_degraded: Counter<u64>,
 }
 
 /// Initialize tracing and metrics exporters. Safe to call multiple times.
 pub fn init() -> Result<()> {
-    configure_logging();
+    init_logging(None, "info");
     configure_metrics()?;
     Ok(())
 }
 
-fn configure_logging() {
+/// Install the JSON log subscriber on stderr; the first call wins.
+///
+/// `filter` (from `--quiet`/`-v`) beats `RUST_LOG`, which beats `fallback`.
+pub fn init_logging(filter: Option<&str>, fallback: &str) {
     LOGGING.get_or_init(|| {
-        let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+        let filter = match filter {
+            Some(filter) => EnvFilter::new(filter),
+            None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(fallback)),
+        };
+        // Logs go to stderr so stdout stays free for results and the stdio JSON-RPC transport.
         let subscriber = fmt::Subscriber::builder()
             .with_env_filter(filter)
+            .with_writer(std::io::stderr)
             .json()
             .with_current_span(false)
             .with_span_list(false)
@@ -77,6 +94,10 @@ fn configure_metrics() -> Result<&'static TelemetryState> {
         .u64_counter("swegrep_tool_results_total")
         .with_description("Number of matches produced by tool invocations")
         .init();
+    let tool_retries = meter
+        .u64_counter("swegrep_tool_retries_total")
+        .with_description("Tool invocations repeated after a transient failure")
+        .init();
     let cache_hits = meter
         .u64_counter("swegrep_cache_hits_total")
         .with_description("Cache hits recorded during search execution")
@@ -85,6 +106,10 @@ fn configure_metrics() -> Result<&'static TelemetryState> {
         .f64_histogram("swegrep_reward_score")
         .with_description("Reward signal produced per reasoning cycle")
         .init();
+    let reward_component_histogram = meter
+        .f64_histogram("swegrep_reward_component")
+        .with_description("Weighted contribution of each quality metric to the cycle reward")
+        .init();
     let cycle_latency_histogram = meter
         .f64_histogram("swegrep_cycle_latency_ms")
         .with_description("End-to-end latency of a reasoning cycle in milliseconds")
@@ -93,15 +118,53 @@ fn configure_metrics() -> Result<&'static TelemetryState> {
         .f64_histogram("swegrep_stage_latency_ms")
         .with_description("Latency of individual pipeline stages in milliseconds")
         .init();
+    let language_hits = meter
+        .u64_counter("swegrep_language_hits_total")
+        .with_description("Candidates and hits per language and pipeline stage")
+        .init();
+    let language_latency_histogram = meter
+        .f64_histogram("swegrep_language_stage_latency_ms")
+        .with_description("Share of each pipeline stage's latency per language in milliseconds")
+        .init();
+
+    let tenant_searches = meter
+        .u64_counter("swegrep_tenant_searches_total")
+        .with_description("Searches requested per tenant, by outcome")
+        .init();
+    let tenant_latency_histogram = meter
+        .f64_histogram("swegrep_tenant_search_latency_ms")
+        .with_description("Latency of admitted tenant searches in milliseconds")
+        .init();
+    let search_queue_depth = meter
+        .i64_up_down_counter("swegrep_search_queue_depth")
+        .with_description("Server searches waiting for a slot, by priority class")
+        .init();
+    let self_benchmark_histogram = meter
+        .f64_histogram("swegrep_self_benchmark_latency_ms")
+        .with_description("Latency of the startup self-benchmark searches in milliseconds")
+        .init();
+    let latency_degraded = meter
+        .u64_counter("swegrep_latency_degraded_total")
+        .with_description("Searches far slower than the startup self-benchmark baseline")
+        .init();
 
     // Initialize metrics handles
     let _ = METRICS.set(MetricsHandles {
         tool_invocations,
         tool_results,
+        tool_retries,
         cache_hits,
         reward_histogram,
+        reward_component_histogram,
         cycle_latency_histogram,
         stage_latency_histogram,
+        language_hits,
+        language_latency_histogram,
+        tenant_searches,
+        tenant_latency_histogram,
+        search_queue_depth,
+        self_benchmark_histogram,
+        latency_degraded,
     });
 
     let state = TelemetryState {
@@ -149,6 +212,13 @@ pub fn record_tool_results(tool: &'static str, count: usize) {
     }
 }
 
+/// Record one retry of a tool invocation after a transient failure.
+pub fn record_tool_retry(tool: &'static str) {
+    if let Some(metrics) = metrics() {
+        metrics.tool_retries.add(1, &[KeyValue::new("tool", tool)]);
+    }
+}
+
 /// Record cache hits for the given cache identifier.
 pub fn record_cache_hits(cache: &'static str, hits: usize) {
     if hits == 0 {
@@ -168,6 +238,16 @@ pub fn record_reward(value: f32) {
     }
 }
 
+/// Record one quality metric's weighted contribution (`precision`, `density`, `clustering`,
+/// `fd_bonus`) to a cycle's reward.
+pub fn record_reward_component(component: &'static str, value: f32) {
+    if let Some(metrics) = metrics() {
+        metrics
+            .reward_component_histogram
+            .record(value as f64, &[KeyValue::new("component", component)]);
+    }
+}
+
 /// Record the total latency of a reasoning cycle in milliseconds.
 pub fn record_cycle_latency(latency_ms: u64) {
     if let Some(metrics) = metrics() {
@@ -189,6 +269,63 @@ pub fn record_stage_latency(stage: &'static str, latency_ms: u64) {
     }
 }
 
+/// Record a stage's hits and latency share for one language; zeros are skipped.
+pub fn record_language_stage(language: &str, stage: &'static str, hits: usize, latency_ms: u64) {
+    if let Some(metrics) = metrics() {
+        let labels = [
+            KeyValue::new("language", language.to_string()),
+            KeyValue::new("stage", stage),
+        ];
+        if hits > 0 {
+            metrics.language_hits.add(hits as u64, &labels);
+        }
+        if latency_ms > 0 {
+            metrics
+                .language_latency_histogram
+                .record(latency_ms as f64, &labels);
+        }
+    }
+}
+
+/// Record one tenant search with its outcome (`ok`, `error`, `unauthorized`, ...); latency is
+/// only recorded for searches that ran.
+pub fn record_tenant_search(tenant: &str, outcome: &'static str, latency_ms: Option<u64>) {
+    if let Some(metrics) = metrics() {
+        let tenant = KeyValue::new("tenant", tenant.to_string());
+        metrics
+            .tenant_searches
+            .add(1, &[tenant.clone(), KeyValue::new("outcome", outcome)]);
+        if let Some(latency_ms) = latency_ms {
+            metrics
+                .tenant_latency_histogram
+                .record(latency_ms as f64, &[tenant]);
+        }
+    }
+}
+
+/// Record `delta` searches entering (positive) or leaving (negative) the `priority` queue.
+pub fn record_queue_depth(priority: &'static str, delta: i64) {
+    if let Some(metrics) = metrics() {
+        metrics
+            .search_queue_depth
+            .add(delta, &[KeyValue::new("priority", priority)]);
+    }
+}
+
+/// Record one self-benchmark search latency in milliseconds.
+pub fn record_self_benchmark(latency_ms: f64) {
+    if let Some(metrics) = metrics() {
+        metrics.self_benchmark_histogram.record(latency_ms, &[]);
+    }
+}
+
+/// Record a search that exceeded the self-benchmark baseline by the degradation factor.
+pub fn record_latency_degraded() {
+    if let Some(metrics) = metrics() {
+        metrics.latency_degraded.add(1, &[]);
+    }
+}
+
 /// Render all currently collected metrics in Prometheus text format.
 pub fn export_prometheus() -> Result<String> {
     let state = state().ok_or_else(|| anyhow!("telemetry not initialized"))?;
@@ -1,18 +1,27 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use serde::Deserialize;
-use tokio::process::Command;
 use tokio::time::timeout;
 use tracing::{debug, warn};
 
+use crate::config::ToolSettings;
+
+use super::backend::{self, ToolBackend};
+use super::binary;
+use super::retry::RetryPolicy;
+
 #[derive(Clone, Debug)]
 pub struct AstGrepTool {
     timeout: Duration,
     max_matches: usize,
+    settings: ToolSettings,
+    retry: RetryPolicy,
+    backend: Arc<dyn ToolBackend>,
 }
 
 impl AstGrepTool {
@@ -20,13 +29,35 @@ impl AstGrepTool {
         Self {
             timeout,
             max_matches,
+            settings: ToolSettings::default(),
+            retry: RetryPolicy::default(),
+            backend: backend::process(),
         }
     }
 
-    pub async fn search_identifier(
+    /// Use a configured binary and extra arguments instead of the `PATH` lookup.
+    pub fn with_settings(mut self, settings: ToolSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Retry transient spawn failures and timeouts per `policy`.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Start runs through `backend` instead of spawning the binary directly.
+    pub fn with_backend(mut self, backend: Arc<dyn ToolBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Structural matches for any of `symbols`, which are tried in order until `max_matches`.
+    pub async fn search_identifiers(
         &self,
         root: &Path,
-        symbol: &str,
+        symbols: &[String],
         languages: &[String],
         paths: &[PathBuf],
     ) -> Result<Vec<AstGrepMatch>> {
@@ -40,14 +71,20 @@ impl AstGrepTool {
         let mut seen: HashSet<(PathBuf, usize)> = HashSet::new();
 
         for lang in hints {
-            let patterns = patterns_for_language(symbol, &lang);
+            let patterns: Vec<String> = symbols
+                .iter()
+                .flat_map(|symbol| patterns_for_language(symbol, &lang))
+                .collect();
             for pattern in patterns {
                 if aggregated.len() >= self.max_matches {
                     break;
                 }
                 let remaining = self.max_matches.saturating_sub(aggregated.len());
                 let matches = self
-                    .run_pattern(root, &lang, &pattern, paths, remaining)
+                    .retry
+                    .run("ast-grep", || {
+                        self.run_pattern(root, &lang, &pattern, paths, remaining)
+                    })
                     .await?;
 
                 for m in matches {
@@ -65,6 +102,193 @@ impl AstGrepTool {
         Ok(aggregated)
     }
 
+    /// Matches for `pattern`, a code snippet that may hold metavariables such as `$ARG`, in
+    /// each of `languages` until `max_matches`.
+    pub async fn search_pattern(
+        &self,
+        root: &Path,
+        pattern: &str,
+        languages: &[String],
+        paths: &[PathBuf],
+    ) -> Result<Vec<AstGrepMatch>> {
+        let hints: Vec<String> = if languages.is_empty() {
+            vec!["rust".to_string()]
+        } else {
+            languages.to_vec()
+        };
+        let mut aggregated: Vec<AstGrepMatch> = Vec::new();
+        for lang in hints {
+            let remaining = self.max_matches.saturating_sub(aggregated.len());
+            if remaining == 0 {
+                break;
+            }
+            let matches = self
+                .retry
+                .run("ast-grep", || {
+                    self.run_pattern(root, &lang, pattern, paths, remaining)
+                })
+                .await?;
+            aggregated.extend(matches.into_iter().take(remaining));
+        }
+        Ok(aggregated)
+    }
+
+    /// Identifiers (including type and field names) whose whole text matches `regex`, in each
+    /// of `languages` until `max_matches`; used for glob symbols such as `handle_*_event`.
+    pub async fn search_identifier_regex(
+        &self,
+        root: &Path,
+        regex: &str,
+        languages: &[String],
+        paths: &[PathBuf],
+    ) -> Result<Vec<AstGrepMatch>> {
+        let hints: Vec<String> = if languages.is_empty() {
+            vec!["rust".to_string()]
+        } else {
+            languages.to_vec()
+        };
+        let paths: Vec<&Path> = paths.iter().map(PathBuf::as_path).collect();
+        let mut aggregated: Vec<AstGrepMatch> = Vec::new();
+        for lang in hints {
+            let remaining = self.max_matches.saturating_sub(aggregated.len());
+            if remaining == 0 {
+                break;
+            }
+            let kinds = identifier_kinds(&lang)
+                .iter()
+                .map(|kind| format!("      - kind: {kind}\n"))
+                .collect::<String>();
+            // Single-quoted YAML keeps the regex's backslashes as written.
+            let rules = format!(
+                "id: identifier\nlanguage: {lang}\nrule:\n  all:\n    - any:\n{kinds}    - regex: '^{regex}$'\n"
+            );
+            let messages = self
+                .retry
+                .run("ast-grep", || self.scan(root, &paths, &rules))
+                .await?;
+            aggregated.extend(
+                messages
+                    .into_iter()
+                    .take(remaining)
+                    .map(|message| AstGrepMatch {
+                        path: PathBuf::from(message.file),
+                        line: message.range.start.line,
+                    }),
+            );
+        }
+        Ok(aggregated)
+    }
+
+    /// Declarations in `path` (relative to `root`) whose node kind is listed in `kinds`, each
+    /// paired with the field that holds its name, e.g. `("function_item", "name")`.
+    pub async fn declarations(
+        &self,
+        root: &Path,
+        path: &Path,
+        language: &str,
+        kinds: &[(&str, &str)],
+    ) -> Result<Vec<AstGrepDeclaration>> {
+        let rules = kinds
+            .iter()
+            .map(|(kind, field)| {
+                format!(
+                    "id: {kind}\nlanguage: {language}\nrule:\n  kind: {kind}\n  has:\n    field: {field}\n    pattern: $NAME\n"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("---\n");
+        let messages = self
+            .retry
+            .run("ast-grep", || {
+                self.scan(root, std::slice::from_ref(&path), &rules)
+            })
+            .await?;
+        Ok(messages
+            .into_iter()
+            .filter_map(AstGrepDeclaration::from_message)
+            .collect())
+    }
+
+    /// Nodes in `path` (relative to `root`) whose kind is one of `kinds`, e.g. `identifier`.
+    pub async fn nodes(
+        &self,
+        root: &Path,
+        path: &Path,
+        language: &str,
+        kinds: &[&str],
+    ) -> Result<Vec<AstGrepNode>> {
+        let rules = kinds
+            .iter()
+            .map(|kind| format!("id: {kind}\nlanguage: {language}\nrule:\n  kind: {kind}\n"))
+            .collect::<Vec<_>>()
+            .join("---\n");
+        let messages = self
+            .retry
+            .run("ast-grep", || {
+                self.scan(root, std::slice::from_ref(&path), &rules)
+            })
+            .await?;
+        Ok(messages.into_iter().map(AstGrepNode::from).collect())
+    }
+
+    /// Run the inline `rules` over `paths` (relative to `root`), or the whole root when empty.
+    async fn scan(
+        &self,
+        root: &Path,
+        paths: &[&Path],
+        rules: &str,
+    ) -> Result<Vec<AstGrepScanMessage>> {
+        let mut cmd = binary::configured_command("ast-grep", &self.settings);
+        cmd.arg("scan")
+            .arg("--json")
+            .arg("--inline-rules")
+            .arg(rules);
+        if paths.is_empty() {
+            cmd.arg(".");
+        } else {
+            cmd.args(paths);
+        }
+        cmd.current_dir(root);
+
+        let collect = async {
+            let output = self
+                .backend
+                .spawn("ast-grep", cmd)
+                .with_context(|| "failed to spawn ast-grep; is it installed and on PATH?")?
+                .output()
+                .await?;
+            if !output.status.success() && output.status.code() != Some(1) {
+                let stderr_text = String::from_utf8_lossy(&output.stderr);
+                anyhow::bail!(
+                    "ast-grep exited with status {}: {}",
+                    output.status,
+                    stderr_text.trim()
+                );
+            }
+
+            let text = String::from_utf8_lossy(&output.stdout);
+            let messages: Vec<AstGrepScanMessage> = match serde_json::from_str(&text) {
+                Ok(parsed) => parsed,
+                Err(_) => text
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .filter_map(|line| match serde_json::from_str(line) {
+                        Ok(msg) => Some(msg),
+                        Err(err) => {
+                            tracing::warn!(error = %err, "failed to parse ast-grep json line");
+                            None
+                        }
+                    })
+                    .collect(),
+            };
+            Ok(messages)
+        };
+
+        timeout(self.timeout, collect)
+            .await
+            .with_context(|| "ast-grep invocation timed out")?
+    }
+
     async fn run_pattern(
         &self,
         root: &Path,
@@ -77,7 +301,7 @@ impl AstGrepTool {
             return Ok(Vec::new());
         }
 
-        let mut cmd = Command::new("ast-grep");
+        let mut cmd = binary::configured_command("ast-grep", &self.settings);
         cmd.arg("--json")
             .arg("--pattern")
             .arg(pattern)
@@ -103,10 +327,12 @@ impl AstGrepTool {
         cmd.current_dir(root);
 
         let collect = async {
-            let output = cmd
+            let output = self
+                .backend
+                .spawn("ast-grep", cmd)
+                .with_context(|| "failed to spawn ast-grep; is it installed and on PATH?")?
                 .output()
-                .await
-                .with_context(|| "failed to spawn ast-grep; is it installed and on PATH?")?;
+                .await?;
 
             let stderr_text = String::from_utf8_lossy(&output.stderr);
             if let Some(diagnostic) = stderr_text
@@ -184,14 +410,12 @@ struct AstGrepMessage {
 #[derive(Debug, Deserialize)]
 struct AstGrepRange {
     start: AstGrepPosition,
-    #[allow(dead_code)]
     end: AstGrepPosition,
 }
 
 #[derive(Debug, Deserialize)]
 struct AstGrepPosition {
     line: usize,
-    #[allow(dead_code)]
     column: usize,
 }
 
@@ -210,6 +434,87 @@ impl From<AstGrepMessage> for AstGrepMatch {
     }
 }
 
+/// One `ast-grep scan --json` match; `ruleId` names the node kind its rule looked for.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AstGrepScanMessage {
+    rule_id: String,
+    /// File of the match, relative to the directory ast-grep ran in.
+    #[serde(default)]
+    file: String,
+    #[serde(default)]
+    text: String,
+    range: AstGrepRange,
+    #[serde(default)]
+    meta_variables: AstGrepMetaVariables,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AstGrepMetaVariables {
+    #[serde(default)]
+    single: HashMap<String, AstGrepCapture>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AstGrepCapture {
+    text: String,
+    #[serde(default)]
+    range: Option<AstGrepRange>,
+}
+
+/// A named declaration with its 1-based, inclusive line range.
+#[derive(Clone, Debug)]
+pub struct AstGrepDeclaration {
+    /// Tree-sitter node kind, e.g. `function_item`.
+    pub kind: String,
+    pub name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Line of the name itself, which modifiers and attributes can push below `start_line`.
+    pub name_line: usize,
+}
+
+impl AstGrepDeclaration {
+    /// ast-grep reports 0-based lines; matches without a captured name are dropped.
+    fn from_message(mut message: AstGrepScanMessage) -> Option<Self> {
+        let name = message.meta_variables.single.remove("NAME")?;
+        let start_line = message.range.start.line + 1;
+        Some(Self {
+            kind: message.rule_id,
+            name: name.text,
+            start_line,
+            end_line: message.range.end.line + 1,
+            name_line: name.range.map_or(start_line, |range| range.start.line + 1),
+        })
+    }
+}
+
+/// A syntax node with its 1-based start and end positions; `end_column` is exclusive.
+#[derive(Clone, Debug)]
+pub struct AstGrepNode {
+    /// Tree-sitter node kind, e.g. `identifier`.
+    pub kind: String,
+    pub text: String,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl From<AstGrepScanMessage> for AstGrepNode {
+    /// ast-grep reports 0-based lines and columns.
+    fn from(message: AstGrepScanMessage) -> Self {
+        Self {
+            kind: message.rule_id,
+            text: message.text,
+            start_line: message.range.start.line + 1,
+            start_column: message.range.start.column + 1,
+            end_line: message.range.end.line + 1,
+            end_column: message.range.end.column + 1,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AstPatternError {
     pattern: String,
@@ -242,6 +547,19 @@ impl fmt::Display for AstPatternError {
 
 impl std::error::Error for AstPatternError {}
 
+/// Node kinds that hold a name in `language`'s grammar: plain, type, and field identifiers.
+fn identifier_kinds(language: &str) -> &'static [&'static str] {
+    match language.to_ascii_lowercase().as_str() {
+        "rust" => &["identifier", "type_identifier", "field_identifier"],
+        "typescript" | "ts" | "tsx" | "javascript" | "js" | "jsx" => {
+            &["identifier", "property_identifier", "type_identifier"]
+        }
+        "swift" => &["simple_identifier", "type_identifier"],
+        "go" => &["identifier", "type_identifier", "field_identifier"],
+        _ => &["identifier"],
+    }
+}
+
 fn patterns_for_language(symbol: &str, language: &str) -> Vec<String> {
     let needle = symbol.trim();
     if needle.is_empty() {
@@ -0,0 +1,292 @@
+use std::fmt;
+use std::io::{self, Cursor};
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Command;
+
+static PROCESS: OnceLock<Arc<dyn ToolBackend>> = OnceLock::new();
+
+/// Starts the external tools a search shells out to.
+///
+/// Tools build their command line as usual and hand it to a backend, which decides what
+/// actually runs: [`ProcessBackend`] spawns the binary, [`MockBackend`] answers from canned
+/// output so tests run without rg, fd, or ast-grep installed.
+pub trait ToolBackend: Send + Sync + fmt::Debug {
+    /// Start `tool` as described by `cmd`. A `NotFound` error reads as a missing tool.
+    fn spawn(&self, tool: &'static str, cmd: Command) -> io::Result<ToolProcess>;
+}
+
+/// The backend that spawns real processes, shared by every tool that is not given another.
+pub fn process() -> Arc<dyn ToolBackend> {
+    PROCESS.get_or_init(|| Arc::new(ProcessBackend)).clone()
+}
+
+/// A started tool: its output streams and its exit.
+pub struct ToolProcess {
+    pub stdout: Box<dyn AsyncRead + Send + Unpin>,
+    pub stderr: Box<dyn AsyncRead + Send + Unpin>,
+    /// Resolves once the tool exits. Dropping it before then stops the tool, so a timed-out
+    /// invocation leaves no process behind.
+    pub exit: BoxFuture<'static, io::Result<ToolExit>>,
+}
+
+impl ToolProcess {
+    /// Read both streams to the end and wait for the exit, like `Command::output`.
+    pub async fn output(self) -> io::Result<ToolOutput> {
+        let Self {
+            mut stdout,
+            mut stderr,
+            exit,
+        } = self;
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        tokio::try_join!(stdout.read_to_end(&mut out), stderr.read_to_end(&mut err))?;
+        Ok(ToolOutput {
+            status: exit.await?,
+            stdout: out,
+            stderr: err,
+        })
+    }
+}
+
+/// Everything a tool printed, and how it exited.
+#[derive(Clone, Debug)]
+pub struct ToolOutput {
+    pub status: ToolExit,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+}
+
+/// How a tool exited: with a code, or killed by a signal when there is none.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ToolExit {
+    code: Option<i32>,
+}
+
+impl ToolExit {
+    pub fn from_code(code: i32) -> Self {
+        Self { code: Some(code) }
+    }
+
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+
+    pub fn code(&self) -> Option<i32> {
+        self.code
+    }
+}
+
+impl From<ExitStatus> for ToolExit {
+    fn from(status: ExitStatus) -> Self {
+        Self {
+            code: status.code(),
+        }
+    }
+}
+
+impl fmt::Display for ToolExit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "exit status: {code}"),
+            None => f.write_str("terminated by signal"),
+        }
+    }
+}
+
+/// Spawns the configured binary with piped output; the child is killed when its exit future
+/// is dropped.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProcessBackend;
+
+impl ToolBackend for ProcessBackend {
+    fn spawn(&self, _tool: &'static str, mut cmd: Command) -> io::Result<ToolProcess> {
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = cmd.spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("tool did not produce stdout pipe"))?;
+        let stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| io::Error::other("tool did not produce stderr pipe"))?;
+        Ok(ToolProcess {
+            stdout: Box::new(stdout),
+            stderr: Box::new(stderr),
+            exit: Box::pin(async move { child.wait().await.map(ToolExit::from) }),
+        })
+    }
+}
+
+/// A tool run recorded by [`MockBackend`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ToolInvocation {
+    pub tool: &'static str,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+}
+
+/// Canned output for a mocked tool run.
+#[derive(Clone, Debug, Default)]
+pub struct MockResponse {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    exit_code: i32,
+    delay: Duration,
+}
+
+impl MockResponse {
+    /// A run that prints `stdout` and exits 0.
+    pub fn stdout(stdout: impl Into<Vec<u8>>) -> Self {
+        Self {
+            stdout: stdout.into(),
+            ..Self::default()
+        }
+    }
+
+    /// ripgrep `--json` output with one match per `(path, line, text)`.
+    pub fn rg_matches(matches: &[(&str, usize, &str)]) -> Self {
+        let mut stdout = String::new();
+        for (path, line, text) in matches {
+            let message = serde_json::json!({
+                "type": "match",
+                "data": {
+                    "path": { "text": path },
+                    "lines": { "text": format!("{text}\n") },
+                    "line_number": line,
+                    "absolute_offset": 0,
+                    "submatches": [],
+                },
+            });
+            stdout.push_str(&message.to_string());
+            stdout.push('\n');
+        }
+        Self::stdout(stdout)
+    }
+
+    /// Print `stderr` as well.
+    pub fn with_stderr(mut self, stderr: impl Into<Vec<u8>>) -> Self {
+        self.stderr = stderr.into();
+        self
+    }
+
+    /// Exit with `code` instead of 0.
+    pub fn with_exit_code(mut self, code: i32) -> Self {
+        self.exit_code = code;
+        self
+    }
+
+    /// Exit only after `delay`, once the output has been read; longer than the tool timeout
+    /// simulates a hung tool.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A rule of [`MockBackend`]: answer runs of `tool`, optionally only those with an argument
+/// containing `needle`.
+#[derive(Clone, Debug)]
+struct MockRule {
+    tool: &'static str,
+    needle: Option<String>,
+    response: MockResponse,
+}
+
+/// Answers tool runs from canned responses and records every invocation.
+///
+/// The most recently added matching rule wins. A run no rule matches fails to spawn with
+/// `NotFound`, as if the tool were not installed.
+#[derive(Debug, Default)]
+pub struct MockBackend {
+    rules: Vec<MockRule>,
+    invocations: Mutex<Vec<ToolInvocation>>,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer every run of `tool` with `response`.
+    pub fn with_response(mut self, tool: &'static str, response: MockResponse) -> Self {
+        self.rules.push(MockRule {
+            tool,
+            needle: None,
+            response,
+        });
+        self
+    }
+
+    /// Answer runs of `tool` that have an argument containing `needle` with `response`.
+    pub fn with_response_for(
+        mut self,
+        tool: &'static str,
+        needle: impl Into<String>,
+        response: MockResponse,
+    ) -> Self {
+        self.rules.push(MockRule {
+            tool,
+            needle: Some(needle.into()),
+            response,
+        });
+        self
+    }
+
+    /// Runs so far, in the order they were started.
+    pub fn invocations(&self) -> Vec<ToolInvocation> {
+        self.invocations
+            .lock()
+            .map(|invocations| invocations.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl ToolBackend for MockBackend {
+    fn spawn(&self, tool: &'static str, cmd: Command) -> io::Result<ToolProcess> {
+        let cmd = cmd.as_std();
+        let invocation = ToolInvocation {
+            tool,
+            args: cmd
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+            cwd: cmd.get_current_dir().map(PathBuf::from),
+        };
+        let rule = self.rules.iter().rev().find(|rule| {
+            rule.tool == tool
+                && rule.needle.as_ref().is_none_or(|needle| {
+                    invocation
+                        .args
+                        .iter()
+                        .any(|arg| arg.contains(needle.as_str()))
+                })
+        });
+        if let Ok(mut invocations) = self.invocations.lock() {
+            invocations.push(invocation);
+        }
+        let Some(MockRule { response, .. }) = rule.cloned() else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no mocked response for {tool}"),
+            ));
+        };
+        Ok(ToolProcess {
+            stdout: Box::new(Cursor::new(response.stdout)),
+            stderr: Box::new(Cursor::new(response.stderr)),
+            exit: Box::pin(async move {
+                tokio::time::sleep(response.delay).await;
+                Ok(ToolExit::from_code(response.exit_code))
+            }),
+        })
+    }
+}
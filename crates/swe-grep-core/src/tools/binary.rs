@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Mutex, OnceLock};
+
+use tokio::process::Command;
+
+use crate::config::ToolSettings;
+
+static RESOLVED: OnceLock<Mutex<HashMap<&'static str, PathBuf>>> = OnceLock::new();
+
+/// Build a `Command` for the named tool using the resolved binary location.
+pub fn command(tool: &'static str) -> Command {
+    Command::new(resolve(tool))
+}
+
+/// Build a `Command` honouring a configured binary path, followed by its extra arguments, that
+/// starts under the configured resource limits.
+pub fn configured_command(tool: &'static str, settings: &ToolSettings) -> Command {
+    let mut cmd = match &settings.path {
+        Some(path) => Command::new(path),
+        None => command(tool),
+    };
+    cmd.args(&settings.extra_args);
+    settings.limits.apply(&mut cmd);
+    cmd
+}
+
+/// Resolve the executable backing `tool`, caching the result for the lifetime of the process.
+///
+/// Lookup order: `PATH` (honouring `PATHEXT` on Windows), `where` on Windows, the directories
+/// bundled next to the running executable, and finally (with the `bundled-tools` feature) the
+/// binaries embedded at build time. When nothing is found the bare tool name is returned so spawn
+/// errors keep their familiar "is it installed" wording.
+pub fn resolve(tool: &'static str) -> PathBuf {
+    let cache = RESOLVED.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Ok(guard) = cache.lock()
+        && let Some(path) = guard.get(tool)
+    {
+        return path.clone();
+    }
+
+    let resolved = locate(tool).unwrap_or_else(|| PathBuf::from(tool));
+    tracing::debug!(tool, path = %resolved.display(), "resolved tool binary");
+    if let Ok(mut guard) = cache.lock() {
+        guard.insert(tool, resolved.clone());
+    }
+    resolved
+}
+
+/// Alternate executable names a tool may be installed under, preferred name first.
+fn candidate_names(tool: &str) -> Vec<&str> {
+    match tool {
+        "ast-grep" => vec!["ast-grep", "sg"],
+        "fd" => vec!["fd", "fdfind"],
+        other => vec![other],
+    }
+}
+
+/// What an alias must print for `--version` before it is trusted. `sg` is also shadow-utils'
+/// `newgrp` on Debian and Ubuntu, so it only counts when it turns out to be ast-grep.
+fn required_identity(name: &str) -> Option<&'static str> {
+    match name {
+        "sg" => Some("ast-grep"),
+        _ => None,
+    }
+}
+
+fn locate(tool: &str) -> Option<PathBuf> {
+    let names = candidate_names(tool);
+    let path_dirs: Vec<PathBuf> = env::var_os("PATH")
+        .map(|path_var| env::split_paths(&path_var).collect())
+        .unwrap_or_default();
+    // All of PATH is searched for one name before the next is tried, so an alias early on PATH
+    // never shadows the tool's own name further down.
+    for name in &names {
+        if let Some(found) = path_dirs.iter().find_map(|dir| find_in_dir(dir, name)) {
+            return Some(found);
+        }
+    }
+
+    if cfg!(windows) {
+        for name in &names {
+            if let Some(found) = where_lookup(name).filter(|path| identifies_as(name, path)) {
+                return Some(found);
+            }
+        }
+    }
+
+    let bundled = bundled_dirs();
+    for name in &names {
+        if let Some(found) = bundled.iter().find_map(|dir| find_in_dir(dir, name)) {
+            return Some(found);
+        }
+    }
+
+    #[cfg(feature = "bundled-tools")]
+    if let Some(found) = super::bundled::materialize(tool) {
+        return Some(found);
+    }
+
+    None
+}
+
+fn find_in_dir(dir: &Path, name: &str) -> Option<PathBuf> {
+    for ext in executable_extensions() {
+        let mut file_name = OsString::from(name);
+        file_name.push(&ext);
+        let candidate = dir.join(file_name);
+        if candidate.is_file() && identifies_as(name, &candidate) {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// Whether the executable found for `name` is the tool it is expected to be.
+fn identifies_as(name: &str, path: &Path) -> bool {
+    let Some(identity) = required_identity(name) else {
+        return true;
+    };
+    let genuine = std::process::Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .is_ok_and(|output| {
+            output.status.success() && String::from_utf8_lossy(&output.stdout).contains(identity)
+        });
+    if !genuine {
+        tracing::debug!(path = %path.display(), identity, "ignoring executable that is not the expected tool");
+    }
+    genuine
+}
+
+/// Extensions appended to bare tool names when probing directories.
+fn executable_extensions() -> Vec<String> {
+    if !cfg!(windows) {
+        return vec![String::new()];
+    }
+    let raw = env::var("PATHEXT").unwrap_or_else(|_| ".EXE;.CMD;.BAT;.COM".to_string());
+    let mut extensions: Vec<String> = raw
+        .split(';')
+        .map(str::trim)
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| ext.to_ascii_lowercase())
+        .collect();
+    extensions.push(String::new());
+    extensions
+}
+
+/// Ask the Windows `where` utility, which also consults the App Paths registry entries.
+fn where_lookup(name: &str) -> Option<PathBuf> {
+    let output = std::process::Command::new("where")
+        .arg(name)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Directories shipped alongside the swe-grep executable that may contain bundled tools.
+fn bundled_dirs() -> Vec<PathBuf> {
+    let Ok(exe) = env::current_exe() else {
+        return Vec::new();
+    };
+    let Some(parent) = exe.parent() else {
+        return Vec::new();
+    };
+    vec![parent.join("tools"), parent.to_path_buf()]
+}
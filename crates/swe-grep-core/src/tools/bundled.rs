@@ -0,0 +1,92 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+
+/// A tool binary embedded at build time from `SWE_GREP_BUNDLE_DIR`.
+pub(crate) struct BundledTool {
+    pub name: &'static str,
+    pub file_name: &'static str,
+    pub sha256: &'static str,
+    pub bytes: &'static [u8],
+}
+
+include!(concat!(env!("OUT_DIR"), "/bundled_tools.rs"));
+
+static INSTALL_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set the directory bundled binaries are extracted into. The first caller wins.
+pub fn set_install_dir(dir: PathBuf) {
+    let _ = INSTALL_DIR.set(dir);
+}
+
+fn install_dir() -> PathBuf {
+    INSTALL_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("swe-grep-tools"))
+}
+
+/// Return a verified on-disk copy of the embedded `tool`, extracting it on first use.
+pub(crate) fn materialize(tool: &str) -> Option<PathBuf> {
+    let entry = BUNDLED.iter().find(|candidate| candidate.name == tool)?;
+    let dir = install_dir().join(&entry.sha256[..12]);
+    let target = dir.join(entry.file_name);
+
+    if target.is_file() && matches_checksum(&target, entry.sha256) {
+        return Some(target);
+    }
+
+    match extract(entry, &dir, &target) {
+        Ok(()) => {
+            tracing::info!(tool, path = %target.display(), "extracted bundled tool");
+            Some(target)
+        }
+        Err(err) => {
+            tracing::warn!(tool, error = %err, "failed to extract bundled tool");
+            None
+        }
+    }
+}
+
+fn extract(entry: &BundledTool, dir: &Path, target: &Path) -> Result<()> {
+    fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create bundled tool directory {}", dir.display()))?;
+
+    let tmp_path = target.with_extension(format!("tmp-{}", std::process::id()));
+    {
+        let mut file = fs::File::create(&tmp_path)
+            .with_context(|| format!("failed to create {}", tmp_path.display()))?;
+        file.write_all(entry.bytes)
+            .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+        file.sync_all()
+            .with_context(|| format!("failed to flush {}", tmp_path.display()))?;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&tmp_path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("failed to mark {} executable", tmp_path.display()))?;
+    }
+
+    if !matches_checksum(&tmp_path, entry.sha256) {
+        let _ = fs::remove_file(&tmp_path);
+        anyhow::bail!("checksum mismatch for extracted {}", entry.name);
+    }
+
+    fs::rename(&tmp_path, target)
+        .with_context(|| format!("failed to move bundled tool into place {}", target.display()))
+}
+
+fn matches_checksum(path: &Path, expected: &str) -> bool {
+    let Ok(bytes) = fs::read(path) else {
+        return false;
+    };
+    let digest = Sha256::digest(&bytes);
+    let actual: String = digest.iter().map(|byte| format!("{byte:02x}")).collect();
+    actual == expected
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tokio::process::Command;
+
+use super::backend::{ToolBackend, ToolProcess};
+
+/// Tools a `--chaos` entry may name.
+const TOOLS: &[&str] = &["rg", "fd", "ast-grep", "rga"];
+
+/// Faults `--chaos` injects per tool, e.g. `rg:delay=500ms,ast-grep:fail=0.2`.
+///
+/// Each comma-separated entry is a tool followed by `:key=value` settings: `delay` holds every
+/// run of the tool back (`500ms`, `2s`, or bare milliseconds) and `fail` is the probability,
+/// from 0 to 1, that a run fails to start. Entries for the same tool are merged.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChaosSpec {
+    faults: BTreeMap<&'static str, Fault>,
+}
+
+/// What `--chaos` does to each run of one tool.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Fault {
+    pub delay: Duration,
+    pub fail: f64,
+}
+
+impl ChaosSpec {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut faults = BTreeMap::new();
+        for entry in text
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let mut parts = entry.split(':');
+            let name = parts.next().unwrap_or_default().trim();
+            let tool = TOOLS
+                .iter()
+                .copied()
+                .find(|tool| *tool == name)
+                .ok_or_else(|| {
+                    format!(
+                        "unknown tool `{name}` in `{entry}` (expected one of {})",
+                        TOOLS.join(", ")
+                    )
+                })?;
+            let fault: &mut Fault = faults.entry(tool).or_default();
+            let mut settings = 0;
+            for setting in parts {
+                settings += 1;
+                let (key, value) = setting
+                    .split_once('=')
+                    .ok_or_else(|| format!("expected `key=value` in `{entry}`"))?;
+                match key.trim() {
+                    "delay" => fault.delay = parse_delay(value.trim())?,
+                    "fail" => {
+                        fault.fail = value
+                            .trim()
+                            .parse()
+                            .ok()
+                            .filter(|fail| (0.0..=1.0).contains(fail))
+                            .ok_or_else(|| {
+                                format!("invalid fail rate `{value}` (expected 0 to 1)")
+                            })?;
+                    }
+                    other => {
+                        return Err(format!(
+                            "unknown setting `{other}` in `{entry}` (expected delay or fail)"
+                        ));
+                    }
+                }
+            }
+            if settings == 0 {
+                return Err(format!("`{entry}` sets neither delay nor fail"));
+            }
+        }
+        if faults.is_empty() {
+            return Err("empty chaos spec".to_string());
+        }
+        Ok(Self { faults })
+    }
+
+    pub fn fault(&self, tool: &str) -> Option<Fault> {
+        self.faults.get(tool).copied()
+    }
+}
+
+/// Parse `500ms`, `2s`, or a bare number of milliseconds.
+fn parse_delay(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid delay `{text}` (expected e.g. 500ms or 2s)");
+    if let Some(ms) = text.strip_suffix("ms") {
+        ms.trim()
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| invalid())
+    } else if let Some(secs) = text.strip_suffix('s') {
+        secs.trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+            .ok_or_else(invalid)
+    } else {
+        text.parse()
+            .map(Duration::from_millis)
+            .map_err(|_| invalid())
+    }
+}
+
+/// Wraps another backend and injects the faults of a [`ChaosSpec`] into its runs.
+///
+/// An injected failure is a spawn error, which retries treat as transient and circuit breakers
+/// count, so both can be exercised without a broken tool. A delay postpones the tool's exit,
+/// counting against its timeout and the stage budgets.
+#[derive(Debug)]
+pub struct ChaosBackend {
+    inner: Arc<dyn ToolBackend>,
+    spec: ChaosSpec,
+    state: AtomicU64,
+}
+
+impl ChaosBackend {
+    pub fn new(inner: Arc<dyn ToolBackend>, spec: ChaosSpec) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos() as u64)
+            .unwrap_or_default()
+            ^ u64::from(std::process::id());
+        Self {
+            inner,
+            spec,
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    /// A uniform sample from [0, 1) (splitmix64).
+    fn sample(&self) -> f64 {
+        let mut z = self
+            .state
+            .fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+            .wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl ToolBackend for ChaosBackend {
+    fn spawn(&self, tool: &'static str, cmd: Command) -> io::Result<ToolProcess> {
+        let Some(fault) = self.spec.fault(tool) else {
+            return self.inner.spawn(tool, cmd);
+        };
+        if fault.fail > 0.0 && self.sample() < fault.fail {
+            tracing::debug!(tool, "chaos: failing tool run");
+            return Err(io::Error::other(format!("chaos: injected {tool} failure")));
+        }
+        let mut process = self.inner.spawn(tool, cmd)?;
+        if !fault.delay.is_zero() {
+            let exit = process.exit;
+            process.exit = Box::pin(async move {
+                tokio::time::sleep(fault.delay).await;
+                exit.await
+            });
+        }
+        Ok(process)
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Consecutive failures that open a tool's circuit when `[tools] circuit_failures` and
+/// `--tool-circuit-failures` are both unset.
+pub const DEFAULT_FAILURES: u32 = 5;
+/// Seconds an open circuit stays open when `[tools] circuit_cooldown_secs` and
+/// `--tool-circuit-cooldown-secs` are unset.
+pub const DEFAULT_COOLDOWN_SECS: u64 = 30;
+
+/// When to stop running an optional tool (fd, ast-grep, rga) that keeps failing.
+///
+/// Failures and timeouts are counted per tool and configured binary across every search in the
+/// process, so one tenant's broken `[tools.ast_grep] path` does not trip another's. After
+/// `failures` in a row the circuit opens and searches skip the tool's stage for `cooldown`;
+/// the first search after that tries the tool again, closing the circuit on success and
+/// reopening it on another failure. `failures == 0` never opens it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitPolicy {
+    pub failures: u32,
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitPolicy {
+    fn default() -> Self {
+        Self {
+            failures: DEFAULT_FAILURES,
+            cooldown: Duration::from_secs(DEFAULT_COOLDOWN_SECS),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Circuit {
+    consecutive_failures: u32,
+    open_until: Option<Instant>,
+}
+
+/// Tool name and configured binary; `None` runs the tool from `PATH`.
+type CircuitKey = (&'static str, Option<PathBuf>);
+
+fn circuits() -> &'static Mutex<HashMap<CircuitKey, Circuit>> {
+    static CIRCUITS: OnceLock<Mutex<HashMap<CircuitKey, Circuit>>> = OnceLock::new();
+    CIRCUITS.get_or_init(Default::default)
+}
+
+impl CircuitPolicy {
+    /// Time left before `tool` run from `binary` is tried again; `None` while its circuit is
+    /// closed.
+    pub fn open_for(&self, tool: &'static str, binary: Option<&Path>) -> Option<Duration> {
+        if self.failures == 0 {
+            return None;
+        }
+        let circuits = circuits().lock().unwrap();
+        let open_until = circuits
+            .get(&(tool, binary.map(Path::to_path_buf)))?
+            .open_until?;
+        open_until.checked_duration_since(Instant::now())
+    }
+
+    /// Count one run of `tool` from `binary`; a success closes its circuit.
+    pub fn record(&self, tool: &'static str, binary: Option<&Path>, failed: bool) {
+        let mut circuits = circuits().lock().unwrap();
+        let circuit = circuits
+            .entry((tool, binary.map(Path::to_path_buf)))
+            .or_default();
+        if !failed {
+            *circuit = Circuit::default();
+            return;
+        }
+        circuit.consecutive_failures = circuit.consecutive_failures.saturating_add(1);
+        if self.failures > 0 && circuit.consecutive_failures >= self.failures {
+            if circuit.consecutive_failures == self.failures {
+                tracing::warn!(
+                    tool,
+                    failures = self.failures,
+                    cooldown_secs = self.cooldown.as_secs(),
+                    "tool keeps failing; skipping it until the cooldown ends"
+                );
+            }
+            circuit.open_until = Some(Instant::now() + self.cooldown);
+        }
+    }
+}
@@ -1,18 +1,87 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 use tokio::time::timeout;
 
-use super::common::ChildGuard;
+use crate::config::ToolSettings;
+use crate::identifier;
+
+use super::backend::{self, ToolBackend};
+use super::binary;
+use super::retry::RetryPolicy;
+
+/// File-name matches per search when a search does not ask for another cap.
+pub const DEFAULT_MAX_RESULTS: usize = 200;
+
+/// Needles of one batch of searches under `root` that may share a single fd walk; see
+/// [`FdTool::share`].
+#[derive(Debug)]
+pub struct SharedWalk {
+    root: PathBuf,
+    needles: Vec<String>,
+    served: AtomicUsize,
+}
+
+impl SharedWalk {
+    /// Forget the needles no search asked for; returns how many searches the walk served.
+    pub fn finish(self: &Arc<Self>) -> usize {
+        shared()
+            .lock()
+            .unwrap()
+            .retain(|_, entry| !entry.belongs_to(self));
+        self.served.load(Ordering::Relaxed)
+    }
+}
+
+enum SharedEntry {
+    /// No search of the batch has walked yet.
+    Pending(Arc<SharedWalk>),
+    /// Another search's walk found these matches.
+    Ready(Arc<SharedWalk>, Vec<PathBuf>),
+}
+
+impl SharedEntry {
+    fn belongs_to(&self, walk: &Arc<SharedWalk>) -> bool {
+        match self {
+            Self::Pending(entry) | Self::Ready(entry, _) => Arc::ptr_eq(entry, walk),
+        }
+    }
+}
+
+/// Root and needle of a search waiting on a shared walk.
+type SharedKey = (PathBuf, String);
+
+fn shared() -> &'static Mutex<HashMap<SharedKey, SharedEntry>> {
+    static SHARED: OnceLock<Mutex<HashMap<SharedKey, SharedEntry>>> = OnceLock::new();
+    SHARED.get_or_init(Default::default)
+}
+
+/// Whether fd, with its smart case, would match `needle` (an identifier) against `path`.
+fn name_matches(path: &Path, needle: &str) -> bool {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    if needle.chars().any(char::is_uppercase) {
+        name.contains(needle)
+    } else {
+        name.to_lowercase().contains(needle)
+    }
+}
 
 /// Async wrapper around the `fd` command.
 #[derive(Clone, Debug)]
 pub struct FdTool {
     timeout: Duration,
     max_results: usize,
+    settings: ToolSettings,
+    retry: RetryPolicy,
+    backend: Arc<dyn ToolBackend>,
 }
 
 impl FdTool {
@@ -20,42 +89,176 @@ impl FdTool {
         Self {
             timeout,
             max_results,
+            settings: ToolSettings::default(),
+            retry: RetryPolicy::default(),
+            backend: backend::process(),
         }
     }
 
+    /// Use a configured binary and extra arguments instead of the `PATH` lookup.
+    pub fn with_settings(mut self, settings: ToolSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Retry transient spawn failures and timeouts per `policy`.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Start runs through `backend` instead of spawning the binary directly.
+    pub fn with_backend(mut self, backend: Arc<dyn ToolBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Files under `root` whose name matches `needle`.
+    ///
+    /// A needle registered with [`Self::share`] walks once for every needle of its batch that
+    /// is still waiting, or takes the matches such a walk already found.
     pub async fn run(&self, root: &Path, needle: &str) -> Result<Vec<PathBuf>> {
-        let mut cmd = Command::new("fd");
+        let key = (root.to_path_buf(), needle.to_string());
+        let walk = match shared().lock().unwrap().remove(&key) {
+            Some(SharedEntry::Ready(walk, matches)) => {
+                walk.served.fetch_add(1, Ordering::Relaxed);
+                return Ok(matches);
+            }
+            Some(SharedEntry::Pending(walk)) => Some(walk),
+            None => None,
+        };
+        if let Some(walk) = walk
+            && let Some(matches) = self.walk_shared(&walk, needle).await
+        {
+            return Ok(matches);
+        }
+        self.retry
+            .run("fd", move || {
+                self.run_once(root, needle, self.max_results, false)
+            })
+            .await
+    }
+
+    /// Let the searches for `needles` under `root` share one fd walk, run by whichever of them
+    /// reaches discovery first; searches answered before discovery cost nothing.
+    ///
+    /// Only plain identifiers take part, since fd reads needles as regular expressions. `None`
+    /// when fewer than two needles would share.
+    pub fn share(root: &Path, needles: &[String]) -> Option<Arc<SharedWalk>> {
+        let mut needles: Vec<String> = needles
+            .iter()
+            .filter(|needle| identifier::is_identifier(needle))
+            .cloned()
+            .collect();
+        needles.sort();
+        needles.dedup();
+        let mut shared = shared().lock().unwrap();
+        // Needles another batch is already sharing keep waiting on that walk.
+        needles.retain(|needle| !shared.contains_key(&(root.to_path_buf(), needle.clone())));
+        if needles.len() < 2 {
+            return None;
+        }
+        let walk = Arc::new(SharedWalk {
+            root: root.to_path_buf(),
+            needles,
+            served: AtomicUsize::new(0),
+        });
+        for needle in &walk.needles {
+            shared.insert(
+                (walk.root.clone(), needle.clone()),
+                SharedEntry::Pending(walk.clone()),
+            );
+        }
+        Some(walk)
+    }
+
+    /// Walk once for `needle` and every needle of `walk` still waiting, handing the others
+    /// their matches; `None` sends them all back to their own runs.
+    async fn walk_shared(&self, walk: &Arc<SharedWalk>, needle: &str) -> Option<Vec<PathBuf>> {
+        let waiting = |shared: &HashMap<SharedKey, SharedEntry>, peer: &String| {
+            matches!(
+                shared.get(&(walk.root.clone(), peer.clone())),
+                Some(SharedEntry::Pending(entry)) if Arc::ptr_eq(entry, walk)
+            )
+        };
+        let peers: Vec<String> = {
+            let shared = shared().lock().unwrap();
+            walk.needles
+                .iter()
+                .filter(|peer| waiting(&shared, peer))
+                .cloned()
+                .collect()
+        };
+        if peers.is_empty() {
+            return None;
+        }
+
+        let pattern = format!("{needle}|{}", peers.join("|"));
+        let cap = self.max_results.saturating_mul(peers.len() + 1);
+        let matches = self
+            .retry
+            .run("fd", || self.run_once(&walk.root, &pattern, cap, true))
+            .await;
+        let mut shared = shared().lock().unwrap();
+        let matches = match matches {
+            Ok(matches) if matches.len() < cap => matches,
+            outcome => {
+                if let Err(err) = outcome {
+                    tracing::debug!(error = %err, "shared fd walk failed");
+                } else {
+                    tracing::debug!(cap, "shared fd walk reached its cap");
+                }
+                shared.retain(|_, entry| !entry.belongs_to(walk));
+                return None;
+            }
+        };
+        let found = |needle: &str| -> Vec<PathBuf> {
+            matches
+                .iter()
+                .filter(|path| name_matches(path, needle))
+                .take(self.max_results)
+                .cloned()
+                .collect()
+        };
+        for peer in peers {
+            if waiting(&shared, &peer) {
+                let entry = SharedEntry::Ready(walk.clone(), found(&peer));
+                shared.insert((walk.root.clone(), peer), entry);
+            }
+        }
+        walk.served.fetch_add(1, Ordering::Relaxed);
+        Some(found(needle))
+    }
+
+    async fn run_once(
+        &self,
+        root: &Path,
+        pattern: &str,
+        max_results: usize,
+        ignore_case: bool,
+    ) -> Result<Vec<PathBuf>> {
+        let mut cmd = binary::configured_command("fd", &self.settings);
         cmd.arg("--type")
             .arg("f")
             .arg("--hidden")
             .arg("--color")
             .arg("never")
             .arg("--max-results")
-            .arg(self.max_results.to_string())
-            .arg(needle)
-            .arg(".");
+            .arg(max_results.to_string());
+        if ignore_case {
+            cmd.arg("--ignore-case");
+        }
+        cmd.arg(pattern).arg(".");
         cmd.current_dir(root);
-        cmd.stdout(std::process::Stdio::piped());
-        cmd.stderr(std::process::Stdio::piped());
 
-        let child = cmd
-            .spawn()
+        let process = self
+            .backend
+            .spawn("fd", cmd)
             .with_context(|| "failed to spawn fd; is it installed and on PATH?")?;
+        let stderr = process.stderr;
+        let exit = process.exit;
 
-        // Wrap child in guard to ensure cleanup on timeout/early exit
-        let mut guard = ChildGuard::new(child);
-        let child_ref = guard.as_mut().context("child process unavailable")?;
-
-        let stdout = child_ref
-            .stdout
-            .take()
-            .context("fd did not produce stdout pipe")?;
-        let stderr = child_ref
-            .stderr
-            .take()
-            .context("fd did not produce stderr pipe")?;
-
-        let mut reader = BufReader::new(stdout).lines();
+        let mut reader = BufReader::new(process.stdout).lines();
         let mut matches = Vec::new();
 
         let collect = async {
@@ -67,9 +270,7 @@ impl FdTool {
                 matches.push(joined);
             }
 
-            // Take ownership from guard before waiting (prevents kill on normal exit)
-            let mut child = guard.take().context("child process already taken")?;
-            let status = child.wait().await?;
+            let status = exit.await?;
 
             if !status.success() && status.code() != Some(1) {
                 // fd returns 1 when no results are found; treat this as a non-fatal outcome.
@@ -0,0 +1,262 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::time::timeout;
+
+use super::binary;
+
+/// Async wrapper around the `git` CLI for read-only access to the object database.
+#[derive(Clone, Debug)]
+pub struct GitTool {
+    timeout: Duration,
+}
+
+impl GitTool {
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+
+    /// Resolve `rev` to a full commit id.
+    pub async fn rev_parse(&self, repo: &Path, rev: &str) -> Result<String> {
+        if rev.trim().is_empty() || rev.starts_with('-') {
+            anyhow::bail!("invalid git revision `{rev}`");
+        }
+        let spec = format!("{rev}^{{commit}}");
+        let output = self
+            .run(repo, &["rev-parse", "--verify", "--quiet", &spec])
+            .await
+            .with_context(|| format!("unknown git revision `{rev}`"))?;
+        Ok(output.trim().to_string())
+    }
+
+    /// Path of `repo` relative to its working tree top level (empty at the top level).
+    pub async fn show_prefix(&self, repo: &Path) -> Result<PathBuf> {
+        let output = self.run(repo, &["rev-parse", "--show-prefix"]).await?;
+        Ok(PathBuf::from(output.trim()))
+    }
+
+    /// Files under `repo` at `commit` whose text contains every one of `terms`, ignoring case,
+    /// relative to the repository's top level. With no terms every text file matches.
+    pub async fn grep_files(
+        &self,
+        repo: &Path,
+        commit: &str,
+        terms: &[String],
+    ) -> Result<Vec<PathBuf>> {
+        let mut args = vec![
+            "grep",
+            "-z",
+            "-l",
+            "-I",
+            "-i",
+            "-F",
+            "--full-name",
+            "--all-match",
+        ];
+        if terms.is_empty() {
+            args.extend(["-e", ""]);
+        }
+        for term in terms {
+            args.extend(["-e", term.as_str()]);
+        }
+        args.push(commit);
+        let output = self.output(repo, &args).await?;
+        // `git grep` exits 1 when nothing matched.
+        if output.status.code() == Some(1) && output.stderr.is_empty() {
+            return Ok(Vec::new());
+        }
+        check_status(&args, &output)?;
+        let prefix = format!("{commit}:");
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter_map(|entry| entry.strip_prefix(&prefix))
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    /// Every file in the tree of `commit`, relative to the repository's top level.
+    pub async fn list_files(&self, repo: &Path, commit: &str) -> Result<Vec<PathBuf>> {
+        let output = self
+            .run(
+                repo,
+                &["ls-tree", "-r", "-z", "--name-only", "--full-tree", commit],
+            )
+            .await?;
+        Ok(output
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect())
+    }
+
+    /// Write the blobs of `paths` (relative to the top level) at `commit` below `dest`, reading
+    /// them from the object database with a single `git cat-file --batch`.
+    pub async fn write_blobs(
+        &self,
+        repo: &Path,
+        commit: &str,
+        paths: &[PathBuf],
+        dest: &Path,
+    ) -> Result<()> {
+        let mut cmd = binary::command("git");
+        cmd.arg("-C").arg(repo).args(["cat-file", "--batch"]);
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        let mut child = cmd
+            .spawn()
+            .with_context(|| "failed to spawn git; is it installed and on PATH?")?;
+        let mut stdin = child
+            .stdin
+            .take()
+            .context("git cat-file did not open stdin")?;
+        let stdout = child
+            .stdout
+            .take()
+            .context("git cat-file did not open stdout")?;
+
+        let requests: String = paths
+            .iter()
+            .map(|path| format!("{commit}:{}\n", path.to_string_lossy()))
+            .collect();
+        let feed = async move {
+            stdin.write_all(requests.as_bytes()).await?;
+            // Closing stdin lets cat-file exit once it has answered every request.
+            drop(stdin);
+            Ok::<_, std::io::Error>(())
+        };
+        let copy = async {
+            let mut reader = BufReader::new(stdout);
+            for path in paths {
+                let mut header = String::new();
+                reader.read_line(&mut header).await?;
+                let size = match header.trim_end().rsplit_once(' ') {
+                    Some((object, size)) if object.ends_with(" blob") => size
+                        .parse::<usize>()
+                        .with_context(|| format!("unexpected git cat-file header `{header}`"))?,
+                    _ => {
+                        tracing::debug!(path = %path.display(), header = header.trim_end(), "skipping non-blob entry");
+                        continue;
+                    }
+                };
+                let mut blob = vec![0; size + 1];
+                reader.read_exact(&mut blob).await?;
+                blob.pop();
+                let target = dest.join(path);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("failed to create {}", parent.display()))?;
+                }
+                fs::write(&target, blob)
+                    .with_context(|| format!("failed to write {}", target.display()))?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+
+        let result = timeout(self.timeout, async {
+            let (fed, copied) = tokio::join!(feed, copy);
+            fed.context("failed to send paths to git cat-file")?;
+            copied?;
+            child
+                .wait()
+                .await
+                .context("failed to wait for git cat-file")
+        })
+        .await
+        .with_context(|| "git cat-file timed out")??;
+        if !result.success() {
+            anyhow::bail!("git cat-file exited with status {result}");
+        }
+        Ok(())
+    }
+
+    /// Which of `paths` (relative to `repo`) differ from HEAD, including untracked files.
+    /// Returns `(modified, untracked)`, both relative to `repo`.
+    pub async fn uncommitted(
+        &self,
+        repo: &Path,
+        paths: &[PathBuf],
+    ) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+        let pathspecs: Vec<String> = paths
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        let mut diff_args = vec!["diff", "--name-only", "-z", "--relative", "HEAD", "--"];
+        diff_args.extend(pathspecs.iter().map(String::as_str));
+        let modified = self.run(repo, &diff_args).await?;
+
+        let mut untracked_args = vec!["ls-files", "-z", "--others", "--exclude-standard", "--"];
+        untracked_args.extend(pathspecs.iter().map(String::as_str));
+        let untracked = self.run(repo, &untracked_args).await?;
+
+        let split = |output: String| -> Vec<PathBuf> {
+            output
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from)
+                .collect()
+        };
+        Ok((split(modified), split(untracked)))
+    }
+
+    /// Zero-context unified diff of `path` (relative to `repo`) from HEAD to the working tree.
+    pub async fn diff_head(&self, repo: &Path, path: &Path) -> Result<String> {
+        let path = path.to_string_lossy();
+        self.run(
+            repo,
+            &[
+                "diff",
+                "-U0",
+                "--no-color",
+                "--no-ext-diff",
+                "HEAD",
+                "--",
+                &path,
+            ],
+        )
+        .await
+    }
+
+    async fn run(&self, repo: &Path, args: &[&str]) -> Result<String> {
+        let output = self.output(repo, args).await?;
+        check_status(args, &output)?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    async fn output(&self, repo: &Path, args: &[&str]) -> Result<Output> {
+        let mut cmd = binary::command("git");
+        cmd.arg("-C").arg(repo).args(args);
+        // Paths passed after `--` are file names, never glob or magic pathspecs.
+        cmd.env("GIT_LITERAL_PATHSPECS", "1");
+        cmd.stdin(Stdio::null());
+        cmd.kill_on_drop(true);
+
+        timeout(self.timeout, cmd.output())
+            .await
+            .with_context(|| "git invocation timed out")?
+            .with_context(|| "failed to spawn git; is it installed and on PATH?")
+    }
+}
+
+fn check_status(args: &[&str], output: &Output) -> Result<()> {
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let trimmed = stderr.trim();
+    if trimmed.is_empty() {
+        anyhow::bail!("git {} exited with status {}", args[0], output.status);
+    }
+    anyhow::bail!(
+        "git {} exited with status {}: {}",
+        args[0],
+        output.status,
+        trimmed
+    );
+}
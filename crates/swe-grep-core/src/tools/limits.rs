@@ -0,0 +1,175 @@
+//! CPU and memory caps for spawned tools, so one heavy search cannot starve the processes it
+//! shares a container with.
+//!
+//! Limits are applied on Linux in the child between `fork` and `exec`: `setpriority` raises its
+//! niceness and `setrlimit(RLIMIT_AS)` caps its address space. Other platforms run tools
+//! unrestricted.
+//!
+//! Only the niceness has a default. An address-space cap counts reserved and mapped memory,
+//! not what a tool actually uses, so it is applied only when one is asked for.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tokio::process::Command;
+
+/// Niceness tools run at by default inside a cgroup with a CPU quota.
+pub const CGROUP_NICE: i32 = 10;
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Caps applied to every rg, fd, ast-grep, and rga process.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Niceness (0-19) tools run at; never lowered below swe-grep's own.
+    pub nice: Option<i32>,
+    /// Address-space limit in bytes; never set by default.
+    pub mem_limit_bytes: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// `nice` and `mem_limit_bytes` where given; niceness defaults to [`CGROUP_NICE`] inside a
+    /// cgroup with a CPU quota.
+    pub fn resolve(nice: Option<i32>, mem_limit_bytes: Option<u64>) -> Self {
+        Self {
+            nice: nice.or(cgroup_cpu_limited().then_some(CGROUP_NICE)),
+            mem_limit_bytes,
+        }
+    }
+
+    /// Arrange for `cmd`'s process to start under these limits.
+    #[cfg(target_os = "linux")]
+    pub fn apply(&self, cmd: &mut Command) {
+        if *self == Self::default() {
+            return;
+        }
+        let limits = *self;
+        // SAFETY: the hook only makes async-signal-safe system calls and does not allocate.
+        unsafe {
+            cmd.pre_exec(move || limits.apply_to_current_process());
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn apply(&self, _cmd: &mut Command) {}
+
+    #[cfg(target_os = "linux")]
+    fn apply_to_current_process(&self) -> std::io::Result<()> {
+        if let Some(nice) = self.nice {
+            // SAFETY: plain system calls on the calling process.
+            unsafe {
+                // -1 is a valid niceness, so errors are told apart through errno.
+                *libc::__errno_location() = 0;
+                let current = libc::getpriority(libc::PRIO_PROCESS, 0);
+                if *libc::__errno_location() != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                if nice > current && libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+        }
+        if let Some(bytes) = self.mem_limit_bytes {
+            let mut limit = libc::rlimit {
+                rlim_cur: 0,
+                rlim_max: 0,
+            };
+            // SAFETY: `limit` is a valid rlimit for both calls.
+            unsafe {
+                if libc::getrlimit(libc::RLIMIT_AS, &mut limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                let bytes = bytes as libc::rlim_t;
+                if limit.rlim_cur == libc::RLIM_INFINITY || bytes < limit.rlim_cur {
+                    limit.rlim_cur = if limit.rlim_max == libc::RLIM_INFINITY {
+                        bytes
+                    } else {
+                        bytes.min(limit.rlim_max)
+                    };
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse a size such as `512M`, `2G`, `1.5GiB`, or `1048576` (bytes). Suffixes are binary
+/// multiples and ignore case.
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let trimmed = text.trim();
+    let split = trimmed
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(trimmed.len());
+    let (number, unit) = trimmed.split_at(split);
+    let shift = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 0,
+        "k" | "kb" | "kib" => 10,
+        "m" | "mb" | "mib" => 20,
+        "g" | "gb" | "gib" => 30,
+        "t" | "tb" | "tib" => 40,
+        _ => return Err(format!("invalid size `{text}` (expected e.g. 512M or 2G)")),
+    };
+    let value: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size `{text}` (expected e.g. 512M or 2G)"))?;
+    let bytes = value * (1u64 << shift) as f64;
+    if !(1.0..=u64::MAX as f64).contains(&bytes) {
+        return Err(format!("size `{text}` is out of range"));
+    }
+    Ok(bytes as u64)
+}
+
+/// Whether the cgroup swe-grep runs in has a CPU quota, read once per process from cgroup v2
+/// (`cpu.max`) or v1 (`cpu.cfs_quota_us`), in the process's own cgroup or, when the container
+/// mounts its cgroup as the root, at the top of `/sys/fs/cgroup`.
+fn cgroup_cpu_limited() -> bool {
+    static LIMITED: OnceLock<bool> = OnceLock::new();
+    *LIMITED.get_or_init(|| {
+        if !cfg!(target_os = "linux") {
+            return false;
+        }
+        let membership = std::fs::read_to_string("/proc/self/cgroup").unwrap_or_default();
+        let limited = detect_cpu_quota(Path::new(CGROUP_ROOT), &membership);
+        tracing::debug!(cpu_limited = limited, "detected cgroup limits");
+        limited
+    })
+}
+
+fn detect_cpu_quota(root: &Path, membership: &str) -> bool {
+    // Lines are `id:controllers:path`; v2 has the single line `0::path`.
+    let path_for = |controller: &str| {
+        membership.lines().find_map(|line| {
+            let mut fields = line.splitn(3, ':');
+            let (_, controllers, path) = (fields.next()?, fields.next()?, fields.next()?);
+            let matches = if controller.is_empty() {
+                controllers.is_empty()
+            } else {
+                controllers.split(',').any(|name| name == controller)
+            };
+            matches.then(|| path.trim_start_matches('/').to_string())
+        })
+    };
+    let read = |dir: &Path, own: Option<&String>, file: &str| -> Option<String> {
+        own.map(|own| dir.join(own).join(file))
+            .into_iter()
+            .chain([dir.join(file)])
+            .find_map(|candidate: PathBuf| std::fs::read_to_string(candidate).ok())
+            .map(|contents| contents.trim().to_string())
+    };
+
+    if let Some(max) = read(root, path_for("").as_ref(), "cpu.max") {
+        return max
+            .split_whitespace()
+            .next()
+            .is_some_and(|quota| quota != "max");
+    }
+    read(
+        &root.join("cpu"),
+        path_for("cpu").as_ref(),
+        "cpu.cfs_quota_us",
+    )
+    .and_then(|quota| quota.parse::<i64>().ok())
+    .is_some_and(|quota| quota > 0)
+}
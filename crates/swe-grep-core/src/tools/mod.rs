@@ -1,5 +1,15 @@
 pub mod ast_grep;
+pub mod backend;
+pub mod binary;
+#[cfg(feature = "bundled-tools")]
+pub mod bundled;
+#[cfg(feature = "chaos")]
+pub mod chaos;
+pub mod circuit;
 pub mod common;
 pub mod fd;
+pub mod git;
+pub mod limits;
 pub mod rg;
+pub mod retry;
 pub mod rga;
@@ -0,0 +1,68 @@
+use std::future::Future;
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use anyhow::Result;
+
+/// Retries applied when `[tools] retries` and `--tool-retries` are both unset.
+pub const DEFAULT_RETRIES: u32 = 1;
+/// First backoff delay when `[tools] retry_backoff_ms` and `--tool-retry-backoff-ms` are unset.
+pub const DEFAULT_BACKOFF_MS: u64 = 100;
+
+/// How often a tool invocation is repeated after a transient failure.
+///
+/// Only spawn errors and timeouts count as transient; a tool that ran and reported an error is
+/// not retried, and neither is a binary that does not exist. The delay doubles per attempt.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            retries: DEFAULT_RETRIES,
+            backoff: Duration::from_millis(DEFAULT_BACKOFF_MS),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Run `op`, repeating it while it fails transiently and retries remain.
+    pub async fn run<T, F, Fut>(&self, tool: &'static str, mut op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Err(err) if attempt < self.retries && is_transient(&err) => {
+                    attempt += 1;
+                    let delay = self.backoff.saturating_mul(1 << (attempt - 1).min(16));
+                    tracing::debug!(
+                        tool,
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %err,
+                        "retrying tool after transient failure"
+                    );
+                    crate::telemetry::record_tool_retry(tool);
+                    tokio::time::sleep(delay).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Whether `err` came from a timeout or an I/O error other than a missing binary.
+fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause.is::<tokio::time::error::Elapsed>()
+            || cause.downcast_ref::<std::io::Error>().is_some_and(|io| {
+                !matches!(io.kind(), ErrorKind::NotFound | ErrorKind::PermissionDenied)
+            })
+    })
+}
@@ -1,21 +1,51 @@
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
+use futures::stream::{self, StreamExt};
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 use tokio::time::timeout;
 
-use super::common::{ChildGuard, RgMessage};
+use crate::cli::CaseMode;
+use crate::config::ToolSettings;
+use crate::file_types::FileTypes;
+
+use super::backend::{self, ToolBackend};
+use super::binary;
+use super::common::RgMessage;
+use super::retry::RetryPolicy;
+
+/// Most paths handed to one ripgrep invocation; larger scopes are split into chunks.
+const MAX_CHUNK_FILES: usize = 1_000;
+/// Bytes of path arguments per invocation, well under the platform's command-line limit
+/// (32 KiB on Windows, 256 KiB on older macOS) once the other arguments and environment count.
+#[cfg(windows)]
+const MAX_CHUNK_ARG_BYTES: usize = 24 * 1024;
+#[cfg(not(windows))]
+const MAX_CHUNK_ARG_BYTES: usize = 128 * 1024;
+/// Chunks of one scope probed at the same time.
+const PARALLEL_CHUNKS: usize = 4;
 
 #[derive(Clone, Debug)]
 pub struct RipgrepTool {
     timeout: Duration,
     max_matches: usize,
+    max_count: Option<usize>,
     context_before: usize,
     context_after: usize,
     max_columns: usize,
     threads: usize,
+    case: CaseMode,
+    /// Read queries as literal strings (`--fixed-strings`).
+    fixed_strings: bool,
+    /// Match queries only as whole words (`--word-regexp`).
+    word_regexp: bool,
+    settings: ToolSettings,
+    retry: RetryPolicy,
+    backend: Arc<dyn ToolBackend>,
+    /// `--type-add`/`--type` arguments limiting directory walks to some file types.
+    type_args: Vec<String>,
 }
 
 impl RipgrepTool {
@@ -30,13 +60,68 @@ impl RipgrepTool {
         Self {
             timeout,
             max_matches,
+            max_count: Some(max_matches),
             context_before,
             context_after,
             max_columns,
             threads: usize::max(1, threads),
+            case: CaseMode::default(),
+            fixed_strings: false,
+            word_regexp: false,
+            settings: ToolSettings::default(),
+            retry: RetryPolicy::default(),
+            backend: backend::process(),
+            type_args: Vec::new(),
         }
     }
 
+    /// Use a configured binary and extra arguments instead of the `PATH` lookup.
+    pub fn with_settings(mut self, settings: ToolSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Cap matches per file at `max_count` instead of `max_matches`; `None` reads them all.
+    pub fn with_max_count(mut self, max_count: Option<usize>) -> Self {
+        self.max_count = max_count;
+        self
+    }
+
+    /// Match case per `case` instead of `--smart-case`.
+    pub fn with_case(mut self, case: CaseMode) -> Self {
+        self.case = case;
+        self
+    }
+
+    /// Read queries as literal strings rather than regexes, optionally matching only whole
+    /// words, since `\b` anchors cannot be spelled in a literal.
+    pub fn with_fixed_strings(mut self, enabled: bool, whole_word: bool) -> Self {
+        self.fixed_strings = enabled;
+        self.word_regexp = enabled && whole_word;
+        self
+    }
+
+    /// Only walk files of `types`; paths passed explicitly are searched whatever their type.
+    pub fn with_types(mut self, types: Option<&FileTypes>) -> Self {
+        self.type_args = types.map(FileTypes::rg_args).unwrap_or_default();
+        self
+    }
+
+    /// Retry transient spawn failures and timeouts per `policy`.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Start runs through `backend` instead of spawning the binary directly.
+    pub fn with_backend(mut self, backend: Arc<dyn ToolBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Search `paths` (the whole root when empty) for any of `queries`. Scopes too large for one
+    /// command line are split into chunks searched in parallel; matches keep the scope's order
+    /// and are capped at `max_matches` overall.
     pub async fn search_union(
         &self,
         root: &Path,
@@ -47,7 +132,48 @@ impl RipgrepTool {
             return Ok(Vec::new());
         }
 
-        let mut cmd = Command::new("rg");
+        let relative: Vec<PathBuf> = paths.iter().map(|path| relative_to(root, path)).collect();
+        let chunks = chunk_scope(&relative);
+        if chunks.len() <= 1 {
+            let scope = chunks.first().copied().unwrap_or_default();
+            return self
+                .retry
+                .run("rg", move || self.search_union_once(root, queries, scope))
+                .await;
+        }
+
+        tracing::debug!(
+            files = relative.len(),
+            chunks = chunks.len(),
+            "splitting ripgrep scope into chunks"
+        );
+        // Built up front: a closure mapping chunks to futures trips up `Send` inference in
+        // the services' handlers.
+        let mut runs = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            runs.push(
+                self.retry
+                    .run("rg", move || self.search_union_once(root, queries, chunk)),
+            );
+        }
+        let results: Vec<Result<Vec<RipgrepMatch>>> =
+            stream::iter(runs).buffered(PARALLEL_CHUNKS).collect().await;
+        let mut matches = Vec::new();
+        for result in results {
+            matches.extend(result?);
+        }
+        matches.truncate(self.max_matches);
+        Ok(matches)
+    }
+
+    /// One ripgrep run over `paths`, relative to `root`.
+    async fn search_union_once(
+        &self,
+        root: &Path,
+        queries: &[String],
+        paths: &[PathBuf],
+    ) -> Result<Vec<RipgrepMatch>> {
+        let mut cmd = binary::configured_command("rg", &self.settings);
         cmd.arg("--json")
             .arg("--line-number")
             .arg("--column")
@@ -55,9 +181,17 @@ impl RipgrepTool {
             .arg(self.threads.to_string())
             .arg("--max-columns")
             .arg(self.max_columns.to_string())
-            .arg("--smart-case")
-            .arg("--max-count")
-            .arg(self.max_matches.to_string());
+            .arg(self.case.rg_flag());
+        if let Some(max_count) = self.max_count {
+            cmd.arg("--max-count").arg(max_count.to_string());
+        }
+        if self.fixed_strings {
+            cmd.arg("--fixed-strings");
+        }
+        if self.word_regexp {
+            cmd.arg("--word-regexp");
+        }
+        cmd.args(&self.type_args);
 
         if self.context_before > 0 {
             cmd.arg("--before-context")
@@ -75,42 +209,19 @@ impl RipgrepTool {
         if paths.is_empty() {
             cmd.arg(".");
         } else {
-            for path in paths.iter().take(self.max_matches) {
-                let absolute = if path.is_absolute() {
-                    path.clone()
-                } else {
-                    root.join(path)
-                };
-                let relative = absolute
-                    .strip_prefix(root)
-                    .map(|p| p.to_path_buf())
-                    .unwrap_or(absolute);
-                cmd.arg(relative);
-            }
+            cmd.args(paths);
         }
 
         cmd.current_dir(root);
-        cmd.stdout(std::process::Stdio::piped());
-        cmd.stderr(std::process::Stdio::piped());
 
-        let child = cmd
-            .spawn()
+        let process = self
+            .backend
+            .spawn("rg", cmd)
             .with_context(|| "failed to spawn ripgrep; is rg installed and on PATH?")?;
+        let stderr = process.stderr;
+        let exit = process.exit;
 
-        // Wrap child in guard to ensure cleanup on timeout/early exit
-        let mut guard = ChildGuard::new(child);
-        let child_ref = guard.as_mut().context("child process unavailable")?;
-
-        let stdout = child_ref
-            .stdout
-            .take()
-            .context("ripgrep did not produce stdout pipe")?;
-        let stderr = child_ref
-            .stderr
-            .take()
-            .context("ripgrep did not produce stderr pipe")?;
-
-        let mut reader = BufReader::new(stdout).lines();
+        let mut reader = BufReader::new(process.stdout).lines();
         let mut matches = Vec::new();
         let max_matches = self.max_matches;
 
@@ -132,14 +243,16 @@ impl RipgrepTool {
                         path,
                         line_number: data.line_number,
                         lines: data.lines.text,
+                        submatch: data
+                            .submatches
+                            .first()
+                            .map(|submatch| (submatch.start, submatch.end)),
                         raw_json: line.clone(),
                     });
                 }
             }
 
-            // Take ownership from guard before waiting (prevents kill on normal exit)
-            let mut child = guard.take().context("child process already taken")?;
-            let status = child.wait().await?;
+            let status = exit.await?;
 
             if !status.success() && status.code() != Some(1) {
                 // Capture stderr for better error diagnostics
@@ -174,10 +287,47 @@ impl RipgrepTool {
     }
 }
 
+/// `path` relative to `root` when it lies inside it, otherwise unchanged.
+fn relative_to(root: &Path, path: &Path) -> PathBuf {
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        root.join(path)
+    };
+    absolute
+        .strip_prefix(root)
+        .map(|p| p.to_path_buf())
+        .unwrap_or(absolute)
+}
+
+/// Split `paths` into runs that each fit one command line.
+fn chunk_scope(paths: &[PathBuf]) -> Vec<&[PathBuf]> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0;
+    for (index, path) in paths.iter().enumerate() {
+        // Each argument also costs a pointer and a terminating NUL.
+        let cost = path.as_os_str().len() + std::mem::size_of::<usize>() + 1;
+        if index > start && (index - start >= MAX_CHUNK_FILES || bytes + cost > MAX_CHUNK_ARG_BYTES)
+        {
+            chunks.push(&paths[start..index]);
+            start = index;
+            bytes = 0;
+        }
+        bytes += cost;
+    }
+    if start < paths.len() {
+        chunks.push(&paths[start..]);
+    }
+    chunks
+}
+
 #[derive(Clone, Debug)]
 pub struct RipgrepMatch {
     pub path: PathBuf,
     pub line_number: usize,
     pub lines: String,
+    /// Byte range of the first match within `lines`.
+    pub submatch: Option<(usize, usize)>,
     pub raw_json: String,
 }
@@ -1,17 +1,27 @@
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::{Context, Result};
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 use tokio::time::timeout;
 
-use super::common::{ChildGuard, RgMessage};
+use crate::config::ToolSettings;
+
+use super::backend::{self, ToolBackend};
+use super::binary;
+use super::common::RgMessage;
+use super::retry::RetryPolicy;
 
 #[derive(Clone, Debug)]
 pub struct RgaTool {
     timeout: Duration,
     max_matches: usize,
+    settings: ToolSettings,
+    retry: RetryPolicy,
+    backend: Arc<dyn ToolBackend>,
+    adapters: Vec<String>,
+    word_regexp: bool,
 }
 
 impl RgaTool {
@@ -19,40 +29,74 @@ impl RgaTool {
         Self {
             timeout,
             max_matches,
+            settings: ToolSettings::default(),
+            retry: RetryPolicy::default(),
+            backend: backend::process(),
+            adapters: Vec::new(),
+            word_regexp: false,
         }
     }
 
+    /// Use a configured binary and extra arguments instead of the `PATH` lookup.
+    pub fn with_settings(mut self, settings: ToolSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Retry transient spawn failures and timeouts per `policy`.
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Start runs through `backend` instead of spawning the binary directly.
+    pub fn with_backend(mut self, backend: Arc<dyn ToolBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Restrict ripgrep-all to these adapters (`--rga-adapters`); empty keeps its defaults.
+    pub fn with_adapters(mut self, adapters: Vec<String>) -> Self {
+        self.adapters = adapters;
+        self
+    }
+
+    /// Match the query only as a whole word (`--word-regexp`).
+    pub fn with_word_regexp(mut self, enabled: bool) -> Self {
+        self.word_regexp = enabled;
+        self
+    }
+
     pub async fn search(&self, root: &Path, query: &str) -> Result<Vec<RgaMatch>> {
-        let mut cmd = Command::new("rga");
+        self.retry
+            .run("rga", move || self.search_once(root, query))
+            .await
+    }
+
+    async fn search_once(&self, root: &Path, query: &str) -> Result<Vec<RgaMatch>> {
+        let mut cmd = binary::configured_command("rga", &self.settings);
+        if !self.adapters.is_empty() {
+            cmd.arg(format!("--rga-adapters={}", self.adapters.join(",")));
+        }
         cmd.arg("--json")
             .arg("--line-number")
             .arg("--column")
             .arg("--max-columns")
-            .arg("200")
-            .arg(query)
-            .arg(".");
+            .arg("200");
+        if self.word_regexp {
+            cmd.arg("--word-regexp");
+        }
+        cmd.arg(query).arg(".");
         cmd.current_dir(root);
-        cmd.stdout(std::process::Stdio::piped());
-        cmd.stderr(std::process::Stdio::piped());
 
-        let child = cmd
-            .spawn()
+        let process = self
+            .backend
+            .spawn("rga", cmd)
             .with_context(|| "failed to spawn rga; is ripgrep-all installed and on PATH?")?;
+        let stderr = process.stderr;
+        let exit = process.exit;
 
-        // Wrap child in guard to ensure cleanup on timeout/early exit
-        let mut guard = ChildGuard::new(child);
-        let child_ref = guard.as_mut().context("child process unavailable")?;
-
-        let stdout = child_ref
-            .stdout
-            .take()
-            .context("rga did not produce stdout pipe")?;
-        let stderr = child_ref
-            .stderr
-            .take()
-            .context("rga did not produce stderr pipe")?;
-
-        let mut reader = BufReader::new(stdout).lines();
+        let mut reader = BufReader::new(process.stdout).lines();
         let mut matches = Vec::new();
         let max_matches = self.max_matches;
 
@@ -69,18 +113,17 @@ impl RgaTool {
                     }
                 };
                 if let RgMessage::Match { data } = parsed {
-                    let path = PathBuf::from(data.path.text);
+                    let (path, inner_path) = split_archive_path(root, Path::new(&data.path.text));
                     matches.push(RgaMatch {
                         path,
+                        inner_path,
                         line_number: data.line_number,
                         lines: data.lines.text,
                     });
                 }
             }
 
-            // Take ownership from guard before waiting (prevents kill on normal exit)
-            let mut child = guard.take().context("child process already taken")?;
-            let status = child.wait().await?;
+            let status = exit.await?;
 
             if !status.success() && status.code() != Some(1) {
                 // Capture stderr for better error diagnostics
@@ -113,7 +156,36 @@ impl RgaTool {
 
 #[derive(Clone, Debug)]
 pub struct RgaMatch {
+    /// File on disk; the archive itself when the match came from one of its members.
     pub path: PathBuf,
+    /// Member of the archive at `path` that matched, e.g. `docs/guide.md` in `manual.zip`.
+    pub inner_path: Option<PathBuf>,
     pub line_number: usize,
     pub lines: String,
 }
+
+/// Split a path ripgrep-all reported for an archive member (`foo.zip/inner.txt`) into the
+/// archive on disk and the member inside it.
+///
+/// The archive is the shortest prefix of `path` that is a regular file under `root`; nested
+/// archives stay in the inner path. Paths that exist as given, or whose prefixes do not, are
+/// returned unsplit.
+pub fn split_archive_path(root: &Path, path: &Path) -> (PathBuf, Option<PathBuf>) {
+    let absolute = root.join(path);
+    if absolute.exists() {
+        return (path.to_path_buf(), None);
+    }
+    let mut archive = PathBuf::new();
+    let mut components = path.components();
+    while let Some(component) = components.next() {
+        archive.push(component);
+        if root.join(&archive).is_file() {
+            let inner = components.as_path();
+            if inner.as_os_str().is_empty() {
+                break;
+            }
+            return (archive, Some(inner.to_path_buf()));
+        }
+    }
+    (path.to_path_buf(), None)
+}
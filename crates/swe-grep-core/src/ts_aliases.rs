@@ -0,0 +1,258 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::identifier;
+
+/// Rename hops followed from the searched name before giving up.
+pub const MAX_ALIAS_DEPTH: usize = 3;
+
+/// Share of a file's statement lines that must be imports and re-exports for it to count as a
+/// barrel file.
+const BARREL_RATIO: f32 = 0.8;
+
+/// Larger files are never treated as barrels; re-export lists stay small.
+const MAX_BARREL_BYTES: u64 = 64 * 1024;
+
+/// Extensions tried, in order, for an extensionless relative import.
+const MODULE_EXTENSIONS: &[&str] = &["ts", "tsx", "mts", "js", "jsx", "mjs"];
+
+/// One rename followed from the searched name, reported in the summary's `aliases`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SymbolAlias {
+    /// The searched name, or the target of an earlier hop.
+    pub alias: String,
+    /// The name `alias` stands for; its rewrites were added to the search.
+    pub target: String,
+    pub kind: AliasKind,
+    /// Where the rename was found.
+    pub path: String,
+    pub line: usize,
+}
+
+/// How a TypeScript/JavaScript module gives a binding another name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum AliasKind {
+    /// `export { target as alias }` or `import { target as alias }`.
+    Renamed,
+    /// `import alias from './module'`, where the module has `export default target`.
+    DefaultExport,
+}
+
+impl AliasKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Renamed => "renamed",
+            Self::DefaultExport => "default_export",
+        }
+    }
+}
+
+/// Whether `path` is a TypeScript or JavaScript module that can hold renames.
+pub fn is_module_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| MODULE_EXTENSIONS.contains(&ext))
+}
+
+/// Whether the TypeScript/JavaScript module at `path` is a barrel file: one that consists
+/// mostly of re-exports (`export * from`, `export { x } from`, or imports passed on through
+/// an `export { ... }` list), such as the `index.ts` of a package.
+pub fn is_barrel_file(path: &Path) -> bool {
+    if !is_module_path(path)
+        || fs::metadata(path).map_or(true, |metadata| metadata.len() > MAX_BARREL_BYTES)
+    {
+        return false;
+    }
+    fs::read_to_string(path).is_ok_and(|source| is_barrel(&source))
+}
+
+fn is_barrel(source: &str) -> bool {
+    let (mut statements, mut reexports, mut exported) = (0usize, 0usize, false);
+    // Lines of an import or export list that continues past its first line.
+    let mut in_list = false;
+    let mut in_comment = false;
+    for line in source.lines() {
+        let line = line.trim();
+        if in_comment {
+            in_comment = !line.contains("*/");
+            continue;
+        }
+        if line.starts_with("/*") {
+            in_comment = !line.contains("*/");
+            continue;
+        }
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        statements += 1;
+        if in_list {
+            reexports += 1;
+            in_list = !line.contains('}');
+            continue;
+        }
+        let export = line.strip_prefix("export ").map(str::trim_start);
+        let passes_on = match export {
+            Some(rest) => {
+                let rest = rest.strip_prefix("type ").unwrap_or(rest).trim_start();
+                rest.starts_with('*') || rest.starts_with('{')
+            }
+            None => line.starts_with("import "),
+        };
+        if !passes_on {
+            continue;
+        }
+        reexports += 1;
+        exported |= export.is_some();
+        in_list = line.contains('{') && !line.contains('}');
+    }
+    exported && reexports as f32 >= statements as f32 * BARREL_RATIO
+}
+
+/// Names the line `text` of `path` (relative to `root`) binds to `name` under another name,
+/// e.g. `loginUser` for `export { loginUser as signIn }` when `name` is `signIn`.
+pub fn targets(root: &Path, path: &Path, text: &str, name: &str) -> Vec<(String, AliasKind)> {
+    let mut found = Vec::new();
+    let statement = text.trim();
+    for (target, alias) in renamed_bindings(statement) {
+        if alias != name {
+            continue;
+        }
+        if target == "default" {
+            // `export { default as name } from './module'`
+            if let Some(target) = from_specifier(statement)
+                .and_then(|specifier| default_export_of(root, path, specifier))
+                .filter(|target| target != name)
+            {
+                found.push((target, AliasKind::DefaultExport));
+            }
+        } else {
+            found.push((target.to_string(), AliasKind::Renamed));
+        }
+    }
+    if let Some(target) = default_import(statement, name)
+        .and_then(|specifier| default_export_of(root, path, specifier))
+        .filter(|target| target != name)
+    {
+        found.push((target, AliasKind::DefaultExport));
+    }
+    found
+}
+
+/// `(target, alias)` pairs of `target as alias` entries in an import or export list. Lines of a
+/// list split over several lines are accepted when they hold nothing but list entries, so
+/// `value as Type` casts in ordinary code are not taken for renames.
+fn renamed_bindings(statement: &str) -> Vec<(&str, &str)> {
+    let list = if statement.starts_with("export") || statement.starts_with("import") {
+        match statement.split_once('{') {
+            Some((_, rest)) => rest.split('}').next().unwrap_or(rest),
+            None => return Vec::new(),
+        }
+    } else {
+        let list = statement.trim_end_matches([',', ';']);
+        let list = list.split('}').next().unwrap_or(list);
+        let list = list.trim_start_matches('{');
+        if list.contains(['(', ')', '=', '.', ':']) {
+            return Vec::new();
+        }
+        list
+    };
+    list.split(',')
+        .filter_map(|entry| {
+            let entry = entry.trim();
+            let entry = entry.strip_prefix("type ").unwrap_or(entry);
+            let mut words = entry.split_whitespace();
+            let (target, keyword, alias) = (words.next()?, words.next()?, words.next()?);
+            (keyword == "as"
+                && words.next().is_none()
+                && identifier::is_identifier(target)
+                && identifier::is_identifier(alias))
+            .then_some((target, alias))
+        })
+        .collect()
+}
+
+/// Module specifier of `import name from '...'` or `import name, { ... } from '...'`.
+fn default_import<'a>(statement: &'a str, name: &str) -> Option<&'a str> {
+    let rest = statement.strip_prefix("import ")?.trim_start();
+    let rest = rest.strip_prefix("type ").unwrap_or(rest).trim_start();
+    let rest = rest.strip_prefix(name)?;
+    let next = rest.trim_start();
+    if !(next.starts_with(',') || next.starts_with("from"))
+        || rest.starts_with(identifier::is_continue)
+    {
+        return None;
+    }
+    from_specifier(statement)
+}
+
+/// The quoted specifier after `from`.
+fn from_specifier(statement: &str) -> Option<&str> {
+    let (_, rest) = statement.rsplit_once(" from ")?;
+    let rest = rest.trim_start();
+    let quote = rest
+        .chars()
+        .next()
+        .filter(|c| matches!(c, '\'' | '"' | '`'))?;
+    rest[1..].split(quote).next()
+}
+
+/// Name of the default export of the relative module `specifier` imported by `importer`.
+fn default_export_of(root: &Path, importer: &Path, specifier: &str) -> Option<String> {
+    if !specifier.starts_with('.') {
+        return None;
+    }
+    let base = root.join(importer).parent()?.join(specifier);
+    let source = fs::read_to_string(resolve_module(&base)?).ok()?;
+    source.lines().find_map(default_export_name)
+}
+
+/// The file a relative import resolves to, trying TypeScript and JavaScript extensions and
+/// `index` files; `./util.js` also finds `util.ts`, as TypeScript's ESM resolution does.
+fn resolve_module(base: &Path) -> Option<PathBuf> {
+    let mut candidates = vec![base.to_path_buf()];
+    let stem = match base.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if MODULE_EXTENSIONS.contains(&ext) => base.with_extension(""),
+        _ => base.to_path_buf(),
+    };
+    for ext in MODULE_EXTENSIONS {
+        candidates.push(stem.with_extension(ext));
+    }
+    for ext in MODULE_EXTENSIONS {
+        candidates.push(base.join(format!("index.{ext}")));
+    }
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+/// The name a line exports as default: `export default function name`, `export default class
+/// name`, `export default name;`, or `export { name as default }`.
+fn default_export_name(line: &str) -> Option<String> {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("export default ") {
+        let mut rest = rest.trim_start();
+        let mut declaration = false;
+        for keyword in ["async ", "function*", "function ", "abstract ", "class "] {
+            if let Some(stripped) = rest.strip_prefix(keyword) {
+                declaration |= keyword != "async ";
+                rest = stripped.trim_start();
+            }
+        }
+        let name: String = rest
+            .chars()
+            .take_while(|c| identifier::is_continue(*c))
+            .collect();
+        let after = rest[name.len()..].trim_start();
+        // A bare value must be the whole expression; `export default connect(App)` names no
+        // binding.
+        let named = declaration || after.is_empty() || after.starts_with(';');
+        return (identifier::is_identifier(&name) && named).then_some(name);
+    }
+    renamed_bindings(line)
+        .into_iter()
+        .find(|(_, alias)| *alias == "default")
+        .map(|(target, _)| target.to_string())
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::cli::SearchArgs;
+use crate::file_types::FileTypes;
+
+/// Longest symbol accepted, in bytes; it is passed to every tool on the command line.
+pub const MAX_SYMBOL_LENGTH: usize = 256;
+/// Longest `language`, `profile`, `owner`, `rg_types`, or `rg_type_adds` value, in bytes.
+pub const MAX_NAME_LENGTH: usize = 256;
+pub const MAX_MATCHES: usize = 10_000;
+pub const MAX_SCOPE_FILES: usize = 100_000;
+pub const MAX_TIMEOUT_SECS: u64 = 600;
+pub const MAX_LATENCY_TARGET_MS: u64 = 600_000;
+pub const MAX_CONCURRENCY: usize = 256;
+/// Largest `context_before` or `context_after`, in lines.
+pub const MAX_CONTEXT_LINES: usize = 1_000;
+/// Largest encoded search request accepted over HTTP, gRPC, or stdio.
+pub const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+/// One rejected request field.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct FieldError {
+    /// Request field name, as spelled in the HTTP and gRPC APIs.
+    pub field: &'static str,
+    pub message: String,
+}
+
+/// Every field of a search request that is out of bounds.
+#[derive(Debug)]
+pub struct ValidationError {
+    pub fields: Vec<FieldError>,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid search request: ")?;
+        for (index, error) in self.fields.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{} {}", error.field, error.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Check a search's options against the limits above, reporting every offending field at once.
+///
+/// Runs before any tool is spawned, for the CLI and every served API alike.
+pub fn search_args(args: &SearchArgs) -> Result<(), ValidationError> {
+    let mut check = Checks::default();
+    if args.symbol.chars().any(char::is_control) {
+        check.reject("symbol", "must not contain control characters".to_string());
+    }
+    check.length("symbol", Some(&args.symbol), MAX_SYMBOL_LENGTH);
+    check.length("language", args.language.as_ref(), MAX_NAME_LENGTH);
+    check.length("profile", args.profile.as_ref(), MAX_NAME_LENGTH);
+    check.length("owner", args.owner.as_ref(), MAX_NAME_LENGTH);
+    for name in &args.rg_types {
+        check.length("rg_types", Some(name), MAX_NAME_LENGTH);
+    }
+    for definition in &args.rg_type_adds {
+        check.length("rg_type_adds", Some(definition), MAX_NAME_LENGTH);
+    }
+    if let Err(err) = FileTypes::new(&[], &args.rg_type_adds) {
+        check.reject("rg_type_adds", err.to_string());
+    } else if let Err(err) = FileTypes::new(&args.rg_types, &args.rg_type_adds) {
+        check.reject("rg_types", err.to_string());
+    }
+    check.at_most("max_matches", args.max_matches, MAX_MATCHES);
+    if args.max_scope_files == Some(0) {
+        check.reject("max_scope_files", "must be at least 1".to_string());
+    }
+    check.at_most("max_scope_files", args.max_scope_files, MAX_SCOPE_FILES);
+    check.at_most("timeout_secs", args.timeout_secs, MAX_TIMEOUT_SECS);
+    check.at_most(
+        "latency_target_ms",
+        args.latency_target_ms,
+        MAX_LATENCY_TARGET_MS,
+    );
+    check.at_most("concurrency", Some(args.concurrency), MAX_CONCURRENCY);
+    check.at_most(
+        "context_before",
+        Some(args.context_before),
+        MAX_CONTEXT_LINES,
+    );
+    check.at_most("context_after", Some(args.context_after), MAX_CONTEXT_LINES);
+
+    if check.fields.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError {
+            fields: check.fields,
+        })
+    }
+}
+
+#[derive(Default)]
+struct Checks {
+    fields: Vec<FieldError>,
+}
+
+impl Checks {
+    fn reject(&mut self, field: &'static str, message: String) {
+        self.fields.push(FieldError { field, message });
+    }
+
+    fn length(&mut self, field: &'static str, value: Option<&String>, max: usize) {
+        if let Some(value) = value
+            && value.len() > max
+        {
+            let length = value.len();
+            self.reject(field, format!("must be at most {max} bytes (got {length})"));
+        }
+    }
+
+    fn at_most<T: PartialOrd + fmt::Display>(
+        &mut self,
+        field: &'static str,
+        value: Option<T>,
+        max: T,
+    ) {
+        if let Some(value) = value
+            && value > max
+        {
+            self.reject(field, format!("must be at most {max} (got {value})"));
+        }
+    }
+}
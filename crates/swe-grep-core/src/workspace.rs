@@ -0,0 +1,474 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use futures::future::join_all;
+
+use crate::cli::{SearchArgs, SortOrder};
+use crate::clusters::{self, HitCluster};
+use crate::iac;
+use crate::plan::Strategy;
+use crate::profile::SearchProfile;
+use crate::search::{
+    self, MAX_TOP_HITS, MatchedIdentifier, RewardComponents, SearchSummary, StageStats, TopHit,
+    Warning, WarningKind, round_two,
+};
+use crate::stats::RepoStats;
+use crate::submodules;
+use crate::suggest::{LanguageHint, MAX_LANGUAGE_HINTS, MAX_SYMBOL_SUGGESTIONS, Suggestions};
+use crate::tools::backend::ToolBackend;
+use crate::ts_aliases::SymbolAlias;
+
+/// One repository of a workspace search: a `--path` root, or a submodule at `prefix` in it.
+struct Repo {
+    root: PathBuf,
+    prefix: PathBuf,
+}
+
+impl Repo {
+    fn dir(&self) -> PathBuf {
+        self.root.join(&self.prefix)
+    }
+}
+
+/// Search every `--path` root, and with `--include-submodules` every submodule checked out in
+/// them, concurrently and merge the results into one summary.
+///
+/// Hits carry the root they came from (when there are several) and are re-ranked by score
+/// across roots. Submodules are searched as repositories of their own, so their ignore rules
+/// and config apply; their hits replace the ones the enclosing root found in them and keep
+/// paths relative to that root. Explicit cache, index, and log directories get one subfolder
+/// per repository so hint state never mixes.
+pub(crate) async fn execute(
+    args: SearchArgs,
+    backend: Arc<dyn ToolBackend>,
+) -> Result<SearchSummary> {
+    if args.files_from.is_some() {
+        anyhow::bail!(
+            "--files-from cannot be combined with multiple --path roots or --include-submodules"
+        );
+    }
+    if args.include_submodules && args.rev.is_some() {
+        anyhow::bail!("--include-submodules cannot be combined with --rev");
+    }
+
+    let mut roots = Vec::with_capacity(args.path.len());
+    for path in &args.path {
+        let root = search::canonicalize_path(path).with_context(|| {
+            format!(
+                "failed to canonicalize repository root path: {}",
+                path.display()
+            )
+        })?;
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+    let multi_root = roots.len() > 1;
+    let mut repos = Vec::with_capacity(roots.len());
+    for root in roots {
+        let submodules = if args.include_submodules {
+            submodules::discover(&root)?
+        } else {
+            Vec::new()
+        };
+        repos.push(Repo {
+            root: root.clone(),
+            prefix: PathBuf::new(),
+        });
+        repos.extend(submodules.into_iter().map(|prefix| Repo {
+            root: root.clone(),
+            prefix,
+        }));
+    }
+    if repos.len() == 1 {
+        // A single root without submodules is an ordinary search.
+        return search::execute_root(args, backend).await;
+    }
+    let dirs: Vec<PathBuf> = repos.iter().map(Repo::dir).collect();
+    let labels = root_labels(&dirs);
+
+    // Each repository resolves the profile against its own config; keep as many hits as the
+    // most generous one asks for.
+    let mut top_hit_limit = 0;
+    for dir in &dirs {
+        let profile = SearchProfile::load(dir, args.profile.as_deref())?;
+        top_hit_limit = top_hit_limit.max(profile.top_hits.unwrap_or(MAX_TOP_HITS).max(1));
+    }
+
+    let searches = dirs.iter().zip(&labels).map(|(dir, label)| {
+        let per_root = SearchArgs {
+            symbol: args.symbol.clone(),
+            path: vec![dir.clone()],
+            language: args.language.clone(),
+            rg_types: args.rg_types.clone(),
+            rg_type_adds: args.rg_type_adds.clone(),
+            config: args.config,
+            rev: args.rev.clone(),
+            files_from: None,
+            include_submodules: false,
+            owner: args.owner.clone(),
+            case: args.case,
+            word: args.word,
+            profile: args.profile.clone(),
+            trust_repo_hooks: args.trust_repo_hooks,
+            trust_repo_state: args.trust_repo_state,
+            timeout_secs: args.timeout_secs,
+            max_matches: args.max_matches,
+            max_scope_files: args.max_scope_files,
+            latency_target_ms: args.latency_target_ms,
+            concurrency: args.concurrency,
+            context_before: args.context_before,
+            context_after: args.context_after,
+            body: args.body,
+            redact_secrets: args.redact_secrets,
+            enable_index: args.enable_index,
+            index_dir: args.index_dir.as_ref().map(|dir| dir.join(label)),
+            enable_rga: args.enable_rga,
+            cache_dir: args.cache_dir.as_ref().map(|dir| dir.join(label)),
+            state_max_symbols: args.state_max_symbols,
+            log_dir: args.log_dir.as_ref().map(|dir| dir.join(label)),
+            use_fd: args.use_fd,
+            use_ast_grep: args.use_ast_grep,
+            tools: args.tools.clone(),
+            fields: args.fields.clone(),
+            explain: args.explain,
+            highlight: args.highlight,
+            link_format: args.link_format,
+            permalinks: args.permalinks,
+            sort: args.sort,
+            deterministic: args.deterministic,
+            format: args.format,
+            exec: None,
+        };
+        search::execute_root(per_root, backend.clone())
+    });
+    let results = join_all(searches).await;
+
+    let mut summaries = Vec::new();
+    let mut warnings = Vec::new();
+    let mut first_error = None;
+    for (repo, result) in repos.into_iter().zip(results) {
+        match result {
+            Ok(summary) => summaries.push((repo, summary)),
+            Err(err) => {
+                let dir = repo.dir();
+                tracing::warn!(root = %dir.display(), error = %err, "root search failed");
+                warnings.push(Warning::new(
+                    WarningKind::RootFailed,
+                    format!("search failed in {}: {err:#}", dir.display()),
+                ));
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+    if summaries.is_empty() {
+        return Err(first_error.expect("at least one root was searched"));
+    }
+
+    // A submodule's own search answers for its directory; drop what the enclosing root's
+    // walk found there, unless that search failed.
+    let searched: Vec<(PathBuf, PathBuf)> = summaries
+        .iter()
+        .filter(|(repo, _)| !repo.prefix.as_os_str().is_empty())
+        .map(|(repo, _)| (repo.root.clone(), repo.prefix.clone()))
+        .collect();
+    let summaries = summaries
+        .into_iter()
+        .map(|(repo, mut summary)| {
+            let inside = |path: &Path| {
+                searched.iter().any(|(root, prefix)| {
+                    *root == repo.root
+                        && prefix.starts_with(&repo.prefix)
+                        && *prefix != repo.prefix
+                        && repo.prefix.join(path).starts_with(prefix)
+                })
+            };
+            summary.top_hits.retain(|hit| !inside(Path::new(&hit.path)));
+            summary
+                .clusters
+                .retain(|cluster| cluster.path == "." || !inside(Path::new(&cluster.path)));
+            summary.fd_candidates.retain(|path| !inside(path));
+            summary.ast_hits.retain(|(path, _)| !inside(path));
+            (repo.root, repo.prefix, summary)
+        })
+        .collect();
+
+    Ok(merge(
+        &args.symbol,
+        summaries,
+        multi_root,
+        warnings,
+        top_hit_limit,
+        args.sort.unwrap_or_default(),
+        args.config,
+    ))
+}
+
+/// Subfolder name per root: the directory name, suffixed when two roots share one.
+fn root_labels(roots: &[PathBuf]) -> Vec<String> {
+    let mut seen: BTreeMap<String, usize> = BTreeMap::new();
+    roots
+        .iter()
+        .map(|root| {
+            let name = root
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "root".to_string());
+            let count = seen.entry(name.clone()).or_default();
+            *count += 1;
+            if *count == 1 {
+                name
+            } else {
+                format!("{name}-{count}")
+            }
+        })
+        .collect()
+}
+
+/// Combine per-repository summaries, each given with its root and the submodule prefix within
+/// it. Hits are labelled with their root only when `multi_root` is set; with a single root,
+/// every path stays relative to it.
+fn merge(
+    symbol: &str,
+    summaries: Vec<(PathBuf, PathBuf, SearchSummary)>,
+    multi_root: bool,
+    mut warnings: Vec<Warning>,
+    top_hit_limit: usize,
+    sort: SortOrder,
+    config: bool,
+) -> SearchSummary {
+    let roots = summaries.len() as f32;
+    let mut strategy = Strategy::default();
+    let mut queries: Vec<String> = Vec::new();
+    let mut matched_identifiers: BTreeMap<String, usize> = BTreeMap::new();
+    let mut aliases: Vec<SymbolAlias> = Vec::new();
+    let mut revisions = Vec::new();
+    let mut top_hits = Vec::new();
+    let mut deduped = 0;
+    let mut clusters: Vec<HitCluster> = Vec::new();
+    let mut fd_candidates = Vec::new();
+    let mut ast_hits = Vec::new();
+    let mut stage_stats = StageStats::default();
+    let (mut precision, mut density, mut clustering, mut fd_bonus) = (0.0, 0.0, 0.0, 0.0);
+    let (mut stage_reward, mut reward) = (0.0, 0.0);
+    let mut components = RewardComponents::default();
+    let mut suggestions: Option<Suggestions> = None;
+    let mut budget_exceeded_stages: Vec<String> = Vec::new();
+    let mut skipped: Vec<String> = Vec::new();
+    let mut repo_stats: Option<RepoStats> = None;
+
+    let fallback_root = summaries
+        .first()
+        .map(|(root, _, _)| root.clone())
+        .unwrap_or_default();
+    for (root, prefix, summary) in summaries {
+        let root_label = multi_root.then(|| root.display().to_string());
+        // Paths other than hits are reported from the repository's own directory.
+        let base = if multi_root {
+            root.join(&prefix)
+        } else {
+            prefix.clone()
+        };
+        let label = base.display().to_string();
+        let labelled = |message: String| {
+            if label.is_empty() {
+                message
+            } else {
+                format!("{label}: {message}")
+            }
+        };
+        // A Rust path resolves to a module in some roots only; any root that did so reports it.
+        if !summary.strategy.is_default() {
+            strategy = summary.strategy;
+        }
+        // Roots share their rewrites except for the renames each one followed.
+        for query in summary.queries {
+            if !queries.contains(&query) {
+                queries.push(query);
+            }
+        }
+        for matched in summary.matched_identifiers {
+            *matched_identifiers.entry(matched.identifier).or_default() += matched.hits;
+        }
+        aliases.extend(summary.aliases.into_iter().map(|mut alias| {
+            alias.path = base.join(&alias.path).display().to_string();
+            alias
+        }));
+        if let Some(revision) = summary.revision
+            && !revisions.contains(&revision)
+        {
+            revisions.push(revision);
+        }
+        top_hits.extend(summary.top_hits.into_iter().map(|mut hit| {
+            if !prefix.as_os_str().is_empty() {
+                hit.path = prefix.join(&hit.path).display().to_string();
+            }
+            hit.root = root_label.clone();
+            hit
+        }));
+        deduped += summary.deduped;
+        clusters.extend(summary.clusters.into_iter().map(|mut cluster| {
+            cluster.path = if cluster.path != "." {
+                base.join(&cluster.path).display().to_string()
+            } else if label.is_empty() {
+                cluster.path
+            } else {
+                label.clone()
+            };
+            cluster.best_path = base.join(&cluster.best_path).display().to_string();
+            cluster
+        }));
+        fd_candidates.extend(summary.fd_candidates.iter().map(|path| base.join(path)));
+        ast_hits.extend(
+            summary
+                .ast_hits
+                .into_iter()
+                .map(|(path, line)| (base.join(path), line)),
+        );
+        precision += summary.stage_stats.precision;
+        density += summary.stage_stats.density;
+        clustering += summary.stage_stats.clustering;
+        fd_bonus += summary.stage_stats.fd_bonus;
+        stage_reward += summary.stage_stats.reward;
+        let theirs = summary.stage_stats.reward_components;
+        components.precision += theirs.precision;
+        components.density += theirs.density;
+        components.clustering += theirs.clustering;
+        components.fd_bonus += theirs.fd_bonus;
+        reward += summary.reward;
+        stage_stats.absorb(summary.stage_stats);
+        if let Some(mut found) = summary.suggestions {
+            for suggestion in &mut found.symbols {
+                suggestion.path = base.join(&suggestion.path).display().to_string();
+            }
+            suggestions = Some(match suggestions {
+                Some(merged) => merge_suggestions(merged, found),
+                None => found,
+            });
+        }
+        warnings.extend(summary.warnings.into_iter().map(|warning| Warning {
+            message: labelled(warning.message),
+            ..warning
+        }));
+        skipped.extend(summary.skipped.into_iter().map(labelled));
+        if let Some(stats) = summary.repo_stats {
+            match &mut repo_stats {
+                Some(merged) => merged.absorb(stats),
+                None => repo_stats = Some(stats),
+            }
+        }
+        for stage in summary.budget_exceeded_stages {
+            if !budget_exceeded_stages.contains(&stage) {
+                budget_exceeded_stages.push(stage);
+            }
+        }
+    }
+
+    let outside_config = |hit: &TopHit| config && !iac::is_config_file(Path::new(&hit.path));
+    top_hits.sort_by(|a, b| {
+        outside_config(a)
+            .cmp(&outside_config(b))
+            .then_with(|| a.barrel.cmp(&b.barrel))
+            .then_with(|| b.score.total_cmp(&a.score))
+            .then_with(|| a.root.cmp(&b.root))
+            .then_with(|| a.path.cmp(&b.path))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    top_hits.truncate(top_hit_limit);
+    // Merged hits carry their own root unless there is only one.
+    search::sort_top_hits(&mut top_hits, sort, &fallback_root);
+    stage_stats.precision = round_two(precision / roots);
+    stage_stats.density = round_two(density / roots);
+    stage_stats.clustering = round_two(clustering / roots);
+    stage_stats.fd_bonus = round_two(fd_bonus / roots);
+    stage_stats.reward = round_two(stage_reward / roots);
+    stage_stats.reward_components = RewardComponents {
+        precision: round_two(components.precision / roots),
+        density: round_two(components.density / roots),
+        clustering: round_two(components.clustering / roots),
+        fd_bonus: round_two(components.fd_bonus / roots),
+    };
+
+    let suggestions = if top_hits.is_empty() {
+        suggestions
+    } else {
+        None
+    };
+    let mut next_actions = search::hit_actions(&top_hits);
+    if let Some(suggestions) = &suggestions {
+        next_actions.extend(suggestions.next_actions());
+    }
+
+    clusters::reshare(&mut clusters);
+
+    SearchSummary {
+        cycle: 1,
+        symbol: symbol.to_string(),
+        strategy,
+        revision: (!revisions.is_empty()).then(|| revisions.join(",")),
+        search_id: None,
+        queries,
+        matched_identifiers: merge_matched_identifiers(matched_identifiers),
+        aliases,
+        top_hits,
+        deduped,
+        clusters,
+        next_actions,
+        suggestions,
+        fd_candidates,
+        ast_hits,
+        startup_stats: None,
+        stage_stats,
+        reward: round_two(reward / roots),
+        warnings,
+        budget_exceeded_stages,
+        skipped,
+        repo_stats,
+    }
+}
+
+/// Identifier counts summed over every root, most common first.
+fn merge_matched_identifiers(counts: BTreeMap<String, usize>) -> Vec<MatchedIdentifier> {
+    let mut matched: Vec<MatchedIdentifier> = counts
+        .into_iter()
+        .map(|(identifier, hits)| MatchedIdentifier { identifier, hits })
+        .collect();
+    matched.sort_by(|a, b| {
+        b.hits
+            .cmp(&a.hits)
+            .then_with(|| a.identifier.cmp(&b.identifier))
+    });
+    matched
+}
+
+/// Combine per-root suggestions: nearest symbols overall, summed language counts.
+fn merge_suggestions(mut merged: Suggestions, other: Suggestions) -> Suggestions {
+    for suggestion in other.symbols {
+        if !merged
+            .symbols
+            .iter()
+            .any(|existing| existing.symbol == suggestion.symbol)
+        {
+            merged.symbols.push(suggestion);
+        }
+    }
+    merged
+        .symbols
+        .sort_by(|a, b| a.distance.cmp(&b.distance).then(a.symbol.cmp(&b.symbol)));
+    merged.symbols.truncate(MAX_SYMBOL_SUGGESTIONS);
+
+    let mut languages: BTreeMap<String, usize> = BTreeMap::new();
+    for hint in merged.languages.into_iter().chain(other.languages) {
+        *languages.entry(hint.language).or_default() += hint.files;
+    }
+    let mut languages: Vec<LanguageHint> = languages
+        .into_iter()
+        .map(|(language, files)| LanguageHint { language, files })
+        .collect();
+    languages.sort_by(|a, b| b.files.cmp(&a.files).then(a.language.cmp(&b.language)));
+    languages.truncate(MAX_LANGUAGE_HINTS);
+    merged.languages = languages;
+    merged
+}
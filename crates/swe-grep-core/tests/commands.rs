@@ -0,0 +1,524 @@
+use std::path::PathBuf;
+
+use swe_grep::cli::{
+    CalibrateArgs, CaseMode, DiffArgs, FlagArgs, HeatmapArgs, HeatmapFormat, OutlineArgs,
+};
+use swe_grep::config::RepoConfig;
+use swe_grep::flag::{self, FlagUsage};
+use swe_grep::heatmap::{self, HeatmapNodeKind};
+use swe_grep::{calibrate, diff, outline};
+use tempfile::tempdir;
+
+mod common;
+
+use common::{fixture_root, repo_tools, write_fake_rg, write_fake_tool};
+
+#[tokio::test]
+async fn diffs_saved_summaries() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let before = temp.path().join("before.json");
+    let after = temp.path().join("after.json");
+    std::fs::write(
+        &before,
+        r#"{"symbol":"login_user","top_hits":[
+            {"path":"src/lib.rs","line":3,"snippet":"pub fn login_user()"},
+            {"path":"src/auth.rs","line":10,"snippet":"login_user(&ctx)"},
+            {"path":"src/old.rs","line":7,"snippet":"legacy login_user"}
+        ]}"#,
+    )
+    .expect("failed to write before summary");
+    std::fs::write(
+        &after,
+        r#"{"summary":{"symbol":"login_user","top_hits":[
+            {"path":"src/lib.rs","line":3,"snippet":"pub fn login_user()"},
+            {"path":"src/auth.rs","line":14,"snippet":"login_user(&ctx)"},
+            {"path":"src/new.rs","line":2,"snippet":"login_user(token)"}
+        ]}}"#,
+    )
+    .expect("failed to write after summary");
+
+    let args = DiffArgs {
+        symbol: None,
+        path: None,
+        language: None,
+        before: None,
+        after: None,
+        before_summary: Some(before),
+        after_summary: Some(after),
+        timeout_secs: 3,
+        max_matches: 20,
+        cache_dir: None,
+    };
+
+    let report = diff::run(args).await.expect("diff should succeed");
+    assert_eq!(report.symbol, "login_user");
+    assert_eq!(report.unchanged, 1);
+    assert_eq!(report.moved.len(), 1);
+    assert_eq!(report.moved[0].from.line, 10);
+    assert_eq!(report.moved[0].to.line, 14);
+    assert_eq!(report.removed.len(), 1);
+    assert_eq!(report.removed[0].path, "src/old.rs");
+    assert_eq!(report.added.len(), 1);
+    assert_eq!(report.added[0].path, "src/new.rs");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn classifies_feature_flag_provenance() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(&repo).expect("failed to create repo");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+    write_fake_rg(
+        &bin,
+        &[
+            (
+                "src/server.rs",
+                10,
+                "    if env::var(\"ENABLE_FOO\").is_ok() {",
+            ),
+            ("docs/flags.md", 7, "- `ENABLE_FOO` turns on foo."),
+            ("tests/setup.py", 5, "os.environ[\"ENABLE_FOO\"] = \"1\""),
+            ("deploy/app.yaml", 12, "        - name: ENABLE_FOO"),
+            ("src/app.py", 3, "# ENABLE_FOO gates the new checkout"),
+            (
+                "src/config.ts",
+                2,
+                "export const ENABLE_FOO = process.env.ENABLE_FOO === \"true\";",
+            ),
+            ("scripts/dev.sh", 4, "export ENABLE_FOO=1"),
+            ("Dockerfile", 3, "ENV ENABLE_FOO=1"),
+            (".env", 1, "ENABLE_FOO=true"),
+        ],
+    );
+
+    let report = flag::run(FlagArgs {
+        name: "ENABLE_FOO".to_string(),
+        path: Some(repo.clone()),
+        max_matches: 200,
+        timeout_secs: 3,
+        tools: repo_tools(),
+    })
+    .await
+    .expect("flag trace should succeed");
+
+    let hits: Vec<(&str, FlagUsage)> = report
+        .hits
+        .iter()
+        .map(|hit| (hit.path.as_str(), hit.kind))
+        .collect();
+    assert_eq!(
+        hits,
+        [
+            (".env", FlagUsage::Definition),
+            ("Dockerfile", FlagUsage::Definition),
+            ("deploy/app.yaml", FlagUsage::Definition),
+            ("src/config.ts", FlagUsage::Definition),
+            ("scripts/dev.sh", FlagUsage::Write),
+            ("tests/setup.py", FlagUsage::Write),
+            ("src/server.rs", FlagUsage::Read),
+            ("docs/flags.md", FlagUsage::Documentation),
+            ("src/app.py", FlagUsage::Documentation),
+        ],
+        "definitions first, then writes, reads, and documentation"
+    );
+    assert_eq!(report.counts.get(&FlagUsage::Definition), Some(&4));
+    assert!(!report.truncated);
+
+    let log = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+    for arg in ["--hidden", "--case-sensitive", "\\bENABLE_FOO\\b"] {
+        assert!(log.lines().any(|line| line == arg), "rg gets {arg}: {log}");
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn counts_symbol_occurrences_per_directory_and_file() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(&repo).expect("failed to create repo");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+    write_fake_rg(
+        &bin,
+        &[
+            ("src/auth/login.rs", 3, "fn load_user() {}"),
+            ("src/auth/login.rs", 9, "load_user();"),
+            ("src/auth/session.rs", 4, "load_user();"),
+            ("src/main.rs", 2, "load_user();"),
+            ("tests/auth.rs", 7, "load_user();"),
+            ("build.rs", 1, "// load_user"),
+        ],
+    );
+
+    let report = heatmap::run(HeatmapArgs {
+        symbol: "load_user".to_string(),
+        path: Some(repo.clone()),
+        case: CaseMode::Smart,
+        max_matches: 10_000,
+        timeout_secs: 3,
+        format: HeatmapFormat::Json,
+        tools: repo_tools(),
+    })
+    .await
+    .expect("heatmap should succeed");
+
+    assert_eq!((report.total, report.files), (6, 5));
+    assert!(!report.truncated);
+    let top: Vec<(&str, HeatmapNodeKind, usize)> = report
+        .tree
+        .children
+        .iter()
+        .map(|node| (node.path.as_str(), node.kind, node.count))
+        .collect();
+    assert_eq!(
+        top,
+        [
+            ("src", HeatmapNodeKind::Directory, 4),
+            ("build.rs", HeatmapNodeKind::File, 1),
+            ("tests", HeatmapNodeKind::Directory, 1),
+        ],
+        "most matches first, then by name"
+    );
+    let auth = &report.tree.children[0].children[0];
+    assert_eq!((auth.path.as_str(), auth.count), ("src/auth", 3));
+    assert_eq!(
+        (auth.children[0].path.as_str(), auth.children[0].count),
+        ("src/auth/login.rs", 2)
+    );
+
+    let svg = heatmap::svg(&report);
+    assert!(svg.starts_with("<svg"), "{svg}");
+    assert_eq!(
+        svg.matches("<rect").count(),
+        5 + 3,
+        "one rectangle per file and per directory below the root: {svg}"
+    );
+    assert!(
+        svg.contains("<title>src/auth/login.rs (2)</title>"),
+        "{svg}"
+    );
+
+    let log = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+    assert!(log.lines().any(|line| line == "\\bload_user\\b"), "{log}");
+}
+
+#[tokio::test]
+async fn calibrate_writes_weights_and_keeps_other_settings() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let scenarios = temp.path().join("scenarios.json");
+    let scenario_json = serde_json::json!([
+        {
+            "name": "rust_login",
+            "path": fixture_root().join("fixtures/multi_lang"),
+            "symbol": "login_user",
+            "language": "rust",
+            "expected": { "path": "src/lib.rs", "top_n": 3 }
+        },
+        {
+            "name": "unlabeled",
+            "path": fixture_root().join("fixtures/multi_lang"),
+            "symbol": "getUser"
+        }
+    ]);
+    std::fs::write(&scenarios, scenario_json.to_string()).expect("failed to write scenarios");
+    let config_path = temp.path().join(".swe-grep.toml");
+    std::fs::write(&config_path, "[other]\nkeep = \"me\"\n").expect("failed to write config");
+
+    let args = CalibrateArgs {
+        scenarios: vec![scenarios],
+        rounds: 1,
+        write: Some(config_path.clone()),
+        timeout_secs: 3,
+        cache_dir: Some(temp.path().join("cache")),
+    };
+
+    let report = calibrate::run(args)
+        .await
+        .expect("calibration should succeed");
+    assert_eq!(report.scenarios, 1);
+    assert_eq!(report.skipped, 1);
+
+    let loaded = RepoConfig::load(temp.path()).expect("written config should parse");
+    assert_eq!(loaded.scoring, report.best.scoring);
+    assert_eq!(loaded.reward, report.best.reward);
+    let raw = std::fs::read_to_string(&config_path).expect("failed to read config");
+    assert!(
+        raw.contains("keep = \"me\""),
+        "unrelated settings must survive: {raw}"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn outlines_a_file_with_nested_methods() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    // ast-grep reports 0-based lines; the stand-in answers in scan order, not source order.
+    let message = |rule: &str, name: &str, start: usize, end: usize| {
+        serde_json::json!({
+            "ruleId": rule,
+            "range": {
+                "start": { "line": start, "column": 0 },
+                "end": { "line": end, "column": 1 },
+            },
+            "metaVariables": { "single": { "NAME": { "text": name } } },
+        })
+    };
+    let messages = serde_json::json!([
+        message("function_item", "load", 5, 7),
+        message("function_item", "main", 10, 10),
+        message("struct_item", "Config", 0, 2),
+        message("impl_item", "Config", 4, 8),
+    ]);
+    write_fake_tool(
+        &bin,
+        "ast-grep",
+        &format!(
+            "printf '%s\\n' \"$@\" >> \"$(dirname \"$0\")/ast-grep-args.log\"\ncat <<'EOF'\n{messages}\nEOF\n"
+        ),
+    );
+
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(
+        repo.join("src/lib.rs"),
+        "pub struct Config {\n    path: String,\n}\n\nimpl Config {\n    pub fn load() -> Self {\n        todo!()\n    }\n}\n\npub fn main() {}\n",
+    )
+    .expect("failed to write source");
+    std::fs::write(repo.join("notes.txt"), "todo\n").expect("failed to write notes");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.ast_grep]\npath = \"../bin/ast-grep\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = |file: &str| OutlineArgs {
+        file: PathBuf::from(file),
+        path: Some(repo.clone()),
+        language: None,
+        timeout_secs: 3,
+        tools: repo_tools(),
+    };
+    let outline = outline::run(args("src/lib.rs"))
+        .await
+        .expect("outline should succeed");
+    assert_eq!(outline.path, "src/lib.rs");
+    assert_eq!(outline.language, "rust");
+
+    let entries = |symbols: &[outline::OutlineSymbol]| -> Vec<(String, String, usize, usize)> {
+        symbols
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.kind.clone(),
+                    symbol.name.clone(),
+                    symbol.start_line,
+                    symbol.end_line,
+                )
+            })
+            .collect()
+    };
+    let owned = |kind: &str, name: &str, start: usize, end: usize| {
+        (kind.to_string(), name.to_string(), start, end)
+    };
+    assert_eq!(
+        entries(&outline.symbols),
+        vec![
+            owned("struct", "Config", 1, 3),
+            owned("impl", "Config", 5, 9),
+            owned("function", "main", 11, 11),
+        ]
+    );
+    assert_eq!(
+        entries(&outline.symbols[1].children),
+        vec![owned("method", "load", 6, 8)]
+    );
+
+    let log = std::fs::read_to_string(bin.join("ast-grep-args.log")).expect("ast-grep should run");
+    assert!(log.starts_with("scan\n"), "{log}");
+    assert!(log.contains("kind: impl_item"), "{log}");
+    assert!(log.contains("src/lib.rs"), "{log}");
+
+    let err = outline::run(args("notes.txt"))
+        .await
+        .expect_err("plain text has no grammar");
+    assert!(err.to_string().contains("--language"), "{err}");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn replays_logged_searches_and_compares_top_hits() {
+    use swe_grep::bench;
+    use swe_grep::cli::BenchReplayArgs;
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let hits = [
+        ("src/lib.rs", 1, "pub fn replay_symbol() {}"),
+        ("src/main.rs", 3, "replay_symbol();"),
+    ];
+    write_fake_rg(&temp.path().join("bin"), &hits);
+    let root = temp.path().join("repo");
+    std::fs::create_dir_all(root.join("src")).expect("failed to create repo");
+    for (path, line, text) in hits {
+        let mut contents = "\n".repeat(line - 1);
+        contents.push_str(text);
+        contents.push('\n');
+        std::fs::write(root.join(path), contents).expect("failed to write source");
+    }
+    std::fs::write(
+        root.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let entry = |root: &std::path::Path, latency_ms: u64, top_hits: &[(&str, usize)]| {
+        let top_hits: Vec<_> = top_hits
+            .iter()
+            .map(|(path, line)| serde_json::json!({ "path": path, "line": line }))
+            .collect();
+        serde_json::json!({
+            "timestamp": 1_700_000_000.0,
+            "root": root,
+            "symbol": "replay_symbol",
+            "use_index": false,
+            "use_rga": false,
+            "use_fd": false,
+            "use_ast_grep": false,
+            "status": "ok",
+            "latency_ms": latency_ms,
+            "summary": { "top_hits": top_hits },
+        })
+        .to_string()
+    };
+    let log = temp.path().join("logs/search.log.jsonl");
+    std::fs::create_dir_all(log.parent().unwrap()).expect("failed to create log dir");
+    let lines = [
+        entry(&root, 1, &[("src/old.rs", 1)]),
+        "not json".to_string(),
+        entry(&root, 40, &[("src/lib.rs", 1), ("src/main.rs", 3)]),
+        entry(&root, 40, &[("src/old.rs", 1), ("src/main.rs", 3)]),
+        entry(&temp.path().join("gone"), 40, &[("src/lib.rs", 1)]),
+    ];
+    let raw = lines.join("\n") + "\n";
+    std::fs::write(&log, &raw).expect("failed to write log");
+
+    let report = bench::replay(BenchReplayArgs {
+        log: log.clone(),
+        last: 3,
+        target: None,
+        cache_dir: Some(temp.path().join("cache")),
+        tools: repo_tools(),
+    })
+    .await
+    .expect("replay should succeed");
+    let report = serde_json::to_value(&report).expect("report serializes");
+
+    assert_eq!(report["skipped"], 1, "the unparsable line is skipped");
+    let searches = report["searches"].as_array().expect("searches");
+    assert_eq!(searches.len(), 3, "only the last three searches replay");
+
+    assert_eq!(searches[0]["logged_top_hit"], "src/lib.rs:1");
+    assert_eq!(searches[0]["replayed_top_hit"], "src/lib.rs:1");
+    assert_eq!(searches[0]["top_hit_stable"], true);
+    assert_eq!(searches[0]["overlap"], 1.0);
+    assert!(searches[0]["latency_delta_ms"].is_number());
+
+    assert_eq!(searches[1]["top_hit_stable"], false);
+    let overlap = searches[1]["overlap"].as_f64().expect("overlap");
+    assert!((overlap - 1.0 / 3.0).abs() < 1e-9, "overlap was {overlap}");
+
+    assert!(searches[2]["error"].is_string(), "a missing root fails");
+
+    let totals = &report["totals"];
+    assert_eq!(
+        (totals["replayed"].clone(), totals["errors"].clone()),
+        (2.into(), 1.into())
+    );
+    assert_eq!(totals["top_hit_stability"], 0.5);
+    assert_eq!(totals["mean_logged_latency_ms"], 40.0);
+
+    assert_eq!(
+        std::fs::read_to_string(&log).expect("log still readable"),
+        raw,
+        "replays do not append to the log"
+    );
+}
+
+#[tokio::test]
+async fn generates_fixture_repo_with_known_definitions() {
+    use swe_grep::cli::{
+        FixtureLanguage, FixtureSize, FixturesArgs, FixturesCommand, FixturesGenerateArgs,
+    };
+    use swe_grep::fixtures;
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let output = temp.path().join("fixture");
+    let args = || FixturesArgs {
+        command: FixturesCommand::Generate(FixturesGenerateArgs {
+            languages: vec![
+                FixtureLanguage::Rust,
+                FixtureLanguage::Ts,
+                FixtureLanguage::Rust,
+            ],
+            size: FixtureSize::Small,
+            modules: Some(3),
+            files_per_module: None,
+            output: Some(output.clone()),
+        }),
+    };
+    let report = fixtures::run(args())
+        .await
+        .expect("generate should succeed");
+
+    assert_eq!(report.languages, ["rust", "ts"], "duplicates are dropped");
+    assert_eq!((report.modules, report.files_per_module), (3, 3));
+    // Rust: manifest, lib.rs, core.rs, and a mod.rs per module; TypeScript: manifest and core.ts.
+    assert_eq!(report.files, 2 * 3 * 3 + 3 + 3 + 2);
+    assert!(output.join("rust/src/module_2/file_2.rs").is_file());
+    assert!(output.join("ts/src/module_2/file_2.ts").is_file());
+
+    let symbol = |name: &str| {
+        report
+            .symbols
+            .iter()
+            .find(|symbol| symbol.symbol == name)
+            .unwrap_or_else(|| panic!("{name} missing from the report"))
+    };
+    assert_eq!(symbol("resolve_session").definitions.len(), 2);
+    assert_eq!(
+        symbol("login_user").definitions.len(),
+        6,
+        "one per module and language"
+    );
+    assert_eq!(symbol("rs_handler_0_0_0").references, 0);
+    for symbol in &report.symbols {
+        for definition in &symbol.definitions {
+            let source = std::fs::read_to_string(output.join(&definition.path))
+                .expect("defining file exists");
+            let line = source.lines().nth(definition.line - 1).unwrap_or_default();
+            let camel = symbol.symbol.replace('_', "");
+            assert!(
+                line.contains(&symbol.symbol) || line.to_lowercase().contains(&camel),
+                "{}:{} should define {}: {line}",
+                definition.path,
+                definition.line,
+                symbol.symbol
+            );
+        }
+    }
+
+    let Err(err) = fixtures::run(args()).await else {
+        panic!("a non-empty output directory must be refused");
+    };
+    assert!(err.to_string().contains("not empty"), "{err}");
+}
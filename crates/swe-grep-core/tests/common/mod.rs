@@ -0,0 +1,89 @@
+//! Fixtures and fake tool binaries shared by the integration tests.
+//!
+//! Each test binary uses a different subset of these helpers.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use swe_grep::cli::ToolArgs;
+
+pub fn fixture_root() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.pop(); // swe-grep-core
+    path.pop(); // crates
+    path
+}
+
+/// Tool arguments that use the fake binaries named in the test repository's `.swe-grep.toml`.
+pub fn repo_tools() -> ToolArgs {
+    ToolArgs {
+        trust_repo_tools: true,
+        ..ToolArgs::default()
+    }
+}
+
+/// Write `dir/name` as an executable `/bin/sh` script running `body`, creating `dir`.
+#[cfg(unix)]
+pub fn write_fake_tool(dir: &Path, name: &str, body: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::create_dir_all(dir).expect("failed to create bin dir");
+    let tool = dir.join(name);
+    std::fs::write(&tool, format!("#!/bin/sh\n{body}")).expect("failed to write fake tool");
+    std::fs::set_permissions(&tool, std::fs::Permissions::from_mode(0o755))
+        .expect("failed to mark fake tool executable");
+    tool
+}
+
+/// One `rg --json` match message for `text` at `path:line`.
+pub fn rg_match(path: &str, line: usize, text: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "match",
+        "data": {
+            "path": { "text": path },
+            "lines": { "text": format!("{text}\n") },
+            "line_number": line,
+        },
+    })
+}
+
+/// Script body that appends its arguments to `rg-args.log` next to the script and prints one
+/// JSON match per `(path, line, text)`.
+pub fn fake_rg_script(matches: &[(&str, usize, &str)]) -> String {
+    let mut script =
+        String::from("printf '%s\\n' \"$@\" >> \"$(dirname \"$0\")/rg-args.log\"\ncat <<'EOF'\n");
+    for (path, line, text) in matches {
+        script.push_str(&format!("{}\n", rg_match(path, *line, text)));
+    }
+    script.push_str("EOF\n");
+    script
+}
+
+/// Write an executable `rg` stand-in to `bin/rg` running [`fake_rg_script`].
+#[cfg(unix)]
+pub fn write_fake_rg(bin: &Path, matches: &[(&str, usize, &str)]) {
+    write_fake_tool(bin, "rg", &fake_rg_script(matches));
+}
+
+/// Run `git` in `repo` with a fixed identity and return its trimmed stdout.
+pub fn git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "-c",
+            "user.name=swe-grep",
+            "-c",
+            "user.email=swe-grep@example.com",
+        ])
+        .args(args)
+        .output()
+        .expect("failed to run git");
+    assert!(
+        output.status.success(),
+        "git {args:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
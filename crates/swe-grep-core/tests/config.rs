@@ -0,0 +1,410 @@
+use swe_grep::cli::SearchArgs;
+use swe_grep::search::{self, WarningKind};
+use tempfile::tempdir;
+
+mod common;
+
+use common::{repo_tools, write_fake_rg, write_fake_tool};
+
+#[cfg(unix)]
+#[tokio::test]
+async fn applies_search_profiles_with_config_overrides() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let hits = [
+        ("src/a.rs", 1, "pub fn profiled() {}"),
+        ("src/b.rs", 1, "profiled();"),
+        ("src/c.rs", 1, "profiled();"),
+        ("src/d.rs", 1, "profiled();"),
+    ];
+    write_fake_rg(&bin, &hits);
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    for (path, _, text) in hits {
+        std::fs::write(repo.join(path), format!("{text}\n")).expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n\n[profiles.fast]\ntop_hits = 2\n\n\
+         [profiles.review]\ntop_hits = 4\nmax_matches = 7\n",
+    )
+    .expect("failed to write config");
+
+    let args = |profile: &str, max_matches: Option<usize>| SearchArgs {
+        symbol: "profiled".to_string(),
+        path: vec![repo.clone()],
+        profile: Some(profile.to_string()),
+        max_matches,
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let max_counts = || {
+        let logged = std::fs::read_to_string(bin.join("rg-args.log")).expect("fake rg should run");
+        std::fs::remove_file(bin.join("rg-args.log")).expect("failed to reset log");
+        let args: Vec<String> = logged.lines().map(str::to_string).collect();
+        args.windows(2)
+            .filter(|pair| pair[0] == "--max-count")
+            .map(|pair| pair[1].clone())
+            .collect::<Vec<_>>()
+    };
+
+    let fast = search::execute(args("fast", None))
+        .await
+        .expect("fast profile should run");
+    assert_eq!(
+        fast.top_hits.len(),
+        2,
+        "config overrides the built-in top-N"
+    );
+    assert!(max_counts().iter().all(|count| count == "10"));
+
+    let review = search::execute(args("review", Some(9)))
+        .await
+        .expect("custom profile should run");
+    assert_eq!(review.top_hits.len(), 4);
+    assert!(
+        max_counts().iter().all(|count| count == "9"),
+        "explicit options beat the profile"
+    );
+
+    let err = match search::execute(args("nope", None)).await {
+        Ok(_) => panic!("an unknown profile should be rejected"),
+        Err(err) => err.to_string(),
+    };
+    assert!(err.contains("unknown profile `nope`"), "{err}");
+    assert!(err.contains("review"), "{err}");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn pipes_summaries_through_configured_hooks() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(&bin, &[("src/lib.rs", 1, "pub fn hooked() {}")]);
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn hooked() {}\n")
+        .expect("failed to write source");
+    let scripts = repo.join("scripts");
+    write_fake_tool(
+        &scripts,
+        "redact.sh",
+        "sed 's/pub fn hooked/[redacted]/g'\n",
+    );
+    write_fake_tool(
+        &scripts,
+        "broken.sh",
+        "cat >/dev/null\necho boom >&2\nexit 3\n",
+    );
+    let config = |required: bool| {
+        std::fs::write(
+            repo.join(".swe-grep.toml"),
+            format!(
+                "[tools.rg]\npath = \"../bin/rg\"\n\n\
+                 [[hooks]]\ncommand = [\"scripts/redact.sh\"]\n\n\
+                 [[hooks]]\ncommand = [\"./scripts/broken.sh\"]\nrequired = {required}\n\n\
+                 [[hooks]]\ncommand = [\"cat\"]\n"
+            ),
+        )
+        .expect("failed to write config");
+    };
+
+    let args = |trust_repo_hooks: bool| SearchArgs {
+        symbol: "hooked".to_string(),
+        trust_repo_hooks,
+        path: vec![repo.clone()],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        log_dir: Some(temp.path().join("logs")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    config(false);
+    let summary = search::execute(args(true))
+        .await
+        .expect("search should succeed");
+    let hit = summary.top_hits.first().expect("expected a hit");
+    let snippet = hit.snippet.as_deref().unwrap_or_default();
+    assert!(snippet.contains("[redacted]"), "{snippet}");
+    assert!(!snippet.contains("pub fn"), "{snippet}");
+    let warning = summary
+        .warnings
+        .iter()
+        .find(|warning| warning.kind == WarningKind::HookFailed)
+        .expect("expected a hook_failed warning");
+    assert_eq!(warning.tool.as_deref(), Some("hook"));
+    assert!(warning.message.contains("broken.sh"), "{}", warning.message);
+    assert!(warning.message.contains("boom"), "{}", warning.message);
+
+    let log = std::fs::read_to_string(temp.path().join("logs/search.log.jsonl"))
+        .expect("summary should be logged");
+    assert!(
+        log.contains("[redacted]"),
+        "logs record the rewritten summary"
+    );
+
+    config(true);
+    let err = match search::execute(args(true)).await {
+        Ok(_) => panic!("a failing required hook should fail the search"),
+        Err(err) => format!("{err:#}"),
+    };
+    assert!(err.contains("required hook"), "{err}");
+    assert!(err.contains("boom"), "{err}");
+
+    // Without --trust-repo-hooks the repository's hooks never start, required or not.
+    let summary = search::execute(args(false))
+        .await
+        .expect("untrusted hooks are skipped");
+    let snippet = summary.top_hits[0].snippet.as_deref().unwrap_or_default();
+    assert!(snippet.contains("pub fn hooked"), "{snippet}");
+    assert!(
+        summary
+            .warnings
+            .iter()
+            .all(|warning| warning.kind != WarningKind::HookFailed)
+    );
+}
+
+#[cfg(all(unix, feature = "plugins"))]
+#[tokio::test]
+async fn scores_and_filters_hits_with_wasm_plugins() {
+    // Components in the text format; the runtime accepts them in place of binaries. `rank`
+    // drops hits under `vendor/` and scores the rest by line number, `broken` traps.
+    const COMPONENT: &str = r#"(component
+  (core module $m
+    (memory (export "memory") 1)
+    (global $next (mut i32) (i32.const 1024))
+    (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+      (local $ptr i32)
+      (local.set $ptr (i32.and (i32.add (global.get $next) (i32.const 7)) (i32.const -8)))
+      (global.set $next (i32.add (local.get $ptr) (local.get 3)))
+      (local.get $ptr))
+    (func (export "filter")
+      (param $path i32) (param i32 i32 f32 i32 i32 i32 i32 i32 i32 i32) (result i32)
+      FILTER)
+    (func (export "score")
+      (param i32 i32) (param $line i32) (param f32 i32 i32 i32 i32 i32 i32 i32) (result f32)
+      (f32.convert_i32_u (local.get $line))))
+  (core instance $i (instantiate $m))
+  (type $hit' (record
+    (field "path" string) (field "line" u32) (field "score" float32) (field "origin" string)
+    (field "snippet" string) (field "language" (option string))))
+  (export $hit "hit" (type $hit'))
+  (func (export "filter") (param "hit" $hit) (result bool)
+    (canon lift (core func $i "filter") (memory $i "memory") (realloc (func $i "cabi_realloc"))))
+  (func (export "score") (param "hit" $hit) (result float32)
+    (canon lift (core func $i "score") (memory $i "memory") (realloc (func $i "cabi_realloc")))))"#;
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let hits = [
+        ("src/a.rs", 1, "pub fn ranked() {}"),
+        ("src/b.rs", 3, "ranked();"),
+        ("vendor/c.rs", 5, "ranked();"),
+    ];
+    write_fake_rg(&bin, &hits);
+    let repo = temp.path().join("repo");
+    for (path, line, text) in hits {
+        let file = repo.join(path);
+        std::fs::create_dir_all(file.parent().unwrap()).expect("failed to create dirs");
+        std::fs::write(file, format!("{}{text}\n", "\n".repeat(line - 1)))
+            .expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+    let plugins = repo.join(".swe-grep/plugins");
+    std::fs::create_dir_all(&plugins).expect("failed to create plugin dir");
+    let keep_outside_vendor = "(i32.ne (i32.load8_u (local.get $path)) (i32.const 118))";
+    std::fs::write(
+        plugins.join("10-rank.wasm"),
+        COMPONENT.replace("FILTER", keep_outside_vendor),
+    )
+    .expect("failed to write plugin");
+    std::fs::write(
+        plugins.join("20-broken.wasm"),
+        COMPONENT.replace("FILTER", "unreachable"),
+    )
+    .expect("failed to write plugin");
+
+    let args = || SearchArgs {
+        symbol: "ranked".to_string(),
+        path: vec![repo.clone()],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    let summary = search::execute(args())
+        .await
+        .expect("search should succeed");
+    let ranked: Vec<(String, f32)> = summary
+        .top_hits
+        .iter()
+        .map(|hit| (hit.path.clone(), hit.score))
+        .collect();
+    assert_eq!(
+        ranked,
+        [("src/b.rs".to_string(), 3.0), ("src/a.rs".to_string(), 1.0)]
+    );
+    let failures: Vec<_> = summary
+        .warnings
+        .iter()
+        .filter(|warning| warning.kind == WarningKind::PluginFailed)
+        .collect();
+    assert_eq!(failures.len(), 1, "a failed plugin is reported once");
+    assert_eq!(failures[0].tool.as_deref(), Some("plugin"));
+    assert!(
+        failures[0].message.contains("20-broken.wasm"),
+        "{}",
+        failures[0].message
+    );
+
+    std::fs::write(plugins.join("30-invalid.wasm"), "not a module").expect("failed to write");
+    let err = match search::execute(args()).await {
+        Ok(_) => panic!("an invalid plugin should fail the search"),
+        Err(err) => format!("{err:#}"),
+    };
+    assert!(err.contains("30-invalid.wasm"), "{err}");
+}
+
+#[cfg(unix)]
+#[test]
+fn keeps_logs_on_stderr_under_verbosity_flags() {
+    use std::process::Command;
+
+    let temp = tempdir().expect("failed to create tempdir");
+    write_fake_rg(
+        &temp.path().join("bin"),
+        &[("src/lib.rs", 1, "pub fn noisy_symbol() {}")],
+    );
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn noisy_symbol() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+    let files = temp.path().join("files.txt");
+    std::fs::write(&files, "src/lib.rs\nsrc/missing.rs\n").expect("failed to write file list");
+
+    let run = |flags: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_swe-grep"))
+            .args(flags)
+            .args(["--disable-telemetry", "search", "--symbol", "noisy_symbol"])
+            .args([
+                "--trust-repo-tools",
+                "--disable-fd",
+                "--disable-ast-grep",
+                "--path",
+            ])
+            .arg(&repo)
+            .arg("--files-from")
+            .arg(&files)
+            .arg("--cache-dir")
+            .arg(temp.path().join("cache"))
+            .output()
+            .expect("failed to run swe-grep");
+        assert!(output.status.success());
+        let summary: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be the summary only");
+        assert!(
+            summary["warnings"]
+                .as_array()
+                .is_some_and(|warnings| !warnings.is_empty()),
+            "warnings stay in the summary regardless of log level"
+        );
+        String::from_utf8(output.stderr).expect("stderr should be UTF-8")
+    };
+
+    let default = run(&[]);
+    assert!(default.contains("files-from entries skipped"), "{default}");
+    assert!(!default.contains("search_cycle_start"), "{default}");
+    assert!(run(&["-v"]).contains("search_cycle_start"));
+    assert_eq!(run(&["--quiet"]), "");
+}
+
+#[cfg(unix)]
+#[test]
+fn reads_search_options_from_environment() {
+    use std::process::Command;
+
+    let temp = tempdir().expect("failed to create tempdir");
+    write_fake_rg(
+        &temp.path().join("bin"),
+        &[("src/lib.rs", 1, "pub fn env_symbol() {}")],
+    );
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn env_symbol() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let run = |flags: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_swe-grep"))
+            .args(["--disable-telemetry", "--quiet", "search"])
+            .args(flags)
+            .env("SWE_GREP_SYMBOL", "env_symbol")
+            .env("SWE_GREP_PATH", &repo)
+            .env("SWE_GREP_CACHE_DIR", temp.path().join("cache"))
+            .env("SWE_GREP_TRUST_REPO_TOOLS", "true")
+            .env("SWE_GREP_DISABLE_FD", "true")
+            .env("SWE_GREP_DISABLE_AST_GREP", "true")
+            .env("SWE_GREP_FIELDS", "path,line")
+            .output()
+            .expect("failed to run swe-grep");
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let summary: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("stdout should be the summary");
+        summary["top_hits"][0].clone()
+    };
+
+    assert_eq!(
+        run(&[]),
+        serde_json::json!({ "path": "src/lib.rs", "line": 1 })
+    );
+    // Flags on the command line take precedence over the environment.
+    assert_eq!(
+        run(&["--fields", "path"]),
+        serde_json::json!({ "path": "src/lib.rs" })
+    );
+}
+
+#[test]
+fn default_search_args_match_a_bare_search() {
+    use clap::Parser;
+    use swe_grep::cli::{Cli, Commands};
+
+    let cli = Cli::parse_from(["swe-grep", "search", "--symbol", "login_user"]);
+    let Commands::Search(parsed) = cli.command else {
+        panic!("expected the search subcommand");
+    };
+    let built = SearchArgs {
+        symbol: "login_user".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(format!("{parsed:?}"), format!("{built:?}"));
+}
@@ -0,0 +1,326 @@
+#[cfg(feature = "indexing")]
+use std::path::PathBuf;
+
+use swe_grep::symbols;
+use tempfile::tempdir;
+
+mod common;
+
+use common::fixture_root;
+
+#[tokio::test]
+async fn completes_symbol_prefixes_from_the_index() {
+    let repo_root = fixture_root().join("fixtures/multi_lang");
+    let temp = tempdir().expect("failed to create tempdir");
+
+    let result = symbols::lookup(&repo_root, Some(temp.path().join("index")), "login_", 5).await;
+    if !cfg!(feature = "indexing") {
+        let err = result.expect_err("lookup needs the indexing feature");
+        assert!(
+            err.to_string().contains("indexing"),
+            "unexpected error: {err}"
+        );
+        return;
+    }
+
+    let response = result.expect("lookup should succeed");
+    let names: Vec<&str> = response
+        .symbols
+        .iter()
+        .map(|entry| entry.symbol.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "login_user",
+            "login_user_allows_admin",
+            "login_user_endpoint"
+        ]
+    );
+    assert_eq!(response.symbols[0].path, "src/lib.rs");
+}
+
+#[cfg(feature = "indexing")]
+#[tokio::test]
+async fn index_matches_identifier_words() {
+    use swe_grep_indexer::{IndexConfig, TantivyIndex};
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let root = temp.path().join("repo");
+    std::fs::create_dir_all(root.join("src")).expect("failed to create repo");
+    let files = [
+        ("src/model.rs", "pub struct UserProfile;\n"),
+        ("src/store.py", "def load(user_profile):\n    pass\n"),
+        (
+            "src/api.ts",
+            "export const getUserProfileById = () => null;\n",
+        ),
+        ("src/other.rs", "pub struct ProfileUser;\nfn user() {}\n"),
+    ];
+    for (path, contents) in files {
+        std::fs::write(root.join(path), contents).expect("failed to write source");
+    }
+    let index = TantivyIndex::open_or_build(IndexConfig {
+        root: root.clone(),
+        index_dir: temp.path().join("index"),
+        extensions: None,
+    })
+    .await
+    .expect("index should build");
+
+    let search = |query: &'static str| {
+        let index = index.clone();
+        async move {
+            let mut lines: Vec<(PathBuf, usize)> = index
+                .search(query, 10)
+                .await
+                .expect("index search")
+                .into_iter()
+                .map(|found| (found.path, found.line))
+                .collect();
+            lines.sort();
+            lines
+        }
+    };
+    let matching = [
+        (PathBuf::from("src/api.ts"), 1),
+        (PathBuf::from("src/model.rs"), 1),
+        (PathBuf::from("src/store.py"), 1),
+    ];
+    assert_eq!(search("UserProfile").await, matching);
+    assert_eq!(search("user_profile").await, matching);
+    // The whole identifier is indexed too, so a flattened query still finds it.
+    assert_eq!(
+        search("getuserprofilebyid").await,
+        [(PathBuf::from("src/api.ts"), 1)]
+    );
+    assert_eq!(search("user").await.len(), 5);
+    assert!(
+        search("user")
+            .await
+            .contains(&(PathBuf::from("src/other.rs"), 2))
+    );
+}
+
+#[cfg(all(unix, feature = "indexing"))]
+#[tokio::test]
+async fn answers_exact_identifiers_from_indexed_lines() {
+    use common::{repo_tools, write_fake_rg};
+    use swe_grep::cli::SearchArgs;
+    use swe_grep::search;
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create src dir");
+    std::fs::write(
+        repo.join("src/session.rs"),
+        "use crate::auth;\n\npub fn renew_session() {}\n\nfn renew_sessions() {}\n",
+    )
+    .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+    // ripgrep finds nothing, so every hit must come from the index.
+    write_fake_rg(&bin, &[]);
+
+    let args = SearchArgs {
+        symbol: "renew_session".to_string(),
+        path: vec![repo.clone()],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        enable_index: true,
+        index_dir: Some(temp.path().join("index")),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    let summary = search::execute(args).await.expect("search should succeed");
+    let hits: Vec<(&str, usize, &str)> = summary
+        .top_hits
+        .iter()
+        .map(|hit| (hit.path.as_str(), hit.line, hit.origin.as_str()))
+        .collect();
+    // `renew_sessions` shares the words but not the whole identifier.
+    assert_eq!(hits, [("src/session.rs", 3, "index")]);
+    assert_eq!(
+        summary.top_hits[0].snippet.as_deref().map(str::trim_end),
+        Some("pub fn renew_session() {}")
+    );
+    assert_eq!(summary.stage_stats.index_probe_hits, 1);
+}
+
+#[cfg(feature = "indexing")]
+#[tokio::test]
+async fn index_build_reports_progress() {
+    use std::sync::{Arc, Mutex};
+    use swe_grep_indexer::{IndexConfig, IndexProgress, TantivyIndex};
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let root = temp.path().join("repo");
+    std::fs::create_dir_all(root.join("src")).expect("failed to create repo");
+    for number in 0..40 {
+        std::fs::write(
+            root.join(format!("src/module_{number}.rs")),
+            format!("pub fn handler_{number}() {{}}\n\npub struct Widget{number};\n"),
+        )
+        .expect("failed to write source");
+    }
+    let config = IndexConfig {
+        root: root.clone(),
+        index_dir: temp.path().join("index"),
+        extensions: None,
+    };
+    let reports: Arc<Mutex<Vec<IndexProgress>>> = Arc::default();
+    let sink = reports.clone();
+    let index = TantivyIndex::open_or_build_with_progress(
+        config.clone(),
+        Arc::new(move |progress: &IndexProgress| sink.lock().unwrap().push(*progress)),
+    )
+    .await
+    .expect("index should build");
+
+    let reports = std::mem::take(&mut *reports.lock().unwrap());
+    let last = reports.last().expect("build should report progress");
+    assert_eq!((last.files_done, last.files_total), (40, 40));
+    assert!(last.is_finished());
+    assert!(
+        reports
+            .windows(2)
+            .all(|pair| pair[0].files_done <= pair[1].files_done)
+    );
+    // Every file's lines are indexed, whichever thread wrote them.
+    assert_eq!(index.num_docs(), 80);
+    let found = index.search("handler_17", 10).await.expect("index search");
+    assert!(
+        found
+            .iter()
+            .any(|hit| hit.path == std::path::Path::new("src/module_17.rs") && hit.line == 1)
+    );
+    assert_eq!(index.symbols().with_prefix("Widget3", 20).len(), 11);
+
+    // An up-to-date index opens without a build.
+    let quiet: Arc<Mutex<Vec<IndexProgress>>> = Arc::default();
+    let sink = quiet.clone();
+    TantivyIndex::open_or_build_with_progress(
+        config,
+        Arc::new(move |progress: &IndexProgress| sink.lock().unwrap().push(*progress)),
+    )
+    .await
+    .expect("index should open");
+    assert!(quiet.lock().unwrap().is_empty());
+}
+
+#[cfg(feature = "indexing")]
+#[tokio::test]
+async fn quarantines_and_rebuilds_broken_indexes() {
+    use swe_grep_indexer::{IndexConfig, QUARANTINE_DIRNAME, TantivyIndex};
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let root = temp.path().join("repo");
+    std::fs::create_dir_all(root.join("src")).expect("failed to create repo");
+    std::fs::write(root.join("src/lib.rs"), "pub fn parse_config() {}\n")
+        .expect("failed to write source");
+    let index_dir = temp.path().join("index");
+    let config = IndexConfig {
+        root: root.clone(),
+        index_dir: index_dir.clone(),
+        extensions: None,
+    };
+    let open = || TantivyIndex::open_or_build(config.clone());
+    let assert_searchable = |index: &TantivyIndex| assert_eq!(index.num_docs(), 1);
+
+    let index = open().await.expect("index should build");
+    assert_eq!(index.recovery(), None);
+    assert!(!index_dir.join(".swe-grep-building").exists());
+    drop(index);
+
+    // Built by a release with another index format.
+    let meta_path = index_dir.join("meta.json");
+    let meta = std::fs::read_to_string(&meta_path).expect("index metadata should exist");
+    let current = format!(
+        "swe-grep-index-format:{}",
+        swe_grep_indexer::INDEX_FORMAT_VERSION
+    );
+    assert!(meta.contains(&current), "{meta}");
+    std::fs::write(
+        &meta_path,
+        meta.replace(&current, "swe-grep-index-format:0"),
+    )
+    .expect("failed to rewrite metadata");
+    let index = open().await.expect("outdated index should be rebuilt");
+    let reason = index.recovery().expect("recovery should be reported");
+    assert!(reason.contains("older version (format 0"), "{reason}");
+    assert_searchable(&index);
+    drop(index);
+
+    // Unreadable metadata.
+    std::fs::write(index_dir.join("meta.json"), "{ not json").expect("failed to corrupt");
+    let index = open().await.expect("broken index should be rebuilt");
+    let reason = index.recovery().expect("recovery should be reported");
+    assert!(reason.contains("metadata"), "unexpected reason: {reason}");
+    assert_searchable(&index);
+    let quarantined = std::fs::read_to_string(index_dir.join(QUARANTINE_DIRNAME).join("meta.json"))
+        .expect("broken metadata should be quarantined");
+    assert_eq!(quarantined, "{ not json");
+    drop(index);
+
+    // A build that crashed leaves its marker behind; the writer lock it held is free.
+    std::fs::write(index_dir.join(".swe-grep-building"), "").expect("failed to write marker");
+    let index = open().await.expect("interrupted build should be redone");
+    assert!(
+        index
+            .recovery()
+            .is_some_and(|reason| reason.contains("did not finish"))
+    );
+    assert_searchable(&index);
+    drop(index);
+
+    let index = open().await.expect("healthy index should open");
+    assert_eq!(index.recovery(), None);
+    assert_searchable(&index);
+}
+
+#[cfg(feature = "indexing")]
+#[tokio::test]
+async fn symbol_store_refreshes_changed_files() {
+    use swe_grep_indexer::{IndexConfig, SymbolStore};
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let root = temp.path().join("repo");
+    std::fs::create_dir_all(root.join("src")).expect("failed to create repo");
+    std::fs::write(root.join("src/a.rs"), "fn parse_config() {}\n").expect("failed to write");
+    std::fs::write(root.join("src/b.rs"), "fn render() { parse_config(); }\n")
+        .expect("failed to write");
+    let config = IndexConfig {
+        root: root.clone(),
+        index_dir: temp.path().join("index"),
+        extensions: None,
+    };
+
+    let store = SymbolStore::open_or_refresh(config.clone())
+        .await
+        .expect("store should build");
+    assert_eq!(
+        store.lookup("parse_config"),
+        [PathBuf::from("src/a.rs"), PathBuf::from("src/b.rs")]
+    );
+    assert_eq!(store.prefix("par", 5), ["parse_config"]);
+    assert_eq!(
+        store.fuzzy("parse_confg", 1, 5).expect("fuzzy lookup"),
+        ["parse_config"]
+    );
+
+    std::fs::write(root.join("src/b.rs"), "fn render_all() {}\n").expect("failed to write");
+    let store = SymbolStore::open_or_refresh(config)
+        .await
+        .expect("store should refresh");
+    assert_eq!(store.lookup("parse_config"), [PathBuf::from("src/a.rs")]);
+    assert_eq!(store.lookup("render_all"), [PathBuf::from("src/b.rs")]);
+    assert!(store.lookup("render").is_empty());
+}
@@ -0,0 +1,659 @@
+use swe_grep::cli::SearchArgs;
+use swe_grep::search;
+use swe_grep::ts_aliases::AliasKind;
+use tempfile::tempdir;
+
+mod common;
+
+use common::{repo_tools, write_fake_rg};
+
+#[cfg(unix)]
+#[tokio::test]
+async fn follows_typescript_export_aliases() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(
+        &bin,
+        &[
+            (
+                "src/index.ts",
+                1,
+                "export { loginUser as signIn } from './auth';",
+            ),
+            ("src/routes.ts", 1, "import loginUser from './session';"),
+            ("src/login.ts", 3, "const user = signIn as Handler;"),
+        ],
+    );
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(
+        repo.join("src/session.ts"),
+        "export default function startSession() {}\n",
+    )
+    .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let summary = search::execute(SearchArgs {
+        symbol: "signIn".to_string(),
+        path: vec![repo.clone()],
+        language: Some("ts".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    })
+    .await
+    .expect("alias search should succeed");
+
+    let chain: Vec<(&str, &str, AliasKind, &str)> = summary
+        .aliases
+        .iter()
+        .map(|alias| {
+            (
+                alias.alias.as_str(),
+                alias.target.as_str(),
+                alias.kind,
+                alias.path.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        chain,
+        [
+            ("signIn", "loginUser", AliasKind::Renamed, "src/index.ts"),
+            (
+                "loginUser",
+                "startSession",
+                AliasKind::DefaultExport,
+                "src/routes.ts"
+            ),
+        ],
+        "casts are not renames; default imports resolve through the module"
+    );
+    for target in ["loginUser", "startSession"] {
+        assert!(
+            summary.queries.iter().any(|query| query.contains(target)),
+            "{target} should join the rewrites: {:?}",
+            summary.queries
+        );
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn ranks_barrel_file_hits_last() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(
+        &bin,
+        &[
+            (
+                "src/auth/index.ts",
+                2,
+                "export { loginUser } from './login';",
+            ),
+            ("src/app.ts", 1, "import { loginUser } from './auth';"),
+            ("src/auth/login.ts", 1, "export function loginUser() {}"),
+        ],
+    );
+    let repo = temp.path().join("repo");
+    let files = [
+        (
+            "src/auth/index.ts",
+            "// Public surface of the auth package.\nexport * from './session';\nexport { loginUser } from './login';\nexport {\n  logout,\n  type Token,\n} from './tokens';\n",
+        ),
+        (
+            "src/app.ts",
+            "import { loginUser } from './auth';\n\nexport function start() {\n  loginUser();\n}\n",
+        ),
+        ("src/auth/login.ts", "export function loginUser() {}\n"),
+    ];
+    for (path, contents) in files {
+        let path = repo.join(path);
+        std::fs::create_dir_all(path.parent().expect("path has a parent"))
+            .expect("failed to create dir");
+        std::fs::write(path, contents).expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let summary = search::execute(SearchArgs {
+        symbol: "loginUser".to_string(),
+        path: vec![repo.clone()],
+        language: Some("ts".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    })
+    .await
+    .expect("search should succeed");
+
+    let ranked: Vec<(&str, bool)> = summary
+        .top_hits
+        .iter()
+        .map(|hit| (hit.path.as_str(), hit.barrel))
+        .collect();
+    assert_eq!(
+        ranked,
+        [
+            ("src/app.ts", false),
+            ("src/auth/login.ts", false),
+            ("src/auth/index.ts", true),
+        ],
+        "re-export files rank after the definition and ordinary imports"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn discovers_and_labels_swift_targets() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let repo = temp.path().join("repo");
+    let package = r#"// swift-tools-version:5.9
+import PackageDescription
+
+let package = Package(
+    name: "Demo",
+    products: [
+        .library(name: "Core", targets: ["Core"]),
+    ],
+    targets: [
+        // .target(name: "Disabled"),
+        .target(
+            name: "Core",
+            dependencies: [.product(name: "Logging", package: "swift-log")],
+            path: "Modules/Core/Sources",
+            exclude: ["Legacy"]
+        ),
+        .executableTarget(name: "CLI", dependencies: ["Core"]),
+        .testTarget(name: "CoreTests", dependencies: [.target(name: "Core")]),
+    ]
+)
+"#;
+    let project = r#"// !$*UTF8*$!
+{
+	archiveVersion = 1;
+	objects = {
+		A1 /* Project object */ = {
+			isa = PBXProject;
+			mainGroup = G1;
+			targets = (
+				T1 /* DemoApp */,
+				T2 /* Widget */,
+			);
+		};
+		G1 = {
+			isa = PBXGroup;
+			children = (
+				G2 /* DemoApp */,
+				S1 /* Widget */,
+			);
+			sourceTree = "<group>";
+		};
+		G2 /* DemoApp */ = {
+			isa = PBXGroup;
+			children = (
+				F1 /* ContentView.swift */,
+			);
+			path = DemoApp;
+			sourceTree = "<group>";
+		};
+		S1 /* Widget */ = {isa = PBXFileSystemSynchronizedRootGroup; path = Widget; sourceTree = "<group>"; };
+		F1 /* ContentView.swift */ = {isa = PBXFileReference; lastKnownFileType = sourcecode.swift; path = ContentView.swift; sourceTree = "<group>"; };
+		B1 /* ContentView.swift in Sources */ = {isa = PBXBuildFile; fileRef = F1 /* ContentView.swift */; };
+		P1 /* Sources */ = {
+			isa = PBXSourcesBuildPhase;
+			files = (
+				B1 /* ContentView.swift in Sources */,
+			);
+		};
+		T1 /* DemoApp */ = {
+			isa = PBXNativeTarget;
+			buildPhases = (
+				P1 /* Sources */,
+			);
+			name = DemoApp;
+		};
+		T2 /* Widget */ = {
+			isa = PBXNativeTarget;
+			buildPhases = (
+			);
+			fileSystemSynchronizedGroups = (
+				S1 /* Widget */,
+			);
+			name = Widget;
+		};
+	};
+	rootObject = A1 /* Project object */;
+}
+"#;
+    let files = [
+        ("Package.swift", package),
+        (
+            "Modules/Core/Sources/Auth/Session/Login.swift",
+            "func login() {}\n",
+        ),
+        ("Modules/Core/Sources/Legacy/Old.swift", "func login() {}\n"),
+        ("Sources/CLI/main.swift", "login()\n"),
+        (
+            "Tests/CoreTests/LoginTests.swift",
+            "func testLogin() { login() }\n",
+        ),
+        ("iOS/Demo.xcodeproj/project.pbxproj", project),
+        ("iOS/DemoApp/ContentView.swift", "let action = login\n"),
+        ("iOS/Widget/Deep/WidgetView.swift", "let action = login\n"),
+    ];
+    for (path, contents) in files {
+        let path = repo.join(path);
+        std::fs::create_dir_all(path.parent().expect("path has a parent"))
+            .expect("failed to create dir");
+        std::fs::write(path, contents).expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = || SearchArgs {
+        symbol: "login".to_string(),
+        path: vec![repo.clone()],
+        language: Some("swift".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    // Without fast-path matches the cycle discovers each target's files.
+    write_fake_rg(&bin, &[]);
+    let summary = search::execute(args())
+        .await
+        .expect("search should succeed");
+    let mut discovered: Vec<String> = summary
+        .fd_candidates
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    discovered.sort();
+    assert_eq!(
+        discovered,
+        [
+            "Modules/Core/Sources/Auth/Session/Login.swift",
+            "Package.swift",
+            "Sources/CLI/main.swift",
+            "Tests/CoreTests/LoginTests.swift",
+            "iOS/DemoApp/ContentView.swift",
+            "iOS/Widget/Deep/WidgetView.swift",
+        ],
+        "nested and custom target paths are found; excluded paths are not"
+    );
+
+    write_fake_rg(
+        &bin,
+        &[
+            (
+                "Modules/Core/Sources/Auth/Session/Login.swift",
+                1,
+                "func login() {}",
+            ),
+            ("Sources/CLI/main.swift", 1, "login()"),
+            (
+                "Tests/CoreTests/LoginTests.swift",
+                1,
+                "func testLogin() { login() }",
+            ),
+            ("iOS/DemoApp/ContentView.swift", 1, "let action = login"),
+            ("iOS/Widget/Deep/WidgetView.swift", 1, "let action = login"),
+        ],
+    );
+    let summary = search::execute(args())
+        .await
+        .expect("search should succeed");
+    let mut labelled: Vec<(&str, Vec<String>)> = summary
+        .top_hits
+        .iter()
+        .map(|hit| (hit.path.as_str(), hit.targets.clone()))
+        .collect();
+    labelled.sort();
+    assert_eq!(
+        labelled,
+        [
+            (
+                "Modules/Core/Sources/Auth/Session/Login.swift",
+                vec!["Core".to_string()]
+            ),
+            ("Sources/CLI/main.swift", vec!["CLI".to_string()]),
+            (
+                "Tests/CoreTests/LoginTests.swift",
+                vec!["CoreTests".to_string()]
+            ),
+            ("iOS/DemoApp/ContentView.swift", vec!["DemoApp".to_string()]),
+            (
+                "iOS/Widget/Deep/WidgetView.swift",
+                vec!["Widget".to_string()]
+            ),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn searches_objc_counterparts_through_bridging_headers() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let repo = temp.path().join("repo");
+    let project = r#"// !$*UTF8*$!
+{
+	objects = {
+		C1 /* Debug */ = {
+			isa = XCBuildConfiguration;
+			buildSettings = {
+				SWIFT_OBJC_BRIDGING_HEADER = "$(SRCROOT)/Shared/Bridge.h";
+			};
+			name = Debug;
+		};
+	};
+}
+"#;
+    let files = [
+        ("Demo.xcodeproj/project.pbxproj", project),
+        (
+            "App/App-Bridging-Header.h",
+            "#import <UIKit/UIKit.h>\n#import \"Legacy/LGSession.h\"\n",
+        ),
+        (
+            "App/Legacy/LGSession.h",
+            "@interface LGSession : NSObject\n@end\n",
+        ),
+        (
+            "App/Legacy/LGSession.m",
+            "@implementation LGSession\n@end\n",
+        ),
+        (
+            "App/Legacy/Unrelated.m",
+            "@implementation Unrelated\n@end\n",
+        ),
+        ("Shared/Bridge.h", "#include \"Analytics.h\"\n"),
+        ("Shared/Analytics.h", "@class LGSession;\n"),
+        ("Shared/Analytics.mm", "#import \"Analytics.h\"\n"),
+        ("App/ContentView.swift", "let session = LGSession()\n"),
+    ];
+    for (path, contents) in files {
+        let path = repo.join(path);
+        std::fs::create_dir_all(path.parent().expect("path has a parent"))
+            .expect("failed to create dir");
+        std::fs::write(path, contents).expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+    write_fake_rg(&bin, &[]);
+
+    let summary = search::execute(SearchArgs {
+        symbol: "LGSession".to_string(),
+        path: vec![repo.clone()],
+        language: Some("swift".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    })
+    .await
+    .expect("search should succeed");
+
+    let mut discovered: Vec<String> = summary
+        .fd_candidates
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    discovered.sort();
+    assert_eq!(
+        discovered,
+        [
+            "App/App-Bridging-Header.h",
+            "App/Legacy/LGSession.h",
+            "App/Legacy/LGSession.m",
+            "Shared/Analytics.h",
+            "Shared/Analytics.mm",
+            "Shared/Bridge.h",
+        ],
+        "bridging headers, their quoted imports, and the implementations beside them"
+    );
+    assert!(
+        summary
+            .queries
+            .iter()
+            .any(|query| query.contains("@interface LGSession")),
+        "objc rewrites join the swift ones: {:?}",
+        summary.queries
+    );
+    let log = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+    assert!(
+        log.lines().any(|arg| arg == "--type=objc"),
+        "rg searches objective-c files too: {log}"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn maps_notebook_hits_to_code_cells() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(&repo).expect("failed to create repo");
+    let notebook = serde_json::json!({
+        "cells": [
+            {
+                "cell_type": "markdown",
+                "metadata": {},
+                "source": ["# Loading with load_frame\n"],
+            },
+            {
+                "cell_type": "code",
+                "execution_count": 1,
+                "metadata": {},
+                "outputs": [],
+                "source": ["import pandas\n"],
+            },
+            {
+                "cell_type": "code",
+                "execution_count": 2,
+                "metadata": {},
+                "outputs": [
+                    { "name": "stdout", "output_type": "stream", "text": ["load_frame ready\n"] },
+                ],
+                "source": [
+                    "# Read a CSV export.\n",
+                    "def load_frame(path):\n",
+                    "    return pandas.read_csv(path)\n",
+                ],
+            },
+        ],
+        "metadata": {},
+        "nbformat": 4,
+        "nbformat_minor": 5,
+    });
+    let raw = serde_json::to_string_pretty(&notebook).unwrap();
+    std::fs::write(repo.join("analysis.ipynb"), &raw).expect("failed to write notebook");
+    let json_line = |needle: &str| {
+        raw.lines()
+            .position(|line| line.contains(needle))
+            .expect("needle should be in the notebook")
+            + 1
+    };
+
+    let bin = temp.path().join("bin");
+    write_fake_rg(
+        &bin,
+        &[
+            (
+                "analysis.ipynb",
+                json_line("# Loading with load_frame"),
+                "\"# Loading with load_frame\\n\"",
+            ),
+            (
+                "analysis.ipynb",
+                json_line("load_frame ready"),
+                "\"load_frame ready\\n\"",
+            ),
+            (
+                "analysis.ipynb",
+                json_line("def load_frame(path)"),
+                "\"def load_frame(path):\\n\",",
+            ),
+        ],
+    );
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = SearchArgs {
+        symbol: "load_frame".to_string(),
+        path: vec![repo],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        context_before: 1,
+        context_after: 1,
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let summary = search::execute(args).await.expect("search should succeed");
+
+    assert_eq!(
+        summary.top_hits.len(),
+        1,
+        "markdown and output matches are dropped"
+    );
+    let hit = &summary.top_hits[0];
+    assert_eq!(hit.path, "analysis.ipynb#cell=2");
+    assert_eq!(hit.cell, Some(2));
+    assert_eq!(hit.line, 2);
+    assert_eq!(hit.snippet.as_deref(), Some("def load_frame(path):"));
+    assert_eq!(
+        hit.expanded_snippet.as_deref(),
+        Some(
+            "1 # Read a CSV export.\n2 def load_frame(path):\n3     return pandas.read_csv(path)\n"
+        )
+    );
+    assert_eq!(
+        summary.next_actions[0].to_string(),
+        "inspect analysis.ipynb#cell=2:2"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn links_generated_files_to_their_schema() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let repo = temp.path().join("repo");
+    let files = [
+        (
+            "proto/users.proto",
+            "syntax = \"proto3\";\npackage acme.users.v1;\n\nmessage GetUserRequest {}\n\nservice Users {\n  rpc GetUser(GetUserRequest) returns (GetUserRequest);\n}\n",
+        ),
+        (
+            "build.rs",
+            "fn main() {\n    tonic_build::compile_protos(\"proto/users.proto\").unwrap();\n}\n",
+        ),
+        (
+            "src/gen/acme.users.v1.rs",
+            "// This file is @generated by prost-build.\npub async fn get_user() {}\n",
+        ),
+        (
+            "api/users.pb.go",
+            "// Code generated by protoc-gen-go. DO NOT EDIT.\n// source: users.proto\n\nfunc GetUser() {}\n",
+        ),
+        (
+            "schema.graphql",
+            "type Query {\n  getUser(id: ID!): User\n}\n",
+        ),
+        (
+            "codegen.yml",
+            "schema: ./schema.graphql\ngenerates:\n  src/gql/types.ts:\n    plugins:\n      - typescript\n",
+        ),
+        ("src/gql/types.ts", "export type GetUserQuery = {};\n"),
+        ("src/users.rs", "pub fn get_user() {}\n"),
+        (".swe-grep.toml", "[tools.rg]\npath = \"../bin/rg\"\n"),
+    ];
+    for (path, contents) in files {
+        let path = repo.join(path);
+        std::fs::create_dir_all(path.parent().expect("path has a parent"))
+            .expect("failed to create dir");
+        std::fs::write(path, contents).expect("failed to write file");
+    }
+    write_fake_rg(
+        &temp.path().join("bin"),
+        &[
+            ("src/gen/acme.users.v1.rs", 2, "pub async fn get_user() {}"),
+            ("api/users.pb.go", 4, "func GetUser() {}"),
+            ("src/gql/types.ts", 1, "export type GetUserQuery = {};"),
+            ("src/users.rs", 1, "pub fn get_user() {}"),
+        ],
+    );
+
+    let args = SearchArgs {
+        symbol: "GetUser".to_string(),
+        path: vec![repo],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let summary = search::execute(args).await.expect("search should succeed");
+
+    let generated_from = |path: &str| {
+        let hit = summary
+            .top_hits
+            .iter()
+            .find(|hit| hit.path == path)
+            .unwrap_or_else(|| panic!("{path} should be a top hit"));
+        hit.hints
+            .iter()
+            .find(|hint| hint.kind == "generated_from")
+            .map(|hint| (hint.label.clone(), hint.line))
+    };
+    assert_eq!(
+        generated_from("src/gen/acme.users.v1.rs"),
+        Some(("proto/users.proto".to_string(), 7))
+    );
+    assert_eq!(
+        generated_from("api/users.pb.go"),
+        Some(("proto/users.proto".to_string(), 7))
+    );
+    assert_eq!(
+        generated_from("src/gql/types.ts"),
+        Some(("schema.graphql".to_string(), 2))
+    );
+    assert_eq!(generated_from("src/users.rs"), None);
+}
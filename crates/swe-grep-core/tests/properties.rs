@@ -0,0 +1,131 @@
+use std::path::{Path, PathBuf};
+
+use proptest::prelude::*;
+use swe_grep::plan::Strategy as SearchStrategy;
+use swe_grep::search;
+use tempfile::tempdir;
+
+/// Language tokens the rewriter adds variants for.
+const LANGUAGES: &[&str] = &[
+    "rust",
+    "typescript",
+    "swift",
+    "proto",
+    "graphql",
+    "openapi",
+    "objc",
+    "hcl",
+    "yaml",
+];
+
+/// Symbols as agents type them: identifiers, arbitrary printable Unicode, runs of regex
+/// metacharacters, and path-like strings.
+fn symbols() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[A-Za-z_][A-Za-z0-9_]{0,20}",
+        "\\PC{1,24}",
+        "[a-z.*+?()\\[\\]{}|^$\\\\/:<>'\"` -]{1,16}",
+        "(\\.{0,2}/)?[a-z]{1,6}(/[a-z.]{1,6}){0,3}",
+    ]
+}
+
+fn languages() -> impl Strategy<Value = Vec<String>> {
+    proptest::sample::subsequence(LANGUAGES, 0..=LANGUAGES.len())
+        .prop_map(|tokens| tokens.into_iter().map(str::to_string).collect())
+}
+
+proptest! {
+    #[test]
+    fn rewrites_compile_as_ripgrep_regexes(
+        symbol in symbols(),
+        languages in languages(),
+        word in prop_oneof![Just(None), Just(Some(true)), Just(Some(false))],
+    ) {
+        let (strategy, queries) = search::rewrite_queries(&symbol, &languages, word);
+        if strategy == SearchStrategy::Phrase {
+            // Probed with `--fixed-strings`.
+            return Ok(());
+        }
+        for query in &queries {
+            prop_assert!(!query.contains('\n'), "ripgrep rejects line breaks: {query:?}");
+            prop_assert!(
+                regex::Regex::new(query).is_ok(),
+                "{symbol:?} ({}) rewrote to an invalid pattern {query:?}",
+                strategy.as_str()
+            );
+        }
+        if strategy == SearchStrategy::Identifier && !symbol.trim().is_empty() {
+            prop_assert!(
+                queries.iter().any(|query| {
+                    regex::Regex::new(query).is_ok_and(|regex| regex.is_match(symbol.trim()))
+                }),
+                "no rewrite of {symbol:?} matches the symbol itself: {queries:?}"
+            );
+        }
+    }
+}
+
+fn segments() -> impl Strategy<Value = Vec<String>> {
+    proptest::collection::vec("[a-z0-9_]{1,8}", 1..4)
+}
+
+/// A fresh canonical root holding a file at `relative`.
+fn repo_with(relative: &Path) -> (tempfile::TempDir, PathBuf) {
+    let temp = tempdir().expect("failed to create tempdir");
+    let root = search::canonicalize_path(temp.path()).expect("tempdir resolves");
+    let file = root.join(relative);
+    std::fs::create_dir_all(file.parent().unwrap()).expect("failed to create parents");
+    std::fs::write(&file, "fn main() {}\n").expect("failed to write file");
+    (temp, root)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn normalize_path_round_trips_relative_and_absolute_inputs(segments in segments()) {
+        let relative: PathBuf = segments.iter().collect();
+        let (_temp, root) = repo_with(&relative);
+
+        let normalized = search::normalize_path(&root, &relative).unwrap();
+        prop_assert_eq!(&normalized, &relative);
+        prop_assert_eq!(&search::normalize_path(&root, &root.join(&relative)).unwrap(), &relative);
+        prop_assert_eq!(&search::normalize_path(&root, &normalized).unwrap(), &relative);
+
+        let mut dotted = PathBuf::from(".");
+        for segment in &segments {
+            dotted.push(segment);
+            dotted.push(".");
+        }
+        prop_assert_eq!(&search::normalize_path(&root, &dotted).unwrap(), &relative);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn normalize_path_resolves_symlinks_inside_the_root(segments in segments()) {
+        let relative: PathBuf = segments.iter().collect();
+        let (_temp, root) = repo_with(&relative);
+
+        // `-` never appears in generated segments, so the link cannot shadow them.
+        let link = root.join("link-");
+        std::os::unix::fs::symlink(root.join(&segments[0]), &link).unwrap();
+        let through_link: PathBuf = std::iter::once("link-")
+            .chain(segments[1..].iter().map(String::as_str))
+            .collect();
+        prop_assert_eq!(&search::normalize_path(&root, &through_link).unwrap(), &relative);
+        prop_assert_eq!(
+            &search::normalize_path(&root, &root.join(&through_link)).unwrap(),
+            &relative
+        );
+
+        let outside = tempdir().expect("failed to create tempdir");
+        let target = search::canonicalize_path(outside.path()).unwrap().join("outside.rs");
+        std::fs::write(&target, "").unwrap();
+        std::os::unix::fs::symlink(&target, root.join("escape-")).unwrap();
+        prop_assert_eq!(
+            &search::normalize_path(&root, Path::new("escape-")).unwrap(),
+            &target,
+            "links out of the root resolve to their absolute target"
+        );
+    }
+}
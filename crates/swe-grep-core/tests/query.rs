@@ -0,0 +1,729 @@
+use swe_grep::cli::{CaseMode, SearchArgs};
+use swe_grep::{identifier, search};
+use tempfile::tempdir;
+
+mod common;
+
+use common::{fixture_root, repo_tools, write_fake_rg};
+
+#[cfg(unix)]
+#[tokio::test]
+async fn scopes_rust_path_symbols_to_their_module() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(
+        &bin,
+        &[("src/auth/session.rs", 1, "pub fn login_user() {}")],
+    );
+    let repo = temp.path().join("repo");
+    let files = [
+        (
+            "Cargo.toml",
+            "[package]\nname = \"demo-app\"\nversion = \"0.1.0\"\n",
+        ),
+        (
+            "src/lib.rs",
+            "pub mod auth;\nmod billing;\n#[path = \"legacy_impl.rs\"]\nmod legacy;\nmod util {\n    pub fn login_user() {}\n}\n",
+        ),
+        (
+            "src/auth/mod.rs",
+            "mod session;\npub use session::login_user;\n",
+        ),
+        ("src/auth/session.rs", "pub fn login_user() {}\n"),
+        ("src/billing.rs", "pub fn login_user() {}\n"),
+        ("src/legacy_impl.rs", "pub fn login_user() {}\n"),
+    ];
+    for (path, contents) in files {
+        let path = repo.join(path);
+        std::fs::create_dir_all(path.parent().expect("path has a parent"))
+            .expect("failed to create dir");
+        std::fs::write(path, contents).expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = |symbol: &str| SearchArgs {
+        symbol: symbol.to_string(),
+        path: vec![repo.clone()],
+        language: Some("rust".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let probed = || {
+        let log = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+        std::fs::remove_file(bin.join("rg-args.log")).expect("failed to reset log");
+        let mut files: Vec<String> = log
+            .lines()
+            .filter(|arg| arg.ends_with(".rs"))
+            .map(str::to_string)
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    };
+
+    for symbol in [
+        "crate::auth::login_user",
+        "auth::login_user",
+        "demo_app::auth::login_user",
+    ] {
+        let summary = search::execute(args(symbol))
+            .await
+            .expect("path search should succeed");
+        assert_eq!(summary.symbol, "login_user");
+        assert_eq!(
+            probed(),
+            ["src/auth/mod.rs", "src/auth/session.rs"],
+            "{symbol} should probe the auth module"
+        );
+        assert_eq!(summary.top_hits[0].path, "src/auth/session.rs");
+    }
+
+    search::execute(args("legacy::login_user"))
+        .await
+        .expect("path search should succeed");
+    assert_eq!(
+        probed(),
+        ["src/legacy_impl.rs"],
+        "#[path] declarations are followed"
+    );
+
+    search::execute(args("util::login_user"))
+        .await
+        .expect("path search should succeed");
+    assert_eq!(
+        probed(),
+        ["src/lib.rs"],
+        "inline modules stay in their file"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn searches_infrastructure_config_first() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let repo = temp.path().join("repo");
+    let manifest = "apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: web\nspec:\n  replicas: 3\nstatus:\n  replicas: 1\n";
+    let files = [
+        (
+            "infra/main.tf",
+            "resource \"aws_s3_bucket\" \"logs\" {\n  bucket = \"logs\"\n}\n",
+        ),
+        ("k8s/deploy.yaml", manifest),
+        ("src/app.rs", "fn logs() {}\n"),
+    ];
+    for (path, contents) in files {
+        let path = repo.join(path);
+        std::fs::create_dir_all(path.parent().expect("path has a parent"))
+            .expect("failed to create dir");
+        std::fs::write(path, contents).expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = |symbol: &str| SearchArgs {
+        symbol: symbol.to_string(),
+        path: vec![repo.clone()],
+        config: true,
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    // Without fast-path matches the cycle discovers config files, leaving rg's types alone.
+    write_fake_rg(&bin, &[]);
+    let summary = search::execute(args("aws_s3_bucket.logs"))
+        .await
+        .expect("search should succeed");
+    let discovered: Vec<String> = summary
+        .fd_candidates
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect();
+    assert_eq!(discovered, ["infra/main.tf", "k8s/deploy.yaml"]);
+    assert!(
+        summary
+            .queries
+            .iter()
+            .any(|query| query == "resource \"aws_s3_bucket\" \"logs\""),
+        "terraform addresses map to their blocks: {:?}",
+        summary.queries
+    );
+    let log = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+    assert!(
+        !log.lines().any(|arg| arg.starts_with("--type")),
+        "--config alone does not restrict rg: {log}"
+    );
+
+    write_fake_rg(
+        &bin,
+        &[
+            ("src/app.rs", 1, "fn logs() {}"),
+            ("infra/main.tf", 1, "resource \"aws_s3_bucket\" \"logs\" {"),
+        ],
+    );
+    let summary = search::execute(args("logs"))
+        .await
+        .expect("search should succeed");
+    let paths: Vec<&str> = summary
+        .top_hits
+        .iter()
+        .map(|hit| hit.path.as_str())
+        .collect();
+    assert_eq!(
+        paths,
+        ["infra/main.tf", "src/app.rs"],
+        "config hits rank first"
+    );
+
+    write_fake_rg(
+        &bin,
+        &[
+            ("k8s/deploy.yaml", 6, "  replicas: 3"),
+            ("k8s/deploy.yaml", 8, "  replicas: 1"),
+        ],
+    );
+    let summary = search::execute(args("spec.replicas"))
+        .await
+        .expect("search should succeed");
+    let lines: Vec<usize> = summary.top_hits.iter().map(|hit| hit.line).collect();
+    assert_eq!(lines, [6], "only the key under `spec` matches the key path");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn rewrites_schema_symbols_for_idl_languages() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("api")).expect("failed to create repo");
+    std::fs::write(
+        repo.join("api/user.proto"),
+        "syntax = \"proto3\";\n\nservice Users {\n  rpc GetUser(GetUserRequest) returns (User);\n}\n",
+    )
+    .expect("failed to write proto");
+    write_fake_rg(
+        &temp.path().join("bin"),
+        &[(
+            "api/user.proto",
+            4,
+            "  rpc GetUser(GetUserRequest) returns (User);",
+        )],
+    );
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = SearchArgs {
+        symbol: "GetUser".to_string(),
+        path: vec![repo],
+        language: Some("proto+graphql+openapi".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let summary = search::execute(args).await.expect("search should succeed");
+
+    // Literal symbols match whole words, so each rewrite is bounded by `\b`.
+    for query in [
+        r"\brpc GetUser\b",
+        r"\bmessage GetUser\b",
+        r"\btype GetUser\b",
+        r"\bquery GetUser\b",
+        r"\boperationId: GetUser\b",
+    ] {
+        assert!(
+            summary.queries.iter().any(|candidate| candidate == query),
+            "missing `{query}` in {:?}",
+            summary.queries
+        );
+    }
+    assert_eq!(summary.top_hits[0].path, "api/user.proto");
+    assert!(
+        summary.warnings.is_empty(),
+        "ast-grep is skipped for schema-only searches: {:?}",
+        summary.warnings
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn passes_case_mode_to_ripgrep() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(&bin, &[("src/lib.rs", 1, "pub fn parse_config() {}")]);
+
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn parse_config() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = |case: Option<CaseMode>| SearchArgs {
+        symbol: "parse_config".to_string(),
+        path: vec![repo.clone()],
+        case,
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let case_flags = || {
+        let logged = std::fs::read_to_string(bin.join("rg-args.log")).expect("fake rg should run");
+        std::fs::remove_file(bin.join("rg-args.log")).expect("failed to reset log");
+        let mut flags: Vec<String> = logged
+            .lines()
+            .filter(|arg| arg.ends_with("-case") || *arg == "--case-sensitive")
+            .map(str::to_string)
+            .collect();
+        flags.dedup();
+        flags
+    };
+
+    for (case, flag) in [
+        (None, "--smart-case"),
+        (Some(CaseMode::Smart), "--smart-case"),
+        (Some(CaseMode::Sensitive), "--case-sensitive"),
+        (Some(CaseMode::Insensitive), "--ignore-case"),
+    ] {
+        let summary = search::execute(args(case))
+            .await
+            .expect("search should succeed");
+        assert!(summary.top_hits.iter().any(|hit| hit.path == "src/lib.rs"));
+        assert_eq!(case_flags(), vec![flag.to_string()], "{case:?}");
+    }
+
+    assert_eq!(CaseMode::parse("Sensitive"), Ok(CaseMode::Sensitive));
+    assert!(CaseMode::parse("upper").is_err());
+    assert!(CaseMode::Smart.ignores_case("parse_config"));
+    assert!(!CaseMode::Smart.ignores_case("ParseConfig"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn bounds_literal_symbols_at_word_boundaries() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(&bin, &[("src/lib.rs", 1, "pub fn run() {}")]);
+
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn run() {}\n").expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = |word: Option<bool>| SearchArgs {
+        symbol: "run".to_string(),
+        path: vec![repo.clone()],
+        language: Some("rust".to_string()),
+        word,
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let patterns = || {
+        let logged = std::fs::read_to_string(bin.join("rg-args.log")).expect("fake rg should run");
+        std::fs::remove_file(bin.join("rg-args.log")).expect("failed to reset log");
+        let args: Vec<String> = logged.lines().map(str::to_string).collect();
+        args.windows(2)
+            .filter(|pair| pair[0] == "-e")
+            .map(|pair| pair[1].clone())
+            .collect::<Vec<_>>()
+    };
+
+    search::execute(args(None))
+        .await
+        .expect("search should succeed");
+    let bounded = patterns();
+    for expected in [r"\brun\b", r"\bfn run\b", r"::run\b", r"\brun::<"] {
+        assert!(
+            bounded.iter().any(|p| p == expected),
+            "{expected}: {bounded:?}"
+        );
+    }
+    assert!(!bounded.iter().any(|p| p == "run"), "{bounded:?}");
+
+    search::execute(args(Some(false)))
+        .await
+        .expect("search should succeed");
+    let partial = patterns();
+    assert!(partial.iter().any(|p| p == "run"), "{partial:?}");
+    assert!(!partial.iter().any(|p| p.contains(r"\b")), "{partial:?}");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn matches_unicode_identifiers_at_script_aware_boundaries() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(
+        &bin,
+        &[
+            ("Profile.swift", 2, "    let 用户名: String"),
+            ("src/units.py", 1, "def größe(maße):"),
+        ],
+    );
+
+    let fixtures = fixture_root().join("fixtures/unicode");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    for file in ["Profile.swift", "src/units.py"] {
+        std::fs::copy(fixtures.join(file), repo.join(file)).expect("failed to copy fixture");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = |symbol: &str, language: &str| SearchArgs {
+        symbol: symbol.to_string(),
+        path: vec![repo.clone()],
+        language: Some(language.to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let patterns = || {
+        let logged = std::fs::read_to_string(bin.join("rg-args.log")).expect("fake rg should run");
+        std::fs::remove_file(bin.join("rg-args.log")).expect("failed to reset log");
+        let args: Vec<String> = logged.lines().map(str::to_string).collect();
+        args.windows(2)
+            .filter(|pair| pair[0] == "-e")
+            .map(|pair| pair[1].clone())
+            .collect::<Vec<_>>()
+    };
+
+    // Latin letters outside ASCII still take word boundaries.
+    let summary = search::execute(args("größe", "python"))
+        .await
+        .expect("search should succeed");
+    let latin = patterns();
+    assert!(latin.iter().any(|p| p == r"\bgröße\b"), "{latin:?}");
+    assert!(!latin.iter().any(|p| p == "größe"), "{latin:?}");
+    assert!(
+        summary
+            .top_hits
+            .iter()
+            .any(|hit| hit.path == "src/units.py")
+    );
+
+    // Han has no spaces between words, so its ends stay unanchored.
+    let summary = search::execute(args("用户名", "swift"))
+        .await
+        .expect("search should succeed");
+    let han = patterns();
+    assert!(han.iter().any(|p| p == "用户名"), "{han:?}");
+    assert!(
+        !han.iter()
+            .any(|p| p.contains(r"\b用户名") || p.contains(r"用户名\b")),
+        "{han:?}"
+    );
+    assert!(
+        summary
+            .top_hits
+            .iter()
+            .any(|hit| hit.path == "Profile.swift")
+    );
+
+    assert!(identifier::is_identifier("größe"));
+    assert!(identifier::is_identifier("用户名"));
+    assert!(identifier::is_identifier("_ready"));
+    assert!(!identifier::is_identifier("2fa"));
+    assert!(!identifier::is_identifier("用户-名"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn plans_searches_from_the_symbol_shape() {
+    use swe_grep::plan::{QueryPlan, Strategy};
+
+    let strategy = |symbol: &str| QueryPlan::for_symbol(symbol, true).strategy;
+    assert_eq!(strategy("login_user"), Strategy::Identifier);
+    assert_eq!(strategy("config.load"), Strategy::Identifier);
+    assert_eq!(strategy("crate::auth::login_user"), Strategy::ModulePath);
+    assert_eq!(strategy("failed to canonicalize"), Strategy::Phrase);
+    assert_eq!(
+        strategy("\"failed to canonicalize\""),
+        Strategy::StringLiteral
+    );
+    assert_eq!(strategy("handle_*_event"), Strategy::Glob);
+    assert_eq!(strategy("Vec<String>"), Strategy::Structural);
+    assert_eq!(strategy("login_user($A, $$$)"), Strategy::Structural);
+    assert_eq!(
+        QueryPlan::for_symbol("auth::login_user", false).strategy,
+        Strategy::Identifier,
+        "Rust paths are only resolved when the language allows Rust"
+    );
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(
+        &bin,
+        &[(
+            "src/lib.rs",
+            2,
+            "    .context(\"failed to canonicalize root\")?;",
+        )],
+    );
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(
+        repo.join("src/lib.rs"),
+        "pub fn open(root: &Path) -> Result<PathBuf> {\n    .context(\"failed to canonicalize root\")?;\n}\n",
+    )
+    .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let search = |symbol: &str| SearchArgs {
+        symbol: symbol.to_string(),
+        path: vec![repo.clone()],
+        language: Some("rust".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    let quoted = search::execute(search("\"failed to canonicalize\""))
+        .await
+        .expect("string literal search should succeed");
+    assert_eq!(quoted.strategy, Strategy::StringLiteral);
+    assert_eq!(quoted.symbol, "failed to canonicalize");
+    assert_eq!(
+        quoted.queries,
+        [
+            "[\"'`]failed\\s+to\\s+canonicalize[\"'`]",
+            "failed\\s+to\\s+canonicalize"
+        ],
+        "quoted phrases get no identifier rewrites"
+    );
+    assert_eq!(quoted.top_hits[0].line, 2);
+
+    let phrase = search::execute(search("failed to canonicalize"))
+        .await
+        .expect("phrase search should succeed");
+    assert_eq!(phrase.strategy, Strategy::Phrase);
+    assert_eq!(phrase.queries, ["failed to canonicalize"]);
+
+    let glob = search::execute(search("handle_*_event"))
+        .await
+        .expect("glob search should succeed");
+    assert_eq!(glob.strategy, Strategy::Glob);
+    assert_eq!(glob.queries, ["\\bhandle_\\w*_event\\b"]);
+
+    let structural = search::execute(search("Result<PathBuf>"))
+        .await
+        .expect("structural search should succeed");
+    assert_eq!(structural.strategy, Strategy::Structural);
+    assert_eq!(structural.queries, ["Result<PathBuf>"]);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn searches_phrases_as_fixed_strings() {
+    use swe_grep::plan::Strategy;
+
+    let files = [
+        ("src/a.rs", "// Failed To Canonicalize root"),
+        ("src/b.rs", "// failed to canonicalize root"),
+        ("src/c.rs", "// failed to canonicalizes root"),
+    ];
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let matches: Vec<(&str, usize, &str)> =
+        files.iter().map(|(path, text)| (*path, 1, *text)).collect();
+    write_fake_rg(&bin, &matches);
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    for (path, text) in files {
+        std::fs::write(repo.join(path), format!("{text}\n")).expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let summary = search::execute(SearchArgs {
+        symbol: "failed to canonicalize".to_string(),
+        path: vec![repo.clone()],
+        case: Some(CaseMode::Insensitive),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        tools: repo_tools(),
+        ..Default::default()
+    })
+    .await
+    .expect("phrase search should succeed");
+
+    assert_eq!(summary.strategy, Strategy::Phrase);
+    assert_eq!(summary.queries, ["failed to canonicalize"]);
+    let rg_args = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+    assert!(
+        rg_args.lines().any(|arg| arg == "--fixed-strings"),
+        "{rg_args}"
+    );
+    assert!(
+        !rg_args.lines().any(|arg| arg == "--word-regexp"),
+        "{rg_args}"
+    );
+    assert_eq!(
+        summary.stage_stats.disambiguate_ms, 0,
+        "phrases skip ast-grep"
+    );
+    let ranked: Vec<&str> = summary
+        .top_hits
+        .iter()
+        .map(|hit| hit.path.as_str())
+        .collect();
+    assert_eq!(
+        ranked,
+        ["src/b.rs", "src/c.rs", "src/a.rs"],
+        "the exact phrase outranks one running into a longer word and one matching only folded"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn reports_identifiers_a_glob_symbol_matched() {
+    use swe_grep::plan::{self, Strategy};
+    use swe_grep::search::MatchedIdentifier;
+
+    assert!(plan::glob_matches(
+        "handle_*_event",
+        "handle_click_event",
+        false
+    ));
+    assert!(plan::glob_matches("handle_*_event", "handle__event", false));
+    assert!(plan::glob_matches("get?", "getX", false));
+    assert!(!plan::glob_matches("get?", "get", false));
+    assert!(!plan::glob_matches(
+        "handle_*_event",
+        "Handle_click_event",
+        false
+    ));
+    assert!(plan::glob_matches(
+        "handle_*_event",
+        "Handle_click_event",
+        true
+    ));
+    assert_eq!(
+        plan::glob_identifiers(
+            "*Service",
+            "let auth: AuthService = UserService::new();",
+            false
+        )
+        .collect::<Vec<_>>(),
+        ["AuthService", "UserService"]
+    );
+
+    let files = [
+        (
+            "src/a.rs",
+            "fn handle_click_event() { handle_key_event(); }",
+        ),
+        ("src/b.rs", "    handle_click_event();"),
+        (
+            "src/c.rs",
+            "// handle_*_event dispatches handle_scroll_event",
+        ),
+    ];
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let matches: Vec<(&str, usize, &str)> =
+        files.iter().map(|(path, text)| (*path, 1, *text)).collect();
+    write_fake_rg(&bin, &matches);
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    for (path, text) in files {
+        std::fs::write(repo.join(path), format!("{text}\n")).expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let summary = search::execute(SearchArgs {
+        symbol: "handle_*_event".to_string(),
+        path: vec![repo.clone()],
+        language: Some("rust".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    })
+    .await
+    .expect("glob search should succeed");
+
+    assert_eq!(summary.strategy, Strategy::Glob);
+    assert_eq!(summary.queries, ["\\bhandle_\\w*_event\\b"]);
+    let rg_args = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+    assert!(
+        rg_args.lines().any(|arg| arg == "\\bhandle_\\w*_event\\b"),
+        "the `*` reaches ripgrep as a wildcard: {rg_args}"
+    );
+    let matched = |identifier: &str, hits: usize| MatchedIdentifier {
+        identifier: identifier.to_string(),
+        hits,
+    };
+    assert_eq!(
+        summary.matched_identifiers,
+        [
+            matched("handle_click_event", 2),
+            matched("handle_key_event", 1),
+            matched("handle_scroll_event", 1),
+        ]
+    );
+}
@@ -0,0 +1,230 @@
+use std::path::Path;
+
+use swe_grep::cli::SearchArgs;
+use swe_grep::{revision, search};
+use tempfile::tempdir;
+
+mod common;
+
+use common::{fixture_root, git};
+
+/// A repository whose first commit holds `files`; returns the canonical root and the commit id.
+fn repo_with(temp: &Path, files: &[(&str, &str)]) -> (std::path::PathBuf, String) {
+    let root = temp.join("repo");
+    std::fs::create_dir_all(&root).expect("failed to create repo");
+    for (path, contents) in files {
+        let file = root.join(path);
+        std::fs::create_dir_all(file.parent().unwrap()).expect("failed to create parents");
+        std::fs::write(&file, contents).expect("failed to write file");
+    }
+    git(&root, &["init", "-q"]);
+    git(&root, &["add", "."]);
+    git(&root, &["commit", "-q", "-m", "initial"]);
+    let commit = git(&root, &["rev-parse", "HEAD"]);
+    (root.canonicalize().unwrap(), commit)
+}
+
+#[test]
+fn splits_symbols_into_search_terms() {
+    assert_eq!(revision::search_terms("login_user"), ["login", "user"]);
+    assert_eq!(
+        revision::search_terms("HTTPServerV2"),
+        ["http", "server", "v2"]
+    );
+    assert_eq!(
+        revision::search_terms("crate::auth::LoginUser"),
+        ["login", "user"]
+    );
+    assert_eq!(revision::search_terms("FOO_BAR foo"), ["foo", "bar"]);
+    assert!(revision::search_terms("$A").is_empty());
+}
+
+#[tokio::test]
+async fn exports_only_candidate_files_of_a_revision() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let (root, commit) = repo_with(
+        temp.path(),
+        &[
+            ("src/auth.rs", "pub fn login_user() {}\n"),
+            ("src/session.ts", "export const LoginUser = 1;\n"),
+            ("src/billing.rs", "pub fn charge() {}\n"),
+            ("docs/login.md", "Run login as any user.\n"),
+        ],
+    );
+    // Working-tree edits never reach the snapshot.
+    std::fs::write(root.join("src/auth.rs"), "uncommitted\n").unwrap();
+
+    let cache = temp.path().join("cache");
+    let snapshot = revision::materialize(&root, "HEAD", &cache, "login_user")
+        .await
+        .expect("materialize should succeed");
+
+    assert_eq!(snapshot.commit, commit);
+    assert_eq!(
+        std::fs::read_to_string(snapshot.root.join("src/auth.rs")).unwrap(),
+        "pub fn login_user() {}\n"
+    );
+    assert!(snapshot.root.join("src/session.ts").is_file());
+    assert!(
+        !snapshot.root.join("src/billing.rs").exists(),
+        "files without the symbol's words are not exported"
+    );
+    assert!(
+        snapshot.root.join("docs/login.md").is_file(),
+        "words may sit apart"
+    );
+    assert!(
+        snapshot
+            .root
+            .starts_with(cache.join("revisions").join(&commit))
+    );
+    assert_eq!(git(&root, &["status", "--porcelain"]), "M src/auth.rs");
+
+    let nested = revision::materialize(&root.join("src"), "HEAD", &cache, "charge")
+        .await
+        .expect("materialize from a subdirectory should succeed");
+    assert!(nested.root.ends_with("src"));
+    assert!(nested.root.join("billing.rs").is_file());
+    assert!(!nested.root.join("auth.rs").exists());
+
+    let missing = revision::materialize(&root, "HEAD", &cache, "no_such_symbol")
+        .await
+        .expect("a symbol with no candidates still yields a snapshot");
+    assert!(missing.root.is_dir());
+    assert_eq!(std::fs::read_dir(&missing.root).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn exports_config_ignore_rules_and_manifests_of_a_revision() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let (root, _) = repo_with(
+        temp.path(),
+        &[
+            (".swe-grep.toml", "[scoring]\n"),
+            (".gitignore", "/generated/\n"),
+            ("Cargo.toml", "[package]\nname = \"widgets\"\n"),
+            ("src/lib.rs", "pub mod auth;\n"),
+            ("src/auth.rs", "pub fn login_user() {}\n"),
+            ("web/Package.swift", "// swift-tools-version:5.9\n"),
+        ],
+    );
+    // Tracked despite the ignore rule, so only the rule keeps it out of a walk.
+    std::fs::create_dir_all(root.join("generated")).unwrap();
+    std::fs::write(
+        root.join("generated/login_user.rs"),
+        "pub fn login_user() {}\n",
+    )
+    .unwrap();
+    git(&root, &["add", "-f", "generated"]);
+    git(&root, &["commit", "-q", "-m", "generated"]);
+    std::fs::write(root.join(".swe-grep.toml"), "uncommitted\n").unwrap();
+
+    let cache = temp.path().join("cache");
+    let snapshot = revision::materialize(&root, "HEAD", &cache, "login_user")
+        .await
+        .expect("materialize should succeed");
+    assert_eq!(
+        std::fs::read_to_string(snapshot.root.join(".swe-grep.toml")).unwrap(),
+        "[scoring]\n"
+    );
+    for file in [
+        ".gitignore",
+        "Cargo.toml",
+        "web/Package.swift",
+        "src/auth.rs",
+    ] {
+        assert!(
+            snapshot.root.join(file).is_file(),
+            "{file} should be exported"
+        );
+    }
+    assert!(
+        !snapshot.root.join("generated/login_user.rs").exists(),
+        "ignored files are not exported"
+    );
+    assert!(
+        !snapshot.root.join("src/lib.rs").exists(),
+        "other sources are only exported for Rust paths"
+    );
+
+    let path = revision::materialize(&root, "HEAD", &cache, "crate::auth::login_user")
+        .await
+        .expect("materialize should succeed");
+    assert_ne!(path.root, snapshot.root);
+    assert_eq!(
+        std::fs::read_to_string(path.root.join("src/lib.rs")).unwrap(),
+        "pub mod auth;\n"
+    );
+
+    let nested = revision::materialize(&root.join("src"), "HEAD", &cache, "auth")
+        .await
+        .expect("materialize from a subdirectory should succeed");
+    assert!(
+        nested.root.join("../.gitignore").is_file(),
+        "ignore files above the root still apply"
+    );
+    assert!(nested.root.join("lib.rs").is_file());
+    assert!(!nested.root.join("../Cargo.toml").exists());
+}
+
+#[tokio::test]
+async fn evicts_least_recently_used_snapshots() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let (root, _) = repo_with(temp.path(), &[("src/lib.rs", "pub fn lib() {}\n")]);
+    let cache = temp.path().join("cache");
+
+    let mut snapshots = Vec::new();
+    for idx in 0..16 {
+        let symbol = format!("symbol{idx}");
+        let snapshot = revision::materialize(&root, "HEAD", &cache, &symbol)
+            .await
+            .expect("materialize should succeed");
+        snapshots.push(snapshot.root);
+    }
+    assert!(snapshots.iter().all(|root| root.is_dir()));
+
+    // Reusing the oldest snapshot makes the second one the least recently used.
+    revision::materialize(&root, "HEAD", &cache, "symbol0")
+        .await
+        .expect("reuse should succeed");
+    let latest = revision::materialize(&root, "HEAD", &cache, "symbol16")
+        .await
+        .expect("materialize should succeed");
+
+    assert!(latest.root.is_dir());
+    assert!(snapshots[0].is_dir(), "recently reused snapshots are kept");
+    assert!(
+        !snapshots[1].exists(),
+        "the least recently used snapshot is evicted"
+    );
+    assert!(snapshots[2..].iter().all(|root| root.is_dir()));
+}
+
+#[tokio::test]
+async fn rejects_unknown_revision() {
+    let repo_root = fixture_root().join("fixtures/multi_lang");
+    let temp = tempdir().expect("failed to create tempdir");
+    let args = SearchArgs {
+        symbol: "login_user".to_string(),
+        path: vec![repo_root],
+        language: Some("rust".to_string()),
+        rev: Some("swe-grep-missing-revision".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        ..Default::default()
+    };
+
+    let err = match search::execute(args).await {
+        Ok(_) => panic!("search at a missing revision should fail"),
+        Err(err) => err,
+    };
+    assert!(
+        format!("{err:#}").contains("swe-grep-missing-revision"),
+        "error should name the revision: {err:#}"
+    );
+    assert!(
+        !temp.path().join("cache/revisions").exists(),
+        "no snapshot should be exported for an unknown revision"
+    );
+}
@@ -0,0 +1,359 @@
+use swe_grep::cli::SearchArgs;
+use swe_grep::search::{self, WarningKind};
+use tempfile::tempdir;
+
+mod common;
+
+use common::{fixture_root, repo_tools, write_fake_rg, write_fake_tool};
+
+#[cfg(unix)]
+#[tokio::test]
+async fn caps_probe_scope_and_warns_when_truncated() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(&bin, &[("src/a.rs", 1, "pub fn load_config() {}")]);
+
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    for name in ["a", "b", "c", "d"] {
+        std::fs::write(
+            repo.join(format!("src/{name}.rs")),
+            "pub fn load_config() {}\n",
+        )
+        .expect("failed to write source");
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+    let list = temp.path().join("files.txt");
+    std::fs::write(&list, "src/a.rs\nsrc/b.rs\nsrc/c.rs\nsrc/d.rs\n")
+        .expect("failed to write file list");
+
+    let args = SearchArgs {
+        symbol: "load_config".to_string(),
+        path: vec![repo],
+        files_from: Some(list),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        max_scope_files: Some(2),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    let summary = search::execute(args).await.expect("search should succeed");
+    assert_eq!(summary.stage_stats.discover_candidates, 4);
+    assert_eq!(summary.top_hits.len(), 1);
+    let warning = summary
+        .warnings
+        .iter()
+        .find(|warning| warning.kind == WarningKind::ScopeTruncated)
+        .expect("expected a scope_truncated warning");
+    assert_eq!(warning.tool.as_deref(), Some("rg"));
+    assert!(
+        warning.message.contains("probed 2 of 4"),
+        "{}",
+        warning.message
+    );
+
+    let log = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+    let probed: Vec<&str> = log.lines().filter(|arg| arg.starts_with("src/")).collect();
+    assert_eq!(probed, ["src/a.rs", "src/b.rs"]);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn limits_probes_to_ripgrep_file_types() {
+    use swe_grep::file_types::FileTypes;
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(&bin, &[("src/lib.rs", 1, "pub fn typed() {}")]);
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn typed() {}\n").expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = |language: Option<&str>, rg_types: &[&str], rg_type_adds: &[&str]| SearchArgs {
+        symbol: "typed".to_string(),
+        path: vec![repo.clone()],
+        language: language.map(str::to_string),
+        rg_types: rg_types.iter().map(|name| name.to_string()).collect(),
+        rg_type_adds: rg_type_adds.iter().map(|spec| spec.to_string()).collect(),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let type_args = || {
+        let log = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+        std::fs::remove_file(bin.join("rg-args.log")).expect("failed to reset log");
+        let mut args: Vec<String> = log
+            .lines()
+            .filter(|arg| arg.starts_with("--type"))
+            .map(str::to_string)
+            .collect();
+        args.dedup();
+        args
+    };
+
+    search::execute(args(Some("rust"), &[], &[]))
+        .await
+        .expect("language search should succeed");
+    assert_eq!(type_args(), ["--type=rust"]);
+
+    search::execute(args(Some("rust"), &["proto"], &["proto:*.proto"]))
+        .await
+        .expect("typed search should succeed");
+    assert_eq!(
+        type_args(),
+        ["--type-add=proto:*.proto", "--type=proto"],
+        "explicit types replace the language's"
+    );
+
+    let rejected = |args: SearchArgs| -> Vec<&'static str> {
+        swe_grep::validate::search_args(&args)
+            .expect_err("invalid types should be rejected")
+            .fields
+            .iter()
+            .map(|error| error.field)
+            .collect()
+    };
+    assert_eq!(rejected(args(None, &["nosuchtype"], &[])), ["rg_types"]);
+    assert_eq!(rejected(args(None, &[], &["no-glob"])), ["rg_type_adds"]);
+
+    let tokens =
+        |tokens: &[&str]| -> Vec<String> { tokens.iter().map(|t| t.to_string()).collect() };
+    let typescript =
+        FileTypes::for_languages(&tokens(&["ts", "tsx"])).expect("typescript has an rg type");
+    assert_eq!(typescript.rg_args(), ["--type=ts"]);
+    assert!(typescript.matches(std::path::Path::new("src/server.mts")));
+    assert!(!typescript.matches(std::path::Path::new("src/lib.rs")));
+    assert!(
+        FileTypes::for_languages(&tokens(&["tsx"])).is_none(),
+        "no rg type covers only tsx"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn splits_large_scopes_across_rg_invocations() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(&bin, &[("src/f0.rs", 1, "pub fn load_config() {}")]);
+
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    let mut list = String::new();
+    for index in 0..1_205 {
+        let name = format!("src/f{index}.rs");
+        std::fs::write(repo.join(&name), "pub fn load_config() {}\n")
+            .expect("failed to write source");
+        list.push_str(&name);
+        list.push('\n');
+    }
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+    let list_path = temp.path().join("files.txt");
+    std::fs::write(&list_path, list).expect("failed to write file list");
+
+    let args = SearchArgs {
+        symbol: "load_config".to_string(),
+        path: vec![repo],
+        files_from: Some(list_path),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    let summary = search::execute(args).await.expect("search should succeed");
+    assert!(!summary.top_hits.is_empty());
+    assert!(
+        !summary
+            .warnings
+            .iter()
+            .any(|warning| warning.kind == WarningKind::ScopeTruncated),
+        "an uncapped scope is never truncated: {:?}",
+        summary.warnings
+    );
+
+    // Every listed file reaches ripgrep, split over two invocations.
+    let log = std::fs::read_to_string(bin.join("rg-args.log")).expect("rg should have run");
+    assert_eq!(log.lines().filter(|arg| *arg == "--json").count(), 2);
+    let probed: std::collections::HashSet<&str> =
+        log.lines().filter(|arg| arg.starts_with("src/")).collect();
+    assert_eq!(probed.len(), 1_205);
+}
+
+#[tokio::test]
+async fn files_from_scope_skips_discovery_and_global_fallbacks() {
+    let repo_root = fixture_root().join("fixtures/multi_lang");
+    let temp = tempdir().expect("failed to create tempdir");
+    let list = temp.path().join("files.txt");
+    std::fs::write(&list, "src/missing.rs\n\n../outside.rs\n").expect("failed to write file list");
+
+    let args = SearchArgs {
+        symbol: "login_user".to_string(),
+        path: vec![repo_root],
+        language: Some("rust".to_string()),
+        files_from: Some(list),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        ..Default::default()
+    };
+
+    let summary = search::execute(args).await.expect("search should succeed");
+    assert!(summary.top_hits.is_empty());
+    assert_eq!(summary.stage_stats.discover_candidates, 0);
+    assert!(
+        summary
+            .warnings
+            .iter()
+            .any(|warning| warning.kind == WarningKind::ScopeSkipped
+                && warning.message.contains("skipped 2 --files-from entries")),
+        "expected skipped entries warning, got {:?}",
+        summary.warnings
+    );
+    assert!(
+        !summary
+            .warnings
+            .iter()
+            .any(|warning| warning.tool.as_deref() == Some("rg")),
+        "an empty scope must not fall back to a global probe"
+    );
+    assert_eq!(
+        summary.next_actions,
+        vec![search::NextAction::EscalateGlobal]
+    );
+    let rendered = serde_json::to_value(&summary).expect("summary should serialize");
+    assert_eq!(
+        rendered["next_actions"],
+        serde_json::json!([{ "kind": "escalate_global" }])
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn probes_tiny_repositories_without_a_per_file_cap() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(&bin, &[("src/lib.rs", 1, "pub fn load_config() {}")]);
+
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn load_config() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = |max_matches: Option<usize>| SearchArgs {
+        symbol: "load_config".to_string(),
+        path: vec![repo.clone()],
+        timeout_secs: Some(3),
+        max_matches,
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    for (max_matches, expected) in [(None, None), (Some(7), Some("7"))] {
+        let summary = search::execute(args(max_matches))
+            .await
+            .expect("search should succeed");
+        assert_eq!(summary.top_hits.len(), 1);
+        let logged = std::fs::read_to_string(bin.join("rg-args.log")).expect("fake rg should run");
+        std::fs::remove_file(bin.join("rg-args.log")).expect("failed to reset log");
+        let lines: Vec<&str> = logged.lines().collect();
+        let max_count = lines
+            .iter()
+            .position(|line| *line == "--max-count")
+            .map(|index| lines[index + 1]);
+        // A one-file repository is probed exhaustively unless a limit is given.
+        assert_eq!(max_count, expected, "{max_matches:?}");
+    }
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn reports_ignored_directories_and_filtered_extensions() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(
+        &bin,
+        &[("src/config.rs", 1, "impl Config { fn load() {} }")],
+    );
+    write_fake_tool(
+        &bin,
+        "fd",
+        "printf '%s\\n' src/config.rs docs/config.md docs/Config.MD\n",
+    );
+
+    let repo = temp.path().join("repo");
+    let files = [
+        ("src/config.rs", "impl Config { fn load() {} }\n"),
+        ("docs/config.md", "Call `Config::load` at startup.\n"),
+        ("docs/Config.MD", "Config::load\n"),
+        ("node_modules/pkg/index.js", "Config.load();\n"),
+        ("web/node_modules/pkg/index.js", "Config.load();\n"),
+        ("web/app.ts", "Config.load();\n"),
+        (".github/workflows/ci.yml", "run: Config::load\n"),
+        (".ignore", "node_modules/\n"),
+        (
+            ".swe-grep.toml",
+            "[tools.rg]\npath = \"../bin/rg\"\n\n[tools.fd]\npath = \"../bin/fd\"\n",
+        ),
+    ];
+    for (path, contents) in files {
+        let path = repo.join(path);
+        std::fs::create_dir_all(path.parent().expect("path has a parent"))
+            .expect("failed to create dir");
+        std::fs::write(path, contents).expect("failed to write file");
+    }
+
+    let args = SearchArgs {
+        symbol: "Config::load".to_string(),
+        path: vec![repo],
+        language: Some("rust".to_string()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let summary = search::execute(args).await.expect("search should succeed");
+
+    assert_eq!(
+        summary.skipped,
+        vec![
+            ".github (hidden)",
+            "node_modules (ignored)",
+            "web/node_modules (ignored)",
+            "*.md (extension filter)",
+        ]
+    );
+}
@@ -0,0 +1,680 @@
+use swe_grep::cli::{SearchArgs, ToolArgs};
+use swe_grep::config::RepoConfig;
+use swe_grep::search::{self, WarningKind};
+use tempfile::tempdir;
+
+mod common;
+
+use common::{fake_rg_script, repo_tools, rg_match, write_fake_rg, write_fake_tool};
+
+#[tokio::test]
+async fn reports_tool_timeouts_as_typed_warnings() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_tool(&bin, "rg", "exec sleep 5\n");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn slow_symbol() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = SearchArgs {
+        symbol: "slow_symbol".to_string(),
+        path: vec![repo],
+        timeout_secs: Some(1),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: ToolArgs {
+            trust_repo_tools: true,
+            tool_retries: Some(0),
+            ..ToolArgs::default()
+        },
+        ..Default::default()
+    };
+
+    let summary = search::execute(args).await.expect("search should succeed");
+    assert!(summary.top_hits.is_empty());
+    assert!(!summary.warnings.is_empty());
+    for warning in &summary.warnings {
+        assert_eq!(warning.kind, WarningKind::ToolTimedOut, "{warning:?}");
+        assert_eq!(warning.tool.as_deref(), Some("rg"));
+    }
+
+    let value = serde_json::to_value(&summary).expect("summary should serialize");
+    assert_eq!(value["warnings"][0]["kind"], "tool_timed_out");
+    assert_eq!(value["warnings"][0]["tool"], "rg");
+}
+
+#[tokio::test]
+async fn retries_tools_after_a_timeout() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    let hit = rg_match("src/lib.rs", 1, "pub fn flaky_symbol() {}");
+    // The first invocation hangs past the timeout; every later one answers at once.
+    write_fake_tool(
+        &bin,
+        "rg",
+        &format!(
+            "marker=\"$(dirname \"$0\")/hung\"\nif [ ! -e \"$marker\" ]; then\n  touch \"$marker\"\n  exec sleep 5\nfi\nprintf '%s\\n' '{hit}'\n"
+        ),
+    );
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn flaky_symbol() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools]\nretries = 2\nretry_backoff_ms = 10\n\n[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = SearchArgs {
+        symbol: "flaky_symbol".to_string(),
+        path: vec![repo],
+        timeout_secs: Some(1),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    let summary = search::execute(args).await.expect("search should succeed");
+    assert!(
+        summary.warnings.is_empty(),
+        "a retried timeout is not a warning: {:?}",
+        summary.warnings
+    );
+    assert!(
+        summary
+            .top_hits
+            .iter()
+            .any(|hit| hit.path == "src/lib.rs" && hit.line == 1),
+        "the retry should surface the hit"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn starts_tools_under_resource_limits() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    // Record the address-space limit (KiB) and niceness the fake rg was started with.
+    write_fake_tool(
+        &bin,
+        "rg",
+        &format!(
+            "echo \"$(ulimit -v) $(awk '{{print $19}}' /proc/$$/stat)\" >> \"$(dirname \"$0\")/limits.log\"\n{}",
+            fake_rg_script(&[("src/lib.rs", 1, "pub fn capped_symbol() {}")])
+        ),
+    );
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn capped_symbol() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools]\nnice = 7\nmem_limit = \"1G\"\n\n[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = SearchArgs {
+        symbol: "capped_symbol".to_string(),
+        path: vec![repo],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        // The flag replaces the config file's `mem_limit`; `nice` still comes from the file.
+        tools: ToolArgs {
+            trust_repo_tools: true,
+            tool_mem_limit: Some(512 << 20),
+            ..ToolArgs::default()
+        },
+        ..Default::default()
+    };
+
+    let summary = search::execute(args).await.expect("search should succeed");
+    assert_eq!(summary.top_hits[0].path, "src/lib.rs");
+    let log = std::fs::read_to_string(bin.join("limits.log")).expect("rg should have run");
+    assert!(!log.is_empty());
+    for line in log.lines() {
+        assert_eq!(line, "524288 7");
+    }
+
+    let err = swe_grep::config::ToolsConfig {
+        mem_limit: Some("lots".to_string()),
+        ..Default::default()
+    }
+    .resolve(temp.path(), &ToolArgs::default())
+    .expect_err("an unparsable mem_limit should be rejected");
+    assert!(format!("{err:#}").contains("lots"), "{err:#}");
+
+    // Tools are only capped on request, even inside a cgroup with a memory limit.
+    let limits = swe_grep::tools::limits::ResourceLimits::resolve(None, None);
+    assert_eq!(limits.mem_limit_bytes, None);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn skips_tools_whose_circuit_is_open() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(&bin, &[("src/lib.rs", 1, "pub fn tripped_symbol() {}")]);
+    write_fake_tool(
+        &bin,
+        "ast-grep",
+        "echo run >> \"$(dirname \"$0\")/ast-grep.log\"\necho 'bad config' >&2\nexit 2\n",
+    );
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn tripped_symbol() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools]\ncircuit_failures = 2\ncircuit_cooldown_secs = 60\n\n[tools.rg]\npath = \"../bin/rg\"\n\n[tools.ast_grep]\npath = \"../bin/ast-grep\"\n",
+    )
+    .expect("failed to write config");
+    // A scoped search skips the ripgrep-only fast path, so ast-grep runs.
+    let list = temp.path().join("files.txt");
+    std::fs::write(&list, "src/lib.rs\n").expect("failed to write file list");
+
+    let args = || SearchArgs {
+        symbol: "tripped_symbol".to_string(),
+        path: vec![repo.clone()],
+        language: Some("rust".to_string()),
+        files_from: Some(list.clone()),
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+    let ast_grep_warnings = |summary: &search::SearchSummary| -> Vec<&'static str> {
+        summary
+            .warnings
+            .iter()
+            .filter(|warning| warning.tool.as_deref() == Some("ast-grep"))
+            .map(|warning| warning.kind.as_str())
+            .collect()
+    };
+    let runs = || {
+        std::fs::read_to_string(bin.join("ast-grep.log"))
+            .unwrap_or_default()
+            .lines()
+            .count()
+    };
+
+    for _ in 0..2 {
+        let summary = search::execute(args())
+            .await
+            .expect("search should succeed");
+        assert_eq!(ast_grep_warnings(&summary), ["tool_failed"]);
+    }
+    assert_eq!(runs(), 2);
+
+    let summary = search::execute(args())
+        .await
+        .expect("search should succeed");
+    assert_eq!(ast_grep_warnings(&summary), ["tool_circuit_open"]);
+    assert_eq!(runs(), 2, "an open circuit skips the tool");
+    assert_eq!(summary.top_hits[0].path, "src/lib.rs");
+}
+
+#[tokio::test]
+async fn skips_stages_once_the_latency_target_is_spent() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_tool(&bin, "rg", "exec sleep 5\n");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn budget_symbol() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = SearchArgs {
+        symbol: "budget_symbol".to_string(),
+        path: vec![repo],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        latency_target_ms: Some(200),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: repo_tools(),
+        ..Default::default()
+    };
+
+    let started = std::time::Instant::now();
+    let summary = search::execute(args).await.expect("search should succeed");
+    assert!(
+        started.elapsed() < std::time::Duration::from_secs(2),
+        "the hung probe should be cut off at its budget, took {:?}",
+        started.elapsed()
+    );
+    assert!(summary.top_hits.is_empty());
+    for stage in ["probe", "escalate"] {
+        assert!(
+            summary
+                .budget_exceeded_stages
+                .iter()
+                .any(|name| name == stage),
+            "expected {stage} in {:?}",
+            summary.budget_exceeded_stages
+        );
+    }
+    assert!(
+        !summary
+            .budget_exceeded_stages
+            .iter()
+            .any(|name| name == "ast"),
+        "disabled stages are not reported"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn splits_rga_archive_hits_into_archive_and_inner_path() {
+    let temp = tempdir().expect("failed to create tempdir");
+    write_fake_rg(&temp.path().join("bin"), &[]);
+    let rga_bin = temp.path().join("rga-bin");
+    write_fake_tool(
+        &rga_bin,
+        "rga",
+        &fake_rg_script(&[(
+            "./docs/manual.zip/guide/intro.md",
+            3,
+            "call archived_symbol() first",
+        )]),
+    );
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("docs")).expect("failed to create repo");
+    std::fs::write(repo.join("docs/manual.zip"), b"PK\x03\x04").expect("failed to write archive");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\n\n[tools.rga]\npath = \"../rga-bin/rga\"\n",
+    )
+    .expect("failed to write config");
+
+    let args = SearchArgs {
+        symbol: "archived_symbol".to_string(),
+        path: vec![repo],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        body: true,
+        enable_rga: true,
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: ToolArgs {
+            trust_repo_tools: true,
+            rga_adapters: vec!["zip".to_string(), "pdfpages".to_string()],
+            ..ToolArgs::default()
+        },
+        ..Default::default()
+    };
+    let summary = search::execute(args).await.expect("search should succeed");
+
+    let hit = summary
+        .top_hits
+        .iter()
+        .find(|hit| hit.origin == "rga")
+        .expect("rga hit should be reported");
+    assert_eq!(hit.path, "docs/manual.zip/guide/intro.md");
+    assert_eq!(hit.archive_path.as_deref(), Some("docs/manual.zip"));
+    assert_eq!(hit.inner_path.as_deref(), Some("guide/intro.md"));
+    assert_eq!(hit.line, 3);
+    assert!(hit.expanded_snippet.is_none());
+    assert!(hit.body.is_none());
+
+    let rga_args = std::fs::read_to_string(rga_bin.join("rg-args.log")).expect("rga should run");
+    assert!(
+        rga_args
+            .lines()
+            .any(|arg| arg == "--rga-adapters=zip,pdfpages"),
+        "{rga_args}"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn runs_configured_tool_binaries_with_extra_args() {
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    write_fake_rg(&bin, &[("src/lib.rs", 1, "pub fn pinned_tool() {}")]);
+
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn pinned_tool() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"../bin/rg\"\nextra_args = [\"--no-ignore-vcs\"]\n",
+    )
+    .expect("failed to write config");
+
+    let args = |tools: ToolArgs| SearchArgs {
+        symbol: "pinned_tool".to_string(),
+        path: vec![repo.clone()],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools,
+        ..Default::default()
+    };
+
+    let summary = search::execute(args(repo_tools()))
+        .await
+        .expect("search should use the configured rg");
+    assert!(summary.top_hits.iter().any(|hit| hit.path == "src/lib.rs"));
+    let logged = std::fs::read_to_string(bin.join("rg-args.log")).expect("fake rg should run");
+    assert_eq!(logged.lines().next(), Some("--no-ignore-vcs"));
+
+    let missing = temp.path().join("missing/rg");
+    let err = match search::execute(args(ToolArgs {
+        rg_path: Some(missing.clone()),
+        ..ToolArgs::default()
+    }))
+    .await
+    {
+        Ok(_) => panic!("a missing --rg-path should be rejected at startup"),
+        Err(err) => err,
+    };
+    let message = format!("{err:#}");
+    assert!(
+        message.contains("invalid rg tool configuration"),
+        "{message}"
+    );
+    assert!(
+        message.contains(&missing.display().to_string()),
+        "{message}"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn ignores_repository_tool_settings_unless_trusted() {
+    let temp = tempdir().expect("failed to create tempdir");
+    // The repository names its own "rg", e.g. one that runs `--pre` scripts it ships.
+    let repo_bin = temp.path().join("repo/bin");
+    write_fake_rg(&repo_bin, &[("src/lib.rs", 1, "pub fn pinned_tool() {}")]);
+    let operator_bin = temp.path().join("bin");
+    write_fake_rg(
+        &operator_bin,
+        &[("src/lib.rs", 1, "pub fn pinned_tool() {}")],
+    );
+
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn pinned_tool() {}\n")
+        .expect("failed to write source");
+    std::fs::write(
+        repo.join(".swe-grep.toml"),
+        "[tools.rg]\npath = \"bin/rg\"\nextra_args = [\"--pre=./bin/rg\"]\n\n\
+         [tools.fd]\npath = \"bin/rg\"\n",
+    )
+    .expect("failed to write config");
+
+    let config = RepoConfig::load(&repo).expect("config should load").tools;
+    let untrusted = config
+        .clone()
+        .resolve(&repo, &ToolArgs::default())
+        .expect("untrusted config should resolve");
+    assert_eq!(untrusted.rg.path, None);
+    assert!(untrusted.rg.extra_args.is_empty());
+    assert_eq!(untrusted.fd.path, None);
+    let trusted = config
+        .resolve(&repo, &repo_tools())
+        .expect("trusted config should resolve");
+    assert!(
+        trusted
+            .rg
+            .path
+            .is_some_and(|path| path.ends_with("repo/bin/rg"))
+    );
+    assert_eq!(trusted.rg.extra_args, ["--pre=./bin/rg"]);
+
+    let summary = search::execute(SearchArgs {
+        symbol: "pinned_tool".to_string(),
+        path: vec![repo.clone()],
+        timeout_secs: Some(3),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: ToolArgs {
+            rg_path: Some(operator_bin.join("rg")),
+            ..ToolArgs::default()
+        },
+        ..Default::default()
+    })
+    .await
+    .expect("search should use the operator's rg");
+    assert!(summary.top_hits.iter().any(|hit| hit.path == "src/lib.rs"));
+    let logged =
+        std::fs::read_to_string(operator_bin.join("rg-args.log")).expect("operator rg should run");
+    assert!(
+        !logged.lines().any(|arg| arg.starts_with("--pre")),
+        "the repository's extra_args are dropped: {logged}"
+    );
+    assert!(
+        !repo_bin.join("rg-args.log").exists(),
+        "the repository's rg never runs"
+    );
+}
+
+#[tokio::test]
+async fn runs_hermetically_against_mocked_tools() {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use swe_grep::tools::backend::{MockBackend, MockResponse};
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(
+        repo.join("src/session.rs"),
+        "pub fn refresh_session() {}\n\nfn caller() {\n    refresh_session();\n}\n",
+    )
+    .expect("failed to write source");
+    let args = |timeout_secs| SearchArgs {
+        symbol: "refresh_session".to_string(),
+        path: vec![repo.clone()],
+        language: Some("rust".to_string()),
+        timeout_secs: Some(timeout_secs),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        tools: ToolArgs {
+            tool_retries: Some(0),
+            ..ToolArgs::default()
+        },
+        deterministic: true,
+        ..Default::default()
+    };
+
+    // Malformed JSON lines are skipped.
+    let stdout = [
+        (1, "pub fn refresh_session() {}"),
+        (4, "    refresh_session();"),
+    ]
+    .iter()
+    .map(|(line, text)| rg_match("src/session.rs", *line, text).to_string())
+    .collect::<Vec<_>>()
+    .join("\n{\"type\":\"match\",\"data\":\n");
+    let backend = Arc::new(MockBackend::new().with_response("rg", MockResponse::stdout(stdout)));
+    let summary = search::execute_with_backend(args(5), backend.clone())
+        .await
+        .expect("mocked search should succeed");
+    let hits: Vec<(&str, usize)> = summary
+        .top_hits
+        .iter()
+        .map(|hit| (hit.path.as_str(), hit.line))
+        .collect();
+    assert_eq!(hits, [("src/session.rs", 1), ("src/session.rs", 4)]);
+    let invocations = backend.invocations();
+    assert_eq!(invocations.len(), 1, "{invocations:?}");
+    assert_eq!(invocations[0].tool, "rg");
+    assert!(invocations[0].args.iter().any(|arg| arg == "--json"));
+    assert_eq!(
+        invocations[0].cwd,
+        Some(search::canonicalize_path(&repo).expect("repo resolves"))
+    );
+
+    // A non-zero exit surfaces the tool's stderr; tools without a response read as missing.
+    let backend = Arc::new(
+        MockBackend::new().with_response(
+            "rg",
+            MockResponse::stdout("")
+                .with_stderr("rg: simulated failure")
+                .with_exit_code(2),
+        ),
+    );
+    let summary = search::execute_with_backend(args(5), backend)
+        .await
+        .expect("a failing tool only warns");
+    assert!(summary.top_hits.is_empty());
+    assert!(
+        summary.warnings.iter().any(|warning| {
+            warning.kind == WarningKind::ToolFailed
+                && warning.tool.as_deref() == Some("rg")
+                && warning.message.contains("rg: simulated failure")
+        }),
+        "{:?}",
+        summary.warnings
+    );
+    assert!(
+        summary.warnings.iter().any(|warning| {
+            warning.kind == WarningKind::ToolMissing && warning.tool.as_deref() == Some("ast-grep")
+        }),
+        "{:?}",
+        summary.warnings
+    );
+
+    // A tool that outlives the timeout is reported as timed out.
+    let backend = Arc::new(
+        MockBackend::new().with_response(
+            "rg",
+            MockResponse::rg_matches(&[("src/session.rs", 1, "pub fn refresh_session() {}")])
+                .with_delay(Duration::from_secs(30)),
+        ),
+    );
+    let summary = search::execute_with_backend(args(1), backend)
+        .await
+        .expect("a timed-out tool only warns");
+    assert!(
+        summary.warnings.iter().any(|warning| {
+            warning.kind == WarningKind::ToolTimedOut && warning.tool.as_deref() == Some("rg")
+        }),
+        "{:?}",
+        summary.warnings
+    );
+}
+
+#[cfg(feature = "chaos")]
+#[tokio::test]
+async fn injects_chaos_into_tool_runs() {
+    use std::sync::Arc;
+
+    use swe_grep::tools::backend::{MockBackend, MockResponse};
+    use swe_grep::tools::chaos::ChaosSpec;
+
+    for invalid in [
+        "grep:delay=1s",
+        "rg:fail=2",
+        "rg",
+        "rg:delay=soon",
+        "rg:jitter=5ms",
+    ] {
+        assert!(
+            ChaosSpec::parse(invalid).is_err(),
+            "{invalid} should be rejected"
+        );
+    }
+    let spec = ChaosSpec::parse("rg:delay=500ms,ast-grep:fail=0.2,rg:fail=0").unwrap();
+    let rg = spec.fault("rg").expect("rg has a fault");
+    assert_eq!(rg.delay, std::time::Duration::from_millis(500));
+    assert_eq!(rg.fail, 0.0);
+    assert_eq!(spec.fault("ast-grep").map(|fault| fault.fail), Some(0.2));
+    assert!(spec.fault("fd").is_none());
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn refresh_session() {}\n")
+        .expect("failed to write source");
+    let args = |chaos: &str, timeout_secs| SearchArgs {
+        symbol: "refresh_session".to_string(),
+        path: vec![repo.clone()],
+        language: Some("rust".to_string()),
+        timeout_secs: Some(timeout_secs),
+        max_matches: Some(20),
+        cache_dir: Some(temp.path().join("cache")),
+        use_fd: false,
+        use_ast_grep: false,
+        tools: ToolArgs {
+            tool_retries: Some(1),
+            tool_retry_backoff_ms: Some(1),
+            chaos: Some(ChaosSpec::parse(chaos).unwrap()),
+            ..ToolArgs::default()
+        },
+        deterministic: true,
+        ..Default::default()
+    };
+    let backend = || {
+        Arc::new(MockBackend::new().with_response(
+            "rg",
+            MockResponse::rg_matches(&[("src/lib.rs", 1, "pub fn refresh_session() {}")]),
+        ))
+    };
+
+    // Injected failures are retried like spawn errors and end up as warnings.
+    let mock = backend();
+    let summary = search::execute_with_backend(args("rg:fail=1", 5), mock.clone())
+        .await
+        .expect("chaos only warns");
+    assert!(summary.top_hits.is_empty());
+    assert!(
+        summary.warnings.iter().any(|warning| {
+            warning.kind == WarningKind::ToolFailed && warning.tool.as_deref() == Some("rg")
+        }),
+        "{:?}",
+        summary.warnings
+    );
+    assert!(mock.invocations().is_empty(), "failed runs never start");
+
+    // A delay past the timeout reads as a hung tool; a shorter one only slows the search.
+    let summary = search::execute_with_backend(args("rg:delay=2s", 1), backend())
+        .await
+        .expect("chaos only warns");
+    assert!(
+        summary
+            .warnings
+            .iter()
+            .any(|warning| warning.kind == WarningKind::ToolTimedOut),
+        "{:?}",
+        summary.warnings
+    );
+    let started = std::time::Instant::now();
+    let summary = search::execute_with_backend(args("rg:delay=300ms", 5), backend())
+        .await
+        .expect("delayed search should succeed");
+    assert_eq!(summary.top_hits.len(), 1);
+    assert!(started.elapsed() >= std::time::Duration::from_millis(300));
+}
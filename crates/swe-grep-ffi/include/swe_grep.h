@@ -0,0 +1,34 @@
+/*
+ * C interface to swe-grep, for runtimes (Go, Swift, ...) that embed the search engine instead
+ * of talking to `swe-grep serve`.
+ *
+ * Link against the `swe_grep_ffi` shared library built by `cargo build -p swe-grep-ffi`.
+ */
+#ifndef SWE_GREP_H
+#define SWE_GREP_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Run one search and block until it finishes.
+ *
+ * `request_json` is a NUL-terminated UTF-8 JSON object with the fields of the `POST /search`
+ * body, e.g. {"symbol":"login_user","root":"/repo"}; `root` defaults to the process's working
+ * directory. The result is a NUL-terminated JSON object, either {"summary":{...}} or
+ * {"error":{"message":"...","fields":[...]}} where `fields` lists invalid request fields when
+ * there are any. It is never NULL and must be released with swegrep_free_string.
+ *
+ * Safe to call from several threads at once.
+ */
+char *swegrep_search(const char *request_json);
+
+/* Release a string returned by swegrep_search; NULL is ignored. */
+void swegrep_free_string(char *response);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SWE_GREP_H */
@@ -0,0 +1,108 @@
+//! C ABI for embedding swe-grep in other runtimes without running `swe-grep serve`.
+//!
+//! `swegrep_search` takes the JSON body of `POST /search` and returns the result as JSON;
+//! `swegrep_free_string` releases it. `include/swe_grep.h` declares both.
+
+use std::collections::HashMap;
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use anyhow::{Context, Result, anyhow, bail};
+use serde_json::{Value, json};
+use swe_grep::fields::FieldSelection;
+use swe_grep::search;
+use swe_grep::service::http::HttpSearchRequest;
+use swe_grep::service::server::{SearchExecutor, SearchInput, ServeConfig};
+use swe_grep::validate::ValidationError;
+use tokio::runtime::Runtime;
+
+/// Run one search and return `{"summary": ...}` or `{"error": {"message": ...}}` as a JSON
+/// string, never NULL. Release the result with [`swegrep_free_string`].
+///
+/// # Safety
+///
+/// `request_json` must be NULL or point to a NUL-terminated string that stays valid for the
+/// duration of the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn swegrep_search(request_json: *const c_char) -> *mut c_char {
+    let response = panic::catch_unwind(AssertUnwindSafe(|| {
+        if request_json.is_null() {
+            bail!("request is NULL");
+        }
+        // SAFETY: not NULL, and the caller guarantees a NUL-terminated string.
+        let request = unsafe { CStr::from_ptr(request_json) }
+            .to_str()
+            .context("request is not valid UTF-8")?;
+        run_search(request)
+    }))
+    .unwrap_or_else(|_| Err(anyhow!("search panicked")))
+    .unwrap_or_else(|err| error_response(&err));
+    // serde_json escapes NUL as `\u0000`, so the text never contains one.
+    CString::new(response.to_string())
+        .expect("JSON text contains no NUL bytes")
+        .into_raw()
+}
+
+/// Release a string returned by [`swegrep_search`]; NULL is ignored.
+///
+/// # Safety
+///
+/// `response` must be NULL or a pointer returned by `swegrep_search` that has not been released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn swegrep_free_string(response: *mut c_char) {
+    if !response.is_null() {
+        // SAFETY: the pointer came from `CString::into_raw` in `swegrep_search`.
+        drop(unsafe { CString::from_raw(response) });
+    }
+}
+
+fn run_search(request: &str) -> Result<Value> {
+    let request: HttpSearchRequest =
+        serde_json::from_str(request).context("invalid search request")?;
+    let fields = FieldSelection::parse(&request.fields)?;
+    let root = match &request.root {
+        Some(root) => PathBuf::from(root),
+        None => std::env::current_dir().context("failed to resolve current directory")?,
+    };
+    let root = search::canonicalize_path(&root)
+        .with_context(|| format!("failed to canonicalize search root: {}", root.display()))?;
+    let executor = executor(root);
+    let mut input: SearchInput = request.into();
+    // The executor is already rooted there.
+    input.root = None;
+    let summary = runtime()?.block_on(executor.execute(input))?;
+    Ok(json!({ "summary": fields.to_value(&summary)? }))
+}
+
+fn error_response(err: &anyhow::Error) -> Value {
+    let mut body = json!({ "message": format!("{err:#}") });
+    if let Some(invalid) = err.downcast_ref::<ValidationError>() {
+        body["fields"] = json!(invalid.fields);
+    }
+    json!({ "error": body })
+}
+
+/// Executor for `root`, shared by every call so concurrent searches of one repository queue for
+/// its slots (one per CPU) like they would on a server.
+fn executor(root: PathBuf) -> Arc<SearchExecutor> {
+    static EXECUTORS: OnceLock<Mutex<HashMap<PathBuf, Arc<SearchExecutor>>>> = OnceLock::new();
+    let mut executors = EXECUTORS.get_or_init(Default::default).lock().unwrap();
+    executors
+        .entry(root.clone())
+        .or_insert_with(|| Arc::new(SearchExecutor::new(ServeConfig::embedded(root))))
+        .clone()
+}
+
+fn runtime() -> Result<&'static Runtime> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .context("failed to start the search runtime")?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
@@ -0,0 +1,78 @@
+use std::ffi::{CStr, CString};
+
+use serde_json::Value;
+use swe_grep_ffi::{swegrep_free_string, swegrep_search};
+use tempfile::tempdir;
+
+fn search(request: &Value) -> Value {
+    let request = CString::new(request.to_string()).expect("request has no NUL bytes");
+    // SAFETY: a NUL-terminated request, and the response is released exactly once.
+    unsafe {
+        let response = swegrep_search(request.as_ptr());
+        let text = CStr::from_ptr(response)
+            .to_str()
+            .expect("response is UTF-8")
+            .to_string();
+        swegrep_free_string(response);
+        serde_json::from_str(&text).expect("response is JSON")
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn searches_through_the_c_abi() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp = tempdir().expect("failed to create tempdir");
+    let bin = temp.path().join("bin");
+    std::fs::create_dir_all(&bin).expect("failed to create bin dir");
+    let message = serde_json::json!({
+        "type": "match",
+        "data": {
+            "path": { "text": "src/lib.rs" },
+            "lines": { "text": "pub fn embedded_lookup() {}\n" },
+            "line_number": 1,
+        },
+    });
+    std::fs::write(
+        bin.join("rg"),
+        format!("#!/bin/sh\ncat <<'EOF'\n{message}\nEOF\n"),
+    )
+    .expect("failed to write fake rg");
+    std::fs::set_permissions(bin.join("rg"), std::fs::Permissions::from_mode(0o755))
+        .expect("failed to mark fake rg executable");
+    let repo = temp.path().join("repo");
+    std::fs::create_dir_all(repo.join("src")).expect("failed to create repo");
+    std::fs::write(repo.join("src/lib.rs"), "pub fn embedded_lookup() {}\n")
+        .expect("failed to write source");
+    // Embedded searches ignore the repository's tool paths, so the fake rg goes on PATH.
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let path = std::env::join_paths(std::iter::once(bin).chain(std::env::split_paths(&path)))
+        .expect("PATH entries are joinable");
+    // SAFETY: this is the only test in the binary, so no other thread reads the environment.
+    unsafe { std::env::set_var("PATH", path) };
+
+    let response = search(&serde_json::json!({
+        "symbol": "embedded_lookup",
+        "root": repo,
+        "fields": ["path", "line"],
+    }));
+    let hits = response["summary"]["top_hits"]
+        .as_array()
+        .unwrap_or_else(|| panic!("expected hits: {response}"));
+    assert_eq!(hits[0]["path"], "src/lib.rs");
+    assert_eq!(hits[0]["line"], 1);
+    assert!(hits[0].get("snippet").is_none(), "{response}");
+
+    let response = search(&serde_json::json!({ "symbol": " ", "root": repo }));
+    assert_eq!(response["error"]["message"], "symbol is required");
+
+    // SAFETY: NULL is accepted and the response is released once.
+    let response = unsafe {
+        let response = swegrep_search(std::ptr::null());
+        let text = CStr::from_ptr(response).to_string_lossy().into_owned();
+        swegrep_free_string(response);
+        text
+    };
+    assert!(response.contains("request is NULL"), "{response}");
+}
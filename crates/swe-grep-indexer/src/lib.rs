@@ -1,15 +1,50 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use ignore::WalkBuilder;
+use rayon::prelude::*;
 use tantivy::collector::TopDocs;
-use tantivy::directory::MmapDirectory;
-use tantivy::schema::{STORED, Schema, SchemaBuilder, TEXT};
+use tantivy::directory::error::LockError;
+use tantivy::directory::{Directory, INDEX_WRITER_LOCK, META_LOCK, MmapDirectory};
+use tantivy::schema::{
+    IndexRecordOption, STORED, Schema, SchemaBuilder, TextFieldIndexing, TextOptions,
+};
 use tantivy::{Index, IndexReader, ReloadPolicy};
 use tokio::task;
 
+mod notebook;
+mod symbol_store;
+mod tokenizer;
+
+pub use symbol_store::SymbolStore;
+
 const INDEX_FILENAME: &str = "meta.json";
+/// Tantivy's list of the files it manages in an index directory.
+const MANAGED_FILENAME: &str = ".managed.json";
+/// Sidecar holding the identifier dictionary, one `identifier<TAB>path` line per symbol.
+pub const IDENTIFIERS_FILENAME: &str = "identifiers.tsv";
+/// Present while a build runs; one found on open means that build never finished.
+const BUILD_MARKER_FILENAME: &str = ".swe-grep-building";
+/// Subdirectory of the index directory that a broken index is moved into before the rebuild.
+pub const QUARANTINE_DIRNAME: &str = "quarantine";
+/// Version of the index layout (schema, tokenizer, documents), stored as the payload of every
+/// commit. Bump it whenever the way files are indexed changes, so older indexes are rebuilt.
+pub const INDEX_FORMAT_VERSION: u32 = 1;
+const FORMAT_PAYLOAD_PREFIX: &str = "swe-grep-index-format:";
+/// Extensions of tantivy's segment files.
+const SEGMENT_EXTENSIONS: &[&str] = &["idx", "pos", "term", "store", "fast", "fieldnorm", "del"];
+const MIN_IDENTIFIER_LENGTH: usize = 3;
+/// Most indexing threads a build uses.
+const MAX_WRITER_THREADS: usize = 8;
+/// Writer memory budget per indexing thread.
+const WRITER_MEMORY_PER_THREAD: usize = 25_000_000;
+/// Shortest interval between two progress reports of a build.
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
 
 #[derive(Clone)]
 pub struct TantivyIndex {
@@ -18,9 +53,22 @@ pub struct TantivyIndex {
     reader: IndexReader,
     query_parser: tantivy::query::QueryParser,
     path_field: tantivy::schema::Field,
+    line_field: tantivy::schema::Field,
     #[allow(dead_code)]
     body_field: tantivy::schema::Field,
     root: PathBuf,
+    symbols: Arc<SymbolDictionary>,
+    recovery: Option<String>,
+}
+
+/// Line of an indexed file whose text matched a query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexMatch {
+    /// The file, relative to the indexed root.
+    pub path: PathBuf,
+    /// 1-based line number; for notebooks it counts within their code cells, joined by blank
+    /// lines, rather than the JSON file.
+    pub line: usize,
 }
 
 #[derive(Clone, Debug)]
@@ -30,8 +78,57 @@ pub struct IndexConfig {
     pub extensions: Option<Vec<String>>,
 }
 
+/// How far an index build has got, reported about once a second and when it finishes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IndexProgress {
+    /// Files read and added to the index so far.
+    pub files_done: usize,
+    /// Files the build covers.
+    pub files_total: usize,
+    pub elapsed: Duration,
+}
+
+impl IndexProgress {
+    pub fn files_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.files_done as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Time left at the rate so far; `None` until a file is done.
+    pub fn eta(&self) -> Option<Duration> {
+        let rate = self.files_per_sec();
+        (rate > 0.0).then(|| {
+            Duration::from_secs_f64(self.files_total.saturating_sub(self.files_done) as f64 / rate)
+        })
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.files_done >= self.files_total
+    }
+}
+
+/// Receives the progress of an index build; called from the build's worker threads.
+pub type ProgressCallback = Arc<dyn Fn(&IndexProgress) + Send + Sync>;
+
 impl TantivyIndex {
+    /// Open the index in `config.index_dir`, building it first when it is missing or outdated.
+    /// An index that fails its health check (an interrupted build, an older schema, metadata
+    /// or segments that cannot be read) is moved to [`QUARANTINE_DIRNAME`] and rebuilt; see
+    /// [`TantivyIndex::recovery`]. Build progress is logged at info level.
     pub async fn open_or_build(config: IndexConfig) -> Result<Self> {
+        Self::open_or_build_with_progress(config, Arc::new(log_progress)).await
+    }
+
+    /// [`TantivyIndex::open_or_build`], reporting build progress to `progress` instead of the
+    /// log. Nothing is reported when the index is already up to date.
+    pub async fn open_or_build_with_progress(
+        config: IndexConfig,
+        progress: ProgressCallback,
+    ) -> Result<Self> {
         let IndexConfig {
             root,
             index_dir,
@@ -43,13 +140,35 @@ impl TantivyIndex {
             .with_context(|| format!("failed to create index directory {}", index_dir.display()))?;
         let directory = MmapDirectory::open(&index_dir)
             .with_context(|| format!("failed to open index directory {}", index_dir.display()))?;
+        let recovery = health_check(&directory, &index_dir, &schema)?;
+        if let Some(reason) = &recovery {
+            tracing::warn!(
+                index_dir = %index_dir.display(),
+                reason = %reason,
+                "quarantining broken index and rebuilding it"
+            );
+            quarantine_index(&index_dir)?;
+        }
+        // Checked before opening, which writes the metadata of a new index.
+        let needs_build = !index_dir.join(INDEX_FILENAME).exists();
         let index = Index::open_or_create(directory, schema.clone())
             .with_context(|| format!("failed to open/create index at {}", index_dir.display()))?;
+        tokenizer::register_for_indexing(index.tokenizers());
 
-        let needs_build = !index_dir.join(INDEX_FILENAME).exists();
         if needs_build {
-            build_index(index.clone(), &root, extensions.clone()).await?;
+            build_index(
+                index.clone(),
+                &root,
+                &index_dir,
+                extensions.clone(),
+                progress,
+            )
+            .await?;
+        } else if !index_dir.join(IDENTIFIERS_FILENAME).exists() {
+            // Indexes built before the dictionary existed get the sidecar on first open.
+            build_dictionary(&root, &index_dir, extensions.clone()).await?;
         }
+        let symbols = SymbolDictionary::load(&index_dir)?;
 
         let reader = index
             .reader_builder()
@@ -62,23 +181,54 @@ impl TantivyIndex {
             .schema()
             .get_field("path")
             .context("path field missing")?;
+        let line_field = index
+            .schema()
+            .get_field("line")
+            .context("line field missing")?;
         let body_field = index
             .schema()
             .get_field("body")
             .context("body field missing")?;
-        let query_parser = tantivy::query::QueryParser::for_index(&index, vec![body_field]);
+        let query_parser = tantivy::query::QueryParser::new(
+            index.schema(),
+            vec![body_field],
+            tokenizer::query_tokenizers(),
+        );
 
         Ok(Self {
             index,
             reader,
             query_parser,
             path_field,
+            line_field,
             body_field,
             root,
+            symbols: Arc::new(symbols),
+            recovery,
         })
     }
 
-    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<PathBuf>> {
+    /// Why the index was found broken, moved to [`QUARANTINE_DIRNAME`], and rebuilt when it
+    /// was opened; `None` when it opened cleanly or was built for the first time.
+    pub fn recovery(&self) -> Option<&str> {
+        self.recovery.as_deref()
+    }
+
+    /// Identifiers extracted while the index was built.
+    pub fn symbols(&self) -> &SymbolDictionary {
+        &self.symbols
+    }
+
+    /// Number of documents (indexed lines) in the index.
+    pub fn num_docs(&self) -> u64 {
+        self.reader.searcher().num_docs()
+    }
+
+    /// Lines whose text matches `query`, best first; every line of a file is its own document.
+    /// Text is lowercased when tokenized, so matching ignores case; callers check the lines to
+    /// apply their own case rule. Identifiers are split into their camelCase and snake_case
+    /// words, so `UserProfile` also finds `user_profile` and `getUserProfileById`.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<IndexMatch>> {
         if query.trim().is_empty() {
             return Ok(Vec::new());
         }
@@ -87,6 +237,7 @@ impl TantivyIndex {
         let parser = self.query_parser.clone();
         let reader = self.reader.clone();
         let path_field = self.path_field;
+        let line_field = self.line_field;
         let root = self.root.clone();
 
         task::spawn_blocking(move || {
@@ -101,13 +252,20 @@ impl TantivyIndex {
             let mut results = Vec::new();
             for (_score, doc_address) in top_docs {
                 let retrieved = searcher.doc(doc_address)?;
-                if let Some(value) = retrieved.get_first(path_field) {
-                    let text = value.as_text().unwrap_or_default();
-                    let path = PathBuf::from(text);
-                    results.push(normalize_path(&root, &path));
+                let path = retrieved
+                    .get_first(path_field)
+                    .and_then(|value| value.as_text());
+                let line = retrieved
+                    .get_first(line_field)
+                    .and_then(|value| value.as_u64());
+                if let (Some(path), Some(line)) = (path, line) {
+                    results.push(IndexMatch {
+                        path: normalize_path(&root, Path::new(path)),
+                        line: usize::try_from(line).unwrap_or(usize::MAX),
+                    });
                 }
             }
-            Ok::<Vec<PathBuf>, anyhow::Error>(results)
+            Ok::<Vec<IndexMatch>, anyhow::Error>(results)
         })
         .await
         .context("tantivy search task cancelled")?
@@ -117,75 +275,460 @@ impl TantivyIndex {
 fn build_schema() -> Schema {
     let mut builder = SchemaBuilder::default();
     builder.add_text_field("path", STORED);
-    builder.add_text_field("body", TEXT);
+    builder.add_u64_field("line", STORED);
+    let body = TextFieldIndexing::default()
+        .set_tokenizer(tokenizer::IDENTIFIER_TOKENIZER)
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+    builder.add_text_field("body", TextOptions::default().set_indexing_options(body));
     builder.build()
 }
 
-async fn build_index(index: Index, root: &Path, extensions: Option<Vec<String>>) -> Result<()> {
+/// Why the index in `index_dir` cannot be used as it is; `None` when it is healthy or does not
+/// exist yet. Fails only when another process is building the index right now.
+fn health_check(
+    directory: &MmapDirectory,
+    index_dir: &Path,
+    schema: &Schema,
+) -> Result<Option<String>> {
+    if index_dir.join(BUILD_MARKER_FILENAME).exists() {
+        // A live build holds the writer lock; a crashed one released it with its process.
+        return match directory.acquire_lock(&INDEX_WRITER_LOCK) {
+            Ok(_lock) => Ok(Some("an earlier index build did not finish".to_string())),
+            Err(LockError::LockBusy) => anyhow::bail!(
+                "the index in {} is being built by another process",
+                index_dir.display()
+            ),
+            Err(LockError::IoError(err)) => Err(err).with_context(|| {
+                format!("failed to check the index lock in {}", index_dir.display())
+            }),
+        };
+    }
+    if !index_dir.join(INDEX_FILENAME).exists() {
+        return Ok(None);
+    }
+    let index = match Index::open(directory.clone()) {
+        Ok(index) => index,
+        Err(err) => return Ok(Some(format!("the index metadata cannot be read: {err}"))),
+    };
+    if index.schema() != *schema {
+        return Ok(Some("the index was built by an older version".to_string()));
+    }
+    let payload = match index.load_metas() {
+        Ok(metas) => metas.payload,
+        Err(err) => return Ok(Some(format!("the index metadata cannot be read: {err}"))),
+    };
+    let format = payload
+        .as_deref()
+        .and_then(|payload| payload.strip_prefix(FORMAT_PAYLOAD_PREFIX))
+        .and_then(|version| version.parse::<u32>().ok());
+    if format != Some(INDEX_FORMAT_VERSION) {
+        let age = if format.is_some_and(|version| version > INDEX_FORMAT_VERSION) {
+            "a newer"
+        } else {
+            "an older"
+        };
+        let found = format.map_or("no format version".to_string(), |version| {
+            format!("format {version}")
+        });
+        return Ok(Some(format!(
+            "the index was built by {age} version ({found}; this build uses format {INDEX_FORMAT_VERSION})"
+        )));
+    }
+    // Opening a reader loads every segment, so missing or truncated files show up here.
+    match index.reader() {
+        Ok(_) => Ok(None),
+        Err(err) => Ok(Some(format!("the index segments cannot be read: {err}"))),
+    }
+}
+
+/// Move the index files in `index_dir` (metadata, segments, locks, the identifier dictionary,
+/// and any build marker) into its [`QUARANTINE_DIRNAME`] subdirectory, replacing what an
+/// earlier recovery left there. The symbol store and anything else is left in place.
+fn quarantine_index(index_dir: &Path) -> Result<()> {
+    let quarantine = index_dir.join(QUARANTINE_DIRNAME);
+    if quarantine.exists() {
+        fs::remove_dir_all(&quarantine).with_context(|| {
+            format!("failed to clear index quarantine {}", quarantine.display())
+        })?;
+    }
+    fs::create_dir_all(&quarantine)
+        .with_context(|| format!("failed to create index quarantine {}", quarantine.display()))?;
+    let lock_files = [&INDEX_WRITER_LOCK, &META_LOCK].map(|lock| lock.filepath.as_os_str());
+    let entries = fs::read_dir(index_dir)
+        .with_context(|| format!("failed to read index directory {}", index_dir.display()))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let is_index_file = [
+            INDEX_FILENAME,
+            MANAGED_FILENAME,
+            IDENTIFIERS_FILENAME,
+            BUILD_MARKER_FILENAME,
+        ]
+        .iter()
+        .any(|known| name == *known)
+            || lock_files.contains(&name.as_os_str())
+            || path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| SEGMENT_EXTENSIONS.contains(&ext));
+        if is_index_file && path.is_file() {
+            fs::rename(&path, quarantine.join(&name))
+                .with_context(|| format!("failed to quarantine index file {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete the index in `index_dir` so the next open rebuilds it. Only the index's own files
+/// are removed; a directory without an index is left as it is.
+pub fn remove_index(index_dir: &Path) -> Result<()> {
+    if index_dir.is_dir() {
+        remove_index_files(index_dir)?;
+    }
+    Ok(())
+}
+
+/// Delete the index in `index_dir`: the files tantivy manages there, its metadata, the
+/// identifier dictionary, and any build marker. Anything else in the directory is left alone.
+fn remove_index_files(index_dir: &Path) -> Result<()> {
+    let managed: Vec<PathBuf> = fs::read_to_string(index_dir.join(MANAGED_FILENAME))
+        .ok()
+        .and_then(|raw| serde_json::from_str(&raw).ok())
+        .unwrap_or_default();
+    let files = managed.iter().map(PathBuf::as_path).chain(
+        [
+            MANAGED_FILENAME,
+            INDEX_FILENAME,
+            IDENTIFIERS_FILENAME,
+            BUILD_MARKER_FILENAME,
+        ]
+        .map(Path::new),
+    );
+    for file in files {
+        let path = index_dir.join(file);
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to remove index file {}", path.display()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Index every source file under `root`. Files are read and turned into documents on the
+/// rayon pool, and tantivy's writer threads index them as they arrive.
+async fn build_index(
+    index: Index,
+    root: &Path,
+    index_dir: &Path,
+    extensions: Option<Vec<String>>,
+    progress: ProgressCallback,
+) -> Result<()> {
     let root = root.to_path_buf();
+    let index_dir = index_dir.to_path_buf();
     task::spawn_blocking(move || {
+        let threads = std::thread::available_parallelism()
+            .map_or(1, usize::from)
+            .min(MAX_WRITER_THREADS);
         let mut writer = index
-            .writer(50_000_000)
+            .writer_with_num_threads(threads, threads * WRITER_MEMORY_PER_THREAD)
             .context("failed to create index writer")?;
+        // Written once the writer holds its lock, so other processes can tell this build from
+        // one that crashed.
+        let marker = index_dir.join(BUILD_MARKER_FILENAME);
+        fs::write(&marker, b"")
+            .with_context(|| format!("failed to write build marker {}", marker.display()))?;
         let schema = index.schema();
         let path_field = schema.get_field("path").context("path field missing")?;
+        let line_field = schema.get_field("line").context("line field missing")?;
         let body_field = schema.get_field("body").context("body field missing")?;
 
-        let mut walker = WalkBuilder::new(&root);
-        walker
-            .hidden(false)
-            .follow_links(false)
-            .standard_filters(true);
+        let files = source_files(&root, extensions);
+        let reporter = ProgressReporter::new(files.len(), progress);
+        let writer_ref = &writer;
+        let per_file: Vec<BTreeMap<String, String>> = files
+            .par_iter()
+            .map(|path| {
+                let mut identifiers = BTreeMap::new();
+                if let Some(content) = read_source(path) {
+                    extract_identifiers(&root, path, &content, &mut identifiers);
+                    let path_text = path.display().to_string();
+                    for (number, line) in content.lines().enumerate() {
+                        if line.trim().is_empty() {
+                            continue;
+                        }
+                        let mut doc = tantivy::Document::new();
+                        doc.add_text(path_field, &path_text);
+                        doc.add_u64(line_field, number as u64 + 1);
+                        doc.add_text(body_field, line);
+                        if let Err(err) = writer_ref.add_document(doc) {
+                            tracing::warn!(error = %err, "failed to add document to index");
+                        }
+                    }
+                }
+                reporter.file_done();
+                identifiers
+            })
+            .collect();
 
-        let exts = extensions.unwrap_or_default();
-        let filter_by_ext = !exts.is_empty();
+        let mut commit = writer
+            .prepare_commit()
+            .context("failed to prepare index commit")?;
+        commit.set_payload(&format!("{FORMAT_PAYLOAD_PREFIX}{INDEX_FORMAT_VERSION}"));
+        commit.commit().context("failed to commit index writer")?;
+        reporter.finish();
+        // Merged in walk order so each identifier keeps the first file it was seen in.
+        let mut identifiers = BTreeMap::new();
+        for file_identifiers in per_file {
+            for (symbol, path) in file_identifiers {
+                identifiers.entry(symbol).or_insert(path);
+            }
+        }
+        write_dictionary(&index_dir, &identifiers)?;
+        fs::remove_file(&marker)
+            .with_context(|| format!("failed to remove build marker {}", marker.display()))
+    })
+    .await
+    .context("index build task cancelled")??;
 
-        for result in walker.build() {
-            let entry = match result {
-                Ok(entry) => entry,
-                Err(err) => {
-                    tracing::warn!(error = %err, "failed to read entry during indexing");
-                    continue;
-                }
-            };
-            if !entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
+    Ok(())
+}
+
+/// Counts finished files across the build's threads and reports progress at most once per
+/// [`PROGRESS_INTERVAL`].
+struct ProgressReporter {
+    started: Instant,
+    files_total: usize,
+    files_done: AtomicUsize,
+    last_report: Mutex<Instant>,
+    callback: ProgressCallback,
+}
+
+impl ProgressReporter {
+    fn new(files_total: usize, callback: ProgressCallback) -> Self {
+        let started = Instant::now();
+        Self {
+            started,
+            files_total,
+            files_done: AtomicUsize::new(0),
+            last_report: Mutex::new(started),
+            callback,
+        }
+    }
+
+    fn file_done(&self) {
+        let files_done = self.files_done.fetch_add(1, Ordering::Relaxed) + 1;
+        // Skip the report when another thread is already making one.
+        let Ok(mut last_report) = self.last_report.try_lock() else {
+            return;
+        };
+        if last_report.elapsed() < PROGRESS_INTERVAL || files_done >= self.files_total {
+            return;
+        }
+        *last_report = Instant::now();
+        drop(last_report);
+        (self.callback)(&self.progress(files_done));
+    }
+
+    /// Report the finished build, once its documents are committed.
+    fn finish(&self) {
+        (self.callback)(&self.progress(self.files_done.load(Ordering::Relaxed)));
+    }
+
+    fn progress(&self, files_done: usize) -> IndexProgress {
+        IndexProgress {
+            files_done,
+            files_total: self.files_total,
+            elapsed: self.started.elapsed(),
+        }
+    }
+}
+
+fn log_progress(progress: &IndexProgress) {
+    tracing::info!(
+        files_done = progress.files_done,
+        files_total = progress.files_total,
+        files_per_sec = progress.files_per_sec(),
+        eta_secs = progress.eta().map(|eta| eta.as_secs()),
+        "index build progress"
+    );
+}
+
+async fn build_dictionary(
+    root: &Path,
+    index_dir: &Path,
+    extensions: Option<Vec<String>>,
+) -> Result<()> {
+    let root = root.to_path_buf();
+    let index_dir = index_dir.to_path_buf();
+    task::spawn_blocking(move || {
+        let mut identifiers = BTreeMap::new();
+        for path in source_files(&root, extensions) {
+            if let Some(content) = read_source(&path) {
+                extract_identifiers(&root, &path, &content, &mut identifiers);
+            }
+        }
+        write_dictionary(&index_dir, &identifiers)
+    })
+    .await
+    .context("dictionary build task cancelled")?
+}
+
+/// Files under `root` that the index covers, honouring the optional extension filter.
+fn source_files(root: &Path, extensions: Option<Vec<String>>) -> Vec<PathBuf> {
+    let mut walker = WalkBuilder::new(root);
+    walker
+        .hidden(false)
+        .follow_links(false)
+        .standard_filters(true);
+
+    let exts = extensions.unwrap_or_default();
+    let filter_by_ext = !exts.is_empty();
+
+    let mut files = Vec::new();
+    for result in walker.build() {
+        let entry = match result {
+            Ok(entry) => entry,
+            Err(err) => {
+                tracing::warn!(error = %err, "failed to read entry during indexing");
                 continue;
             }
-            if filter_by_ext {
-                let path_ext = entry.path().extension().and_then(|e| e.to_str());
-                if let Some(ext) = path_ext {
-                    if !exts
-                        .iter()
-                        .any(|candidate| candidate.eq_ignore_ascii_case(ext))
-                    {
-                        continue;
-                    }
-                } else {
+        };
+        if !entry.file_type().map(|ft| ft.is_file()).unwrap_or(false) {
+            continue;
+        }
+        if filter_by_ext {
+            let path_ext = entry.path().extension().and_then(|e| e.to_str());
+            if let Some(ext) = path_ext {
+                if !exts
+                    .iter()
+                    .any(|candidate| candidate.eq_ignore_ascii_case(ext))
+                {
                     continue;
                 }
+            } else {
+                continue;
             }
+        }
+        files.push(entry.into_path());
+    }
+    files
+}
 
-            let path = entry.path();
-            let content = match fs::read_to_string(path) {
-                Ok(text) => text,
-                Err(_) => continue,
-            };
-
-            let mut doc = tantivy::Document::new();
-            doc.add_text(path_field, path.display().to_string());
-            doc.add_text(body_field, content);
-            if let Err(err) = writer.add_document(doc) {
-                tracing::warn!(error = %err, "failed to add document to index");
-            }
+/// Text of `path` as the index sees it: notebooks contribute only their code cells.
+fn read_source(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ipynb"))
+    {
+        return Some(notebook::code_cells(&content).unwrap_or(content));
+    }
+    Some(content)
+}
+
+/// Record every identifier in `content`, keeping the first file it was seen in.
+fn extract_identifiers(
+    root: &Path,
+    path: &Path,
+    content: &str,
+    identifiers: &mut BTreeMap<String, String>,
+) {
+    let relative = normalize_path(root, path);
+    for token in identifier_tokens(content) {
+        if !identifiers.contains_key(token) {
+            identifiers.insert(token.to_string(), relative.display().to_string());
         }
+    }
+}
 
-        writer.commit().context("failed to commit index writer")?;
-        Ok::<(), anyhow::Error>(())
-    })
-    .await
-    .context("index build task cancelled")??;
+/// Identifier-shaped tokens in `content` (Unicode XID_Start/XID_Continue, so `größe` and
+/// `用户名` count), in order of appearance (duplicates included).
+fn identifier_tokens(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .split(|c: char| !unicode_ident::is_xid_continue(c))
+        .filter(|token| {
+            token.len() >= MIN_IDENTIFIER_LENGTH
+                && token.starts_with(|c: char| c == '_' || unicode_ident::is_xid_start(c))
+        })
+}
 
-    Ok(())
+fn write_dictionary(index_dir: &Path, identifiers: &BTreeMap<String, String>) -> Result<()> {
+    let mut contents = String::new();
+    for (symbol, path) in identifiers {
+        contents.push_str(symbol);
+        contents.push('\t');
+        contents.push_str(path);
+        contents.push('\n');
+    }
+    let target = index_dir.join(IDENTIFIERS_FILENAME);
+    fs::write(&target, contents)
+        .with_context(|| format!("failed to write symbol dictionary {}", target.display()))
+}
+
+/// Identifier extracted during indexing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolEntry {
+    pub symbol: String,
+    /// First file the identifier appears in, relative to the indexed root.
+    pub path: PathBuf,
+}
+
+/// Sorted identifier set persisted next to the index.
+#[derive(Clone, Debug, Default)]
+pub struct SymbolDictionary {
+    entries: Vec<SymbolEntry>,
+}
+
+impl SymbolDictionary {
+    /// Load the dictionary sidecar from `index_dir`; a missing file yields an empty dictionary.
+    pub fn load(index_dir: &Path) -> Result<Self> {
+        let path = index_dir.join(IDENTIFIERS_FILENAME);
+        if !path.is_file() {
+            return Ok(Self::default());
+        }
+        let raw = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read symbol dictionary {}", path.display()))?;
+        let mut entries: Vec<SymbolEntry> = raw
+            .lines()
+            .filter_map(|line| line.split_once('\t'))
+            .map(|(symbol, path)| SymbolEntry {
+                symbol: symbol.to_string(),
+                path: PathBuf::from(path),
+            })
+            .collect();
+        entries.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        Ok(Self { entries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SymbolEntry> {
+        self.entries.iter()
+    }
+
+    /// Up to `limit` identifiers starting with `prefix`, in lexicographic order.
+    pub fn with_prefix(&self, prefix: &str, limit: usize) -> Vec<SymbolEntry> {
+        let start = self
+            .entries
+            .partition_point(|entry| entry.symbol.as_str() < prefix);
+        self.entries[start..]
+            .iter()
+            .take_while(|entry| entry.symbol.starts_with(prefix))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
 }
 
 fn normalize_path(root: &Path, path: &Path) -> PathBuf {
@@ -0,0 +1,24 @@
+use serde_json::Value;
+
+/// Code cells of a Jupyter notebook joined in order, one blank line between cells; `None` when
+/// `raw` is not nbformat JSON.
+pub(crate) fn code_cells(raw: &str) -> Option<String> {
+    let document: Value = serde_json::from_str(raw).ok()?;
+    let cells = document.get("cells")?.as_array()?;
+    let mut code = String::new();
+    for cell in cells {
+        if cell.get("cell_type").and_then(Value::as_str) != Some("code") {
+            continue;
+        }
+        match cell.get("source") {
+            Some(Value::String(text)) => code.push_str(text),
+            Some(Value::Array(lines)) => lines
+                .iter()
+                .filter_map(Value::as_str)
+                .for_each(|line| code.push_str(line)),
+            _ => continue,
+        }
+        code.push_str("\n\n");
+    }
+    Some(code)
+}
@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{Context, Result};
+use fst::automaton::{Levenshtein, Str};
+use fst::{Automaton, IntoStreamer, Map, MapBuilder, Streamer};
+use tokio::task;
+
+use crate::{IndexConfig, identifier_tokens, normalize_path, read_source, source_files};
+
+/// FST mapping each identifier to its row in the postings file.
+const FST_FILENAME: &str = "symbols.fst";
+/// One line per identifier: space-separated ids into the file table.
+const POSTINGS_FILENAME: &str = "symbols.postings";
+/// One line per file: `path<TAB>mtime<TAB>size<TAB>identifiers`, used for incremental refresh.
+const FILES_FILENAME: &str = "symbols.files.tsv";
+
+/// Identifier -> file map persisted next to the Tantivy index.
+///
+/// Lookups walk an in-memory FST, so exact and prefix queries never touch the filesystem; only
+/// files whose size or modification time changed are re-read when the store is refreshed.
+pub struct SymbolStore {
+    map: Map<Vec<u8>>,
+    postings: Vec<Vec<u32>>,
+    files: Vec<PathBuf>,
+}
+
+/// Matches keys equal to the wrapped bytes up to ASCII case.
+struct IgnoreAsciiCase<'a>(&'a [u8]);
+
+impl Automaton for IgnoreAsciiCase<'_> {
+    /// Bytes matched so far; `None` once a byte differs.
+    type State = Option<usize>;
+
+    fn start(&self) -> Self::State {
+        Some(0)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        *state == Some(self.0.len())
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.is_some()
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        let matched = (*state)?;
+        self.0
+            .get(matched)
+            .filter(|expected| expected.eq_ignore_ascii_case(&byte))
+            .map(|_| matched + 1)
+    }
+}
+
+/// Per-file identifiers together with the stamp they were extracted at.
+struct FileEntry {
+    modified: u128,
+    len: u64,
+    symbols: Vec<String>,
+}
+
+impl SymbolStore {
+    /// Open the store in `config.index_dir`, re-extracting only files that changed since the
+    /// last refresh and rebuilding the FST when anything did.
+    pub async fn open_or_refresh(config: IndexConfig) -> Result<Self> {
+        task::spawn_blocking(move || Self::refresh(&config))
+            .await
+            .context("symbol store refresh task cancelled")?
+    }
+
+    /// Files (relative to the root) that mention `symbol` verbatim.
+    pub fn lookup(&self, symbol: &str) -> Vec<PathBuf> {
+        match self.map.get(symbol) {
+            Some(row) => self.files_for(row),
+            None => Vec::new(),
+        }
+    }
+
+    /// Files that mention `symbol` in any ASCII case (`parseConfig`, `ParseConfig`, ...).
+    pub fn lookup_ignore_case(&self, symbol: &str) -> Vec<PathBuf> {
+        let mut stream = self
+            .map
+            .search(IgnoreAsciiCase(symbol.as_bytes()))
+            .into_stream();
+        let mut files = BTreeSet::new();
+        while let Some((_, row)) = stream.next() {
+            files.extend(self.files_for(row));
+        }
+        files.into_iter().collect()
+    }
+
+    /// Up to `limit` identifiers starting with `prefix`, in lexicographic order.
+    pub fn prefix(&self, prefix: &str, limit: usize) -> Vec<String> {
+        self.collect(Str::new(prefix).starts_with(), limit)
+    }
+
+    /// Up to `limit` identifiers within `distance` edits of `symbol`, in lexicographic order.
+    pub fn fuzzy(&self, symbol: &str, distance: u32, limit: usize) -> Result<Vec<String>> {
+        let automaton = Levenshtein::new(symbol, distance)
+            .with_context(|| format!("failed to build fuzzy matcher for `{symbol}`"))?;
+        Ok(self.collect(automaton, limit))
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    fn collect<A: Automaton>(&self, automaton: A, limit: usize) -> Vec<String> {
+        let mut stream = self.map.search(automaton).into_stream();
+        let mut symbols = Vec::new();
+        while symbols.len() < limit {
+            let Some((key, _)) = stream.next() else {
+                break;
+            };
+            symbols.push(String::from_utf8_lossy(key).into_owned());
+        }
+        symbols
+    }
+
+    fn files_for(&self, row: u64) -> Vec<PathBuf> {
+        self.postings
+            .get(row as usize)
+            .map(|ids| {
+                ids.iter()
+                    .filter_map(|id| self.files.get(*id as usize).cloned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn refresh(config: &IndexConfig) -> Result<Self> {
+        fs::create_dir_all(&config.index_dir).with_context(|| {
+            format!(
+                "failed to create index directory {}",
+                config.index_dir.display()
+            )
+        })?;
+        let mut previous = load_files(&config.index_dir);
+        let mut changed = !config.index_dir.join(FST_FILENAME).is_file();
+
+        let mut entries: BTreeMap<PathBuf, FileEntry> = BTreeMap::new();
+        for path in source_files(&config.root, config.extensions.clone()) {
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|elapsed| elapsed.as_nanos())
+                .unwrap_or_default();
+            let relative = normalize_path(&config.root, &path);
+
+            if let Some(entry) = previous.remove(&relative)
+                && entry.modified == modified
+                && entry.len == metadata.len()
+            {
+                entries.insert(relative, entry);
+                continue;
+            }
+
+            let Some(content) = read_source(&path) else {
+                continue;
+            };
+            let symbols: BTreeSet<&str> = identifier_tokens(&content).collect();
+            entries.insert(
+                relative,
+                FileEntry {
+                    modified,
+                    len: metadata.len(),
+                    symbols: symbols.into_iter().map(str::to_string).collect(),
+                },
+            );
+            changed = true;
+        }
+        // Anything left over was deleted or is no longer covered by the extension filter.
+        changed |= !previous.is_empty();
+
+        if !changed && let Ok(store) = Self::load(&config.index_dir, &entries) {
+            return Ok(store);
+        }
+        Self::build(&config.index_dir, &entries)
+    }
+
+    fn load(index_dir: &Path, entries: &BTreeMap<PathBuf, FileEntry>) -> Result<Self> {
+        let bytes = fs::read(index_dir.join(FST_FILENAME))?;
+        let map = Map::new(bytes)?;
+        let postings = fs::read_to_string(index_dir.join(POSTINGS_FILENAME))?
+            .lines()
+            .map(parse_ids)
+            .collect();
+        Ok(Self {
+            map,
+            postings,
+            files: entries.keys().cloned().collect(),
+        })
+    }
+
+    fn build(index_dir: &Path, entries: &BTreeMap<PathBuf, FileEntry>) -> Result<Self> {
+        let mut by_symbol: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+        for (id, entry) in entries.values().enumerate() {
+            for symbol in &entry.symbols {
+                by_symbol.entry(symbol).or_default().push(id as u32);
+            }
+        }
+
+        let mut builder = MapBuilder::memory();
+        let mut postings = Vec::with_capacity(by_symbol.len());
+        for (row, (symbol, ids)) in by_symbol.into_iter().enumerate() {
+            builder
+                .insert(symbol, row as u64)
+                .context("failed to insert symbol into FST")?;
+            postings.push(ids);
+        }
+        let bytes = builder
+            .into_inner()
+            .context("failed to finish symbol FST")?;
+
+        write(index_dir, FST_FILENAME, &bytes)?;
+        write(
+            index_dir,
+            POSTINGS_FILENAME,
+            render_postings(&postings).as_bytes(),
+        )?;
+        write(index_dir, FILES_FILENAME, render_files(entries).as_bytes())?;
+
+        Ok(Self {
+            map: Map::new(bytes).context("failed to load symbol FST")?,
+            postings,
+            files: entries.keys().cloned().collect(),
+        })
+    }
+}
+
+fn load_files(index_dir: &Path) -> HashMap<PathBuf, FileEntry> {
+    let Ok(raw) = fs::read_to_string(index_dir.join(FILES_FILENAME)) else {
+        return HashMap::new();
+    };
+    raw.lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            let path = PathBuf::from(fields.next()?);
+            let modified = fields.next()?.parse().ok()?;
+            let len = fields.next()?.parse().ok()?;
+            let symbols = fields
+                .next()
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect();
+            Some((
+                path,
+                FileEntry {
+                    modified,
+                    len,
+                    symbols,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn render_files(entries: &BTreeMap<PathBuf, FileEntry>) -> String {
+    let mut contents = String::new();
+    for (path, entry) in entries {
+        contents.push_str(&format!(
+            "{}\t{}\t{}\t{}\n",
+            path.display(),
+            entry.modified,
+            entry.len,
+            entry.symbols.join(" ")
+        ));
+    }
+    contents
+}
+
+fn render_postings(postings: &[Vec<u32>]) -> String {
+    let mut contents = String::new();
+    for ids in postings {
+        let line: Vec<String> = ids.iter().map(u32::to_string).collect();
+        contents.push_str(&line.join(" "));
+        contents.push('\n');
+    }
+    contents
+}
+
+fn parse_ids(line: &str) -> Vec<u32> {
+    line.split_whitespace()
+        .filter_map(|id| id.parse().ok())
+        .collect()
+}
+
+fn write(index_dir: &Path, name: &str, contents: &[u8]) -> Result<()> {
+    let target = index_dir.join(name);
+    fs::write(&target, contents).with_context(|| format!("failed to write {}", target.display()))
+}
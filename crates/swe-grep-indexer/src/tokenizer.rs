@@ -0,0 +1,159 @@
+use tantivy::tokenizer::{
+    BoxTokenStream, LowerCaser, RemoveLongFilter, TextAnalyzer, Token, TokenStream,
+    TokenizerManager,
+};
+
+/// Name the `body` field's tokenizer is registered under.
+pub(crate) const IDENTIFIER_TOKENIZER: &str = "identifier";
+/// Longest token kept, as with tantivy's default tokenizer.
+const MAX_TOKEN_LENGTH: usize = 40;
+
+/// Splits text into identifiers (runs of alphanumerics and `_`) and each identifier into its
+/// camelCase and snake_case words, lowercased. `getUserProfileById` indexes as
+/// `getuserprofilebyid` followed by `get`, `user`, `profile`, `by`, `id` at consecutive
+/// positions, so a phrase of words matches inside longer identifiers.
+#[derive(Clone)]
+pub(crate) struct IdentifierTokenizer {
+    /// Also emit each identifier whole, at the position of its first word. Indexing keeps it so
+    /// a query written without case or underscores (`userprofile`) still matches; queries leave
+    /// it out so `UserProfile` becomes the phrase `user profile`.
+    keep_original: bool,
+}
+
+/// Register the indexing tokenizer with `tokenizers`.
+pub(crate) fn register_for_indexing(tokenizers: &TokenizerManager) {
+    tokenizers.register(
+        IDENTIFIER_TOKENIZER,
+        analyzer(IdentifierTokenizer {
+            keep_original: true,
+        }),
+    );
+}
+
+/// Tokenizers for parsing queries against the `body` field, which match words only.
+pub(crate) fn query_tokenizers() -> TokenizerManager {
+    let tokenizers = TokenizerManager::default();
+    tokenizers.register(
+        IDENTIFIER_TOKENIZER,
+        analyzer(IdentifierTokenizer {
+            keep_original: false,
+        }),
+    );
+    tokenizers
+}
+
+fn analyzer(tokenizer: IdentifierTokenizer) -> TextAnalyzer {
+    TextAnalyzer::from(tokenizer)
+        .filter(RemoveLongFilter::limit(MAX_TOKEN_LENGTH))
+        .filter(LowerCaser)
+}
+
+impl tantivy::tokenizer::Tokenizer for IdentifierTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        let mut tokens = Vec::new();
+        let mut position = 0;
+        for (start, identifier) in identifiers(text) {
+            let words = words(identifier);
+            if self.keep_original && words.len() > 1 {
+                tokens.push(token(start, identifier, position));
+            }
+            for (offset, word) in words {
+                tokens.push(token(start + offset, word, position));
+                position += 1;
+            }
+        }
+        BoxTokenStream::from(IdentifierTokenStream {
+            tokens,
+            index: 0,
+            token: Token::default(),
+        })
+    }
+}
+
+fn token(offset: usize, text: &str, position: usize) -> Token {
+    Token {
+        offset_from: offset,
+        offset_to: offset + text.len(),
+        position,
+        text: text.to_string(),
+        position_length: 1,
+    }
+}
+
+/// Runs of alphanumerics and `_` in `text`, with their byte offsets.
+fn identifiers(text: &str) -> Vec<(usize, &str)> {
+    let mut identifiers = Vec::new();
+    let mut start = None;
+    for (offset, ch) in text.char_indices().chain([(text.len(), ' ')]) {
+        let is_part = ch.is_alphanumeric() || ch == '_';
+        match (start, is_part) {
+            (None, true) => start = Some(offset),
+            (Some(from), false) => {
+                identifiers.push((from, &text[from..offset]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    identifiers
+}
+
+/// Words of `identifier` with their byte offsets: split at `_`, before an uppercase letter
+/// that follows a lowercase letter or digit (`userId`), and before the last capital of an
+/// acronym (`HTTPServer`). Digits stay with the word before them (`utf8Decode`).
+fn words(identifier: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    for (segment_start, segment) in identifier
+        .split('_')
+        .scan(0, |offset, segment| {
+            let start = *offset;
+            *offset += segment.len() + 1;
+            Some((start, segment))
+        })
+        .filter(|(_, segment)| !segment.is_empty())
+    {
+        let chars: Vec<(usize, char)> = segment.char_indices().collect();
+        let mut start = 0;
+        for (index, &(offset, ch)) in chars.iter().enumerate().skip(1) {
+            let previous = chars[index - 1].1;
+            let next_is_lower = chars
+                .get(index + 1)
+                .is_some_and(|&(_, next)| next.is_lowercase());
+            let boundary = ch.is_uppercase()
+                && (previous.is_lowercase()
+                    || previous.is_numeric()
+                    || (previous.is_uppercase() && next_is_lower));
+            if boundary {
+                words.push((segment_start + start, &segment[start..offset]));
+                start = offset;
+            }
+        }
+        words.push((segment_start + start, &segment[start..]));
+    }
+    words
+}
+
+struct IdentifierTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+    token: Token,
+}
+
+impl TokenStream for IdentifierTokenStream {
+    fn advance(&mut self) -> bool {
+        let Some(token) = self.tokens.get(self.index) else {
+            return false;
+        };
+        self.token = token.clone();
+        self.index += 1;
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
@@ -0,0 +1,5 @@
+﻿// Session helpers.
+
+pub fn open_session() -> u32 {
+    7
+}
@@ -0,0 +1,4 @@
+struct 用户资料 {
+    let 用户名: String
+    var 显示名称: String { "用户名：\(用户名)" }
+}
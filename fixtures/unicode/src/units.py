@@ -0,0 +1,6 @@
+def größe(maße):
+    return sum(maße)
+
+
+def größenordnung(wert):
+    return len(str(wert))